@@ -0,0 +1,192 @@
+//! The Node.js bindings, which let Node.js run programs natively.
+//! The bindings are a crate of their own, so that the library and the binary of the DarkVM never link against Node-API.
+//! The addon is built with `cargo build --release -p dark-vm-node`, and the shared library that it produces is renamed to `dark_vm.node`, so that it can be loaded with `require`.
+//!
+//! Values are marshaled in both directions. Ints and floats become numbers, strings and chars become strings, bytes become buffers,
+//! arrays become arrays, maps become objects, null becomes null, and void becomes undefined. Numbers that are whole and safe integers become ints,
+//! and every other number becomes a float, so ints beyond 2^53 lose precision. Errors are thrown as JavaScript errors, with the message that the command line would print.
+
+use dark_vm::{
+    code::Code,
+    errors::{error::Error, error_kind::ErrorKind},
+    values::{value::Value, value_kinds::ValueKind},
+    VMBuilder,
+};
+use napi::{Env, JsBuffer, JsObject, JsString, JsUnknown, ValueType};
+use napi_derive::napi;
+use std::{collections::BTreeMap, convert::TryFrom, sync::Arc};
+
+/// The largest integer that a JavaScript number holds exactly.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// Runs the program with the default options, and returns the value that it left on top of the stack.
+///
+/// # Arguments
+/// `source` - The contents of the program.
+#[napi]
+pub fn run(env: Env, source: String) -> napi::Result<JsUnknown> {
+    compile(source)?.run(env)
+}
+
+/// Lexes and loads the program once, so that it can be run and its labels can be called many times.
+///
+/// # Arguments
+/// `source` - The contents of the program.
+#[napi]
+pub fn compile(source: String) -> napi::Result<Program> {
+    let code = dark_vm::lex(&source)
+        .and_then(dark_vm::load)
+        .map_err(|error| throw(error, &source))?;
+    Ok(Program { source, code })
+}
+
+/// The Program class is a program that was compiled for JavaScript. Every run and every call gets a VM of its own.
+#[napi]
+pub struct Program {
+    source: String,
+    code: Arc<Code>,
+}
+
+#[napi]
+impl Program {
+    /// Runs the program with the default options, and returns the value that it left on top of the stack.
+    #[napi]
+    pub fn run(&self, env: Env) -> napi::Result<JsUnknown> {
+        let result = VMBuilder::default()
+            .build_shared(Arc::clone(&self.code))
+            .and_then(|mut vm| {
+                vm.run().result?;
+                Ok(vm.operand_stack.0.pop())
+            })
+            .map_err(|error| throw(error, &self.source))?;
+        to_js(env, result.as_deref())
+    }
+
+    /// Calls a label of the program with the given arguments, without running the main label, and returns the value that the label left on the stack.
+    ///
+    /// # Arguments
+    /// `label` - The name of the label.
+    /// `args` - The values of the parameters of the label, from the first parameter to the last.
+    #[napi]
+    pub fn call(&self, env: Env, label: String, args: Vec<JsUnknown>) -> napi::Result<JsUnknown> {
+        let arguments = args
+            .into_iter()
+            .map(|arg| from_js(arg).map(Arc::new))
+            .collect::<napi::Result<_>>()?;
+        let result = VMBuilder::default()
+            .build_shared(Arc::clone(&self.code))
+            .and_then(|mut vm| vm.call_label(&label, arguments))
+            .map_err(|error| throw(error, &self.source))?;
+        to_js(env, result.as_deref())
+    }
+}
+
+/// Converts an error of the VM into a JavaScript error.
+///
+/// # Arguments
+/// `error` - The error to convert.
+/// `source` - The contents of the program, which the error points into.
+fn throw(error: Error, source: &str) -> napi::Error {
+    napi::Error::from_reason(error.prettify(source))
+}
+
+/// Converts a value of the VM into a JavaScript value.
+/// Values that have no JavaScript form, such as any, identifiers, labels, and instructions, produce an error.
+///
+/// # Arguments
+/// `value` - The value to convert, or None if there was no value to return.
+fn to_js(env: Env, value: Option<&Value>) -> napi::Result<JsUnknown> {
+    let kind = match value {
+        Some(value) => &value.kind,
+        None => return Ok(env.get_undefined()?.into_unknown()),
+    };
+
+    Ok(match kind {
+        ValueKind::Void => env.get_undefined()?.into_unknown(),
+        ValueKind::Null => env.get_null()?.into_unknown(),
+        ValueKind::Int(value) => env.create_int64(*value)?.into_unknown(),
+        ValueKind::Float(value) => env.create_double(*value)?.into_unknown(),
+        ValueKind::Boolean(value) => env.get_boolean(*value)?.into_unknown(),
+        ValueKind::String(value) => env.create_string(value)?.into_unknown(),
+        ValueKind::Char(value) => env.create_string(&value.to_string())?.into_unknown(),
+        ValueKind::Bytes(value) => env.create_buffer_with_data(value.clone())?.into_unknown(),
+        ValueKind::Array(values) => {
+            let mut array = env.create_array_with_length(values.len())?;
+            for (index, value) in values.iter().enumerate() {
+                array.set_element(index as u32, to_js(env, Some(value))?)?;
+            }
+
+            array.into_unknown()
+        }
+        ValueKind::Map(entries) => {
+            let mut object = env.create_object()?;
+            for (key, value) in entries {
+                object.set_named_property(key, to_js(env, Some(value))?)?;
+            }
+
+            object.into_unknown()
+        }
+        kind => {
+            return Err(napi::Error::from_reason(String::from(
+                ErrorKind::UnsupportedOperation("node".to_owned(), kind.get_value_name()),
+            )))
+        }
+    })
+}
+
+/// Converts a JavaScript value into a value of the VM. Undefined becomes void, and null becomes null.
+/// Values that have no form in the VM, such as functions, symbols, and big integers, produce an error.
+///
+/// # Arguments
+/// `value` - The value to convert.
+fn from_js(value: JsUnknown) -> napi::Result<Value> {
+    let kind = match value.get_type()? {
+        ValueType::Undefined => ValueKind::Void,
+        ValueType::Null => ValueKind::Null,
+        ValueType::Boolean => ValueKind::Boolean(value.coerce_to_bool()?.get_value()?),
+        ValueType::Number => {
+            let number = value.coerce_to_number()?.get_double()?;
+            if number.fract() == 0.0 && number.abs() <= MAX_SAFE_INTEGER {
+                ValueKind::Int(number as i64)
+            } else {
+                ValueKind::Float(number)
+            }
+        }
+        ValueType::String => {
+            ValueKind::String(value.coerce_to_string()?.into_utf8()?.into_owned()?)
+        }
+        ValueType::Object if value.is_buffer()? => {
+            ValueKind::Bytes(JsBuffer::try_from(value)?.into_value()?.to_vec())
+        }
+        ValueType::Object if value.is_array()? => {
+            let array = JsObject::try_from(value)?;
+            let values = (0..array.get_array_length()?)
+                .map(|index| from_js(array.get_element(index)?).map(Arc::new))
+                .collect::<napi::Result<_>>()?;
+            ValueKind::Array(values)
+        }
+        ValueType::Object => {
+            let object = JsObject::try_from(value)?;
+            let keys = object.get_property_names()?;
+            let mut entries = BTreeMap::new();
+            for index in 0..keys.get_array_length()? {
+                let key = keys
+                    .get_element::<JsString>(index)?
+                    .into_utf8()?
+                    .into_owned()?;
+                let value = from_js(object.get_named_property(&key)?)?;
+                entries.insert(key, Arc::new(value));
+            }
+
+            ValueKind::Map(entries)
+        }
+        value_type => {
+            return Err(napi::Error::from_reason(format!(
+                "A JavaScript Value Of The Type {} Can Not Be Passed To The VM.",
+                value_type
+            )))
+        }
+    };
+
+    Ok(Value::new(0, kind))
+}
@@ -0,0 +1,60 @@
+//! These tests load the addon into Node.js, so they are skipped where Node.js is not installed.
+
+use std::{
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    path::PathBuf,
+    process::Command,
+};
+
+/// Builds the addon and returns its path. Cargo does not build a cdylib for the tests of its crate,
+/// so it is built into a directory of its own, which the running `cargo test` does not lock.
+fn addon() -> PathBuf {
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("addon");
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--quiet", "--manifest-path"])
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    target_dir
+        .join("debug")
+        .join(format!("{}dark_vm_node{}", DLL_PREFIX, DLL_SUFFIX))
+}
+
+/// Loads the addon into Node.js as `dark`, runs the script, and returns what it printed.
+/// If Node.js is not installed, None is returned.
+///
+/// # Arguments
+/// `script` - The JavaScript to run.
+fn node(script: &str) -> Option<String> {
+    Command::new("node").arg("--version").output().ok()?;
+    let prelude = "const module = { exports: {} }; process.dlopen(module, process.argv[1]); const dark = module.exports;";
+    let output = Command::new("node")
+        .arg("-e")
+        .arg(format!("{}\n{}", prelude, script))
+        .arg(addon())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Some(String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn null_and_undefined_survive_a_round_trip() {
+    let script = r#"
+        const program = dark.compile("@main end @id #x push x end @nothing end");
+        console.log(program.call("id", [null]) === null);
+        console.log(program.call("id", [undefined]) === undefined);
+        console.log(program.call("nothing", []) === undefined);
+        console.log(dark.run("@main push null end") === null);
+    "#;
+    if let Some(output) = node(script) {
+        assert_eq!(output, "true\ntrue\ntrue\ntrue\n");
+    }
+}
@@ -0,0 +1,495 @@
+//! The VMBuilder struct configures a VM before it is constructed.
+//! This includes the resource limits, such as the fuel and the maximum call depth, and the streams that the VM writes to.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{builder::VMBuilder, errors::error::Error, lexer::Lexer};
+//! # fn run() -> Result<(), Error> {
+//! let contents = "@main push 1 end";
+//! let tokens = Lexer::default().lex(contents)?;
+//! let result = VMBuilder::default().fuel(1000).max_depth(64).build(tokens)?.run().into_result()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    code::Code,
+    errors::error::Error,
+    lexer::Lexer,
+    tokens::token::Token,
+    type_checker,
+    utils::{
+        aliases::Aliases,
+        calling_convention::CallingConvention,
+        capabilities::Capabilities,
+        case::Case,
+        float_equality::FloatEquality,
+        fs::FileSystem,
+        history::History,
+        io::VmIo,
+        limits::Limits,
+        log_level::LogLevel,
+        metrics::Metrics,
+        natives::Natives,
+        overflow::Overflow,
+        process::ProcessPolicy,
+        shadowing::Shadowing,
+        trace::Trace,
+        watch::{WatchEvent, Watchpoints},
+    },
+    values::value::Value,
+    vm::VM,
+};
+use std::{
+    collections::VecDeque,
+    io::{Read, Write},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+#[derive(Debug, Default, Clone)]
+pub struct VMBuilder {
+    limits: Limits,
+    io: VmIo,
+    log_level: LogLevel,
+    shadowing: Shadowing,
+    case: Case,
+    overflow: Overflow,
+    calling_convention: CallingConvention,
+    float_equality: FloatEquality,
+    history: History,
+    metrics: Option<Metrics>,
+    capabilities: Capabilities,
+    trace: Option<Trace>,
+    type_check: bool,
+    suspend_on_break: bool,
+    assert_stack_empty: bool,
+    watchpoints: Watchpoints,
+    aliases: Aliases,
+    natives: Natives,
+}
+
+impl VMBuilder {
+    /// Sets the amount of fuel that the VM has. Every instruction that is run consumes one unit of fuel.
+    /// When the VM runs out of fuel, an error is returned.
+    ///
+    /// # Arguments
+    /// `fuel` - The number of instructions the VM may run.
+    pub fn fuel(mut self, fuel: u64) -> VMBuilder {
+        self.limits.fuel = Some(fuel);
+        self
+    }
+
+    /// Sets the amount of time that the VM may run for before an error is returned.
+    ///
+    /// # Arguments
+    /// `timeout` - The maximum duration of the run.
+    pub fn timeout(mut self, timeout: Duration) -> VMBuilder {
+        self.limits.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of frames that may be on the call stack, including the main frame.
+    ///
+    /// # Arguments
+    /// `max_depth` - The maximum call depth.
+    pub fn max_depth(mut self, max_depth: usize) -> VMBuilder {
+        self.limits.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets the maximum number of values that may be on the operand stack.
+    ///
+    /// # Arguments
+    /// `max_stack` - The maximum size of the operand stack.
+    pub fn max_stack(mut self, max_stack: usize) -> VMBuilder {
+        self.limits.max_stack = Some(max_stack);
+        self
+    }
+
+    /// Sets the maximum length of strings and bytes, in bytes.
+    ///
+    /// # Arguments
+    /// `max_string_len` - The maximum length of a string.
+    pub fn max_string_len(mut self, max_string_len: usize) -> VMBuilder {
+        self.limits.max_string_len = Some(max_string_len);
+        self
+    }
+
+    /// Sets the maximum number of elements in an array or a map.
+    ///
+    /// # Arguments
+    /// `max_array_len` - The maximum length of an array.
+    pub fn max_array_len(mut self, max_array_len: usize) -> VMBuilder {
+        self.limits.max_array_len = Some(max_array_len);
+        self
+    }
+
+    /// Sets the maximum number of arrays and maps that may be nested inside of each other.
+    ///
+    /// # Arguments
+    /// `max_value_depth` - The maximum nesting of a value.
+    pub fn max_value_depth(mut self, max_value_depth: usize) -> VMBuilder {
+        self.limits.max_value_depth = Some(max_value_depth);
+        self
+    }
+
+    /// Sets the stream that the print and printn instructions write to. By default, this is stdout.
+    ///
+    /// # Arguments
+    /// `out` - The stream to write the output of the program to.
+    pub fn stdout<W: Write + 'static>(mut self, out: W) -> VMBuilder {
+        self.io.set_out(out);
+        self
+    }
+
+    /// Captures the output of the print and printn instructions in memory, instead of writing it to a stream.
+    /// The captured output is returned in the outcome of the run method of the VM.
+    pub fn capture_output(mut self) -> VMBuilder {
+        self.io.capture_out();
+        self
+    }
+
+    /// Sets the stream that the eprint and eprintn instructions write to. By default, this is stderr.
+    ///
+    /// # Arguments
+    /// `err` - The stream to write the diagnostics of the program to.
+    pub fn stderr<W: Write + 'static>(mut self, err: W) -> VMBuilder {
+        self.io.set_err(err);
+        self
+    }
+
+    /// Sets the stream that the read instructions and the readbytes instruction read from. By default, this is stdin.
+    ///
+    /// # Arguments
+    /// `input` - The stream to read the input of the program from.
+    pub fn stdin<R: Read + 'static>(mut self, input: R) -> VMBuilder {
+        self.io.set_in(input);
+        self
+    }
+
+    /// Puts the streams in binary mode, where the print instructions write bytes values as their raw bytes instead of as an escaped literal.
+    /// Together with the readbytes and writebytes instructions, this allows programs to filter binary streams.
+    pub fn binary_io(mut self) -> VMBuilder {
+        self.io.set_binary(true);
+        self
+    }
+
+    /// Sets the streams that the VM reads from and writes to.
+    ///
+    /// # Arguments
+    /// `io` - The streams to use.
+    pub fn io(mut self, io: VmIo) -> VMBuilder {
+        self.io = io;
+        self
+    }
+
+    /// Sets the lowest level of the messages that the log instruction writes. By default, this is info.
+    ///
+    /// # Arguments
+    /// `log_level` - The lowest level that is written.
+    pub fn log_level(mut self, log_level: LogLevel) -> VMBuilder {
+        self.log_level = log_level;
+        self
+    }
+
+    /// Sets what the set instruction does when it defines a variable that an outer scope already defines. By default, this is allowed.
+    /// Warnings are collected in the outcome of the run, and denying shadowing turns them into errors.
+    ///
+    /// # Arguments
+    /// `shadowing` - What to do when a variable is shadowed.
+    pub fn shadowing(mut self, shadowing: Shadowing) -> VMBuilder {
+        self.shadowing = shadowing;
+        self
+    }
+
+    /// Sets whether the names of variables are folded to lower case when they are defined and looked up. By default, names are strict.
+    /// The tokens have to be lexed with the same policy, which the lexer method of this builder takes care of.
+    ///
+    /// # Arguments
+    /// `case` - Whether the names are folded.
+    pub fn case(mut self, case: Case) -> VMBuilder {
+        self.case = case;
+        self
+    }
+
+    /// Creates a lexer that applies the same case policy as the VMs that this builder constructs.
+    pub fn lexer(&self) -> Lexer {
+        Lexer::with_case(self.case)
+    }
+
+    /// Returns the case policy of the VMs that this builder constructs.
+    pub(crate) fn get_case(&self) -> Case {
+        self.case
+    }
+
+    /// Returns the aliases that the programs of this builder are loaded with.
+    pub(crate) fn get_aliases(&self) -> &Aliases {
+        &self.aliases
+    }
+
+    /// Sets what arithmetic on ints does when the result does not fit in an int. By default, the result wraps around.
+    /// In checked mode, an IntegerOverflow error is returned instead.
+    ///
+    /// # Arguments
+    /// `overflow` - What to do when an int overflows.
+    pub fn overflow(mut self, overflow: Overflow) -> VMBuilder {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Sets whether the labels that are called share the operand stack of their caller. By default, they do.
+    /// With isolated stacks, every call starts with a stack of its own that holds its arguments, and the values left on it are pushed on to the stack of the caller when the label ends.
+    ///
+    /// # Arguments
+    /// `calling_convention` - How the labels share the operand stack.
+    pub fn calling_convention(mut self, calling_convention: CallingConvention) -> VMBuilder {
+        self.calling_convention = calling_convention;
+        self
+    }
+
+    /// Sets the aliases that stand for instructions in the programs that are built from tokens, in addition to the aliases that a program defines itself.
+    ///
+    /// # Arguments
+    /// `aliases` - The aliases, usually parsed from a file.
+    pub fn aliases(mut self, aliases: Aliases) -> VMBuilder {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Sets how the equality instructions compare two floats. By default, floats are equal if they are at most 4 ULPs apart.
+    ///
+    /// # Arguments
+    /// `float_equality` - How floats are compared.
+    pub fn float_equality(mut self, float_equality: FloatEquality) -> VMBuilder {
+        self.float_equality = float_equality;
+        self
+    }
+
+    /// Sets the number of recently executed instructions that the VM keeps, which are attached to runtime errors.
+    /// By default, the last 16 instructions are kept. If this is 0, no instructions are kept, which makes the VM slightly faster.
+    ///
+    /// # Arguments
+    /// `size` - The number of instructions to keep.
+    pub fn history(mut self, size: usize) -> VMBuilder {
+        self.history = History::new(size);
+        self
+    }
+
+    /// Sets the metrics that the VM counts into. This allows several VMs to share the same counters.
+    /// By default, every VM has its own metrics, which can be read with the get_metrics method of the VM.
+    ///
+    /// # Arguments
+    /// `metrics` - The handle to the counters.
+    pub fn metrics(mut self, metrics: Metrics) -> VMBuilder {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Allows the readfile, writefile, and appendfile instructions to access the disk.
+    /// By default, these instructions return an error, so that untrusted programs can not touch the disk.
+    pub fn allow_fs(mut self) -> VMBuilder {
+        if self.capabilities.fs.is_none() {
+            self.capabilities.fs = Some(FileSystem::default());
+        }
+
+        self
+    }
+
+    /// Allows the file instructions to access the disk, but only inside of the given directory.
+    /// Relative paths are resolved against this directory.
+    ///
+    /// # Arguments
+    /// `root` - The directory that every path must be inside of.
+    pub fn fs_root<P: Into<PathBuf>>(mut self, root: P) -> VMBuilder {
+        self.capabilities.fs = Some(FileSystem::new(Some(root.into())));
+        self
+    }
+
+    /// Allows the exec instruction to run any command.
+    /// By default, the exec instruction returns an error, so that untrusted programs can not run commands.
+    pub fn allow_exec(mut self) -> VMBuilder {
+        self.capabilities.exec = Some(ProcessPolicy::default());
+        self
+    }
+
+    /// Allows the exec instruction to run the given command. Once a command is allowed, every other command is denied,
+    /// unless the exec instruction is allowed to run any command.
+    ///
+    /// # Arguments
+    /// `command` - The name of the program, which is compared against the name given to the exec instruction exactly.
+    pub fn allow_command<S: Into<String>>(mut self, command: S) -> VMBuilder {
+        self.capabilities
+            .exec
+            .get_or_insert_with(|| ProcessPolicy::new(Some(vec![])))
+            .allow(command.into());
+        self
+    }
+
+    /// Allows the fetch instruction to send requests over the network.
+    /// By default, the fetch instruction returns an error, so that untrusted programs can not reach the network.
+    pub fn allow_net(mut self) -> VMBuilder {
+        self.capabilities.net = true;
+        self
+    }
+
+    /// Allows the env instruction to read the environment variables of the host.
+    /// By default, the env instruction returns an error, so that untrusted programs can not read secrets from the environment.
+    pub fn allow_env(mut self) -> VMBuilder {
+        self.capabilities.env = true;
+        self
+    }
+
+    /// Allows the now instruction to read the clock of the host.
+    /// By default, the now instruction returns an error, so that untrusted programs can not observe the time.
+    pub fn allow_clock(mut self) -> VMBuilder {
+        self.capabilities.clock = true;
+        self
+    }
+
+    /// Replaces every capability of the VM at once. Every capability is denied by default.
+    ///
+    /// # Arguments
+    /// `capabilities` - The capabilities to grant.
+    pub fn capabilities(mut self, capabilities: Capabilities) -> VMBuilder {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Sets the trace that the VM records its nondeterministic inputs to, or replays them from.
+    /// The trace is shared, so the embedder can keep a clone of it and save it after the VM finishes.
+    ///
+    /// # Arguments
+    /// `trace` - The handle to the trace.
+    pub fn trace(mut self, trace: Trace) -> VMBuilder {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// Checks the types of the operands of every instruction before the VM is constructed.
+    /// If an instruction would certainly fail because of the types of its operands, such as an add of a boolean, the first such error is returned instead of the VM.
+    /// By default, the types are only checked when the instructions are run.
+    pub fn type_check(mut self) -> VMBuilder {
+        self.type_check = true;
+        self
+    }
+
+    /// Makes the program fail if it leaves any values on the operand stack when it finishes, which usually means that a value was pushed by mistake.
+    /// In REPL mode, this is checked at the end of every input. By default, the values that are left on the stack are kept silently.
+    pub fn assert_stack_empty(mut self) -> VMBuilder {
+        self.assert_stack_empty = true;
+        self
+    }
+
+    /// Makes the brk instruction and the writes to watched variables suspend the VM, so that stepping it returns `StepResult::Suspended` and the host can inspect the program.
+    /// The debugger always does this. By default, the brk instruction does nothing.
+    pub fn suspend_on_break(mut self) -> VMBuilder {
+        self.suspend_on_break = true;
+        self
+    }
+
+    /// Watches the variable with the given name, in every frame. Every write to the variable is passed to the callback given to `on_watch`,
+    /// and suspends the VM if it suspends on breaks.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    pub fn watch(mut self, name: &str) -> VMBuilder {
+        self.watchpoints.add(name);
+        self
+    }
+
+    /// Sets the callback that receives every write to a watched variable, along with its old and new value.
+    ///
+    /// # Arguments
+    /// `callback` - The callback.
+    pub fn on_watch<F: FnMut(&WatchEvent) + 'static>(mut self, callback: F) -> VMBuilder {
+        self.watchpoints.set_callback(callback);
+        self
+    }
+
+    /// Registers a native function, which programs call with the call instructions as if it were a label with the given number of parameters.
+    /// A native function takes the place of a label with the same name. It is given the VM and the values of its arguments,
+    /// and the value that it returns is the value of the call instruction.
+    ///
+    /// # Arguments
+    /// `name` - The name that programs call the function by.
+    /// `arity` - The number of arguments that the function takes.
+    /// `function` - The function.
+    pub fn native<F>(mut self, name: &str, arity: usize, function: F) -> VMBuilder
+    where
+        F: Fn(&mut VM, Vec<Arc<Value>>) -> Result<Option<Value>, Error> + 'static,
+    {
+        self.natives.register(name, arity, function);
+        self
+    }
+
+    /// Constructs a new VM with the specified tokens and the options of this builder.
+    ///
+    /// # Arguments
+    /// `tokens` - The tokens produced by the lexer.
+    pub fn build(self, tokens: VecDeque<Token>) -> Result<VM, Error> {
+        let vm = {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::info_span!("load").entered();
+            let code = if self.aliases.is_empty() {
+                Code::new(tokens)?
+            } else {
+                Code::with_aliases(tokens, &self.aliases)?
+            };
+            VM::from_code_with_natives(Arc::new(code), self.natives.clone())?
+        };
+
+        self.configure(vm)
+    }
+
+    /// Constructs a new VM that runs the given code with the options of this builder.
+    /// The code may be shared by many VMs, so that the same program can be run many times without lexing it again.
+    ///
+    /// # Arguments
+    /// `code` - The code to run.
+    pub fn build_shared(self, code: Arc<Code>) -> Result<VM, Error> {
+        let vm = VM::from_code_with_natives(code, self.natives.clone())?;
+        self.configure(vm)
+    }
+
+    /// Constructs a new VM in REPL mode with the options of this builder. The VM starts without any code, which is loaded one input at a time.
+    pub fn build_repl(self) -> Result<VM, Error> {
+        let vm = VM::repl()?;
+        self.configure(vm)
+    }
+
+    /// Applies the options of this builder to the given VM, checking the types of its code first if that was asked for.
+    ///
+    /// # Arguments
+    /// `vm` - The VM to configure.
+    fn configure(self, mut vm: VM) -> Result<VM, Error> {
+        if self.type_check {
+            let errors = type_checker::check_types_with_natives(vm.get_code(), &self.natives)?;
+            if let Some(error) = errors.into_iter().next() {
+                return Err(error);
+            }
+        }
+
+        vm.limits = self.limits;
+        vm.io = self.io;
+        vm.log_level = self.log_level;
+        vm.shadowing = self.shadowing;
+        vm.set_case(self.case);
+        vm.overflow = self.overflow;
+        vm.calling_convention = self.calling_convention;
+        vm.float_equality = self.float_equality;
+        vm.history = self.history;
+        vm.capabilities = self.capabilities;
+        vm.trace = self.trace;
+        vm.suspend_on_break = self.suspend_on_break;
+        vm.assert_stack_empty = self.assert_stack_empty;
+        vm.watchpoints = self.watchpoints;
+        vm.natives = self.natives;
+        if let Some(metrics) = self.metrics {
+            vm.metrics = metrics;
+        }
+
+        Ok(vm)
+    }
+}
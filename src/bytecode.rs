@@ -0,0 +1,460 @@
+//! The bytecode format stores a Code object on disk, so that a program can be compiled once and loaded again without lexing or lowering it.
+//! Files of this format usually end in `.darkc`.
+//!
+//! A file starts with the magic bytes `DRKC` and the version of the format, as a little endian u16.
+//! The constant pool follows, which holds every string of the program once: the string literals, the names of the identifiers, labels, parameters, and instructions,
+//! the documentation of the labels, and the source of the program, if it was included.
+//! After the pool come the values of the program, in order, the documentation of each label, and the names of the labels that are annotated as pure.
+//! Strings are always written as their index in the pool.
+//!
+//! Counts, lengths, indices, and positions are written as unsigned LEB128. Ints and floats are written as 8 little endian bytes.
+//! Instructions are written by name, rather than by opcode, so that adding an instruction does not change the meaning of older files.
+//!
+//! The source of the program is optional. When it is included, the errors of the loaded program point into it, just like the errors of a program that was lexed.
+//! When the file is loaded, the labels are found again from the values and the code is verified, so a file that was changed or corrupted is rejected instead of being run.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{bytecode, code::Code, errors::error::Error, lexer::Lexer};
+//! # fn run() -> Result<(), Error> {
+//! let source = "@main printn add 1 2 end";
+//! let code = Code::new(Lexer::default().lex(source)?)?;
+//! let bytes = bytecode::write(&code, Some(source));
+//! let compiled = bytecode::read(&bytes)?;
+//! assert_eq!(compiled.code.get_values(), code.get_values());
+//! assert_eq!(compiled.source.as_deref(), Some(source));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    code::Code,
+    errors::{error::Error, error_kind::ErrorKind},
+    instructions::Opcode,
+    utils::parameter::Parameter,
+    values::{value::Value, value_kinds::ValueKind},
+    verifier::{self, MAX_NESTING},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    convert::TryFrom,
+    sync::Arc,
+};
+
+/// The bytes that every file of this format starts with.
+pub const MAGIC: &[u8; 4] = b"DRKC";
+
+/// The version of the format that is written, and the only version that can be read.
+pub const VERSION: u16 = 2;
+
+/// The tags that describe the kind of each value.
+const TAG_VOID: u8 = 0;
+const TAG_ANY: u8 = 1;
+const TAG_INT: u8 = 2;
+const TAG_FLOAT: u8 = 3;
+const TAG_BOOLEAN: u8 = 4;
+const TAG_STRING: u8 = 5;
+const TAG_BYTES: u8 = 6;
+const TAG_ARRAY: u8 = 7;
+const TAG_MAP: u8 = 8;
+const TAG_IDENTIFIER: u8 = 9;
+const TAG_LABEL: u8 = 10;
+const TAG_END: u8 = 11;
+const TAG_INSTRUCTION: u8 = 12;
+const TAG_CHAR: u8 = 13;
+const TAG_NULL: u8 = 14;
+
+/// The Compiled struct maintains a program that was loaded from bytecode.
+#[derive(Debug)]
+pub struct Compiled {
+    /// The verified code of the program.
+    pub code: Code,
+
+    /// The source of the program, if it was included when the program was compiled.
+    pub source: Option<String>,
+}
+
+/// This function writes the code as bytecode.
+///
+/// # Arguments
+/// `code` - The code to write.
+/// `source` - The source of the code, which is included so that errors can point into it. If this is None, the source is left out.
+pub fn write(code: &Code, source: Option<&str>) -> Vec<u8> {
+    let mut writer = Writer::default();
+    match source {
+        Some(source) => {
+            writer.body.push(1);
+            let index = writer.intern(source);
+            write_unsigned(&mut writer.body, index);
+        }
+        None => writer.body.push(0),
+    }
+
+    write_unsigned(&mut writer.body, code.get_values().len());
+    for value in code.get_values() {
+        writer.write_value(value);
+    }
+
+    // The labels are sorted, so that compiling the same program always produces the same bytes.
+    let docs = code
+        .get_labels()
+        .iter()
+        .filter_map(|(name, label)| Some((name.as_str(), label.doc.as_deref()?)))
+        .collect::<BTreeMap<_, _>>();
+    write_unsigned(&mut writer.body, docs.len());
+    for (name, doc) in docs {
+        let (name, doc) = (writer.intern(name), writer.intern(doc));
+        write_unsigned(&mut writer.body, name);
+        write_unsigned(&mut writer.body, doc);
+    }
+
+    let pure = code
+        .get_labels()
+        .iter()
+        .filter(|(_, label)| label.pure)
+        .map(|(name, _)| name.as_str())
+        .collect::<BTreeSet<_>>();
+    write_unsigned(&mut writer.body, pure.len());
+    for name in pure {
+        writer.write_string(name);
+    }
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    write_unsigned(&mut bytes, writer.pool.len());
+    for string in &writer.pool {
+        write_unsigned(&mut bytes, string.len());
+        bytes.extend_from_slice(string.as_bytes());
+    }
+
+    bytes.extend_from_slice(&writer.body);
+    bytes
+}
+
+/// This function reads the bytecode into a program. The code is checked in the same way as a program that was lexed, and then verified.
+///
+/// # Arguments
+/// `bytes` - The bytecode.
+pub fn read(bytes: &[u8]) -> Result<Compiled, Error> {
+    let mut reader = Reader { bytes, offset: 0 };
+    if reader.read_bytes(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+        return Err(invalid("The File Is Not DarkVM Bytecode"));
+    }
+
+    let version = u16::from_le_bytes([reader.read_u8()?, reader.read_u8()?]);
+    if version != VERSION {
+        return Err(Error::message_only(ErrorKind::UnsupportedBytecodeVersion(
+            version,
+        )));
+    }
+
+    let mut pool = vec![];
+    for _ in 0..reader.read_unsigned()? {
+        let len = reader.read_unsigned()?;
+        let string = std::str::from_utf8(reader.read_bytes(len)?)
+            .map_err(|_| invalid("A String In The Constant Pool Is Not Valid UTF-8"))?;
+        pool.push(string);
+    }
+
+    let mut reader = PoolReader { reader, pool };
+    let source = match reader.reader.read_u8()? {
+        0 => None,
+        1 => Some(reader.read_string()?.to_owned()),
+        _ => return Err(invalid("The Source Of The Program Is Not Valid")),
+    };
+
+    let mut values = VecDeque::new();
+    for _ in 0..reader.reader.read_unsigned()? {
+        values.push_back(Arc::new(reader.read_value(0)?));
+    }
+
+    let mut docs = HashMap::new();
+    for _ in 0..reader.reader.read_unsigned()? {
+        let name = reader.read_string()?.to_owned();
+        let doc = reader.read_string()?.to_owned();
+        docs.insert(name, doc);
+    }
+
+    let mut pure = HashSet::new();
+    for _ in 0..reader.reader.read_unsigned()? {
+        pure.insert(reader.read_string()?.to_owned());
+    }
+
+    if reader.reader.offset != bytes.len() {
+        return Err(invalid("There Are Extra Bytes After The Program"));
+    }
+
+    let code = Code::from_values(values, docs, pure)?;
+    verifier::verify(&code)?;
+    Ok(Compiled { code, source })
+}
+
+/// This function creates the error for bytecode that is not valid.
+///
+/// # Arguments
+/// `reason` - Why the bytecode is not valid.
+fn invalid(reason: &str) -> Error {
+    Error::message_only(ErrorKind::InvalidBytecode(reason.to_owned()))
+}
+
+/// This function writes the number as unsigned LEB128: seven bits at a time, from the lowest, with the high bit set on every byte but the last.
+///
+/// # Arguments
+/// `bytes` - The bytes to write to.
+/// `value` - The number to write.
+fn write_unsigned(bytes: &mut Vec<u8>, value: usize) {
+    let mut value = value as u64;
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+
+    bytes.push(value as u8);
+}
+
+/// The Writer struct maintains the constant pool and the rest of the bytecode while it is written.
+/// The pool is only complete once everything else is written, so it is placed in front of the body at the end.
+#[derive(Default)]
+struct Writer<'a> {
+    pool: Vec<&'a str>,
+    indices: HashMap<&'a str, usize>,
+    body: Vec<u8>,
+}
+
+impl<'a> Writer<'a> {
+    /// This function returns the index of the string in the constant pool, adding it if it is not there yet.
+    ///
+    /// # Arguments
+    /// `string` - The string to find.
+    fn intern(&mut self, string: &'a str) -> usize {
+        let pool = &mut self.pool;
+        *self.indices.entry(string).or_insert_with(|| {
+            pool.push(string);
+            pool.len() - 1
+        })
+    }
+
+    /// This function writes the string as its index in the constant pool.
+    ///
+    /// # Arguments
+    /// `string` - The string to write.
+    fn write_string(&mut self, string: &'a str) {
+        let index = self.intern(string);
+        write_unsigned(&mut self.body, index);
+    }
+
+    /// This function writes the position, the tag, and the contents of the value.
+    ///
+    /// # Arguments
+    /// `value` - The value to write.
+    fn write_value(&mut self, value: &'a Value) {
+        write_unsigned(&mut self.body, value.pos);
+        match &value.kind {
+            ValueKind::Void => self.body.push(TAG_VOID),
+            ValueKind::Null => self.body.push(TAG_NULL),
+            ValueKind::Any => self.body.push(TAG_ANY),
+            ValueKind::Int(value) => {
+                self.body.push(TAG_INT);
+                self.body.extend_from_slice(&value.to_le_bytes());
+            }
+            ValueKind::Float(value) => {
+                self.body.push(TAG_FLOAT);
+                self.body.extend_from_slice(&value.to_bits().to_le_bytes());
+            }
+            ValueKind::Boolean(value) => self.body.extend_from_slice(&[TAG_BOOLEAN, *value as u8]),
+            ValueKind::String(value) => {
+                self.body.push(TAG_STRING);
+                self.write_string(value);
+            }
+            ValueKind::Char(value) => {
+                self.body.push(TAG_CHAR);
+                write_unsigned(&mut self.body, *value as usize);
+            }
+            ValueKind::Bytes(bytes) => {
+                self.body.push(TAG_BYTES);
+                write_unsigned(&mut self.body, bytes.len());
+                self.body.extend_from_slice(bytes);
+            }
+            ValueKind::Array(values) => {
+                self.body.push(TAG_ARRAY);
+                write_unsigned(&mut self.body, values.len());
+                values.iter().for_each(|value| self.write_value(value));
+            }
+            ValueKind::Map(entries) => {
+                self.body.push(TAG_MAP);
+                write_unsigned(&mut self.body, entries.len());
+                for (key, value) in entries {
+                    self.write_string(key);
+                    self.write_value(value);
+                }
+            }
+            ValueKind::Identifier(name) => {
+                self.body.push(TAG_IDENTIFIER);
+                self.write_string(name);
+            }
+            ValueKind::Label(name, parameters) => {
+                self.body.push(TAG_LABEL);
+                self.write_string(name);
+                write_unsigned(&mut self.body, parameters.len());
+                for parameter in parameters {
+                    write_unsigned(&mut self.body, parameter.pos);
+                    self.write_string(&parameter.name);
+                }
+            }
+            ValueKind::End => self.body.push(TAG_END),
+            ValueKind::Instruction(opcode) => {
+                self.body.push(TAG_INSTRUCTION);
+                self.write_string(opcode.get_name());
+            }
+        }
+    }
+}
+
+/// The Reader struct reads the bytecode from the start, one field at a time.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// This function reads the given number of bytes.
+    ///
+    /// # Arguments
+    /// `len` - The number of bytes to read.
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| invalid("The File Ended Before The Program Did"))?;
+        let bytes = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    /// This function reads a single byte.
+    fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    /// This function reads 8 little endian bytes.
+    fn read_u64(&mut self) -> Result<u64, Error> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// This function reads a number that was written as unsigned LEB128.
+    fn read_unsigned(&mut self) -> Result<usize, Error> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return usize::try_from(value).map_err(|_| invalid("A Number Is Too Large"));
+            }
+        }
+
+        Err(invalid("A Number Is Too Large"))
+    }
+}
+
+/// The PoolReader struct reads the values of the bytecode, once the constant pool has been read.
+struct PoolReader<'a> {
+    reader: Reader<'a>,
+    pool: Vec<&'a str>,
+}
+
+impl<'a> PoolReader<'a> {
+    /// This function reads a string, which is written as its index in the constant pool.
+    fn read_string(&mut self) -> Result<&'a str, Error> {
+        let index = self.reader.read_unsigned()?;
+        self.pool
+            .get(index)
+            .copied()
+            .ok_or_else(|| invalid("A String Is Not In The Constant Pool"))
+    }
+
+    /// This function reads the position, the tag, and the contents of a value.
+    ///
+    /// # Arguments
+    /// `depth` - The number of arrays and maps that the value is inside of, which is limited so that a corrupted file can not overflow the stack.
+    fn read_value(&mut self, depth: usize) -> Result<Value, Error> {
+        if depth > MAX_NESTING {
+            return Err(invalid("The Values Are Nested Too Deeply"));
+        }
+
+        let pos = self.reader.read_unsigned()?;
+        let kind = match self.reader.read_u8()? {
+            TAG_VOID => ValueKind::Void,
+            TAG_NULL => ValueKind::Null,
+            TAG_ANY => ValueKind::Any,
+            TAG_INT => ValueKind::Int(self.reader.read_u64()? as i64),
+            TAG_FLOAT => ValueKind::Float(f64::from_bits(self.reader.read_u64()?)),
+            TAG_BOOLEAN => match self.reader.read_u8()? {
+                0 => ValueKind::Boolean(false),
+                1 => ValueKind::Boolean(true),
+                _ => return Err(invalid("A Boolean Is Not Valid")),
+            },
+            TAG_STRING => ValueKind::String(self.read_string()?.to_owned()),
+            TAG_CHAR => match u32::try_from(self.reader.read_unsigned()?)
+                .ok()
+                .and_then(char::from_u32)
+            {
+                Some(value) => ValueKind::Char(value),
+                None => return Err(invalid("A Char Is Not Valid")),
+            },
+            TAG_BYTES => {
+                let len = self.reader.read_unsigned()?;
+                ValueKind::Bytes(self.reader.read_bytes(len)?.to_vec())
+            }
+            TAG_ARRAY => {
+                let mut values = vec![];
+                for _ in 0..self.reader.read_unsigned()? {
+                    values.push(Arc::new(self.read_value(depth + 1)?));
+                }
+
+                ValueKind::Array(values)
+            }
+            TAG_MAP => {
+                let mut entries = BTreeMap::new();
+                for _ in 0..self.reader.read_unsigned()? {
+                    let key = self.read_string()?.to_owned();
+                    entries.insert(key, Arc::new(self.read_value(depth + 1)?));
+                }
+
+                ValueKind::Map(entries)
+            }
+            TAG_IDENTIFIER => ValueKind::Identifier(self.read_string()?.to_owned()),
+            TAG_LABEL => {
+                let name = self.read_string()?.to_owned();
+                let mut parameters = vec![];
+                for _ in 0..self.reader.read_unsigned()? {
+                    let pos = self.reader.read_unsigned()?;
+                    parameters.push(Parameter::new(pos, self.read_string()?.to_owned()));
+                }
+
+                ValueKind::Label(name, parameters)
+            }
+            TAG_END => ValueKind::End,
+            TAG_INSTRUCTION => {
+                let name = self.read_string()?;
+                let opcode = Opcode::from_name(name).ok_or_else(|| {
+                    Error::message_only(ErrorKind::InvalidBytecode(format!(
+                        "The Instruction '{}' Does Not Exist",
+                        name
+                    )))
+                })?;
+                ValueKind::Instruction(opcode)
+            }
+            tag => {
+                return Err(Error::message_only(ErrorKind::InvalidBytecode(format!(
+                    "The Tag {} Is Not A Kind Of Value",
+                    tag
+                ))))
+            }
+        };
+
+        Ok(Value::new(pos, kind))
+    }
+}
@@ -0,0 +1,234 @@
+//! The CallGraph struct describes which labels of a program call or jump into which other labels.
+//! It is built from the code without running it, so it is useful for documentation and for finding dead code.
+//!
+//! A call is found wherever one of the call instructions names a label, even if the call is conditional,
+//! wherever the recurse instruction calls the label that it is in, and for every pattern of a match instruction. A jump is found wherever a jump instruction
+//! takes a literal int that lands inside of a different label. Jumps whose target is computed at runtime can not be followed.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{call_graph::CallGraph, code::Code, errors::error::Error, lexer::Lexer};
+//! # fn run() -> Result<(), Error> {
+//! let code = Code::new(Lexer::default().lex("@main call greet end @greet printn \"Hi\" end")?)?;
+//! let graph = CallGraph::new(&code);
+//! assert_eq!(graph.get_callees("main"), vec!["greet"]);
+//! println!("{}", graph.to_dot());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    code::Code, instructions::Opcode, utils::natives::Natives, values::value_kinds::ValueKind,
+    verifier,
+};
+use std::collections::{BTreeSet, VecDeque};
+
+/// The EdgeKind enum describes how one label reaches another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// The label is called, so a new frame is pushed for it.
+    Call,
+
+    /// The label is jumped into, so it runs in the frame of the caller.
+    Jump,
+}
+
+/// The Edge struct describes a single place where one label reaches another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    /// The name of the label that contains the instruction.
+    pub caller: String,
+
+    /// The name of the label that is reached.
+    pub callee: String,
+
+    /// Whether the label is called or jumped into.
+    pub kind: EdgeKind,
+
+    /// The position of the instruction in the source.
+    pub pos: usize,
+}
+
+/// The CallGraph struct maintains every label of a program and every edge between them.
+#[derive(Debug, Clone)]
+pub struct CallGraph {
+    labels: Vec<String>,
+    edges: Vec<Edge>,
+}
+
+impl CallGraph {
+    /// Constructs the call graph of the given code.
+    ///
+    /// # Arguments
+    /// `code` - The code to analyze.
+    pub fn new(code: &Code) -> CallGraph {
+        let ranges = code
+            .get_labels_in_order()
+            .into_iter()
+            .map(|(name, label)| (name.to_owned(), label.start_pos, label.end_pos))
+            .collect::<Vec<_>>();
+
+        // Labels may be nested, so the innermost label is the one that starts last.
+        let label_at = |idx: usize| {
+            ranges
+                .iter()
+                .rev()
+                .find(|(_, start, end)| *start < idx && idx <= *end)
+                .map(|(name, _, _)| name.as_str())
+        };
+
+        let values = code.get_values();
+        let mut edges = vec![];
+        for (idx, value) in values.iter().enumerate() {
+            let caller = match label_at(idx) {
+                Some(caller) => caller,
+                None => continue,
+            };
+
+            let edge = |callee: &str, kind| Edge {
+                caller: caller.to_owned(),
+                callee: callee.to_owned(),
+                kind,
+                pos: value.pos,
+            };
+
+            // The label of the times instruction comes after its count, which may be any expression.
+            if let ValueKind::Instruction(Opcode::Times) = value.kind {
+                let label =
+                    verifier::skip_arguments(code, &Natives::default(), idx + 1, 1, value.pos)
+                        .ok()
+                        .and_then(|idx| values.get(idx));
+                if let Some(ValueKind::Identifier(callee)) = label.map(|label| &label.kind) {
+                    edges.push(edge(callee, EdgeKind::Call));
+                }
+
+                continue;
+            }
+
+            // The labels of the match instruction follow each of its patterns.
+            if let ValueKind::Instruction(Opcode::Match) = value.kind {
+                let labels = values
+                    .iter()
+                    .skip(idx + 1)
+                    .take_while(|arg| !matches!(arg.kind, ValueKind::Instruction(Opcode::EndMatch)))
+                    .skip(1)
+                    .step_by(2);
+                for label in labels {
+                    if let ValueKind::Identifier(callee) = &label.kind {
+                        edges.push(edge(callee, EdgeKind::Call));
+                    }
+                }
+
+                continue;
+            }
+
+            match (&value.kind, values.get(idx + 1).map(|arg| &arg.kind)) {
+                (ValueKind::Instruction(Opcode::Recurse), _) => {
+                    edges.push(edge(caller, EdgeKind::Call))
+                }
+                (
+                    ValueKind::Instruction(Opcode::Call)
+                    | ValueKind::Instruction(Opcode::CallIfTrue)
+                    | ValueKind::Instruction(Opcode::CallIfFalse)
+                    | ValueKind::Instruction(Opcode::Defer),
+                    Some(ValueKind::Identifier(callee)),
+                ) => edges.push(edge(callee, EdgeKind::Call)),
+                _ => {
+                    if let Some(callee) = code.get_jump_target(idx).and_then(label_at) {
+                        if callee != caller {
+                            edges.push(edge(callee, EdgeKind::Jump));
+                        }
+                    }
+                }
+            }
+        }
+
+        CallGraph {
+            labels: ranges.into_iter().map(|(name, _, _)| name).collect(),
+            edges,
+        }
+    }
+
+    /// This function returns the names of every label, in the order that they appear in the program.
+    pub fn get_labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// This function returns every edge, in the order that they appear in the program.
+    pub fn get_edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// This function returns the names of the labels that the given label reaches, without duplicates.
+    ///
+    /// # Arguments
+    /// `label` - The name of the label.
+    pub fn get_callees(&self, label: &str) -> Vec<&str> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.caller == label)
+            .map(|edge| edge.callee.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// This function returns the names of the labels that reach the given label, without duplicates.
+    ///
+    /// # Arguments
+    /// `label` - The name of the label.
+    pub fn get_callers(&self, label: &str) -> Vec<&str> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.callee == label)
+            .map(|edge| edge.caller.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// This function returns the names of the labels that can not be reached from the main label, in the order that they appear in the program.
+    /// These labels are dead code, unless they are only reached by a jump whose target is computed at runtime.
+    pub fn get_unreachable(&self) -> Vec<&str> {
+        let mut reached = BTreeSet::new();
+        let mut pending = VecDeque::from(vec!["main"]);
+        while let Some(label) = pending.pop_front() {
+            if reached.insert(label) {
+                pending.extend(self.get_callees(label));
+            }
+        }
+
+        self.labels
+            .iter()
+            .map(String::as_str)
+            .filter(|label| !reached.contains(label))
+            .collect()
+    }
+
+    /// This function writes the call graph in the DOT language, which can be drawn by Graphviz.
+    /// Calls are drawn as solid edges and jumps as dashed edges. Each pair of labels is connected at most once for each kind.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph calls {\n");
+        for label in &self.labels {
+            dot.push_str(&format!("    {:?};\n", label));
+        }
+
+        let mut drawn = BTreeSet::new();
+        for edge in &self.edges {
+            let style = match edge.kind {
+                EdgeKind::Call => "solid",
+                EdgeKind::Jump => "dashed",
+            };
+
+            if drawn.insert((&edge.caller, &edge.callee, style)) {
+                dot.push_str(&format!(
+                    "    {:?} -> {:?} [style={}];\n",
+                    edge.caller, edge.callee, style
+                ));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
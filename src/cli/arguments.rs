@@ -0,0 +1,439 @@
+use super::{doc::DocFormat, languages::Language};
+use dark_vm::{
+    builder::VMBuilder,
+    errors::{error::Error, error_kind::ErrorKind},
+    utils::{
+        aliases::Aliases, calling_convention::CallingConvention, case::Case,
+        float_equality::FloatEquality, log_level::LogLevel, overflow::Overflow,
+        shadowing::Shadowing, trace::Trace,
+    },
+};
+use std::{env, fs, str::FromStr, time::Duration};
+
+/// The Command enum describes what the program should do with the file passed in.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run,
+    Debug,
+    Dap,
+    Lsp,
+    Serve,
+    Kernel,
+    Xref,
+    Doc,
+    Profile,
+    Disasm,
+    Instructions,
+}
+
+pub struct Arguments {
+    command: Command,
+    path: Option<String>,
+    show_time: bool,
+    show_machine: bool,
+    show_metrics: bool,
+    show_diff: bool,
+    dump_code: bool,
+    doc_format: Option<DocFormat>,
+    language: Option<Language>,
+    type_check: bool,
+    assert_stack_empty: bool,
+    fuel: Option<u64>,
+    timeout: Option<u64>,
+    max_depth: Option<usize>,
+    max_stack: Option<usize>,
+    max_string: Option<usize>,
+    max_array: Option<usize>,
+    max_value_depth: Option<usize>,
+    port: Option<u16>,
+    listen: Option<String>,
+    log_level: Option<LogLevel>,
+    shadowing: Option<Shadowing>,
+    case: Option<Case>,
+    overflow: Option<Overflow>,
+    calling_convention: Option<CallingConvention>,
+    float_equality: Option<FloatEquality>,
+    history: Option<usize>,
+    allow_fs: bool,
+    fs_root: Option<String>,
+    allow_exec: bool,
+    allowed_commands: Vec<String>,
+    allow_net: bool,
+    allow_env: bool,
+    allow_clock: bool,
+    binary: bool,
+    record: Option<String>,
+    compile: Option<String>,
+    aliases_path: Option<String>,
+    aliases: Aliases,
+    timeline: Option<String>,
+    sample: Option<u64>,
+    folded: Option<String>,
+    replay: Option<String>,
+}
+
+impl Arguments {
+    pub fn new() -> Result<Arguments, Error> {
+        let mut args = env::args().skip(1).peekable();
+        let mut arguments = Arguments {
+            command: Command::Run,
+            path: None,
+            show_time: false,
+            show_machine: false,
+            show_metrics: false,
+            show_diff: false,
+            dump_code: false,
+            doc_format: None,
+            language: None,
+            type_check: false,
+            assert_stack_empty: false,
+            fuel: None,
+            timeout: None,
+            max_depth: None,
+            max_stack: None,
+            max_string: None,
+            max_array: None,
+            max_value_depth: None,
+            port: None,
+            listen: None,
+            log_level: None,
+            shadowing: None,
+            case: None,
+            overflow: None,
+            calling_convention: None,
+            float_equality: None,
+            history: None,
+            allow_fs: false,
+            fs_root: None,
+            allow_exec: false,
+            allowed_commands: vec![],
+            allow_net: false,
+            allow_env: false,
+            allow_clock: false,
+            binary: false,
+            record: None,
+            compile: None,
+            aliases_path: None,
+            aliases: Aliases::default(),
+            timeline: None,
+            sample: None,
+            folded: None,
+            replay: None,
+        };
+
+        // The command must come first, so that it is not confused with a file.
+        let command = match args.peek().map(String::as_str) {
+            Some("debug") => Some(Command::Debug),
+            Some("dap") => Some(Command::Dap),
+            Some("lsp") => Some(Command::Lsp),
+            Some("serve") => Some(Command::Serve),
+            Some("kernel") => Some(Command::Kernel),
+            Some("xref") => Some(Command::Xref),
+            Some("doc") => Some(Command::Doc),
+            Some("profile") => Some(Command::Profile),
+            Some("disasm") => Some(Command::Disasm),
+            Some("instructions") => Some(Command::Instructions),
+            _ => None,
+        };
+
+        if let Some(command) = command {
+            arguments.command = command;
+            args.next();
+        }
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-t" | "--show-time" => arguments.show_time = true,
+                "-m" | "--show-machine" => arguments.show_machine = true,
+                "--metrics" => arguments.show_metrics = true,
+                "--diff" => arguments.show_diff = true,
+                "--dump-code" => arguments.dump_code = true,
+                // The flag is another way to choose the debug command, so it can not be combined with a different command.
+                "--debug" if arguments.command == Command::Run => {
+                    arguments.command = Command::Debug
+                }
+                "--debug" => return Err(Error::message_only(ErrorKind::UnrecognizedArgument(arg))),
+                "--type-check" => arguments.type_check = true,
+                "--assert-stack-empty" => arguments.assert_stack_empty = true,
+                "--lang" => arguments.language = Some(parse_value(&arg, args.next())?),
+                "--format" => arguments.doc_format = Some(parse_value(&arg, args.next())?),
+                "--fuel" => arguments.fuel = Some(parse_value(&arg, args.next())?),
+                "--timeout" => arguments.timeout = Some(parse_value(&arg, args.next())?),
+                "--max-depth" => arguments.max_depth = Some(parse_value(&arg, args.next())?),
+                "--max-stack" => arguments.max_stack = Some(parse_value(&arg, args.next())?),
+                "--max-string" => arguments.max_string = Some(parse_value(&arg, args.next())?),
+                "--max-array" => arguments.max_array = Some(parse_value(&arg, args.next())?),
+                "--max-value-depth" => {
+                    arguments.max_value_depth = Some(parse_value(&arg, args.next())?)
+                }
+                "--port" => arguments.port = Some(parse_value(&arg, args.next())?),
+                "--listen" => arguments.listen = Some(parse_value(&arg, args.next())?),
+                "--log-level" => arguments.log_level = Some(parse_value(&arg, args.next())?),
+                "--shadowing" => arguments.shadowing = Some(parse_value(&arg, args.next())?),
+                "--case" => arguments.case = Some(parse_value(&arg, args.next())?),
+                "--overflow" => arguments.overflow = Some(parse_value(&arg, args.next())?),
+                "--calling-convention" => {
+                    arguments.calling_convention = Some(parse_value(&arg, args.next())?)
+                }
+                "--history" => arguments.history = Some(parse_value(&arg, args.next())?),
+                "--float-equality" => {
+                    arguments.float_equality = Some(parse_value(&arg, args.next())?)
+                }
+                "--allow-fs" => arguments.allow_fs = true,
+                "--fs-root" => arguments.fs_root = Some(parse_value(&arg, args.next())?),
+                "--allow-exec" => arguments.allow_exec = true,
+                "--allow-net" => arguments.allow_net = true,
+                "--allow-env" => arguments.allow_env = true,
+                "--allow-clock" => arguments.allow_clock = true,
+                "--binary" => arguments.binary = true,
+                "--record" => arguments.record = Some(parse_value(&arg, args.next())?),
+                "--compile" => arguments.compile = Some(parse_value(&arg, args.next())?),
+                "--aliases" => arguments.aliases_path = Some(parse_value(&arg, args.next())?),
+                "--timeline" => arguments.timeline = Some(parse_value(&arg, args.next())?),
+                "--sample" => arguments.sample = Some(parse_value(&arg, args.next())?),
+                "--folded" => arguments.folded = Some(parse_value(&arg, args.next())?),
+                "--replay" => arguments.replay = Some(parse_value(&arg, args.next())?),
+                "--allow-command" => arguments
+                    .allowed_commands
+                    .push(parse_value(&arg, args.next())?),
+                _ if arguments.path.is_none() => arguments.path = Some(arg),
+                _ => return Err(Error::message_only(ErrorKind::UnrecognizedArgument(arg))),
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    pub fn get_command(&self) -> &Command {
+        &self.command
+    }
+
+    pub fn get_path(&self) -> Option<&String> {
+        self.path.as_ref()
+    }
+
+    pub fn show_machine(&self) -> bool {
+        self.show_machine
+    }
+
+    pub fn show_time(&self) -> bool {
+        self.show_time
+    }
+
+    pub fn show_metrics(&self) -> bool {
+        self.show_metrics
+    }
+
+    pub fn show_diff(&self) -> bool {
+        self.show_diff
+    }
+
+    pub fn dump_code(&self) -> bool {
+        self.dump_code
+    }
+
+    /// This function gets the format that the `doc` command writes the documentation in. The default format is plain text.
+    pub fn get_doc_format(&self) -> DocFormat {
+        self.doc_format.unwrap_or_default()
+    }
+
+    /// This function gets the language that was passed with the `--lang` flag, which overrides the extension of the file.
+    pub fn get_language(&self) -> Option<Language> {
+        self.language
+    }
+
+    /// This function gets the port that the debug adapter listens on. The default port is 4711.
+    pub fn get_port(&self) -> u16 {
+        self.port.unwrap_or(4711)
+    }
+
+    /// This function gets the address that the evaluation server listens on. The default address is 127.0.0.1:7777.
+    pub fn get_listen_address(&self) -> &str {
+        self.listen.as_deref().unwrap_or("127.0.0.1:7777")
+    }
+
+    /// This function gets the path that the timeline of the run is saved to, if one was asked for.
+    pub fn get_timeline_path(&self) -> Option<&String> {
+        self.timeline.as_ref()
+    }
+
+    /// This function gets how often the `profile` command times a step. By default, every step is timed.
+    pub fn get_sample(&self) -> u64 {
+        self.sample.unwrap_or(1)
+    }
+
+    /// This function gets the path that the `profile` command saves the folded stacks to, if one was asked for.
+    pub fn get_folded_path(&self) -> Option<&String> {
+        self.folded.as_ref()
+    }
+
+    /// This function gets the path that the program is compiled to, if it should be compiled instead of run.
+    pub fn get_compile_path(&self) -> Option<&String> {
+        self.compile.as_ref()
+    }
+
+    /// This function gets the path that the trace of the program is saved to, if the program is recorded.
+    pub fn get_record_path(&self) -> Option<&String> {
+        self.record.as_ref().filter(|_| self.replay.is_none())
+    }
+
+    /// This function reads the aliases from the file that was passed with --aliases, if there was one.
+    /// The errors in the file point into it, so they are prettified here, rather than with the program.
+    pub fn load_aliases(&mut self) -> Result<(), String> {
+        if let Some(path) = &self.aliases_path {
+            let text = fs::read_to_string(path).map_err(|error| {
+                Error::message_only(ErrorKind::FailedFileOperation(
+                    path.to_owned(),
+                    error.to_string(),
+                ))
+                .prettify("")
+            })?;
+            self.aliases = Aliases::parse(&text).map_err(|error| error.prettify(&text))?;
+        }
+
+        Ok(())
+    }
+
+    /// This function gets the aliases that were read from the file passed with --aliases.
+    pub fn get_aliases(&self) -> &Aliases {
+        &self.aliases
+    }
+
+    /// This function creates the trace of the program. A trace that is replayed is read from its file,
+    /// and replaying takes precedence over recording, because a replayed run has no new inputs to record.
+    pub fn trace(&self) -> Result<Option<Trace>, Error> {
+        if let Some(path) = &self.replay {
+            let text = fs::read_to_string(path).map_err(|error| {
+                Error::message_only(ErrorKind::FailedFileOperation(
+                    path.to_owned(),
+                    error.to_string(),
+                ))
+            })?;
+            Trace::replay(&text).map(Some)
+        } else if self.record.is_some() {
+            Ok(Some(Trace::record()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// This function creates a VMBuilder with the resource limits, the log level, and the capabilities passed on the command line.
+    /// Passing a root for the file system also allows the file system, and allowing any command overrides the allowed commands.
+    /// The timeout is given in milliseconds.
+    pub fn builder(&self) -> VMBuilder {
+        let mut builder = VMBuilder::default();
+        if let Some(fuel) = self.fuel {
+            builder = builder.fuel(fuel);
+        }
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(Duration::from_millis(timeout));
+        }
+
+        if let Some(max_depth) = self.max_depth {
+            builder = builder.max_depth(max_depth);
+        }
+
+        if let Some(max_stack) = self.max_stack {
+            builder = builder.max_stack(max_stack);
+        }
+
+        if let Some(max_string) = self.max_string {
+            builder = builder.max_string_len(max_string);
+        }
+
+        if let Some(max_array) = self.max_array {
+            builder = builder.max_array_len(max_array);
+        }
+
+        if let Some(max_value_depth) = self.max_value_depth {
+            builder = builder.max_value_depth(max_value_depth);
+        }
+
+        if let Some(log_level) = self.log_level {
+            builder = builder.log_level(log_level);
+        }
+
+        if let Some(shadowing) = self.shadowing {
+            builder = builder.shadowing(shadowing);
+        }
+
+        if let Some(case) = self.case {
+            builder = builder.case(case);
+        }
+
+        if let Some(overflow) = self.overflow {
+            builder = builder.overflow(overflow);
+        }
+
+        if let Some(calling_convention) = self.calling_convention {
+            builder = builder.calling_convention(calling_convention);
+        }
+
+        if let Some(float_equality) = self.float_equality {
+            builder = builder.float_equality(float_equality);
+        }
+
+        if let Some(history) = self.history {
+            builder = builder.history(history);
+        }
+
+        if let Some(fs_root) = &self.fs_root {
+            builder = builder.fs_root(fs_root);
+        } else if self.allow_fs {
+            builder = builder.allow_fs();
+        }
+
+        if self.allow_exec {
+            builder = builder.allow_exec();
+        } else {
+            for command in &self.allowed_commands {
+                builder = builder.allow_command(command);
+            }
+        }
+
+        if self.allow_net {
+            builder = builder.allow_net();
+        }
+
+        if self.allow_env {
+            builder = builder.allow_env();
+        }
+
+        if self.allow_clock {
+            builder = builder.allow_clock();
+        }
+
+        if self.binary {
+            builder = builder.binary_io();
+        }
+
+        if self.type_check {
+            builder = builder.type_check();
+        }
+
+        if self.assert_stack_empty {
+            builder = builder.assert_stack_empty();
+        }
+
+        if !self.aliases.is_empty() {
+            builder = builder.aliases(self.aliases.clone());
+        }
+
+        builder
+    }
+}
+
+/// This function parses the value that follows an argument.
+/// If the value is missing or can not be parsed, an error is returned.
+///
+/// # Arguments
+/// `arg` - The argument that expects the value.
+/// `value` - The value after the argument.
+fn parse_value<T: FromStr>(arg: &str, value: Option<String>) -> Result<T, Error> {
+    let value = value
+        .ok_or_else(|| Error::message_only(ErrorKind::ExpectedArgumentValue(arg.to_owned())))?;
+    value
+        .parse()
+        .map_err(|_| Error::message_only(ErrorKind::InvalidArgumentValue(arg.to_owned(), value)))
+}
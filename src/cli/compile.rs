@@ -0,0 +1,30 @@
+//! The compiler for the `--compile` flag, which writes a program to the bytecode format instead of running it.
+//! The program is checked as it would be before running, so that a file that is compiled can always be loaded.
+
+use dark_vm::{bytecode, code::Code, lexer::Lexer, utils::aliases::Aliases, verifier};
+use std::fs;
+
+/// Compiles the given program and saves its bytecode, along with its source, to the given path.
+///
+/// # Arguments
+/// `contents` - The contents of the program.
+/// `output` - The path to save the bytecode to.
+/// `aliases` - The aliases that were passed on the command line. They are resolved before the program is compiled, so the bytecode does not need them.
+pub fn run(contents: &str, output: &str, aliases: &Aliases) -> Result<(), String> {
+    let code = Lexer::default()
+        .lex(contents)
+        .and_then(|tokens| Code::with_aliases(tokens, aliases))
+        .map_err(|error| error.prettify(contents))?;
+    verifier::verify(&code).map_err(|error| error.prettify(contents))?;
+    let warnings = verifier::check_stack(&code).map_err(|error| error.prettify(contents))?;
+    for warning in warnings {
+        eprint!("A Warning Occurred.\n{}", warning.prettify(contents));
+    }
+
+    fs::write(output, bytecode::write(&code, Some(contents))).map_err(|error| {
+        format!(
+            "An Error Occurred.\nThe Bytecode Could Not Be Saved To '{}': {}.",
+            output, error
+        )
+    })
+}
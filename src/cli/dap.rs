@@ -0,0 +1,478 @@
+//! The debug adapter, which implements the Debug Adapter Protocol on top of the Debugger struct.
+//! This allows editors, such as VSCode, to set breakpoints, step, and inspect variables using their debugging UI.
+//!
+//! The adapter listens on a TCP port, because the program itself writes to stdout.
+//! Editors connect to it with the `debugServer` option of their launch configuration.
+
+use super::protocol::{read_message, write_message};
+use dark_vm::{
+    builder::VMBuilder,
+    debugger::{Breakpoint, DebugEvent, Debugger},
+    errors::error::Error,
+    utils::json::Json,
+};
+use std::{
+    fs,
+    io::{self, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+/// The id of the only thread in a Dark program.
+const THREAD_ID: i64 = 1;
+
+/// The variables reference of the operand stack. The locals of frame n use the reference n + 2.
+const STACK_REFERENCE: i64 = 1;
+
+/// Listens on the given port and serves debug sessions, one connection at a time.
+///
+/// # Arguments
+/// `port` - The port to listen on.
+/// `builder` - The builder used to construct the VM of every session.
+pub fn run(port: u16, builder: VMBuilder) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|error| error.to_string())?;
+    println!("Listening For Debug Adapter Connections On Port {}.", port);
+    for stream in listener.incoming() {
+        let stream = stream.map_err(|error| error.to_string())?;
+        let mut session =
+            Session::new(stream, builder.clone()).map_err(|error| error.to_string())?;
+        if let Err(error) = session.serve() {
+            println!("The Debug Session Ended With An Error: {}", error);
+        }
+    }
+
+    Ok(())
+}
+
+/// The Session struct maintains the state of a single connection with an editor.
+struct Session {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    seq: i64,
+    builder: VMBuilder,
+    debugger: Option<Debugger>,
+    path: String,
+    stop_on_entry: bool,
+    line_breakpoints: Vec<usize>,
+    label_breakpoints: Vec<usize>,
+}
+
+impl Session {
+    fn new(stream: TcpStream, builder: VMBuilder) -> io::Result<Session> {
+        Ok(Session {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            seq: 1,
+            builder,
+            debugger: None,
+            path: String::new(),
+            stop_on_entry: false,
+            line_breakpoints: vec![],
+            label_breakpoints: vec![],
+        })
+    }
+
+    /// Reads requests until the editor disconnects.
+    fn serve(&mut self) -> io::Result<()> {
+        while let Some(request) = read_message(&mut self.reader)? {
+            let command = request
+                .get("command")
+                .and_then(Json::as_str)
+                .unwrap_or("")
+                .to_owned();
+            let arguments = request.get("arguments").cloned().unwrap_or(Json::Null);
+            if !self.handle(&request, &command, &arguments)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles a single request. This function returns false when the session should end.
+    ///
+    /// # Arguments
+    /// `request` - The whole request.
+    /// `command` - The command of the request.
+    /// `arguments` - The arguments of the request.
+    fn handle(&mut self, request: &Json, command: &str, arguments: &Json) -> io::Result<bool> {
+        match command {
+            "initialize" => {
+                let capabilities = Json::object(vec![
+                    ("supportsConfigurationDoneRequest", true.into()),
+                    ("supportsConditionalBreakpoints", true.into()),
+                    ("supportsFunctionBreakpoints", true.into()),
+                ]);
+                self.respond(request, Ok(capabilities))?;
+                self.send_event("initialized", Json::object(vec![]))?;
+            }
+            "launch" => {
+                self.stop_on_entry = arguments
+                    .get("stopOnEntry")
+                    .and_then(Json::as_bool)
+                    .unwrap_or(false);
+                let result = self.launch(arguments);
+                self.respond(request, result.map(|_| Json::Null))?;
+            }
+            "setBreakpoints" => {
+                let lines = arguments
+                    .get("breakpoints")
+                    .and_then(Json::as_array)
+                    .unwrap_or(&[])
+                    .iter()
+                    .map(|breakpoint| {
+                        let line = breakpoint.get("line").and_then(Json::as_int).unwrap_or(0);
+                        (Breakpoint::Line(line as usize), condition_of(breakpoint))
+                    })
+                    .collect();
+                let ids = self.replace_breakpoints(false, lines);
+                self.respond(request, Ok(Json::object(vec![("breakpoints", ids)])))?;
+            }
+            "setFunctionBreakpoints" => {
+                let labels = arguments
+                    .get("breakpoints")
+                    .and_then(Json::as_array)
+                    .unwrap_or(&[])
+                    .iter()
+                    .map(|breakpoint| {
+                        let name = breakpoint.get("name").and_then(Json::as_str).unwrap_or("");
+                        (
+                            Breakpoint::Label(name.trim_start_matches('@').to_owned()),
+                            condition_of(breakpoint),
+                        )
+                    })
+                    .collect();
+                let ids = self.replace_breakpoints(true, labels);
+                self.respond(request, Ok(Json::object(vec![("breakpoints", ids)])))?;
+            }
+            "configurationDone" => {
+                self.respond(request, Ok(Json::Null))?;
+                if self.stop_on_entry {
+                    self.send_stopped("entry")?;
+                } else {
+                    self.run_debugger(Debugger::resume)?;
+                }
+            }
+            "threads" => {
+                let thread = Json::object(vec![("id", THREAD_ID.into()), ("name", "main".into())]);
+                let body = Json::object(vec![("threads", Json::Array(vec![thread]))]);
+                self.respond(request, Ok(body))?;
+            }
+            "stackTrace" => {
+                let body = self.stack_trace();
+                self.respond(request, Ok(body))?;
+            }
+            "scopes" => {
+                let frame_id = arguments.get("frameId").and_then(Json::as_int).unwrap_or(0);
+                let scope = |name: &str, reference: i64| {
+                    Json::object(vec![
+                        ("name", name.into()),
+                        ("variablesReference", reference.into()),
+                        ("expensive", false.into()),
+                    ])
+                };
+                let scopes = vec![
+                    scope("Locals", frame_id + 2),
+                    scope("Operand Stack", STACK_REFERENCE),
+                ];
+                self.respond(
+                    request,
+                    Ok(Json::object(vec![("scopes", Json::Array(scopes))])),
+                )?;
+            }
+            "variables" => {
+                let reference = arguments
+                    .get("variablesReference")
+                    .and_then(Json::as_int)
+                    .unwrap_or(0);
+                let body = self.variables(reference);
+                self.respond(request, Ok(body))?;
+            }
+            "evaluate" => {
+                let expression = arguments
+                    .get("expression")
+                    .and_then(Json::as_str)
+                    .unwrap_or("")
+                    .to_owned();
+                let result = match self.debugger.as_mut() {
+                    Some(debugger) => match debugger.evaluate(&expression) {
+                        Ok(Some(value)) => Ok(format!("{:?}", value)),
+                        Ok(None) => Ok("Void".to_owned()),
+                        Err(error) => Err(error.prettify(&expression)),
+                    },
+                    None => Err("The Program Has Not Been Launched.".to_owned()),
+                };
+
+                let body = result.map(|result| {
+                    Json::object(vec![
+                        ("result", result.into()),
+                        ("variablesReference", 0i64.into()),
+                    ])
+                });
+                self.respond(request, body)?;
+            }
+            "continue" => {
+                let body = Json::object(vec![("allThreadsContinued", true.into())]);
+                self.respond(request, Ok(body))?;
+                self.run_debugger(Debugger::resume)?;
+            }
+            "next" => {
+                self.respond(request, Ok(Json::Null))?;
+                self.run_debugger(Debugger::step_over)?;
+            }
+            "stepIn" => {
+                self.respond(request, Ok(Json::Null))?;
+                self.run_debugger(Debugger::step)?;
+            }
+            "stepOut" => {
+                self.respond(request, Ok(Json::Null))?;
+                self.run_debugger(Debugger::step_out)?;
+            }
+            "disconnect" | "terminate" => {
+                self.respond(request, Ok(Json::Null))?;
+                return Ok(false);
+            }
+            _ => {
+                let message = format!("The Request '{}' Is Not Supported.", command);
+                self.respond(request, Err(message))?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Loads the program given in the arguments of the launch request.
+    ///
+    /// # Arguments
+    /// `arguments` - The arguments of the launch request.
+    fn launch(&mut self, arguments: &Json) -> Result<(), String> {
+        let path = arguments
+            .get("program")
+            .and_then(Json::as_str)
+            .ok_or_else(|| "Expected A 'program' To Launch.".to_owned())?;
+        let contents = fs::read_to_string(path)
+            .map_err(|_| "An Error Occurred.\nThe Path Provided Is Not Valid.".to_owned())?;
+        let debugger = Debugger::new(&contents, self.builder.clone())
+            .map_err(|error| error.prettify(&contents))?;
+        self.path = path.to_owned();
+        self.debugger = Some(debugger);
+        Ok(())
+    }
+
+    /// Replaces either the line breakpoints or the label breakpoints, and returns the breakpoints to report to the editor.
+    ///
+    /// # Arguments
+    /// `labels` - Whether the label breakpoints should be replaced, instead of the line breakpoints.
+    /// `breakpoints` - The new breakpoints, along with their conditions.
+    fn replace_breakpoints(
+        &mut self,
+        labels: bool,
+        breakpoints: Vec<(Breakpoint, Option<String>)>,
+    ) -> Json {
+        let debugger = match self.debugger.as_mut() {
+            Some(debugger) => debugger,
+            None => return Json::Array(vec![]),
+        };
+
+        let ids = if labels {
+            &mut self.label_breakpoints
+        } else {
+            &mut self.line_breakpoints
+        };
+
+        for id in ids.drain(..) {
+            debugger.remove_breakpoint(id);
+        }
+
+        let mut reported = vec![];
+        for (breakpoint, condition) in breakpoints {
+            let line = match &breakpoint {
+                Breakpoint::Line(line) => Some(*line),
+                Breakpoint::Label(_) => None,
+            };
+
+            let id = debugger.add_breakpoint(breakpoint, condition);
+            ids.push(id);
+            let mut entry = vec![("id", id.into()), ("verified", true.into())];
+            if let Some(line) = line {
+                entry.push(("line", line.into()));
+            }
+
+            reported.push(Json::object(entry));
+        }
+
+        Json::Array(reported)
+    }
+
+    /// Runs the given debugger operation and reports where the program stopped.
+    ///
+    /// # Arguments
+    /// `operation` - The operation to run, such as resuming or stepping.
+    fn run_debugger(
+        &mut self,
+        operation: fn(&mut Debugger) -> Result<DebugEvent, Error>,
+    ) -> io::Result<()> {
+        let debugger = match self.debugger.as_mut() {
+            Some(debugger) => debugger,
+            None => return self.send_terminated(1),
+        };
+
+        match operation(debugger) {
+            Ok(DebugEvent::Paused) => self.send_stopped("step"),
+            Ok(DebugEvent::Breakpoint(_))
+            | Ok(DebugEvent::Break)
+            | Ok(DebugEvent::Watchpoint(_)) => self.send_stopped("breakpoint"),
+            Ok(DebugEvent::Finished(_)) => self.send_terminated(0),
+            Err(error) => {
+                let message = error.prettify(debugger.get_source());
+                let body = Json::object(vec![
+                    ("category", "stderr".into()),
+                    ("output", message.into()),
+                ]);
+                self.send_event("output", body)?;
+                self.send_terminated(1)
+            }
+        }
+    }
+
+    /// This function builds the body of the stackTrace response. The current frame comes first.
+    fn stack_trace(&self) -> Json {
+        let debugger = match &self.debugger {
+            Some(debugger) => debugger,
+            None => return Json::object(vec![("stackFrames", Json::Array(vec![]))]),
+        };
+
+        let source = Json::object(vec![("path", self.path.as_str().into())]);
+        let lines = debugger.get_frame_lines();
+        let frames = debugger
+            .get_vm()
+            .get_frames()
+            .iter()
+            .zip(lines)
+            .enumerate()
+            .rev()
+            .map(|(idx, (frame, line))| {
+                Json::object(vec![
+                    ("id", idx.into()),
+                    ("name", format!("@{}", frame.name).into()),
+                    ("source", source.clone()),
+                    ("line", line.unwrap_or(0).into()),
+                    ("column", 1i64.into()),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let total = frames.len();
+        Json::object(vec![
+            ("stackFrames", Json::Array(frames)),
+            ("totalFrames", total.into()),
+        ])
+    }
+
+    /// This function builds the body of the variables response.
+    ///
+    /// # Arguments
+    /// `reference` - The variables reference, which is either the operand stack or the locals of a frame.
+    fn variables(&self, reference: i64) -> Json {
+        let variable = |name: String, value: String| {
+            Json::object(vec![
+                ("name", name.into()),
+                ("value", value.into()),
+                ("variablesReference", 0i64.into()),
+            ])
+        };
+
+        let vm = match &self.debugger {
+            Some(debugger) => debugger.get_vm(),
+            None => return Json::object(vec![("variables", Json::Array(vec![]))]),
+        };
+
+        let variables = if reference == STACK_REFERENCE {
+            vm.operand_stack
+                .0
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(depth, value)| variable(depth.to_string(), format!("{:?}", value)))
+                .collect()
+        } else {
+            vm.get_frames()
+                .get((reference - 2) as usize)
+                .map(|frame| {
+                    frame
+                        .get_variables()
+                        .into_iter()
+                        .map(|(name, value)| variable(name, format!("{:?}", value)))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        Json::object(vec![("variables", Json::Array(variables))])
+    }
+
+    /// Sends a stopped event with the given reason.
+    fn send_stopped(&mut self, reason: &str) -> io::Result<()> {
+        let body = Json::object(vec![
+            ("reason", reason.into()),
+            ("threadId", THREAD_ID.into()),
+            ("allThreadsStopped", true.into()),
+        ]);
+        self.send_event("stopped", body)
+    }
+
+    /// Sends the exited and terminated events.
+    fn send_terminated(&mut self, exit_code: i64) -> io::Result<()> {
+        self.send_event("exited", Json::object(vec![("exitCode", exit_code.into())]))?;
+        self.send_event("terminated", Json::object(vec![]))
+    }
+
+    /// Sends a response to the given request. If the result is an error, the response is unsuccessful.
+    fn respond(&mut self, request: &Json, result: Result<Json, String>) -> io::Result<()> {
+        let request_seq = request.get("seq").and_then(Json::as_int).unwrap_or(0);
+        let command = request.get("command").cloned().unwrap_or(Json::Null);
+        let mut response = vec![
+            ("type", "response".into()),
+            ("request_seq", request_seq.into()),
+            ("command", command),
+        ];
+
+        match result {
+            Ok(body) => {
+                response.push(("success", true.into()));
+                if body != Json::Null {
+                    response.push(("body", body));
+                }
+            }
+            Err(message) => {
+                response.push(("success", false.into()));
+                response.push(("message", message.into()));
+            }
+        }
+
+        self.send(response)
+    }
+
+    /// Sends an event with the given body.
+    fn send_event(&mut self, event: &str, body: Json) -> io::Result<()> {
+        self.send(vec![
+            ("type", "event".into()),
+            ("event", event.into()),
+            ("body", body),
+        ])
+    }
+
+    /// Sends a message, adding the sequence number and the Content-Length header.
+    fn send(&mut self, mut message: Vec<(&str, Json)>) -> io::Result<()> {
+        message.insert(0, ("seq", self.seq.into()));
+        self.seq += 1;
+        write_message(&mut self.writer, &Json::object(message))
+    }
+}
+
+/// This function gets the condition of a breakpoint sent by the editor, if it has one.
+fn condition_of(breakpoint: &Json) -> Option<String> {
+    breakpoint
+        .get("condition")
+        .and_then(Json::as_str)
+        .filter(|condition| !condition.trim().is_empty())
+        .map(str::to_owned)
+}
@@ -0,0 +1,258 @@
+//! The interactive debugger, which reads commands from stdin and drives the Debugger struct.
+//! The debugger starts paused before the first instruction of the main label.
+
+use dark_vm::{
+    builder::VMBuilder,
+    debugger::{line_of, Breakpoint, DebugEvent, Debugger},
+    errors::error::Error,
+};
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "Commands:
+  break <label|line> [if <condition>]  Adds a breakpoint on a label or a line, with an optional condition.
+  delete <id>                          Removes the breakpoint with the given id.
+  breakpoints                          Lists the breakpoints.
+  watch <variable>                     Pauses whenever the variable is written, in any frame.
+  unwatch <variable>                   Stops watching the variable.
+  step                                 Runs one instruction, stepping into calls.
+  next                                 Runs one instruction, stepping over calls.
+  continue                             Runs until a breakpoint, a brk instruction, or a watchpoint is reached, or the program finishes.
+  stack                                Prints the operand stack, from the top down.
+  frames                               Prints the call stack, from the current frame down.
+  vars                                 Prints the variables of the current frame.
+  print <expression>                   Evaluates an expression in the current frame.
+  where                                Prints the current line.
+  quit                                 Exits the debugger.";
+
+/// Runs the interactive debugger on the given program.
+///
+/// # Arguments
+/// `contents` - The contents of the program.
+/// `builder` - The builder used to construct the VM.
+pub fn run(contents: &str, builder: VMBuilder) -> Result<(), String> {
+    let mut debugger =
+        Debugger::new(contents, builder).map_err(|error| error.prettify(contents))?;
+    println!("Debugging. Type 'help' for a list of commands.");
+    print_location(&debugger);
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("(dark) ");
+        io::stdout().flush().map_err(|error| error.to_string())?;
+        let line = match lines.next() {
+            Some(line) => line.map_err(|error| error.to_string())?,
+            None => return Ok(()),
+        };
+
+        let line = line.trim();
+        let (command, rest) = match line.find(' ') {
+            Some(index) => (&line[..index], line[index + 1..].trim()),
+            None => (line, ""),
+        };
+
+        let event = match command {
+            "" => continue,
+            "q" | "quit" => return Ok(()),
+            "h" | "help" => {
+                println!("{}", HELP);
+                continue;
+            }
+            "b" | "break" => {
+                add_breakpoint(&mut debugger, rest);
+                continue;
+            }
+            "d" | "delete" => {
+                match rest.parse() {
+                    Ok(id) if debugger.remove_breakpoint(id) => {
+                        println!("Removed Breakpoint {}.", id)
+                    }
+                    _ => println!("There Is No Breakpoint '{}'.", rest),
+                }
+
+                continue;
+            }
+            "watch" => {
+                match rest {
+                    "" => println!("Expected A Variable After 'watch'."),
+                    name if debugger.add_watchpoint(name) => println!("Watching '{}'.", name),
+                    name => println!("'{}' Is Already Watched.", name),
+                }
+
+                continue;
+            }
+            "unwatch" => {
+                match rest {
+                    name if debugger.remove_watchpoint(name) => {
+                        println!("Stopped Watching '{}'.", name)
+                    }
+                    name => println!("'{}' Is Not Watched.", name),
+                }
+
+                continue;
+            }
+            "breakpoints" => {
+                for (id, breakpoint, condition) in debugger.get_breakpoints() {
+                    let location = match breakpoint {
+                        Breakpoint::Label(name) => format!("label '{}'", name),
+                        Breakpoint::Line(line) => format!("line {}", line),
+                    };
+
+                    match condition {
+                        Some(condition) => println!("{}: {} if {}", id, location, condition),
+                        None => println!("{}: {}", id, location),
+                    }
+                }
+
+                for name in debugger.get_watchpoints() {
+                    println!("watch '{}'", name);
+                }
+
+                continue;
+            }
+            "stack" => {
+                for (depth, value) in debugger.get_vm().operand_stack.0.iter().rev().enumerate() {
+                    println!("{}: {:?}", depth, value);
+                }
+
+                continue;
+            }
+            "bt" | "frames" => {
+                let lines = debugger.get_frame_lines();
+                for (frame, line) in debugger.get_vm().get_frames().iter().zip(lines).rev() {
+                    match line {
+                        Some(line) => println!("@{} line {}", frame.name, line),
+                        None => println!("@{}", frame.name),
+                    }
+                }
+
+                continue;
+            }
+            "v" | "vars" => {
+                if let Some(frame) = debugger.get_vm().get_frames().last() {
+                    for (name, value) in frame.get_variables() {
+                        println!("{} = {:?}", name, value);
+                    }
+                }
+
+                continue;
+            }
+            "p" | "print" => {
+                match debugger.evaluate(rest) {
+                    Ok(Some(value)) => println!("{:?}", value),
+                    Ok(None) => println!("Void"),
+                    Err(error) => print_error(error, rest),
+                }
+
+                continue;
+            }
+            "w" | "where" => {
+                print_location(&debugger);
+                continue;
+            }
+            "s" | "step" => debugger.step(),
+            "n" | "next" => debugger.step_over(),
+            "c" | "continue" => debugger.resume(),
+            _ => {
+                println!(
+                    "Unknown Command '{}'. Type 'help' for a list of commands.",
+                    command
+                );
+                continue;
+            }
+        };
+
+        match event {
+            Ok(DebugEvent::Finished(result)) => {
+                if let Some(result) = result {
+                    println!("{:#?}", result);
+                }
+
+                println!("The Program Finished.");
+                return Ok(());
+            }
+            Ok(DebugEvent::Breakpoint(id)) => {
+                println!("Hit Breakpoint {}.", id);
+                print_location(&debugger);
+            }
+            Ok(DebugEvent::Watchpoint(events)) => {
+                for event in events {
+                    println!("{}", event);
+                }
+
+                print_location(&debugger);
+            }
+            Ok(DebugEvent::Break) => {
+                println!("The Program Paused At A brk Instruction.");
+                print_location(&debugger);
+            }
+            Ok(DebugEvent::Paused) => print_location(&debugger),
+            Err(error) => {
+                print_error(error, contents);
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Parses the breakpoint passed to the break command and adds it to the debugger.
+///
+/// # Arguments
+/// `debugger` - The debugger to add the breakpoint to.
+/// `rest` - The text after the break command.
+fn add_breakpoint(debugger: &mut Debugger, rest: &str) {
+    let (location, condition) = match rest.find(" if ") {
+        Some(index) => (
+            rest[..index].trim(),
+            Some(rest[index + 4..].trim().to_owned()),
+        ),
+        None => (rest, None),
+    };
+
+    if location.is_empty() {
+        println!("Expected A Label Or A Line After 'break'.");
+        return;
+    }
+
+    let breakpoint = match location.parse() {
+        Ok(line) => Breakpoint::Line(line),
+        Err(_) => Breakpoint::Label(location.trim_start_matches('@').to_owned()),
+    };
+
+    let id = debugger.add_breakpoint(breakpoint, condition);
+    println!("Added Breakpoint {}.", id);
+}
+
+/// Prints the line that the debugger is paused at.
+///
+/// # Arguments
+/// `debugger` - The debugger to print the location of.
+fn print_location(debugger: &Debugger) {
+    let source = debugger.get_source();
+    match debugger.get_vm().peek_next() {
+        Some(value) => {
+            let line = line_of(source, value.pos);
+            let frame = debugger
+                .get_vm()
+                .get_frames()
+                .last()
+                .map_or("", |frame| frame.name.as_str());
+            println!(
+                "@{} line {}: {}",
+                frame,
+                line,
+                source.lines().nth(line - 1).unwrap_or("").trim()
+            );
+        }
+        None => println!("The Program Has Finished."),
+    }
+}
+
+/// Prints the error with respect to the given input.
+///
+/// # Arguments
+/// `error` - The error to print.
+/// `input` - The input that the position of the error refers to.
+fn print_error(error: Error, input: &str) {
+    println!("{}", error.prettify(input));
+}
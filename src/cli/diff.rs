@@ -0,0 +1,34 @@
+//! The diff trace, which runs a program one instruction at a time and prints only what each instruction changed.
+//! The trace is written to stderr, so that it can be separated from the output of the program.
+
+use dark_vm::{
+    debugger::line_of,
+    errors::error::Error,
+    utils::snapshot::Snapshot,
+    values::value::Value,
+    vm::{StepResult, VM},
+};
+use std::sync::Arc;
+
+/// Runs the VM to the end, printing the line and the name of every instruction, followed by its changes.
+///
+/// # Arguments
+/// `vm` - The VM to run.
+/// `contents` - The contents of the program, which are used to find the line of each instruction.
+pub fn run(vm: &mut VM, contents: &str) -> Result<Option<Arc<Value>>, Error> {
+    loop {
+        let next = vm.peek_next();
+        let before = Snapshot::new(vm);
+        let result = vm.step()?;
+        if let Some(next) = next {
+            eprintln!("line {}: {}", line_of(contents, next.pos), next);
+            for change in before.diff(&Snapshot::new(vm)) {
+                eprintln!("    {}", change);
+            }
+        }
+
+        if let StepResult::Finished(result) = result {
+            return Ok(result);
+        }
+    }
+}
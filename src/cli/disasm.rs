@@ -0,0 +1,23 @@
+//! The disassembler for the `disasm` command, which prints the listing of the code of a program.
+//! Both source and bytecode can be disassembled, since the listing only needs the code.
+
+use dark_vm::{code::Code, lexer::Lexer, utils::aliases::Aliases};
+
+/// Prints the listing of the given code, or of the code of the given program if it was not compiled.
+///
+/// # Arguments
+/// `contents` - The contents of the program.
+/// `code` - The code of the program, if it was loaded from bytecode.
+/// `aliases` - The aliases that were passed on the command line.
+pub fn run(contents: &str, code: Option<Code>, aliases: &Aliases) -> Result<(), String> {
+    let code = match code {
+        Some(code) => code,
+        None => Lexer::default()
+            .lex(contents)
+            .and_then(|tokens| Code::with_aliases(tokens, aliases))
+            .map_err(|error| error.prettify(contents))?,
+    };
+
+    print!("{}", code.disassemble());
+    Ok(())
+}
@@ -0,0 +1,266 @@
+//! The documentation of the labels for the `doc` command.
+//! The single line comments directly above a label are its documentation, so a library of labels can document itself.
+//!
+//! The documentation can be printed as plain text, or rendered as markdown or as a standalone HTML page for publishing.
+//! Along with the documentation, every label is listed with its signature, the line that defines it, and the labels that it calls and is called by.
+
+use dark_vm::{
+    call_graph::{CallGraph, EdgeKind},
+    code::Code,
+    debugger::line_of,
+    lexer::{Lexer, PURE_ANNOTATION},
+    utils::aliases::Aliases,
+    verifier,
+};
+use std::str::FromStr;
+
+/// The DocFormat enum describes how the documentation is written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DocFormat {
+    /// Plain text, for reading in a terminal.
+    #[default]
+    Text,
+
+    /// Markdown, for publishing alongside the source.
+    Markdown,
+
+    /// A standalone HTML page.
+    Html,
+}
+
+impl FromStr for DocFormat {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "text" => Ok(DocFormat::Text),
+            "markdown" | "md" => Ok(DocFormat::Markdown),
+            "html" => Ok(DocFormat::Html),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The LabelDoc struct maintains everything that is written about a single label.
+struct LabelDoc<'a> {
+    name: &'a str,
+    signature: String,
+    line: usize,
+    doc: Option<&'a str>,
+    callees: Vec<&'a str>,
+    callers: Vec<&'a str>,
+}
+
+/// Prints the documentation of every label of the given program, in the order that they appear.
+///
+/// # Arguments
+/// `title` - The title of the documentation, which is usually the path of the program.
+/// `contents` - The contents of the program.
+/// `format` - How the documentation is written.
+/// `aliases` - The aliases that were passed on the command line.
+pub fn run(
+    title: &str,
+    contents: &str,
+    format: DocFormat,
+    aliases: &Aliases,
+) -> Result<(), String> {
+    let code = Lexer::default()
+        .lex(contents)
+        .and_then(|tokens| Code::with_aliases(tokens, aliases))
+        .map_err(|error| error.prettify(contents))?;
+    verifier::verify(&code).map_err(|error| error.prettify(contents))?;
+
+    let graph = CallGraph::new(&code);
+    let labels = graph
+        .get_labels()
+        .iter()
+        .map(|name| {
+            // The labels in the call graph come from the code, so they always exist.
+            let label = &code.get_labels()[name];
+            let mut signature = match label.pure {
+                true => format!("@[{}] {}", PURE_ANNOTATION, name),
+                false => format!("@{}", name),
+            };
+            for parameter in &label.parameters {
+                signature.push_str(&format!(" #{}", parameter.name));
+            }
+
+            LabelDoc {
+                name,
+                signature,
+                line: code
+                    .get_value(label.start_pos)
+                    .map_or(0, |value| line_of(contents, value.pos)),
+                doc: label.doc.as_deref(),
+                callees: graph.get_callees(name),
+                callers: graph.get_callers(name),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let output = match format {
+        DocFormat::Text => write_text(&labels),
+        DocFormat::Markdown => write_markdown(title, &labels, &graph),
+        DocFormat::Html => write_html(title, &labels, &graph),
+    };
+    print!("{}", output);
+    Ok(())
+}
+
+/// This function writes the documentation as plain text.
+///
+/// # Arguments
+/// `labels` - The documentation of every label.
+fn write_text(labels: &[LabelDoc]) -> String {
+    let mut output = String::new();
+    for label in labels {
+        output.push_str(&format!("{} (line {})\n", label.signature, label.line));
+        match label.doc {
+            Some(doc) => doc
+                .lines()
+                .for_each(|line| output.push_str(&format!("    {}\n", line))),
+            None => output.push_str("    No documentation.\n"),
+        }
+    }
+
+    output
+}
+
+/// This function writes the documentation as markdown, with a section for every label followed by the edges of the call graph.
+///
+/// # Arguments
+/// `title` - The title of the documentation.
+/// `labels` - The documentation of every label.
+/// `graph` - The call graph of the program.
+fn write_markdown(title: &str, labels: &[LabelDoc], graph: &CallGraph) -> String {
+    let names = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| format!("`@{}`", name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut output = format!("# {}\n\n## Labels\n", title);
+    for label in labels {
+        output.push_str(&format!("\n### `{}`\n\n", label.signature));
+        if let Some(doc) = label.doc {
+            output.push_str(&format!("{}\n\n", doc));
+        }
+
+        output.push_str(&format!("- Defined on line {}.\n", label.line));
+        if !label.callees.is_empty() {
+            output.push_str(&format!("- Calls {}.\n", names(&label.callees)));
+        }
+
+        if !label.callers.is_empty() {
+            output.push_str(&format!("- Called by {}.\n", names(&label.callers)));
+        }
+    }
+
+    output.push_str("\n## Call Graph\n\n");
+    for (caller, callee, kind) in get_edges(graph) {
+        output.push_str(&format!("- `@{}` {} `@{}`\n", caller, kind, callee));
+    }
+
+    output
+}
+
+/// This function writes the documentation as a standalone HTML page, where the names of the labels link to their sections.
+///
+/// # Arguments
+/// `title` - The title of the documentation.
+/// `labels` - The documentation of every label.
+/// `graph` - The call graph of the program.
+fn write_html(title: &str, labels: &[LabelDoc], graph: &CallGraph) -> String {
+    let link = |name: &str| {
+        format!(
+            "<a href=\"#label-{}\"><code>@{}</code></a>",
+            escape_html(name),
+            escape_html(name)
+        )
+    };
+    let links = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| link(name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let title = escape_html(title);
+    let mut output = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n<h1>{}</h1>\n<h2>Labels</h2>\n",
+        title, title
+    );
+    for label in labels {
+        output.push_str(&format!(
+            "<section id=\"label-{}\">\n<h3><code>{}</code></h3>\n",
+            escape_html(label.name),
+            escape_html(&label.signature)
+        ));
+        if let Some(doc) = label.doc {
+            output.push_str(&format!(
+                "<p>{}</p>\n",
+                escape_html(doc).replace('\n', "<br>\n")
+            ));
+        }
+
+        output.push_str(&format!("<ul>\n<li>Defined on line {}.</li>\n", label.line));
+        if !label.callees.is_empty() {
+            output.push_str(&format!("<li>Calls {}.</li>\n", links(&label.callees)));
+        }
+
+        if !label.callers.is_empty() {
+            output.push_str(&format!("<li>Called by {}.</li>\n", links(&label.callers)));
+        }
+
+        output.push_str("</ul>\n</section>\n");
+    }
+
+    output.push_str("<h2>Call Graph</h2>\n<ul>\n");
+    for (caller, callee, kind) in get_edges(graph) {
+        output.push_str(&format!(
+            "<li>{} {} {}</li>\n",
+            link(caller),
+            kind,
+            link(callee)
+        ));
+    }
+
+    output.push_str("</ul>\n</body>\n</html>\n");
+    output
+}
+
+/// This function gets the edges of the call graph without duplicates, in the order that they appear in the program.
+/// Each edge is made of the caller, the callee, and a description of how the callee is reached.
+///
+/// # Arguments
+/// `graph` - The call graph of the program.
+fn get_edges(graph: &CallGraph) -> Vec<(&str, &str, &'static str)> {
+    let mut edges: Vec<(&str, &str, &'static str)> = vec![];
+    for edge in graph.get_edges() {
+        let kind = match edge.kind {
+            EdgeKind::Call => "calls",
+            EdgeKind::Jump => "jumps into",
+        };
+
+        let edge = (edge.caller.as_str(), edge.callee.as_str(), kind);
+        if !edges.contains(&edge) {
+            edges.push(edge);
+        }
+    }
+
+    edges
+}
+
+/// This function escapes the characters that have a special meaning in HTML.
+///
+/// # Arguments
+/// `text` - The text to escape.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
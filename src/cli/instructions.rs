@@ -0,0 +1,27 @@
+//! The reference of every instruction for the `instructions` command, which is printed from the registry of instructions.
+
+use dark_vm::instructions::INSTRUCTIONS;
+
+/// Prints every instruction with its usage, its number of arguments, how it changes the stack, and its description.
+pub fn run() {
+    let width = INSTRUCTIONS
+        .iter()
+        .map(|instruction| instruction.usage.len())
+        .max()
+        .unwrap_or(0);
+    println!(
+        "{:<width$}  ARGS  STACK  DESCRIPTION",
+        "USAGE",
+        width = width
+    );
+    for instruction in INSTRUCTIONS {
+        println!(
+            "{:<width$}  {:<4}  {:<5}  {}",
+            instruction.usage,
+            instruction.arity,
+            instruction.get_stack_effect(),
+            instruction.description,
+            width = width
+        );
+    }
+}
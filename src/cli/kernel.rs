@@ -0,0 +1,624 @@
+//! The Jupyter kernel for the `kernel` command, which runs Dark programs cell by cell in a notebook.
+//! The kernel is started by Jupyter with the path of a connection file, which holds the ports of its sockets and the key that signs its messages.
+//! Every cell is evaluated by the same REPL, so the stack, the globals, and the labels are kept between cells,
+//! and the value of the last expression becomes the result of the cell.
+//!
+//! The sockets speak ZMTP, which is implemented by the zmtp module. Every connection is read on a thread of its own,
+//! but the requests are handled one at a time by the thread that owns the REPL.
+//! Signing messages with HMAC-SHA256 requires the `jupyter` feature. Without it, the kernel can only be started with an empty key.
+
+use super::{
+    repl::{Evaluation, Repl},
+    zmtp::Connection,
+};
+use dark_vm::{
+    builder::VMBuilder,
+    errors::error::Error,
+    utils::{encoding, json::Json},
+};
+use std::{
+    fs,
+    net::TcpListener,
+    process,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The version of the messaging protocol of Jupyter that the kernel implements.
+const PROTOCOL_VERSION: &str = "5.3";
+
+/// The frame that separates the identities of a message from its signature.
+const DELIMITER: &[u8] = b"<IDS|MSG>";
+
+/// A request from a shell or control connection, along with the connection that the reply is written to.
+type Request = (Arc<Mutex<Connection>>, Vec<Vec<u8>>);
+
+/// Starts the kernel with the given connection file, and handles requests until Jupyter asks it to shut down.
+///
+/// # Arguments
+/// `path` - The path of the connection file.
+/// `builder` - The builder used to construct the VM of the kernel.
+pub fn run(path: &str, builder: VMBuilder) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|error| {
+        format!(
+            "An Error Occurred.\nThe Connection File '{}' Could Not Be Read: {}.",
+            path, error
+        )
+    })?;
+    let config = Json::parse(&text).map_err(|error| error.prettify(&text))?;
+    let signer = Signer::new(
+        config.get("key").and_then(Json::as_str).unwrap_or(""),
+        config
+            .get("signature_scheme")
+            .and_then(Json::as_str)
+            .unwrap_or("hmac-sha256"),
+    )?;
+
+    let (sender, receiver) = mpsc::channel::<Request>();
+    for name in ["shell_port", "control_port"] {
+        let sender = sender.clone();
+        accept(listen(&config, name)?, "ROUTER", move |mut connection| {
+            let writer = match connection.try_clone() {
+                Ok(writer) => Arc::new(Mutex::new(writer)),
+                Err(_) => return,
+            };
+
+            while let Ok(Some(frames)) = connection.read_message() {
+                if sender.send((writer.clone(), frames)).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    let subscribers = Arc::new(Mutex::new(vec![]));
+    let iopub = subscribers.clone();
+    accept(listen(&config, "iopub_port")?, "PUB", move |connection| {
+        if let Ok(mut subscribers) = iopub.lock() {
+            subscribers.push(connection);
+        }
+    });
+
+    // The heartbeat only checks that the kernel is alive, so it is answered on its own thread, even while a cell is running.
+    accept(listen(&config, "hb_port")?, "REP", |mut connection| {
+        while let Ok(Some(frames)) = connection.read_message() {
+            if connection.write_message(&frames).is_err() {
+                return;
+            }
+        }
+    });
+
+    // The kernel never asks for input, so the requests on this socket are read and dropped.
+    accept(
+        listen(&config, "stdin_port")?,
+        "ROUTER",
+        |mut connection| {
+            while let Ok(Some(_)) = connection.read_message() {}
+        },
+    );
+
+    let mut kernel = Kernel {
+        repl: Repl::new(builder.capture_output()).map_err(|error| error.prettify(""))?,
+        signer,
+        session: session_id(),
+        subscribers,
+        message_count: 0,
+        execution_count: 0,
+    };
+    for (connection, frames) in receiver {
+        if !kernel.handle(&connection, frames) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// The Kernel struct maintains the REPL that evaluates the cells, and everything that is needed to reply to the frontends.
+struct Kernel {
+    repl: Repl,
+    signer: Signer,
+    session: String,
+    subscribers: Arc<Mutex<Vec<Connection>>>,
+    message_count: u64,
+    execution_count: i64,
+}
+
+/// The Message struct maintains the parts of a request that the kernel reads.
+struct Message {
+    identities: Vec<Vec<u8>>,
+    header: Json,
+    content: Json,
+}
+
+impl Kernel {
+    /// Handles a single request. The status of the kernel is published as busy while the request is handled, and as idle afterwards.
+    /// This function returns false once the kernel was asked to shut down.
+    ///
+    /// # Arguments
+    /// `connection` - The connection that the reply is written to.
+    /// `frames` - The frames of the request.
+    fn handle(&mut self, connection: &Mutex<Connection>, frames: Vec<Vec<u8>>) -> bool {
+        let message = match self.parse(frames) {
+            Some(message) => message,
+            None => {
+                eprintln!("A Message With An Invalid Signature Or Format Was Dropped.");
+                return true;
+            }
+        };
+
+        let parent = &message.header;
+        let msg_type = parent.get("msg_type").and_then(Json::as_str).unwrap_or("");
+        self.publish("status", parent, status("busy"));
+        let (reply, running) = match msg_type {
+            "kernel_info_request" => (Some(kernel_info()), true),
+            "execute_request" => (Some(self.execute(&message.content, parent)), true),
+            "is_complete_request" => (Some(self.is_complete(&message.content)), true),
+            "comm_info_request" => (
+                Some(Json::object(vec![
+                    ("status", "ok".into()),
+                    ("comms", Json::Object(vec![])),
+                ])),
+                true,
+            ),
+            "shutdown_request" => {
+                let restart = message.content.get("restart").cloned();
+                (
+                    Some(Json::object(vec![
+                        ("status", "ok".into()),
+                        ("restart", restart.unwrap_or(Json::Boolean(false))),
+                    ])),
+                    false,
+                )
+            }
+            _ => (None, true),
+        };
+
+        if let Some(reply) = reply {
+            let reply_type = msg_type.replace("_request", "_reply");
+            let frames = self.frames(message.identities, &reply_type, parent, reply);
+            if let Ok(mut connection) = connection.lock() {
+                if let Err(error) = connection.write_message(&frames) {
+                    eprintln!("The Reply Could Not Be Sent: {}", error);
+                }
+            }
+        }
+
+        self.publish("status", parent, status("idle"));
+        running
+    }
+
+    /// Evaluates a cell, and publishes its input, its output, its warnings, and its result or its error.
+    /// This function returns the content of the reply.
+    ///
+    /// # Arguments
+    /// `content` - The content of the request.
+    /// `parent` - The header of the request.
+    fn execute(&mut self, content: &Json, parent: &Json) -> Json {
+        let code = content.get("code").and_then(Json::as_str).unwrap_or("");
+        let silent = content
+            .get("silent")
+            .and_then(Json::as_bool)
+            .unwrap_or(false);
+        if !silent {
+            self.execution_count += 1;
+            let input = Json::object(vec![
+                ("code", code.into()),
+                ("execution_count", self.execution_count.into()),
+            ]);
+            self.publish("execute_input", parent, input);
+        }
+
+        let Evaluation { source, outcome } = self.repl.evaluate(code);
+        let result = match outcome {
+            Ok(outcome) => {
+                if let Some(output) = outcome.output.filter(|output| !output.is_empty()) {
+                    self.publish("stream", parent, stream("stdout", output));
+                }
+
+                for warning in outcome.warnings {
+                    let warning = format!("A Warning Occurred.\n{}", warning.prettify(&source));
+                    self.publish("stream", parent, stream("stderr", warning));
+                }
+
+                outcome.result
+            }
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok(value) => {
+                if let Some(value) = value.filter(|_| !silent) {
+                    let result = Json::object(vec![
+                        ("execution_count", self.execution_count.into()),
+                        (
+                            "data",
+                            Json::object(vec![("text/plain", format!("{:?}", value).into())]),
+                        ),
+                        ("metadata", Json::Object(vec![])),
+                    ]);
+                    self.publish("execute_result", parent, result);
+                }
+
+                Json::object(vec![
+                    ("status", "ok".into()),
+                    ("execution_count", self.execution_count.into()),
+                    ("user_expressions", Json::Object(vec![])),
+                    ("payload", Json::Array(vec![])),
+                ])
+            }
+            Err(error) => {
+                let (ename, evalue, traceback) = describe(error, &source);
+                let report = |status: Option<&str>| {
+                    let mut entries = vec![
+                        ("ename", ename.as_str().into()),
+                        ("evalue", evalue.as_str().into()),
+                        ("traceback", Json::Array(traceback.clone())),
+                    ];
+                    if let Some(status) = status {
+                        entries.push(("status", status.into()));
+                        entries.push(("execution_count", self.execution_count.into()));
+                    }
+
+                    Json::object(entries)
+                };
+
+                let (published, reply) = (report(None), report(Some("error")));
+                self.publish("error", parent, published);
+                reply
+            }
+        }
+    }
+
+    /// Checks whether a cell is complete, so that a console knows whether to run it or to continue it onto the next line.
+    ///
+    /// # Arguments
+    /// `content` - The content of the request.
+    fn is_complete(&self, content: &Json) -> Json {
+        let code = content.get("code").and_then(Json::as_str).unwrap_or("");
+        match self.repl.is_complete(code) {
+            Ok(true) => Json::object(vec![("status", "complete".into())]),
+            Ok(false) => Json::object(vec![
+                ("status", "incomplete".into()),
+                ("indent", "  ".into()),
+            ]),
+            Err(_) => Json::object(vec![("status", "invalid".into())]),
+        }
+    }
+
+    /// Reads the frames of a request. If the request is malformed, or its signature does not match, None is returned.
+    ///
+    /// # Arguments
+    /// `frames` - The frames of the request.
+    fn parse(&self, mut frames: Vec<Vec<u8>>) -> Option<Message> {
+        let delimiter = frames.iter().position(|frame| frame == DELIMITER)?;
+        let parts = frames.split_off(delimiter + 1);
+        frames.pop();
+        if parts.len() < 5 || !self.signer.verify(&parts[1..5], &parts[0]) {
+            return None;
+        }
+
+        let parse = |part: &[u8]| Json::parse(std::str::from_utf8(part).ok()?).ok();
+        Some(Message {
+            identities: frames,
+            header: parse(&parts[1])?,
+            content: parse(&parts[4])?,
+        })
+    }
+
+    /// Builds the frames of a message, with a new header, and signs it.
+    ///
+    /// # Arguments
+    /// `identities` - The identities that route the message.
+    /// `msg_type` - The type of the message.
+    /// `parent` - The header of the request that the message belongs to.
+    /// `content` - The content of the message.
+    fn frames(
+        &mut self,
+        identities: Vec<Vec<u8>>,
+        msg_type: &str,
+        parent: &Json,
+        content: Json,
+    ) -> Vec<Vec<u8>> {
+        self.message_count += 1;
+        let header = Json::object(vec![
+            (
+                "msg_id",
+                format!("{}-{}", self.session, self.message_count).into(),
+            ),
+            ("session", self.session.as_str().into()),
+            ("username", "dark".into()),
+            ("date", timestamp().into()),
+            ("msg_type", msg_type.into()),
+            ("version", PROTOCOL_VERSION.into()),
+        ]);
+
+        let parts = [header, parent.clone(), Json::Object(vec![]), content]
+            .map(|part| part.to_string().into_bytes());
+        let mut frames = identities;
+        frames.push(DELIMITER.to_vec());
+        frames.push(self.signer.sign(&parts).into_bytes());
+        frames.extend(parts);
+        frames
+    }
+
+    /// Publishes a message to every frontend. The frontends that can no longer be written to are dropped.
+    ///
+    /// # Arguments
+    /// `msg_type` - The type of the message.
+    /// `parent` - The header of the request that the message belongs to.
+    /// `content` - The content of the message.
+    fn publish(&mut self, msg_type: &str, parent: &Json, content: Json) {
+        let topic = format!("kernel.{}.{}", self.session, msg_type).into_bytes();
+        let frames = self.frames(vec![topic], msg_type, parent, content);
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain_mut(|subscriber| subscriber.write_message(&frames).is_ok());
+        }
+    }
+}
+
+/// The Signer struct signs messages with the key from the connection file. An empty key turns signing off.
+struct Signer {
+    key: Vec<u8>,
+}
+
+impl Signer {
+    /// Constructs a new Signer. Only the HMAC-SHA256 scheme is supported, and only when the `jupyter` feature is enabled.
+    ///
+    /// # Arguments
+    /// `key` - The key from the connection file.
+    /// `scheme` - The signature scheme from the connection file.
+    fn new(key: &str, scheme: &str) -> Result<Signer, String> {
+        if key.is_empty() {
+            return Ok(Signer { key: vec![] });
+        } else if scheme != "hmac-sha256" {
+            return Err(format!(
+                "An Error Occurred.\nThe Signature Scheme '{}' Is Not Supported. Only 'hmac-sha256' Is.",
+                scheme
+            ));
+        } else if !cfg!(feature = "jupyter") {
+            return Err("An Error Occurred.\nSigning Messages Requires The 'jupyter' Feature, Which Was Not Enabled When The VM Was Built.".to_owned());
+        }
+
+        Ok(Signer {
+            key: key.as_bytes().to_vec(),
+        })
+    }
+
+    /// Returns the signature of the parts of a message, as hex.
+    ///
+    /// # Arguments
+    /// `parts` - The header, the parent header, the metadata, and the content of the message.
+    fn sign(&self, parts: &[Vec<u8>]) -> String {
+        if self.key.is_empty() {
+            return String::new();
+        }
+
+        hmac_sha256(&self.key, parts)
+    }
+
+    /// Returns whether the signature of a request matches its parts.
+    /// The signature is compared in constant time, so that a forged signature can not be found one byte at a time.
+    ///
+    /// # Arguments
+    /// `parts` - The header, the parent header, the metadata, and the content of the request.
+    /// `signature` - The signature of the request, as hex.
+    fn verify(&self, parts: &[Vec<u8>], signature: &[u8]) -> bool {
+        if self.key.is_empty() {
+            return signature.is_empty();
+        }
+
+        std::str::from_utf8(signature)
+            .ok()
+            .and_then(encoding::hex_decode)
+            .is_some_and(|signature| verify_hmac_sha256(&self.key, parts, &signature))
+    }
+}
+
+/// Returns the HMAC-SHA256 of the parts, before it is finalized.
+///
+/// # Arguments
+/// `key` - The key of the HMAC.
+/// `parts` - The parts, which are signed as if they were one message.
+#[cfg(feature = "jupyter")]
+fn mac(key: &[u8], parts: &[Vec<u8>]) -> hmac::Hmac<sha2::Sha256> {
+    use hmac::{Hmac, Mac};
+
+    let mut mac =
+        Hmac::<sha2::Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+
+    mac
+}
+
+/// Computes the HMAC-SHA256 of the parts, as hex.
+///
+/// # Arguments
+/// `key` - The key of the HMAC.
+/// `parts` - The parts, which are signed as if they were one message.
+#[cfg(feature = "jupyter")]
+fn hmac_sha256(key: &[u8], parts: &[Vec<u8>]) -> String {
+    use hmac::Mac;
+
+    mac(key, parts)
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Without the `jupyter` feature, a Signer is never given a key, so nothing is ever signed.
+#[cfg(not(feature = "jupyter"))]
+fn hmac_sha256(_: &[u8], _: &[Vec<u8>]) -> String {
+    String::new()
+}
+
+/// Checks the HMAC-SHA256 of the parts against the signature in constant time.
+///
+/// # Arguments
+/// `key` - The key of the HMAC.
+/// `parts` - The parts, which are signed as if they were one message.
+/// `signature` - The signature, as bytes.
+#[cfg(feature = "jupyter")]
+fn verify_hmac_sha256(key: &[u8], parts: &[Vec<u8>], signature: &[u8]) -> bool {
+    use hmac::Mac;
+
+    mac(key, parts).verify_slice(signature).is_ok()
+}
+
+/// Without the `jupyter` feature, a Signer is never given a key, so no signature is ever checked.
+#[cfg(not(feature = "jupyter"))]
+fn verify_hmac_sha256(_: &[u8], _: &[Vec<u8>], _: &[u8]) -> bool {
+    false
+}
+
+/// Binds the socket whose port is given under the name in the connection file.
+///
+/// # Arguments
+/// `config` - The connection file.
+/// `name` - The name of the port, such as `shell_port`.
+fn listen(config: &Json, name: &str) -> Result<TcpListener, String> {
+    let ip = config
+        .get("ip")
+        .and_then(Json::as_str)
+        .unwrap_or("127.0.0.1");
+    let port = config
+        .get(name)
+        .and_then(Json::as_int)
+        .ok_or_else(|| format!("An Error Occurred.\nThe Connection File Has No '{}'.", name))?;
+    TcpListener::bind(format!("{}:{}", ip, port)).map_err(|error| {
+        format!(
+            "An Error Occurred.\nThe Kernel Could Not Listen On {}:{}: {}.",
+            ip, port, error
+        )
+    })
+}
+
+/// Accepts the connections to a socket on a new thread, and hands every connection to its own thread once the handshake is done.
+///
+/// # Arguments
+/// `listener` - The socket.
+/// `socket_type` - The ZMTP type of the socket.
+/// `handle` - The function that runs on the thread of each connection.
+fn accept<F>(listener: TcpListener, socket_type: &'static str, handle: F)
+where
+    F: Fn(Connection) + Send + Clone + 'static,
+{
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let handle = handle.clone();
+            thread::spawn(move || match Connection::accept(stream, socket_type) {
+                Ok(connection) => handle(connection),
+                Err(error) => eprintln!("A Connection Was Refused: {}", error),
+            });
+        }
+    });
+}
+
+/// Returns the content of the reply to a kernel_info_request.
+fn kernel_info() -> Json {
+    Json::object(vec![
+        ("status", "ok".into()),
+        ("protocol_version", PROTOCOL_VERSION.into()),
+        ("implementation", "dark-vm".into()),
+        ("implementation_version", env!("CARGO_PKG_VERSION").into()),
+        (
+            "language_info",
+            Json::object(vec![
+                ("name", "dark".into()),
+                ("version", env!("CARGO_PKG_VERSION").into()),
+                ("mimetype", "text/x-dark".into()),
+                ("file_extension", ".dark".into()),
+            ]),
+        ),
+        ("banner", "DarkVM".into()),
+        ("help_links", Json::Array(vec![])),
+    ])
+}
+
+/// Returns the content of a status message.
+///
+/// # Arguments
+/// `state` - The state of the kernel, which is `busy` or `idle`.
+fn status(state: &str) -> Json {
+    Json::object(vec![("execution_state", state.into())])
+}
+
+/// Returns the content of a stream message.
+///
+/// # Arguments
+/// `name` - The name of the stream, which is `stdout` or `stderr`.
+/// `text` - The text written to the stream.
+fn stream(name: &str, text: String) -> Json {
+    Json::object(vec![("name", name.into()), ("text", text.into())])
+}
+
+/// Describes an error the way Jupyter displays it: the name of its kind, its message, and the prettified error in red, one line at a time.
+///
+/// # Arguments
+/// `error` - The error.
+/// `source` - The source that the error points into.
+fn describe(error: Error, source: &str) -> (String, String, Vec<Json>) {
+    let kind = error.get_kind().clone();
+    let ename = format!("{:?}", kind)
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+    let evalue = String::from(kind);
+    let traceback = error
+        .prettify(source)
+        .lines()
+        .map(|line| format!("\x1b[31m{}\x1b[0m", line).into())
+        .collect();
+    (ename, evalue, traceback)
+}
+
+/// Returns an id for the session of the kernel, which is unique enough to tell its messages apart from those of another kernel.
+fn session_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("{:x}-{:x}", process::id(), nanos)
+}
+
+/// Returns the current time in UTC, in the ISO 8601 format that Jupyter expects in the header of a message.
+fn timestamp() -> String {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let seconds = elapsed.as_secs() as i64;
+    let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // This converts the number of days since 1970 to a date in the proleptic Gregorian calendar.
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+        elapsed.subsec_micros()
+    )
+}
@@ -0,0 +1,97 @@
+//! The registry of the languages that the CLI can run, and the extensions of the files that each one loads.
+//! The language of a file is chosen by the `--lang` flag if it was passed, then by the extension of the file, and then by the shebang line of the file,
+//! so that an executable script without an extension, such as one that starts with `#!/usr/bin/env dark`, still runs.
+//! A file of bytecode without an extension is recognized by the magic bytes at its start.
+
+use dark_vm::bytecode;
+use std::{path::Path, str::FromStr};
+
+/// The Language enum describes how the contents of a file are turned into a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    /// The source of a DarkVM program.
+    Dark,
+
+    /// A DarkVM program that was compiled to bytecode with the `--compile` flag.
+    Bytecode,
+}
+
+/// The extensions that are registered, along with the language of the files that have them.
+const EXTENSIONS: &[(&str, Language)] = &[("dark", Language::Dark), ("darkc", Language::Bytecode)];
+
+/// The names of the interpreters that a shebang line may run, along with their language.
+const INTERPRETERS: &[(&str, Language)] = &[("dark", Language::Dark), ("dark-vm", Language::Dark)];
+
+impl FromStr for Language {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        EXTENSIONS
+            .iter()
+            .find(|(extension, _)| extension.eq_ignore_ascii_case(name))
+            .map(|(_, language)| *language)
+            .ok_or(())
+    }
+}
+
+impl Language {
+    /// This function chooses the language of the file at the given path.
+    /// If no language was passed, the extension of the file is looked up, then the magic bytes of bytecode, and then the interpreter of its shebang line.
+    ///
+    /// # Arguments
+    /// `path` - The path of the file.
+    /// `contents` - The contents of the file.
+    /// `language` - The language that was passed with the `--lang` flag, if it was.
+    pub fn detect(
+        path: &str,
+        contents: &[u8],
+        language: Option<Language>,
+    ) -> Result<Language, String> {
+        if let Some(language) = language {
+            return Ok(language);
+        }
+
+        let by_extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| extension.parse().ok());
+        by_extension
+            .or_else(|| {
+                contents
+                    .starts_with(bytecode::MAGIC)
+                    .then_some(Language::Bytecode)
+            })
+            .or_else(|| get_interpreter(&String::from_utf8_lossy(contents)))
+            .ok_or_else(|| {
+                let extensions = EXTENSIONS
+                    .iter()
+                    .map(|(extension, _)| format!(".{}", extension))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "An Error Occurred.\nThe Language Of '{}' Could Not Be Determined. Expected A File Ending In {}, A Shebang Line Such As #!/usr/bin/env dark, Or The --lang Flag.",
+                    path, extensions
+                )
+            })
+    }
+}
+
+/// This function finds the language of the interpreter that the shebang line of the file runs, if it has one.
+/// Both `#!/usr/bin/env dark` and `#!/path/to/dark` are recognized.
+///
+/// # Arguments
+/// `contents` - The contents of the file.
+fn get_interpreter(contents: &str) -> Option<Language> {
+    let line = contents.strip_prefix("#!")?.lines().next()?;
+    let mut words = line.split_whitespace();
+    let mut program = words.next()?;
+    if program.ends_with("/env") {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+
+    let name = program.rsplit('/').next()?;
+    INTERPRETERS
+        .iter()
+        .find(|(interpreter, _)| *interpreter == name)
+        .map(|(_, language)| *language)
+}
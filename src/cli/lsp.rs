@@ -0,0 +1,391 @@
+//! The language server, which implements the Language Server Protocol over stdin and stdout.
+//! This allows editors to show the errors of a program as it is written, jump to the definitions of labels,
+//! show the documentation of instructions and labels on hover, and complete the names of instructions.
+//!
+//! Unlike the debug adapter, the language server never runs the program, so stdout is free to carry the protocol.
+
+use super::protocol::{read_message, write_message};
+use dark_vm::{
+    errors::diagnostic::Diagnostic,
+    instructions::{Opcode, INSTRUCTIONS},
+    lexer::Lexer,
+    tokens::token_kind::TokenKind,
+    utils::json::Json,
+};
+use std::{
+    collections::HashMap,
+    io::{self, BufReader, Stdout},
+};
+
+/// The error code sent when the editor calls a method that the server does not implement.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Serves a single editor over stdin and stdout until the editor sends the exit notification.
+pub fn run() -> Result<(), String> {
+    let mut server = Server {
+        writer: io::stdout(),
+        documents: HashMap::new(),
+    };
+
+    let stdin = io::stdin();
+    let mut reader = BufReader::new(stdin.lock());
+    while let Some(message) = read_message(&mut reader).map_err(|error| error.to_string())? {
+        if !server.handle(&message).map_err(|error| error.to_string())? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// The Server struct maintains the documents that the editor has opened.
+struct Server {
+    writer: Stdout,
+    documents: HashMap<String, String>,
+}
+
+impl Server {
+    /// Handles a single request or notification. This function returns false when the server should exit.
+    ///
+    /// # Arguments
+    /// `message` - The message sent by the editor.
+    fn handle(&mut self, message: &Json) -> io::Result<bool> {
+        let method = message.get("method").and_then(Json::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Json::Null);
+        let result = match method {
+            "initialize" => {
+                let capabilities = Json::object(vec![
+                    ("textDocumentSync", Json::Int(1)),
+                    ("hoverProvider", true.into()),
+                    ("definitionProvider", true.into()),
+                    ("completionProvider", Json::object(vec![])),
+                ]);
+                Some(Json::object(vec![
+                    ("capabilities", capabilities),
+                    ("serverInfo", Json::object(vec![("name", "dark".into())])),
+                ]))
+            }
+            "textDocument/didOpen" => {
+                let document = params.get("textDocument");
+                let uri = document.and_then(|document| document.get("uri"));
+                let text = document.and_then(|document| document.get("text"));
+                if let (Some(uri), Some(text)) =
+                    (uri.and_then(Json::as_str), text.and_then(Json::as_str))
+                {
+                    self.update(uri, text.to_owned())?;
+                }
+
+                None
+            }
+            "textDocument/didChange" => {
+                // The server asks for full synchronization, so the last change holds the whole text.
+                let uri = document_uri(&params);
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Json::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Json::as_str);
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    self.update(&uri, text.to_owned())?;
+                }
+
+                None
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = document_uri(&params) {
+                    self.documents.remove(&uri);
+                    self.publish_diagnostics(&uri, vec![])?;
+                }
+
+                None
+            }
+            "textDocument/hover" => Some(self.hover(&params)),
+            "textDocument/definition" => Some(self.definition(&params)),
+            "textDocument/completion" => Some(self.completion(&params)),
+            "shutdown" => Some(Json::Null),
+            "exit" => return Ok(false),
+            _ => None,
+        };
+
+        // Notifications do not have an id, so they never receive a response.
+        if let Some(id) = message.get("id").cloned() {
+            let mut response = vec![("jsonrpc", "2.0".into()), ("id", id)];
+            match result {
+                Some(result) => response.push(("result", result)),
+                None => response.push((
+                    "error",
+                    Json::object(vec![
+                        ("code", METHOD_NOT_FOUND.into()),
+                        ("message", format!("Unknown Method '{}'.", method).into()),
+                    ]),
+                )),
+            }
+
+            write_message(&mut self.writer, &Json::object(response))?;
+        }
+
+        Ok(true)
+    }
+
+    /// Stores the new text of the document and publishes its diagnostics.
+    ///
+    /// # Arguments
+    /// `uri` - The uri of the document.
+    /// `text` - The new text of the document.
+    fn update(&mut self, uri: &str, text: String) -> io::Result<()> {
+        let diagnostics = dark_vm::check(&text)
+            .iter()
+            .map(|diagnostic| to_lsp_diagnostic(&text, diagnostic))
+            .collect();
+        self.documents.insert(uri.to_owned(), text);
+        self.publish_diagnostics(uri, diagnostics)
+    }
+
+    /// Sends the diagnostics of the document to the editor.
+    ///
+    /// # Arguments
+    /// `uri` - The uri of the document.
+    /// `diagnostics` - The diagnostics, already converted to the format of the protocol.
+    fn publish_diagnostics(&mut self, uri: &str, diagnostics: Vec<Json>) -> io::Result<()> {
+        let notification = Json::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("method", "textDocument/publishDiagnostics".into()),
+            (
+                "params",
+                Json::object(vec![
+                    ("uri", uri.into()),
+                    ("diagnostics", Json::Array(diagnostics)),
+                ]),
+            ),
+        ]);
+        write_message(&mut self.writer, &notification)
+    }
+
+    /// Shows the documentation of the instruction or the label under the cursor.
+    fn hover(&self, params: &Json) -> Json {
+        let documentation = self
+            .word_at(params)
+            .and_then(|word| match Opcode::from_name(&word) {
+                Some(opcode) => Some(opcode.get_instruction().get_documentation()),
+                None => self.label_documentation(params, word.trim_start_matches('@')),
+            });
+        match documentation {
+            Some(documentation) => Json::object(vec![(
+                "contents",
+                Json::object(vec![
+                    ("kind", "plaintext".into()),
+                    ("value", documentation.into()),
+                ]),
+            )]),
+            None => Json::Null,
+        }
+    }
+
+    /// Finds the documentation of the label with the given name, which is its signature followed by the comments directly above it.
+    ///
+    /// # Arguments
+    /// `params` - The parameters of the request, which refer to the document.
+    /// `name` - The name of the label.
+    fn label_documentation(&self, params: &Json, name: &str) -> Option<String> {
+        let (_, text) = self.document(params)?;
+        let tokens = Vec::from(Lexer::default().lex(text).ok()?);
+
+        // An annotation is the token directly before the label that it belongs to.
+        let idx = tokens.iter().position(
+            |token| matches!(&token.kind, TokenKind::Label(label, ..) if label == name),
+        )?;
+        let annotation = match idx.checked_sub(1).map(|idx| &tokens[idx].kind) {
+            Some(TokenKind::Annotation(annotation)) => format!("[{}] ", annotation),
+            _ => String::new(),
+        };
+        match tokens.into_iter().nth(idx)?.kind {
+            TokenKind::Label(label, parameters, doc) => {
+                let mut documentation = format!("@{}{}", annotation, label);
+                for parameter in parameters {
+                    documentation.push_str(&format!(" #{}", parameter.name));
+                }
+
+                if let Some(doc) = doc {
+                    documentation.push_str(&format!("\n\n{}", doc));
+                }
+
+                Some(documentation)
+            }
+            _ => None,
+        }
+    }
+
+    /// Finds the definition of the label under the cursor.
+    fn definition(&self, params: &Json) -> Json {
+        let (uri, text) = match self.document(params) {
+            Some(document) => document,
+            None => return Json::Null,
+        };
+
+        let name = match self.word_at(params) {
+            Some(word) => word.trim_start_matches('@').to_owned(),
+            None => return Json::Null,
+        };
+
+        let tokens = match Lexer::default().lex(text) {
+            Ok(tokens) => tokens,
+            Err(_) => return Json::Null,
+        };
+
+        tokens
+            .iter()
+            .find(|token| matches!(&token.kind, TokenKind::Label(label, ..) if label == &name))
+            .map_or(Json::Null, |token| {
+                let start = position_of(text, token.pos);
+                let end = position_of(text, token.pos + name.chars().count() + 1);
+                Json::object(vec![("uri", uri.into()), ("range", range(start, end))])
+            })
+    }
+
+    /// Completes the names of the instructions, followed by the names of the labels in the document.
+    fn completion(&self, params: &Json) -> Json {
+        let mut items: Vec<Json> = INSTRUCTIONS
+            .iter()
+            .map(|instruction| {
+                Json::object(vec![
+                    ("label", instruction.name.into()),
+                    // The kind of a keyword.
+                    ("kind", Json::Int(14)),
+                    ("detail", instruction.usage.into()),
+                    ("documentation", instruction.get_documentation().into()),
+                ])
+            })
+            .collect();
+
+        let tokens = self
+            .document(params)
+            .and_then(|(_, text)| Lexer::default().lex(text).ok())
+            .unwrap_or_default();
+        for token in tokens {
+            if let TokenKind::Label(name, ..) = token.kind {
+                // The kind of a function.
+                items.push(Json::object(vec![
+                    ("label", name.into()),
+                    ("kind", Json::Int(3)),
+                ]));
+            }
+        }
+
+        Json::Array(items)
+    }
+
+    /// This function returns the uri and the text of the document that the request refers to.
+    fn document(&self, params: &Json) -> Option<(String, &str)> {
+        let uri = document_uri(params)?;
+        let text = self.documents.get(&uri)?;
+        Some((uri, text.as_str()))
+    }
+
+    /// This function returns the word under the cursor of the request.
+    /// A word is made of the characters that may appear in instructions, identifiers, and labels.
+    fn word_at(&self, params: &Json) -> Option<String> {
+        let (_, text) = self.document(params)?;
+        let position = params.get("position")?;
+        let line = position.get("line").and_then(Json::as_int)? as usize;
+        let character = position.get("character").and_then(Json::as_int)? as usize;
+        let chars: Vec<char> = text.lines().nth(line)?.chars().collect();
+        let cursor = char_index(&chars, character);
+        let is_word = |ch: &char| ch.is_alphanumeric() || ch == &'_' || ch == &'@';
+
+        let start = chars[..cursor]
+            .iter()
+            .rposition(|ch| !is_word(ch))
+            .map_or(0, |idx| idx + 1);
+        let end = chars[cursor..]
+            .iter()
+            .position(|ch| !is_word(ch))
+            .map_or(chars.len(), |idx| cursor + idx);
+        if start == end {
+            None
+        } else {
+            Some(chars[start..end].iter().collect())
+        }
+    }
+}
+
+/// This function gets the uri of the text document that the parameters refer to.
+fn document_uri(params: &Json) -> Option<String> {
+    params
+        .get("textDocument")
+        .and_then(|document| document.get("uri"))
+        .and_then(Json::as_str)
+        .map(str::to_owned)
+}
+
+/// Converts a diagnostic of the VM into a diagnostic of the protocol, which covers the character of the error.
+///
+/// # Arguments
+/// `text` - The text of the document.
+/// `diagnostic` - The diagnostic to convert.
+fn to_lsp_diagnostic(text: &str, diagnostic: &Diagnostic) -> Json {
+    let line = diagnostic.line - 1;
+    let chars: Vec<char> = text.lines().nth(line).unwrap_or("").chars().collect();
+    let column = (diagnostic.column - 1).min(chars.len());
+    let start = (line, utf16_len(&chars[..column]));
+    let end = (line, utf16_len(&chars[..(column + 1).min(chars.len())]));
+    Json::object(vec![
+        ("range", range(start, end)),
+        // The severity of an error.
+        ("severity", Json::Int(1)),
+        ("source", "dark".into()),
+        ("message", diagnostic.message.clone().into()),
+    ])
+}
+
+/// This function gets the line and the character of the given position, both counted from 0.
+/// Characters are counted in UTF-16 code units, as the protocol requires.
+///
+/// # Arguments
+/// `text` - The text of the document.
+/// `pos` - The position, which is the 1-based index of the character.
+fn position_of(text: &str, pos: usize) -> (usize, usize) {
+    let (mut line, mut character) = (0, 0);
+    for ch in text.chars().take(pos.saturating_sub(1)) {
+        if ch == '\n' {
+            line += 1;
+            character = 0;
+        } else {
+            character += ch.len_utf16();
+        }
+    }
+
+    (line, character)
+}
+
+/// This function converts a character of the protocol, which is counted in UTF-16 code units, into an index into the characters of the line.
+///
+/// # Arguments
+/// `chars` - The characters of the line.
+/// `character` - The character sent by the editor.
+fn char_index(chars: &[char], character: usize) -> usize {
+    let mut units = 0;
+    for (idx, ch) in chars.iter().enumerate() {
+        if units >= character {
+            return idx;
+        }
+
+        units += ch.len_utf16();
+    }
+
+    chars.len()
+}
+
+/// This function counts the UTF-16 code units of the given characters.
+fn utf16_len(chars: &[char]) -> usize {
+    chars.iter().map(|ch| ch.len_utf16()).sum()
+}
+
+/// Constructs a range of the protocol from the given start and end.
+fn range(start: (usize, usize), end: (usize, usize)) -> Json {
+    let position = |(line, character): (usize, usize)| {
+        Json::object(vec![("line", line.into()), ("character", character.into())])
+    };
+
+    Json::object(vec![("start", position(start)), ("end", position(end))])
+}
@@ -0,0 +1,53 @@
+/// The Arguments module, which holds all of the arguments to the program.
+pub mod arguments;
+
+/// The Compile module, which writes a program to bytecode for the `--compile` flag.
+pub mod compile;
+
+/// The Debug module, which provides the interactive debugger for the `debug` command.
+pub mod debug;
+
+/// The Diff module, which prints what each instruction changed for the `--diff` flag.
+pub mod diff;
+
+/// The Dap module, which provides the debug adapter for the `dap` command.
+pub mod dap;
+
+/// The Kernel module, which provides the Jupyter kernel for the `kernel` command.
+pub mod kernel;
+
+/// The Languages module, which maintains the registry of the languages that the CLI runs and the extensions of their files.
+pub mod languages;
+
+/// The Lsp module, which provides the language server for the `lsp` command.
+pub mod lsp;
+
+/// The Instructions module, which prints the registry of instructions for the `instructions` command.
+pub mod instructions;
+
+/// The Disasm module, which prints the listing of the code of a program for the `disasm` command.
+pub mod disasm;
+
+/// The Doc module, which prints or renders the documentation of the labels for the `doc` command.
+pub mod doc;
+
+/// The Profile module, which runs a program under the profiler for the `profile` command.
+pub mod profile;
+
+/// The Repl module, which provides the interactive REPL that runs when no file is passed.
+pub mod repl;
+
+/// The Serve module, which provides the evaluation server for the `serve` command.
+pub mod serve;
+
+/// The Session module, which saves and restores the labels, globals, and history of the REPL.
+pub mod session;
+
+/// The Xref module, which prints the cross reference report of the labels for the `xref` command.
+pub mod xref;
+
+/// The Zmtp module, which reads and writes the messages of the Jupyter kernel over ZeroMQ connections.
+pub mod zmtp;
+
+/// The Protocol module, which reads and writes the messages of the debug adapter and the language server.
+pub mod protocol;
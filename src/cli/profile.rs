@@ -0,0 +1,41 @@
+//! The profiler for the `profile` command.
+//! The program is run to the end, and then a histogram of the time spent in each instruction is printed.
+//! The time spent in each call stack can also be saved as folded stacks, which can be drawn as a flame graph.
+
+use dark_vm::{builder::VMBuilder, utils::profiler::Profiler};
+use std::fs;
+
+/// Runs the given program under the profiler and prints the histogram of its instructions.
+/// The histogram and the folded stacks are written even if the program failed, so that the run up to the failure can be inspected.
+///
+/// # Arguments
+/// `contents` - The contents of the program.
+/// `builder` - The builder used to construct the VM.
+/// `sample_every` - How often a step is timed.
+/// `folded_path` - The path that the folded stacks are saved to, if they were asked for.
+pub fn run(
+    contents: &str,
+    builder: VMBuilder,
+    sample_every: u64,
+    folded_path: Option<&String>,
+) -> Result<(), String> {
+    let mut vm = builder
+        .lexer()
+        .lex(contents)
+        .and_then(|tokens| builder.build(tokens))
+        .map_err(|error| error.prettify(contents))?;
+
+    let mut profiler = Profiler::new(sample_every);
+    let result = profiler.run(&mut vm);
+    print!("{}", profiler.to_histogram());
+    if let Some(path) = folded_path {
+        fs::write(path, profiler.to_folded()).map_err(|error| {
+            format!(
+                "An Error Occurred.\nThe Folded Stacks Could Not Be Saved To '{}': {}.",
+                path, error
+            )
+        })?;
+    }
+
+    result.map(|_| ()).map_err(|error| error.prettify(contents))
+}
@@ -0,0 +1,57 @@
+//! The framing shared by the debug adapter and the language server.
+//! Both protocols send JSON messages, each preceded by a Content-Length header and a blank line.
+
+use dark_vm::utils::json::Json;
+use std::io::{self, BufRead, Write};
+
+/// Reads the next message. If the stream was closed, None is returned.
+///
+/// # Arguments
+/// `reader` - The stream to read the message from.
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<Json>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some(length) = header.strip_prefix("Content-Length:") {
+            content_length = length.trim().parse::<usize>().ok();
+        }
+    }
+
+    let length = content_length.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Expected A Content-Length Header.",
+        )
+    })?;
+    let mut content = vec![0; length];
+    reader.read_exact(&mut content)?;
+    let content = String::from_utf8_lossy(&content);
+    Json::parse(&content)
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.prettify(&content)))
+}
+
+/// Writes the message, adding the Content-Length header.
+///
+/// # Arguments
+/// `writer` - The stream to write the message to.
+/// `message` - The message to write.
+pub fn write_message<W: Write>(writer: &mut W, message: &Json) -> io::Result<()> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
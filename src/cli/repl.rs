@@ -0,0 +1,302 @@
+//! The REPL, which runs when no file is passed.
+//! Every input is lexed and run in the same VM, so the operand stack and the variables are kept between inputs, and the value of the last expression is printed.
+//!
+//! The labels that an input defines are also kept, by running their definitions again in front of every later input. Defining a label again replaces it.
+//! An input continues onto the next line until every label, if, while, and match in it is closed.
+//!
+//! The labels, the globals, and the inputs of the session can be saved to a file with `:save <file>` and restored with `:load <file>`.
+//! The inputs so far are listed with `:history`.
+
+use super::session::{Definition, Session};
+use dark_vm::{
+    builder::VMBuilder,
+    code::Code,
+    errors::{error::Error, error_kind::ErrorKind},
+    instructions::Opcode,
+    lexer::Lexer,
+    tokens::{token::Token, token_kind::TokenKind},
+    utils::outcome::RunOutcome,
+    vm::VM,
+};
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, BufRead, Write},
+};
+
+/// The Repl struct maintains the VM of the REPL, along with its session, so that inputs can be evaluated one after the other.
+/// It is shared by the interactive REPL and the evaluation server.
+pub struct Repl {
+    lexer: Lexer,
+    vm: VM,
+    session: Session,
+}
+
+/// The Evaluation struct describes the result of evaluating a single input.
+pub struct Evaluation {
+    /// The source that was run, which is the input followed by the definitions of the earlier labels. The errors and the warnings point into it.
+    pub source: String,
+
+    /// The outcome of the run, or the error that stopped the source from being loaded.
+    pub outcome: Result<RunOutcome, Error>,
+}
+
+impl Repl {
+    /// Constructs a new REPL with an empty session.
+    ///
+    /// # Arguments
+    /// `builder` - The builder used to construct the VM.
+    pub fn new(builder: VMBuilder) -> Result<Repl, Error> {
+        Ok(Repl {
+            lexer: builder.lexer(),
+            vm: builder.build_repl()?,
+            session: Session::default(),
+        })
+    }
+
+    /// Lexes the input and returns whether it is complete, which means that every label, if, while, and match in it is closed.
+    ///
+    /// # Arguments
+    /// `input` - The input so far.
+    pub fn is_complete(&self, input: &str) -> Result<bool, Error> {
+        Ok(get_depth(&self.lexer.clone().lex(input)?) <= 0)
+    }
+
+    /// Evaluates the input in the VM of the REPL. The labels that an earlier input defined are run in front of it, so that they can be called.
+    ///
+    /// # Arguments
+    /// `input` - The input to evaluate.
+    pub fn evaluate(&mut self, input: &str) -> Evaluation {
+        let tokens = match self.lexer.clone().lex(input) {
+            Ok(tokens) => tokens,
+            Err(error) => {
+                return Evaluation {
+                    source: input.to_owned(),
+                    outcome: Err(error),
+                }
+            }
+        };
+
+        self.session.history.push(input.trim_end().to_owned());
+        let defined = get_definitions(input, &tokens);
+
+        // The earlier definitions of the labels that this input defines again are dropped, so that they are not duplicated.
+        let kept = self
+            .session
+            .definitions
+            .iter()
+            .filter(|old| defined.iter().all(|new| new.name != old.name))
+            .map(|old| old.source.as_str())
+            .collect::<Vec<_>>();
+        // The definitions run in front of the input, but they are placed after it in the source, so that the positions in the input,
+        // and the lines of its errors, are the same as if it ran on its own.
+        let definitions = kept.join("\n");
+        let mut source = input.to_owned();
+        if !kept.is_empty() {
+            source.push('\n');
+            source.push_str(&definitions);
+        }
+
+        let offset = input.chars().count() + 1;
+        let vm = &mut self.vm;
+        let outcome = self
+            .lexer
+            .clone()
+            .lex(&definitions)
+            .map(|mut all| {
+                for token in all.iter_mut() {
+                    token.pos += offset;
+                }
+                all.extend(tokens);
+                all
+            })
+            .and_then(Code::repl)
+            .and_then(|code| vm.swap_code(code))
+            .map(|_| vm.run());
+
+        // The labels are only kept once their input ran, so that a broken definition does not break every later input.
+        if outcome.as_ref().is_ok_and(RunOutcome::is_success) {
+            self.session
+                .definitions
+                .retain(|old| defined.iter().all(|new| new.name != old.name));
+            self.session.definitions.extend(defined);
+        }
+
+        Evaluation { source, outcome }
+    }
+}
+
+/// Runs the REPL until the input ends or `:quit` is entered.
+///
+/// # Arguments
+/// `builder` - The builder used to construct the VM.
+pub fn run(builder: VMBuilder) -> Result<(), String> {
+    let mut repl = Repl::new(builder).map_err(|error| error.prettify(""))?;
+    println!("DarkVM REPL. Type ':quit' to exit.");
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut input = String::new();
+    loop {
+        print!("{}", if input.is_empty() { "> " } else { ". " });
+        io::stdout().flush().map_err(|error| error.to_string())?;
+        let line = match lines.next() {
+            Some(line) => line.map_err(|error| error.to_string())?,
+            None => return Ok(()),
+        };
+
+        if input.is_empty() && matches!(line.trim(), ":q" | ":quit") {
+            return Ok(());
+        } else if input.is_empty() && line.trim().starts_with(':') {
+            if let Err(error) = run_command(line.trim(), &mut repl) {
+                eprint!("{}", error);
+            }
+
+            continue;
+        }
+
+        input.push_str(&line);
+        input.push('\n');
+        match repl.is_complete(&input) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(error) => {
+                eprint!("{}", error.prettify(&std::mem::take(&mut input)));
+                continue;
+            }
+        }
+
+        let Evaluation { source, outcome } = repl.evaluate(&std::mem::take(&mut input));
+        let outcome = match outcome {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                eprint!("{}", error.prettify(&source));
+                continue;
+            }
+        };
+
+        for warning in outcome.warnings {
+            eprint!("A Warning Occurred.\n{}", warning.prettify(&source));
+        }
+
+        match outcome.result {
+            Ok(Some(result)) => println!("{:?}", result),
+            Ok(None) => {}
+            Err(error) => eprint!("{}", error.prettify(&source)),
+        }
+    }
+}
+
+/// This function runs a command of the REPL, such as `:save <file>`.
+/// The errors are returned prettified, so that they can be printed as they are.
+///
+/// # Arguments
+/// `command` - The line of the command.
+/// `repl` - The REPL that the command applies to.
+fn run_command(command: &str, repl: &mut Repl) -> Result<(), String> {
+    let Repl { vm, session, .. } = repl;
+    let (name, path) = match command.find(' ') {
+        Some(index) => (&command[..index], command[index + 1..].trim()),
+        None => (command, ""),
+    };
+
+    let failed = |error: io::Error| {
+        Error::message_only(ErrorKind::FailedFileOperation(
+            path.to_owned(),
+            error.to_string(),
+        ))
+        .prettify("")
+    };
+    match name {
+        ":save" if !path.is_empty() => {
+            session.globals = vm.get_globals();
+            let (json, skipped) = session.to_json();
+            fs::write(path, json).map_err(failed)?;
+            for name in skipped {
+                println!("The Global '{}' Has No JSON Form, So It Was Not Saved.", name);
+            }
+
+            Ok(())
+        }
+        ":load" if !path.is_empty() => {
+            let text = fs::read_to_string(path).map_err(failed)?;
+            let loaded = Session::parse(&text).map_err(|error| error.prettify(""))?;
+            for (name, value) in &loaded.globals {
+                vm.define_global(name, value.clone());
+            }
+
+            *session = loaded;
+            Ok(())
+        }
+        ":history" => {
+            for (idx, input) in session.history.iter().enumerate() {
+                println!("{}: {}", idx + 1, input);
+            }
+
+            Ok(())
+        }
+        _ => Err(format!(
+            "Unknown Command '{}'. The Commands Are ':save <file>', ':load <file>', ':history', And ':quit'.\n",
+            command
+        )),
+    }
+}
+
+/// This function counts how many labels and structured instructions the tokens open without closing.
+/// A positive depth means that the input continues onto the next line.
+///
+/// # Arguments
+/// `tokens` - The tokens of the input so far.
+fn get_depth(tokens: &VecDeque<Token>) -> i64 {
+    tokens
+        .iter()
+        .map(|token| match token.kind {
+            TokenKind::Label(..)
+            | TokenKind::Instruction(Opcode::If)
+            | TokenKind::Instruction(Opcode::While)
+            | TokenKind::Instruction(Opcode::Match) => 1,
+            TokenKind::End
+            | TokenKind::Instruction(Opcode::EndIf)
+            | TokenKind::Instruction(Opcode::EndWhile)
+            | TokenKind::Instruction(Opcode::EndMatch) => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// This function finds the labels that the input defines at the top level, along with their source from the label to its end.
+///
+/// # Arguments
+/// `input` - The source of the input.
+/// `tokens` - The tokens of the input.
+fn get_definitions(input: &str, tokens: &VecDeque<Token>) -> Vec<Definition> {
+    // The positions of the tokens count characters from 1.
+    let chars = input.chars().collect::<Vec<_>>();
+    let mut definitions = vec![];
+    let mut open: Option<(String, usize)> = None;
+    let mut depth = 0;
+    for token in tokens {
+        match &token.kind {
+            TokenKind::Label(name, ..) => {
+                if depth == 0 {
+                    open = Some((name.to_owned(), token.pos));
+                }
+
+                depth += 1;
+            }
+            TokenKind::End if depth > 0 => {
+                depth -= 1;
+                if let (0, Some((name, start))) = (depth, open.take()) {
+                    let end = (token.pos - 1 + "end".len()).min(chars.len());
+                    definitions.push(Definition {
+                        name,
+                        source: chars[start - 1..end].iter().collect(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    definitions
+}
@@ -0,0 +1,164 @@
+//! The evaluation server for the `serve` command, which lets editors and notebooks talk to a long-lived VM.
+//! Every connection gets a VM of its own, which behaves like the REPL: the stack, the globals, and the labels are kept between requests.
+//! Every connection is served on a thread of its own, so a client that stays idle does not hold up the others.
+//!
+//! The protocol is line based. Each request is a JSON object on a single line, such as `{"id": 1, "source": "push 1 push 2 add"}`,
+//! and each response is a JSON object on a single line, with the id of the request, the value of the last expression,
+//! the output of the program, the error that stopped it, and its warnings. The errors and the warnings are prettified.
+//! The value is written as JSON, and a value that has no JSON form, such as bytes, is written as a string, the same way that print writes it.
+
+use super::repl::{Evaluation, Repl};
+use dark_vm::{builder::VMBuilder, utils::json::Json};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::Arc,
+    thread,
+};
+
+/// Listens on the given address and serves evaluation requests, every connection on a thread of its own.
+///
+/// # Arguments
+/// `address` - The address to listen on, such as `127.0.0.1:7777`. If the port is 0, a free port is chosen, and printed.
+/// `builder` - The function that constructs the builder of the VM of every connection. The builder itself stays on the thread that uses it.
+pub fn run<F>(address: &str, builder: F) -> Result<(), String>
+where
+    F: Fn() -> VMBuilder + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind(address).map_err(|error| error.to_string())?;
+    let address = listener.local_addr().map_err(|error| error.to_string())?;
+    println!("Listening For Evaluation Requests On {}.", address);
+    io::stdout().flush().map_err(|error| error.to_string())?;
+
+    let builder = Arc::new(builder);
+    for stream in listener.incoming() {
+        // A connection that could not be accepted, such as one that was aborted, only affects that client.
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                println!("A Connection Could Not Be Accepted: {}", error);
+                continue;
+            }
+        };
+
+        let builder = Arc::clone(&builder);
+        thread::spawn(move || {
+            if let Err(error) = serve(stream, builder()) {
+                println!("The Connection Ended With An Error: {}", error);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Reads requests from the connection until it is closed, and answers each of them.
+///
+/// # Arguments
+/// `stream` - The connection.
+/// `builder` - The builder used to construct the VM of the connection.
+fn serve(stream: TcpStream, builder: VMBuilder) -> io::Result<()> {
+    let mut repl = Repl::new(builder.capture_output())
+        .map_err(|error| io::Error::other(error.prettify("")))?;
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = handle(&mut repl, &line);
+        writeln!(writer, "{}", response)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Handles a single request and returns its response.
+///
+/// # Arguments
+/// `repl` - The REPL of the connection.
+/// `line` - The line of the request.
+fn handle(repl: &mut Repl, line: &str) -> Json {
+    let request = Json::parse(line).ok();
+    let id = request
+        .as_ref()
+        .and_then(|request| request.get("id"))
+        .cloned()
+        .unwrap_or(Json::Null);
+    let source = match request
+        .as_ref()
+        .and_then(|request| request.get("source"))
+        .and_then(Json::as_str)
+    {
+        Some(source) => source,
+        None => {
+            return response(
+                id,
+                Json::Null,
+                Json::Null,
+                "The Request Must Be A JSON Object With A 'source' String.".into(),
+                vec![],
+            )
+        }
+    };
+
+    let Evaluation { source, outcome } = repl.evaluate(source);
+    match outcome {
+        Ok(outcome) => {
+            let warnings = outcome
+                .warnings
+                .into_iter()
+                .map(|warning| warning.prettify(&source).into())
+                .collect();
+            let output = outcome.output.map_or(Json::Null, Json::from);
+            match outcome.result {
+                Ok(value) => response(
+                    id,
+                    value.map_or(Json::Null, |value| {
+                        Json::from_value(&value, value.pos)
+                            .unwrap_or_else(|_| value.to_string().into())
+                    }),
+                    output,
+                    Json::Null,
+                    warnings,
+                ),
+                Err(error) => response(
+                    id,
+                    Json::Null,
+                    output,
+                    error.prettify(&source).into(),
+                    warnings,
+                ),
+            }
+        }
+        Err(error) => response(
+            id,
+            Json::Null,
+            Json::Null,
+            error.prettify(&source).into(),
+            vec![],
+        ),
+    }
+}
+
+/// Builds a response. A response succeeded if it has no error.
+///
+/// # Arguments
+/// `id` - The id of the request.
+/// `value` - The value of the last expression.
+/// `output` - The output of the program.
+/// `error` - The error that stopped the program.
+/// `warnings` - The warnings of the run.
+fn response(id: Json, value: Json, output: Json, error: Json, warnings: Vec<Json>) -> Json {
+    Json::object(vec![
+        ("id", id),
+        ("ok", (error == Json::Null).into()),
+        ("value", value),
+        ("output", output),
+        ("error", error),
+        ("warnings", Json::Array(warnings)),
+    ])
+}
@@ -0,0 +1,119 @@
+//! The Session struct maintains what the REPL has built up: the labels that were defined, the globals, and the inputs that were entered.
+//! A session is saved as JSON with `:save`, and restored with `:load`, so that an exploratory session survives a restart.
+//!
+//! Labels are saved by their source, and they are defined again by running them in front of the next input, like every other label of the REPL.
+//! Globals are saved as JSON values, so only the values that have a JSON form are saved. The others, such as bytes, are skipped.
+
+use dark_vm::{
+    errors::{error::Error, error_kind::ErrorKind},
+    utils::json::Json,
+    values::value::Value,
+};
+use std::sync::Arc;
+
+/// The version of the session format. A session with a different version is rejected, rather than restored incorrectly.
+const VERSION: i64 = 1;
+
+/// The Definition struct maintains the source of a label that an earlier input defined.
+pub struct Definition {
+    pub name: String,
+    pub source: String,
+}
+
+#[derive(Default)]
+pub struct Session {
+    pub definitions: Vec<Definition>,
+    pub globals: Vec<(String, Arc<Value>)>,
+    pub history: Vec<String>,
+}
+
+impl Session {
+    /// This function writes the session as JSON.
+    /// This function returns the JSON, along with the names of the globals that were skipped because they have no JSON form.
+    pub fn to_json(&self) -> (String, Vec<String>) {
+        let definitions = self
+            .definitions
+            .iter()
+            .map(|definition| {
+                Json::object(vec![
+                    ("name", definition.name.as_str().into()),
+                    ("source", definition.source.as_str().into()),
+                ])
+            })
+            .collect();
+
+        let mut skipped = vec![];
+        let mut globals = vec![];
+        for (name, value) in &self.globals {
+            match Json::from_value(value, value.pos) {
+                Ok(json) => globals.push((name.to_owned(), json)),
+                Err(_) => skipped.push(name.to_owned()),
+            }
+        }
+
+        let history = self
+            .history
+            .iter()
+            .map(|input| input.as_str().into())
+            .collect();
+        let json = Json::object(vec![
+            ("version", VERSION.into()),
+            ("labels", Json::Array(definitions)),
+            ("globals", Json::Object(globals)),
+            ("history", Json::Array(history)),
+        ]);
+        (json.to_string(), skipped)
+    }
+
+    /// This function reads a session that was written by the to_json method.
+    ///
+    /// # Arguments
+    /// `text` - The JSON of the session.
+    pub fn parse(text: &str) -> Result<Session, Error> {
+        let invalid = || Error::message_only(ErrorKind::InvalidSession);
+        let json = Json::parse(text).map_err(|_| invalid())?;
+        if json.get("version").and_then(Json::as_int) != Some(VERSION) {
+            return Err(invalid());
+        }
+
+        let definitions = json
+            .get("labels")
+            .and_then(Json::as_array)
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|definition| {
+                let name = definition.get("name").and_then(Json::as_str);
+                let source = definition.get("source").and_then(Json::as_str);
+                match (name, source) {
+                    (Some(name), Some(source)) => Ok(Definition {
+                        name: name.to_owned(),
+                        source: source.to_owned(),
+                    }),
+                    _ => Err(invalid()),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        let globals = match json.get("globals") {
+            Some(Json::Object(entries)) => entries
+                .iter()
+                .map(|(name, value)| (name.to_owned(), Arc::new(value.to_value(0))))
+                .collect(),
+            _ => return Err(invalid()),
+        };
+
+        let history = json
+            .get("history")
+            .and_then(Json::as_array)
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|input| input.as_str().map(str::to_owned).ok_or_else(invalid))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Session {
+            definitions,
+            globals,
+            history,
+        })
+    }
+}
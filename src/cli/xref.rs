@@ -0,0 +1,62 @@
+//! The cross reference report for the `xref` command.
+//! For every label, the report lists its parameters, every call site, and every jump into it, with their lines.
+
+use dark_vm::{
+    call_graph::{CallGraph, EdgeKind},
+    code::Code,
+    debugger::line_of,
+    lexer::Lexer,
+    utils::aliases::Aliases,
+    verifier,
+};
+
+/// Prints the cross reference report of the given program.
+///
+/// # Arguments
+/// `contents` - The contents of the program.
+/// `aliases` - The aliases that were passed on the command line.
+pub fn run(contents: &str, aliases: &Aliases) -> Result<(), String> {
+    let code = Lexer::default()
+        .lex(contents)
+        .and_then(|tokens| Code::with_aliases(tokens, aliases))
+        .map_err(|error| error.prettify(contents))?;
+    verifier::verify(&code).map_err(|error| error.prettify(contents))?;
+
+    let graph = CallGraph::new(&code);
+    for name in graph.get_labels() {
+        // The labels in the call graph come from the code, so they always exist.
+        let label = &code.get_labels()[name];
+        let line = code
+            .get_value(label.start_pos)
+            .map_or(0, |value| line_of(contents, value.pos));
+        println!("@{} (line {})", name, line);
+
+        let parameters = label
+            .parameters
+            .iter()
+            .map(|parameter| parameter.name.as_str())
+            .collect::<Vec<_>>();
+        if parameters.is_empty() {
+            println!("  parameters: none");
+        } else {
+            println!("  parameters: {}", parameters.join(", "));
+        }
+
+        for (kind, heading) in [(EdgeKind::Call, "called"), (EdgeKind::Jump, "jumped into")].iter()
+        {
+            let sites = graph
+                .get_edges()
+                .iter()
+                .filter(|edge| &edge.callee == name && edge.kind == *kind)
+                .map(|edge| format!("line {} in @{}", line_of(contents, edge.pos), edge.caller))
+                .collect::<Vec<_>>();
+            if sites.is_empty() {
+                println!("  {}: never", heading);
+            } else {
+                println!("  {}: {}", heading, sites.join(", "));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+//! A minimal implementation of ZMTP 3.0, the wire protocol of ZeroMQ, which the Jupyter kernel uses to talk to its frontends.
+//! Only what the kernel needs is supported: the NULL security mechanism, and sockets over TCP that only accept connections.
+//! Every connection is a single peer, so a ROUTER socket replies on the connection that the request came from,
+//! and a PUB socket writes every message to each of its connections.
+
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+};
+
+/// The flag of a frame that is followed by another frame of the same message.
+const MORE: u8 = 0x01;
+
+/// The flag of a frame whose size is written in 8 bytes, instead of 1.
+const LONG: u8 = 0x02;
+
+/// The flag of a frame that holds a command, rather than a part of a message.
+const COMMAND: u8 = 0x04;
+
+/// The Connection struct maintains a connection to a single peer, after the handshake.
+pub struct Connection {
+    stream: TcpStream,
+}
+
+impl Connection {
+    /// Performs the handshake on a new connection. Both peers send their greeting, and then a READY command with the type of their socket.
+    ///
+    /// # Arguments
+    /// `stream` - The new connection.
+    /// `socket_type` - The type of the socket that accepted the connection, such as `ROUTER`.
+    pub fn accept(stream: TcpStream, socket_type: &str) -> io::Result<Connection> {
+        let mut connection = Connection { stream };
+
+        let mut greeting = [0; 64];
+        greeting[0] = 0xff;
+        greeting[9] = 0x7f;
+        greeting[10] = 3;
+        greeting[12..16].copy_from_slice(b"NULL");
+        connection.stream.write_all(&greeting)?;
+
+        let mut peer = [0; 64];
+        connection.stream.read_exact(&mut peer)?;
+        if peer[0] != 0xff || peer[9] & 0x01 == 0 || peer[10] < 3 || &peer[12..17] != b"NULL\0" {
+            return Err(invalid(
+                "The Peer Does Not Speak ZMTP 3 With The NULL Mechanism.",
+            ));
+        }
+
+        let mut ready = vec![5];
+        ready.extend_from_slice(b"READY");
+        ready.push(11);
+        ready.extend_from_slice(b"Socket-Type");
+        ready.extend_from_slice(&(socket_type.len() as u32).to_be_bytes());
+        ready.extend_from_slice(socket_type.as_bytes());
+        connection.write_frame(COMMAND, &ready)?;
+
+        match connection.read_frame()? {
+            Some((flags, body)) if flags & COMMAND != 0 && body.starts_with(b"\x05READY") => {
+                Ok(connection)
+            }
+            _ => Err(invalid("The Peer Did Not Send A READY Command.")),
+        }
+    }
+
+    /// Creates another handle to the same connection, so that one thread can read from it while another writes to it.
+    pub fn try_clone(&self) -> io::Result<Connection> {
+        Ok(Connection {
+            stream: self.stream.try_clone()?,
+        })
+    }
+
+    /// Reads the next message, which is a list of frames. Commands are skipped. If the connection was closed, None is returned.
+    pub fn read_message(&mut self) -> io::Result<Option<Vec<Vec<u8>>>> {
+        let mut frames = vec![];
+        while let Some((flags, body)) = self.read_frame()? {
+            if flags & COMMAND != 0 {
+                continue;
+            }
+
+            frames.push(body);
+            if flags & MORE == 0 {
+                return Ok(Some(frames));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Writes a message, which is a list of frames.
+    ///
+    /// # Arguments
+    /// `frames` - The frames of the message.
+    pub fn write_message(&mut self, frames: &[Vec<u8>]) -> io::Result<()> {
+        for (index, frame) in frames.iter().enumerate() {
+            let flags = if index + 1 < frames.len() { MORE } else { 0 };
+            self.write_frame(flags, frame)?;
+        }
+
+        self.stream.flush()
+    }
+
+    /// Reads the next frame and returns its flags and its body. If the connection was closed, None is returned.
+    fn read_frame(&mut self) -> io::Result<Option<(u8, Vec<u8>)>> {
+        let mut flags = [0];
+        if self.stream.read(&mut flags)? == 0 {
+            return Ok(None);
+        }
+
+        let size = if flags[0] & LONG != 0 {
+            let mut size = [0; 8];
+            self.stream.read_exact(&mut size)?;
+            u64::from_be_bytes(size)
+        } else {
+            let mut size = [0];
+            self.stream.read_exact(&mut size)?;
+            size[0] as u64
+        };
+
+        // The body is read as it arrives, so that a peer can not make the kernel allocate more memory than it actually sends.
+        let mut body = vec![];
+        (&mut self.stream).take(size).read_to_end(&mut body)?;
+        if body.len() as u64 != size {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+
+        Ok(Some((flags[0], body)))
+    }
+
+    /// Writes a single frame.
+    ///
+    /// # Arguments
+    /// `flags` - The flags of the frame. The flag for the size is added if it is needed.
+    /// `body` - The body of the frame.
+    fn write_frame(&mut self, flags: u8, body: &[u8]) -> io::Result<()> {
+        let mut frame = vec![];
+        if body.len() > u8::MAX as usize {
+            frame.push(flags | LONG);
+            frame.extend_from_slice(&(body.len() as u64).to_be_bytes());
+        } else {
+            frame.push(flags);
+            frame.push(body.len() as u8);
+        }
+
+        frame.extend_from_slice(body);
+        self.stream.write_all(&frame)
+    }
+}
+
+/// Creates the error for a peer that broke the protocol.
+///
+/// # Arguments
+/// `message` - The description of the problem.
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
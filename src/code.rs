@@ -1,22 +1,44 @@
-//! The Code struct maintains the values and the current position of the values vector.
-//! In the future, it should maintain labels, constants, and other information about the code.
-//! This Code struct is called internally and should not be called from the outside.
+//! The Code struct maintains the values and the labels of a program. It never changes once it is constructed,
+//! so it can be shared through an Arc by many VMs, even on different threads, without parsing the program again.
+//! The Cursor struct maintains the current position of a single VM in the code.
+//!
+//! The structured pseudo-instructions, such as if and while, are lowered into relative jumps when the code is constructed.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{builder::VMBuilder, code::Code, errors::error::Error, lexer::Lexer};
+//! # use std::{sync::Arc, thread};
+//! # fn run() -> Result<(), Error> {
+//! let code = Arc::new(Code::new(Lexer::default().lex("@main push 1 end")?)?);
+//! let workers = (0..4).map(|_| {
+//!     let code = Arc::clone(&code);
+//!     thread::spawn(move || VMBuilder::default().build_shared(code).map(|mut vm| vm.run().is_success()).unwrap_or(false))
+//! });
+//! assert!(workers.collect::<Vec<_>>().into_iter().all(|worker| worker.join().unwrap()));
+//! # Ok(())
+//! # }
+//! ```
 
-use crate::utils::label::Label;
+use crate::utils::{aliases::Aliases, label::Label};
 use crate::{
     errors::{error::Error, error_kind::ErrorKind},
+    instructions::Opcode,
+    lexer::PURE_ANNOTATION,
+    lowering,
     tokens::{token::Token, token_kind::TokenKind},
-    values::value::Value,
+    values::{value::Value, value_kinds::ValueKind},
 };
 use std::{
-    collections::{HashMap, VecDeque},
-    rc::Rc,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    convert::TryFrom,
+    fmt,
+    sync::Arc,
 };
 
 #[derive(Debug)]
 pub struct Code {
-    value_pointer: usize,
-    values: VecDeque<Rc<Value>>,
+    entry: usize,
+    values: VecDeque<Arc<Value>>,
     labels: HashMap<String, Label>,
 }
 
@@ -27,47 +49,57 @@ impl Code {
     /// # Arguments
     /// `tokens` - The tokens from the lexer.
     pub fn new(tokens: VecDeque<Token>) -> Result<Code, Error> {
-        let mut labels = HashMap::new();
-        let mut values = VecDeque::new();
-        let iter = tokens.into_iter().enumerate();
-        let mut label_stack = vec![];
-        for (pos, token) in iter {
-            if let Token {
-                kind: TokenKind::Label(name, parameters),
-                pos: token_position,
-            } = &token
-            {
-                label_stack.push((pos, *token_position, name.to_owned(), parameters.to_vec()));
-            } else if let Token {
-                kind: TokenKind::End,
-                pos: token_position,
-            } = &token
-            {
-                match label_stack.pop() {
-                    Some((last_start, last_pos, last_name, last_parameters)) => {
-                        if labels
-                            .insert(last_name, Label::new(last_start, pos, last_parameters))
-                            .is_some()
-                        {
-                            return Err(Error::new(ErrorKind::DuplicateLabel, last_pos));
-                        }
-                    }
-                    None => return Err(Error::new(ErrorKind::EndWithoutLabel, *token_position)),
-                }
-            }
+        Code::with_aliases(tokens, &Aliases::default())
+    }
 
-            values.push_back(Rc::new(token.into()));
-        }
+    /// This constructs a new Code struct with the specified tokens, in which the given aliases stand for their instructions.
+    ///
+    /// # Arguments
+    /// `tokens` - The tokens from the lexer.
+    /// `aliases` - The aliases that were defined outside of the program.
+    pub fn with_aliases(tokens: VecDeque<Token>, aliases: &Aliases) -> Result<Code, Error> {
+        let (tokens, pure) = take_annotations(tokens)?;
+        let (values, docs) = into_values(lowering::lower(tokens, aliases)?);
+        Code::from_values(values, docs, pure)
+    }
 
-        if let Some((_, last_pos, _, _)) = label_stack.pop() {
-            Err(Error::new(ErrorKind::NoEndOfLabel, last_pos))
-        } else if let Some(Label {
+    /// This constructs a new Code struct with the specified tokens.
+    /// Internally, the tokens are converted to reference counted values.
+    /// Additionally, the Code struct does not check for a main label and instead starts at the first token.
+    ///
+    /// # Arguments
+    /// `tokens` - The tokens from the lexer.
+    pub fn repl(tokens: VecDeque<Token>) -> Result<Code, Error> {
+        let (tokens, pure) = take_annotations(tokens)?;
+        let (values, docs) = into_values(lowering::lower(tokens, &Aliases::default())?);
+        let labels = collect_labels(&values, docs, pure)?;
+        Ok(Code {
+            entry: 0,
+            values,
+            labels,
+        })
+    }
+
+    /// This constructs a new Code struct from values that were already lowered, such as the values that are loaded from bytecode.
+    /// The labels are found again from the values, so that values which were changed or corrupted are checked just like a program that was lexed.
+    ///
+    /// # Arguments
+    /// `values` - The lowered values of the program.
+    /// `docs` - The documentation of each label, by the name of the label.
+    /// `pure` - The names of the labels that are annotated as pure.
+    pub(crate) fn from_values(
+        values: VecDeque<Arc<Value>>,
+        docs: HashMap<String, String>,
+        pure: HashSet<String>,
+    ) -> Result<Code, Error> {
+        let labels = collect_labels(&values, docs, pure)?;
+        if let Some(Label {
             start_pos: value_pointer,
             ..
-        }) = labels.get(&"main".to_owned())
+        }) = labels.get("main")
         {
             Ok(Code {
-                value_pointer: value_pointer + 1,
+                entry: value_pointer + 1,
                 values,
                 labels,
             })
@@ -76,50 +108,408 @@ impl Code {
         }
     }
 
-    /// This constructs a new Code struct with the specified tokens.
-    /// Internally, the tokens are converted to reference counted values.
-    /// Additionally, the Code struct does not check for a main label and instead starts at the first token.
+    /// Gets the value pointer to the location of the label passed in. If the label name does not exist, an error is reported.
+    /// Additionally, it returns the position of the label.
     ///
     /// # Arguments
-    /// `tokens` - The tokens from the lexer.
-    pub fn repl(tokens: VecDeque<Token>) -> Result<Code, Error> {
-        let mut labels = HashMap::new();
-        let mut values = VecDeque::new();
-        let iter = tokens.into_iter().enumerate();
-        let mut label_stack = vec![];
-        for (pos, token) in iter {
-            if let Token {
-                kind: TokenKind::Label(name, parameters),
-                pos: token_position,
-            } = &token
-            {
-                label_stack.push((pos, *token_position, name.to_owned(), parameters.to_vec()));
-            } else if let Token {
-                kind: TokenKind::End,
-                pos: token_position,
-            } = &token
-            {
-                match label_stack.pop() {
-                    Some((last_start, last_pos, last_name, last_parameters)) => {
-                        if labels
-                            .insert(last_name, Label::new(last_start, pos, last_parameters))
-                            .is_some()
-                        {
-                            return Err(Error::new(ErrorKind::DuplicateLabel, last_pos));
+    /// `label_name` - The name of the label.
+    /// `pos` - The position where this was needed.
+    pub fn get_label_location(
+        &self,
+        label_name: &str,
+        pos: usize,
+    ) -> Result<(usize, usize, Vec<String>), Error> {
+        if let Some(Label {
+            start_pos: label_pos_start,
+            end_pos: label_pos_end,
+            parameters,
+            ..
+        }) = self.labels.get(label_name)
+        {
+            Ok((
+                *label_pos_start,
+                *label_pos_end,
+                parameters
+                    .iter()
+                    .map(|param| param.name.to_string())
+                    .collect::<Vec<_>>(),
+            ))
+        } else {
+            Err(Error::new(ErrorKind::UndefinedLabel, pos))
+        }
+    }
+
+    /// This function gets the start and end locations of the given label.
+    /// This function returns None if the label does not exist.
+    pub fn get_label_start_end(&self, label_name: &str) -> Option<(usize, usize)> {
+        self.labels
+            .get(label_name)
+            .map(|label| (label.start_pos, label.end_pos))
+    }
+
+    /// This function returns every label, by name.
+    pub fn get_labels(&self) -> &HashMap<String, Label> {
+        &self.labels
+    }
+
+    /// This function returns the position of the first value that is evaluated.
+    /// This is the value after the main label, or the first value in REPL mode.
+    pub fn get_entry(&self) -> usize {
+        self.entry
+    }
+
+    /// This function returns the value at the given index, if it exists.
+    ///
+    /// # Arguments
+    /// `index` - The index of the value.
+    pub fn get_value(&self, index: usize) -> Option<Arc<Value>> {
+        self.values.get(index).cloned()
+    }
+
+    /// This function returns all of the values, in the order that they appear in the program.
+    pub fn get_values(&self) -> &VecDeque<Arc<Value>> {
+        &self.values
+    }
+
+    /// This function returns every instruction with its index in the code and its position in the source, in the order that they appear in the program.
+    /// Instructions that are passed as arguments to other instructions are included.
+    pub fn get_instructions(&self) -> impl Iterator<Item = (usize, Opcode, usize)> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| match value.kind {
+                ValueKind::Instruction(opcode) => Some((index, opcode, value.pos)),
+                _ => None,
+            })
+    }
+
+    /// This function returns every label with its name, in the order that they appear in the program.
+    /// Each label maintains the range of values that it spans, its parameters, and its documentation.
+    pub fn get_labels_in_order(&self) -> Vec<(&str, &Label)> {
+        let mut labels = self
+            .labels
+            .iter()
+            .map(|(name, label)| (name.as_str(), label))
+            .collect::<Vec<_>>();
+        labels.sort_by_key(|(_, label)| label.start_pos);
+        labels
+    }
+
+    /// This function returns the index that the jump at the given index lands on.
+    /// The relative jumps are measured from the value after the instruction. An index equal to the number of values means that the jump finishes the program.
+    /// This function returns None if the value is not a jump, if its target is computed at runtime, or if its target lies outside of the code.
+    ///
+    /// # Arguments
+    /// `index` - The index of the jump instruction.
+    pub fn get_jump_target(&self, index: usize) -> Option<usize> {
+        let offset = match self.values.get(index + 1).map(|arg| &arg.kind) {
+            Some(ValueKind::Int(offset)) => *offset,
+            _ => return None,
+        };
+
+        let target = match self.values.get(index)?.kind {
+            ValueKind::Instruction(Opcode::Jump)
+            | ValueKind::Instruction(Opcode::JumpIfTrue)
+            | ValueKind::Instruction(Opcode::JumpIfFalse) => offset,
+            ValueKind::Instruction(Opcode::RelativeJump)
+            | ValueKind::Instruction(Opcode::RelativeJumpIfTrue)
+            | ValueKind::Instruction(Opcode::RelativeJumpIfFalse) => {
+                offset.checked_add(index as i64 + 1)?
+            }
+            _ => return None,
+        };
+
+        usize::try_from(target)
+            .ok()
+            .filter(|target| *target <= self.values.len())
+    }
+
+    /// This function returns the jump table of the program: the index of every jump whose target is known before it runs, mapped to the index that it lands on.
+    /// This includes the jumps that the structured instructions, such as if and while, are lowered into.
+    pub fn get_jump_table(&self) -> BTreeMap<usize, usize> {
+        (0..self.values.len())
+            .filter_map(|index| Some((index, self.get_jump_target(index)?)))
+            .collect()
+    }
+
+    /// This function writes a listing of the code, with one value per line: its index, its kind, and its position in the source.
+    /// The arguments of each instruction are indented below it, and the values of each label are indented below the label, so that the structure of the program can be seen.
+    /// Every jump whose target is a literal is annotated with the index that it lands on, since the jumps that the structured instructions are lowered into only hold offsets.
+    pub fn disassemble(&self) -> String {
+        let mut output = format!("{:>5}  {:<11}  {:>8}  Value\n", "Index", "Kind", "Position");
+        let mut label_depth = 0;
+
+        // The number of arguments that are still expected by each instruction whose arguments are being written, from the outermost.
+        let mut pending: Vec<usize> = vec![];
+        for (idx, value) in self.values.iter().enumerate() {
+            if let ValueKind::End = value.kind {
+                label_depth -= usize::from(label_depth > 0);
+            }
+
+            // The kind of an instruction is already clear from its name.
+            let kind = match value.kind {
+                ValueKind::Instruction(_) => "Instruction".to_owned(),
+                ref kind => kind.get_value_name(),
+            };
+            let (text, note) = match &value.kind {
+                ValueKind::Label(name, parameters) => {
+                    let mut text = format!("@{}", name);
+                    for parameter in parameters {
+                        text.push_str(&format!(" #{}", parameter.name));
+                    }
+
+                    let note = self
+                        .get_label_start_end(name)
+                        .map(|(start, end)| format!("Values {} To {}", start, end));
+                    (text, note)
+                }
+                ValueKind::End => ("end".to_owned(), None),
+                ValueKind::Instruction(opcode) => {
+                    let note = self.get_jump_target(idx).map(|target| {
+                        if target == self.values.len() {
+                            "Jumps To The End Of The Program".to_owned()
+                        } else {
+                            format!("Jumps To {}", target)
+                        }
+                    });
+                    (opcode.get_name().to_owned(), note)
+                }
+                ValueKind::Identifier(name) => (name.to_owned(), None),
+                kind => (format!("{:?}", kind), None),
+            };
+
+            let indent = "  ".repeat(label_depth + pending.len());
+            output.push_str(&format!(
+                "{:>5}  {:<11}  {:>8}  {}{}",
+                idx, kind, value.pos, indent, text
+            ));
+            if let Some(note) = note {
+                output.push_str(&format!("  ; {}", note));
+            }
+
+            output.push('\n');
+            let arguments = match value.kind {
+                ValueKind::Instruction(opcode) => self.get_argument_count(idx, opcode),
+                _ => 0,
+            };
+            match &value.kind {
+                ValueKind::Label(_, _) => {
+                    label_depth += 1;
+                    pending.clear();
+                }
+                ValueKind::End => pending.clear(),
+                _ if arguments > 0 => pending.push(arguments),
+                _ => {
+                    // The value is complete, so it fills an argument of the instruction above it, which may complete that instruction in turn.
+                    while let Some(remaining) = pending.last_mut() {
+                        *remaining -= 1;
+                        if *remaining > 0 {
+                            break;
                         }
+
+                        pending.pop();
                     }
-                    None => return Err(Error::new(ErrorKind::EndWithoutLabel, *token_position)),
                 }
             }
+        }
+
+        output
+    }
+
+    /// This function returns the number of arguments that the instruction at the given index takes.
+    /// The call instructions take the name of the label and one argument for each of its parameters, and the recurse instruction takes one argument for each parameter of the label that it is in.
+    ///
+    /// # Arguments
+    /// `index` - The index of the instruction.
+    /// `opcode` - The instruction.
+    fn get_argument_count(&self, index: usize, opcode: Opcode) -> usize {
+        let parameters = match opcode {
+            Opcode::Call | Opcode::CallIfTrue | Opcode::CallIfFalse => {
+                match self.values.get(index + 1).map(|value| &value.kind) {
+                    Some(ValueKind::Identifier(name)) => self.labels.get(name),
+                    _ => None,
+                }
+            }
+            Opcode::Recurse => self
+                .labels
+                .values()
+                .filter(|label| label.start_pos < index && index <= label.end_pos)
+                .max_by_key(|label| label.start_pos),
+            _ => None,
+        };
 
-            values.push_back(Rc::new(token.into()));
+        opcode.get_instruction().arity + parameters.map_or(0, |label| label.parameters.len())
+    }
+}
+
+/// This function takes the annotations out of the tokens, before they are lowered, and returns the names of the labels that are annotated as pure.
+/// An error is returned if an annotation is not directly followed by a label.
+///
+/// # Arguments
+/// `tokens` - The tokens from the lexer.
+fn take_annotations(tokens: VecDeque<Token>) -> Result<(VecDeque<Token>, HashSet<String>), Error> {
+    let mut pure = HashSet::new();
+    let mut kept = VecDeque::with_capacity(tokens.len());
+    let mut annotation: Option<usize> = None;
+    for token in tokens {
+        match &token.kind {
+            TokenKind::Annotation(_) if annotation.is_some() => {
+                return Err(Error::new(ErrorKind::MisplacedAnnotation, token.pos))
+            }
+            TokenKind::Annotation(name) if name == PURE_ANNOTATION => {
+                annotation = Some(token.pos);
+                continue;
+            }
+            TokenKind::Annotation(name) => {
+                return Err(Error::new(
+                    ErrorKind::UnknownAnnotation(name.to_owned()),
+                    token.pos,
+                ))
+            }
+            TokenKind::Label(name, ..) => {
+                if annotation.take().is_some() {
+                    pure.insert(name.to_owned());
+                }
+            }
+            _ => {
+                if let Some(pos) = annotation {
+                    return Err(Error::new(ErrorKind::MisplacedAnnotation, pos));
+                }
+            }
         }
 
-        Ok(Code {
-            value_pointer: 0,
-            values,
-            labels,
+        kept.push_back(token);
+    }
+
+    match annotation {
+        Some(pos) => Err(Error::new(ErrorKind::MisplacedAnnotation, pos)),
+        None => Ok((kept, pure)),
+    }
+}
+
+/// This function converts the lowered tokens into values, and takes the documentation of the labels out of them, by the name of the label.
+///
+/// # Arguments
+/// `tokens` - The lowered tokens.
+fn into_values(tokens: VecDeque<Token>) -> (VecDeque<Arc<Value>>, HashMap<String, String>) {
+    let mut docs = HashMap::new();
+    let values = tokens
+        .into_iter()
+        .map(|mut token| {
+            if let TokenKind::Label(name, _, doc) = &mut token.kind {
+                if let Some(doc) = doc.take() {
+                    docs.insert(name.to_owned(), doc);
+                }
+            }
+
+            Arc::new(token.into())
         })
+        .collect();
+    (values, docs)
+}
+
+/// This function finds every label in the values, along with the range of values that it spans.
+/// An error is returned if a label is not ended, if an end does not belong to a label, or if two labels have the same name.
+///
+/// # Arguments
+/// `values` - The values of the program.
+/// `docs` - The documentation of each label, by the name of the label.
+/// `pure` - The names of the labels that are annotated as pure.
+fn collect_labels(
+    values: &VecDeque<Arc<Value>>,
+    mut docs: HashMap<String, String>,
+    pure: HashSet<String>,
+) -> Result<HashMap<String, Label>, Error> {
+    let mut labels = HashMap::new();
+    let mut label_stack = vec![];
+    for (idx, value) in values.iter().enumerate() {
+        match &value.kind {
+            ValueKind::Label(name, parameters) => {
+                // The end of the label is filled in once its end is found.
+                let label = Label::new(
+                    idx,
+                    0,
+                    parameters.to_vec(),
+                    docs.remove(name),
+                    pure.contains(name),
+                );
+                label_stack.push((value.pos, name.to_owned(), label));
+            }
+            ValueKind::End => match label_stack.pop() {
+                Some((last_pos, last_name, mut label)) => {
+                    label.end_pos = idx;
+                    if labels.insert(last_name, label).is_some() {
+                        return Err(Error::new(ErrorKind::DuplicateLabel, last_pos));
+                    }
+                }
+                None => return Err(Error::new(ErrorKind::EndWithoutLabel, value.pos)),
+            },
+            _ => {}
+        }
+    }
+
+    match label_stack.pop() {
+        Some((last_pos, _, _)) => Err(Error::new(ErrorKind::NoEndOfLabel, last_pos)),
+        None => Ok(labels),
+    }
+}
+
+impl fmt::Display for Code {
+    /// Writes the code for people: every label, in the order that they appear, with its parameters, the values that it spans, and its documentation,
+    /// followed by every value with its index and its position in the source.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels = self.get_labels_in_order();
+        writeln!(f, "Labels ({}):", labels.len())?;
+        for (name, label) in labels {
+            let annotation = if label.pure { "[pure] " } else { "" };
+            write!(f, "    @{}{}", annotation, name)?;
+            for parameter in &label.parameters {
+                write!(f, " #{}", parameter.name)?;
+            }
+
+            writeln!(f, " (Values {} To {})", label.start_pos, label.end_pos)?;
+            for line in label.doc.iter().flat_map(|doc| doc.lines()) {
+                writeln!(f, "        -- {}", line)?;
+            }
+        }
+
+        writeln!(f, "Values ({}):", self.values.len())?;
+        for (idx, value) in self.values.iter().enumerate() {
+            // The parameters of a label are already listed above, so only its name is written.
+            match &value.kind {
+                ValueKind::Label(name, _) => write!(f, "    {}: Label '{}'", idx, name)?,
+                kind => write!(f, "    {}: {:?}", idx, kind)?,
+            }
+
+            writeln!(f, " At Position {}", value.pos)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The Cursor struct is the position of a single VM in a shared Code struct.
+#[derive(Debug)]
+pub struct Cursor {
+    code: Arc<Code>,
+    value_pointer: usize,
+}
+
+impl Cursor {
+    /// This constructs a new Cursor struct at the entry of the given code.
+    ///
+    /// # Arguments
+    /// `code` - The code to move through.
+    pub fn new(code: Arc<Code>) -> Cursor {
+        Cursor {
+            value_pointer: code.entry,
+            code,
+        }
+    }
+
+    /// This function returns the code that the cursor moves through.
+    pub fn get_code(&self) -> &Arc<Code> {
+        &self.code
     }
 
     /// This function updates the value_pointer to have the value of jump_location
@@ -129,13 +519,13 @@ impl Code {
     /// `jump_location` - The new value of value_pointer.
     /// `pos` - The position where this was needed.
     pub fn jump(&mut self, jump_location: i64, pos: usize) -> Option<Error> {
-        let upper_bound = self.values.len() as i64;
+        let upper_bound = self.code.values.len() as i64;
         if jump_location >= 0 && jump_location <= upper_bound {
             self.value_pointer = jump_location as usize;
             None
         } else {
             Some(Error::new(
-                ErrorKind::OutOfBounds(0, self.values.len() + 1),
+                ErrorKind::OutOfBounds(0, self.code.values.len() + 1),
                 pos,
             ))
         }
@@ -149,7 +539,7 @@ impl Code {
     /// `pos` - The position where this was needed.
     pub fn relative_jump(&mut self, jump_location: i64, pos: usize) -> Option<Error> {
         let lower_bound = -(self.value_pointer as i64);
-        let upper_bound = self.values.len() as i64;
+        let upper_bound = self.code.values.len() as i64;
         if jump_location >= lower_bound && jump_location <= upper_bound {
             if jump_location < 0 {
                 self.value_pointer -= (-jump_location) as usize;
@@ -160,35 +550,12 @@ impl Code {
             }
         } else {
             Some(Error::new(
-                ErrorKind::OutOfBounds(lower_bound as usize, self.values.len()),
+                ErrorKind::OutOfBounds(lower_bound as usize, self.code.values.len()),
                 pos,
             ))
         }
     }
 
-    /// Gets the value pointer to the location of the label passed in. If the label name does not exist, an error is reported.
-    /// Additionally, it returns the position of the label.
-    ///
-    /// # Arguments
-    /// `label_name` - The name of the label.
-    /// `pos` - The position where this was needed.
-    pub fn get_label_location(
-        &self,
-        label_name: &str,
-        pos: usize,
-    ) -> Result<(usize, usize, Vec<String>), Error> {
-        if let Some(Label {
-            start_pos: label_pos_start,
-            end_pos: label_pos_end,
-            parameters,
-        }) = self.labels.get(label_name)
-        {
-            Ok((*label_pos_start, *label_pos_end, parameters.iter().map(|param| param.name.to_string()).collect::<Vec<_>>()))
-        } else {
-            Err(Error::new(ErrorKind::UndefinedLabel, pos))
-        }
-    }
-
     /// Sets the value pointer to the location of the label passed in. If the label name does not exist, an error is reported.
     /// Additionally, it returns the position of the label.
     ///
@@ -200,25 +567,12 @@ impl Code {
         label_name: &str,
         pos: usize,
     ) -> Result<(usize, usize), Error> {
-        if let Some(Label {
-            start_pos: label_pos_start,
-            end_pos: label_pos_end,
-            ..
-        }) = self.labels.get_mut(label_name)
-        {
-            self.value_pointer = *label_pos_start + 1;
-            Ok((*label_pos_start, *label_pos_end))
-        } else {
-            Err(Error::new(ErrorKind::UndefinedLabel, pos))
-        }
-    }
-
-    /// This function gets the start and end locations of the given label.
-    /// This function returns None if the label does not exist.
-    pub fn get_label_start_end(&self, label_name: &str) -> Option<(usize, usize)> {
-        self.labels
-            .get(label_name)
-            .map(|label| (label.start_pos, label.end_pos))
+        let (label_pos_start, label_pos_end) = self
+            .code
+            .get_label_start_end(label_name)
+            .ok_or_else(|| Error::new(ErrorKind::UndefinedLabel, pos))?;
+        self.value_pointer = label_pos_start + 1;
+        Ok((label_pos_start, label_pos_end))
     }
 
     /// This function gets the current value of value pointer.
@@ -226,19 +580,24 @@ impl Code {
         self.value_pointer
     }
 
-    /// This function returns true if there are no more values in the Code struct.
+    /// This function returns the value that the value pointer points to, without advancing it.
+    pub fn peek(&self) -> Option<Arc<Value>> {
+        self.code.values.get(self.value_pointer).cloned()
+    }
+
+    /// This function returns true if there are no more values in the code.
     pub fn is_finished(&self) -> bool {
-        self.value_pointer >= self.values.len()
+        self.value_pointer >= self.code.values.len()
     }
 }
 
-impl Iterator for Code {
-    type Item = Rc<Value>;
+impl Iterator for Cursor {
+    type Item = Arc<Value>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.value_pointer += 1;
 
         // Cloning the object is cheap because it is reference counted.
-        self.values.get(self.value_pointer - 1).cloned()
+        self.code.values.get(self.value_pointer - 1).cloned()
     }
 }
@@ -0,0 +1,214 @@
+//! The CodeCache struct maintains the code of programs that were already lexed and loaded, keyed by their source.
+//! A server that runs the same scripts over and over can ask the cache for their code, and only the first request for each source pays for lexing and loading it.
+//! The cache can be shared by many threads. Sources are compiled outside of the lock, so a slow compilation never blocks the threads that hit the cache.
+//!
+//! The cache holds a limited number of programs. Once it is full, the program that was used least recently is evicted to make room for a new one.
+//! A cache made with `for_builder` lexes and loads programs with the case policy and the aliases of the builder, so that its code matches the VMs that the builder constructs.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{CodeCache, Error};
+//! # use std::sync::Arc;
+//! # fn run() -> Result<(), Error> {
+//! let cache = CodeCache::default();
+//! let first = cache.get_or_compile("@main push 1 end")?;
+//! let second = cache.get_or_compile("@main push 1 end")?;
+//! assert!(Arc::ptr_eq(&first, &second));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    builder::VMBuilder,
+    code::Code,
+    errors::error::Error,
+    lexer::Lexer,
+    utils::{aliases::Aliases, case::Case},
+    verifier,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock, RwLock,
+    },
+};
+
+/// The number of programs that a cache holds, unless another capacity is given.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+#[derive(Debug)]
+pub struct CodeCache {
+    entries: RwLock<HashMap<String, Entry>>,
+    capacity: usize,
+    clock: AtomicU64,
+    case: Case,
+    aliases: Aliases,
+}
+
+/// A single program in the cache, along with the last time that it was used, as a tick of the clock of the cache.
+#[derive(Debug)]
+struct Entry {
+    code: Arc<Code>,
+    last_used: AtomicU64,
+}
+
+impl Default for CodeCache {
+    fn default() -> CodeCache {
+        CodeCache::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl CodeCache {
+    /// This function constructs an empty cache that holds at most the given number of programs.
+    /// A cache with a capacity of zero compiles every source, but never keeps its code.
+    ///
+    /// # Arguments
+    /// `capacity` - The largest number of programs that the cache holds.
+    pub fn new(capacity: usize) -> CodeCache {
+        CodeCache {
+            entries: RwLock::new(HashMap::new()),
+            capacity,
+            clock: AtomicU64::new(0),
+            case: Case::default(),
+            aliases: Aliases::default(),
+        }
+    }
+
+    /// This function constructs an empty cache that lexes and loads programs with the case policy and the aliases of the builder.
+    /// Native functions are not known to the cache, so programs that call them must still be built with the builder itself.
+    ///
+    /// # Arguments
+    /// `builder` - The builder whose options are used.
+    /// `capacity` - The largest number of programs that the cache holds.
+    pub fn for_builder(builder: &VMBuilder, capacity: usize) -> CodeCache {
+        CodeCache {
+            case: builder.get_case(),
+            aliases: builder.get_aliases().clone(),
+            ..CodeCache::new(capacity)
+        }
+    }
+
+    /// This function returns the cache that is shared by the whole process, which is created the first time that it is needed.
+    pub fn global() -> &'static CodeCache {
+        static GLOBAL: OnceLock<CodeCache> = OnceLock::new();
+        GLOBAL.get_or_init(CodeCache::default)
+    }
+
+    /// This function returns the code of the source, lexing and loading it first if the cache does not have it yet.
+    /// Errors are not cached, so a source that does not compile is compiled again every time that it is requested.
+    /// If two threads compile the same source at once, the code of the first one to finish is kept, and both receive it.
+    ///
+    /// # Arguments
+    /// `source` - The contents of the program.
+    pub fn get_or_compile(&self, source: &str) -> Result<Arc<Code>, Error> {
+        if let Some(code) = self.get(source) {
+            return Ok(code);
+        }
+
+        let code = self.compile(source)?;
+        if self.capacity == 0 {
+            return Ok(code);
+        }
+
+        let mut entries = self.write();
+        if let Some(entry) = entries.get(source) {
+            entry.last_used.store(self.tick(), Ordering::Relaxed);
+            return Ok(Arc::clone(&entry.code));
+        }
+
+        if entries.len() >= self.capacity {
+            let least_recently_used = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used.load(Ordering::Relaxed))
+                .map(|(source, _)| source.clone());
+            if let Some(source) = least_recently_used {
+                entries.remove(&source);
+            }
+        }
+
+        entries.insert(
+            source.to_owned(),
+            Entry {
+                code: Arc::clone(&code),
+                last_used: AtomicU64::new(self.tick()),
+            },
+        );
+        Ok(code)
+    }
+
+    /// This function removes the code of the source from the cache, and returns whether the cache had it.
+    ///
+    /// # Arguments
+    /// `source` - The contents of the program.
+    pub fn remove(&self, source: &str) -> bool {
+        self.write().remove(source).is_some()
+    }
+
+    /// This function removes every program from the cache. The VMs that are running them keep their code.
+    pub fn clear(&self) {
+        self.write().clear();
+    }
+
+    /// This function returns the number of programs in the cache.
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// This function returns whether the cache has no programs.
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// This function returns the largest number of programs that the cache holds.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// This function looks up the code of the source, without compiling it, and marks it as used.
+    ///
+    /// # Arguments
+    /// `source` - The contents of the program.
+    fn get(&self, source: &str) -> Option<Arc<Code>> {
+        self.read().get(source).map(|entry| {
+            entry.last_used.store(self.tick(), Ordering::Relaxed);
+            Arc::clone(&entry.code)
+        })
+    }
+
+    /// This function lexes and loads the source with the options of the cache.
+    ///
+    /// # Arguments
+    /// `source` - The contents of the program.
+    fn compile(&self, source: &str) -> Result<Arc<Code>, Error> {
+        let tokens = Lexer::with_case(self.case).lex(source)?;
+        let code = if self.aliases.is_empty() {
+            Code::new(tokens)?
+        } else {
+            Code::with_aliases(tokens, &self.aliases)?
+        };
+
+        verifier::verify(&code)?;
+        Ok(Arc::new(code))
+    }
+
+    /// This function advances the clock of the cache, and returns the new time.
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// This function locks the entries for reading. The entries are always consistent, because they are only changed by single inserts and removals,
+    /// so a lock that was poisoned by a panic in another thread is still used.
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, Entry>> {
+        self.entries
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// This function locks the entries for writing, in the same way as the read function.
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, Entry>> {
+        self.entries
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,311 @@
+//! The Debugger struct wraps a VM and runs it one instruction at a time.
+//! It maintains the breakpoints, which may be placed on labels or on lines, and may optionally have a condition.
+//! A condition is any expression, such as `eq x 5`, which is evaluated in the current frame when the breakpoint is reached.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{builder::VMBuilder, debugger::{Breakpoint, Debugger}, errors::error::Error};
+//! # fn run() -> Result<(), Error> {
+//! let contents = "@main call greet end @greet push 1 end";
+//! let mut debugger = Debugger::new(contents, VMBuilder::default())?;
+//! debugger.add_breakpoint(Breakpoint::Label("greet".to_owned()), None);
+//! debugger.resume()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    builder::VMBuilder,
+    errors::error::Error,
+    lexer::Lexer,
+    utils::watch::WatchEvent,
+    values::value::Value,
+    vm::{StepResult, VM},
+};
+use std::sync::Arc;
+
+/// The Breakpoint enum describes where the debugger should pause.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Breakpoint {
+    /// Pauses before the first instruction of the label with the given name.
+    Label(String),
+
+    /// Pauses before any instruction on the given line. Lines are counted from 1.
+    Line(usize),
+}
+
+/// The DebugEvent enum describes why the debugger returned control to the caller.
+#[derive(Debug)]
+pub enum DebugEvent {
+    /// The debugger paused after a step.
+    Paused,
+
+    /// The debugger paused at the breakpoint with the given id.
+    Breakpoint(usize),
+
+    /// The debugger paused because the program ran the brk instruction.
+    Break,
+
+    /// The debugger paused because the program wrote to watched variables, which are described by the events.
+    Watchpoint(Vec<WatchEvent>),
+
+    /// The program finished, optionally producing the value of the last expression.
+    Finished(Option<Arc<Value>>),
+}
+
+pub struct Debugger {
+    vm: VM,
+    source: String,
+    breakpoints: Vec<(usize, Breakpoint, Option<String>)>,
+    next_id: usize,
+    has_started: bool,
+}
+
+impl Debugger {
+    /// Constructs a new debugger for the given program.
+    /// The program is lexed and loaded, but no instructions are run.
+    ///
+    /// # Arguments
+    /// `source` - The contents of the program.
+    /// `builder` - The builder used to construct the VM.
+    pub fn new(source: &str, builder: VMBuilder) -> Result<Debugger, Error> {
+        let tokens = builder.lexer().lex(source)?;
+        Ok(Debugger {
+            vm: builder.suspend_on_break().build(tokens)?,
+            source: source.to_owned(),
+            breakpoints: vec![],
+            next_id: 1,
+            has_started: false,
+        })
+    }
+
+    /// Adds a breakpoint with an optional condition and returns the id of the breakpoint.
+    ///
+    /// # Arguments
+    /// `breakpoint` - The location of the breakpoint.
+    /// `condition` - An expression that must be truthy for the debugger to pause.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint, condition: Option<String>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.breakpoints.push((id, breakpoint, condition));
+        id
+    }
+
+    /// Removes the breakpoint with the given id. This function returns whether the breakpoint existed.
+    ///
+    /// # Arguments
+    /// `id` - The id of the breakpoint.
+    pub fn remove_breakpoint(&mut self, id: usize) -> bool {
+        let len = self.breakpoints.len();
+        self.breakpoints
+            .retain(|(breakpoint_id, _, _)| breakpoint_id != &id);
+        len != self.breakpoints.len()
+    }
+
+    /// Removes all of the breakpoints.
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// This function returns the breakpoints, along with their ids and conditions.
+    pub fn get_breakpoints(&self) -> &[(usize, Breakpoint, Option<String>)] {
+        &self.breakpoints
+    }
+
+    /// Starts watching the variable with the given name, in every frame. The debugger pauses after every write to the variable.
+    /// This function returns false if the variable was already watched.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    pub fn add_watchpoint(&mut self, name: &str) -> bool {
+        self.vm.watch(name)
+    }
+
+    /// Stops watching the variable with the given name.
+    /// This function returns false if the variable was not watched.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    pub fn remove_watchpoint(&mut self, name: &str) -> bool {
+        self.vm.unwatch(name)
+    }
+
+    /// This function returns the names of the watched variables, sorted by name.
+    pub fn get_watchpoints(&self) -> Vec<&str> {
+        self.vm.get_watched()
+    }
+
+    /// Runs a single instruction. If the instruction is a call, the debugger pauses inside of the label.
+    pub fn step(&mut self) -> Result<DebugEvent, Error> {
+        self.has_started = true;
+        match self.vm.step()? {
+            StepResult::Running => Ok(DebugEvent::Paused),
+            StepResult::Suspended => {
+                let events = self.vm.take_watch_events();
+                if events.is_empty() {
+                    Ok(DebugEvent::Break)
+                } else {
+                    Ok(DebugEvent::Watchpoint(events))
+                }
+            }
+            StepResult::Finished(result) => Ok(DebugEvent::Finished(result)),
+        }
+    }
+
+    /// Runs a single instruction. If the instruction is a call, the whole call is run.
+    /// The debugger still pauses if a breakpoint is reached inside of the call.
+    pub fn step_over(&mut self) -> Result<DebugEvent, Error> {
+        let depth = self.vm.get_frames().len();
+        let event = self.step()?;
+        self.run_while(|vm| vm.get_frames().len() > depth, event, false)
+    }
+
+    /// Runs until the current label returns to its caller.
+    /// The debugger still pauses if a breakpoint is reached before the label returns.
+    pub fn step_out(&mut self) -> Result<DebugEvent, Error> {
+        let depth = self.vm.get_frames().len();
+        let event = self.step()?;
+        self.run_while(|vm| vm.get_frames().len() >= depth, event, false)
+    }
+
+    /// Runs the program until a breakpoint, a brk instruction, or a write to a watched variable is reached, or the program finishes.
+    pub fn resume(&mut self) -> Result<DebugEvent, Error> {
+        // The debugger is already paused at the current value, so its breakpoint should not be hit again.
+        self.run_while(|_| true, DebugEvent::Paused, self.has_started)
+    }
+
+    /// Runs the program until it finishes, ignoring all of the breakpoints, brk instructions, and watchpoints.
+    pub fn finish(&mut self) -> Result<DebugEvent, Error> {
+        loop {
+            if let DebugEvent::Finished(result) = self.step()? {
+                return Ok(DebugEvent::Finished(result));
+            }
+        }
+    }
+
+    /// Evaluates the given source in the current frame and returns the value of the last expression.
+    ///
+    /// # Arguments
+    /// `expression` - The expression to evaluate.
+    pub fn evaluate(&mut self, expression: &str) -> Result<Option<Arc<Value>>, Error> {
+        let tokens = Lexer::with_case(self.vm.case).lex(expression)?;
+        self.vm.evaluate(tokens)
+    }
+
+    /// This function returns the VM that is being debugged.
+    pub fn get_vm(&self) -> &VM {
+        &self.vm
+    }
+
+    /// This function returns the source of the program being debugged.
+    pub fn get_source(&self) -> &str {
+        &self.source
+    }
+
+    /// This function returns the line of the next value that will be evaluated.
+    /// If the program has finished, None is returned.
+    pub fn current_line(&self) -> Option<usize> {
+        self.vm
+            .peek_next()
+            .map(|value| line_of(&self.source, value.pos))
+    }
+
+    /// This function returns the line that each frame is at, in the same order as the frames of the VM.
+    /// For every frame except the current one, this is the line where it called the next frame.
+    pub fn get_frame_lines(&self) -> Vec<Option<usize>> {
+        let frames = self.vm.get_frames();
+        (0..frames.len())
+            .map(|idx| match frames.get(idx + 1) {
+                // The caller position is right after the call, so the value before it is still part of the call.
+                Some(callee) => self
+                    .vm
+                    .get_code()
+                    .get_value(callee.get_caller_position().saturating_sub(1))
+                    .map(|value| line_of(&self.source, value.pos)),
+                None => self.current_line(),
+            })
+            .collect()
+    }
+
+    /// Keeps stepping while the predicate holds, pausing at any breakpoint.
+    ///
+    /// # Arguments
+    /// `predicate` - The condition on the VM that must hold to keep stepping.
+    /// `event` - The event to return if the predicate does not hold at the start.
+    /// `skip_current` - Whether the breakpoints on the current value should be skipped.
+    fn run_while<F: Fn(&VM) -> bool>(
+        &mut self,
+        predicate: F,
+        mut event: DebugEvent,
+        mut skip_current: bool,
+    ) -> Result<DebugEvent, Error> {
+        loop {
+            if let DebugEvent::Finished(_) | DebugEvent::Break | DebugEvent::Watchpoint(_) = event {
+                return Ok(event);
+            }
+
+            if !predicate(&self.vm) {
+                return Ok(event);
+            }
+
+            if !skip_current {
+                if let Some(id) = self.hit_breakpoint()? {
+                    return Ok(DebugEvent::Breakpoint(id));
+                }
+            }
+
+            skip_current = false;
+            event = self.step()?;
+        }
+    }
+
+    /// This function returns the id of the breakpoint that matches the next value, if its condition holds.
+    fn hit_breakpoint(&mut self) -> Result<Option<usize>, Error> {
+        let next = match self.vm.peek_next() {
+            Some(next) => next,
+            None => return Ok(None),
+        };
+
+        let current_pos = self.vm.get_current_pos();
+        let line = line_of(&self.source, next.pos);
+        let mut hit = None;
+        for (id, breakpoint, condition) in &self.breakpoints {
+            let matches = match breakpoint {
+                Breakpoint::Label(name) => self
+                    .vm
+                    .get_code()
+                    .get_label_start_end(name)
+                    .is_some_and(|(start, _)| start + 1 == current_pos),
+                Breakpoint::Line(breakpoint_line) => *breakpoint_line == line,
+            };
+
+            if matches {
+                hit = Some((*id, condition.clone()));
+                break;
+            }
+        }
+
+        match hit {
+            Some((id, Some(condition))) => {
+                let result = self.evaluate(&condition)?;
+                Ok(result.filter(|value| value.is_truthy()).map(|_| id))
+            }
+            Some((id, None)) => Ok(Some(id)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// This function gets the line of the given position in the source. Lines are counted from 1.
+///
+/// # Arguments
+/// `source` - The source of the program.
+/// `pos` - The position, which is the 1-based index of the character.
+pub fn line_of(source: &str, pos: usize) -> usize {
+    source
+        .chars()
+        .take(pos.saturating_sub(1))
+        .filter(|ch| ch == &'\n')
+        .count()
+        + 1
+}
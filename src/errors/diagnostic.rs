@@ -0,0 +1,14 @@
+//! The Diagnostic struct describes an error by its line and column, instead of its position in the input.
+//! This allows tools, such as editors, to report the errors of a program without formatting them.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// The line where the error occurred. Lines are counted from 1.
+    pub line: usize,
+
+    /// The column where the error occurred. Columns are counted from 1.
+    pub column: usize,
+
+    /// The message of the error.
+    pub message: String,
+}
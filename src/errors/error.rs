@@ -1,10 +1,29 @@
 //! The Error struct maintains the errors that occur during execution.
 
-use super::error_kind::ErrorKind;
+use super::{diagnostic::Diagnostic, error_kind::ErrorKind};
+use crate::utils::{frames::FrameEntry, history::HistoryEntry};
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Error {
     kind: ErrorKind,
     position: Option<usize>,
+
+    /// The rest of the information about the error, which most errors do not have.
+    /// It is boxed, so that errors stay small as they are returned through the VM.
+    context: Option<Box<Context>>,
+}
+
+/// The Context struct maintains the information about an error that is only present for some errors.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Context {
+    /// A second position that the error refers to, such as the earlier definition of a shadowed variable, along with a note that describes it.
+    related: Option<(usize, String)>,
+
+    /// The instructions that were executed before a runtime error occurred, from the oldest to the most recent.
+    history: Vec<HistoryEntry>,
+
+    /// The frames that were on the call stack when a runtime error occurred, from the innermost frame to the main frame.
+    frames: Vec<FrameEntry>,
 }
 
 impl Error {
@@ -17,6 +36,7 @@ impl Error {
         Error {
             kind,
             position: Some(position),
+            context: None,
         }
     }
 
@@ -28,6 +48,87 @@ impl Error {
         Error {
             kind,
             position: None,
+            context: None,
+        }
+    }
+
+    /// Adds a second position that the error refers to. It is shown below the error, along with the note, when the error is prettified.
+    ///
+    /// # Arguments
+    /// `related` - The position that the error refers to.
+    /// `note` - The description of the position, such as "Previously Defined Here.".
+    pub fn with_related(mut self, related: usize, note: &str) -> Error {
+        self.context.get_or_insert_with(Box::default).related = Some((related, note.to_owned()));
+        self
+    }
+
+    /// Attaches the instructions that were executed before the error occurred. They are listed below the error when the error is prettified.
+    ///
+    /// # Arguments
+    /// `history` - The instructions, from the oldest to the most recent.
+    pub fn with_history(mut self, history: Vec<HistoryEntry>) -> Error {
+        self.context.get_or_insert_with(Box::default).history = history;
+        self
+    }
+
+    /// Attaches the frames that were on the call stack when the error occurred. They are listed below the error when the error is prettified.
+    ///
+    /// # Arguments
+    /// `frames` - The frames, from the innermost frame to the main frame.
+    pub fn with_frames(mut self, frames: Vec<FrameEntry>) -> Error {
+        self.context.get_or_insert_with(Box::default).frames = frames;
+        self
+    }
+
+    /// This function returns the frames that were on the call stack when the error occurred, from the innermost frame to the main frame.
+    pub fn get_frames(&self) -> &[FrameEntry] {
+        self.context
+            .as_ref()
+            .map_or(&[], |context| context.frames.as_slice())
+    }
+
+    /// This function returns the instructions that were executed before the error occurred, from the oldest to the most recent.
+    pub fn get_history(&self) -> &[HistoryEntry] {
+        self.context
+            .as_ref()
+            .map_or(&[], |context| context.history.as_slice())
+    }
+
+    /// This function returns the second position that the error refers to, if there is one.
+    pub fn get_related_position(&self) -> Option<usize> {
+        self.context
+            .as_ref()
+            .and_then(|context| context.related.as_ref())
+            .map(|(related, _)| *related)
+    }
+
+    /// This function returns the kind of the error.
+    pub fn get_kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// This function returns the position where the error occurred, if there is one.
+    pub fn get_position(&self) -> Option<usize> {
+        self.position
+    }
+
+    /// This function converts the error into a diagnostic, which describes the error by its line and column.
+    /// Unlike prettify, this function does not consume the error.
+    /// If the error has no position, the diagnostic points to the start of the input.
+    ///
+    /// # Arguments
+    /// `input` - The input for the program. This is not maintained with every error because the input might be different.
+    pub fn diagnose(&self, input: &str) -> Diagnostic {
+        let (line, column) = if self.position.is_some() {
+            self.get_line_column_info(input)
+        } else {
+            (1, 1)
+        };
+
+        Diagnostic {
+            line,
+            column: column.max(1),
+            message: self.kind.clone().into(),
         }
     }
 
@@ -37,6 +138,19 @@ impl Error {
     /// # Arguments
     /// `input` - The input for the program. This is not maintained with every error because the input might be different.
     pub fn prettify(self, input: &str) -> String {
+        let frames = write_frames(self.get_frames(), input);
+        let history = write_history(self.get_history(), input);
+        let mut pretty = self.prettify_error(input);
+        pretty.push_str(&frames);
+        pretty.push_str(&history);
+        pretty
+    }
+
+    /// This function generates the pretty version of the error, without the frames and the history.
+    ///
+    /// # Arguments
+    /// `input` - The input for the program.
+    fn prettify_error(self, input: &str) -> String {
         if self.position.is_some() {
             // Get the line and column number of where the error occurred.
             let (line_number, column_number) = self.get_line_column_info(input);
@@ -48,16 +162,21 @@ impl Error {
             // Convert the kind into an error message.
             let error_message: String = self.kind.into();
             if let Some(line) = option_line {
-                let len = line_number.to_string().len();
-                format!(
-                    "{} |\n{} | {}\n{} | {}^-- {}\n",
-                    " ".repeat(len),
-                    line_number,
-                    line,
-                    " ".repeat(len),
-                    " ".repeat(column_number - 1),
-                    error_message,
-                )
+                let mut pretty = excerpt(line_number, column_number, line, &error_message);
+
+                // The related position is shown below the error, so that both places can be seen at once.
+                let related = self
+                    .context
+                    .as_ref()
+                    .and_then(|context| context.related.as_ref());
+                if let Some((related, note)) = related {
+                    let (line_number, column_number) = get_line_column_info(input, *related);
+                    if let Some(line) = input.split_terminator('\n').nth(line_number - 1) {
+                        pretty.push_str(&excerpt(line_number, column_number, line, note));
+                    }
+                }
+
+                pretty
             } else {
                 format!(
                     "An Error Occurred On Line {} And Column {}.\n{}",
@@ -73,20 +192,106 @@ impl Error {
 
     /// This function gets the line and column number of where the error occurred with respect to the input.
     fn get_line_column_info(&self, input: &str) -> (usize, usize) {
-        let (mut line_number, mut column_number) = (1, 0);
+        get_line_column_info(input, self.position.unwrap_or(0))
+    }
+}
 
-        // Go through the characters and find the index that matches the position given in the error struct.
-        input.chars().enumerate().find(|(idx, ch)| {
-            if ch == &'\n' {
-                line_number += 1;
-                column_number = 0;
-            } else {
-                column_number += 1;
-            }
+/// This function gets the line and column number of the given position with respect to the input.
+///
+/// # Arguments
+/// `input` - The input for the program.
+/// `position` - The position to find.
+fn get_line_column_info(input: &str, position: usize) -> (usize, usize) {
+    let (mut line_number, mut column_number) = (1, 0);
+
+    // Go through the characters and find the index that matches the position.
+    input.chars().enumerate().find(|(idx, ch)| {
+        if ch == &'\n' {
+            line_number += 1;
+            column_number = 0;
+        } else {
+            column_number += 1;
+        }
+
+        idx == &position.saturating_sub(1)
+    });
+
+    (line_number, column_number)
+}
+
+/// This function writes the instructions that were executed before an error, with the line and column of each one.
+/// If there are no instructions, nothing is written.
+///
+/// # Arguments
+/// `history` - The instructions, from the oldest to the most recent.
+/// `input` - The input for the program.
+fn write_history(history: &[HistoryEntry], input: &str) -> String {
+    if history.is_empty() {
+        return String::new();
+    }
+
+    let mut written = String::from(
+        "Recently Executed Instructions, Oldest First:
+",
+    );
+    for entry in history {
+        let (line_number, column_number) = get_line_column_info(input, entry.pos);
+        written.push_str(&format!(
+            "    {}:{} {} (Stack Depth {})\n",
+            line_number,
+            column_number,
+            entry.opcode.get_instruction().name,
+            entry.stack_depth
+        ));
+    }
 
-            idx == &(self.position.unwrap() - 1)
-        });
+    written
+}
+
+/// This function writes the frames that were on the call stack when an error occurred, with the line and column where each one was called.
+/// If there is only the main frame, nothing is written, because the position of the error already says where it is.
+///
+/// # Arguments
+/// `frames` - The frames, from the innermost frame to the main frame.
+/// `input` - The input for the program.
+fn write_frames(frames: &[FrameEntry], input: &str) -> String {
+    if frames.len() < 2 {
+        return String::new();
+    }
 
-        (line_number, column_number)
+    let mut written = String::from("Frames, Innermost First:\n");
+    for frame in frames {
+        match frame.call_pos {
+            Some(pos) => {
+                let (line_number, column_number) = get_line_column_info(input, pos);
+                written.push_str(&format!(
+                    "    @{} Called At {}:{}\n",
+                    frame.name, line_number, column_number
+                ));
+            }
+            None => written.push_str(&format!("    @{}\n", frame.name)),
+        }
     }
+
+    written
+}
+
+/// This function writes a line of the input with an arrow that points to the given column, followed by the message.
+///
+/// # Arguments
+/// `line_number` - The number of the line.
+/// `column_number` - The column that the arrow points to.
+/// `line` - The text of the line.
+/// `message` - The message written after the arrow.
+fn excerpt(line_number: usize, column_number: usize, line: &str, message: &str) -> String {
+    let len = line_number.to_string().len();
+    format!(
+        "{} |\n{} | {}\n{} | {}^-- {}\n",
+        " ".repeat(len),
+        line_number,
+        line,
+        " ".repeat(len),
+        " ".repeat(column_number.saturating_sub(1)),
+        message,
+    )
 }
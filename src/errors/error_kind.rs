@@ -2,50 +2,155 @@
 //! This allows for uniformity across the various errors because the error messages are the same.
 //! This also increases readibility within the code, because the ErrorKind's are more descriptive.
 
+use crate::bytecode;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ErrorKind {
     UnrecognizedArgument(String),
+    ExpectedArgumentValue(String),
+    InvalidArgumentValue(String, String),
 
     UnknownCharacter,
     InvalidNumberFormat,
     InvalidLabelName,
+    UnknownAnnotation(String),
+    MisplacedAnnotation,
     InvalidParameterName,
     UnterminatedString,
+    InvalidChar,
+    InvalidJson,
 
     DuplicateLabel,
     NoMainLabel,
     EndWithoutLabel,
 
     EmptyStack,
+    EmptyArray,
+    MissingKey(String),
+    InvalidCodePoint(i64),
+    UnbalancedStack(i64),
     ExpectedArgs(usize),
     ValueMismatch(String, String),
     UnsupportedOperation(String, String),
     NoEndOfLabel,
     DivisionByZero,
+    IntegerOverflow,
+    InvalidShift(i64),
+    StringTooLong,
     OutOfBounds(usize, usize),
-    UndefinedVariable,
+    UndefinedVariable(String, Vec<String>, Vec<String>),
+    ShadowedVariable(String),
+    IdentifierCaseMismatch(String, String),
     UndefinedLabel,
+    EndBlockWithoutBegin,
+    EndMatchWithoutMatch,
+    ElseWithoutIf,
+    EndIfWithoutIf,
+    DuplicateElse,
+    UnterminatedIf,
+    DoWithoutWhile,
+    EndWhileWithoutWhile,
+    MissingDo,
+    UnterminatedWhile,
+    BreakOutsideLoop,
+    ContinueOutsideLoop,
+    UnterminatedMatch,
+    ExpectedAlias,
+    AliasIsInstruction(String),
+    DuplicateAlias(String),
+    TooManyParameters(usize),
+    WrongArgumentCount(usize, usize),
+    NoActiveFrame,
+    NestingTooDeep(usize),
+    FailedWrite(String),
+    FailedRead(String),
+    InvalidBytecode(String),
+    UnsupportedBytecodeVersion(u16),
+    UnknownLogLevel(String),
+    CapabilityDenied(String, String),
+    PathOutsideRoot(String),
+    FailedFileOperation(String, String),
+    CommandNotAllowed(String),
+    FailedCommand(String, String),
+    FailedRequest(String, String),
+    InvalidCsv(String),
+    InvalidEncoding(String),
+    InvalidRegex(String),
+    InvalidDate(String),
+    InvalidInput(String, String),
+    InvalidFormatSpecifier(String),
+    FeatureDisabled(String, String),
+    InvalidTrace,
+    InvalidSession,
+    TraceExhausted(String),
+    TraceMismatch(String, String),
+    ReplayedError(String),
+
+    OutOfFuel(u64),
+    Timeout(Duration),
+    CallDepthExceeded(usize),
+    StackLimitExceeded(usize),
+    StringLimitExceeded(usize),
+    ArrayLimitExceeded(usize),
+    ValueDepthExceeded(usize),
+    StackNotEmpty(usize),
+    AssertionFailed,
+    ValuesNotEqual(Vec<String>),
+    NativeConflict(String),
 }
 
 /// Converts the ErrorKind into a String.
 /// This is used in the prettify method to produce the error messages needed.
-impl Into<String> for ErrorKind {
-    fn into(self) -> String {
-        match self {
+impl From<ErrorKind> for String {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
             ErrorKind::UnrecognizedArgument(arg) => {
                 return format!("The Argument '{}' Is Not A Valid Argument.", arg)
             }
+            ErrorKind::ExpectedArgumentValue(arg) => {
+                return format!("The Argument '{}' Expects A Value After It.", arg)
+            }
+            ErrorKind::InvalidArgumentValue(arg, value) => {
+                return format!(
+                    "The Value '{}' Is Not A Valid Value For The Argument '{}'.",
+                    value, arg
+                )
+            }
 
             ErrorKind::UnknownCharacter => "Unknown Character Found Here.",
             ErrorKind::InvalidNumberFormat => "Invalid Number Format.",
             ErrorKind::InvalidLabelName => "Invalid Label Name.",
+            ErrorKind::UnknownAnnotation(name) => {
+                return format!(
+                    "Unknown Annotation '{}'. The Only Annotation Is 'pure'.",
+                    name
+                )
+            }
+            ErrorKind::MisplacedAnnotation => "An Annotation Must Be Followed By A Label.",
             ErrorKind::InvalidParameterName => "Invalid Parameter Name.",
             ErrorKind::UnterminatedString => "Expected The End Of This String.",
+            ErrorKind::InvalidChar => "A Char Must Be A Single Character Between Backticks.",
+            ErrorKind::InvalidJson => "Invalid JSON Found Here.",
 
             ErrorKind::DuplicateLabel => "Another Label With This Name Was Defined Already.",
             ErrorKind::NoMainLabel => "A Main Label Could Not Be Found.",
             ErrorKind::EndWithoutLabel => "Found An End That Is Not Associated With A Label.",
 
             ErrorKind::EmptyStack => "Tried To Pop From An Empty Stack.",
+            ErrorKind::EmptyArray => "Tried To Pop From An Empty Array.",
+            ErrorKind::MissingKey(key) => {
+                return format!("The Key '{}' Is Not In The Map.", key)
+            }
+            ErrorKind::InvalidCodePoint(code_point) => {
+                return format!("{} Is Not A Valid Unicode Code Point.", code_point)
+            }
+            ErrorKind::UnbalancedStack(difference) => {
+                return format!(
+                    "The Paths Into This Value Leave Different Numbers Of Values On The Stack, Differing By {}.",
+                    difference
+                )
+            }
             ErrorKind::ExpectedArgs(arg_amt) => {
                 return format!(
                     "Expected {} More {}.",
@@ -71,14 +176,265 @@ impl Into<String> for ErrorKind {
             }
             ErrorKind::NoEndOfLabel => "No 'end' Could Be Found To This Label.",
             ErrorKind::DivisionByZero => "Tried To Divide By 0.",
+            ErrorKind::IntegerOverflow => "The Result Of This Operation Does Not Fit In An Int.",
+            ErrorKind::InvalidShift(amount) => {
+                return format!(
+                    "Tried To Shift By {}. The Amount Has To Be Between 0 And 63 Inclusive.",
+                    amount
+                )
+            }
+            ErrorKind::StringTooLong => "The Resulting String Is Too Long.",
             ErrorKind::OutOfBounds(beginning, end) => {
                 return format!(
                     "An Invalid Index Was Given. The Index Has To Be Between {} And {} Exclusive.",
                     beginning, end
                 )
             }
-            ErrorKind::UndefinedVariable => "Tried To Use A Variable That Has Not Been Defined.",
+            ErrorKind::UndefinedVariable(name, scopes, suggestions) => {
+                let mut message = format!(
+                    "Tried To Use The Variable '{}', Which Has Not Been Defined. Searched {}.",
+                    name,
+                    scopes.join(", Then ")
+                );
+                if !suggestions.is_empty() {
+                    let suggestions = suggestions
+                        .iter()
+                        .map(|suggestion| format!("'{}'", suggestion))
+                        .collect::<Vec<_>>();
+                    message.push_str(&format!(" Did You Mean {}?", suggestions.join(" Or ")));
+                }
+
+                return message;
+            }
+            ErrorKind::ShadowedVariable(name) => {
+                return format!(
+                    "The Variable '{}' Shadows A Variable With The Same Name In An Outer Scope.",
+                    name
+                )
+            }
+            ErrorKind::IdentifierCaseMismatch(name, other) => {
+                return format!(
+                    "The Identifier '{}' Differs From The Identifier '{}' Only By Case.",
+                    name, other
+                )
+            }
             ErrorKind::UndefinedLabel => "Tried To Use A Label That Has Not Been Defined.",
+            ErrorKind::EndBlockWithoutBegin => {
+                "Found An endblock Instruction Without A Matching begin Instruction."
+            }
+            ErrorKind::EndMatchWithoutMatch => {
+                "Found An endmatch Instruction Without A Matching match Instruction."
+            }
+            ErrorKind::ElseWithoutIf => {
+                "Found An else Instruction Without A Matching if Instruction In The Same Label."
+            }
+            ErrorKind::EndIfWithoutIf => {
+                "Found An endif Instruction Without A Matching if Instruction In The Same Label."
+            }
+            ErrorKind::DuplicateElse => "This if Instruction Already Has An else Instruction.",
+            ErrorKind::UnterminatedIf => {
+                "Expected An endif Instruction For This if Instruction In The Same Label."
+            }
+            ErrorKind::DoWithoutWhile => {
+                "Found A do Instruction That Does Not Follow The Condition Of A while Instruction In The Same Label."
+            }
+            ErrorKind::EndWhileWithoutWhile => {
+                "Found An endwhile Instruction Without A Matching while Instruction In The Same Label."
+            }
+            ErrorKind::MissingDo => {
+                "Expected A do Instruction After The Condition Of This while Instruction."
+            }
+            ErrorKind::UnterminatedWhile => {
+                "Expected An endwhile Instruction For This while Instruction In The Same Label."
+            }
+            ErrorKind::BreakOutsideLoop => {
+                "Found A break Instruction Outside Of The Body Of A while Instruction In The Same Label."
+            }
+            ErrorKind::ContinueOutsideLoop => {
+                "Found A continue Instruction Outside Of The Body Of A while Instruction In The Same Label."
+            }
+            ErrorKind::UnterminatedMatch => {
+                "Expected An endmatch Instruction After The Patterns Of This match Instruction."
+            }
+            ErrorKind::ExpectedAlias => {
+                "Expected An Alias And The Instruction That It Stands For, Such As 'alias say printn'."
+            }
+            ErrorKind::AliasIsInstruction(name) => {
+                return format!(
+                    "The Alias '{}' Can Not Be Defined, Because It Is The Name Of An Instruction.",
+                    name
+                )
+            }
+            ErrorKind::DuplicateAlias(name) => {
+                return format!("The Alias '{}' Was Defined Already.", name)
+            }
+            ErrorKind::TooManyParameters(max_parameters) => {
+                return format!(
+                    "The Label Defines Too Many Parameters. Expected At Most {}.",
+                    max_parameters
+                )
+            }
+            ErrorKind::WrongArgumentCount(expected, actual) => {
+                return format!(
+                    "The Label Expects {} {}, But Was Given {}.",
+                    expected,
+                    if expected == 1 {
+                        "Argument"
+                    } else {
+                        "Arguments"
+                    },
+                    actual
+                )
+            }
+            ErrorKind::NoActiveFrame => {
+                "There Is No Frame To Run This In Because The Program Has Finished."
+            }
+            ErrorKind::NestingTooDeep(max_nesting) => {
+                return format!(
+                    "Expressions May Only Be Nested {} Levels Deep.",
+                    max_nesting
+                )
+            }
+            ErrorKind::UnknownLogLevel(level) => {
+                return format!(
+                    "'{}' Is Not A Log Level. Expected debug, info, warn, Or error.",
+                    level
+                )
+            }
+            ErrorKind::FailedWrite(reason) => {
+                return format!("The Output Could Not Be Written: {}.", reason)
+            }
+            ErrorKind::FailedRead(reason) => {
+                return format!("The Input Could Not Be Read: {}.", reason)
+            }
+            ErrorKind::InvalidBytecode(reason) => {
+                return format!("The Bytecode Is Not Valid: {}.", reason)
+            }
+            ErrorKind::UnsupportedBytecodeVersion(version) => {
+                return format!(
+                    "The Bytecode Was Compiled For Version {} Of The Format, But Only Version {} Is Supported. The Program Must Be Compiled Again.",
+                    version,
+                    bytecode::VERSION
+                )
+            }
+            ErrorKind::CapabilityDenied(instruction, capability) => {
+                return format!(
+                    "The '{}' Instruction Requires The {} Capability, Which Was Not Granted.",
+                    instruction, capability
+                )
+            }
+            ErrorKind::PathOutsideRoot(path) => {
+                return format!("The Path '{}' Is Outside Of The Root Directory.", path)
+            }
+            ErrorKind::FailedFileOperation(path, reason) => {
+                return format!("The File '{}' Could Not Be Accessed: {}.", path, reason)
+            }
+            ErrorKind::CommandNotAllowed(command) => {
+                return format!("The Command '{}' Is Not Allowed To Run.", command)
+            }
+            ErrorKind::FailedCommand(command, reason) => {
+                return format!("The Command '{}' Could Not Be Run: {}.", command, reason)
+            }
+            ErrorKind::FailedRequest(url, reason) => {
+                return format!("The Request To '{}' Failed: {}.", url, reason)
+            }
+            ErrorKind::InvalidCsv(reason) => {
+                return format!("The CSV Could Not Be Parsed: {}.", reason)
+            }
+            ErrorKind::InvalidEncoding(encoding) => {
+                return format!("The String Is Not Valid {}.", encoding)
+            }
+            ErrorKind::InvalidRegex(reason) => {
+                return format!("The Regular Expression Could Not Be Compiled: {}.", reason)
+            }
+            ErrorKind::InvalidInput(input, expected) => {
+                return format!("The Input '{}' Is Not A Valid {}.", input, expected)
+            }
+            ErrorKind::InvalidDate(reason) => {
+                return format!("The Date Is Not Valid: {}.", reason)
+            }
+            ErrorKind::InvalidFormatSpecifier(spec) => {
+                return format!(
+                    "'{}' Is Not A Valid Format Specifier. Expected Flags, A Width, A Precision, And One Of d, x, X, o, b, f, e, Or E, Such As %08.3f.",
+                    spec
+                )
+            }
+            ErrorKind::FeatureDisabled(instruction, feature) => {
+                return format!(
+                    "The '{}' Instruction Requires The '{}' Feature, Which Was Not Enabled When The VM Was Built.",
+                    instruction, feature
+                )
+            }
+            ErrorKind::InvalidTrace => "The Trace Could Not Be Read.",
+            ErrorKind::InvalidSession => "The Session Could Not Be Read.",
+            ErrorKind::TraceExhausted(instruction) => {
+                return format!(
+                    "The Trace Ended Before The '{}' Instruction Was Run.",
+                    instruction
+                )
+            }
+            ErrorKind::TraceMismatch(expected, actual) => {
+                return format!(
+                    "The Trace Expected The '{}' Instruction, But The '{}' Instruction Was Run.",
+                    expected, actual
+                )
+            }
+            ErrorKind::ReplayedError(message) => return message,
+
+            ErrorKind::OutOfFuel(fuel) => {
+                return format!("The Program Ran Out Of Fuel After {} Instructions.", fuel)
+            }
+            ErrorKind::Timeout(timeout) => {
+                return format!("The Program Did Not Finish Within {:?}.", timeout)
+            }
+            ErrorKind::CallDepthExceeded(max_depth) => {
+                return format!("The Maximum Call Depth Of {} Was Exceeded.", max_depth)
+            }
+            ErrorKind::StackLimitExceeded(max_stack) => {
+                return format!("The Maximum Stack Size Of {} Was Exceeded.", max_stack)
+            }
+            ErrorKind::StringLimitExceeded(max_string_len) => {
+                return format!(
+                    "The Maximum String Length Of {} Bytes Was Exceeded.",
+                    max_string_len
+                )
+            }
+            ErrorKind::ArrayLimitExceeded(max_array_len) => {
+                return format!(
+                    "The Maximum Array Length Of {} Elements Was Exceeded.",
+                    max_array_len
+                )
+            }
+            ErrorKind::ValueDepthExceeded(max_value_depth) => {
+                return format!(
+                    "Values May Only Be Nested {} Levels Deep.",
+                    max_value_depth
+                )
+            }
+            ErrorKind::StackNotEmpty(len) => {
+                return format!(
+                    "The Program Finished With {} {} Left On The Stack.",
+                    len,
+                    if len == 1 { "Value" } else { "Values" }
+                )
+            }
+            ErrorKind::AssertionFailed => "The Assertion Failed.",
+            ErrorKind::ValuesNotEqual(differences) => {
+                return format!(
+                    "The Values Are Not Equal.\n{}",
+                    differences
+                        .iter()
+                        .map(|difference| format!("    {}", difference))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                )
+            }
+            ErrorKind::NativeConflict(name) => {
+                return format!(
+                    "The Native Function '{}' Conflicts With A Label Or A Native Function Of The Program.",
+                    name
+                )
+            }
         }
         .to_owned()
     }
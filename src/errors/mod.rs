@@ -3,3 +3,6 @@ pub mod error;
 
 /// The error_kind module, which contains the ErrorKind enum. This enum describes the various kinds of errors that can occur.
 pub mod error_kind;
+
+/// The diagnostic module, which contains the Diagnostic struct. This describes an error by its line and column, so that it can be reported by tools such as editors.
+pub mod diagnostic;
@@ -0,0 +1,294 @@
+//! The Program struct generates whole programs for fuzzing and property tests.
+//! Tokens already implement Arbitrary, but a stream of arbitrary tokens is almost never a valid program,
+//! so it rarely gets past the verifier. Instead, a Program is made of labels, and the body of each label is made of expressions
+//! whose instructions mostly have the arguments they expect. Occasionally, a stray token is inserted, so that the errors are still exercised.
+//!
+//! Every program maintains both its source and the tokens that the lexer should produce for it,
+//! which allows the lexer to be checked with a round trip.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{fuzzing::Program, lexer::Lexer};
+//! use arbitrary::{Arbitrary, Unstructured};
+//!
+//! let bytes = [7; 64];
+//! let program = Program::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+//! let tokens = Lexer::default().lex(program.get_source()).ok().unwrap();
+//! assert_eq!(Vec::from(tokens), program.get_tokens());
+//! ```
+
+use crate::{
+    instructions::{Opcode, INSTRUCTIONS},
+    tokens::{token::Token, token_kind::TokenKind},
+    utils::parameter::Parameter,
+    verifier::WILDCARD,
+};
+use arbitrary::{Arbitrary, Result, Unstructured};
+use std::collections::VecDeque;
+
+/// The names of the labels. The first label is always the main label.
+const LABELS: &[&str] = &["main", "a", "b", "c"];
+
+/// The names of the variables and parameters.
+const VARIABLES: &[&str] = &["x", "y", "z"];
+
+/// The names of the log levels, along with a name that is not a log level.
+const LOG_LEVELS: &[&str] = &["debug", "info", "warn", "error", "loud"];
+
+/// The deepest that the generated expressions are nested.
+const MAX_DEPTH: usize = 4;
+
+/// The largest number of expressions in the body of a label.
+const MAX_BODY: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct Program {
+    source: String,
+    tokens: Vec<Token>,
+    len: usize,
+}
+
+impl Program {
+    /// This function returns the source of the program.
+    pub fn get_source(&self) -> &str {
+        &self.source
+    }
+
+    /// This function returns the tokens that the lexer should produce for the source.
+    pub fn get_tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// This function returns the tokens of the program, in the form that the VM expects.
+    pub fn to_tokens(&self) -> VecDeque<Token> {
+        self.tokens.iter().cloned().collect()
+    }
+
+    /// Appends the given token to the source, followed by a separator.
+    ///
+    /// # Arguments
+    /// `u` - The source of randomness.
+    /// `kind` - The kind of the token.
+    fn push(&mut self, u: &mut Unstructured, kind: TokenKind) -> Result<()> {
+        let pos = self.len + 1;
+        let text = match &kind {
+            TokenKind::Label(name, parameters, _) => {
+                let mut text = format!("@{}", name);
+                let mut parameter_pos = pos + text.chars().count();
+                let mut positioned = vec![];
+                for parameter in parameters {
+                    // The position of a parameter is the position of its '#'.
+                    text.push_str(&format!(" #{}", parameter.name));
+                    positioned.push(Parameter::new(parameter_pos + 1, parameter.name.clone()));
+                    parameter_pos += parameter.name.chars().count() + 2;
+                }
+
+                self.tokens.push(Token::new(
+                    TokenKind::Label(name.clone(), positioned, None),
+                    pos,
+                ));
+                text
+            }
+            kind => {
+                self.tokens.push(Token::new(kind.clone(), pos));
+                write_token(kind)
+            }
+        };
+
+        let separator = match u.int_in_range(0..=7)? {
+            0 => "\n",
+            1 => "  ",
+            2 => "\t",
+            3 => " -- A comment.\n",
+            _ => " ",
+        };
+
+        self.len += text.chars().count() + separator.chars().count();
+        self.source.push_str(&text);
+        self.source.push_str(separator);
+        Ok(())
+    }
+
+    /// Appends an expression, which is an instruction followed by its arguments, or a single value.
+    ///
+    /// # Arguments
+    /// `u` - The source of randomness.
+    /// `parameter_counts` - The number of parameters that each label takes.
+    /// `depth` - The number of expressions that this expression is nested in.
+    fn push_expression(
+        &mut self,
+        u: &mut Unstructured,
+        parameter_counts: &[usize],
+        depth: usize,
+    ) -> Result<()> {
+        // Deeply nested expressions are always values, so that the expressions stay small.
+        if depth >= MAX_DEPTH || u.ratio(1, 3)? {
+            let kind = arbitrary_value(u)?;
+            return self.push(u, kind);
+        }
+
+        let opcode = u.choose(INSTRUCTIONS)?.opcode;
+        let kind = TokenKind::Instruction(opcode);
+        let arity = match opcode {
+            Opcode::Set | Opcode::IncrementVariable | Opcode::DecrementVariable => {
+                self.push(u, kind)?;
+                let name = u.choose(VARIABLES)?;
+                self.push(u, TokenKind::Identifier((*name).to_owned()))?;
+                1
+            }
+            Opcode::SwapVariables => {
+                self.push(u, kind)?;
+                for _ in 0..2 {
+                    let name = u.choose(VARIABLES)?;
+                    self.push(u, TokenKind::Identifier((*name).to_owned()))?;
+                }
+
+                0
+            }
+            Opcode::Log => {
+                self.push(u, kind)?;
+                let level = u.choose(LOG_LEVELS)?;
+                self.push(u, TokenKind::Identifier((*level).to_owned()))?;
+                1
+            }
+            Opcode::Times => {
+                self.push(u, kind)?;
+                self.push_expression(u, parameter_counts, depth + 1)?;
+                let label = u.choose_index(parameter_counts.len())?;
+                self.push(u, TokenKind::Identifier(LABELS[label].to_owned()))?;
+                0
+            }
+            Opcode::Match => {
+                self.push(u, kind)?;
+                for _ in 0..u.int_in_range(0..=3)? {
+                    let pattern = match u.ratio(1, 4)? {
+                        true => TokenKind::Identifier(WILDCARD.to_owned()),
+                        false => arbitrary_value(u)?,
+                    };
+                    self.push(u, pattern)?;
+                    let label = u.choose_index(parameter_counts.len())?;
+                    self.push(u, TokenKind::Identifier(LABELS[label].to_owned()))?;
+                }
+
+                self.push(u, TokenKind::Instruction(Opcode::EndMatch))?;
+                0
+            }
+            Opcode::Call | Opcode::CallIfTrue | Opcode::CallIfFalse => {
+                self.push(u, kind)?;
+                let label = u.choose_index(parameter_counts.len())?;
+                self.push(u, TokenKind::Identifier(LABELS[label].to_owned()))?;
+                parameter_counts[label]
+            }
+            Opcode::Defer => {
+                self.push(u, kind)?;
+                let label = u.choose_index(parameter_counts.len())?;
+                self.push(u, TokenKind::Identifier(LABELS[label].to_owned()))?;
+                0
+            }
+            opcode => {
+                self.push(u, kind)?;
+                opcode.get_instruction().arity
+            }
+        };
+
+        for _ in 0..arity {
+            self.push_expression(u, parameter_counts, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Arbitrary<'a> for Program {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut program = Program {
+            source: String::new(),
+            tokens: vec![],
+            len: 0,
+        };
+
+        let label_count = u.int_in_range(1..=LABELS.len())?;
+        let mut parameter_counts = vec![];
+        for _ in 0..label_count {
+            parameter_counts.push(u.int_in_range(0..=VARIABLES.len())?);
+        }
+
+        for (label, parameter_count) in parameter_counts.iter().enumerate() {
+            let parameters = VARIABLES[..*parameter_count]
+                .iter()
+                .map(|name| Parameter::new(0, (*name).to_owned()))
+                .collect();
+            program.push(
+                u,
+                TokenKind::Label(LABELS[label].to_owned(), parameters, None),
+            )?;
+
+            for _ in 0..u.int_in_range(0..=MAX_BODY)? {
+                if u.ratio(1, 20)? {
+                    // A stray token, which is usually an error.
+                    let kind = match u.int_in_range(0..=2)? {
+                        0 => TokenKind::End,
+                        1 => TokenKind::Identifier((*u.choose(LABELS)?).to_owned()),
+                        _ => TokenKind::Instruction(u.choose(INSTRUCTIONS)?.opcode),
+                    };
+                    program.push(u, kind)?;
+                } else {
+                    program.push_expression(u, &parameter_counts, 0)?;
+                }
+            }
+
+            program.push(u, TokenKind::End)?;
+        }
+
+        Ok(program)
+    }
+}
+
+/// This function generates a value that is not an instruction.
+/// Small integers are more likely, so that jumps sometimes land inside of the program.
+///
+/// # Arguments
+/// `u` - The source of randomness.
+fn arbitrary_value(u: &mut Unstructured) -> Result<TokenKind> {
+    Ok(match u.int_in_range(0..=9)? {
+        0 => TokenKind::Void,
+        1 => TokenKind::Any,
+        2 => TokenKind::IntegerLiteral(i64::arbitrary(u)?),
+        3 => TokenKind::FloatLiteral(f64::from(i16::arbitrary(u)?) / 4.0),
+        4 => TokenKind::BooleanLiteral(bool::arbitrary(u)?),
+        5 => {
+            let len = u.int_in_range(0..=8)?;
+            let mut string = String::new();
+            for _ in 0..len {
+                string.push(*u.choose(&['a', 'b', 'Z', '0', ' ', '"', '\n', 'é'])?);
+            }
+
+            TokenKind::StringLiteral(string)
+        }
+        6 | 7 => TokenKind::Identifier((*u.choose(VARIABLES)?).to_owned()),
+        _ => TokenKind::IntegerLiteral(u.int_in_range(-4..=32)?),
+    })
+}
+
+/// This function writes the given token as it would appear in the source.
+///
+/// # Arguments
+/// `kind` - The kind of the token.
+fn write_token(kind: &TokenKind) -> String {
+    match kind {
+        TokenKind::Void => "void".to_owned(),
+        TokenKind::Null => "null".to_owned(),
+        TokenKind::Any => "any".to_owned(),
+        TokenKind::IntegerLiteral(value) => value.to_string(),
+        // The lexer does not support exponents, but the generated floats are always small enough to be written without one.
+        TokenKind::FloatLiteral(value) => format!("{:?}", value),
+        TokenKind::BooleanLiteral(value) => value.to_string(),
+        TokenKind::StringLiteral(value) => format!("'{}'", value),
+        TokenKind::CharLiteral(value) => format!("`{}`", value),
+        TokenKind::Identifier(name) => name.to_owned(),
+        TokenKind::Label(name, _, _) => format!("@{}", name),
+        TokenKind::Annotation(name) => format!("@[{}]", name),
+        TokenKind::End => "end".to_owned(),
+        TokenKind::Instruction(opcode) => opcode.get_name().to_owned(),
+    }
+}
@@ -0,0 +1,1251 @@
+//! The Opcode enum names every instruction, and the INSTRUCTIONS registry describes each of them:
+//! its name in the source, the number of inline arguments it takes, how it changes the stack, and what it does.
+//! The lexer, the verifier, and the tooling all consult this registry, so that the knowledge about an instruction lives in one place.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::instructions::{Opcode, INSTRUCTIONS};
+//! let instruction = Opcode::from_name("rreplace").unwrap().get_instruction();
+//! assert_eq!(instruction.arity, 3);
+//!
+//! // The registry is indexed by opcode.
+//! assert!(INSTRUCTIONS.iter().enumerate().all(|(idx, instruction)| instruction.opcode as usize == idx));
+//! ```
+
+/// The table of every instruction, in the order that they are documented.
+/// This is the only place that lists the instructions. Adding an instruction means adding an entry here, along with the method of the VM that handles it.
+///
+/// The table is passed to the given macro, which generates code for every instruction.
+/// This module generates the Opcode enum and the INSTRUCTIONS registry, and the VM generates the match that calls each handler.
+macro_rules! instructions {
+    ($generate:ident) => {
+        $generate! {
+            Push {
+                name: "push",
+                handler: push,
+                arity: 1,
+                pops: 0,
+                pushes: 1,
+                usage: "push <value>",
+                description: "Pushes the value on to the stack.",
+            },
+            Pop {
+                name: "pop",
+                handler: pop_top,
+                arity: 0,
+                pops: 1,
+                pushes: 0,
+                usage: "pop",
+                description: "Pops the top value from the stack and returns it.",
+            },
+            Peek {
+                name: "peek",
+                handler: peek,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "peek",
+                description: "Returns the top value of the stack without popping it.",
+            },
+            Add {
+                name: "add",
+                handler: add,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "add",
+                description: "Pops the top two values from the stack and adds them together.",
+            },
+            Sub {
+                name: "sub",
+                handler: sub,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "sub",
+                description: "Pops the top two values from the stack and subtracts the second value from the first.",
+            },
+            Mul {
+                name: "mul",
+                handler: mul,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "mul",
+                description: "Pops the top two values from the stack and multiplies them.",
+            },
+            Div {
+                name: "div",
+                handler: div,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "div",
+                description: "Pops the top two values from the stack and divides the first value by the second. The result is always a float.",
+            },
+            IntegerDivide {
+                name: "idiv",
+                handler: idiv,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "idiv",
+                description: "Pops the top two ints from the stack and divides the first int by the second, rounding toward zero.",
+            },
+            DivideModulo {
+                name: "divmod",
+                handler: divmod,
+                arity: 0,
+                pops: 2,
+                pushes: 2,
+                usage: "divmod",
+                description: "Pops the top two ints from the stack and pushes the quotient of the integer division of the first int by the second, followed by the remainder.",
+            },
+            Mod {
+                name: "mod",
+                handler: modulus,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "mod",
+                description: "Pops the top two values from the stack and returns the remainder of dividing the first value by the second.",
+            },
+            Negate {
+                name: "neg",
+                handler: neg,
+                arity: 0,
+                pops: 1,
+                pushes: 0,
+                usage: "neg",
+                description: "Pops the top int or float from the stack and returns it with its sign flipped.",
+            },
+            Absolute {
+                name: "abs",
+                handler: abs,
+                arity: 0,
+                pops: 1,
+                pushes: 0,
+                usage: "abs",
+                description: "Pops the top int or float from the stack and returns its absolute value.",
+            },
+            BitAnd {
+                name: "band",
+                handler: band,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "band",
+                description: "Pops the top two ints from the stack and returns their bitwise and.",
+            },
+            BitOr {
+                name: "bor",
+                handler: bor,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "bor",
+                description: "Pops the top two ints from the stack and returns their bitwise or.",
+            },
+            BitXor {
+                name: "bxor",
+                handler: bxor,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "bxor",
+                description: "Pops the top two ints from the stack and returns their bitwise exclusive or.",
+            },
+            BitNot {
+                name: "bnot",
+                handler: bnot,
+                arity: 0,
+                pops: 1,
+                pushes: 0,
+                usage: "bnot",
+                description: "Pops the top int from the stack and returns it with every bit flipped.",
+            },
+            ShiftLeft {
+                name: "shl",
+                handler: shl,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "shl",
+                description: "Pops the top two ints from the stack and shifts the first int to the left by the second, which has to be between 0 and 63.",
+            },
+            ShiftRight {
+                name: "shr",
+                handler: shr,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "shr",
+                description: "Pops the top two ints from the stack and shifts the first int to the right by the second, which has to be between 0 and 63. The sign of the first int is kept.",
+            },
+            LessThan {
+                name: "lt",
+                handler: lt,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "lt <value> <value>",
+                description: "Returns whether the first value is less than the second value.",
+            },
+            LessThanEqual {
+                name: "lte",
+                handler: lte,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "lte <value> <value>",
+                description: "Returns whether the first value is less than or equal to the second value.",
+            },
+            GreaterThan {
+                name: "gt",
+                handler: gt,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "gt <value> <value>",
+                description: "Returns whether the first value is greater than the second value.",
+            },
+            GreaterThanEqual {
+                name: "gte",
+                handler: gte,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "gte <value> <value>",
+                description: "Returns whether the first value is greater than or equal to the second value.",
+            },
+            Equal {
+                name: "eq",
+                handler: eq,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "eq <value> <value>",
+                description: "Returns whether the two values are equal.",
+            },
+            NotEqual {
+                name: "neq",
+                handler: neq,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "neq <value> <value>",
+                description: "Returns whether the two values are not equal.",
+            },
+            LessThanIgnoreCase {
+                name: "ltci",
+                handler: ltci,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "ltci <string> <string>",
+                description: "Returns whether the first string is less than the second string, ignoring case.",
+            },
+            EqualIgnoreCase {
+                name: "eqci",
+                handler: eqci,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "eqci <string> <string>",
+                description: "Returns whether the two strings are equal, ignoring case.",
+            },
+            And {
+                name: "and",
+                handler: and,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "and <value> <value>",
+                description: "Returns whether both values are truthy. Both values are always evaluated.",
+            },
+            Or {
+                name: "or",
+                handler: or,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "or <value> <value>",
+                description: "Returns whether either value is truthy. Both values are always evaluated.",
+            },
+            Not {
+                name: "not",
+                handler: not,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "not <value>",
+                description: "Returns whether the value is not truthy.",
+            },
+            Length {
+                name: "len",
+                handler: len,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "len <string|array|map|bytes>",
+                description: "Returns the number of characters in a string, elements in an array, entries in a map, or bytes in bytes.",
+            },
+            Ordinal {
+                name: "ord",
+                handler: ord,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "ord <char>",
+                description: "Returns the Unicode code point of the char as an int.",
+            },
+            Character {
+                name: "chr",
+                handler: chr,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "chr <int>",
+                description: "Returns the char whose Unicode code point is the int.",
+            },
+            CharAt {
+                name: "charat",
+                handler: charat,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "charat <string> <index>",
+                description: "Returns the char of the string at the index, which counts characters rather than bytes and starts at 0.",
+            },
+            Chars {
+                name: "chars",
+                handler: chars,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "chars <string>",
+                description: "Returns an array of the chars of the string.",
+            },
+            StringLength {
+                name: "slen",
+                handler: slen,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "slen <string>",
+                description: "Returns the number of characters in the string.",
+            },
+            Substring {
+                name: "substr",
+                handler: substr,
+                arity: 3,
+                pops: 0,
+                pushes: 0,
+                usage: "substr <string> <start> <length>",
+                description: "Returns the part of the string that starts at the index and has the length, both counted in characters.",
+            },
+            StringConcat {
+                name: "sconcat",
+                handler: sconcat,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "sconcat <string> <string>",
+                description: "Returns the first string followed by the second string.",
+            },
+            Uppercase {
+                name: "supper",
+                handler: supper,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "supper <string>",
+                description: "Returns the string with every character in uppercase.",
+            },
+            Lowercase {
+                name: "slower",
+                handler: slower,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "slower <string>",
+                description: "Returns the string with every character in lowercase.",
+            },
+            Array {
+                name: "arr",
+                handler: arr,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "arr",
+                description: "Returns an empty array.",
+            },
+            ArrayGet {
+                name: "aget",
+                handler: aget,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "aget <array> <index>",
+                description: "Returns the element of the array at the index, which starts at 0.",
+            },
+            ArraySet {
+                name: "aset",
+                handler: aset,
+                arity: 3,
+                pops: 0,
+                pushes: 0,
+                usage: "aset <array> <index> <value>",
+                description: "Returns a copy of the array with the element at the index replaced by the value.",
+            },
+            ArrayPush {
+                name: "apush",
+                handler: apush,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "apush <array> <value>",
+                description: "Returns a copy of the array with the value added to its end.",
+            },
+            ArrayPop {
+                name: "apop",
+                handler: apop,
+                arity: 1,
+                pops: 0,
+                pushes: 2,
+                usage: "apop <array>",
+                description: "Pushes a copy of the array without its last element, followed by the last element.",
+            },
+            ArrayLength {
+                name: "alen",
+                handler: alen,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "alen <array>",
+                description: "Returns the number of elements in the array.",
+            },
+            Map {
+                name: "mnew",
+                handler: mnew,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "mnew",
+                description: "Returns an empty map.",
+            },
+            MapGet {
+                name: "mget",
+                handler: mget,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "mget <map> <key>",
+                description: "Returns the value of the key in the map. The key must be in the map.",
+            },
+            MapSet {
+                name: "mset",
+                handler: mset,
+                arity: 3,
+                pops: 0,
+                pushes: 0,
+                usage: "mset <map> <key> <value>",
+                description: "Returns a copy of the map with the key set to the value.",
+            },
+            MapDelete {
+                name: "mdel",
+                handler: mdel,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "mdel <map> <key>",
+                description: "Returns a copy of the map without the key.",
+            },
+            MapHas {
+                name: "mhas",
+                handler: mhas,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "mhas <map> <key>",
+                description: "Returns whether the key is in the map.",
+            },
+            MapKeys {
+                name: "mkeys",
+                handler: mkeys,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "mkeys <map>",
+                description: "Returns an array of the keys of the map, in sorted order.",
+            },
+            StackLessThan {
+                name: "slt",
+                handler: slt,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "slt",
+                description: "Pops the top two values from the stack and returns whether the first value popped is less than the second.",
+            },
+            StackLessThanEqual {
+                name: "slte",
+                handler: slte,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "slte",
+                description: "Pops the top two values from the stack and returns whether the first value popped is less than or equal to the second.",
+            },
+            StackGreaterThan {
+                name: "sgt",
+                handler: sgt,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "sgt",
+                description: "Pops the top two values from the stack and returns whether the first value popped is greater than the second.",
+            },
+            StackGreaterThanEqual {
+                name: "sgte",
+                handler: sgte,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "sgte",
+                description: "Pops the top two values from the stack and returns whether the first value popped is greater than or equal to the second.",
+            },
+            StackEqual {
+                name: "seq",
+                handler: seq,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "seq",
+                description: "Pops the top two values from the stack and returns whether the first value popped is equal to the second.",
+            },
+            StackNotEqual {
+                name: "sneq",
+                handler: sneq,
+                arity: 0,
+                pops: 2,
+                pushes: 0,
+                usage: "sneq",
+                description: "Pops the top two values from the stack and returns whether the first value popped is not equal to the second.",
+            },
+            Jump {
+                name: "jmp",
+                handler: jmp,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "jmp <int>",
+                description: "Moves the instruction pointer to the given position.",
+            },
+            RelativeJump {
+                name: "rjmp",
+                handler: rjmp,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "rjmp <int>",
+                description: "Moves the instruction pointer by the given offset, which may be negative.",
+            },
+            JumpIfTrue {
+                name: "jmpt",
+                handler: jmpt,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "jmpt <int>",
+                description: "Moves the instruction pointer to the given position if the top value of the stack is truthy.",
+            },
+            JumpIfFalse {
+                name: "jmpf",
+                handler: jmpf,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "jmpf <int>",
+                description: "Moves the instruction pointer to the given position if the top value of the stack is falsy.",
+            },
+            RelativeJumpIfTrue {
+                name: "rjmpt",
+                handler: rjmpt,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "rjmpt <int>",
+                description: "Moves the instruction pointer by the given offset if the top value of the stack is truthy.",
+            },
+            RelativeJumpIfFalse {
+                name: "rjmpf",
+                handler: rjmpf,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "rjmpf <int>",
+                description: "Moves the instruction pointer by the given offset if the top value of the stack is falsy.",
+            },
+            Print {
+                name: "print",
+                handler: print,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "print <value>",
+                description: "Prints the value.",
+            },
+            PrintNewLine {
+                name: "printn",
+                handler: printn,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "printn <value>",
+                description: "Prints the value with a new line after it.",
+            },
+            ErrorPrint {
+                name: "eprint",
+                handler: eprint,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "eprint <value>",
+                description: "Prints the value to the error stream.",
+            },
+            ErrorPrintNewLine {
+                name: "eprintn",
+                handler: eprintn,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "eprintn <value>",
+                description: "Prints the value to the error stream with a new line after it.",
+            },
+            Read {
+                name: "read",
+                handler: read,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "read",
+                description: "Reads a line from the input stream and returns it as a string, without its line ending. Null is returned once the input has ended.",
+            },
+            ReadInt {
+                name: "readint",
+                handler: readint,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "readint",
+                description: "Reads a line from the input stream and returns it as an int. Null is returned once the input has ended.",
+            },
+            ReadFloat {
+                name: "readfloat",
+                handler: readfloat,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "readfloat",
+                description: "Reads a line from the input stream and returns it as a float. Null is returned once the input has ended.",
+            },
+            ReadBytes {
+                name: "readbytes",
+                handler: readbytes,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "readbytes <count>",
+                description: "Reads at most the given number of bytes from the input stream and returns them. The bytes are empty once the input has ended.",
+            },
+            WriteBytes {
+                name: "writebytes",
+                handler: writebytes,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "writebytes <string|bytes>",
+                description: "Writes the raw bytes of the value to the output stream. Strings are written as UTF-8.",
+            },
+            Format {
+                name: "format",
+                handler: format,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "format <specifier> <number>",
+                description: "Formats the number as a string, using a printf style specifier such as %.2f, %e, %5d, %x, or %08b.",
+            },
+            Breakpoint {
+                name: "brk",
+                handler: brk,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "brk",
+                description: "Pauses the program in the debugger, or in any host that suspends on breaks. Otherwise, this does nothing.",
+            },
+            Dump {
+                name: "dump",
+                handler: dump,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "dump",
+                description: "Writes the operand stack and the variables of the current frame to the error stream, without changing them.",
+            },
+            Log {
+                name: "log",
+                handler: log,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "log <level> <value>",
+                description: "Writes the value to the error stream if the level is enabled. The level is one of debug, info, warn, or error.",
+            },
+            Assert {
+                name: "assert",
+                handler: assert,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "assert <value>",
+                description: "Returns an error if the value is not truthy.",
+            },
+            AssertEqual {
+                name: "asserteq",
+                handler: assert_eq,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "asserteq <expected> <actual>",
+                description: "Returns an error if the values are not equal, which lists every difference between them, such as the elements of arrays and the keys of maps that differ.",
+            },
+            ReadFile {
+                name: "readfile",
+                handler: readfile,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "readfile <path>",
+                description: "Reads the file at the path and returns its contents as a string. This requires the file system capability.",
+            },
+            WriteFile {
+                name: "writefile",
+                handler: writefile,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "writefile <path> <value>",
+                description: "Writes the value to the file at the path, replacing its contents. This requires the file system capability.",
+            },
+            AppendFile {
+                name: "appendfile",
+                handler: appendfile,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "appendfile <path> <value>",
+                description: "Writes the value to the end of the file at the path. This requires the file system capability.",
+            },
+            Exec {
+                name: "exec",
+                handler: exec,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "exec <string|array>",
+                description: "Runs the command and returns a map with its exit code and its captured stdout and stderr. A string is split on whitespace, and an array holds the program and each of its arguments. The command is never run through a shell. This requires the exec feature, and the VM must allow the command.",
+            },
+            Env {
+                name: "env",
+                handler: env,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "env <string>",
+                description: "Returns the value of the environment variable, or void if it is not set. The VM must allow the environment.",
+            },
+            Fetch {
+                name: "fetch",
+                handler: fetch,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "fetch <url>",
+                description: "Sends a GET request to the url, and returns a map with the status of the response and its body. This requires the http feature, and the VM must allow the network.",
+            },
+            JsonParse {
+                name: "jsonparse",
+                handler: jsonparse,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "jsonparse <string>",
+                description: "Parses the string as JSON and returns the value. Arrays become arrays, objects become maps, and null becomes null.",
+            },
+            JsonStringify {
+                name: "jsonstringify",
+                handler: jsonstringify,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "jsonstringify <value>",
+                description: "Converts the value into a JSON string. Maps become objects, and void becomes null.",
+            },
+            CsvParse {
+                name: "csvparse",
+                handler: csvparse,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "csvparse <string> <boolean>",
+                description: "Parses the string as CSV and returns an array of rows. If the boolean is true, the first row is the header, and every other row is a map from the header to the field. Otherwise, every row is an array of fields. This requires the csv feature.",
+            },
+            Base64Encode {
+                name: "b64encode",
+                handler: b64encode,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "b64encode <string|bytes>",
+                description: "Encodes the value with the standard base64 alphabet and returns the encoded string. Strings are encoded as UTF-8.",
+            },
+            Base64Decode {
+                name: "b64decode",
+                handler: b64decode,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "b64decode <string>",
+                description: "Decodes the base64 string and returns the bytes. The padding is optional.",
+            },
+            HexEncode {
+                name: "hexencode",
+                handler: hexencode,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "hexencode <string|bytes>",
+                description: "Encodes the value as lowercase hexadecimal and returns the encoded string. Strings are encoded as UTF-8.",
+            },
+            HexDecode {
+                name: "hexdecode",
+                handler: hexdecode,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "hexdecode <string>",
+                description: "Decodes the hexadecimal string and returns the bytes. Both uppercase and lowercase digits are accepted.",
+            },
+            Crc32 {
+                name: "crc32",
+                handler: crc32,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "crc32 <string|bytes>",
+                description: "Returns the CRC-32 checksum of the value as a hexadecimal string. Strings are hashed as UTF-8. This requires the hashing feature.",
+            },
+            Md5 {
+                name: "md5",
+                handler: md5,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "md5 <string|bytes>",
+                description: "Returns the MD5 digest of the value as a hexadecimal string. Strings are hashed as UTF-8. This requires the hashing feature.",
+            },
+            Sha256 {
+                name: "sha256",
+                handler: sha256,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "sha256 <string|bytes>",
+                description: "Returns the SHA-256 digest of the value as a hexadecimal string. Strings are hashed as UTF-8. This requires the hashing feature.",
+            },
+            RMatch {
+                name: "rmatch",
+                handler: rmatch,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "rmatch <pattern> <string>",
+                description: "Returns whether the regular expression matches anywhere in the string. This requires the regex feature.",
+            },
+            RFind {
+                name: "rfind",
+                handler: rfind,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "rfind <pattern> <string>",
+                description: "Returns an array of every match of the regular expression in the string. This requires the regex feature.",
+            },
+            RReplace {
+                name: "rreplace",
+                handler: rreplace,
+                arity: 3,
+                pops: 0,
+                pushes: 0,
+                usage: "rreplace <pattern> <string> <replacement>",
+                description: "Replaces every match of the regular expression in the string, and returns the new string. The replacement may refer to groups with $1 or $name. This requires the regex feature.",
+            },
+            Now {
+                name: "now",
+                handler: now,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "now",
+                description: "Returns the current time as a Unix timestamp, which is the number of seconds since the Unix epoch. This requires the datetime feature.",
+            },
+            DateFormat {
+                name: "dateformat",
+                handler: dateformat,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "dateformat <int> <format>",
+                description: "Formats the Unix timestamp as a date in UTC, using strftime syntax such as %Y-%m-%d %H:%M:%S. This requires the datetime feature.",
+            },
+            DateParse {
+                name: "dateparse",
+                handler: dateparse,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "dateparse <string> <format>",
+                description: "Parses the string as a date with the strftime format, and returns its Unix timestamp. Dates without an offset are in UTC. This requires the datetime feature.",
+            },
+            Set {
+                name: "set",
+                handler: set,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "set <identifier> <value>",
+                description: "Defines the variable in the current frame with the value.",
+            },
+            IncrementVariable {
+                name: "incvar",
+                handler: incvar,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "incvar <identifier> <value>",
+                description: "Adds the value to the variable, in the scope that defines the variable.",
+            },
+            DecrementVariable {
+                name: "decvar",
+                handler: decvar,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "decvar <identifier> <value>",
+                description: "Subtracts the value from the variable, in the scope that defines the variable.",
+            },
+            SwapVariables {
+                name: "swapvar",
+                handler: swapvar,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "swapvar <identifier> <identifier>",
+                description: "Exchanges the values of the two variables, in the scopes that define them.",
+            },
+            Begin {
+                name: "begin",
+                handler: begin,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "begin",
+                description: "Enters a block. The variables defined inside of the block are discarded when it is left.",
+            },
+            EndBlock {
+                name: "endblock",
+                handler: endblock,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "endblock",
+                description: "Leaves the innermost block that was entered with begin.",
+            },
+            Call {
+                name: "call",
+                handler: call,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "call <label> <arguments>",
+                description: "Calls the label, passing one argument for each of its parameters.",
+            },
+            Defer {
+                name: "defer",
+                handler: defer,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "defer <label>",
+                description: "Calls the label, which takes no parameters, when the current label ends or is unwound by an error. The deferred labels run in the reverse order of their defer instructions.",
+            },
+            Params {
+                name: "params",
+                handler: params,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "params",
+                description: "Returns the names of the parameters of the label of the current frame, as an array of strings.",
+            },
+            Recurse {
+                name: "recurse",
+                handler: recurse,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "recurse <arguments>",
+                description: "Calls the label of the current frame again, passing one argument for each of its parameters.",
+            },
+            Times {
+                name: "times",
+                handler: times,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "times <int> <label>",
+                description: "Calls the label the given number of times. If the label defines a parameter, it receives the number of the iteration, starting from 0.",
+            },
+            CallIfTrue {
+                name: "callt",
+                handler: callt,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "callt <label> <arguments>",
+                description: "Calls the label if the top value of the stack is truthy. Otherwise, the arguments are skipped without being evaluated.",
+            },
+            CallIfFalse {
+                name: "callf",
+                handler: callf,
+                arity: 1,
+                pops: 0,
+                pushes: 0,
+                usage: "callf <label> <arguments>",
+                description: "Calls the label if the top value of the stack is falsy. Otherwise, the arguments are skipped without being evaluated.",
+            },
+            Alias {
+                name: "alias",
+                handler: alias,
+                arity: 2,
+                pops: 0,
+                pushes: 0,
+                usage: "alias <name> <instruction>",
+                description: "Defines a name that stands for the instruction everywhere in the program. This is removed when the program is loaded.",
+            },
+            If {
+                name: "if",
+                handler: if_block,
+                arity: 0,
+                pops: 1,
+                pushes: 0,
+                usage: "if <instructions> [else <instructions>] endif",
+                description: "Pops the top value of the stack and runs the instructions before the else if it is truthy, or the instructions after the else otherwise. This is lowered into jumps when the program is loaded.",
+            },
+            Else {
+                name: "else",
+                handler: else_block,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "else",
+                description: "Separates the instructions of an if that run when the value is truthy from the ones that run when it is falsy.",
+            },
+            EndIf {
+                name: "endif",
+                handler: endif,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "endif",
+                description: "Ends the instructions of an if.",
+            },
+            While {
+                name: "while",
+                handler: while_block,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "while <condition> do <instructions> endwhile",
+                description: "Runs the condition, which leaves a value on the stack, and then pops the value and runs the instructions if it is truthy, until it is falsy. This is lowered into jumps when the program is loaded.",
+            },
+            Do {
+                name: "do",
+                handler: do_block,
+                arity: 0,
+                pops: 1,
+                pushes: 0,
+                usage: "do",
+                description: "Separates the condition of a while from the instructions that it repeats.",
+            },
+            EndWhile {
+                name: "endwhile",
+                handler: endwhile,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "endwhile",
+                description: "Ends the instructions of a while.",
+            },
+            Break {
+                name: "break",
+                handler: break_loop,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "break",
+                description: "Leaves the innermost while, continuing after its endwhile. This is lowered into a jump when the program is loaded.",
+            },
+            Continue {
+                name: "continue",
+                handler: continue_loop,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "continue",
+                description: "Runs the condition of the innermost while again, skipping the rest of its instructions. This is lowered into a jump when the program is loaded.",
+            },
+            Match {
+                name: "match",
+                handler: match_value,
+                arity: 0,
+                pops: 1,
+                pushes: 0,
+                usage: "match <pattern> <label> ... endmatch",
+                description: "Pops the top value of the stack and calls the label of the first literal pattern that is equal to it, or of the wildcard _. The label may define one parameter, which receives the value.",
+            },
+            EndMatch {
+                name: "endmatch",
+                handler: endmatch,
+                arity: 0,
+                pops: 0,
+                pushes: 0,
+                usage: "endmatch",
+                description: "Ends the patterns of a match instruction.",
+            },
+        }
+    };
+}
+
+pub(crate) use instructions;
+
+/// Generates the Opcode enum and the INSTRUCTIONS registry from the table of instructions.
+macro_rules! registry {
+    ($($opcode:ident {
+        name: $name:literal,
+        handler: $handler:ident,
+        arity: $arity:literal,
+        pops: $pops:literal,
+        pushes: $pushes:literal,
+        usage: $usage:literal,
+        description: $description:literal,
+    },)*) => {
+        /// The Opcode enum names every instruction, in the order that they are documented.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+        pub enum Opcode {
+            $(
+                #[doc = $description]
+                $opcode,
+            )*
+        }
+
+        /// Every instruction, in the same order as the Opcode enum.
+        pub const INSTRUCTIONS: &[Instruction] = &[
+            $(
+                Instruction {
+                    opcode: Opcode::$opcode,
+                    name: $name,
+                    arity: $arity,
+                    pops: $pops,
+                    pushes: $pushes,
+                    usage: $usage,
+                    description: $description,
+                },
+            )*
+        ];
+    };
+}
+
+instructions!(registry);
+
+/// The Instruction struct describes a single instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    /// The opcode of the instruction.
+    pub opcode: Opcode,
+
+    /// The name of the instruction, as it is written in the source.
+    pub name: &'static str,
+
+    /// The number of inline arguments that the instruction takes.
+    /// The call instruction takes one more argument for each parameter of the label that it calls.
+    pub arity: usize,
+
+    /// The number of values that the instruction pops from the stack.
+    pub pops: usize,
+
+    /// The number of values that the instruction pushes on to the stack.
+    pub pushes: usize,
+
+    /// How the instruction is written, along with its arguments.
+    pub usage: &'static str,
+
+    /// What the instruction does.
+    pub description: &'static str,
+}
+
+impl Opcode {
+    /// This function returns the opcode of the instruction with the given name, or None if the name is not an instruction.
+    /// Names are not case sensitive.
+    ///
+    /// # Arguments
+    /// `name` - The name of the instruction.
+    pub fn from_name(name: &str) -> Option<Opcode> {
+        INSTRUCTIONS
+            .iter()
+            .find(|instruction| instruction.name.eq_ignore_ascii_case(name))
+            .map(|instruction| instruction.opcode)
+    }
+
+    /// This function returns the description of the instruction in the registry.
+    pub fn get_instruction(self) -> &'static Instruction {
+        &INSTRUCTIONS[self as usize]
+    }
+
+    /// This function returns the name of the instruction, as it is written in the source.
+    pub fn get_name(self) -> &'static str {
+        self.get_instruction().name
+    }
+}
+
+impl Instruction {
+    /// This function returns the documentation of the instruction, which is its usage followed by its description.
+    /// This is used by tools, such as the language server, to describe instructions.
+    pub fn get_documentation(&self) -> String {
+        format!("{}\n\n{}", self.usage, self.description)
+    }
+
+    /// This function describes how the instruction changes the stack, such as `-2` for an instruction that pops two values.
+    pub fn get_stack_effect(&self) -> String {
+        match (self.pops, self.pushes) {
+            (0, 0) => "none".to_owned(),
+            (pops, 0) => format!("-{}", pops),
+            (0, pushes) => format!("+{}", pushes),
+            (pops, pushes) => format!("-{} +{}", pops, pushes),
+        }
+    }
+}
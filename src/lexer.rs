@@ -5,6 +5,7 @@
 //!
 //! # Example
 //! ```
+//! # use dark_vm::{errors::error::Error, lexer::Lexer};
 //! # fn run() -> Result<(), Error> {
 //! let contents = "push 1";
 //! let tokens = Lexer::default().lex(contents)?;
@@ -12,7 +13,7 @@
 //! # }
 //! ```
 
-use crate::utils::parameter::Parameter;
+use crate::utils::{case::Case, parameter::Parameter};
 use crate::{
     errors::{error::Error, error_kind::ErrorKind},
     tokens::{token::Token, token_kind::TokenKind},
@@ -20,33 +21,73 @@ use crate::{
 
 use std::{collections::VecDeque, iter::Peekable, str::Chars};
 
-#[derive(Default)]
+/// The annotation that gives a label a stack of its own. It is written in brackets after the @, such as `@[pure] double #x`, so it can not be confused with the name of a label.
+pub const PURE_ANNOTATION: &str = "pure";
+
+#[derive(Default, Clone)]
 pub struct Lexer {
     current_position: usize,
+
+    /// Whether the names of variables, labels, and parameters are folded to lower case.
+    case: Case,
+
+    /// Whether a token or a comment has been found on the current line, so that a comment can tell whether it starts the line.
+    line_has_content: bool,
+
+    /// The lines of the single line comments that directly precede the next token, which become the documentation of the next label.
+    doc_lines: Vec<String>,
 }
 
 impl Lexer {
+    /// Constructs a new lexer that applies the given case policy to the names of variables, labels, and parameters.
+    /// Instructions and keywords are matched regardless of case either way.
+    ///
+    /// # Arguments
+    /// * `case` - Whether the names are folded to lower case.
+    pub fn with_case(case: Case) -> Lexer {
+        Lexer {
+            case,
+            ..Lexer::default()
+        }
+    }
+
     /// This function lexes the input and returns either a VecDeque of tokens or an error.
     /// The return value of this function may change to returning a vector of errors.
     ///
     /// # Arguments
-    /// * `contents` - The contents to lex. This may come from a file or from the REPL.
+    /// * `contents` - The contents to lex. This may come from a file or from the REPL. A shebang line at the start of the contents is skipped.
     pub fn lex(&mut self, contents: &str) -> Result<VecDeque<Token>, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("lex").entered();
         let mut iter = contents.chars().peekable();
         let mut tokens = VecDeque::new();
+        if self.current_position == 0 && contents.starts_with("#!") {
+            self.skip_shebang(&mut iter);
+        }
+
         while let Some(ch) = iter.next() {
             self.current_position += 1;
+            self.handle_new_line(ch);
 
             // If the current character is a whitespace or a comment, handle it, and continue lexing.
             if ch.is_ascii_whitespace() || self.handle_comments(ch, &mut iter) {
                 continue;
             }
 
+            // The documentation only belongs to a label, so any other token discards it.
+            let doc_lines = std::mem::take(&mut self.doc_lines);
+            self.line_has_content = true;
+
             // Identify what the character is and try to lex as much of it as possible.
             match ch {
                 '0'..='9' | '-' => tokens.push_back(self.make_number(ch, &mut iter)?),
                 '\'' | '"' => tokens.push_back(self.make_string(ch, &mut iter)?),
-                '@' => tokens.push_back(self.make_label(&mut iter)?),
+                '`' => tokens.push_back(self.make_char(&mut iter)?),
+                '@' => {
+                    let (annotation, label) = self.make_label(doc_lines, &mut iter)?;
+                    tokens.extend(annotation);
+                    tokens.push_back(label);
+                }
                 letter if ch.is_ascii_alphabetic() || ch == '_' => {
                     tokens.push_back(self.make_word(letter, &mut iter))
                 }
@@ -75,9 +116,9 @@ impl Lexer {
             // After the value of the character has been identified, it is important to remember to advance the iterator.
             // Otherwise, an infinite loop will be generated.
             if ch.is_ascii_digit() {
-                number.push(self.advance(iter));
+                number.extend(self.advance(iter));
             } else if ch == &'.' && !has_decimal_point {
-                number.push(self.advance(iter));
+                number.extend(self.advance(iter));
                 has_decimal_point = true;
             } else {
                 break;
@@ -117,13 +158,14 @@ impl Lexer {
                 self.advance(iter);
                 break;
             } else {
-                word.push(self.advance(iter));
+                word.extend(self.advance(iter));
             }
         }
 
         // This probably could be written using a match statement.
         match word.to_ascii_lowercase().as_str() {
             "void" => Token::new(TokenKind::Void, initial_point),
+            "null" => Token::new(TokenKind::Null, initial_point),
             "any" => Token::new(TokenKind::Any, initial_point),
             "true" => Token::new(TokenKind::BooleanLiteral(true), initial_point),
             "false" => Token::new(TokenKind::BooleanLiteral(false), initial_point),
@@ -132,7 +174,8 @@ impl Lexer {
                 if let Some(instruction) = TokenKind::is_instruction(instr) {
                     Token::new(instruction, initial_point)
                 } else {
-                    Token::new(TokenKind::Identifier(word), initial_point)
+                    let name = self.case.apply(&word).into_owned();
+                    Token::new(TokenKind::Identifier(name), initial_point)
                 }
             }
         }
@@ -157,7 +200,7 @@ impl Lexer {
                 is_terminated = true;
                 break;
             } else {
-                string.push(self.advance(iter));
+                string.extend(self.advance(iter));
             }
         }
 
@@ -169,22 +212,70 @@ impl Lexer {
         }
     }
 
-    /// This function produces a label or an error.
+    /// This function produces a char or an error. A char is a single character between backticks, such as `a`.
     ///
     /// # Arguments
     /// * `iter` - The iterator which contains all of the characters.
-    fn make_label(&mut self, iter: &mut Peekable<Chars>) -> Result<Token, Error> {
+    fn make_char(&mut self, iter: &mut Peekable<Chars>) -> Result<Token, Error> {
         let initial_point = self.current_position;
-        let mut label = String::new();
+        let mut chars = vec![];
         while let Some(ch) = iter.peek() {
-            if ch.is_ascii_whitespace() {
-                break;
-            } else {
-                label.push(self.advance(iter));
+            if ch == &'`' {
+                self.advance(iter);
+                return match chars[..] {
+                    [value] => Ok(Token::new(TokenKind::CharLiteral(value), initial_point)),
+                    _ => Err(Error::new(ErrorKind::InvalidChar, initial_point)),
+                };
             }
+
+            chars.extend(self.advance(iter));
         }
 
-        if label.is_empty() {
+        Err(Error::new(ErrorKind::InvalidChar, initial_point))
+    }
+
+    /// This function produces a label or an error.
+    /// The single line comments on the lines directly above the label become its documentation.
+    /// If the label is annotated, such as `@[pure] double`, the annotation is returned as a token of its own, and the name of the label follows it.
+    ///
+    /// # Arguments
+    /// * `doc_lines` - The lines of the comments directly above the label.
+    /// * `iter` - The iterator which contains all of the characters.
+    fn make_label(
+        &mut self,
+        doc_lines: Vec<String>,
+        iter: &mut Peekable<Chars>,
+    ) -> Result<(Option<Token>, Token), Error> {
+        let initial_point = self.current_position;
+        let annotation = if iter.peek() == Some(&'[') {
+            self.advance(iter);
+            let mut annotation = String::new();
+            loop {
+                match self.advance(iter) {
+                    Some(']') => break,
+                    Some(ch) if !ch.is_ascii_whitespace() => annotation.push(ch),
+                    _ => return Err(Error::new(ErrorKind::InvalidLabelName, initial_point)),
+                }
+            }
+
+            if annotation != PURE_ANNOTATION {
+                return Err(Error::new(
+                    ErrorKind::UnknownAnnotation(annotation),
+                    initial_point,
+                ));
+            }
+
+            while iter.peek().is_some_and(char::is_ascii_whitespace) {
+                self.advance(iter);
+            }
+
+            Some(Token::new(TokenKind::Annotation(annotation), initial_point))
+        } else {
+            None
+        };
+
+        let label = self.make_label_name(iter);
+        if label.is_empty() || (annotation.is_some() && label.starts_with('#')) {
             Err(Error::new(ErrorKind::InvalidLabelName, initial_point))
         } else {
             let mut parameters = vec![];
@@ -200,16 +291,47 @@ impl Lexer {
                 }
             }
 
-            Ok(Token::new(
-                TokenKind::Label(label, parameters),
-                initial_point,
+            let doc = if doc_lines.is_empty() {
+                None
+            } else {
+                Some(doc_lines.join("\n"))
+            };
+
+            let label = self.case.apply(&label).into_owned();
+            Ok((
+                annotation,
+                Token::new(TokenKind::Label(label, parameters, doc), initial_point),
             ))
         }
     }
 
+    /// This function reads the name of a label, which ends at the next whitespace.
+    ///
+    /// # Arguments
+    /// * `iter` - The iterator which contains all of the characters.
+    fn make_label_name(&mut self, iter: &mut Peekable<Chars>) -> String {
+        let mut label = String::new();
+        while let Some(ch) = iter.peek() {
+            if ch.is_ascii_whitespace() {
+                break;
+            } else {
+                label.extend(self.advance(iter));
+            }
+        }
+
+        label
+    }
+
     fn make_parameter(&mut self, iter: &mut Peekable<Chars>) -> Result<Parameter, Error> {
         let initial_point = self.current_position;
-        let ch = self.advance(iter);
+
+        // A parameter name must follow the '#' immediately, and it must start the same way as an identifier.
+        let ch = match iter.peek() {
+            Some(&ch) if ch.is_ascii_alphabetic() || ch == '_' => ch,
+            _ => return Err(Error::new(ErrorKind::InvalidParameterName, initial_point)),
+        };
+
+        self.advance(iter);
         let token = self.make_word(ch, iter);
         match token.kind {
             TokenKind::Identifier(ref name) => Ok(Parameter::new(initial_point, name.to_owned())),
@@ -241,16 +363,41 @@ impl Lexer {
     }
 
     /// This function handles single line comments.
+    /// A comment that starts its line is kept as a line of documentation, while a comment after a token discards the documentation.
     ///
     /// # Arguments
     /// * `iter` - The iterator which contains all of the characters.
     fn handle_single_line_comments(&mut self, iter: &mut Peekable<Chars>) {
         self.advance(iter);
+        let mut line = String::new();
         for c in iter {
             self.current_position += 1;
             if c == '\n' {
                 break;
             }
+
+            line.push(c);
+        }
+
+        if self.line_has_content {
+            self.doc_lines.clear();
+        } else {
+            let line = line.strip_prefix(' ').unwrap_or(&line);
+            self.doc_lines.push(line.trim_end().to_owned());
+        }
+
+        // The new line that ends the comment was consumed along with it.
+        self.line_has_content = false;
+    }
+
+    /// This function skips the shebang line at the start of a program, such as `#!/usr/bin/env dark`, so that scripts can be run directly on Unix.
+    /// The characters are still counted, so that the positions of the tokens after it stay correct. The new line is left for the lexer.
+    ///
+    /// # Arguments
+    /// * `iter` - The iterator which contains all of the characters.
+    fn skip_shebang(&mut self, iter: &mut Peekable<Chars>) {
+        while iter.next_if(|ch| *ch != '\n').is_some() {
+            self.current_position += 1;
         }
     }
 
@@ -259,6 +406,8 @@ impl Lexer {
     /// # Arguments
     /// * `iter` - The iterator which contains all of the characters.
     fn handle_multi_line_comments(&mut self, iter: &mut Peekable<Chars>) {
+        self.doc_lines.clear();
+        self.line_has_content = true;
         self.advance(iter);
         while let Some(c) = iter.next() {
             self.current_position += 1;
@@ -271,13 +420,30 @@ impl Lexer {
         }
     }
 
-    /// This function increments the current position and returns the next character.
-    /// The bounds check was already performed by the loops, so there is no need to return an option.
+    /// This function consumes the next character, increments the current position, and returns the character.
+    /// The loops peek at the character before they consume it, so None is only returned if there are no characters left, and nothing is consumed then.
     ///
     /// # Arguments
     /// * `iter` - The iterator which contains all of the characters.
-    fn advance(&mut self, iter: &mut Peekable<Chars>) -> char {
+    fn advance(&mut self, iter: &mut Peekable<Chars>) -> Option<char> {
+        let ch = iter.next()?;
         self.current_position += 1;
-        iter.next().unwrap()
+        self.handle_new_line(ch);
+        Some(ch)
+    }
+
+    /// This function starts a new line if the given character is a new line.
+    /// A blank line separates the comments above it from the label below it, so it discards the documentation.
+    ///
+    /// # Arguments
+    /// * `ch` - The character that was consumed.
+    fn handle_new_line(&mut self, ch: char) {
+        if ch == '\n' {
+            if !self.line_has_content {
+                self.doc_lines.clear();
+            }
+
+            self.line_has_content = false;
+        }
     }
 }
@@ -3,6 +3,25 @@
 //! The VM was created with zero-copy, speed, and simplicity in mind.
 //! This means that the implementation of the VM uses reference counted values, resembling Swift.
 //! Currently, the VM is highly experimental and may change, therefore writing programs in this language is not recommended.
+//!
+//! The public types are available from the crate root.
+//! A program is run in three phases, which are also available on their own, so that tools can stop after any of them:
+//! `lex` turns the source into tokens, `load` turns the tokens into verified code, and `execute` runs the code.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{Error, Lexer, VM};
+//! # fn run() -> Result<(), Error> {
+//! let tokens = Lexer::default().lex("@main push 1 end")?;
+//! let mut vm = VM::new(tokens)?;
+//! vm.run().into_result()?;
+//!
+//! let code = dark_vm::load(dark_vm::lex("@main push 1 end")?)?;
+//! dark_vm::execute(code.clone())?.into_result()?;
+//! assert_eq!(dark_vm::execute(code)?.exit_code, 0);
+//! # Ok(())
+//! # }
+//! ```
 
 /// The Tokens module, which contains the Token struct and the TokenKind enum. These describe the various tokens that can be recognized.
 pub mod tokens;
@@ -16,29 +35,164 @@ pub mod utils;
 /// The Lexer module, which creates a vector of all of the tokens in the input. This input may come from either a file or a REPL.
 pub mod lexer;
 
+/// The Instructions module, which contains the Opcode enum and the registry that describes every instruction.
+pub mod instructions;
+
 /// The Values module, which contains the Value struct and ValueKind enum. These describe the various values within the program.
 pub mod values;
 
 /// The Code module, which maintains the different values generated by the lexer.
 pub mod code;
 
+/// The Code Cache module, which contains the CodeCache struct. This keeps the code of programs that were already loaded, so that they are not lexed again.
+pub mod code_cache;
+
+/// The Bytecode module, which writes code to the bytecode format and reads it back, so that a program can be loaded without lexing it again.
+pub mod bytecode;
+
+/// The Lowering module, which lowers the structured pseudo-instructions, such as if and while, into relative jumps when a program is loaded.
+mod lowering;
+
 /// The VM module. This maintains most of the code for the behavior of different instructions and the behavior of the VM in general.
 pub mod vm;
 
-use lexer::Lexer;
-use vm::VM;
+/// The Verifier module, which checks that every instruction in a program has the arguments it expects before the program is run.
+pub mod verifier;
+
+/// The Type Checker module, which finds the instructions that would certainly fail because of the types of their operands, before the program is run.
+pub mod type_checker;
+
+/// The Call Graph module, which contains the CallGraph struct. This describes which labels call or jump into which other labels, without running the program.
+pub mod call_graph;
+
+/// The Builder module, which contains the VMBuilder struct. This configures the VM, such as its resource limits, before it is constructed.
+pub mod builder;
+
+/// The Debugger module, which contains the Debugger struct. This runs the VM one instruction at a time and pauses at breakpoints.
+pub mod debugger;
+
+/// The Fuzzing module, which contains the Program struct. This generates whole programs, so that the VM can be fuzzed.
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
+
+// The public types are re-exported at the crate root, so that embedders do not need to know the module tree.
+pub use builder::VMBuilder;
+pub use call_graph::CallGraph;
+pub use code::Code;
+pub use code_cache::CodeCache;
+pub use debugger::{Breakpoint, DebugEvent, Debugger};
+pub use errors::{diagnostic::Diagnostic, error::Error, error_kind::ErrorKind};
+pub use instructions::{Instruction, Opcode, INSTRUCTIONS};
+pub use lexer::Lexer;
+pub use tokens::{token::Token, token_kind::TokenKind};
+pub use utils::{
+    aliases::Aliases,
+    calling_convention::CallingConvention,
+    capabilities::Capabilities,
+    case::Case,
+    float_equality::FloatEquality,
+    fs::FileSystem,
+    io::VmIo,
+    limits::Limits,
+    log_level::LogLevel,
+    metrics::Metrics,
+    natives::{NativeFunction, Natives},
+    outcome::{RunOutcome, RunStats},
+    overflow::Overflow,
+    process::ProcessPolicy,
+    shadowing::Shadowing,
+    trace::Trace,
+};
+pub use values::{value::Value, value_kinds::ValueKind};
+pub use vm::{StepResult, VM};
+
+use std::{collections::VecDeque, sync::Arc};
+
+/// Lexes the source into tokens. This is the first phase of running a program.
+///
+/// # Arguments
+/// `source` - The contents of the program.
+pub fn lex(source: &str) -> Result<VecDeque<Token>, Error> {
+    Lexer::default().lex(source)
+}
+
+/// Loads the tokens into code and verifies it. This is the second phase of running a program.
+/// The code can be shared, so that the program can be executed many times without lexing or loading it again.
+///
+/// # Arguments
+/// `tokens` - The tokens produced by the lex function.
+pub fn load(tokens: VecDeque<Token>) -> Result<Arc<Code>, Error> {
+    let code = Code::new(tokens)?;
+    verifier::verify(&code)?;
+    Ok(Arc::new(code))
+}
+
+/// Executes the code with the default options, and produces the outcome of the run. This is the last phase of running a program.
+/// To execute the code with other options, use the build_shared method of the VMBuilder.
+///
+/// # Arguments
+/// `code` - The code produced by the load function.
+pub fn execute(code: Arc<Code>) -> Result<RunOutcome, Error> {
+    Ok(VM::from_code(code)?.run())
+}
 
 /// Runs the VM, and produces either an error, or the final state of the VM after the operations.
-/// The errors produced can be found in the utils::error::ErrorKind enum.
+/// The errors produced can be found in the errors::error_kind::ErrorKind enum.
 pub fn run(contents: &str) -> Result<String, String> {
-    let tokens = Lexer::default()
-        .lex(contents)
+    run_with(contents, VMBuilder::default())
+}
+
+/// Runs the VM with the options of the given builder, and produces either an error, or the final state of the VM after the operations.
+///
+/// # Arguments
+/// `contents` - The contents of the program.
+/// `builder` - The builder used to construct the VM.
+pub fn run_with(contents: &str, builder: VMBuilder) -> Result<String, String> {
+    let mut vm = lex(contents)
+        .and_then(load)
+        .and_then(|code| builder.build_shared(code))
         .map_err(|error| error.prettify(contents))?;
-    let mut vm = VM::new(tokens).map_err(|error| error.prettify(contents))?;
-    let result = vm.run().map_err(|error| error.prettify(contents))?;
+    let result = vm.run().result.map_err(|error| error.prettify(contents))?;
     if result.is_some() {
         println!("{:#?}\n", result);
     }
 
     Ok(format!("{:#?}", vm))
 }
+
+/// Runs the program with the default options, but captures its output in memory instead of writing it to stdout.
+/// This function produces the value of the last expression along with everything that the program printed, which is useful for testing programs.
+///
+/// # Arguments
+/// `source` - The contents of the program.
+///
+/// # Example
+/// ```
+/// # use dark_vm::Error;
+/// # fn run() -> Result<(), Error> {
+/// let (_, output) = dark_vm::run_captured("@main printn \"Hello\" end")?;
+/// assert_eq!(output, "Hello\n");
+/// # Ok(())
+/// # }
+/// ```
+pub fn run_captured(source: &str) -> Result<(Option<Arc<Value>>, String), Error> {
+    let outcome = VMBuilder::default()
+        .capture_output()
+        .build_shared(load(lex(source)?)?)?
+        .run();
+    let output = outcome.output.unwrap_or_default();
+    Ok((outcome.result?, output))
+}
+
+/// Lexes and loads the program without running it, and produces the diagnostics of any errors that were found.
+/// Currently, lexing and loading stop at the first error, so at most one diagnostic is produced.
+///
+/// # Arguments
+/// `contents` - The contents of the program.
+pub fn check(contents: &str) -> Vec<Diagnostic> {
+    let result = lex(contents).and_then(load);
+    match result {
+        Ok(_) => vec![],
+        Err(error) => vec![error.diagnose(contents)],
+    }
+}
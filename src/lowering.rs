@@ -0,0 +1,398 @@
+//! The lowering pass turns the structured pseudo-instructions into relative jumps before the code is constructed,
+//! so the VM itself only ever runs jumps. The pass works on tokens, and each lowered token keeps the position of the pseudo-instruction that produced it.
+//!
+//! The condition of every construct is removed from the stack on both paths, so the lowered forms are:
+//! - `if A else B endif` becomes `rjmpf x pop A rjmp y pop B`.
+//! - `if A endif` becomes `rjmpf x pop A rjmp y pop`.
+//! - `while C do A endwhile` becomes `C rjmpf x pop A rjmp z pop`, where z jumps back to the start of C.
+//! - `break` and `continue` become `rjmp` to the end of the innermost loop, or back to the start of its condition.
+//!
+//! Every construct must be closed in the same label that opened it, and the constructs must be nested properly.
+//!
+//! Lowering changes the number of tokens, so the jumps that the program wrote itself would land somewhere else afterwards.
+//! Every jump whose target is a literal int is moved, so that it lands on the lowered form of the token that it pointed to.
+//! A jump to a pseudo-instruction lands on the first token that it was lowered into, or on the token after it, if it was lowered into nothing.
+//!
+//! Before anything is lowered, the alias pseudo-instructions are removed, and every alias is replaced with the instruction that it stands for.
+
+use crate::{
+    errors::{error::Error, error_kind::ErrorKind},
+    instructions::Opcode,
+    tokens::{token::Token, token_kind::TokenKind},
+    utils::aliases::Aliases,
+};
+use std::{collections::VecDeque, convert::TryFrom, mem};
+
+/// The BlockKind enum describes the construct that a block was opened by, along with what is needed to finish lowering it.
+enum BlockKind {
+    /// An if, with the index of the jump that has to be patched once its target is known.
+    /// This is the jump of the if until the else is found, and the jump of the else afterwards.
+    If { jump: usize, has_else: bool },
+
+    /// A while, with the index of the first token of its condition, the index of the jump of its do once the do is found,
+    /// and the indices of the jumps of its breaks, which are patched once its endwhile is found.
+    While {
+        start: usize,
+        jump: Option<usize>,
+        breaks: Vec<usize>,
+    },
+}
+
+/// The Block struct maintains a construct whose closing pseudo-instruction has not been found yet.
+struct Block {
+    /// The position of the pseudo-instruction that opened the block.
+    pos: usize,
+
+    /// The number of labels that the block is nested in, which must be the same for the pseudo-instructions that continue or close it.
+    label_depth: usize,
+
+    /// The construct that opened the block.
+    kind: BlockKind,
+}
+
+/// The Jump struct maintains a jump that the program wrote itself, whose target is a literal int.
+struct Jump {
+    /// Whether the target is an offset, rather than an index.
+    relative: bool,
+
+    /// The index of the int that holds the target, before lowering.
+    offset: usize,
+
+    /// The index of the int that holds the target, after lowering.
+    lowered_offset: usize,
+}
+
+/// The Lowering struct maintains the tokens that have been lowered so far and the blocks that are still open.
+/// It also maintains where every token was lowered to, so that the jumps of the program can be moved afterwards.
+struct Lowering {
+    lowered: VecDeque<Token>,
+    blocks: Vec<Block>,
+    label_depth: usize,
+    indices: Vec<usize>,
+    jumps: Vec<Jump>,
+    pending_jump: Option<bool>,
+}
+
+/// This function resolves the aliases and lowers the structured pseudo-instructions into relative jumps.
+///
+/// # Arguments
+/// `tokens` - The tokens from the lexer.
+/// `aliases` - The aliases that were defined outside of the program, which the program may not define again.
+pub(crate) fn lower(tokens: VecDeque<Token>, aliases: &Aliases) -> Result<VecDeque<Token>, Error> {
+    let mut aliases = aliases.clone();
+    let tokens = aliases.take_definitions(tokens)?;
+    let tokens = aliases.resolve(tokens);
+    let mut lowering = Lowering {
+        lowered: VecDeque::with_capacity(tokens.len()),
+        blocks: vec![],
+        label_depth: 0,
+        indices: Vec::with_capacity(tokens.len() + 1),
+        jumps: vec![],
+        pending_jump: None,
+    };
+
+    for token in tokens {
+        lowering.indices.push(lowering.lowered.len());
+        lowering.lower_token(token)?;
+    }
+
+    match lowering.blocks.pop() {
+        Some(block) => Err(block.unterminated()),
+        None => {
+            lowering.indices.push(lowering.lowered.len());
+            lowering.remap_jumps();
+            Ok(lowering.lowered)
+        }
+    }
+}
+
+impl Block {
+    /// This function returns the error for a block that was never closed.
+    fn unterminated(&self) -> Error {
+        match self.kind {
+            BlockKind::If { .. } => Error::new(ErrorKind::UnterminatedIf, self.pos),
+            BlockKind::While { jump: None, .. } => Error::new(ErrorKind::MissingDo, self.pos),
+            BlockKind::While { .. } => Error::new(ErrorKind::UnterminatedWhile, self.pos),
+        }
+    }
+}
+
+impl Lowering {
+    /// This function lowers a single token.
+    ///
+    /// # Arguments
+    /// `token` - The token to lower.
+    fn lower_token(&mut self, token: Token) -> Result<(), Error> {
+        let pos = token.pos;
+        let pending_jump = self.pending_jump.take();
+        match token.kind {
+            TokenKind::Instruction(Opcode::If) => {
+                let jump = self.push_jump(Opcode::RelativeJumpIfFalse, pos);
+                self.push(Opcode::Pop, pos);
+                self.open(
+                    pos,
+                    BlockKind::If {
+                        jump,
+                        has_else: false,
+                    },
+                );
+            }
+            TokenKind::Instruction(Opcode::Else) => {
+                let (if_jump, has_else) = match self.innermost() {
+                    Some(Block {
+                        kind: BlockKind::If { jump, has_else },
+                        ..
+                    }) => (*jump, *has_else),
+                    _ => return Err(Error::new(ErrorKind::ElseWithoutIf, pos)),
+                };
+
+                if has_else {
+                    return Err(Error::new(ErrorKind::DuplicateElse, pos));
+                }
+
+                let else_jump = self.push_jump(Opcode::RelativeJump, pos);
+                self.patch(if_jump);
+                self.push(Opcode::Pop, pos);
+                if let Some(Block { kind, .. }) = self.innermost() {
+                    *kind = BlockKind::If {
+                        jump: else_jump,
+                        has_else: true,
+                    };
+                }
+            }
+            TokenKind::Instruction(Opcode::EndIf) => {
+                let (mut jump, has_else) = match self.innermost() {
+                    Some(Block {
+                        kind: BlockKind::If { jump, has_else },
+                        ..
+                    }) => (*jump, *has_else),
+                    _ => return Err(Error::new(ErrorKind::EndIfWithoutIf, pos)),
+                };
+
+                // Without an else, the false branch only has to remove the condition.
+                if !has_else {
+                    let else_jump = self.push_jump(Opcode::RelativeJump, pos);
+                    self.patch(jump);
+                    self.push(Opcode::Pop, pos);
+                    jump = else_jump;
+                }
+
+                self.patch(jump);
+                self.blocks.pop();
+            }
+            TokenKind::Instruction(Opcode::While) => {
+                let start = self.lowered.len();
+                self.open(
+                    pos,
+                    BlockKind::While {
+                        start,
+                        jump: None,
+                        breaks: vec![],
+                    },
+                );
+            }
+            TokenKind::Instruction(Opcode::Do) => {
+                match self.innermost() {
+                    Some(Block {
+                        kind: BlockKind::While { jump: None, .. },
+                        ..
+                    }) => {}
+                    _ => return Err(Error::new(ErrorKind::DoWithoutWhile, pos)),
+                }
+
+                let do_jump = self.push_jump(Opcode::RelativeJumpIfFalse, pos);
+                self.push(Opcode::Pop, pos);
+                if let Some(Block {
+                    kind: BlockKind::While { jump, .. },
+                    ..
+                }) = self.innermost()
+                {
+                    *jump = Some(do_jump);
+                }
+            }
+            TokenKind::Instruction(Opcode::EndWhile) => {
+                let (start, do_jump, breaks) = match self.innermost() {
+                    Some(Block {
+                        kind:
+                            BlockKind::While {
+                                start,
+                                jump,
+                                breaks,
+                            },
+                        pos: while_pos,
+                        ..
+                    }) => match jump {
+                        Some(jump) => (*start, *jump, mem::take(breaks)),
+                        None => return Err(Error::new(ErrorKind::MissingDo, *while_pos)),
+                    },
+                    _ => return Err(Error::new(ErrorKind::EndWhileWithoutWhile, pos)),
+                };
+
+                // The jump back to the condition is measured from the value after its offset, like every relative jump.
+                let back_jump = self.push_jump(Opcode::RelativeJump, pos);
+                self.lowered[back_jump].kind =
+                    TokenKind::IntegerLiteral(start as i64 - back_jump as i64);
+                self.patch(do_jump);
+                self.push(Opcode::Pop, pos);
+
+                // The condition has already been removed when a break is reached, so breaks land after the pop.
+                for break_jump in breaks {
+                    self.patch(break_jump);
+                }
+
+                self.blocks.pop();
+            }
+            TokenKind::Instruction(Opcode::Break) => {
+                let break_jump = self.push_jump(Opcode::RelativeJump, pos);
+                match self.innermost_loop() {
+                    Some(BlockKind::While { breaks, .. }) => breaks.push(break_jump),
+                    _ => return Err(Error::new(ErrorKind::BreakOutsideLoop, pos)),
+                }
+            }
+            TokenKind::Instruction(Opcode::Continue) => {
+                let start = match self.innermost_loop() {
+                    Some(BlockKind::While { start, .. }) => *start,
+                    _ => return Err(Error::new(ErrorKind::ContinueOutsideLoop, pos)),
+                };
+
+                let continue_jump = self.push_jump(Opcode::RelativeJump, pos);
+                self.lowered[continue_jump].kind =
+                    TokenKind::IntegerLiteral(start as i64 - continue_jump as i64);
+            }
+            TokenKind::Label(..) => {
+                self.label_depth += 1;
+                self.lowered.push_back(token);
+            }
+            TokenKind::End => {
+                if let Some(block) = self.innermost() {
+                    return Err(block.unterminated());
+                }
+
+                self.label_depth = self.label_depth.saturating_sub(1);
+                self.lowered.push_back(token);
+            }
+            TokenKind::Instruction(
+                opcode @ (Opcode::Jump
+                | Opcode::JumpIfTrue
+                | Opcode::JumpIfFalse
+                | Opcode::RelativeJump
+                | Opcode::RelativeJumpIfTrue
+                | Opcode::RelativeJumpIfFalse),
+            ) => {
+                self.pending_jump = Some(matches!(
+                    opcode,
+                    Opcode::RelativeJump | Opcode::RelativeJumpIfTrue | Opcode::RelativeJumpIfFalse
+                ));
+                self.lowered.push_back(token);
+            }
+            TokenKind::IntegerLiteral(_) => {
+                if let Some(relative) = pending_jump {
+                    self.jumps.push(Jump {
+                        relative,
+                        offset: self.indices.len() - 1,
+                        lowered_offset: self.lowered.len(),
+                    });
+                }
+
+                self.lowered.push_back(token);
+            }
+            _ => self.lowered.push_back(token),
+        }
+
+        Ok(())
+    }
+
+    /// This function moves the jumps of the program, so that each one lands on the lowered form of the token that it pointed to.
+    /// A relative jump lands its offset past the value after the offset, so its target is counted from the offset.
+    /// Targets outside of the program are left alone, so that they are still reported when the jump is run.
+    fn remap_jumps(&mut self) {
+        for jump in mem::take(&mut self.jumps) {
+            let target = match &self.lowered[jump.lowered_offset].kind {
+                TokenKind::IntegerLiteral(target) if jump.relative => {
+                    target.checked_add(jump.offset as i64)
+                }
+                TokenKind::IntegerLiteral(target) => Some(*target),
+                _ => None,
+            };
+
+            let lowered_target = match target
+                .and_then(|target| usize::try_from(target).ok())
+                .and_then(|target| self.indices.get(target))
+            {
+                Some(lowered_target) => *lowered_target as i64,
+                None => continue,
+            };
+
+            self.lowered[jump.lowered_offset].kind = TokenKind::IntegerLiteral(if jump.relative {
+                lowered_target - jump.lowered_offset as i64
+            } else {
+                lowered_target
+            });
+        }
+    }
+
+    /// This function returns the innermost block, if it was opened in the current label.
+    fn innermost(&mut self) -> Option<&mut Block> {
+        let label_depth = self.label_depth;
+        self.blocks
+            .last_mut()
+            .filter(|block| block.label_depth == label_depth)
+    }
+
+    /// This function returns the innermost loop whose body is being lowered, if it was opened in the current label.
+    /// The ifs inside of the loop are skipped, since their conditions have already been removed from the stack.
+    fn innermost_loop(&mut self) -> Option<&mut BlockKind> {
+        let label_depth = self.label_depth;
+        self.blocks
+            .iter_mut()
+            .rev()
+            .take_while(|block| block.label_depth == label_depth)
+            .map(|block| &mut block.kind)
+            .find(|kind| matches!(kind, BlockKind::While { jump: Some(_), .. }))
+    }
+
+    /// This function opens a new block in the current label.
+    ///
+    /// # Arguments
+    /// `pos` - The position of the pseudo-instruction that opens the block.
+    /// `kind` - The construct that opens the block.
+    fn open(&mut self, pos: usize, kind: BlockKind) {
+        self.blocks.push(Block {
+            pos,
+            label_depth: self.label_depth,
+            kind,
+        });
+    }
+
+    /// This function pushes an instruction that takes no arguments.
+    ///
+    /// # Arguments
+    /// `opcode` - The instruction to push.
+    /// `pos` - The position of the pseudo-instruction that produced it.
+    fn push(&mut self, opcode: Opcode, pos: usize) {
+        self.lowered
+            .push_back(Token::new(TokenKind::Instruction(opcode), pos));
+    }
+
+    /// This function pushes a relative jump whose offset is patched later, and returns the index of the offset.
+    ///
+    /// # Arguments
+    /// `opcode` - The relative jump to push.
+    /// `pos` - The position of the pseudo-instruction that produced it.
+    fn push_jump(&mut self, opcode: Opcode, pos: usize) -> usize {
+        self.push(opcode, pos);
+        self.lowered
+            .push_back(Token::new(TokenKind::IntegerLiteral(0), pos));
+        self.lowered.len() - 1
+    }
+
+    /// This function points the relative jump whose offset is at the given index to the next token that is lowered.
+    /// A relative jump lands its offset past the value after the offset.
+    ///
+    /// # Arguments
+    /// `jump` - The index of the offset of the jump.
+    fn patch(&mut self, jump: usize) {
+        let offset = (self.lowered.len() - jump) as i64;
+        self.lowered[jump].kind = TokenKind::IntegerLiteral(offset);
+    }
+}
@@ -1,40 +1,189 @@
-/// The Arguments module, which holds all of the arguments to the program.
-pub mod arguments;
+/// The CLI module, which holds the arguments to the program.
+pub mod cli;
 
-use arguments::Arguments;
-use dark_vm::run;
-use std::{fs, time::Instant};
+use cli::{
+    arguments::{Arguments, Command},
+    languages::Language,
+};
+use dark_vm::{bytecode, utils::timeline::Timeline};
+use std::{collections::VecDeque, fs, sync::Arc, time::Instant};
 
 fn main() {
-    if let Err(error) = runner() {
-        println!("{}", error)
+    // Errors go to stderr, and the process fails, so that scripts and CI can tell that the program did not succeed.
+    match runner() {
+        Ok(exit_code) => std::process::exit(exit_code),
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
     }
 }
 
-fn runner() -> Result<(), String> {
-    let args = Arguments::new().map_err(|error| error.prettify(""))?;
-    if args.get_path().is_none() {
-        generate_error("The REPL Is Not Yet Supported.")
-    } else if let Some(path) = args.get_path().filter(|path| path.ends_with(".dark")) {
-        let contents = fs::read_to_string(path)
-            .map_err(|_| "An Error Occurred.\nThe Path Provided Is Not Valid.".to_owned())?;
+/// Runs the command given on the command line, and returns the exit code of the process.
+fn runner() -> Result<i32, String> {
+    let mut args = Arguments::new().map_err(|error| error.prettify(""))?;
+    args.load_aliases()?;
+    if args.get_command() == &Command::Dap {
+        cli::dap::run(args.get_port(), args.builder()).map(|_| 0)
+    } else if args.get_command() == &Command::Lsp {
+        cli::lsp::run().map(|_| 0)
+    } else if args.get_command() == &Command::Serve {
+        let address = args.get_listen_address().to_owned();
+        let args = Arc::new(args);
+        cli::serve::run(&address, move || args.builder()).map(|_| 0)
+    } else if args.get_command() == &Command::Kernel {
+        let path = args.get_path().ok_or_else(|| {
+            "An Error Occurred.\nThe Kernel Needs The Path Of The Connection File That Jupyter Passes To It.".to_owned()
+        })?;
+        cli::kernel::run(path, args.builder()).map(|_| 0)
+    } else if args.get_command() == &Command::Instructions {
+        cli::instructions::run();
+        Ok(0)
+    } else if let Some(path) = args.get_path() {
+        let invalid_path = || "An Error Occurred.\nThe Path Provided Is Not Valid.".to_owned();
+        let bytes = fs::read(path).map_err(|_| invalid_path())?;
+        let language = Language::detect(path, &bytes, args.get_language())?;
+
+        // Bytecode is loaded without lexing it. The source that it carries is only used to point errors into it.
+        let start = Instant::now();
+        let (contents, compiled) = match language {
+            Language::Dark => (String::from_utf8(bytes).map_err(|_| invalid_path())?, None),
+            Language::Bytecode => {
+                let compiled = bytecode::read(&bytes).map_err(|error| error.prettify(""))?;
+                (compiled.source.unwrap_or_default(), Some(compiled.code))
+            }
+        };
+        let decoding_time = start.elapsed();
+
+        if args.get_command() == &Command::Disasm {
+            return cli::disasm::run(&contents, compiled, args.get_aliases()).map(|_| 0);
+        } else if compiled.is_some()
+            && (args.get_command() != &Command::Run || args.get_compile_path().is_some())
+        {
+            return Err(format!(
+                "An Error Occurred.\n'{}' Is Already Compiled, So It Can Only Be Run Or Disassembled. The Other Commands Need The Source Of The Program.",
+                path
+            ));
+        }
+
+        if let Some(output) = args.get_compile_path() {
+            return cli::compile::run(&contents, output, args.get_aliases()).map(|_| 0);
+        } else if args.get_command() == &Command::Debug {
+            return cli::debug::run(&contents, args.builder()).map(|_| 0);
+        } else if args.get_command() == &Command::Xref {
+            return cli::xref::run(&contents, args.get_aliases()).map(|_| 0);
+        } else if args.get_command() == &Command::Doc {
+            return cli::doc::run(path, &contents, args.get_doc_format(), args.get_aliases())
+                .map(|_| 0);
+        } else if args.get_command() == &Command::Profile {
+            return cli::profile::run(
+                &contents,
+                args.builder(),
+                args.get_sample(),
+                args.get_folded_path(),
+            )
+            .map(|_| 0);
+        }
+
+        // Each phase is timed separately, so that it is clear whether startup or execution dominates.
         let start = Instant::now();
-        match run(&contents) {
-            Ok(vm) if args.show_machine() => println!("{}", vm),
+        let mut builder = args.builder();
+        let tokens = match compiled {
+            Some(_) => VecDeque::new(),
+            None => builder
+                .lexer()
+                .lex(&contents)
+                .map_err(|error| error.prettify(&contents))?,
+        };
+        let lexing_time = start.elapsed();
+
+        let start = Instant::now();
+        let trace = args.trace().map_err(|error| error.prettify(""))?;
+        if let Some(trace) = &trace {
+            builder = builder.trace(trace.clone());
+        }
+
+        let mut vm = match compiled {
+            Some(code) => builder.build_shared(Arc::new(code)),
+            None => builder.build(tokens),
+        }
+        .map_err(|error| error.prettify(&contents))?;
+        let loading_time = decoding_time + start.elapsed();
+        if args.dump_code() {
+            print!("{}", vm.get_code());
+        }
+
+        let start = Instant::now();
+        let mut timeline = Timeline::default();
+        let (result, warnings, exit_code) = if args.show_diff() {
+            let result = cli::diff::run(&mut vm, &contents);
+            let exit_code = i32::from(result.is_err());
+            (result, vm.take_warnings(), exit_code)
+        } else if args.get_timeline_path().is_some() {
+            let result = timeline.run(&mut vm);
+            let exit_code = i32::from(result.is_err());
+            (result, vm.take_warnings(), exit_code)
+        } else {
+            let outcome = vm.run();
+            (outcome.result, outcome.warnings, outcome.exit_code)
+        };
+        let execution_time = start.elapsed();
+
+        // The warnings go to stderr, so that they can be separated from the output of the program.
+        for warning in warnings {
+            eprint!("A Warning Occurred.\n{}", warning.prettify(&contents));
+        }
+
+        // The metrics are printed even if the program failed, because the error is counted as well.
+        if args.show_metrics() {
+            print!("{}", vm.get_metrics().to_prometheus());
+        }
+
+        // The trace is saved even if the program failed, because that is usually the run that has to be reproduced.
+        if let (Some(trace), Some(path)) = (&trace, args.get_record_path()) {
+            fs::write(path, trace.to_json()).map_err(|error| {
+                format!(
+                    "An Error Occurred.\nThe Trace Could Not Be Saved To '{}': {}.",
+                    path, error
+                )
+            })?;
+        }
+
+        // Like the trace, the timeline is saved even if the program failed, so that the failure can be stepped through.
+        if let Some(path) = args.get_timeline_path() {
+            fs::write(path, timeline.to_json(&contents)).map_err(|error| {
+                format!(
+                    "An Error Occurred.\nThe Timeline Could Not Be Saved To '{}': {}.",
+                    path, error
+                )
+            })?;
+        }
+
+        // The error of the program is printed like any other error, but the process exits with the exit code of the program.
+        // Like the metrics, the machine and the timings are still shown, because they describe the run that failed.
+        match result {
+            Ok(result) if result.is_some() => println!("{:#?}\n", result),
             Ok(_) => {}
-            Err(error) => return Err(error),
+            Err(error) => eprintln!("{}", error.prettify(&contents)),
+        }
+
+        if args.show_machine() {
+            print!("{}", vm);
         }
 
         if args.show_time() {
-            println!("Time Taken: {:#?}", start.elapsed())
+            println!("Lexing Time: {:#?}", lexing_time);
+            println!("Loading Time: {:#?}", loading_time);
+            println!("Execution Time: {:#?}", execution_time);
+            println!(
+                "Time Taken: {:#?}",
+                lexing_time + loading_time + execution_time
+            );
+            println!("Instructions Executed: {}", vm.get_instruction_count());
         }
 
-        Ok(())
+        Ok(exit_code)
     } else {
-        generate_error("Expected The File Passed In To Be An Dark File.")
+        cli::repl::run(args.builder()).map(|_| 0)
     }
 }
-
-fn generate_error(error_message: &str) -> Result<(), String> {
-    Err(format!("An Error Occurred.\n{}", error_message))
-}
@@ -4,7 +4,8 @@
 
 use super::token_kind::TokenKind;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Token {
     pub kind: TokenKind,
     pub pos: usize,
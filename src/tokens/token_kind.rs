@@ -1,44 +1,26 @@
 //! The TokenKind enum maintains all of the different Tokens that could occur within the program.
 //! Using an enum allows for easy extensibility.
 
-use crate::utils::parameter::Parameter;
+use crate::{instructions::Opcode, utils::parameter::Parameter};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum TokenKind {
     Void,
+    Null,
     Any,
     IntegerLiteral(i64),
     FloatLiteral(f64),
     BooleanLiteral(bool),
     StringLiteral(String),
+    CharLiteral(char),
     Identifier(String),
-    Label(String, Vec<Parameter>),
+    /// A label, with its name, its parameters, and the documentation from the comments directly above it.
+    Label(String, Vec<Parameter>, Option<String>),
+    /// An annotation of the label that directly follows it, such as `pure` in `@[pure] double`.
+    Annotation(String),
     End,
-
-    Push,
-    Pop,
-    Peek,
-    Add,
-    Sub,
-    Mul,
-    Div,
-    Mod,
-    LessThan,
-    LessThanEqual,
-    GreaterThan,
-    GreaterThanEqual,
-    Equal,
-    NotEqual,
-    Jump,
-    RelativeJump,
-    JumpIfTrue,
-    JumpIfFalse,
-    RelativeJumpIfTrue,
-    RelativeJumpIfFalse,
-    Print,
-    PrintNewLine,
-    Set,
-    Call,
+    Instruction(Opcode),
 }
 
 impl TokenKind {
@@ -48,33 +30,6 @@ impl TokenKind {
     /// # Arguments
     /// `name` - The name of the current word.
     pub fn is_instruction(name: &str) -> Option<TokenKind> {
-        match name.to_ascii_lowercase().as_str() {
-            "push" => Some(TokenKind::Push),
-            "pop" => Some(TokenKind::Pop),
-            "peek" => Some(TokenKind::Peek),
-            "add" => Some(TokenKind::Add),
-            "sub" => Some(TokenKind::Sub),
-            "mul" => Some(TokenKind::Mul),
-            "div" => Some(TokenKind::Div),
-            "mod" => Some(TokenKind::Mod),
-            "lt" => Some(TokenKind::LessThan),
-            "lte" => Some(TokenKind::LessThanEqual),
-            "gt" => Some(TokenKind::GreaterThan),
-            "gte" => Some(TokenKind::GreaterThanEqual),
-            "eq" => Some(TokenKind::Equal),
-            "neq" => Some(TokenKind::NotEqual),
-            "jmp" => Some(TokenKind::Jump),
-            "rjmp" => Some(TokenKind::RelativeJump),
-            "jmpt" => Some(TokenKind::JumpIfTrue),
-            "jmpf" => Some(TokenKind::JumpIfFalse),
-            "rjmpt" => Some(TokenKind::RelativeJumpIfTrue),
-            "rjmpf" => Some(TokenKind::RelativeJumpIfFalse),
-            "print" => Some(TokenKind::Print),
-            "printn" => Some(TokenKind::PrintNewLine),
-            "set" => Some(TokenKind::Set),
-            "call" => Some(TokenKind::Call),
-
-            _ => None,
-        }
+        Opcode::from_name(name).map(TokenKind::Instruction)
     }
 }
@@ -0,0 +1,582 @@
+//! The type checker finds the instructions that would certainly fail because of the types of their operands, before the program is run.
+//! It follows the types of the values through each label, both on the operand stack and in variables, and reports instructions such as
+//! the add in `push true push 1 printn add`, which can only produce an UnsupportedOperation error when it is reached.
+//!
+//! The checker is optional, and it only reports errors that are guaranteed. Wherever the type of a value is not known,
+//! such as after a call, or where a jump lands, nothing is reported. Each operation is checked by running it on a sample value of each type,
+//! so the checker always agrees with the VM about which types an operation supports.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{code::Code, errors::error::Error, lexer::Lexer, type_checker};
+//! # fn run() -> Result<(), Error> {
+//! let code = Code::new(Lexer::default().lex("@main push true push 1 printn add end")?)?;
+//! assert_eq!(type_checker::check_types(&code)?.len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    code::Code,
+    errors::{error::Error, error_kind::ErrorKind},
+    instructions::Opcode,
+    utils::{float_equality::FloatEquality, natives::Natives, overflow::Overflow},
+    values::{value::Value, value_kinds::ValueKind},
+    verifier,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The Type enum describes the types of the values that the checker can know about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Void,
+    Null,
+    Int,
+    Float,
+    Boolean,
+    String,
+    Char,
+    Bytes,
+    Array,
+    Map,
+}
+
+impl Type {
+    /// This function gets the type of the given kind of value.
+    /// Names, labels, and instructions have no type of their own, so None is returned for them.
+    ///
+    /// # Arguments
+    /// `kind` - The kind of the value.
+    pub fn of(kind: &ValueKind) -> Option<Type> {
+        match kind {
+            ValueKind::Void => Some(Type::Void),
+            ValueKind::Null => Some(Type::Null),
+            ValueKind::Int(_) => Some(Type::Int),
+            ValueKind::Float(_) => Some(Type::Float),
+            ValueKind::Boolean(_) => Some(Type::Boolean),
+            ValueKind::String(_) => Some(Type::String),
+            ValueKind::Char(_) => Some(Type::Char),
+            ValueKind::Bytes(_) => Some(Type::Bytes),
+            ValueKind::Array(_) => Some(Type::Array),
+            ValueKind::Map(_) => Some(Type::Map),
+            _ => None,
+        }
+    }
+
+    /// This function creates a value of this type, which the operations are run on to find out whether they support the type.
+    /// The numbers are 1, so that no operation fails because of the value itself, such as a division by zero.
+    ///
+    /// # Arguments
+    /// `pos` - The position of the value.
+    fn sample(self, pos: usize) -> Value {
+        let kind = match self {
+            Type::Void => ValueKind::Void,
+            Type::Null => ValueKind::Null,
+            Type::Int => ValueKind::Int(1),
+            Type::Float => ValueKind::Float(1.0),
+            Type::Boolean => ValueKind::Boolean(true),
+            Type::String => ValueKind::String("a".to_owned()),
+            Type::Char => ValueKind::Char('a'),
+            Type::Bytes => ValueKind::Bytes(vec![1]),
+            Type::Array => ValueKind::Array(vec![]),
+            Type::Map => ValueKind::Map(BTreeMap::new()),
+        };
+
+        Value::new(pos, kind)
+    }
+}
+
+/// Checks the types of the operands of every instruction in the code, and returns the errors that would certainly occur when the instructions are reached.
+/// The errors are the same errors that the VM would produce, and they are returned in the order that they appear in the program.
+/// If the code can not be verified, the error of the verifier is returned instead.
+///
+/// # Arguments
+/// `code` - The code to check, which has already been verified.
+pub fn check_types(code: &Code) -> Result<Vec<Error>, Error> {
+    check_types_with_natives(code, &Natives::default())
+}
+
+/// Checks the types of the operands of every instruction in the code, where the call instructions may also call the given native functions.
+/// The type of the value that a native function returns is not known.
+///
+/// # Arguments
+/// `code` - The code to check, which has already been verified.
+/// `natives` - The native functions that the host registered.
+pub fn check_types_with_natives(code: &Code, natives: &Natives) -> Result<Vec<Error>, Error> {
+    let labels = code
+        .get_labels_in_order()
+        .into_iter()
+        .map(|(_, label)| label);
+
+    // The types are forgotten wherever a jump lands, because the types on the other paths are not known.
+    // If a jump can not be followed, it may land anywhere, so the types are forgotten before every statement.
+    let mut bodies = vec![];
+    let mut targets = BTreeSet::new();
+    let mut computed = false;
+    for label in labels {
+        let statements = verifier::get_statements(code, natives, label)?;
+        for (start, end) in &statements {
+            match verifier::get_successors(code, *start, *end) {
+                Some(successors) if is_jump(&code.get_values()[*start].kind) => {
+                    targets.extend(successors.first().copied());
+                }
+                Some(_) => {}
+                None => computed = true,
+            }
+        }
+
+        bodies.push(statements);
+    }
+
+    let mut checker = Checker {
+        code,
+        natives,
+        stack: vec![],
+        variables: HashMap::new(),
+        errors: vec![],
+    };
+
+    for statements in bodies {
+        checker.forget();
+        for start in statements.into_keys() {
+            if computed || targets.contains(&start) {
+                checker.forget();
+            }
+
+            checker.infer(start)?;
+
+            // Nothing falls through an unconditional jump, so the statement after it is only reached by another jump.
+            if matches!(
+                code.get_values()[start].kind,
+                ValueKind::Instruction(Opcode::Jump) | ValueKind::Instruction(Opcode::RelativeJump)
+            ) {
+                checker.forget();
+            }
+        }
+    }
+
+    checker
+        .errors
+        .sort_by_key(|error| error.get_position().unwrap_or_default());
+    Ok(checker.errors)
+}
+
+/// This function determines whether the given kind of value is one of the jump instructions.
+///
+/// # Arguments
+/// `kind` - The kind of the value.
+fn is_jump(kind: &ValueKind) -> bool {
+    matches!(
+        kind,
+        ValueKind::Instruction(Opcode::Jump)
+            | ValueKind::Instruction(Opcode::RelativeJump)
+            | ValueKind::Instruction(Opcode::JumpIfTrue)
+            | ValueKind::Instruction(Opcode::JumpIfFalse)
+            | ValueKind::Instruction(Opcode::RelativeJumpIfTrue)
+            | ValueKind::Instruction(Opcode::RelativeJumpIfFalse)
+    )
+}
+
+/// The Checker struct maintains what is known about the types of the values while a label is checked.
+struct Checker<'a> {
+    /// The code being checked.
+    code: &'a Code,
+
+    /// The native functions that the call instructions may call.
+    natives: &'a Natives,
+
+    /// The types of the values on the top of the operand stack. The values below them are not known.
+    stack: Vec<Option<Type>>,
+
+    /// The types of the variables that are known.
+    variables: HashMap<String, Type>,
+
+    /// The errors that were found.
+    errors: Vec<Error>,
+}
+
+impl Checker<'_> {
+    /// Forgets the types of every value on the stack and of every variable.
+    fn forget(&mut self) {
+        self.stack.clear();
+        self.variables.clear();
+    }
+
+    /// Pops the type of the value on the top of the stack, which is None if it is not known.
+    fn pop(&mut self) -> Option<Type> {
+        self.stack.pop().flatten()
+    }
+
+    /// Finds the type of the expression that starts at the given index, applying its effects on the stack and the variables.
+    /// This function returns the index after the expression, along with the type of its result, if it is known.
+    ///
+    /// # Arguments
+    /// `idx` - The index of the first value of the expression.
+    fn infer(&mut self, idx: usize) -> Result<(usize, Option<Type>), Error> {
+        let code = self.code;
+        let value = &code.get_values()[idx];
+        let opcode = match &value.kind {
+            ValueKind::Identifier(name) => return Ok((idx + 1, self.variables.get(name).copied())),
+            ValueKind::Instruction(opcode) => *opcode,
+            kind => return Ok((idx + 1, Type::of(kind))),
+        };
+
+        let pos = value.pos;
+        let overflow = Overflow::default();
+        match opcode {
+            Opcode::Push => {
+                let (next, arg) = self.infer_defined(idx + 1)?;
+                self.stack.push(arg);
+                Ok((next, Some(Type::Void)))
+            }
+            Opcode::Pop => Ok((idx + 1, self.pop())),
+            Opcode::Peek => Ok((idx + 1, self.stack.last().copied().flatten())),
+            Opcode::Add => Ok((
+                idx + 1,
+                self.apply_stack(pos, |a, b| a.add(b, overflow, pos)),
+            )),
+            Opcode::Sub => Ok((
+                idx + 1,
+                self.apply_stack(pos, |a, b| a.sub(b, overflow, pos)),
+            )),
+            Opcode::Mul => Ok((
+                idx + 1,
+                self.apply_stack(pos, |a, b| a.mul(b, overflow, pos)),
+            )),
+            Opcode::Div => Ok((idx + 1, self.apply_stack(pos, |a, b| a.div(b, pos)))),
+            Opcode::IntegerDivide => Ok((
+                idx + 1,
+                self.apply_stack(pos, |a, b| a.int_div(b, overflow, pos)),
+            )),
+            Opcode::Mod => Ok((
+                idx + 1,
+                self.apply_stack(pos, |a, b| a.modulus(b, overflow, pos)),
+            )),
+            // The remainder is only computed once the quotient is, and both are ints.
+            Opcode::DivideModulo => {
+                let quotient = self.apply_stack(pos, |a, b| a.int_div(b, overflow, pos));
+                self.stack.push(quotient);
+                self.stack.push(quotient);
+                Ok((idx + 1, Some(Type::Void)))
+            }
+            Opcode::BitAnd => Ok((idx + 1, self.apply_stack(pos, |a, b| a.bit_and(b, pos)))),
+            Opcode::BitOr => Ok((idx + 1, self.apply_stack(pos, |a, b| a.bit_or(b, pos)))),
+            Opcode::BitXor => Ok((idx + 1, self.apply_stack(pos, |a, b| a.bit_xor(b, pos)))),
+            Opcode::ShiftLeft => Ok((idx + 1, self.apply_stack(pos, |a, b| a.shift_left(b, pos)))),
+            Opcode::ShiftRight => {
+                Ok((idx + 1, self.apply_stack(pos, |a, b| a.shift_right(b, pos))))
+            }
+            Opcode::BitNot => Ok((idx + 1, self.apply_unary(pos, |a| a.bit_not(pos)))),
+            Opcode::Negate => Ok((idx + 1, self.apply_unary(pos, |a| a.neg(overflow, pos)))),
+            Opcode::Absolute => Ok((idx + 1, self.apply_unary(pos, |a| a.abs(overflow, pos)))),
+            Opcode::StackLessThan => Ok((idx + 1, self.apply_stack(pos, |a, b| a.lt(b, pos)))),
+            Opcode::StackLessThanEqual => {
+                Ok((idx + 1, self.apply_stack(pos, |a, b| a.lte(b, pos))))
+            }
+            Opcode::StackGreaterThan => Ok((idx + 1, self.apply_stack(pos, |a, b| a.gt(b, pos)))),
+            Opcode::StackGreaterThanEqual => {
+                Ok((idx + 1, self.apply_stack(pos, |a, b| a.gte(b, pos))))
+            }
+            Opcode::StackEqual | Opcode::StackNotEqual => {
+                self.pop();
+                self.pop();
+                Ok((idx + 1, Some(Type::Boolean)))
+            }
+            Opcode::LessThan => self.apply_inline(idx, |a, b| a.lt(b, pos)),
+            Opcode::LessThanEqual => self.apply_inline(idx, |a, b| a.lte(b, pos)),
+            Opcode::GreaterThan => self.apply_inline(idx, |a, b| a.gt(b, pos)),
+            Opcode::GreaterThanEqual => self.apply_inline(idx, |a, b| a.gte(b, pos)),
+            Opcode::Equal | Opcode::NotEqual => {
+                self.apply_inline(idx, |a, b| Ok(a.equal(b, FloatEquality::default(), pos)))
+            }
+            Opcode::LessThanIgnoreCase => self.apply_inline(idx, |a, b| a.lt_ignore_case(b, pos)),
+            Opcode::EqualIgnoreCase => self.apply_inline(idx, |a, b| a.equal_ignore_case(b, pos)),
+            // Every value has a truthiness, so the result is a boolean whatever the types of the arguments are.
+            Opcode::And | Opcode::Or => {
+                let (next, _) = self.infer_defined(idx + 1)?;
+                let (next, _) = self.infer_defined(next)?;
+                Ok((next, Some(Type::Boolean)))
+            }
+            Opcode::Not => {
+                let (next, _) = self.infer_defined(idx + 1)?;
+                Ok((next, Some(Type::Boolean)))
+            }
+            Opcode::Length => {
+                let arg_pos = code.get_values()[idx + 1].pos;
+                let (next, arg) = self.infer(idx + 1)?;
+                match arg {
+                    Some(Type::String) | Some(Type::Array) | Some(Type::Map)
+                    | Some(Type::Bytes) | None => {}
+                    Some(arg) => self.errors.push(Error::new(
+                        ErrorKind::ValueMismatch(
+                            "String, Array, Map, Or Bytes".to_owned(),
+                            arg.sample(arg_pos).kind.get_value_name(),
+                        ),
+                        arg_pos,
+                    )),
+                }
+
+                Ok((next, Some(Type::Int)))
+            }
+            Opcode::Ordinal | Opcode::Character | Opcode::CharAt | Opcode::Chars => {
+                let mut next = idx + 1;
+                for _ in 0..opcode.get_instruction().arity {
+                    next = self.infer_defined(next)?.0;
+                }
+
+                let result = match opcode {
+                    Opcode::Ordinal => Type::Int,
+                    Opcode::Chars => Type::Array,
+                    _ => Type::Char,
+                };
+                Ok((next, Some(result)))
+            }
+            Opcode::Array => Ok((idx + 1, Some(Type::Array))),
+            Opcode::ArrayGet
+            | Opcode::ArraySet
+            | Opcode::ArrayPush
+            | Opcode::ArrayPop
+            | Opcode::ArrayLength => self.infer_container(idx, opcode, Type::Array),
+            Opcode::Map => Ok((idx + 1, Some(Type::Map))),
+            Opcode::MapGet
+            | Opcode::MapSet
+            | Opcode::MapDelete
+            | Opcode::MapHas
+            | Opcode::MapKeys => self.infer_container(idx, opcode, Type::Map),
+            Opcode::StringLength
+            | Opcode::Substring
+            | Opcode::StringConcat
+            | Opcode::Uppercase
+            | Opcode::Lowercase => self.infer_container(idx, opcode, Type::String),
+            Opcode::Print
+            | Opcode::PrintNewLine
+            | Opcode::ErrorPrint
+            | Opcode::ErrorPrintNewLine => {
+                let (next, _) = self.infer_defined(idx + 1)?;
+                Ok((next, Some(Type::Void)))
+            }
+            Opcode::Assert | Opcode::AssertEqual => {
+                let mut next = idx + 1;
+                for _ in 0..opcode.get_instruction().arity {
+                    next = self.infer_defined(next)?.0;
+                }
+
+                Ok((next, Some(Type::Void)))
+            }
+            Opcode::Set => {
+                let (next, arg) = self.infer(idx + 2)?;
+                if let ValueKind::Identifier(name) = &code.get_values()[idx + 1].kind {
+                    match arg {
+                        Some(arg) => self.variables.insert(name.to_owned(), arg),
+                        None => self.variables.remove(name),
+                    };
+                }
+
+                Ok((next, Some(Type::Void)))
+            }
+            Opcode::IncrementVariable | Opcode::DecrementVariable => {
+                let (next, _) = self.infer(idx + 2)?;
+                if let ValueKind::Identifier(name) = &code.get_values()[idx + 1].kind {
+                    self.variables.remove(name);
+                }
+
+                Ok((next, None))
+            }
+            Opcode::SwapVariables => {
+                let names = (
+                    &code.get_values()[idx + 1].kind,
+                    &code.get_values()[idx + 2].kind,
+                );
+                if let (ValueKind::Identifier(first), ValueKind::Identifier(second)) = names {
+                    let first_type = self.variables.remove(first);
+                    let second_type = self.variables.remove(second);
+                    self.variables
+                        .extend(first_type.map(|kind| (second.to_owned(), kind)));
+                    self.variables
+                        .extend(second_type.map(|kind| (first.to_owned(), kind)));
+                }
+
+                Ok((idx + 3, None))
+            }
+            // The variables of a block disappear at its end, and the variables that they shadowed come back.
+            Opcode::EndBlock => {
+                self.variables.clear();
+                Ok((idx + 1, None))
+            }
+            // The labels that are called share the stack, and their arguments may not be evaluated, so everything is forgotten.
+            Opcode::Call
+            | Opcode::CallIfTrue
+            | Opcode::CallIfFalse
+            | Opcode::Recurse
+            | Opcode::Times
+            | Opcode::Match => {
+                let next = verifier::skip_arguments(code, self.natives, idx, 1, pos)?;
+                self.forget();
+                Ok((next, None))
+            }
+            _ => {
+                let instruction = opcode.get_instruction();
+                let mut next = idx + 1;
+                for _ in 0..instruction.arity {
+                    next = self.infer(next)?.0;
+                }
+
+                for _ in 0..instruction.pops {
+                    self.pop();
+                }
+
+                self.stack
+                    .extend(std::iter::repeat_n(None, instruction.pushes));
+                Ok((next, None))
+            }
+        }
+    }
+
+    /// Finds the types of the arguments of an instruction whose first argument is an array, a map, or a string, and returns the type of its result.
+    /// If the first argument is certainly not of the given type, the error that the VM would produce is reported.
+    ///
+    /// # Arguments
+    /// `idx` - The index of the instruction.
+    /// `opcode` - The opcode of the instruction.
+    /// `container` - The type of the first argument, which is Array, Map, or String.
+    fn infer_container(
+        &mut self,
+        idx: usize,
+        opcode: Opcode,
+        container: Type,
+    ) -> Result<(usize, Option<Type>), Error> {
+        let arg_pos = self.code.get_values()[idx + 1].pos;
+        let (mut next, arg) = self.infer(idx + 1)?;
+        match arg {
+            Some(arg) if arg != container => self.errors.push(Error::new(
+                ErrorKind::ValueMismatch(
+                    container.sample(arg_pos).kind.get_value_name(),
+                    arg.sample(arg_pos).kind.get_value_name(),
+                ),
+                arg_pos,
+            )),
+            _ => {}
+        }
+
+        for _ in 1..opcode.get_instruction().arity {
+            next = self.infer_defined(next)?.0;
+        }
+
+        let result = match opcode {
+            Opcode::ArrayLength | Opcode::StringLength => Some(Type::Int),
+            Opcode::ArraySet | Opcode::ArrayPush | Opcode::MapKeys => Some(Type::Array),
+            Opcode::Substring | Opcode::StringConcat | Opcode::Uppercase | Opcode::Lowercase => {
+                Some(Type::String)
+            }
+            Opcode::MapSet | Opcode::MapDelete => Some(Type::Map),
+            Opcode::MapHas => Some(Type::Boolean),
+            Opcode::ArrayPop => {
+                self.stack.push(Some(Type::Array));
+                self.stack.push(None);
+                Some(Type::Void)
+            }
+            _ => None,
+        };
+
+        Ok((next, result))
+    }
+
+    /// Finds the type of the argument at the given index, like the infer method.
+    /// The argument must produce a value, so if its result is void, the error that the VM would produce is reported.
+    ///
+    /// # Arguments
+    /// `idx` - The index of the argument.
+    fn infer_defined(&mut self, idx: usize) -> Result<(usize, Option<Type>), Error> {
+        let arg_pos = self.code.get_values()[idx].pos;
+        let (next, arg) = self.infer(idx)?;
+        if arg != Some(Type::Void) {
+            return Ok((next, arg));
+        }
+
+        self.errors.push(Error::new(
+            ErrorKind::ValueMismatch(
+                ValueKind::Any.get_value_name(),
+                ValueKind::Void.get_value_name(),
+            ),
+            arg_pos,
+        ));
+        Ok((next, None))
+    }
+
+    /// Pops the types of the two values on the top of the stack, and applies the operation to them, with the first value popped as the left operand.
+    /// This function returns the type of the result, if it is known.
+    ///
+    /// # Arguments
+    /// `pos` - The position of the instruction.
+    /// `operation` - The operation of the instruction.
+    fn apply_stack<F>(&mut self, pos: usize, operation: F) -> Option<Type>
+    where
+        F: Fn(&Value, &Value) -> Result<Value, Error>,
+    {
+        let first = self.pop();
+        let second = self.pop();
+        self.apply(first?, second?, pos, operation)
+    }
+
+    /// Pops the type of the operand of an instruction that takes one value from the stack, and applies the operation to it.
+    ///
+    /// # Arguments
+    /// `pos` - The position of the instruction.
+    /// `operation` - The operation of the instruction.
+    fn apply_unary<F>(&mut self, pos: usize, operation: F) -> Option<Type>
+    where
+        F: Fn(&Value) -> Result<Value, Error>,
+    {
+        // The operand is passed twice, since the operation only looks at the first.
+        let operand = self.pop()?;
+        self.apply(operand, operand, pos, |a, _| operation(a))
+    }
+
+    /// Finds the types of the two inline arguments of the instruction at the given index, and applies the operation to them.
+    /// This function returns the index after the instruction, along with the type of the result, if it is known.
+    ///
+    /// # Arguments
+    /// `idx` - The index of the instruction.
+    /// `operation` - The operation of the instruction.
+    fn apply_inline<F>(&mut self, idx: usize, operation: F) -> Result<(usize, Option<Type>), Error>
+    where
+        F: Fn(&Value, &Value) -> Result<Value, Error>,
+    {
+        let pos = self.code.get_values()[idx].pos;
+        let (next, first) = self.infer_defined(idx + 1)?;
+        let (next, second) = self.infer_defined(next)?;
+        let result = match (first, second) {
+            (Some(first), Some(second)) => self.apply(first, second, pos, operation),
+            _ => None,
+        };
+
+        Ok((next, result))
+    }
+
+    /// Runs the operation on sample values of the given types, and returns the type of the result.
+    /// If the operation does not support the types, its error is reported. Any other error depends on the values themselves, so it is ignored.
+    ///
+    /// # Arguments
+    /// `first` - The type of the left operand.
+    /// `second` - The type of the right operand.
+    /// `pos` - The position of the instruction.
+    /// `operation` - The operation of the instruction.
+    fn apply<F>(&mut self, first: Type, second: Type, pos: usize, operation: F) -> Option<Type>
+    where
+        F: Fn(&Value, &Value) -> Result<Value, Error>,
+    {
+        match operation(&first.sample(pos), &second.sample(pos)) {
+            Ok(result) => Type::of(&result.kind),
+            Err(error) => {
+                if matches!(
+                    error.get_kind(),
+                    ErrorKind::UnsupportedOperation(_, _) | ErrorKind::ValueMismatch(_, _)
+                ) {
+                    self.errors.push(error);
+                }
+
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! The Aliases struct maintains the mnemonics that stand for instructions, such as `say` for `printn`.
+//! A program defines an alias with the alias pseudo-instruction, `alias say printn`, which is removed when the program is loaded.
+//! Every use of the alias in the program, before or after its definition, is then read as the instruction.
+//! An alias may also stand for another alias that was defined before it.
+//!
+//! The aliases that every program should have can be kept in a file of alias pseudo-instructions, which is parsed on its own and passed to the VMBuilder.
+//! A program may not define an alias again, whether it was defined by the program or by the file.
+//! An alias may not be the name of an instruction, and because it stands for an instruction everywhere, it can not be the name of a variable or a label either.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{builder::VMBuilder, errors::error::Error, lexer::Lexer, utils::aliases::Aliases};
+//! # fn run() -> Result<(), Error> {
+//! let aliases = Aliases::parse("alias say printn")?;
+//! let tokens = Lexer::default().lex("@main say 'Hello' end")?;
+//! VMBuilder::default().aliases(aliases).build(tokens)?.run();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    errors::{error::Error, error_kind::ErrorKind},
+    instructions::Opcode,
+    lexer::Lexer,
+    tokens::{token::Token, token_kind::TokenKind},
+};
+use std::collections::{BTreeMap, VecDeque};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Aliases {
+    names: BTreeMap<String, Opcode>,
+}
+
+impl Aliases {
+    /// This function parses a file of alias pseudo-instructions. Anything else in the file is an error.
+    ///
+    /// # Arguments
+    /// `contents` - The contents of the file.
+    pub fn parse(contents: &str) -> Result<Aliases, Error> {
+        let mut aliases = Aliases::default();
+        let rest = aliases.take_definitions(Lexer::default().lex(contents)?)?;
+        match rest.front() {
+            Some(token) => Err(Error::new(ErrorKind::ExpectedAlias, token.pos)),
+            None => Ok(aliases),
+        }
+    }
+
+    /// This function defines the alias, so that it stands for the instruction.
+    /// An error is returned if the alias is the name of an instruction, or if it was already defined.
+    ///
+    /// # Arguments
+    /// `name` - The alias.
+    /// `opcode` - The instruction that the alias stands for.
+    /// `pos` - The position of the alias, for the errors.
+    pub fn define(&mut self, name: &str, opcode: Opcode, pos: usize) -> Result<(), Error> {
+        if Opcode::from_name(name).is_some() {
+            return Err(Error::new(
+                ErrorKind::AliasIsInstruction(name.to_owned()),
+                pos,
+            ));
+        }
+
+        if self.names.insert(name.to_owned(), opcode).is_some() {
+            return Err(Error::new(ErrorKind::DuplicateAlias(name.to_owned()), pos));
+        }
+
+        Ok(())
+    }
+
+    /// This function returns the instruction that the alias stands for, if it was defined.
+    ///
+    /// # Arguments
+    /// `name` - The alias.
+    pub fn get(&self, name: &str) -> Option<Opcode> {
+        self.names.get(name).copied()
+    }
+
+    /// This function returns every alias with the instruction that it stands for, sorted by the alias.
+    pub fn get_names(&self) -> Vec<(&str, Opcode)> {
+        self.names
+            .iter()
+            .map(|(name, opcode)| (name.as_str(), *opcode))
+            .collect()
+    }
+
+    /// This function returns whether no aliases are defined.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// This function defines the aliases of the alias pseudo-instructions in the tokens, and returns the tokens without them.
+    ///
+    /// # Arguments
+    /// `tokens` - The tokens from the lexer.
+    pub(crate) fn take_definitions(
+        &mut self,
+        tokens: VecDeque<Token>,
+    ) -> Result<VecDeque<Token>, Error> {
+        let mut rest = VecDeque::with_capacity(tokens.len());
+        let mut tokens = tokens.into_iter();
+        while let Some(token) = tokens.next() {
+            if token.kind != TokenKind::Instruction(Opcode::Alias) {
+                rest.push_back(token);
+                continue;
+            }
+
+            let name = match tokens.next() {
+                Some(Token {
+                    kind: TokenKind::Identifier(name),
+                    pos,
+                }) => (name, pos),
+                Some(Token {
+                    kind: TokenKind::Instruction(opcode),
+                    pos,
+                }) => {
+                    return Err(Error::new(
+                        ErrorKind::AliasIsInstruction(opcode.get_name().to_owned()),
+                        pos,
+                    ))
+                }
+                _ => return Err(Error::new(ErrorKind::ExpectedAlias, token.pos)),
+            };
+
+            // The instruction may itself be an alias, as long as it was defined first.
+            let opcode = match tokens.next().map(|target| target.kind) {
+                Some(TokenKind::Instruction(opcode)) if opcode != Opcode::Alias => Some(opcode),
+                Some(TokenKind::Identifier(target)) => self.get(&target),
+                _ => None,
+            }
+            .ok_or_else(|| Error::new(ErrorKind::ExpectedAlias, token.pos))?;
+
+            self.define(&name.0, opcode, name.1)?;
+        }
+
+        Ok(rest)
+    }
+
+    /// This function replaces every identifier that is an alias with the instruction that it stands for.
+    ///
+    /// # Arguments
+    /// `tokens` - The tokens to resolve.
+    pub(crate) fn resolve(&self, tokens: VecDeque<Token>) -> VecDeque<Token> {
+        if self.is_empty() {
+            return tokens;
+        }
+
+        tokens
+            .into_iter()
+            .map(|mut token| {
+                if let TokenKind::Identifier(name) = &token.kind {
+                    if let Some(opcode) = self.get(name) {
+                        token.kind = TokenKind::Instruction(opcode);
+                    }
+                }
+
+                token
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,37 @@
+//! The CallingConvention enum describes how the labels that are called share the operand stack with their caller.
+//! By default, every label shares a single stack, so a label can pop the values that its caller pushed, even the ones that were pushed for other purposes.
+//! With isolated stacks, every call starts with a stack of its own that holds its arguments, with the first argument at the bottom.
+//! When the label ends, the values left on its stack are pushed on to the stack of its caller, in the same order, so they are the results of the call.
+
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CallingConvention {
+    /// Every label shares the stack of its caller.
+    #[default]
+    Shared,
+
+    /// Every call has a stack of its own, which is seeded with its arguments.
+    Isolated,
+}
+
+impl FromStr for CallingConvention {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "shared" => Ok(CallingConvention::Shared),
+            "isolated" => Ok(CallingConvention::Isolated),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for CallingConvention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallingConvention::Shared => write!(f, "shared"),
+            CallingConvention::Isolated => write!(f, "isolated"),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! The Capabilities struct describes what a program is allowed to do outside of the VM.
+//! Every instruction that has an effect on, or observes, the outside world checks its capability before it does anything,
+//! and returns an error if the capability was not granted. Every capability is denied by default,
+//! so embedders grant exactly what a program may do, and untrusted programs can do nothing.
+//!
+//! The capabilities are:
+//! - The file system, which is required by the readfile, writefile, and appendfile instructions. It may be restricted to a root directory.
+//! - The network, which is required by the fetch instruction.
+//! - The environment, which is required by the env instruction.
+//! - Process execution, which is required by the exec instruction. It may be restricted to a list of commands.
+//! - The clock, which is required by the now instruction.
+
+use super::{fs::FileSystem, process::ProcessPolicy};
+use crate::errors::{error::Error, error_kind::ErrorKind};
+
+#[derive(Debug, Default, Clone)]
+pub struct Capabilities {
+    pub fs: Option<FileSystem>,
+    pub net: bool,
+    pub env: bool,
+    pub exec: Option<ProcessPolicy>,
+    pub clock: bool,
+}
+
+impl Capabilities {
+    /// Constructs a new Capabilities, which grants every capability without any restrictions.
+    /// This should only be used for programs that are trusted.
+    pub fn all() -> Capabilities {
+        Capabilities {
+            fs: Some(FileSystem::default()),
+            net: true,
+            env: true,
+            exec: Some(ProcessPolicy::default()),
+            clock: true,
+        }
+    }
+
+    /// This function returns the file system, or an error if the file system capability was not granted.
+    ///
+    /// # Arguments
+    /// `instruction` - The name of the instruction that requires the capability.
+    /// `pos` - The position where the instruction was called.
+    pub(crate) fn get_fs(&self, instruction: &str, pos: usize) -> Result<&FileSystem, Error> {
+        self.fs
+            .as_ref()
+            .ok_or_else(|| denied(instruction, "File System", pos))
+    }
+
+    /// This function returns the process policy, or an error if the process execution capability was not granted.
+    ///
+    /// # Arguments
+    /// `instruction` - The name of the instruction that requires the capability.
+    /// `pos` - The position where the instruction was called.
+    pub(crate) fn get_exec(&self, instruction: &str, pos: usize) -> Result<&ProcessPolicy, Error> {
+        self.exec
+            .as_ref()
+            .ok_or_else(|| denied(instruction, "Process Execution", pos))
+    }
+
+    /// This function returns an error if the network capability was not granted.
+    ///
+    /// # Arguments
+    /// `instruction` - The name of the instruction that requires the capability.
+    /// `pos` - The position where the instruction was called.
+    pub(crate) fn check_net(&self, instruction: &str, pos: usize) -> Result<(), Error> {
+        check(self.net, instruction, "Network", pos)
+    }
+
+    /// This function returns an error if the environment capability was not granted.
+    ///
+    /// # Arguments
+    /// `instruction` - The name of the instruction that requires the capability.
+    /// `pos` - The position where the instruction was called.
+    pub(crate) fn check_env(&self, instruction: &str, pos: usize) -> Result<(), Error> {
+        check(self.env, instruction, "Environment", pos)
+    }
+
+    /// This function returns an error if the clock capability was not granted.
+    ///
+    /// # Arguments
+    /// `instruction` - The name of the instruction that requires the capability.
+    /// `pos` - The position where the instruction was called.
+    pub(crate) fn check_clock(&self, instruction: &str, pos: usize) -> Result<(), Error> {
+        check(self.clock, instruction, "Clock", pos)
+    }
+}
+
+/// This function returns an error if the capability was not granted.
+///
+/// # Arguments
+/// `granted` - Whether the capability was granted.
+/// `instruction` - The name of the instruction that requires the capability.
+/// `capability` - The name of the capability.
+/// `pos` - The position where the instruction was called.
+fn check(granted: bool, instruction: &str, capability: &str, pos: usize) -> Result<(), Error> {
+    if granted {
+        Ok(())
+    } else {
+        Err(denied(instruction, capability, pos))
+    }
+}
+
+/// This function creates the error for a capability that was not granted.
+///
+/// # Arguments
+/// `instruction` - The name of the instruction that requires the capability.
+/// `capability` - The name of the capability.
+/// `pos` - The position where the instruction was called.
+fn denied(instruction: &str, capability: &str, pos: usize) -> Error {
+    Error::new(
+        ErrorKind::CapabilityDenied(instruction.to_owned(), capability.to_owned()),
+        pos,
+    )
+}
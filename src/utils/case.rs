@@ -0,0 +1,99 @@
+//! The Case enum describes whether identifiers that differ only by case, such as `count` and `Count`, are the same.
+//! Instructions and keywords are always matched regardless of case, but identifiers are strict by default, which is a common source of confusion.
+//! When identifiers are folded, the lexer lowers the case of the names of variables, labels, and parameters,
+//! and the stores of the VM lower the case of every name they are given, so that the host can look up variables with any case as well.
+//!
+//! In strict mode, the identifiers that differ only by case are reported as warnings when the code is loaded.
+
+use crate::{
+    code::Code,
+    errors::{error::Error, error_kind::ErrorKind},
+    values::value_kinds::ValueKind,
+};
+use std::{borrow::Cow, collections::HashMap, fmt, str::FromStr};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    /// Identifiers are compared exactly, so `count` and `Count` are different variables.
+    #[default]
+    Strict,
+
+    /// Identifiers are lowered, so `count` and `Count` are the same variable.
+    Fold,
+}
+
+impl Case {
+    /// This function returns the name that this policy uses for the given identifier.
+    ///
+    /// # Arguments
+    /// `name` - The name of the identifier.
+    pub fn apply(self, name: &str) -> Cow<'_, str> {
+        match self {
+            Case::Strict => Cow::Borrowed(name),
+            Case::Fold => Cow::Owned(name.to_lowercase()),
+        }
+    }
+}
+
+impl FromStr for Case {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "strict" => Ok(Case::Strict),
+            "fold" => Ok(Case::Fold),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Case::Strict => write!(f, "strict"),
+            Case::Fold => write!(f, "fold"),
+        }
+    }
+}
+
+/// This function finds the identifiers of the code that differ from an earlier identifier only by case, which usually means that one of them is misspelled.
+/// Each spelling is reported once, at its first use, and the error points back to the first use of the other spelling.
+///
+/// # Arguments
+/// `code` - The code to check.
+pub fn check_identifiers(code: &Code) -> Vec<Error> {
+    let parameters = code
+        .get_labels()
+        .values()
+        .flat_map(|label| &label.parameters)
+        .map(|parameter| (parameter.name.as_str(), parameter.pos));
+    let identifiers = code
+        .get_values()
+        .iter()
+        .filter_map(|value| match &value.kind {
+            ValueKind::Identifier(name) => Some((name.as_str(), value.pos)),
+            _ => None,
+        });
+
+    let mut names = parameters.chain(identifiers).collect::<Vec<_>>();
+    names.sort_by_key(|(_, pos)| *pos);
+
+    let mut first_uses: HashMap<String, (&str, usize)> = HashMap::new();
+    let mut reported = vec![];
+    let mut warnings = vec![];
+    for (name, pos) in names {
+        let (first_name, first_pos) = *first_uses.entry(name.to_lowercase()).or_insert((name, pos));
+        if first_name != name && !reported.contains(&name) {
+            reported.push(name);
+            warnings.push(
+                Error::new(
+                    ErrorKind::IdentifierCaseMismatch(name.to_owned(), first_name.to_owned()),
+                    pos,
+                )
+                .with_related(first_pos, "First Used Here."),
+            );
+        }
+    }
+
+    warnings
+}
@@ -0,0 +1,51 @@
+//! The parse function converts CSV text into the values of the VM. It is used by the csvparse instruction.
+//! Every field is a string, because CSV does not describe the types of its fields.
+//!
+//! If the text has a header, every row becomes a map from the header to the field, and every row must have as many fields as the header.
+//! Otherwise, every row becomes an array of fields, and the rows may have different lengths.
+
+use crate::{
+    errors::{error::Error, error_kind::ErrorKind},
+    values::{value::Value, value_kinds::ValueKind},
+};
+use std::sync::Arc;
+
+/// Parses the given CSV text into an array of rows.
+///
+/// # Arguments
+/// `text` - The CSV text.
+/// `has_header` - Whether the first row is the header.
+/// `pos` - The position of the new values, which is also used for errors.
+pub fn parse(text: &str, has_header: bool, pos: usize) -> Result<Value, Error> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .has_headers(has_header)
+        .flexible(!has_header)
+        .from_reader(text.as_bytes());
+    let csv_error = |error: ::csv::Error| Error::new(ErrorKind::InvalidCsv(error.to_string()), pos);
+    let string = |field: &str| Arc::new(Value::new(pos, ValueKind::String(field.to_owned())));
+
+    let header = if has_header {
+        Some(reader.headers().map_err(csv_error)?.clone())
+    } else {
+        None
+    };
+
+    let mut rows = vec![];
+    for record in reader.records() {
+        let record = record.map_err(csv_error)?;
+        let row = match &header {
+            Some(header) => ValueKind::Map(
+                header
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(key, field)| (key.to_owned(), string(field)))
+                    .collect(),
+            ),
+            None => ValueKind::Array(record.iter().map(string).collect()),
+        };
+
+        rows.push(Arc::new(Value::new(pos, row)));
+    }
+
+    Ok(Value::new(pos, ValueKind::Array(rows)))
+}
@@ -0,0 +1,81 @@
+//! The format and parse functions convert between Unix timestamps and dates. They are used by the dateformat and dateparse instructions.
+//! A timestamp is the number of seconds since the Unix epoch, and dates are always in UTC.
+//! The formats use the strftime syntax of the chrono crate, such as `%Y-%m-%d %H:%M:%S`.
+
+use crate::errors::{error::Error, error_kind::ErrorKind};
+use chrono::{
+    format::{ParseError, ParseErrorKind},
+    DateTime, NaiveDate, NaiveDateTime,
+};
+use std::{
+    fmt::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// This function returns the current time as a Unix timestamp.
+pub fn now() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs() as i64,
+        Err(error) => -(error.duration().as_secs() as i64),
+    }
+}
+
+/// Formats the given timestamp as a date in UTC.
+///
+/// # Arguments
+/// `timestamp` - The number of seconds since the Unix epoch.
+/// `format` - The format of the date.
+/// `pos` - The position used for errors.
+pub fn format(timestamp: i64, format: &str, pos: usize) -> Result<String, Error> {
+    let date = DateTime::from_timestamp(timestamp, 0).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidDate(format!("The Timestamp {} Is Out Of Range", timestamp)),
+            pos,
+        )
+    })?;
+
+    // An invalid format is only detected while the date is written, so the error has to be caught here instead of panicking.
+    let mut text = String::new();
+    write!(text, "{}", date.format(format)).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidDate(format!("'{}' Is Not A Valid Format", format)),
+            pos,
+        )
+    })?;
+    Ok(text)
+}
+
+/// Parses the given text as a date and returns its timestamp.
+/// If the format has an offset, it is used. Otherwise, the date is in UTC, and a date without a time is at midnight.
+///
+/// # Arguments
+/// `text` - The text to parse.
+/// `format` - The format of the date.
+/// `pos` - The position used for errors.
+pub fn parse(text: &str, format: &str, pos: usize) -> Result<i64, Error> {
+    // Every form of the date is tried, and the most specific error is reported if none of them match.
+    let mut reason: Option<ParseError> = None;
+    let mut record = |error: ParseError| {
+        if reason.is_none() || error.kind() != ParseErrorKind::NotEnough {
+            reason = Some(error);
+        }
+    };
+
+    match DateTime::parse_from_str(text, format) {
+        Ok(date) => return Ok(date.timestamp()),
+        Err(error) => record(error),
+    }
+
+    match NaiveDateTime::parse_from_str(text, format) {
+        Ok(date) => return Ok(date.and_utc().timestamp()),
+        Err(error) => record(error),
+    }
+
+    match NaiveDate::parse_from_str(text, format) {
+        Ok(date) => return Ok(date.and_time(Default::default()).and_utc().timestamp()),
+        Err(error) => record(error),
+    }
+
+    let reason = reason.map_or_else(String::new, |reason| reason.to_string());
+    Err(Error::new(ErrorKind::InvalidDate(reason), pos))
+}
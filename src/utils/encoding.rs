@@ -0,0 +1,108 @@
+//! The encoding functions convert bytes to and from base64 and hexadecimal text.
+//! They are used by the b64encode, b64decode, hexencode, and hexdecode instructions.
+
+/// The standard base64 alphabet.
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The hexadecimal digits, in lowercase.
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Encodes the bytes with the standard base64 alphabet. The output is always padded.
+///
+/// # Arguments
+/// `bytes` - The bytes to encode.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (idx, byte)| {
+            group | (u32::from(*byte) << (16 - idx * 8))
+        });
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                let digit = (group >> (18 - idx * 6)) & 0x3F;
+                encoded.push(char::from(BASE64_ALPHABET[digit as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Decodes text in the standard base64 alphabet. The padding is optional, but if it is present, it must be correct.
+/// If the text is not valid base64, None is returned.
+///
+/// # Arguments
+/// `text` - The text to decode.
+pub fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.as_bytes();
+    let unpadded = match text.iter().position(|byte| byte == &b'=') {
+        Some(idx) if text.len().is_multiple_of(4) && text.len() - idx <= 2 => {
+            if text[idx..].iter().any(|byte| byte != &b'=') {
+                return None;
+            }
+
+            &text[..idx]
+        }
+        Some(_) => return None,
+        None => text,
+    };
+
+    // A single character can not hold a whole byte.
+    if unpadded.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(unpadded.len() / 4 * 3 + 2);
+    for chunk in unpadded.chunks(4) {
+        let mut group = 0u32;
+        for (idx, byte) in chunk.iter().enumerate() {
+            let digit = BASE64_ALPHABET.iter().position(|digit| digit == byte)? as u32;
+            group |= digit << (18 - idx * 6);
+        }
+
+        for idx in 0..chunk.len() - 1 {
+            decoded.push((group >> (16 - idx * 8)) as u8);
+        }
+    }
+
+    Some(decoded)
+}
+
+/// Encodes the bytes as lowercase hexadecimal.
+///
+/// # Arguments
+/// `bytes` - The bytes to encode.
+pub fn hex_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        encoded.push(char::from(HEX_DIGITS[usize::from(byte >> 4)]));
+        encoded.push(char::from(HEX_DIGITS[usize::from(byte & 0xF)]));
+    }
+
+    encoded
+}
+
+/// Decodes hexadecimal text. Both uppercase and lowercase digits are accepted.
+/// If the text is not valid hexadecimal, None is returned.
+///
+/// # Arguments
+/// `text` - The text to decode.
+pub fn hex_decode(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+
+    let digits = text
+        .chars()
+        .map(|ch| ch.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<Vec<_>>>()?;
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| (pair[0] << 4) | pair[1])
+            .collect(),
+    )
+}
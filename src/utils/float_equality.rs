@@ -0,0 +1,79 @@
+//! The FloatEquality enum describes how the equality instructions compare two floats.
+//! Comparing against a fixed epsilon is wrong for large and small magnitudes, so floats are compared by their distance
+//! in units in the last place (ULPs) by default, which scales with the magnitude of the floats.
+//! A relative tolerance can be used instead, and the strict mode compares the bits of the floats exactly.
+
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatEquality {
+    /// The floats are equal if there are at most this many representable floats between them.
+    Ulps(u64),
+
+    /// The floats are equal if their difference is at most this fraction of the larger magnitude.
+    Relative(f64),
+
+    /// The floats are equal only if their bits are identical. This means that 0.0 and -0.0 are different, and that NaN may equal itself.
+    Bitwise,
+}
+
+/// The number of ULPs that floats may differ by in the default mode. This absorbs the rounding of a few arithmetic operations.
+const DEFAULT_ULPS: u64 = 4;
+
+impl FloatEquality {
+    /// This function returns whether the two floats are equal in this mode.
+    /// Except in the bitwise mode, NaN is never equal to anything, and 0.0 is equal to -0.0.
+    ///
+    /// # Arguments
+    /// `first` - The first float.
+    /// `second` - The second float.
+    pub fn equal(self, first: f64, second: f64) -> bool {
+        match self {
+            FloatEquality::Bitwise => first.to_bits() == second.to_bits(),
+            _ if first == second => true,
+            _ if first.is_nan() || second.is_nan() => false,
+            FloatEquality::Ulps(max_ulps) => {
+                // Floats of the same sign are ordered like their bits, so the difference of the bits counts the floats between them.
+                first.is_sign_negative() == second.is_sign_negative()
+                    && first.to_bits().abs_diff(second.to_bits()) <= max_ulps
+            }
+            FloatEquality::Relative(tolerance) => {
+                (first - second).abs() <= tolerance * first.abs().max(second.abs())
+            }
+        }
+    }
+}
+
+impl Default for FloatEquality {
+    fn default() -> Self {
+        FloatEquality::Ulps(DEFAULT_ULPS)
+    }
+}
+
+/// Parses the mode from text, such as "ulps:4", "relative:1e-9", or "bitwise".
+impl FromStr for FloatEquality {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.to_ascii_lowercase();
+        match text.split_once(':') {
+            Some(("ulps", ulps)) => ulps.parse().map(FloatEquality::Ulps).map_err(|_| ()),
+            Some(("relative", tolerance)) => match tolerance.parse::<f64>() {
+                Ok(tolerance) if tolerance >= 0.0 => Ok(FloatEquality::Relative(tolerance)),
+                _ => Err(()),
+            },
+            None if text == "bitwise" => Ok(FloatEquality::Bitwise),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for FloatEquality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FloatEquality::Ulps(ulps) => write!(f, "ulps:{}", ulps),
+            FloatEquality::Relative(tolerance) => write!(f, "relative:{}", tolerance),
+            FloatEquality::Bitwise => write!(f, "bitwise"),
+        }
+    }
+}
@@ -1,15 +1,53 @@
 //! The Frame strut maintains information about the current frame.
 //! This includes caller position, parameters, and local variables.
 
-use super::store::Store;
+use super::{case::Case, store::Store};
 use crate::{errors::error::Error, values::value::Value};
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, sync::Arc};
 
 #[derive(Debug, PartialEq)]
 pub struct Frame {
     caller_position: usize,
     pub name: String,
     pub current_store: Rc<RefCell<Store>>,
+
+    /// The progress of the label, if it was called by the times instruction.
+    pub repeat: Option<Repeat>,
+
+    /// The number of blocks that have been entered with the begin instruction and not yet left.
+    blocks: usize,
+
+    /// The operand stack of the caller, which is set aside while the label runs if every call has a stack of its own.
+    pub(crate) caller_stack: Option<Vec<Arc<Value>>>,
+
+    /// The position in the source where the label was called. The main frame is never called, so it has none.
+    pub(crate) call_pos: Option<usize>,
+
+    /// The labels that run when this frame exits, with the positions of their names, in the order that they were deferred.
+    pub(crate) deferred: Vec<(String, usize)>,
+}
+
+/// The FrameEntry struct describes a single frame that was on the call stack when a runtime error occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameEntry {
+    /// The name of the label of the frame.
+    pub name: String,
+
+    /// The position in the source where the label was called, if it was called.
+    pub call_pos: Option<usize>,
+}
+
+/// The Repeat struct maintains the progress of a label that is called many times by the times instruction.
+#[derive(Debug, PartialEq)]
+pub struct Repeat {
+    /// The number of the current iteration, starting from 0.
+    pub iteration: u64,
+
+    /// The number of times that the label runs in total.
+    pub count: u64,
+
+    /// The name of the parameter that receives the number of the iteration, if the label defines one.
+    pub parameter: Option<String>,
 }
 
 impl Frame {
@@ -20,28 +58,79 @@ impl Frame {
     /// # Arguments
     /// `caller_position` - The position where this frame was called or entered.
     /// `name` - The name of this frame.
+    /// `parent_store` - The store of the label that this label is nested in, if it is nested.
+    /// `case` - Whether the names of the variables of this frame are folded to lower case.
     pub fn new(
         caller_position: usize,
         name: &str,
         parent_store: Option<&Rc<RefCell<Store>>>,
+        case: Case,
     ) -> Frame {
         Frame {
             caller_position,
             name: name.to_owned(),
-            current_store: Rc::new(RefCell::new(Store::new(parent_store.cloned()))),
+            current_store: Rc::new(RefCell::new(Store::new(
+                &format!("@{}", name),
+                parent_store.cloned(),
+                case,
+            ))),
+            repeat: None,
+            blocks: 0,
+            caller_stack: None,
+            call_pos: None,
+            deferred: vec![],
         }
     }
 
-    pub fn find(&self, name: &str, pos: usize) -> Result<Rc<Value>, Error> {
+    pub fn find(&self, name: &str, pos: usize) -> Result<Arc<Value>, Error> {
         self.current_store.borrow().get(name, pos)
     }
 
-    pub fn define(&mut self, name: &str, value: Rc<Value>) {
+    pub fn define(&mut self, name: &str, value: Arc<Value>) {
         self.current_store.borrow_mut().define(name, value);
     }
 
+    /// Enters a new block, so that the variables defined until the block is left are discarded when it is left.
+    /// The variables of the rest of the frame are still visible inside of the block.
+    pub fn begin_block(&mut self) {
+        let scope = format!("A Block In @{}", self.name);
+        let case = self.current_store.borrow().get_case();
+        let store = Store::new(&scope, Some(Rc::clone(&self.current_store)), case);
+        self.current_store = Rc::new(RefCell::new(store));
+        self.blocks += 1;
+    }
+
+    /// Leaves the innermost block, discarding the variables defined in it.
+    /// This function returns false if no block has been entered.
+    pub fn end_block(&mut self) -> bool {
+        if self.blocks == 0 {
+            return false;
+        }
+
+        let parent = self.current_store.borrow().get_parent();
+        if let Some(parent) = parent {
+            self.current_store = parent;
+        }
+
+        self.blocks -= 1;
+        true
+    }
+
+    /// This function returns the variables defined in this frame, sorted by name.
+    pub fn get_variables(&self) -> Vec<(String, Arc<Value>)> {
+        self.current_store.borrow().get_variables()
+    }
+
     /// This function gets the position of the caller of this frame.
     pub fn get_caller_position(&self) -> usize {
         self.caller_position
     }
+
+    /// This function describes this frame for the errors that occur while it is on the call stack.
+    pub fn to_entry(&self) -> FrameEntry {
+        FrameEntry {
+            name: self.name.clone(),
+            call_pos: self.call_pos,
+        }
+    }
 }
@@ -0,0 +1,83 @@
+//! The FileSystem struct grants the file instructions access to the disk.
+//! The VM only has a FileSystem if the embedder explicitly allows it, so untrusted programs can not touch the disk by default.
+//! A FileSystem may optionally be restricted to a root directory. Relative paths are resolved against the root,
+//! and any path that leaves the root, whether through '..', an absolute path, or a symbolic link, is rejected.
+
+use crate::errors::{error::Error, error_kind::ErrorKind};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Default, Clone)]
+pub struct FileSystem {
+    root: Option<PathBuf>,
+}
+
+impl FileSystem {
+    /// Constructs a new FileSystem, which is optionally restricted to the given root directory.
+    ///
+    /// # Arguments
+    /// `root` - The directory that every path must be inside of.
+    pub fn new(root: Option<PathBuf>) -> FileSystem {
+        FileSystem { root }
+    }
+
+    /// This function returns the root directory, if there is one.
+    pub fn get_root(&self) -> Option<&Path> {
+        self.root.as_deref()
+    }
+
+    /// This function resolves the path passed to a file instruction.
+    /// If the FileSystem has a root, the path is resolved against it, and an error is returned if it is outside of the root.
+    ///
+    /// # Arguments
+    /// `path` - The path passed to the instruction.
+    /// `pos` - The position where the path was given.
+    pub(crate) fn resolve(&self, path: &str, pos: usize) -> Result<PathBuf, Error> {
+        let root = match &self.root {
+            Some(root) => root
+                .canonicalize()
+                .map_err(|error| file_error(&root.to_string_lossy(), error, pos))?,
+            None => return Ok(PathBuf::from(path)),
+        };
+
+        // The file may not exist yet, so its directory is canonicalized instead of the file itself.
+        let joined = root.join(path);
+        let outside = || Error::new(ErrorKind::PathOutsideRoot(path.to_owned()), pos);
+        let file_name = joined.file_name().ok_or_else(outside)?;
+        let directory = joined
+            .parent()
+            .ok_or_else(outside)?
+            .canonicalize()
+            .map_err(|error| file_error(path, error, pos))?;
+
+        // If the file does exist, it may be a symbolic link, which has to be followed before it is checked.
+        // A link whose target does not exist can not be followed, and writing through it would create the target wherever it points, so it is rejected.
+        let resolved = directory.join(file_name);
+        let resolved = match fs::symlink_metadata(&resolved) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                resolved.canonicalize().map_err(|_| outside())?
+            }
+            _ => resolved,
+        };
+        if resolved.starts_with(&root) {
+            Ok(resolved)
+        } else {
+            Err(outside())
+        }
+    }
+}
+
+/// This function creates an error for a file operation that failed.
+///
+/// # Arguments
+/// `path` - The path of the file.
+/// `error` - The error that occurred.
+/// `pos` - The position where the path was given.
+pub(crate) fn file_error(path: &str, error: std::io::Error, pos: usize) -> Error {
+    Error::new(
+        ErrorKind::FailedFileOperation(path.to_owned(), error.to_string()),
+        pos,
+    )
+}
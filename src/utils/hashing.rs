@@ -0,0 +1,38 @@
+//! The HashAlgorithm enum describes the digests that the crc32, md5, and sha256 instructions produce.
+//! The digests themselves are only available with the hashing feature, which pulls in the crates that implement them.
+
+#[cfg(feature = "hashing")]
+use super::encoding;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlgorithm {
+    Crc32,
+    Md5,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// This function returns the name of the instruction that uses this algorithm.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Crc32 => "crc32",
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha256 => "sha256",
+        }
+    }
+
+    /// This function computes the digest of the bytes and returns it as a lowercase hexadecimal string.
+    /// The CRC-32 checksum is written in big-endian order, as it is usually shown.
+    ///
+    /// # Arguments
+    /// `bytes` - The bytes to hash.
+    #[cfg(feature = "hashing")]
+    pub fn digest(&self, bytes: &[u8]) -> String {
+        use md5::Digest;
+        match self {
+            HashAlgorithm::Crc32 => encoding::hex_encode(&crc32fast::hash(bytes).to_be_bytes()),
+            HashAlgorithm::Md5 => encoding::hex_encode(&md5::Md5::digest(bytes)),
+            HashAlgorithm::Sha256 => encoding::hex_encode(&sha2::Sha256::digest(bytes)),
+        }
+    }
+}
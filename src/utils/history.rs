@@ -0,0 +1,73 @@
+//! The History struct keeps the last few instructions that the VM executed, in a ring buffer of a fixed size.
+//! When a runtime error occurs, the history is attached to the error, so that the report shows how execution arrived at the failure.
+//! Keeping the history costs a little time for every instruction, so its size can be set to 0 to turn it off.
+
+use crate::instructions::Opcode;
+use std::collections::VecDeque;
+
+/// The number of instructions that are kept by default.
+pub const DEFAULT_HISTORY_SIZE: usize = 16;
+
+/// The HistoryEntry struct describes a single instruction that was executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The position of the instruction in the source.
+    pub pos: usize,
+
+    /// The instruction that was executed.
+    pub opcode: Opcode,
+
+    /// The number of values on the operand stack before the instruction was executed.
+    pub stack_depth: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct History {
+    entries: VecDeque<HistoryEntry>,
+    size: usize,
+}
+
+impl History {
+    /// Constructs a history that keeps the given number of instructions.
+    ///
+    /// # Arguments
+    /// `size` - The number of instructions to keep. If this is 0, no instructions are kept.
+    pub fn new(size: usize) -> History {
+        History {
+            entries: VecDeque::with_capacity(size),
+            size,
+        }
+    }
+
+    /// Records an instruction that is about to be executed. If the history is full, the oldest instruction is forgotten.
+    ///
+    /// # Arguments
+    /// `entry` - The instruction to record.
+    pub(crate) fn record(&mut self, entry: HistoryEntry) {
+        if self.size == 0 {
+            return;
+        }
+
+        if self.entries.len() == self.size {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(entry);
+    }
+
+    /// Forgets every instruction that was recorded.
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// This function returns the instructions that are kept, from the oldest to the most recent.
+    pub fn get_entries(&self) -> Vec<HistoryEntry> {
+        self.entries.iter().copied().collect()
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new(DEFAULT_HISTORY_SIZE)
+    }
+}
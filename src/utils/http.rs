@@ -0,0 +1,49 @@
+//! The fetch function sends a GET request for the fetch instruction, and converts the response into a value of the VM.
+//! Responses with any status are returned, so that programs can handle errors such as 404 themselves.
+//! Only the failure to send the request or to read the response is an error.
+
+use crate::{
+    errors::{error::Error, error_kind::ErrorKind},
+    values::{value::Value, value_kinds::ValueKind},
+};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+/// The longest that a request may take, including reading the body.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sends a GET request to the url, and returns a map with the status of the response and its body.
+///
+/// # Arguments
+/// `url` - The url to request.
+/// `pos` - The position of the new value, which is also used for errors.
+pub fn fetch(url: &str, pos: usize) -> Result<Value, Error> {
+    let request_error = |error: ureq::Error| {
+        Error::new(
+            ErrorKind::FailedRequest(url.to_owned(), error.to_string()),
+            pos,
+        )
+    };
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .http_status_as_error(false)
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .into();
+    let mut response = agent.get(url).call().map_err(request_error)?;
+    let status = response.status().as_u16();
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(request_error)?;
+
+    let mut result = BTreeMap::new();
+    result.insert(
+        "status".to_owned(),
+        Arc::new(Value::new(pos, ValueKind::Int(status.into()))),
+    );
+    result.insert(
+        "body".to_owned(),
+        Arc::new(Value::new(pos, ValueKind::String(body))),
+    );
+    Ok(Value::new(pos, ValueKind::Map(result)))
+}
@@ -0,0 +1,137 @@
+//! The VmIo struct maintains the streams that the VM reads from and writes to.
+//! Programs write their output to the out stream, and their diagnostics to the err stream, so that the two can be separated in pipelines.
+//! They read their input from the in stream, either as lines or as raw bytes.
+//! By default, these are stdout, stderr, and stdin, but embedders may replace them with any writer or reader.
+//!
+//! In binary mode, bytes values are printed as the raw bytes that they hold, rather than as an escaped literal such as `b"hi\xff"`,
+//! so that programs can act as filters over binary streams.
+//! The streams are reference counted, so that a VmIo can be cloned and shared with the embedder.
+//! The output can also be captured into memory, in which case it is returned by the run method of the VM.
+
+use std::{
+    cell::{RefCell, RefMut},
+    fmt,
+    io::{self, Read, Write},
+    rc::Rc,
+};
+
+#[derive(Clone)]
+pub struct VmIo {
+    out: Rc<RefCell<dyn Write>>,
+    err: Rc<RefCell<dyn Write>>,
+    input: Rc<RefCell<dyn Read>>,
+    binary: bool,
+    captured: Option<Rc<RefCell<Vec<u8>>>>,
+}
+
+/// The Capture struct writes the output of the program into a buffer that is shared with the VmIo.
+struct Capture(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl VmIo {
+    /// Constructs a new VmIo with the given streams.
+    ///
+    /// # Arguments
+    /// `out` - The stream that the output of the program is written to.
+    /// `err` - The stream that the diagnostics of the program are written to.
+    pub fn new<O: Write + 'static, E: Write + 'static>(out: O, err: E) -> VmIo {
+        VmIo {
+            out: Rc::new(RefCell::new(out)),
+            err: Rc::new(RefCell::new(err)),
+            input: Rc::new(RefCell::new(io::stdin())),
+            binary: false,
+            captured: None,
+        }
+    }
+
+    /// Replaces the stream that the output of the program is written to.
+    ///
+    /// # Arguments
+    /// `out` - The new stream.
+    pub fn set_out<O: Write + 'static>(&mut self, out: O) {
+        self.out = Rc::new(RefCell::new(out));
+        self.captured = None;
+    }
+
+    /// Replaces the stream that the output of the program is written to with a buffer in memory.
+    /// The output that was written since can be taken with the take_captured method.
+    pub fn capture_out(&mut self) {
+        let captured = Rc::new(RefCell::new(vec![]));
+        self.out = Rc::new(RefCell::new(Capture(Rc::clone(&captured))));
+        self.captured = Some(captured);
+    }
+
+    /// This function takes the output that was captured since the last call, or returns None if the output is not captured.
+    /// Any bytes that are not valid UTF-8 are replaced.
+    pub fn take_captured(&self) -> Option<String> {
+        self.captured.as_ref().map(|captured| {
+            let bytes = captured.borrow_mut().split_off(0);
+            String::from_utf8_lossy(&bytes).into_owned()
+        })
+    }
+
+    /// Replaces the stream that the diagnostics of the program are written to.
+    ///
+    /// # Arguments
+    /// `err` - The new stream.
+    pub fn set_err<E: Write + 'static>(&mut self, err: E) {
+        self.err = Rc::new(RefCell::new(err));
+    }
+
+    /// Replaces the stream that the input of the program is read from.
+    ///
+    /// # Arguments
+    /// `input` - The new stream.
+    pub fn set_in<I: Read + 'static>(&mut self, input: I) {
+        self.input = Rc::new(RefCell::new(input));
+    }
+
+    /// Sets whether bytes values are printed as raw bytes.
+    ///
+    /// # Arguments
+    /// `binary` - Whether the streams are in binary mode.
+    pub fn set_binary(&mut self, binary: bool) {
+        self.binary = binary;
+    }
+
+    /// This function returns whether the streams are in binary mode.
+    pub fn is_binary(&self) -> bool {
+        self.binary
+    }
+
+    /// This function returns the stream that the output of the program is written to.
+    pub fn out(&self) -> RefMut<'_, dyn Write> {
+        self.out.borrow_mut()
+    }
+
+    /// This function returns the stream that the diagnostics of the program are written to.
+    pub fn err(&self) -> RefMut<'_, dyn Write> {
+        self.err.borrow_mut()
+    }
+
+    /// This function returns the stream that the input of the program is read from.
+    pub fn input(&self) -> RefMut<'_, dyn Read> {
+        self.input.borrow_mut()
+    }
+}
+
+impl Default for VmIo {
+    fn default() -> Self {
+        VmIo::new(io::stdout(), io::stderr())
+    }
+}
+
+impl fmt::Debug for VmIo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VmIo")
+    }
+}
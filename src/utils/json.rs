@@ -0,0 +1,476 @@
+//! The Json enum is a small representation of JSON documents.
+//! It is used by the tooling that talks to editors, such as the debug adapter, and it can be parsed from and written to strings.
+//! It is also used by the jsonparse and jsonstringify instructions, which convert between JSON and the values of the VM.
+//! Objects maintain the order of their keys, so that the output is predictable.
+
+use crate::{
+    errors::{error::Error, error_kind::ErrorKind},
+    values::{value::Value, value_kinds::ValueKind},
+};
+use std::{fmt, iter::Peekable, str::Chars, sync::Arc};
+
+/// The deepest that arrays and objects may be nested inside of each other.
+/// Values are parsed recursively, so this protects the stack from malicious documents.
+const MAX_DEPTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Boolean(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses the given input into a Json value. The whole input must be a single JSON document.
+    ///
+    /// # Arguments
+    /// `input` - The input to parse.
+    pub fn parse(input: &str) -> Result<Json, Error> {
+        let mut parser = Parser {
+            iter: input.chars().peekable(),
+            current_position: 0,
+            depth: 0,
+        };
+
+        let json = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.iter.peek().is_some() {
+            Err(parser.error())
+        } else {
+            Ok(json)
+        }
+    }
+
+    /// This function gets the value of the given key if this is an object.
+    ///
+    /// # Arguments
+    /// `key` - The key to look up.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries
+                .iter()
+                .find(|(entry_key, _)| entry_key == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// This function returns the string if this is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// This function returns the integer if this is a number without a fractional part.
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Json::Int(value) => Some(*value),
+            Json::Float(value) if value.fract() == 0.0 => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    /// This function returns the boolean if this is a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// This function returns the elements if this is an array.
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Converts this JSON value into a value of the VM. Arrays become arrays, objects become maps, and null becomes null.
+    /// If an object has the same key more than once, the last value is kept.
+    ///
+    /// # Arguments
+    /// `pos` - The position of the new value and all of its elements.
+    pub fn to_value(&self, pos: usize) -> Value {
+        let kind = match self {
+            Json::Null => ValueKind::Null,
+            Json::Boolean(value) => ValueKind::Boolean(*value),
+            Json::Int(value) => ValueKind::Int(*value),
+            Json::Float(value) => ValueKind::Float(*value),
+            Json::String(value) => ValueKind::String(value.to_owned()),
+            Json::Array(values) => ValueKind::Array(
+                values
+                    .iter()
+                    .map(|value| Arc::new(value.to_value(pos)))
+                    .collect(),
+            ),
+            Json::Object(entries) => ValueKind::Map(
+                entries
+                    .iter()
+                    .map(|(key, value)| (key.to_owned(), Arc::new(value.to_value(pos))))
+                    .collect(),
+            ),
+        };
+
+        Value::new(pos, kind)
+    }
+
+    /// Converts a value of the VM into JSON. Maps become objects, chars become strings, and both null and void become null.
+    /// Values that have no JSON form, such as any, identifiers, labels, and instructions, produce an error.
+    ///
+    /// # Arguments
+    /// `value` - The value to convert.
+    /// `pos` - The position where the conversion was needed.
+    pub fn from_value(value: &Value, pos: usize) -> Result<Json, Error> {
+        Ok(match &value.kind {
+            ValueKind::Void | ValueKind::Null => Json::Null,
+            ValueKind::Boolean(value) => Json::Boolean(*value),
+            ValueKind::Int(value) => Json::Int(*value),
+            ValueKind::Float(value) => Json::Float(*value),
+            ValueKind::String(value) => Json::String(value.to_owned()),
+            ValueKind::Char(value) => Json::String(value.to_string()),
+            ValueKind::Array(values) => Json::Array(
+                values
+                    .iter()
+                    .map(|value| Json::from_value(value, pos))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ValueKind::Map(entries) => Json::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| Ok((key.to_owned(), Json::from_value(value, pos)?)))
+                    .collect::<Result<_, Error>>()?,
+            ),
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::UnsupportedOperation(
+                        "jsonstringify".to_owned(),
+                        kind.get_value_name(),
+                    ),
+                    pos,
+                ))
+            }
+        })
+    }
+
+    /// Constructs an object from the given entries.
+    ///
+    /// # Arguments
+    /// `entries` - The keys and values of the object.
+    pub fn object(entries: Vec<(&str, Json)>) -> Json {
+        Json::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value))
+                .collect(),
+        )
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_owned())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::String(value)
+    }
+}
+
+impl From<i64> for Json {
+    fn from(value: i64) -> Self {
+        Json::Int(value)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Int(value as i64)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Boolean(value)
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Boolean(value) => write!(f, "{}", value),
+            Json::Int(value) => write!(f, "{}", value),
+            // JSON does not support NaN or infinity, so they are written as null.
+            Json::Float(value) if !value.is_finite() => write!(f, "null"),
+            Json::Float(value) if value.fract() == 0.0 => write!(f, "{:.1}", value),
+            Json::Float(value) => write!(f, "{}", value),
+            Json::String(value) => write_string(f, value),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{}", value)?;
+                }
+
+                write!(f, "]")
+            }
+            Json::Object(entries) => {
+                write!(f, "{{")?;
+                for (idx, (key, value)) in entries.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Writes the given string with quotes, escaping the characters that JSON requires to be escaped.
+///
+/// # Arguments
+/// `f` - The formatter to write to.
+/// `value` - The string to write.
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for ch in value.chars() {
+        match ch {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            ch if (ch as u32) < 0x20 => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => write!(f, "{}", ch)?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+/// The Parser struct maintains the state needed to parse a JSON document.
+struct Parser<'a> {
+    iter: Peekable<Chars<'a>>,
+    current_position: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// This function parses any JSON value.
+    fn parse_value(&mut self) -> Result<Json, Error> {
+        self.skip_whitespace();
+        match self.advance() {
+            Some('n') => self.expect_word("ull", Json::Null),
+            Some('t') => self.expect_word("rue", Json::Boolean(true)),
+            Some('f') => self.expect_word("alse", Json::Boolean(false)),
+            Some('"') => self.parse_string().map(Json::String),
+            Some('[') => self.parse_nested(Parser::parse_array),
+            Some('{') => self.parse_nested(Parser::parse_object),
+            Some(ch) if ch == '-' || ch.is_ascii_digit() => self.parse_number(ch),
+            _ => Err(self.error()),
+        }
+    }
+
+    /// This function parses an array or an object, as long as the document is not nested too deeply.
+    ///
+    /// # Arguments
+    /// `parse` - The function that parses the array or the object.
+    fn parse_nested(&mut self, parse: fn(&mut Self) -> Result<Json, Error>) -> Result<Json, Error> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error());
+        }
+
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// This function checks that the rest of a keyword matches, and returns the given value if it does.
+    ///
+    /// # Arguments
+    /// `rest` - The remaining characters of the keyword.
+    /// `value` - The value of the keyword.
+    fn expect_word(&mut self, rest: &str, value: Json) -> Result<Json, Error> {
+        for expected in rest.chars() {
+            if self.advance() != Some(expected) {
+                return Err(self.error());
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// This function parses a number. Numbers without a fraction or an exponent are parsed as integers.
+    ///
+    /// # Arguments
+    /// `first` - The first character of the number.
+    fn parse_number(&mut self, first: char) -> Result<Json, Error> {
+        let mut number = first.to_string();
+        let mut is_float = false;
+        while let Some(&ch) = self.iter.peek() {
+            if ch.is_ascii_digit() || ch == '-' || ch == '+' {
+                number.push(ch);
+            } else if ch == '.' || ch == 'e' || ch == 'E' {
+                number.push(ch);
+                is_float = true;
+            } else {
+                break;
+            }
+
+            self.advance();
+        }
+
+        if !is_float {
+            if let Ok(value) = number.parse() {
+                return Ok(Json::Int(value));
+            }
+        }
+
+        number.parse().map(Json::Float).map_err(|_| self.error())
+    }
+
+    /// This function parses a string. The opening quote must already be consumed.
+    fn parse_string(&mut self) -> Result<String, Error> {
+        let mut string = String::new();
+        loop {
+            match self.advance() {
+                Some('"') => return Ok(string),
+                Some('\\') => match self.advance() {
+                    Some('"') => string.push('"'),
+                    Some('\\') => string.push('\\'),
+                    Some('/') => string.push('/'),
+                    Some('b') => string.push('\u{8}'),
+                    Some('f') => string.push('\u{c}'),
+                    Some('n') => string.push('\n'),
+                    Some('r') => string.push('\r'),
+                    Some('t') => string.push('\t'),
+                    Some('u') => {
+                        let code = self.parse_hex()?;
+
+                        // Characters outside of the basic plane are written as surrogate pairs.
+                        let code = if (0xD800..0xDC00).contains(&code) {
+                            if self.advance() != Some('\\') || self.advance() != Some('u') {
+                                return Err(self.error());
+                            }
+
+                            let low = self.parse_hex()?;
+                            0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF)
+                        } else {
+                            code
+                        };
+
+                        string.push(std::char::from_u32(code).ok_or_else(|| self.error())?);
+                    }
+                    _ => return Err(self.error()),
+                },
+                Some(ch) => string.push(ch),
+                None => return Err(self.error()),
+            }
+        }
+    }
+
+    /// This function parses the four hexadecimal digits of a unicode escape.
+    fn parse_hex(&mut self) -> Result<u32, Error> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .advance()
+                .and_then(|ch| ch.to_digit(16))
+                .ok_or_else(|| self.error())?;
+            code = code * 16 + digit;
+        }
+
+        Ok(code)
+    }
+
+    /// This function parses an array. The opening bracket must already be consumed.
+    fn parse_array(&mut self) -> Result<Json, Error> {
+        let mut values = vec![];
+        self.skip_whitespace();
+        if self.iter.peek() == Some(&']') {
+            self.advance();
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.advance() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(values)),
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    /// This function parses an object. The opening brace must already be consumed.
+    fn parse_object(&mut self) -> Result<Json, Error> {
+        let mut entries = vec![];
+        self.skip_whitespace();
+        if self.iter.peek() == Some(&'}') {
+            self.advance();
+            return Ok(Json::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.advance() != Some('"') {
+                return Err(self.error());
+            }
+
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            if self.advance() != Some(':') {
+                return Err(self.error());
+            }
+
+            entries.push((key, self.parse_value()?));
+            self.skip_whitespace();
+            match self.advance() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(entries)),
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    /// This function skips any whitespace.
+    fn skip_whitespace(&mut self) {
+        while let Some(ch) = self.iter.peek() {
+            if ch.is_whitespace() {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// This function increments the current position and returns the next character.
+    fn advance(&mut self) -> Option<char> {
+        self.current_position += 1;
+        self.iter.next()
+    }
+
+    /// This function creates an error at the current position.
+    fn error(&self) -> Error {
+        Error::new(ErrorKind::InvalidJson, self.current_position.max(1))
+    }
+}
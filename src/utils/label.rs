@@ -5,14 +5,28 @@ pub struct Label {
     pub start_pos: usize,
     pub end_pos: usize,
     pub parameters: Vec<Parameter>,
+
+    /// The documentation of the label, from the single line comments directly above it.
+    pub doc: Option<String>,
+
+    /// Whether the label is annotated as pure, so that every call to it has a stack of its own, whatever the calling convention of the VM.
+    pub pure: bool,
 }
 
 impl Label {
-    pub fn new(start_pos: usize, end_pos: usize, parameters: Vec<Parameter>) -> Label {
+    pub fn new(
+        start_pos: usize,
+        end_pos: usize,
+        parameters: Vec<Parameter>,
+        doc: Option<String>,
+        pure: bool,
+    ) -> Label {
         Label {
             start_pos,
             end_pos,
             parameters,
+            doc,
+            pure,
         }
     }
 }
@@ -0,0 +1,169 @@
+//! The Limits struct maintains the resource limits that the VM enforces while it is running.
+//! Every limit is optional. If a limit is None, the VM does not check it.
+//!
+//! The limits on values protect the memory of the host from hostile programs. Strings and bytes are measured in bytes,
+//! arrays and maps are measured in elements, and the depth counts how many arrays and maps are nested inside of each other.
+//! The depth is the one limit that always applies. Values are dropped, compared, and printed recursively,
+//! so the instructions that nest values never go deeper than `MAX_VALUE_DEPTH`, even if no limit is set.
+
+use crate::{
+    errors::{error::Error, error_kind::ErrorKind},
+    values::{value::Value, value_kinds::ValueKind},
+};
+use std::time::Duration;
+
+/// The deepest that arrays and maps may be nested inside of each other when no lower limit is set.
+/// This matches the deepest document that the JSON parser accepts.
+pub const MAX_VALUE_DEPTH: usize = 256;
+
+#[derive(Debug, Default, Clone)]
+pub struct Limits {
+    pub fuel: Option<u64>,
+    pub timeout: Option<Duration>,
+    pub max_depth: Option<usize>,
+    pub max_stack: Option<usize>,
+    pub max_string_len: Option<usize>,
+    pub max_array_len: Option<usize>,
+    pub max_value_depth: Option<usize>,
+}
+
+impl Limits {
+    /// This function returns whether any of the limits on values are set.
+    pub(crate) fn limits_values(&self) -> bool {
+        self.max_string_len.is_some()
+            || self.max_array_len.is_some()
+            || self.max_value_depth.is_some()
+    }
+
+    /// This function checks that the value, and every value inside of it, is within the limits on values.
+    ///
+    /// # Arguments
+    /// `value` - The value to check.
+    /// `pos` - The position used for errors.
+    pub(crate) fn check_value(&self, value: &Value, pos: usize) -> Result<(), Error> {
+        self.check_nested(value, 0, pos)
+    }
+
+    /// This function checks that an array or a map holding the element passed in is not nested too deeply.
+    /// Unlike the other limits, this is checked even if `max_value_depth` is not set.
+    ///
+    /// # Arguments
+    /// `element` - The element that is put in the array or the map.
+    /// `pos` - The position used for errors.
+    pub(crate) fn check_element(&self, element: &Value, pos: usize) -> Result<(), Error> {
+        let max_value_depth = self
+            .max_value_depth
+            .map_or(MAX_VALUE_DEPTH, |max| max.min(MAX_VALUE_DEPTH));
+        if nesting(element) >= max_value_depth {
+            return Err(Error::new(
+                ErrorKind::ValueDepthExceeded(max_value_depth),
+                pos,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// This function checks that repeating a string the given number of times stays within the limit on strings.
+    /// This is checked before the string is repeated, so that the memory is never allocated.
+    ///
+    /// # Arguments
+    /// `len` - The length of the string in bytes.
+    /// `count` - The number of times the string is repeated. The sign of the count is ignored.
+    /// `pos` - The position used for errors.
+    pub(crate) fn check_repeat(&self, len: usize, count: i64, pos: usize) -> Result<(), Error> {
+        let total = len.checked_mul(count.unsigned_abs() as usize);
+        self.check_string_len(total.unwrap_or(usize::MAX), pos)
+    }
+
+    /// This function checks a value that is nested inside of the given number of arrays and maps.
+    ///
+    /// # Arguments
+    /// `value` - The value to check.
+    /// `depth` - The number of arrays and maps that the value is inside of.
+    /// `pos` - The position used for errors.
+    fn check_nested(&self, value: &Value, depth: usize, pos: usize) -> Result<(), Error> {
+        match &value.kind {
+            ValueKind::String(string) => self.check_string_len(string.len(), pos),
+            ValueKind::Bytes(bytes) => self.check_string_len(bytes.len(), pos),
+            ValueKind::Array(values) => {
+                self.check_container(values.len(), depth, pos)?;
+                values
+                    .iter()
+                    .try_for_each(|value| self.check_nested(value, depth + 1, pos))
+            }
+            ValueKind::Map(entries) => {
+                self.check_container(entries.len(), depth, pos)?;
+                entries.iter().try_for_each(|(key, value)| {
+                    self.check_string_len(key.len(), pos)?;
+                    self.check_nested(value, depth + 1, pos)
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// This function checks the length of an array or a map, and how deeply it is nested.
+    ///
+    /// # Arguments
+    /// `len` - The number of elements.
+    /// `depth` - The number of arrays and maps that this one is inside of.
+    /// `pos` - The position used for errors.
+    fn check_container(&self, len: usize, depth: usize, pos: usize) -> Result<(), Error> {
+        if let Some(max_array_len) = self.max_array_len {
+            if len > max_array_len {
+                return Err(Error::new(
+                    ErrorKind::ArrayLimitExceeded(max_array_len),
+                    pos,
+                ));
+            }
+        }
+
+        if let Some(max_value_depth) = self.max_value_depth {
+            if depth >= max_value_depth {
+                return Err(Error::new(
+                    ErrorKind::ValueDepthExceeded(max_value_depth),
+                    pos,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// This function checks the length of a string or bytes.
+    ///
+    /// # Arguments
+    /// `len` - The length in bytes.
+    /// `pos` - The position used for errors.
+    fn check_string_len(&self, len: usize, pos: usize) -> Result<(), Error> {
+        match self.max_string_len {
+            Some(max_string_len) if len > max_string_len => Err(Error::new(
+                ErrorKind::StringLimitExceeded(max_string_len),
+                pos,
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// This function returns how many arrays and maps are nested inside of each other in the value, counting the value itself.
+/// Every array and map is built within `MAX_VALUE_DEPTH`, so the recursion is bounded.
+///
+/// # Arguments
+/// `value` - The value to measure.
+fn nesting(value: &Value) -> usize {
+    match &value.kind {
+        ValueKind::Array(values) => {
+            1 + values.iter().map(|value| nesting(value)).max().unwrap_or(0)
+        }
+        ValueKind::Map(entries) => {
+            1 + entries
+                .values()
+                .map(|value| nesting(value))
+                .max()
+                .unwrap_or(0)
+        }
+        _ => 0,
+    }
+}
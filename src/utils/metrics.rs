@@ -0,0 +1,100 @@
+//! The Metrics struct counts what the VM does, such as the number of instructions executed and the number of calls.
+//! It is a handle to shared counters, so embedders can keep a clone of it and read the counters while, or after, the VM runs.
+//! The counters can also be written in the Prometheus text format, so that they can be scraped.
+
+use std::{cell::Cell, fmt::Write, rc::Rc};
+
+#[derive(Debug, Default)]
+struct Counters {
+    instructions: Cell<u64>,
+    calls: Cell<u64>,
+    errors: Cell<u64>,
+    fuel_consumed: Cell<u64>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Metrics(Rc<Counters>);
+
+impl Metrics {
+    /// This function returns the number of instructions that were executed.
+    pub fn get_instructions(&self) -> u64 {
+        self.0.instructions.get()
+    }
+
+    /// This function returns the number of calls to labels.
+    pub fn get_calls(&self) -> u64 {
+        self.0.calls.get()
+    }
+
+    /// This function returns the number of errors that stopped the VM.
+    pub fn get_errors(&self) -> u64 {
+        self.0.errors.get()
+    }
+
+    /// This function returns the amount of fuel consumed. Fuel is only consumed when the VM has a fuel limit.
+    pub fn get_fuel_consumed(&self) -> u64 {
+        self.0.fuel_consumed.get()
+    }
+
+    /// Counts an instruction that was executed.
+    ///
+    /// # Arguments
+    /// `consumes_fuel` - Whether the VM has a fuel limit, in which case the instruction also consumed fuel.
+    pub(crate) fn add_instruction(&self, consumes_fuel: bool) {
+        increment(&self.0.instructions);
+        if consumes_fuel {
+            increment(&self.0.fuel_consumed);
+        }
+    }
+
+    /// Counts a call to a label.
+    pub(crate) fn add_call(&self) {
+        increment(&self.0.calls);
+    }
+
+    /// Counts an error that stopped the VM.
+    pub(crate) fn add_error(&self) {
+        increment(&self.0.errors);
+    }
+
+    /// This function writes the counters in the Prometheus text format.
+    pub fn to_prometheus(&self) -> String {
+        let counters = [
+            (
+                "dark_vm_instructions_total",
+                "The number of instructions executed.",
+                self.get_instructions(),
+            ),
+            (
+                "dark_vm_calls_total",
+                "The number of calls to labels.",
+                self.get_calls(),
+            ),
+            (
+                "dark_vm_errors_total",
+                "The number of errors that stopped the VM.",
+                self.get_errors(),
+            ),
+            (
+                "dark_vm_fuel_consumed_total",
+                "The amount of fuel consumed.",
+                self.get_fuel_consumed(),
+            ),
+        ];
+
+        let mut output = String::new();
+        for (name, help, value) in counters.iter() {
+            // Writing to a string can not fail.
+            let _ = writeln!(output, "# HELP {} {}", name, help);
+            let _ = writeln!(output, "# TYPE {} counter", name);
+            let _ = writeln!(output, "{} {}", name, value);
+        }
+
+        output
+    }
+}
+
+/// Increments the given counter by one.
+fn increment(counter: &Cell<u64>) {
+    counter.set(counter.get() + 1);
+}
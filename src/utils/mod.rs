@@ -1,12 +1,107 @@
 /// The stack module, which contains the stack and different functions to control the stack.
 pub mod stack;
 
-/// The frames module, which contains the frame struct. Frames are used when the VM jumps to a label.
+/// The frames module, which contains the frame struct and the FrameEntry struct. Frames are used when the VM jumps to a label, and the entries describe them in errors.
 pub mod frames;
 
 /// The store module, which provides a nice wrapper around a HashMap for maintaining variables.
 pub mod store;
 
+/// The aliases module, which contains the Aliases struct. This maintains the names that stand for instructions, which are resolved when a program is loaded.
+pub mod aliases;
+
+/// The label module, which contains the Label struct. This maintains where a label starts and ends, along with its parameters.
 pub mod label;
 
+/// The parameter module, which contains the Parameter struct. This maintains the name and position of a parameter of a label.
 pub mod parameter;
+
+/// The limits module, which contains the resource limits that the VM enforces, such as fuel and the maximum call depth.
+pub mod limits;
+
+/// The json module, which contains a small JSON value that can be parsed and written. This is used by the tooling that talks to editors.
+pub mod json;
+
+/// The io module, which contains the VmIo struct. This maintains the streams that the VM reads its input from and writes its output and diagnostics to.
+pub mod io;
+
+/// The log_level module, which contains the LogLevel enum. This describes the levels of the log instruction, which the VM filters on.
+pub mod log_level;
+
+/// The outcome module, which contains the RunOutcome struct. This describes how a run of the VM ended, including its result, warnings, and statistics.
+pub mod outcome;
+
+/// The history module, which contains the History struct. This keeps the last few instructions that the VM executed, so that runtime errors can show them.
+pub mod history;
+
+/// The float_equality module, which contains the FloatEquality enum. This describes how the equality instructions compare two floats.
+pub mod float_equality;
+
+/// The value_diff module, which finds the differences between two values. This is used by the asserteq instruction to show where its values differ.
+pub mod value_diff;
+
+/// The number_format module, which contains the NumberFormat struct. This describes how the format instruction writes a number, such as its width, its precision, and its base.
+pub mod number_format;
+
+/// The calling_convention module, which contains the CallingConvention enum. This describes whether the labels that are called share the operand stack of their caller.
+pub mod calling_convention;
+
+/// The overflow module, which contains the Overflow enum. This describes whether arithmetic on ints wraps around or returns an error when the result does not fit.
+pub mod overflow;
+
+/// The shadowing module, which contains the Shadowing enum. This describes whether the set instruction may define a variable that an outer scope already defines.
+pub mod shadowing;
+
+/// The case module, which contains the Case enum. This describes whether identifiers that differ only by case are the same.
+pub mod case;
+
+/// The metrics module, which contains the Metrics struct. This counts what the VM does, such as the number of instructions executed.
+pub mod metrics;
+
+/// The snapshot module, which contains the Snapshot struct. This captures the state of the VM, so that the changes made by an instruction can be found.
+pub mod snapshot;
+
+/// The timeline module, which contains the Timeline struct. This records the state of the VM after every step, so that a visualizer can replay the run.
+pub mod timeline;
+
+/// The profiler module, which contains the Profiler struct. This measures the time spent in each instruction and each call stack, optionally by sampling.
+pub mod profiler;
+
+/// The watch module, which contains the Watchpoints struct. This maintains the variables that the host watches, and reports every write to them.
+pub mod watch;
+
+/// The natives module, which contains the Natives struct. This maintains the functions that the host registered, which programs call like labels.
+pub mod natives;
+
+/// The fs module, which contains the FileSystem struct. This grants the file instructions access to the disk, optionally restricted to a root directory.
+pub mod fs;
+
+/// The capabilities module, which contains the Capabilities struct. This describes what a program is allowed to do outside of the VM, and denies everything by default.
+pub mod capabilities;
+
+/// The process module, which contains the ProcessPolicy struct. This grants the exec instruction permission to run commands, optionally restricted to a list of commands.
+pub mod process;
+
+/// The trace module, which contains the Trace struct. This records the nondeterministic inputs of a program, so that a run can be replayed exactly.
+pub mod trace;
+
+/// The encoding module, which converts bytes to and from base64 and hexadecimal text.
+pub mod encoding;
+
+/// The hashing module, which contains the HashAlgorithm enum. This computes the digests of the hashing instructions.
+pub mod hashing;
+
+/// The regex module, which contains the RegexOperation enum. This applies the patterns of the regex instructions to strings.
+pub mod regex;
+
+/// The datetime module, which converts Unix timestamps to and from dates for the date instructions.
+#[cfg(feature = "datetime")]
+pub mod datetime;
+
+/// The http module, which sends the requests of the fetch instruction.
+#[cfg(feature = "http")]
+pub mod http;
+
+/// The csv module, which converts CSV text into arrays of rows for the csvparse instruction.
+#[cfg(feature = "csv")]
+pub mod csv;
@@ -0,0 +1,94 @@
+//! The Natives struct maintains the native functions that the host registered, which programs call like labels.
+//! A native function is written in Rust, so a host can give its programs access to anything that it can do itself, such as its own data or services.
+//! The call, callt, and callf instructions look up the native functions before the labels, so a native function takes the place of a label with the same name.
+//!
+//! A native function takes a fixed number of arguments, which are evaluated like the arguments of a label, and is given the VM, so that it can read and change its state.
+//! The value that it returns is the value of the call instruction, so `push call double 21` pushes the value that double returns.
+//! The natives have to be known when the program is verified, so they are usually registered with the `native` method of the VMBuilder.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{builder::VMBuilder, errors::error::Error, lexer::Lexer, values::{value::Value, value_kinds::ValueKind}};
+//! # fn run() -> Result<(), Error> {
+//! let tokens = Lexer::default().lex("@main push call double 21 end")?;
+//! let mut vm = VMBuilder::default()
+//!     .native("double", 1, |_, args| match args[0].kind {
+//!         ValueKind::Int(value) => Ok(Some(Value::new(args[0].pos, ValueKind::Int(value * 2)))),
+//!         _ => Ok(None),
+//!     })
+//!     .build(tokens)?;
+//! vm.run().into_result()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{errors::error::Error, values::value::Value, vm::VM};
+use std::{collections::BTreeMap, fmt, rc::Rc, sync::Arc};
+
+/// The signature of a native function. It is given the VM and the values of its arguments, from the first argument to the last,
+/// and returns the value of the call, if it has one.
+pub type NativeFunction = Rc<dyn Fn(&mut VM, Vec<Arc<Value>>) -> Result<Option<Value>, Error>>;
+
+#[derive(Clone, Default)]
+pub struct Natives {
+    functions: BTreeMap<String, (usize, NativeFunction)>,
+}
+
+impl Natives {
+    /// This function registers the native function with the given name, replacing any native function that had the name before.
+    /// This function returns false if a native function was replaced.
+    ///
+    /// # Arguments
+    /// `name` - The name that programs call the function by.
+    /// `arity` - The number of arguments that the function takes.
+    /// `function` - The function.
+    pub fn register<F>(&mut self, name: &str, arity: usize, function: F) -> bool
+    where
+        F: Fn(&mut VM, Vec<Arc<Value>>) -> Result<Option<Value>, Error> + 'static,
+    {
+        self.functions
+            .insert(name.to_owned(), (arity, Rc::new(function)))
+            .is_none()
+    }
+
+    /// This function removes the native function with the given name.
+    /// This function returns false if there was no native function with the name.
+    ///
+    /// # Arguments
+    /// `name` - The name of the function.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.functions.remove(name).is_some()
+    }
+
+    /// This function returns the names of the native functions, sorted by name.
+    pub fn get_names(&self) -> Vec<&str> {
+        self.functions.keys().map(String::as_str).collect()
+    }
+
+    /// This function returns the number of arguments that the native function with the given name takes, or None if there is no such function.
+    ///
+    /// # Arguments
+    /// `name` - The name of the function.
+    pub fn get_arity(&self, name: &str) -> Option<usize> {
+        self.functions.get(name).map(|(arity, _)| *arity)
+    }
+
+    /// This function returns the native function with the given name, along with its arity.
+    /// The function is shared, so that it can be called while the VM that holds these natives is borrowed mutably.
+    ///
+    /// # Arguments
+    /// `name` - The name of the function.
+    pub(crate) fn get(&self, name: &str) -> Option<(usize, NativeFunction)> {
+        self.functions
+            .get(name)
+            .map(|(arity, function)| (*arity, Rc::clone(function)))
+    }
+}
+
+impl fmt::Debug for Natives {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Natives")
+            .field("names", &self.get_names())
+            .finish()
+    }
+}
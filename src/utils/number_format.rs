@@ -0,0 +1,187 @@
+//! The NumberFormat struct describes how the format instruction writes a number, in the style of the format specifiers of printf.
+//! A specifier is made of optional flags, an optional width, an optional precision, and a conversion, such as `%08.3f`. The leading `%` may be left out.
+//!
+//! The flags are `-`, which aligns the number to the left of the width, `0`, which pads the number with zeros instead of spaces, and `+`, which always writes the sign.
+//! The conversions `d`, `x`, `X`, `o`, and `b` write ints in decimal, hexadecimal, octal, and binary. Negative ints are written in two's complement in every base but decimal.
+//! The conversions `f`, `e`, and `E` write ints and floats with a fixed number of decimals or in scientific notation. The precision is the number of decimals, which is 6 by default.
+
+use crate::values::value_kinds::ValueKind;
+use std::str::FromStr;
+
+/// The largest width or precision that a specifier may have. This keeps a specifier from allocating an enormous string.
+pub const MAX_WIDTH: usize = 1024;
+
+/// The Conversion enum describes how the digits of the number are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Conversion {
+    Decimal,
+    LowerHex,
+    UpperHex,
+    Octal,
+    Binary,
+    Fixed,
+    Scientific,
+    UpperScientific,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    left_align: bool,
+    zero_pad: bool,
+    plus_sign: bool,
+    width: usize,
+    precision: Option<usize>,
+    conversion: Conversion,
+}
+
+impl NumberFormat {
+    /// This function writes the number with this format.
+    /// If the value can not be written with the conversion of this format, None is returned.
+    ///
+    /// # Arguments
+    /// `kind` - The number to write.
+    pub fn apply(&self, kind: &ValueKind) -> Option<String> {
+        let (negative, digits) = match (self.conversion, kind) {
+            (Conversion::Decimal, ValueKind::Int(value)) => {
+                (*value < 0, value.unsigned_abs().to_string())
+            }
+            (Conversion::LowerHex, ValueKind::Int(value)) => (false, format!("{:x}", value)),
+            (Conversion::UpperHex, ValueKind::Int(value)) => (false, format!("{:X}", value)),
+            (Conversion::Octal, ValueKind::Int(value)) => (false, format!("{:o}", value)),
+            (Conversion::Binary, ValueKind::Int(value)) => (false, format!("{:b}", value)),
+            (conversion, ValueKind::Int(value)) => self.write_float(conversion, *value as f64)?,
+            (conversion, ValueKind::Float(value)) => self.write_float(conversion, *value)?,
+            _ => return None,
+        };
+
+        let sign = if negative {
+            "-"
+        } else if self.plus_sign && self.is_signed() {
+            "+"
+        } else {
+            ""
+        };
+
+        let len = sign.len() + digits.len();
+        if len >= self.width {
+            return Some(format!("{}{}", sign, digits));
+        }
+
+        let padding = self.width - len;
+        Some(if self.left_align {
+            format!("{}{}{}", sign, digits, " ".repeat(padding))
+        } else if self.zero_pad {
+            format!("{}{}{}", sign, "0".repeat(padding), digits)
+        } else {
+            format!("{}{}{}", " ".repeat(padding), sign, digits)
+        })
+    }
+
+    /// This function returns the name of the types that this format can write, for the errors of the format instruction.
+    pub fn get_expected(&self) -> String {
+        if self.is_float() {
+            "Int Or Float".to_owned()
+        } else {
+            ValueKind::Int(0).get_value_name()
+        }
+    }
+
+    /// This function writes the magnitude of the float with the given conversion, and returns whether the float is negative.
+    /// None is returned if the conversion does not write floats.
+    ///
+    /// # Arguments
+    /// `conversion` - The conversion to use.
+    /// `value` - The float to write.
+    fn write_float(&self, conversion: Conversion, value: f64) -> Option<(bool, String)> {
+        let precision = self.precision.unwrap_or(6);
+        let digits = match conversion {
+            Conversion::Fixed => format!("{:.*}", precision, value.abs()),
+            Conversion::Scientific => format!("{:.*e}", precision, value.abs()),
+            Conversion::UpperScientific => format!("{:.*E}", precision, value.abs()),
+            _ => return None,
+        };
+
+        Some((value.is_sign_negative() && !value.is_nan(), digits))
+    }
+
+    /// This function returns whether the conversion of this format writes floats.
+    fn is_float(&self) -> bool {
+        matches!(
+            self.conversion,
+            Conversion::Fixed | Conversion::Scientific | Conversion::UpperScientific
+        )
+    }
+
+    /// This function returns whether the conversion of this format writes a sign. Only the other bases use two's complement instead.
+    fn is_signed(&self) -> bool {
+        self.is_float() || self.conversion == Conversion::Decimal
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = ();
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let spec = spec.strip_prefix('%').unwrap_or(spec);
+        let mut chars = spec.chars().peekable();
+        let (mut left_align, mut zero_pad, mut plus_sign) = (false, false, false);
+        while let Some(flag) = chars.peek() {
+            match flag {
+                '-' => left_align = true,
+                '0' => zero_pad = true,
+                '+' => plus_sign = true,
+                _ => break,
+            }
+
+            chars.next();
+        }
+
+        let number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                digits.push(digit);
+            }
+
+            digits.parse::<usize>().ok().filter(|n| *n <= MAX_WIDTH)
+        };
+
+        let width = match chars.peek() {
+            Some(digit) if digit.is_ascii_digit() => number(&mut chars).ok_or(())?,
+            _ => 0,
+        };
+
+        let precision = if chars.next_if_eq(&'.').is_some() {
+            Some(number(&mut chars).ok_or(())?)
+        } else {
+            None
+        };
+
+        let conversion = match chars.next() {
+            Some('d') => Conversion::Decimal,
+            Some('x') => Conversion::LowerHex,
+            Some('X') => Conversion::UpperHex,
+            Some('o') => Conversion::Octal,
+            Some('b') => Conversion::Binary,
+            Some('f') => Conversion::Fixed,
+            Some('e') => Conversion::Scientific,
+            Some('E') => Conversion::UpperScientific,
+            _ => return Err(()),
+        };
+
+        let format = NumberFormat {
+            left_align,
+            zero_pad,
+            plus_sign,
+            width,
+            precision,
+            conversion,
+        };
+
+        // The precision is the number of decimals, so it only makes sense for floats.
+        if chars.next().is_some() || (precision.is_some() && !format.is_float()) {
+            return Err(());
+        }
+
+        Ok(format)
+    }
+}
@@ -0,0 +1,61 @@
+//! The RunOutcome struct describes how a run of the VM ended.
+//! Besides the final value or the error, it maintains the exit code, the warnings that were collected,
+//! statistics about the run, and the output of the program if it was captured.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{builder::VMBuilder, errors::error::Error, lexer::Lexer};
+//! # fn run() -> Result<(), Error> {
+//! let tokens = Lexer::default().lex("@main printn \"Hi\" end")?;
+//! let outcome = VMBuilder::default().capture_output().build(tokens)?.run();
+//! assert_eq!(outcome.exit_code, 0);
+//! assert_eq!(outcome.output.as_deref(), Some("Hi\n"));
+//! println!("{} instructions in {:?}", outcome.stats.instructions, outcome.stats.elapsed);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{errors::error::Error, values::value::Value};
+use std::{sync::Arc, time::Duration};
+
+/// The RunStats struct maintains statistics about a single run of the VM.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunStats {
+    /// The number of instructions executed during the run.
+    pub instructions: u64,
+
+    /// The time that the run took.
+    pub elapsed: Duration,
+}
+
+/// The RunOutcome struct maintains everything that is known about a run of the VM once it has ended.
+#[derive(Debug)]
+pub struct RunOutcome {
+    /// The value of the last expression, or the error that stopped the program.
+    pub result: Result<Option<Arc<Value>>, Error>,
+
+    /// The exit code of the program, which is 0 if it finished and 1 if it failed.
+    pub exit_code: i32,
+
+    /// The warnings that were collected during the run. These do not stop the program.
+    pub warnings: Vec<Error>,
+
+    /// The statistics of the run.
+    pub stats: RunStats,
+
+    /// The output of the program, if it was captured. Otherwise, the output was written to the stream of the VM.
+    pub output: Option<String>,
+}
+
+impl RunOutcome {
+    /// This function returns whether the program finished without an error.
+    pub fn is_success(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// This function discards everything except the result of the run.
+    /// This is useful for propagating the error with the question mark operator.
+    pub fn into_result(self) -> Result<Option<Arc<Value>>, Error> {
+        self.result
+    }
+}
@@ -0,0 +1,36 @@
+//! The Overflow enum describes what the VM does when the result of an arithmetic instruction on ints does not fit in an int.
+//! By default, the result wraps around, like two's complement arithmetic on the hardware.
+//! In checked mode, an IntegerOverflow error is returned instead, which points to the instruction that overflowed.
+
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// The result wraps around, so that adding 1 to the largest int produces the smallest int.
+    #[default]
+    Wrap,
+
+    /// An error is returned.
+    Check,
+}
+
+impl FromStr for Overflow {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "wrap" => Ok(Overflow::Wrap),
+            "check" => Ok(Overflow::Check),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Overflow::Wrap => write!(f, "wrap"),
+            Overflow::Check => write!(f, "check"),
+        }
+    }
+}
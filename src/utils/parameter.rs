@@ -1,4 +1,5 @@
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Parameter {
     pub pos: usize,
     pub name: String,
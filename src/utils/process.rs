@@ -0,0 +1,109 @@
+//! The ProcessPolicy struct grants the exec instruction permission to run commands.
+//! The VM only has a ProcessPolicy if the embedder explicitly allows it, so untrusted programs can not run commands by default.
+//! A ProcessPolicy may optionally be restricted to a list of commands, which are compared against the name of the program exactly.
+//!
+//! Commands are run directly, never through a shell, so the arguments can not smuggle in other commands.
+//! The command does not receive any input, and its output is captured instead of being written to the streams of the VM.
+
+#[cfg(feature = "exec")]
+use crate::{
+    errors::{error::Error, error_kind::ErrorKind},
+    values::{value::Value, value_kinds::ValueKind},
+};
+#[cfg(feature = "exec")]
+use std::{
+    collections::BTreeMap,
+    process::{Command, Stdio},
+    sync::Arc,
+};
+
+#[derive(Debug, Default, Clone)]
+pub struct ProcessPolicy {
+    allowed: Option<Vec<String>>,
+}
+
+impl ProcessPolicy {
+    /// Constructs a new ProcessPolicy, which is optionally restricted to the given commands.
+    ///
+    /// # Arguments
+    /// `allowed` - The names of the commands that may be run.
+    pub fn new(allowed: Option<Vec<String>>) -> ProcessPolicy {
+        ProcessPolicy { allowed }
+    }
+
+    /// This function returns the names of the commands that may be run, if the policy is restricted.
+    pub fn get_allowed(&self) -> Option<&[String]> {
+        self.allowed.as_deref()
+    }
+
+    /// Allows the given command to be run. If the policy allows every command, this does nothing.
+    ///
+    /// # Arguments
+    /// `command` - The name of the command.
+    pub fn allow(&mut self, command: String) {
+        if let Some(allowed) = &mut self.allowed {
+            allowed.push(command);
+        }
+    }
+
+    /// This function checks that the given command may be run, and returns an error if it may not.
+    ///
+    /// # Arguments
+    /// `command` - The name of the command.
+    /// `pos` - The position where the command was given.
+    #[cfg(feature = "exec")]
+    pub(crate) fn check(&self, command: &str, pos: usize) -> Result<(), Error> {
+        match &self.allowed {
+            Some(allowed) if !allowed.iter().any(|name| name == command) => Err(Error::new(
+                ErrorKind::CommandNotAllowed(command.to_owned()),
+                pos,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs the command after checking that it is allowed, and waits for it to finish.
+    /// This function returns a map with the exit code of the command, along with its stdout and stderr.
+    /// The exit code is void if the command was stopped by a signal.
+    ///
+    /// # Arguments
+    /// `command` - The name of the program, followed by its arguments.
+    /// `pos` - The position where the command was given.
+    #[cfg(feature = "exec")]
+    pub(crate) fn run(&self, command: &[String], pos: usize) -> Result<Value, Error> {
+        let (program, args) = command.split_first().ok_or_else(|| {
+            Error::new(
+                ErrorKind::FailedCommand("".to_owned(), "No Program Was Given".to_owned()),
+                pos,
+            )
+        })?;
+        self.check(program, pos)?;
+
+        let output = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|error| {
+                Error::new(
+                    ErrorKind::FailedCommand(program.to_owned(), error.to_string()),
+                    pos,
+                )
+            })?;
+
+        let value = |kind| Arc::new(Value::new(pos, kind));
+        let text = |bytes: &[u8]| ValueKind::String(String::from_utf8_lossy(bytes).into_owned());
+        let mut result = BTreeMap::new();
+        result.insert(
+            "code".to_owned(),
+            value(
+                output
+                    .status
+                    .code()
+                    .map_or(ValueKind::Void, |code| ValueKind::Int(code.into())),
+            ),
+        );
+        result.insert("stdout".to_owned(), value(text(&output.stdout)));
+        result.insert("stderr".to_owned(), value(text(&output.stderr)));
+        Ok(Value::new(pos, ValueKind::Map(result)))
+    }
+}
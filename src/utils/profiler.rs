@@ -0,0 +1,233 @@
+//! The Profiler struct runs the VM one step at a time and measures where the time goes.
+//! Every instruction is counted, and the time of each step is added to the instruction that ran and to the labels that were on the call stack.
+//! Instructions that are passed as arguments to another instruction are part of its step, so their time is counted with it.
+//!
+//! Reading the clock around every step is slower than the instructions themselves, so the profiler can sample instead.
+//! When sampling one in n steps, each step is timed with a chance of one in n, and the times are scaled up to estimate the whole run.
+//! The steps are chosen at random, rather than every nth step, so that a loop whose length divides n does not always time the same instruction.
+//!
+//! The times can be written as a histogram of the instructions, or as folded stacks, which tools such as inferno and flamegraph.pl draw as a flame graph.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{errors::error::Error, lexer::Lexer, utils::profiler::Profiler, vm::VM};
+//! # fn run() -> Result<(), Error> {
+//! let mut vm = VM::new(Lexer::default().lex("@main push 1 push 2 printn add end")?)?;
+//! let mut profiler = Profiler::new(1);
+//! profiler.run(&mut vm)?;
+//! print!("{}", profiler.to_histogram());
+//! std::fs::write("program.folded", profiler.to_folded()).unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    errors::error::Error,
+    values::{value::Value, value_kinds::ValueKind},
+    vm::{StepResult, VM},
+};
+use std::{collections::BTreeMap, sync::Arc, time::Instant};
+
+/// The seed of the generator that chooses which steps are timed. It is fixed, so that the same steps are timed on every run.
+const RANDOM_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// The width of the longest bar of the histogram.
+const BAR_WIDTH: u128 = 40;
+
+/// The InstructionProfile struct maintains what was measured for a single instruction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InstructionProfile {
+    /// The number of times that the instruction ran.
+    pub count: u64,
+
+    /// The number of times that the instruction was timed.
+    pub samples: u64,
+
+    /// The total time of the timed runs, in nanoseconds.
+    pub nanos: u128,
+}
+
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    sample_every: u64,
+    steps: u64,
+    samples: u64,
+
+    /// The state of the generator that chooses which steps are timed.
+    random: u64,
+    instructions: BTreeMap<String, InstructionProfile>,
+
+    /// The time of the timed steps, in nanoseconds, for each call stack. The labels of a call stack are joined by semicolons, from the main label.
+    stacks: BTreeMap<String, u128>,
+}
+
+impl Profiler {
+    /// Constructs a new profiler that times one in n steps, on average. Passing 1 times every step.
+    ///
+    /// # Arguments
+    /// `sample_every` - How often a step is timed. A value of 0 is treated as 1.
+    pub fn new(sample_every: u64) -> Profiler {
+        Profiler {
+            sample_every: sample_every.max(1),
+            steps: 0,
+            samples: 0,
+            random: RANDOM_SEED,
+            instructions: BTreeMap::new(),
+            stacks: BTreeMap::new(),
+        }
+    }
+
+    /// Runs the VM to the end, measuring every step. The result of the run is returned.
+    ///
+    /// # Arguments
+    /// `vm` - The VM to run.
+    pub fn run(&mut self, vm: &mut VM) -> Result<Option<Arc<Value>>, Error> {
+        loop {
+            let next = vm.peek_next();
+            let timed =
+                self.sample_every == 1 || self.next_random().is_multiple_of(self.sample_every);
+            self.steps += 1;
+
+            // The call stack and the clock are only read for the steps that are timed, so that the other steps stay cheap.
+            let start = if timed {
+                let labels = vm.get_frames().iter().map(|frame| frame.name.as_str());
+                Some((labels.collect::<Vec<_>>().join(";"), Instant::now()))
+            } else {
+                None
+            };
+
+            let result = vm.step();
+            let sample = start.map(|(stack, start)| (stack, start.elapsed().as_nanos()));
+            if let Some(next) = next {
+                self.record(&next, sample);
+            }
+
+            match result? {
+                StepResult::Finished(result) => return Ok(result),
+                StepResult::Running | StepResult::Suspended => {}
+            }
+        }
+    }
+
+    /// Records a single step.
+    ///
+    /// # Arguments
+    /// `value` - The value that the step evaluated.
+    /// `sample` - If the step was timed, the labels on the call stack when it started, joined by semicolons, and its time in nanoseconds.
+    fn record(&mut self, value: &Value, sample: Option<(String, u128)>) {
+        let name = match &value.kind {
+            ValueKind::Instruction(opcode) => opcode.get_name().to_owned(),
+            kind => kind.get_value_name(),
+        };
+
+        let profile = self.instructions.entry(name).or_default();
+        profile.count += 1;
+        if let Some((stack, nanos)) = sample {
+            self.samples += 1;
+            profile.samples += 1;
+            profile.nanos += nanos;
+            *self.stacks.entry(stack).or_default() += nanos;
+        }
+    }
+
+    /// This function returns the next number of the xorshift generator that chooses which steps are timed.
+    fn next_random(&mut self) -> u64 {
+        self.random ^= self.random << 13;
+        self.random ^= self.random >> 7;
+        self.random ^= self.random << 17;
+        self.random
+    }
+
+    /// This function returns every instruction that ran with what was measured for it, from the instruction with the most estimated time.
+    pub fn get_instructions(&self) -> Vec<(&str, InstructionProfile)> {
+        let mut instructions = self
+            .instructions
+            .iter()
+            .map(|(name, profile)| (name.as_str(), *profile))
+            .collect::<Vec<_>>();
+        instructions.sort_by(|(_, first), (_, second)| {
+            self.estimate(second)
+                .cmp(&self.estimate(first))
+                .then(second.count.cmp(&first.count))
+        });
+        instructions
+    }
+
+    /// This function estimates the total time of the instruction in nanoseconds, by scaling up its average timed run to every run.
+    ///
+    /// # Arguments
+    /// `profile` - What was measured for the instruction.
+    pub fn estimate(&self, profile: &InstructionProfile) -> u128 {
+        match profile.samples {
+            0 => 0,
+            samples => profile.nanos * profile.count as u128 / samples as u128,
+        }
+    }
+
+    /// This function writes a histogram of the instructions, with the number of runs, the estimated total and average time, and the share of the total time of each.
+    pub fn to_histogram(&self) -> String {
+        let instructions = self.get_instructions();
+        let total = instructions
+            .iter()
+            .map(|(_, profile)| self.estimate(profile))
+            .sum::<u128>();
+        let longest = instructions
+            .first()
+            .map_or(0, |(_, profile)| self.estimate(profile));
+        let width = instructions
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("Instruction".len());
+
+        let mut output = format!(
+            "{:<width$} {:>10} {:>12} {:>10} {:>7}\n",
+            "Instruction",
+            "Count",
+            "Total (us)",
+            "Avg (ns)",
+            "Share",
+            width = width
+        );
+        for (name, profile) in &instructions {
+            let estimate = self.estimate(profile);
+            let share = if total == 0 {
+                0.0
+            } else {
+                estimate as f64 * 100.0 / total as f64
+            };
+            let bar = (estimate * BAR_WIDTH).checked_div(longest).unwrap_or(0) as usize;
+
+            output.push_str(&format!(
+                "{:<width$} {:>10} {:>12} {:>10} {:>6.1}% {}\n",
+                name,
+                profile.count,
+                estimate / 1000,
+                estimate / profile.count.max(1) as u128,
+                share,
+                "#".repeat(bar),
+                width = width
+            ));
+        }
+
+        if self.sample_every > 1 {
+            output.push_str(&format!(
+                "{} Of {} Steps Were Timed, So The Times Are Estimates.\n",
+                self.samples, self.steps
+            ));
+        }
+
+        output
+    }
+
+    /// This function writes the time of each call stack as folded stacks, one stack per line, such as `main;fib;fib 1200`.
+    /// The weight of each stack is its estimated time in nanoseconds.
+    pub fn to_folded(&self) -> String {
+        let scale = |nanos: u128| nanos * self.steps as u128 / self.samples.max(1) as u128;
+        self.stacks
+            .iter()
+            .map(|(stack, nanos)| format!("{} {}\n", stack, scale(*nanos)))
+            .collect()
+    }
+}
@@ -0,0 +1,88 @@
+//! The RegexOperation enum describes what the rmatch, rfind, and rreplace instructions do with a pattern.
+//! The patterns themselves are only compiled with the regex feature, which pulls in the regex crate.
+//! The regex crate matches in linear time, so a pattern from an untrusted program can not make the VM hang.
+
+#[cfg(feature = "regex")]
+use crate::{
+    errors::{error::Error, error_kind::ErrorKind},
+    values::{value::Value, value_kinds::ValueKind},
+};
+#[cfg(feature = "regex")]
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegexOperation {
+    Match,
+    Find,
+    Replace,
+}
+
+impl RegexOperation {
+    /// This function returns the name of the instruction that performs this operation.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            RegexOperation::Match => "rmatch",
+            RegexOperation::Find => "rfind",
+            RegexOperation::Replace => "rreplace",
+        }
+    }
+
+    /// This function returns the number of arguments that the instruction takes.
+    /// Every instruction takes the pattern and the text, and the rreplace instruction also takes the replacement.
+    pub fn get_arity(&self) -> usize {
+        match self {
+            RegexOperation::Match | RegexOperation::Find => 2,
+            RegexOperation::Replace => 3,
+        }
+    }
+
+    /// This function compiles the pattern and applies it to the text.
+    /// The rmatch instruction returns whether the pattern matches anywhere in the text,
+    /// the rfind instruction returns an array of every match, and the rreplace instruction replaces every match.
+    ///
+    /// # Arguments
+    /// `pattern` - The pattern to compile.
+    /// `pattern_pos` - The position where the pattern was given.
+    /// `text` - The text to apply the pattern to.
+    /// `replacement` - The replacement of the rreplace instruction, which may refer to groups with `$1` or `$name`.
+    /// `pos` - The position of the new value.
+    #[cfg(feature = "regex")]
+    pub fn apply(
+        &self,
+        pattern: &str,
+        pattern_pos: usize,
+        text: &str,
+        replacement: &str,
+        pos: usize,
+    ) -> Result<Value, Error> {
+        // The errors of the regex crate point at the pattern over several lines, but only the last line describes the problem.
+        let regex = ::regex::Regex::new(pattern).map_err(|error| {
+            let reason = error.to_string();
+            let reason = reason
+                .lines()
+                .last()
+                .unwrap_or("")
+                .trim_start_matches("error: ");
+            Error::new(ErrorKind::InvalidRegex(reason.to_owned()), pattern_pos)
+        })?;
+        let kind = match self {
+            RegexOperation::Match => ValueKind::Boolean(regex.is_match(text)),
+            RegexOperation::Find => ValueKind::Array(
+                regex
+                    .find_iter(text)
+                    .map(|found| {
+                        Arc::new(Value::new(
+                            pos,
+                            ValueKind::String(found.as_str().to_owned()),
+                        ))
+                    })
+                    .collect(),
+            ),
+            RegexOperation::Replace => {
+                ValueKind::String(regex.replace_all(text, replacement).into_owned())
+            }
+        };
+
+        Ok(Value::new(pos, kind))
+    }
+}
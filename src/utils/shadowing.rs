@@ -0,0 +1,41 @@
+//! The Shadowing enum describes what the VM does when the set instruction defines a variable that an outer scope already defines.
+//! Shadowing is allowed by default, because nested labels and blocks rely on it, but it is a common source of confusion,
+//! since the variable of the outer scope silently keeps its old value.
+
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Shadowing {
+    /// The variable is defined without any diagnostic.
+    #[default]
+    Allow,
+
+    /// The variable is defined, and a warning is collected in the outcome of the run.
+    Warn,
+
+    /// The variable is not defined, and an error is returned instead.
+    Deny,
+}
+
+impl FromStr for Shadowing {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "allow" => Ok(Shadowing::Allow),
+            "warn" => Ok(Shadowing::Warn),
+            "deny" => Ok(Shadowing::Deny),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Shadowing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Shadowing::Allow => write!(f, "allow"),
+            Shadowing::Warn => write!(f, "warn"),
+            Shadowing::Deny => write!(f, "deny"),
+        }
+    }
+}
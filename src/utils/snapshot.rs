@@ -0,0 +1,136 @@
+//! The Snapshot struct captures the parts of the VM that a program can change: the operand stack, the frames, and their variables.
+//! Two snapshots, taken before and after an instruction, can be compared to find only what the instruction changed,
+//! which is far easier to follow than the whole state of the machine.
+//!
+//! Values are shared between the snapshot and the VM, so a snapshot is cheap to take, and a change is found by identity, not by equality.
+//! This means that pushing a value that equals the one that was popped is still reported.
+
+use super::store::Store;
+use crate::{values::value::Value, vm::VM};
+use std::{cell::RefCell, fmt, rc::Rc, sync::Arc};
+
+/// The state of a single frame, which is identified by its store.
+#[derive(Debug)]
+struct FrameSnapshot {
+    name: String,
+    store: Rc<RefCell<Store>>,
+    variables: Vec<(String, Arc<Value>)>,
+}
+
+#[derive(Debug)]
+pub struct Snapshot {
+    stack: Vec<Arc<Value>>,
+    frames: Vec<FrameSnapshot>,
+}
+
+/// The Change enum describes a single difference between two snapshots.
+#[derive(Debug, Clone)]
+pub enum Change {
+    /// A value was pushed on to the operand stack.
+    Pushed(Arc<Value>),
+
+    /// A value was popped from the operand stack.
+    Popped(Arc<Value>),
+
+    /// A variable was defined or changed in the frame with the given name.
+    Defined(String, String, Arc<Value>),
+
+    /// A frame for the label with the given name was pushed on to the call stack.
+    Entered(String),
+
+    /// The frame for the label with the given name was popped from the call stack.
+    Left(String),
+}
+
+impl Snapshot {
+    /// Captures the current state of the VM.
+    ///
+    /// # Arguments
+    /// `vm` - The VM to capture.
+    pub fn new(vm: &VM) -> Snapshot {
+        Snapshot {
+            stack: vm.operand_stack.0.clone(),
+            frames: vm
+                .get_frames()
+                .iter()
+                .map(|frame| FrameSnapshot {
+                    name: frame.name.clone(),
+                    store: frame.current_store.clone(),
+                    variables: frame.get_variables(),
+                })
+                .collect(),
+        }
+    }
+
+    /// This function finds the changes between this snapshot and a later one.
+    /// Frames that were left come first, from the innermost frame, followed by the values that were popped, from the top of the stack.
+    /// Then, the values that were pushed, the frames that were entered, and the variables that were defined are listed in the order they happened.
+    ///
+    /// # Arguments
+    /// `after` - The later snapshot.
+    pub fn diff(&self, after: &Snapshot) -> Vec<Change> {
+        let mut changes = vec![];
+
+        let kept_frames = self
+            .frames
+            .iter()
+            .zip(&after.frames)
+            .take_while(|(before, after)| Rc::ptr_eq(&before.store, &after.store))
+            .count();
+        for frame in self.frames[kept_frames..].iter().rev() {
+            changes.push(Change::Left(frame.name.clone()));
+        }
+
+        let kept_values = self
+            .stack
+            .iter()
+            .zip(&after.stack)
+            .take_while(|(before, after)| Arc::ptr_eq(before, after))
+            .count();
+        for value in self.stack[kept_values..].iter().rev() {
+            changes.push(Change::Popped(value.clone()));
+        }
+
+        for value in &after.stack[kept_values..] {
+            changes.push(Change::Pushed(value.clone()));
+        }
+
+        for (idx, frame) in after.frames.iter().enumerate() {
+            let before = if idx < kept_frames {
+                Some(&self.frames[idx])
+            } else {
+                changes.push(Change::Entered(frame.name.clone()));
+                None
+            };
+
+            for (name, value) in &frame.variables {
+                let unchanged = before.is_some_and(|before| {
+                    before.variables.iter().any(|(old_name, old_value)| {
+                        old_name == name && Arc::ptr_eq(old_value, value)
+                    })
+                });
+                if !unchanged {
+                    changes.push(Change::Defined(
+                        frame.name.clone(),
+                        name.clone(),
+                        value.clone(),
+                    ));
+                }
+            }
+        }
+
+        changes
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Pushed(value) => write!(f, "+ {:?}", value),
+            Change::Popped(value) => write!(f, "- {:?}", value),
+            Change::Defined(frame, name, value) => write!(f, "@{} {} = {:?}", frame, name, value),
+            Change::Entered(frame) => write!(f, "-> @{}", frame),
+            Change::Left(frame) => write!(f, "<- @{}", frame),
+        }
+    }
+}
@@ -27,24 +27,16 @@ impl<T: Debug + PartialEq> Stack<T> {
             .ok_or_else(|| Error::new(ErrorKind::EmptyStack, pos))
     }
 
-    /// This function returns a reference to the top value on the stack, without consuming it.
+    /// This function returns a reference to the top value on the stack, which is the last value pushed, without consuming it.
     /// If the stack is empty, None is returned.
     pub fn peek(&self) -> Option<&T> {
-        if self.0.is_empty() {
-            None
-        } else {
-            self.0.last()
-        }
+        self.0.last()
     }
 
-    /// This function returns a reference to the top value on the stack, without consuming it.
+    /// This function returns a mutable reference to the top value on the stack, which is the last value pushed, without consuming it.
     /// If the stack is empty, None is returned.
     pub fn peek_mut(&mut self) -> Option<&mut T> {
-        if self.0.is_empty() {
-            None
-        } else {
-            self.0.last_mut()
-        }
+        self.0.last_mut()
     }
 
     /// This function returns true if there are no elements in the stack.
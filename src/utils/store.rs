@@ -1,53 +1,199 @@
 //! The Store struct is a basic wrapper around a HashMap.
 //! It is useful for maintaining variables and available functions.
+//! Each store is named after the scope that it belongs to, so that a failed lookup can describe where it searched.
 
+use super::case::Case;
 use crate::{
     errors::{error::Error, error_kind::ErrorKind},
     values::value::Value,
 };
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Arc};
 
 #[derive(Debug, PartialEq)]
 pub struct Store {
+    scope: String,
     parent_store: Option<Rc<RefCell<Store>>>,
-    store: HashMap<String, Rc<Value>>,
+    store: HashMap<String, Arc<Value>>,
+
+    /// Whether the names given to this store are folded to lower case before they are used.
+    case: Case,
 }
 
+/// The greatest number of similarly named variables that are suggested when a variable is not defined.
+const MAX_SUGGESTIONS: usize = 3;
+
 impl Store {
     /// Creates a new Store.
     ///
     /// # Arguments
+    /// `scope` - The name of the scope that this store belongs to, such as "@main".
     /// `parent_store` - The parent of this store. This maintains all of the variables defined in a higher scope.
-    pub fn new(parent_store: Option<Rc<RefCell<Store>>>) -> Store {
+    /// `case` - Whether the names given to this store are folded to lower case.
+    pub fn new(scope: &str, parent_store: Option<Rc<RefCell<Store>>>, case: Case) -> Store {
         Store {
+            scope: scope.to_owned(),
             parent_store,
             store: HashMap::new(),
+            case,
         }
     }
 
+    /// This function returns the name of the scope that this store belongs to.
+    pub fn get_scope(&self) -> &str {
+        &self.scope
+    }
+
+    /// This function returns whether the names given to this store are folded to lower case.
+    pub fn get_case(&self) -> Case {
+        self.case
+    }
+
+    /// This function changes whether the names given to this store are folded to lower case.
+    /// The variables that are already defined keep their names, so this should be called before any variable is defined.
+    ///
+    /// # Arguments
+    /// `case` - Whether the names are folded.
+    pub fn set_case(&mut self, case: Case) {
+        self.case = case;
+    }
+
     /// This function defines a new variable. The variable will be bound to the scope containing this store.
     /// This function will override any existing value of a previously defined value.
     ///
     /// # Arguments
     /// `name` - The name of the variable.
     /// `value` - The value of the variable.
-    pub fn define(&mut self, name: &str, value: Rc<Value>) {
-        self.store.insert(name.to_owned(), value);
+    pub fn define(&mut self, name: &str, value: Arc<Value>) {
+        self.store.insert(self.case.apply(name).into_owned(), value);
+    }
+
+    /// This function replaces the value of an existing variable in the innermost store that defines it, which may be a parent of this store.
+    /// This function returns false if no store defines the variable.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    /// `value` - The new value of the variable.
+    pub fn assign(&mut self, name: &str, value: Arc<Value>) -> bool {
+        if let Some(existing) = self.store.get_mut(self.case.apply(name).as_ref()) {
+            *existing = value;
+            return true;
+        }
+
+        match &self.parent_store {
+            Some(parent) => parent.borrow_mut().assign(name, value),
+            None => false,
+        }
+    }
+
+    /// This function returns the variables defined directly in this store, sorted by name.
+    /// The variables of the parent stores are not included.
+    pub fn get_variables(&self) -> Vec<(String, Arc<Value>)> {
+        let mut variables = self
+            .store
+            .iter()
+            .map(|(name, value)| (name.to_owned(), value.clone()))
+            .collect::<Vec<_>>();
+        variables.sort_by(|(first, _), (second, _)| first.cmp(second));
+        variables
+    }
+
+    /// This function gets the value of a variable from the parent stores, if this store does not define it yet.
+    /// Defining the variable in this store would then hide the value of the parent, so this is used to find variables that are shadowed.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    pub fn get_shadowed(&self, name: &str) -> Option<Arc<Value>> {
+        if self.store.contains_key(self.case.apply(name).as_ref()) {
+            return None;
+        }
+
+        self.parent_store
+            .as_ref()
+            .and_then(|parent| parent.borrow().lookup(name))
+    }
+
+    /// This function returns the parent of this store, if it has one.
+    pub fn get_parent(&self) -> Option<Rc<RefCell<Store>>> {
+        self.parent_store.clone()
     }
 
     /// This function gets the value of a variable. If the variable does not exist, then an error is reported.
+    /// The error names the variable, the scopes that were searched, and the variables in those scopes with similar names.
     ///
     /// # Arguments
     /// `name` - The name of the variable.
     /// `pos` - The position where this operation was called.
-    pub fn get(&self, name: &str, pos: usize) -> Result<Rc<Value>, Error> {
-        let var = self.store.get(name);
-        if let Some(variable) = var {
-            Ok(variable.clone())
-        } else if let Some(parent) = &self.parent_store {
-            parent.borrow().get(name, pos)
-        } else {
-            Err(Error::new(ErrorKind::UndefinedVariable, pos))
+    pub fn get(&self, name: &str, pos: usize) -> Result<Arc<Value>, Error> {
+        self.lookup(name)
+            .ok_or_else(|| Error::new(self.describe_undefined(&self.case.apply(name)), pos))
+    }
+
+    /// This function gets the value of a variable from this store or any of its parents.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    fn lookup(&self, name: &str) -> Option<Arc<Value>> {
+        match self.store.get(self.case.apply(name).as_ref()) {
+            Some(value) => Some(value.clone()),
+            None => self
+                .parent_store
+                .as_ref()
+                .and_then(|parent| parent.borrow().lookup(name)),
+        }
+    }
+
+    /// This function describes why the variable could not be found: the scopes that were searched, from the innermost,
+    /// and the names of the variables in those scopes that are a small number of edits away from the name.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    fn describe_undefined(&self, name: &str) -> ErrorKind {
+        let mut scopes = vec![self.scope.clone()];
+        let mut names = self.store.keys().cloned().collect::<Vec<_>>();
+        let mut parent = self.parent_store.clone();
+        while let Some(store) = parent {
+            let store = store.borrow();
+            scopes.push(store.scope.clone());
+            names.extend(store.store.keys().cloned());
+            parent = store.parent_store.clone();
+        }
+
+        // Short names are only allowed a single edit, so that every short name is not suggested.
+        let max_distance = (name.chars().count() / 3).max(1);
+        let mut suggestions = names
+            .into_iter()
+            .map(|candidate| (edit_distance(name, &candidate), candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .collect::<Vec<_>>();
+        suggestions.sort();
+        suggestions.dedup_by(|(_, first), (_, second)| first == second);
+
+        let suggestions = suggestions
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, candidate)| candidate)
+            .collect();
+        ErrorKind::UndefinedVariable(name.to_owned(), scopes, suggestions)
+    }
+}
+
+/// This function counts the insertions, deletions, and substitutions of characters that turn one name into the other.
+///
+/// # Arguments
+/// `first` - The first name.
+/// `second` - The second name.
+fn edit_distance(first: &str, second: &str) -> usize {
+    let second = second.chars().collect::<Vec<_>>();
+    let mut previous = (0..=second.len()).collect::<Vec<_>>();
+    for (i, first_char) in first.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, second_char) in second.iter().enumerate() {
+            let substitution = previous[j] + usize::from(first_char != *second_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
         }
+
+        previous = current;
     }
+
+    previous[second.len()]
 }
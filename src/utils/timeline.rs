@@ -0,0 +1,172 @@
+//! The Timeline struct records every step of a run: the instruction that ran, along with the operand stack and the frames after it.
+//! The timeline is written as JSON, so that a visualizer can replay the run one step at a time, which is useful for teaching how a stack machine works.
+//!
+//! The timeline holds a copy of the state after every step, so it grows with the length of the run. Long runs should be limited with fuel.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{errors::error::Error, lexer::Lexer, utils::timeline::Timeline, vm::VM};
+//! # fn run() -> Result<(), Error> {
+//! let source = "@main push 1 push 2 printn add end";
+//! let mut vm = VM::new(Lexer::default().lex(source)?)?;
+//! let mut timeline = Timeline::default();
+//! timeline.run(&mut vm)?;
+//! std::fs::write("timeline.json", timeline.to_json(source)).unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use super::json::Json;
+use crate::{
+    debugger::line_of,
+    errors::error::Error,
+    values::value::Value,
+    vm::{StepResult, VM},
+};
+use std::sync::Arc;
+
+/// The state of a single frame after a step.
+#[derive(Debug, Clone)]
+struct FrameState {
+    name: String,
+    variables: Vec<(String, Arc<Value>)>,
+}
+
+/// A single step of the run.
+#[derive(Debug, Clone)]
+struct Step {
+    /// The position of the instruction that ran.
+    pos: usize,
+
+    /// The instruction that ran, as it is written in the source.
+    instruction: String,
+
+    /// The operand stack after the instruction ran, from the bottom to the top.
+    stack: Vec<Arc<Value>>,
+
+    /// The frames after the instruction ran, from the main frame to the current frame.
+    frames: Vec<FrameState>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    steps: Vec<Step>,
+
+    /// The message and the position of the error that ended the run, if there was one.
+    error: Option<(String, Option<usize>)>,
+}
+
+impl Timeline {
+    /// Runs the VM to the end, recording every step. The result of the run is returned, and an error that ends the run is also recorded.
+    ///
+    /// # Arguments
+    /// `vm` - The VM to run.
+    pub fn run(&mut self, vm: &mut VM) -> Result<Option<Arc<Value>>, Error> {
+        loop {
+            let next = vm.peek_next();
+            let result = vm.step();
+            if let Some(next) = next {
+                self.record(vm, &next);
+            }
+
+            match result {
+                Ok(StepResult::Finished(result)) => return Ok(result),
+                Ok(_) => {}
+                Err(error) => {
+                    self.error = Some((error.get_kind().clone().into(), error.get_position()));
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    /// Records the state of the VM after the given instruction ran.
+    ///
+    /// # Arguments
+    /// `vm` - The VM that ran the instruction.
+    /// `instruction` - The instruction that ran.
+    pub fn record(&mut self, vm: &VM, instruction: &Value) {
+        self.steps.push(Step {
+            pos: instruction.pos,
+            instruction: instruction.to_string(),
+            stack: vm.operand_stack.0.clone(),
+            frames: vm
+                .get_frames()
+                .iter()
+                .map(|frame| FrameState {
+                    name: frame.name.clone(),
+                    variables: frame.get_variables(),
+                })
+                .collect(),
+        });
+    }
+
+    /// This function gets the number of steps that were recorded.
+    pub fn get_len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// This function writes the timeline as JSON, along with the source of the program, so that a visualizer can highlight the line of every step.
+    /// Every value is written with its type and the way that the print instruction writes it.
+    ///
+    /// # Arguments
+    /// `source` - The source of the program.
+    pub fn to_json(&self, source: &str) -> String {
+        let value = |value: &Arc<Value>| {
+            Json::object(vec![
+                ("type", value.kind.get_value_name().into()),
+                ("value", value.to_string().into()),
+            ])
+        };
+
+        let steps = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(idx, step)| {
+                let frames = step
+                    .frames
+                    .iter()
+                    .map(|frame| {
+                        let variables = frame
+                            .variables
+                            .iter()
+                            .map(|(name, variable)| {
+                                Json::object(vec![
+                                    ("name", name.as_str().into()),
+                                    ("value", value(variable)),
+                                ])
+                            })
+                            .collect();
+                        Json::object(vec![
+                            ("label", frame.name.as_str().into()),
+                            ("variables", Json::Array(variables)),
+                        ])
+                    })
+                    .collect();
+
+                Json::object(vec![
+                    ("step", idx.into()),
+                    ("line", line_of(source, step.pos).into()),
+                    ("pos", step.pos.into()),
+                    ("instruction", step.instruction.as_str().into()),
+                    ("stack", Json::Array(step.stack.iter().map(value).collect())),
+                    ("frames", Json::Array(frames)),
+                ])
+            })
+            .collect();
+
+        let mut entries = vec![("source", source.into()), ("steps", Json::Array(steps))];
+        if let Some((message, pos)) = &self.error {
+            let mut error = vec![("message", message.as_str().into())];
+            if let Some(pos) = pos {
+                error.push(("line", line_of(source, *pos).into()));
+                error.push(("pos", (*pos).into()));
+            }
+
+            entries.push(("error", Json::object(error)));
+        }
+
+        Json::object(entries).to_string()
+    }
+}
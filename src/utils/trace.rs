@@ -0,0 +1,186 @@
+//! The Trace struct records the nondeterministic inputs of a program, so that a run can be replayed exactly.
+//! Every instruction that observes the outside world, such as the time, the contents of a file, or a response from the network,
+//! asks the trace for its result. While recording, the result is computed and appended to the trace.
+//! While replaying, the result is taken from the trace instead, in the same order, and the outside world is never touched.
+//!
+//! Errors are recorded as well, by their message, so that a failure that depended on the outside world is reproduced too.
+//! A trace is a handle to shared state, so embedders can keep a clone of it and save it after the VM finishes.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{builder::VMBuilder, lexer::Lexer, utils::trace::Trace};
+//! let trace = Trace::record();
+//! let tokens = Lexer::default().lex("@main push 1 end").ok().unwrap();
+//! let mut vm = VMBuilder::default().trace(trace.clone()).build(tokens).ok().unwrap();
+//! vm.run().into_result().ok().unwrap();
+//!
+//! let replay = Trace::replay(&trace.to_json()).ok().unwrap();
+//! assert!(replay.is_replaying());
+//! ```
+
+use crate::{
+    errors::{error::Error, error_kind::ErrorKind},
+    utils::json::Json,
+    values::value::Value,
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// The Outcome enum describes the result of an instruction that observed the outside world.
+#[derive(Debug, Clone)]
+enum Outcome {
+    Value(Json),
+    Error(String, Option<usize>),
+}
+
+#[derive(Debug, Clone)]
+struct Event {
+    instruction: String,
+    outcome: Outcome,
+}
+
+#[derive(Debug)]
+struct State {
+    replaying: bool,
+    events: Vec<Event>,
+    next: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Trace(Rc<RefCell<State>>);
+
+impl Trace {
+    /// Constructs a new, empty Trace that records the inputs of the program.
+    pub fn record() -> Trace {
+        Trace(Rc::new(RefCell::new(State {
+            replaying: false,
+            events: vec![],
+            next: 0,
+        })))
+    }
+
+    /// Constructs a new Trace that replays the inputs saved in the given text, which was produced by the to_json method.
+    ///
+    /// # Arguments
+    /// `text` - The saved trace.
+    pub fn replay(text: &str) -> Result<Trace, Error> {
+        let invalid = || Error::message_only(ErrorKind::InvalidTrace);
+        let json = Json::parse(text).map_err(|_| invalid())?;
+        let events = json
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|event| {
+                let instruction = event.get("instruction").and_then(Json::as_str);
+                let outcome = match (event.get("value"), event.get("error")) {
+                    (Some(value), None) => Outcome::Value(value.clone()),
+                    (None, Some(error)) => Outcome::Error(
+                        error.as_str()?.to_owned(),
+                        event
+                            .get("pos")
+                            .and_then(Json::as_int)
+                            .map(|pos| pos as usize),
+                    ),
+                    _ => return None,
+                };
+
+                Some(Event {
+                    instruction: instruction?.to_owned(),
+                    outcome,
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?;
+
+        Ok(Trace(Rc::new(RefCell::new(State {
+            replaying: true,
+            events,
+            next: 0,
+        }))))
+    }
+
+    /// This function returns whether the trace replays inputs, instead of recording them.
+    pub fn is_replaying(&self) -> bool {
+        self.0.borrow().replaying
+    }
+
+    /// This function returns the number of inputs in the trace.
+    pub fn get_len(&self) -> usize {
+        self.0.borrow().events.len()
+    }
+
+    /// This function writes the trace as JSON, so that it can be saved and replayed later.
+    pub fn to_json(&self) -> String {
+        let events = self
+            .0
+            .borrow()
+            .events
+            .iter()
+            .map(|event| {
+                let mut entries = vec![("instruction", event.instruction.clone().into())];
+                match &event.outcome {
+                    Outcome::Value(value) => entries.push(("value", value.clone())),
+                    Outcome::Error(message, pos) => {
+                        entries.push(("error", message.clone().into()));
+                        if let Some(pos) = pos {
+                            entries.push(("pos", (*pos).into()));
+                        }
+                    }
+                }
+
+                Json::object(entries)
+            })
+            .collect();
+        Json::Array(events).to_string()
+    }
+
+    /// This function gets the result of an instruction that observes the outside world.
+    /// While recording, the result is computed and saved. While replaying, the next saved result is returned instead.
+    ///
+    /// # Arguments
+    /// `instruction` - The name of the instruction.
+    /// `pos` - The position of the instruction, which is also the position of the result.
+    /// `observe` - The function that computes the result from the outside world.
+    pub(crate) fn observe<F: FnOnce() -> Result<Value, Error>>(
+        &self,
+        instruction: &str,
+        pos: usize,
+        observe: F,
+    ) -> Result<Value, Error> {
+        let mut state = self.0.borrow_mut();
+        if !state.replaying {
+            let result = observe();
+            let outcome = match &result {
+                Ok(value) => Outcome::Value(Json::from_value(value, pos)?),
+                Err(error) => Outcome::Error(error.get_kind().clone().into(), error.get_position()),
+            };
+
+            state.events.push(Event {
+                instruction: instruction.to_owned(),
+                outcome,
+            });
+            return result;
+        }
+
+        let event =
+            state.events.get(state.next).cloned().ok_or_else(|| {
+                Error::new(ErrorKind::TraceExhausted(instruction.to_owned()), pos)
+            })?;
+        if event.instruction != instruction {
+            return Err(Error::new(
+                ErrorKind::TraceMismatch(event.instruction, instruction.to_owned()),
+                pos,
+            ));
+        }
+
+        state.next += 1;
+        match event.outcome {
+            Outcome::Value(value) => Ok(value.to_value(pos)),
+            Outcome::Error(message, Some(pos)) => {
+                Err(Error::new(ErrorKind::ReplayedError(message), pos))
+            }
+            Outcome::Error(message, None) => {
+                Err(Error::message_only(ErrorKind::ReplayedError(message)))
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! The diff function finds the differences between two values, so that a failed assertion can point to what differs, instead of printing both values whole.
+//! Arrays are compared element by element and maps key by key, so a difference deep inside of a large value is reported with the path to it, such as `["users"][2]`.
+//! Strings are reported with the first character at which they differ.
+//! Only the first few differences are reported, followed by the number of the others.
+
+use super::float_equality::FloatEquality;
+use crate::values::{value::Value, value_kinds::ValueKind};
+
+/// The most differences that are reported. The rest are only counted.
+const MAX_DIFFERENCES: usize = 16;
+
+/// This function returns a line for every difference between the expected value and the actual value, from the outside in.
+/// The values are equal if no lines are returned. Floats are compared with the given mode, like the eq instruction compares them.
+///
+/// # Arguments
+/// `expected` - The value that was expected.
+/// `actual` - The value that was found.
+/// `float_equality` - How floats are compared.
+pub fn diff(expected: &Value, actual: &Value, float_equality: FloatEquality) -> Vec<String> {
+    let mut differences = vec![];
+    diff_into(
+        &mut String::new(),
+        expected,
+        actual,
+        float_equality,
+        &mut differences,
+    );
+    if differences.len() > MAX_DIFFERENCES {
+        let others = differences.len() - MAX_DIFFERENCES;
+        differences.truncate(MAX_DIFFERENCES);
+        differences.push(format!(
+            "... and {} more {}",
+            others,
+            if others == 1 {
+                "difference"
+            } else {
+                "differences"
+            }
+        ));
+    }
+
+    differences
+}
+
+/// This function adds the differences between the two values to the list, with the given path in front of each one.
+///
+/// # Arguments
+/// `path` - The path from the outermost value to these values, which is empty for the outermost value.
+/// `expected` - The value that was expected.
+/// `actual` - The value that was found.
+/// `float_equality` - How floats are compared.
+/// `differences` - The list of differences so far.
+fn diff_into(
+    path: &mut String,
+    expected: &Value,
+    actual: &Value,
+    float_equality: FloatEquality,
+    differences: &mut Vec<String>,
+) {
+    match (&expected.kind, &actual.kind) {
+        (ValueKind::Array(expected_values), ValueKind::Array(actual_values)) => {
+            if expected_values.len() != actual_values.len() {
+                differences.push(format!(
+                    "{}: expected {} elements, found {}",
+                    describe(path),
+                    expected_values.len(),
+                    actual_values.len()
+                ));
+            }
+
+            for index in 0..expected_values.len().max(actual_values.len()) {
+                let len = path.len();
+                path.push_str(&format!("[{}]", index));
+                match (expected_values.get(index), actual_values.get(index)) {
+                    (Some(expected), Some(actual)) => {
+                        diff_into(path, expected, actual, float_equality, differences)
+                    }
+                    (Some(expected), None) => {
+                        differences.push(format!("{}: missing {:?}", path, expected))
+                    }
+                    (None, Some(actual)) => {
+                        differences.push(format!("{}: unexpected {:?}", path, actual))
+                    }
+                    (None, None) => {}
+                }
+
+                path.truncate(len);
+            }
+        }
+        (ValueKind::Map(expected_entries), ValueKind::Map(actual_entries)) => {
+            let mut keys = expected_entries.keys().collect::<Vec<_>>();
+            keys.extend(
+                actual_entries
+                    .keys()
+                    .filter(|key| !expected_entries.contains_key(*key)),
+            );
+            keys.sort();
+            for key in keys {
+                let len = path.len();
+                path.push_str(&format!("[{:?}]", key));
+                match (expected_entries.get(key), actual_entries.get(key)) {
+                    (Some(expected), Some(actual)) => {
+                        diff_into(path, expected, actual, float_equality, differences)
+                    }
+                    (Some(expected), None) => {
+                        differences.push(format!("{}: missing {:?}", path, expected))
+                    }
+                    (None, Some(actual)) => {
+                        differences.push(format!("{}: unexpected {:?}", path, actual))
+                    }
+                    (None, None) => {}
+                }
+
+                path.truncate(len);
+            }
+        }
+        (ValueKind::String(expected_text), ValueKind::String(actual_text))
+            if expected_text != actual_text =>
+        {
+            let index = expected_text
+                .chars()
+                .zip(actual_text.chars())
+                .take_while(|(expected, actual)| expected == actual)
+                .count();
+            differences.push(format!(
+                "{}: expected {:?}, found {:?}, which first differ at character {}",
+                describe(path),
+                expected_text,
+                actual_text,
+                index
+            ));
+        }
+        (ValueKind::Void, ValueKind::Void) => {}
+        _ if expected.equal(actual, float_equality, 0).is_truthy() => {}
+        (expected_kind, actual_kind)
+            if expected_kind.get_value_name() != actual_kind.get_value_name() =>
+        {
+            differences.push(format!(
+                "{}: expected {} {:?}, found {} {:?}",
+                describe(path),
+                expected_kind.get_value_name(),
+                expected,
+                actual_kind.get_value_name(),
+                actual
+            ))
+        }
+        _ => differences.push(format!(
+            "{}: expected {:?}, found {:?}",
+            describe(path),
+            expected,
+            actual
+        )),
+    }
+}
+
+/// This function describes the path to a value in a difference. The outermost value has no path, so it is called the value.
+///
+/// # Arguments
+/// `path` - The path to the value.
+fn describe(path: &str) -> &str {
+    if path.is_empty() {
+        "value"
+    } else {
+        path
+    }
+}
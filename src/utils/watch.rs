@@ -0,0 +1,146 @@
+//! The Watchpoints struct maintains the variables that the host watches.
+//! Whenever a watched variable is written, whether by the set instruction, by the instructions that update variables, or by the parameters of a label,
+//! the VM reports a WatchEvent with the old and the new value of the variable.
+//!
+//! The events are passed to the callback of the watchpoints, if one was given. If the VM suspends on breaks, it also suspends after the step that wrote the variable,
+//! and the events of that step can be taken from the VM. This is how the debugger stops on watchpoints.
+//!
+//! # Example
+//! ```
+//! # use dark_vm::{builder::VMBuilder, errors::error::Error, lexer::Lexer};
+//! # fn run() -> Result<(), Error> {
+//! let tokens = Lexer::default().lex("@main set x 1 set x 2 end")?;
+//! VMBuilder::default()
+//!     .watch("x")
+//!     .on_watch(|event| println!("{}", event))
+//!     .build(tokens)?
+//!     .run();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::values::value::Value;
+use std::{cell::RefCell, collections::BTreeSet, fmt, rc::Rc, sync::Arc};
+
+/// The callback that receives every write to a watched variable.
+pub type WatchCallback = Rc<RefCell<dyn FnMut(&WatchEvent)>>;
+
+/// The WatchEvent struct describes a single write to a watched variable.
+#[derive(Debug, Clone)]
+pub struct WatchEvent {
+    /// The name of the variable.
+    pub name: String,
+
+    /// The name of the label whose frame the variable was written in.
+    pub label: String,
+
+    /// The value of the variable before the write, if it was defined.
+    pub old: Option<Arc<Value>>,
+
+    /// The value of the variable after the write.
+    pub new: Arc<Value>,
+
+    /// The position of the instruction that wrote the variable.
+    pub pos: usize,
+}
+
+impl fmt::Display for WatchEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.old {
+            Some(old) => write!(
+                f,
+                "The Variable '{}' In @{} Changed From {:?} To {:?}.",
+                self.name, self.label, old, self.new
+            ),
+            None => write!(
+                f,
+                "The Variable '{}' In @{} Was Defined As {:?}.",
+                self.name, self.label, self.new
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Watchpoints {
+    names: BTreeSet<String>,
+    callback: Option<WatchCallback>,
+
+    /// The events of the current step, which are only kept if the VM suspends on breaks.
+    events: Vec<WatchEvent>,
+}
+
+impl Watchpoints {
+    /// This function starts watching the variable with the given name, in every frame.
+    /// This function returns false if the variable was already watched.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    pub fn add(&mut self, name: &str) -> bool {
+        self.names.insert(name.to_owned())
+    }
+
+    /// This function stops watching the variable with the given name.
+    /// This function returns false if the variable was not watched.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.names.remove(name)
+    }
+
+    /// This function returns the names of the watched variables, sorted by name.
+    pub fn get_names(&self) -> Vec<&str> {
+        self.names.iter().map(String::as_str).collect()
+    }
+
+    /// This function returns whether the variable with the given name is watched.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    pub fn is_watched(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    /// This function sets the callback that receives every write to a watched variable.
+    ///
+    /// # Arguments
+    /// `callback` - The callback.
+    pub fn set_callback<F: FnMut(&WatchEvent) + 'static>(&mut self, callback: F) {
+        self.callback = Some(Rc::new(RefCell::new(callback)));
+    }
+
+    /// This function passes the event to the callback, and keeps it until the events are taken if asked to.
+    ///
+    /// # Arguments
+    /// `event` - The write to a watched variable.
+    /// `keep` - Whether the event should be kept.
+    pub(crate) fn notify(&mut self, event: WatchEvent, keep: bool) {
+        if let Some(callback) = &self.callback {
+            (callback.borrow_mut())(&event);
+        }
+
+        if keep {
+            self.events.push(event);
+        }
+    }
+
+    /// This function removes the events that were kept and returns them, in the order that they happened.
+    pub fn take_events(&mut self) -> Vec<WatchEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// This function discards the events that were kept. The VM does this at the start of every step.
+    pub(crate) fn clear_events(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl fmt::Debug for Watchpoints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watchpoints")
+            .field("names", &self.names)
+            .field("events", &self.events)
+            .finish()
+    }
+}
@@ -2,8 +2,9 @@ use super::value_kinds::ValueKind;
 use crate::{
     errors::{error::Error, error_kind::ErrorKind},
     tokens::{token::Token, token_kind::TokenKind},
+    utils::{float_equality::FloatEquality, overflow::Overflow},
 };
-use std::fmt;
+use std::{fmt, sync::Arc};
 
 /// The Value struct maintains both the position where this value is used and its kind.
 /// Maintaining the position is useful because it can be used to produce good error messages.
@@ -31,8 +32,9 @@ impl Value {
     ///
     /// # Arguments
     /// `other` - The other value to add.
+    /// `overflow` - What to do if the result is an int that does not fit.
     /// `pos` - The position where this operation was called.
-    pub fn add(&self, other: &Value, pos: usize) -> Result<Value, Error> {
+    pub fn add(&self, other: &Value, overflow: Overflow, pos: usize) -> Result<Value, Error> {
         match (&self.kind, &other.kind) {
             (ValueKind::String(val1), ValueKind::String(val2)) => Ok(Value::new(
                 pos,
@@ -47,9 +49,12 @@ impl Value {
                 ValueKind::String(format!("{}{:#?}", val1, other)),
             )),
 
-            (ValueKind::Int(val1), ValueKind::Int(val2)) => {
-                Ok(Value::new(pos, ValueKind::Int(val1 + val2)))
-            }
+            (ValueKind::Int(val1), ValueKind::Int(val2)) => int_result(
+                val1.checked_add(*val2),
+                val1.wrapping_add(*val2),
+                overflow,
+                pos,
+            ),
             (ValueKind::Int(val1), ValueKind::Float(val2)) => {
                 Ok(Value::new(pos, ValueKind::Float(*val1 as f64 + val2)))
             }
@@ -79,12 +84,16 @@ impl Value {
     ///
     /// # Arguments
     /// `other` - The other value to subtract.
+    /// `overflow` - What to do if the result is an int that does not fit.
     /// `pos` - The position where this operation was called.
-    pub fn sub(&self, other: &Value, pos: usize) -> Result<Value, Error> {
+    pub fn sub(&self, other: &Value, overflow: Overflow, pos: usize) -> Result<Value, Error> {
         match (&self.kind, &other.kind) {
-            (ValueKind::Int(val1), ValueKind::Int(val2)) => {
-                Ok(Value::new(pos, ValueKind::Int(val1 - val2)))
-            }
+            (ValueKind::Int(val1), ValueKind::Int(val2)) => int_result(
+                val1.checked_sub(*val2),
+                val1.wrapping_sub(*val2),
+                overflow,
+                pos,
+            ),
             (ValueKind::Int(val1), ValueKind::Float(val2)) => {
                 Ok(Value::new(pos, ValueKind::Float(*val1 as f64 - val2)))
             }
@@ -114,20 +123,19 @@ impl Value {
     ///
     /// # Arguments
     /// `other` - The other value to multiply.
+    /// `overflow` - What to do if the result is an int that does not fit.
     /// `pos` - The position where this operation was called.
-    pub fn mul(&self, other: &Value, pos: usize) -> Result<Value, Error> {
+    pub fn mul(&self, other: &Value, overflow: Overflow, pos: usize) -> Result<Value, Error> {
         match (&self.kind, &other.kind) {
-            (ValueKind::String(val1), ValueKind::Int(val2)) => Ok(Value::new(
+            (ValueKind::String(val1), ValueKind::Int(val2)) => repeat(val1, *val2, pos),
+            (ValueKind::Int(val1), ValueKind::String(val2)) => repeat(val2, *val1, pos),
+
+            (ValueKind::Int(val1), ValueKind::Int(val2)) => int_result(
+                val1.checked_mul(*val2),
+                val1.wrapping_mul(*val2),
+                overflow,
                 pos,
-                ValueKind::String(val1.repeat(val2.abs() as usize)),
-            )),
-            (ValueKind::Int(val1), ValueKind::String(val2)) if self.kind != ValueKind::Void => Ok(
-                Value::new(pos, ValueKind::String(val2.repeat(val1.abs() as usize))),
             ),
-
-            (ValueKind::Int(val1), ValueKind::Int(val2)) => {
-                Ok(Value::new(pos, ValueKind::Int(val1 * val2)))
-            }
             (ValueKind::Int(val1), ValueKind::Float(val2)) => {
                 Ok(Value::new(pos, ValueKind::Float(*val1 as f64 * val2)))
             }
@@ -153,6 +161,7 @@ impl Value {
     }
 
     /// This function takes the current value and a reference to another value and divides them.
+    /// The result is always a float, even if both values are ints, so that no precision is lost silently.
     /// Note that this function does not take ownership of either value. Instead, it creates a new value.
     ///
     /// # Arguments
@@ -164,25 +173,28 @@ impl Value {
                 if val2 == &0 {
                     Err(Error::new(ErrorKind::DivisionByZero, pos))
                 } else {
-                    Ok(Value::new(pos, ValueKind::Int(val1 / val2)))
+                    Ok(Value::new(
+                        pos,
+                        ValueKind::Float(*val1 as f64 / *val2 as f64),
+                    ))
                 }
             }
             (ValueKind::Int(val1), ValueKind::Float(val2)) => {
-                if val2 - 0.0 < std::f64::EPSILON {
+                if *val2 == 0.0 {
                     Err(Error::new(ErrorKind::DivisionByZero, pos))
                 } else {
                     Ok(Value::new(pos, ValueKind::Float(*val1 as f64 / val2)))
                 }
             }
             (ValueKind::Float(val1), ValueKind::Int(val2)) => {
-                if val1 - 0.0 < std::f64::EPSILON {
+                if *val2 == 0 {
                     Err(Error::new(ErrorKind::DivisionByZero, pos))
                 } else {
                     Ok(Value::new(pos, ValueKind::Float(val1 / *val2 as f64)))
                 }
             }
             (ValueKind::Float(val1), ValueKind::Float(val2)) => {
-                if val2 - 0.0 < std::f64::EPSILON {
+                if *val2 == 0.0 {
                     Err(Error::new(ErrorKind::DivisionByZero, pos))
                 } else {
                     Ok(Value::new(pos, ValueKind::Float(val1 / val2)))
@@ -202,19 +214,82 @@ impl Value {
         }
     }
 
-    /// This function takes the current value and a reference to another value and divides them.
-    /// Note that this function does not take ownership of either value. Instead, it creates a new value.
+    /// This function takes the current value and a reference to another value and divides them with integer division, which rounds toward zero.
+    /// Both values must be ints. Note that this function does not take ownership of either value. Instead, it creates a new value.
     ///
     /// # Arguments
     /// `other` - The other value to divide.
+    /// `overflow` - What to do if the result is an int that does not fit.
+    /// `pos` - The position where this operation was called.
+    pub fn int_div(&self, other: &Value, overflow: Overflow, pos: usize) -> Result<Value, Error> {
+        match (&self.kind, &other.kind) {
+            (ValueKind::Int(val1), ValueKind::Int(val2)) => {
+                if val2 == &0 {
+                    Err(Error::new(ErrorKind::DivisionByZero, pos))
+                } else {
+                    int_result(
+                        val1.checked_div(*val2),
+                        val1.wrapping_div(*val2),
+                        overflow,
+                        pos,
+                    )
+                }
+            }
+            _ => Err(Error::new(
+                ErrorKind::UnsupportedOperation(
+                    "IDiv".to_owned(),
+                    format!(
+                        "The Value '{}' And The Value '{}'.",
+                        self.kind.get_value_name(),
+                        other.kind.get_value_name()
+                    ),
+                ),
+                pos,
+            )),
+        }
+    }
+
+    /// This function takes the current value and a reference to another value and returns the remainder of dividing them.
+    /// Note that this function does not take ownership of either value. Instead, it creates a new value.
+    ///
+    /// # Arguments
+    /// `other` - The other value to divide by.
+    /// `overflow` - What to do if the result is an int that does not fit.
     /// `pos` - The position where this operation was called.
-    pub fn modulus(&self, other: &Value, pos: usize) -> Result<Value, Error> {
+    pub fn modulus(&self, other: &Value, overflow: Overflow, pos: usize) -> Result<Value, Error> {
         match (&self.kind, &other.kind) {
             (ValueKind::Int(val1), ValueKind::Int(val2)) => {
-                Ok(Value::new(pos, ValueKind::Int(val1 % val2)))
+                if val2 == &0 {
+                    Err(Error::new(ErrorKind::DivisionByZero, pos))
+                } else {
+                    int_result(
+                        val1.checked_rem(*val2),
+                        val1.wrapping_rem(*val2),
+                        overflow,
+                        pos,
+                    )
+                }
+            }
+            (ValueKind::Int(val1), ValueKind::Float(val2)) => {
+                if *val2 == 0.0 {
+                    Err(Error::new(ErrorKind::DivisionByZero, pos))
+                } else {
+                    Ok(Value::new(pos, ValueKind::Float(*val1 as f64 % val2)))
+                }
+            }
+            (ValueKind::Float(val1), ValueKind::Int(val2)) => {
+                if *val2 == 0 {
+                    Err(Error::new(ErrorKind::DivisionByZero, pos))
+                } else {
+                    Ok(Value::new(pos, ValueKind::Float(val1 % *val2 as f64)))
+                }
             }
             (ValueKind::Float(val1), ValueKind::Float(val2)) => {
-                Ok(Value::new(pos, ValueKind::Float(val1 % val2)))
+                if *val2 == 0.0 {
+                    Err(Error::new(ErrorKind::DivisionByZero, pos))
+                } else {
+                    Ok(Value::new(pos, ValueKind::Float(val1 % val2)))
+                }
             }
             _ => Err(Error::new(
                 ErrorKind::UnsupportedOperation(
@@ -230,6 +305,151 @@ impl Value {
         }
     }
 
+    /// This function returns the current value with its sign flipped, which must be an int or a float.
+    ///
+    /// # Arguments
+    /// `overflow` - What to do if the value is the smallest int, whose negation does not fit.
+    /// `pos` - The position where this operation was called.
+    pub fn neg(&self, overflow: Overflow, pos: usize) -> Result<Value, Error> {
+        match &self.kind {
+            ValueKind::Int(val) => int_result(val.checked_neg(), val.wrapping_neg(), overflow, pos),
+            ValueKind::Float(val) => Ok(Value::new(pos, ValueKind::Float(-val))),
+            _ => Err(Error::new(
+                ErrorKind::UnsupportedOperation(
+                    "Neg".to_owned(),
+                    format!("The Value '{}'.", self.kind.get_value_name()),
+                ),
+                pos,
+            )),
+        }
+    }
+
+    /// This function returns the absolute value of the current value, which must be an int or a float.
+    ///
+    /// # Arguments
+    /// `overflow` - What to do if the value is the smallest int, whose absolute value does not fit.
+    /// `pos` - The position where this operation was called.
+    pub fn abs(&self, overflow: Overflow, pos: usize) -> Result<Value, Error> {
+        match &self.kind {
+            ValueKind::Int(val) => int_result(val.checked_abs(), val.wrapping_abs(), overflow, pos),
+            ValueKind::Float(val) => Ok(Value::new(pos, ValueKind::Float(val.abs()))),
+            _ => Err(Error::new(
+                ErrorKind::UnsupportedOperation(
+                    "Abs".to_owned(),
+                    format!("The Value '{}'.", self.kind.get_value_name()),
+                ),
+                pos,
+            )),
+        }
+    }
+
+    /// This function takes the current value and a reference to another value and returns the bitwise and of them.
+    /// Both values must be ints. Note that this function does not take ownership of either value. Instead, it creates a new value.
+    ///
+    /// # Arguments
+    /// `other` - The other value.
+    /// `pos` - The position where this operation was called.
+    pub fn bit_and(&self, other: &Value, pos: usize) -> Result<Value, Error> {
+        self.bitwise(other, "BAnd", pos, |val1, val2| Ok(val1 & val2))
+    }
+
+    /// This function takes the current value and a reference to another value and returns the bitwise or of them.
+    /// Both values must be ints. Note that this function does not take ownership of either value. Instead, it creates a new value.
+    ///
+    /// # Arguments
+    /// `other` - The other value.
+    /// `pos` - The position where this operation was called.
+    pub fn bit_or(&self, other: &Value, pos: usize) -> Result<Value, Error> {
+        self.bitwise(other, "BOr", pos, |val1, val2| Ok(val1 | val2))
+    }
+
+    /// This function takes the current value and a reference to another value and returns the bitwise exclusive or of them.
+    /// Both values must be ints. Note that this function does not take ownership of either value. Instead, it creates a new value.
+    ///
+    /// # Arguments
+    /// `other` - The other value.
+    /// `pos` - The position where this operation was called.
+    pub fn bit_xor(&self, other: &Value, pos: usize) -> Result<Value, Error> {
+        self.bitwise(other, "BXor", pos, |val1, val2| Ok(val1 ^ val2))
+    }
+
+    /// This function takes the current value and shifts its bits to the left by the other value. The bits shifted out are discarded.
+    /// Both values must be ints, and the amount has to be between 0 and 63, otherwise an InvalidShift error is returned.
+    ///
+    /// # Arguments
+    /// `other` - The amount to shift by.
+    /// `pos` - The position where this operation was called.
+    pub fn shift_left(&self, other: &Value, pos: usize) -> Result<Value, Error> {
+        self.bitwise(other, "Shl", pos, |val1, val2| {
+            shift_amount(val2, pos).map(|amount| val1 << amount)
+        })
+    }
+
+    /// This function takes the current value and shifts its bits to the right by the other value.
+    /// The shift is arithmetic, so the sign of a negative int is kept.
+    /// Both values must be ints, and the amount has to be between 0 and 63, otherwise an InvalidShift error is returned.
+    ///
+    /// # Arguments
+    /// `other` - The amount to shift by.
+    /// `pos` - The position where this operation was called.
+    pub fn shift_right(&self, other: &Value, pos: usize) -> Result<Value, Error> {
+        self.bitwise(other, "Shr", pos, |val1, val2| {
+            shift_amount(val2, pos).map(|amount| val1 >> amount)
+        })
+    }
+
+    /// This function returns the bitwise not of the current value, which must be an int.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this operation was called.
+    pub fn bit_not(&self, pos: usize) -> Result<Value, Error> {
+        match &self.kind {
+            ValueKind::Int(val) => Ok(Value::new(pos, ValueKind::Int(!val))),
+            _ => Err(Error::new(
+                ErrorKind::UnsupportedOperation(
+                    "BNot".to_owned(),
+                    format!("The Value '{}'.", self.kind.get_value_name()),
+                ),
+                pos,
+            )),
+        }
+    }
+
+    /// This function applies a bitwise operation to the current value and the other value, which must both be ints.
+    ///
+    /// # Arguments
+    /// `other` - The other value.
+    /// `name` - The name of the operation, which is used in the error message.
+    /// `pos` - The position where this operation was called.
+    /// `operation` - The operation to apply to the two ints.
+    fn bitwise<F>(
+        &self,
+        other: &Value,
+        name: &str,
+        pos: usize,
+        operation: F,
+    ) -> Result<Value, Error>
+    where
+        F: Fn(i64, i64) -> Result<i64, Error>,
+    {
+        match (&self.kind, &other.kind) {
+            (ValueKind::Int(val1), ValueKind::Int(val2)) => {
+                Ok(Value::new(pos, ValueKind::Int(operation(*val1, *val2)?)))
+            }
+            _ => Err(Error::new(
+                ErrorKind::UnsupportedOperation(
+                    name.to_owned(),
+                    format!(
+                        "The Value '{}' And The Value '{}'.",
+                        self.kind.get_value_name(),
+                        other.kind.get_value_name()
+                    ),
+                ),
+                pos,
+            )),
+        }
+    }
+
     /// This function takes the current value and a reference to another value and returns if the current value
     /// is less than the second one. Note that this function does not consume either value.
     ///
@@ -244,9 +464,13 @@ impl Value {
             (ValueKind::Float(val1), ValueKind::Float(val2)) => {
                 Ok(Value::new(pos, ValueKind::Boolean(val1 < val2)))
             }
+            // Strings and chars are ordered by their Unicode code points, which is the same as the order of their UTF-8 bytes.
             (ValueKind::String(val1), ValueKind::String(val2)) => {
                 Ok(Value::new(pos, ValueKind::Boolean(val1 < val2)))
             }
+            (ValueKind::Char(val1), ValueKind::Char(val2)) => {
+                Ok(Value::new(pos, ValueKind::Boolean(val1 < val2)))
+            }
 
             _ => Err(Error::new(
                 ErrorKind::UnsupportedOperation(
@@ -262,6 +486,58 @@ impl Value {
         }
     }
 
+    /// This function takes the current string and a reference to another string and returns if the current string
+    /// is less than the second one, ignoring case. Note that this function does not consume either value.
+    ///
+    /// # Arguments
+    /// `other` - The other string to compare.
+    /// `pos` - The position where this operation was called.
+    pub fn lt_ignore_case(&self, other: &Value, pos: usize) -> Result<Value, Error> {
+        match (&self.kind, &other.kind) {
+            (ValueKind::String(val1), ValueKind::String(val2)) => Ok(Value::new(
+                pos,
+                ValueKind::Boolean(fold_case(val1).lt(fold_case(val2))),
+            )),
+            _ => Err(Error::new(
+                ErrorKind::UnsupportedOperation(
+                    "LtCi".to_owned(),
+                    format!(
+                        "The Value '{}' And The Value '{}'.",
+                        self.kind.get_value_name(),
+                        other.kind.get_value_name()
+                    ),
+                ),
+                pos,
+            )),
+        }
+    }
+
+    /// This function takes the current string and a reference to another string and returns if the current string
+    /// is equal to the second one, ignoring case. Note that this function does not consume either value.
+    ///
+    /// # Arguments
+    /// `other` - The other string to compare.
+    /// `pos` - The position where this operation was called.
+    pub fn equal_ignore_case(&self, other: &Value, pos: usize) -> Result<Value, Error> {
+        match (&self.kind, &other.kind) {
+            (ValueKind::String(val1), ValueKind::String(val2)) => Ok(Value::new(
+                pos,
+                ValueKind::Boolean(fold_case(val1).eq(fold_case(val2))),
+            )),
+            _ => Err(Error::new(
+                ErrorKind::UnsupportedOperation(
+                    "EqCi".to_owned(),
+                    format!(
+                        "The Value '{}' And The Value '{}'.",
+                        self.kind.get_value_name(),
+                        other.kind.get_value_name()
+                    ),
+                ),
+                pos,
+            )),
+        }
+    }
+
     /// This function takes the current value and a reference to another value and returns if the current value
     /// is less than or equal to the second one. Note that this function does not consume either value.
     ///
@@ -279,6 +555,9 @@ impl Value {
             (ValueKind::String(val1), ValueKind::String(val2)) => {
                 Ok(Value::new(pos, ValueKind::Boolean(val1 <= val2)))
             }
+            (ValueKind::Char(val1), ValueKind::Char(val2)) => {
+                Ok(Value::new(pos, ValueKind::Boolean(val1 <= val2)))
+            }
 
             _ => Err(Error::new(
                 ErrorKind::UnsupportedOperation(
@@ -311,6 +590,9 @@ impl Value {
             (ValueKind::String(val1), ValueKind::String(val2)) => {
                 Ok(Value::new(pos, ValueKind::Boolean(val1 > val2)))
             }
+            (ValueKind::Char(val1), ValueKind::Char(val2)) => {
+                Ok(Value::new(pos, ValueKind::Boolean(val1 > val2)))
+            }
 
             _ => Err(Error::new(
                 ErrorKind::UnsupportedOperation(
@@ -343,6 +625,9 @@ impl Value {
             (ValueKind::String(val1), ValueKind::String(val2)) => {
                 Ok(Value::new(pos, ValueKind::Boolean(val1 >= val2)))
             }
+            (ValueKind::Char(val1), ValueKind::Char(val2)) => {
+                Ok(Value::new(pos, ValueKind::Boolean(val1 >= val2)))
+            }
 
             _ => Err(Error::new(
                 ErrorKind::UnsupportedOperation(
@@ -363,22 +648,35 @@ impl Value {
     ///
     /// # Arguments
     /// `other` - The other value to compare.
+    /// `float_equality` - How floats are compared, including the floats inside of arrays and maps.
     /// `pos` - The position where this operation was called.
-    pub fn equal(&self, other: &Value, pos: usize) -> Value {
+    pub fn equal(&self, other: &Value, float_equality: FloatEquality, pos: usize) -> Value {
         match (&self.kind, &other.kind) {
             (ValueKind::Int(val1), ValueKind::Int(val2)) => {
                 Value::new(pos, ValueKind::Boolean(val1 == val2))
             }
-            (ValueKind::Float(val1), ValueKind::Float(val2)) => Value::new(
-                pos,
-                ValueKind::Boolean((val1 - val2).abs() < std::f64::EPSILON),
-            ),
+            (ValueKind::Float(val1), ValueKind::Float(val2)) => {
+                Value::new(pos, ValueKind::Boolean(float_equality.equal(*val1, *val2)))
+            }
             (ValueKind::Boolean(val1), ValueKind::Boolean(val2)) => {
                 Value::new(pos, ValueKind::Boolean(val1 == val2))
             }
+            (ValueKind::Null, ValueKind::Null) => Value::new(pos, ValueKind::Boolean(true)),
             (ValueKind::String(val1), ValueKind::String(val2)) => {
                 Value::new(pos, ValueKind::Boolean(val1 == val2))
             }
+            (ValueKind::Char(val1), ValueKind::Char(val2)) => {
+                Value::new(pos, ValueKind::Boolean(val1 == val2))
+            }
+            (ValueKind::Bytes(val1), ValueKind::Bytes(val2)) => {
+                Value::new(pos, ValueKind::Boolean(val1 == val2))
+            }
+            (ValueKind::Array(_), ValueKind::Array(_)) | (ValueKind::Map(_), ValueKind::Map(_)) => {
+                Value::new(
+                    pos,
+                    ValueKind::Boolean(elements_equal(&self.kind, &other.kind, float_equality)),
+                )
+            }
 
             _ => Value::new(pos, ValueKind::Boolean(false)),
         }
@@ -389,42 +687,190 @@ impl Value {
     ///
     /// # Arguments
     /// `other` - The other value to compare.
+    /// `float_equality` - How floats are compared, including the floats inside of arrays and maps.
     /// `pos` - The position where this operation was called.
-    pub fn not_equal(&self, other: &Value, pos: usize) -> Value {
+    pub fn not_equal(&self, other: &Value, float_equality: FloatEquality, pos: usize) -> Value {
         match (&self.kind, &other.kind) {
             (ValueKind::Int(val1), ValueKind::Int(val2)) => {
                 Value::new(pos, ValueKind::Boolean(val1 != val2))
             }
-            (ValueKind::Float(val1), ValueKind::Float(val2)) => Value::new(
-                pos,
-                ValueKind::Boolean((val1 - val2).abs() > std::f64::EPSILON),
-            ),
+            (ValueKind::Float(val1), ValueKind::Float(val2)) => {
+                Value::new(pos, ValueKind::Boolean(!float_equality.equal(*val1, *val2)))
+            }
             (ValueKind::Boolean(val1), ValueKind::Boolean(val2)) => {
                 Value::new(pos, ValueKind::Boolean(val1 != val2))
             }
+            (ValueKind::Null, ValueKind::Null) => Value::new(pos, ValueKind::Boolean(false)),
             (ValueKind::String(val1), ValueKind::String(val2)) => {
                 Value::new(pos, ValueKind::Boolean(val1 != val2))
             }
+            (ValueKind::Char(val1), ValueKind::Char(val2)) => {
+                Value::new(pos, ValueKind::Boolean(val1 != val2))
+            }
+            (ValueKind::Bytes(val1), ValueKind::Bytes(val2)) => {
+                Value::new(pos, ValueKind::Boolean(val1 != val2))
+            }
+            (ValueKind::Array(_), ValueKind::Array(_)) | (ValueKind::Map(_), ValueKind::Map(_)) => {
+                Value::new(
+                    pos,
+                    ValueKind::Boolean(!elements_equal(&self.kind, &other.kind, float_equality)),
+                )
+            }
 
             _ => Value::new(pos, ValueKind::Boolean(true)),
         }
     }
 
+    /// This function takes the current value and a reference to another value and returns if both of them are truthy.
+    /// Note that this function does not consume either value.
+    ///
+    /// # Arguments
+    /// `other` - The other value.
+    /// `pos` - The position where this operation was called.
+    pub fn logical_and(&self, other: &Value, pos: usize) -> Value {
+        Value::new(
+            pos,
+            ValueKind::Boolean(self.is_truthy() && other.is_truthy()),
+        )
+    }
+
+    /// This function takes the current value and a reference to another value and returns if either of them is truthy.
+    /// Note that this function does not consume either value.
+    ///
+    /// # Arguments
+    /// `other` - The other value.
+    /// `pos` - The position where this operation was called.
+    pub fn logical_or(&self, other: &Value, pos: usize) -> Value {
+        Value::new(
+            pos,
+            ValueKind::Boolean(self.is_truthy() || other.is_truthy()),
+        )
+    }
+
+    /// This function returns if the current value is not truthy.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this operation was called.
+    pub fn logical_not(&self, pos: usize) -> Value {
+        Value::new(pos, ValueKind::Boolean(!self.is_truthy()))
+    }
+
     /// This function takes the current value and returns if it is "truthy".
-    /// This can mean different things for differet values. For ints, it is whether it is not 0.
-    /// For floats, it is whether it is not NAN, infinite, and not 0. For strings, it is whether
-    /// it is not empty. Every other value is considered to be false.
+    /// This can mean different things for differet values. For ints, it is whether it is not 0, and for chars, whether it is not the null character.
+    /// For floats, it is whether it is not NAN, infinite, and not 0. For strings, bytes, arrays, and maps, it is whether
+    /// they are not empty. Every other value, including null, is considered to be false.
     pub fn is_truthy(&self) -> bool {
         match &self.kind {
             ValueKind::Int(value) => value != &0,
             ValueKind::Float(value) => value.is_normal(),
             ValueKind::Boolean(value) => *value,
             ValueKind::String(value) => !value.is_empty(),
+            ValueKind::Char(value) => value != &'\0',
+            ValueKind::Bytes(bytes) => !bytes.is_empty(),
+            ValueKind::Array(values) => !values.is_empty(),
+            ValueKind::Map(entries) => !entries.is_empty(),
             _ => false,
         }
     }
 }
 
+/// This function checks whether two arrays or two maps are equal, using the equal method on each pair of elements.
+/// The positions of the elements are ignored, and void elements are equal to each other.
+///
+/// # Arguments
+/// `kind1` - The first array or map.
+/// `kind2` - The second array or map.
+fn elements_equal(kind1: &ValueKind, kind2: &ValueKind, float_equality: FloatEquality) -> bool {
+    let is_equal = |val1: &Arc<Value>, val2: &Arc<Value>| match (&val1.kind, &val2.kind) {
+        (ValueKind::Void, ValueKind::Void) => true,
+        _ => val1.equal(val2, float_equality, 0).is_truthy(),
+    };
+    match (kind1, kind2) {
+        (ValueKind::Array(values1), ValueKind::Array(values2)) => {
+            values1.len() == values2.len()
+                && values1
+                    .iter()
+                    .zip(values2)
+                    .all(|(val1, val2)| is_equal(val1, val2))
+        }
+        (ValueKind::Map(entries1), ValueKind::Map(entries2)) => {
+            entries1.len() == entries2.len()
+                && entries1
+                    .iter()
+                    .zip(entries2)
+                    .all(|((key1, val1), (key2, val2))| key1 == key2 && is_equal(val1, val2))
+        }
+        _ => false,
+    }
+}
+
+/// This function folds the case of the string, so that strings that only differ in case produce the same characters.
+/// The folding does not depend on the locale. Each character is uppercased and then lowercased, so that characters such as 'ß' fold to the same characters as "SS".
+///
+/// # Arguments
+/// `text` - The string to fold.
+fn fold_case(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.chars()
+        .flat_map(char::to_uppercase)
+        .flat_map(char::to_lowercase)
+}
+
+/// This function converts the result of an integer operation into a value.
+/// If the operation overflowed, the wrapped result is used, or an error is returned in checked mode. The operation never panics.
+///
+/// # Arguments
+/// `checked` - The result of the checked operation, which is None if it overflowed.
+/// `wrapped` - The result of the wrapping operation.
+/// `overflow` - What to do if the operation overflowed.
+/// `pos` - The position where this operation was called.
+fn int_result(
+    checked: Option<i64>,
+    wrapped: i64,
+    overflow: Overflow,
+    pos: usize,
+) -> Result<Value, Error> {
+    match (checked, overflow) {
+        (Some(value), _) => Ok(Value::new(pos, ValueKind::Int(value))),
+        (None, Overflow::Wrap) => Ok(Value::new(pos, ValueKind::Int(wrapped))),
+        (None, Overflow::Check) => Err(Error::new(ErrorKind::IntegerOverflow, pos)),
+    }
+}
+
+/// This function checks that the amount of a shift is between 0 and 63, which are the only amounts that keep any of the bits of an int.
+///
+/// # Arguments
+/// `amount` - The amount to shift by.
+/// `pos` - The position where this operation was called.
+fn shift_amount(amount: i64, pos: usize) -> Result<u32, Error> {
+    if (0..64).contains(&amount) {
+        Ok(amount as u32)
+    } else {
+        Err(Error::new(ErrorKind::InvalidShift(amount), pos))
+    }
+}
+
+/// This function repeats the string the given number of times. The sign of the count is ignored.
+/// If the resulting string can not be allocated, an error is returned instead of aborting.
+///
+/// # Arguments
+/// `string` - The string to repeat.
+/// `count` - The number of times to repeat the string.
+/// `pos` - The position where this operation was called.
+fn repeat(string: &str, count: i64, pos: usize) -> Result<Value, Error> {
+    let count = count.unsigned_abs() as usize;
+    let mut repeated = String::new();
+    string
+        .len()
+        .checked_mul(count)
+        .filter(|len| repeated.try_reserve_exact(*len).is_ok())
+        .ok_or_else(|| Error::new(ErrorKind::StringTooLong, pos))?;
+    for _ in 0..count {
+        repeated.push_str(string);
+    }
+
+    Ok(Value::new(pos, ValueKind::String(repeated)))
+}
+
 /// Converts a token into a value. This is used by the Code struct when generating the vector of values.
 impl From<Token> for Value {
     fn from(token: Token) -> Self {
@@ -432,44 +878,30 @@ impl From<Token> for Value {
             pos: token.pos,
             kind: match token.kind {
                 TokenKind::Void => ValueKind::Void,
+                TokenKind::Null => ValueKind::Null,
                 TokenKind::Any => ValueKind::Any,
                 TokenKind::IntegerLiteral(value) => ValueKind::Int(value),
                 TokenKind::FloatLiteral(value) => ValueKind::Float(value),
                 TokenKind::BooleanLiteral(value) => ValueKind::Boolean(value),
                 TokenKind::StringLiteral(value) => ValueKind::String(value),
+                TokenKind::CharLiteral(value) => ValueKind::Char(value),
                 TokenKind::Identifier(name) => ValueKind::Identifier(name),
-                TokenKind::Label(name, parameters) => ValueKind::Label(name, parameters),
+                TokenKind::Label(name, parameters, _) => ValueKind::Label(name, parameters),
+                // The annotations are taken out of the tokens before they become values, so this is never reached.
+                TokenKind::Annotation(_) => ValueKind::Void,
                 TokenKind::End => ValueKind::End,
-
-                TokenKind::Push => ValueKind::Push,
-                TokenKind::Pop => ValueKind::Pop,
-                TokenKind::Peek => ValueKind::Peek,
-                TokenKind::Add => ValueKind::Add,
-                TokenKind::Sub => ValueKind::Sub,
-                TokenKind::Mul => ValueKind::Mul,
-                TokenKind::Div => ValueKind::Div,
-                TokenKind::Mod => ValueKind::Mod,
-                TokenKind::LessThan => ValueKind::LessThan,
-                TokenKind::LessThanEqual => ValueKind::LessThanEqual,
-                TokenKind::GreaterThan => ValueKind::GreaterThan,
-                TokenKind::GreaterThanEqual => ValueKind::GreaterThanEqual,
-                TokenKind::Equal => ValueKind::Equal,
-                TokenKind::NotEqual => ValueKind::NotEqual,
-                TokenKind::Jump => ValueKind::Jump,
-                TokenKind::RelativeJump => ValueKind::RelativeJump,
-                TokenKind::JumpIfTrue => ValueKind::JumpIfTrue,
-                TokenKind::JumpIfFalse => ValueKind::JumpIfFalse,
-                TokenKind::RelativeJumpIfTrue => ValueKind::RelativeJumpIfTrue,
-                TokenKind::RelativeJumpIfFalse => ValueKind::RelativeJumpIfFalse,
-                TokenKind::Print => ValueKind::Print,
-                TokenKind::PrintNewLine => ValueKind::PrintNewLine,
-                TokenKind::Set => ValueKind::Set,
-                TokenKind::Call => ValueKind::Call,
+                TokenKind::Instruction(opcode) => ValueKind::Instruction(opcode),
             },
         }
     }
 }
 
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
 impl fmt::Debug for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:#?}", self.kind)
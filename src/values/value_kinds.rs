@@ -1,45 +1,27 @@
 //! The ValueKind enum maintains the various values in the language.
 //! All of the supported values are in this enum. This makes it easy to expand in the future.
 
-use crate::utils::parameter::Parameter;
-use std::fmt;
+use super::value::Value;
+use crate::{instructions::Opcode, utils::parameter::Parameter};
+use std::{collections::BTreeMap, fmt, sync::Arc};
 
 #[derive(PartialEq, Clone)]
 pub enum ValueKind {
     Void,
+    Null,
     Any,
     Int(i64),
     Float(f64),
     Boolean(bool),
     String(String),
+    Char(char),
+    Bytes(Vec<u8>),
+    Array(Vec<Arc<Value>>),
+    Map(BTreeMap<String, Arc<Value>>),
     Identifier(String),
     Label(String, Vec<Parameter>),
     End,
-
-    Push,
-    Pop,
-    Peek,
-    Add,
-    Sub,
-    Mul,
-    Div,
-    Mod,
-    LessThan,
-    LessThanEqual,
-    GreaterThan,
-    GreaterThanEqual,
-    Equal,
-    NotEqual,
-    Jump,
-    RelativeJump,
-    JumpIfTrue,
-    JumpIfFalse,
-    RelativeJumpIfTrue,
-    RelativeJumpIfFalse,
-    Print,
-    PrintNewLine,
-    Set,
-    Call,
+    Instruction(Opcode),
 }
 
 impl ValueKind {
@@ -49,79 +31,137 @@ impl ValueKind {
     pub fn get_value_name(&self) -> String {
         match self {
             ValueKind::Void => "Void",
+            ValueKind::Null => "Null",
             ValueKind::Any => "Any",
             ValueKind::Int(_) => "Int",
             ValueKind::Float(_) => "Float",
             ValueKind::Boolean(_) => "Boolean",
             ValueKind::String(_) => "String",
+            ValueKind::Char(_) => "Char",
+            ValueKind::Bytes(_) => "Bytes",
+            ValueKind::Array(_) => "Array",
+            ValueKind::Map(_) => "Map",
             ValueKind::Identifier(_) => "Identifier",
             ValueKind::Label(_, _) => "Label",
             ValueKind::End => "End",
-            ValueKind::Push => "Instruction Push",
-            ValueKind::Pop => "Instruction Pop",
-            ValueKind::Peek => "Instruction Peek",
-            ValueKind::Add => "Instruction Add",
-            ValueKind::Sub => "Instruction Sub",
-            ValueKind::Mul => "Instruction Mul",
-            ValueKind::Div => "Instruction Div",
-            ValueKind::Mod => "Instruction Mod",
-            ValueKind::LessThan => "Instruction LessThan",
-            ValueKind::LessThanEqual => "Instruction LessThanEqual",
-            ValueKind::GreaterThan => "Instruction GreaterThan",
-            ValueKind::GreaterThanEqual => "Instruction GreaterThanEqual",
-            ValueKind::Equal => "Instruction Equal",
-            ValueKind::NotEqual => "Instruction NotEqual",
-            ValueKind::Jump => "Instruction Jump",
-            ValueKind::RelativeJump => "Instruction JumpRelative",
-            ValueKind::JumpIfTrue => "Instruction JumpIfTrue",
-            ValueKind::JumpIfFalse => "Instruction JumpIfFalse",
-            ValueKind::RelativeJumpIfTrue => "Instruction RelativeJumpIfTrue",
-            ValueKind::RelativeJumpIfFalse => "Instruction RelativeJumpIfFalse",
-            ValueKind::Print => "Instruction Print",
-            ValueKind::PrintNewLine => "Instruction PrintNewLine",
-            ValueKind::Set => "Instruction Set",
-            ValueKind::Call => "Instruction Call",
+            ValueKind::Instruction(opcode) => return format!("Instruction {:?}", opcode),
         }
         .to_owned()
     }
 }
 
+/// Formats the value for users, such as when it is printed by the print instruction.
+/// Strings are written without quotes, and instructions are written as their names.
+/// Inside of arrays and maps, strings are quoted, so that the elements can be told apart.
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueKind::Void => write!(f, "void"),
+            ValueKind::Null => write!(f, "null"),
+            ValueKind::Any => write!(f, "any"),
+            ValueKind::Int(value) => write!(f, "{}", value),
+            // A float that is whole keeps its decimal point, so that it can be told apart from an int.
+            ValueKind::Float(value) if value.is_finite() && value.fract() == 0.0 => {
+                write!(f, "{:.1}", value)
+            }
+            ValueKind::Float(value) => write!(f, "{}", value),
+            ValueKind::Boolean(value) => write!(f, "{}", value),
+            ValueKind::String(value) => write!(f, "{}", value),
+            ValueKind::Char(value) => write!(f, "{}", value),
+            ValueKind::Bytes(bytes) => write!(f, "b\"{}\"", bytes.escape_ascii()),
+            ValueKind::Array(values) => write_array(f, values, write_element),
+            ValueKind::Map(entries) => write_map(f, entries, write_element),
+            ValueKind::Identifier(name) => write!(f, "{}", name),
+            ValueKind::Label(name, _) => write!(f, "@{}", name),
+            ValueKind::End => write!(f, "end"),
+            ValueKind::Instruction(opcode) => write!(f, "{}", opcode.get_name()),
+        }
+    }
+}
+
+/// Formats the value for debugging, such as in machine dumps and the debugger.
+/// Unlike Display, strings are quoted, so that they can be told apart from other values.
 impl fmt::Debug for ValueKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ValueKind::Void => write!(f, "Void"),
+            ValueKind::Null => write!(f, "Null"),
             ValueKind::Any => write!(f, "Any"),
             ValueKind::Int(value) => write!(f, "{}", value),
             ValueKind::Float(value) => write!(f, "{}", value),
             ValueKind::Boolean(value) => write!(f, "{}", value),
-            ValueKind::String(value) => write!(f, "{}", value),
+            ValueKind::String(value) => write!(f, "{:?}", value),
+            ValueKind::Char(value) => write!(f, "`{}`", value),
+            ValueKind::Bytes(bytes) => write!(f, "b\"{}\"", bytes.escape_ascii()),
+            ValueKind::Array(values) => write_array(f, values, |f, value| write!(f, "{:?}", value)),
+            ValueKind::Map(entries) => write_map(f, entries, |f, value| write!(f, "{:?}", value)),
             ValueKind::Identifier(name) => write!(f, "Identifier '{}'", name),
             ValueKind::Label(name, parameters) => write!(f, "Label '{}' => {:?}", name, parameters),
             ValueKind::End => write!(f, "End"),
-            ValueKind::Push => write!(f, "<instruction push>"),
-            ValueKind::Pop => write!(f, "<instruction pop>"),
-            ValueKind::Peek => write!(f, "<instruction peek>"),
-            ValueKind::Add => write!(f, "<instruction add>"),
-            ValueKind::Sub => write!(f, "<instruction sub>"),
-            ValueKind::Mul => write!(f, "<instruction mul>"),
-            ValueKind::Div => write!(f, "<instruction div>"),
-            ValueKind::Mod => write!(f, "<instruction mod>"),
-            ValueKind::LessThan => write!(f, "<instruction lt>"),
-            ValueKind::LessThanEqual => write!(f, "<instruction lte>"),
-            ValueKind::GreaterThan => write!(f, "<instruction gt>"),
-            ValueKind::GreaterThanEqual => write!(f, "<instruction gte>"),
-            ValueKind::Equal => write!(f, "<instruction eq>"),
-            ValueKind::NotEqual => write!(f, "<instruction neq>"),
-            ValueKind::Jump => write!(f, "<instruction jmp>"),
-            ValueKind::RelativeJump => write!(f, "<instruction rjmp>"),
-            ValueKind::JumpIfTrue => write!(f, "<instruction jmpt>"),
-            ValueKind::JumpIfFalse => write!(f, "<instruction jmpf>"),
-            ValueKind::RelativeJumpIfTrue => write!(f, "<instruction rjmpt>"),
-            ValueKind::RelativeJumpIfFalse => write!(f, "<instruction rjmpf>"),
-            ValueKind::Print => write!(f, "<instruction print>"),
-            ValueKind::PrintNewLine => write!(f, "<instruction printn>"),
-            ValueKind::Set => write!(f, "<instruction set>"),
-            ValueKind::Call => write!(f, "<instruction call>"),
+            ValueKind::Instruction(opcode) => write!(f, "<instruction {}>", opcode.get_name()),
         }
     }
 }
+
+/// Writes a single element of an array or a map for users. Unlike top-level strings, the element is quoted if it is a string,
+/// and a char is written between backticks, so that it can be told apart from a string.
+///
+/// # Arguments
+/// `f` - The formatter to write to.
+/// `value` - The element to write.
+fn write_element(f: &mut fmt::Formatter<'_>, value: &Value) -> fmt::Result {
+    match &value.kind {
+        ValueKind::String(value) => write!(f, "{:?}", value),
+        ValueKind::Char(value) => write!(f, "`{}`", value),
+        kind => write!(f, "{}", kind),
+    }
+}
+
+/// Writes the elements of an array between brackets, separated by commas.
+///
+/// # Arguments
+/// `f` - The formatter to write to.
+/// `values` - The elements of the array.
+/// `write_value` - The function that writes a single element.
+fn write_array<F>(f: &mut fmt::Formatter<'_>, values: &[Arc<Value>], write_value: F) -> fmt::Result
+where
+    F: Fn(&mut fmt::Formatter<'_>, &Value) -> fmt::Result,
+{
+    write!(f, "[")?;
+    for (idx, value) in values.iter().enumerate() {
+        if idx > 0 {
+            write!(f, ", ")?;
+        }
+
+        write_value(f, value)?;
+    }
+
+    write!(f, "]")
+}
+
+/// Writes the entries of a map between braces, separated by commas. The keys are always quoted.
+///
+/// # Arguments
+/// `f` - The formatter to write to.
+/// `entries` - The entries of the map.
+/// `write_value` - The function that writes a single value.
+fn write_map<F>(
+    f: &mut fmt::Formatter<'_>,
+    entries: &BTreeMap<String, Arc<Value>>,
+    write_value: F,
+) -> fmt::Result
+where
+    F: Fn(&mut fmt::Formatter<'_>, &Value) -> fmt::Result,
+{
+    write!(f, "{{")?;
+    for (idx, (key, value)) in entries.iter().enumerate() {
+        if idx > 0 {
+            write!(f, ", ")?;
+        }
+
+        write!(f, "{:?}: ", key)?;
+        write_value(f, value)?;
+    }
+
+    write!(f, "}}")
+}
@@ -0,0 +1,601 @@
+//! The verifier checks the structure of a program before it is run.
+//! Every instruction takes a fixed number of inline arguments, and each argument is itself an expression.
+//! The verifier walks these expressions and reports any instruction that is missing arguments,
+//! so that a mistake such as `lt 1` is found when the program is loaded, instead of when it is reached.
+//!
+//! The instructions follow a single convention for their operands:
+//! - The arithmetic instructions (add, sub, mul, div, mod) and the stack comparisons (slt, slte, sgt, sgte, seq, sneq) take no inline arguments and pop both operands from the stack.
+//! - The inline comparisons (lt, lte, gt, gte, eq, neq) take both operands as inline arguments.
+//!
+//! In both cases, the first operand is the left operand. For the stack forms, this is the first value popped.
+//!
+//! The verifier also tracks the depth of the operand stack through each label, following the jumps whose targets are literal ints.
+//! An instruction in the main label, or in a label that is annotated as pure, that would certainly pop from an empty stack is reported when the program is loaded,
+//! and a value that is reached by paths that leave different numbers of values on the stack is reported as a warning.
+
+use crate::{
+    code::Code,
+    errors::{error::Error, error_kind::ErrorKind},
+    instructions::Opcode,
+    utils::{label::Label, log_level::LogLevel, natives::Natives},
+    values::value_kinds::ValueKind,
+};
+use std::collections::BTreeMap;
+
+/// The deepest that arguments may be nested inside of each other.
+/// Arguments are verified and evaluated recursively, so this protects the stack of the host from malicious programs.
+/// In debug builds, some instructions use enough of the stack that about 250 levels fill the 2 MiB stack of a thread, so the limit leaves room for the host.
+pub const MAX_NESTING: usize = 128;
+
+/// The pattern of the match instruction that matches every value.
+pub const WILDCARD: &str = "_";
+
+/// Verifies every expression in the code.
+/// If an instruction is missing arguments, or an argument has the wrong kind, an error is returned.
+///
+/// # Arguments
+/// `code` - The code to verify.
+pub fn verify(code: &Code) -> Result<(), Error> {
+    verify_with_natives(code, &Natives::default())
+}
+
+/// Verifies every expression in the code, where the call instructions may also call the given native functions.
+/// A native function takes the place of a label with the same name, and takes as many arguments as its arity.
+///
+/// # Arguments
+/// `code` - The code to verify.
+/// `natives` - The native functions that the host registered.
+pub fn verify_with_natives(code: &Code, natives: &Natives) -> Result<(), Error> {
+    let mut idx = 0;
+    while idx < code.get_values().len() {
+        idx = verify_expression(code, natives, idx, 0)?;
+    }
+
+    Ok(())
+}
+
+/// Checks the effect of every label on the depth of the operand stack, without running the program.
+/// If an instruction in the main label would certainly pop from an empty stack, an error is returned.
+/// Otherwise, a warning is returned for every value that is reached by paths that leave different numbers of values on the stack.
+///
+/// The labels that are called share the stack of their caller, so the depth is unknown after a call,
+/// and the depths within the other labels are relative to the depth when the label was entered.
+/// A label that is annotated as pure starts with a stack of its own that holds its arguments, so it is checked in the same way as the main label.
+/// A label with a jump whose target is computed at runtime, or lies outside of the label, is not checked past that jump.
+///
+/// # Arguments
+/// `code` - The code to check, which has already been verified.
+pub fn check_stack(code: &Code) -> Result<Vec<Error>, Error> {
+    check_stack_with_natives(code, &Natives::default())
+}
+
+/// Checks the effect of every label on the depth of the operand stack, where the call instructions may also call the given native functions.
+/// The depth is unknown after a call to a native function, just like after a call to a label.
+///
+/// # Arguments
+/// `code` - The code to check, which has already been verified.
+/// `natives` - The native functions that the host registered.
+pub fn check_stack_with_natives(code: &Code, natives: &Natives) -> Result<Vec<Error>, Error> {
+    let labels = code
+        .get_labels_in_order()
+        .into_iter()
+        .map(|(_, label)| label);
+
+    let mut warnings = vec![];
+    for label in labels {
+        // Only the main label of a program starts with an empty stack. In the REPL, the stack is kept between inputs.
+        // A pure label starts with a stack that holds its arguments. The times instruction starts it with an empty stack instead, which is only caught when it runs.
+        if label.pure {
+            check_label(
+                code,
+                natives,
+                label,
+                label.parameters.len() as i64,
+                true,
+                &mut warnings,
+            )?;
+        } else {
+            let exact = label.start_pos + 1 == code.get_entry();
+            check_label(code, natives, label, 0, exact, &mut warnings)?;
+        }
+    }
+
+    Ok(warnings)
+}
+
+/// Follows every path through the given label, tracking the depth of the stack at the start of each statement.
+/// A depth of None means that the depth is unknown, and a depth is only ever changed to None, so every statement is checked a bounded number of times.
+///
+/// # Arguments
+/// `code` - The code being checked.
+/// `natives` - The native functions that the host registered.
+/// `label` - The label to check.
+/// `initial` - The depth of the stack when the label is entered.
+/// `exact` - Whether the initial depth is relative to an empty stack, so that popping from an empty stack can be reported.
+/// `warnings` - The warnings that have been found so far.
+fn check_label(
+    code: &Code,
+    natives: &Natives,
+    label: &Label,
+    initial: i64,
+    exact: bool,
+    warnings: &mut Vec<Error>,
+) -> Result<(), Error> {
+    let values = code.get_values();
+    let statements = get_statements(code, natives, label)?;
+    let mut depths = BTreeMap::new();
+    let mut pending = vec![label.start_pos + 1];
+    depths.insert(label.start_pos + 1, Some(initial));
+    while let Some(start) = pending.pop() {
+        // The end of the label returns from it, so no path continues past it.
+        let end = match statements.get(&start) {
+            Some(end) => *end,
+            None => continue,
+        };
+
+        let depth = get_stack_effect(code, natives, start, end, depths[&start], exact)?;
+        let targets = match get_successors(code, start, end) {
+            Some(targets) => targets,
+            None => return Ok(()),
+        };
+
+        for target in targets {
+            if target != label.end_pos && !statements.contains_key(&target) {
+                return Ok(());
+            }
+
+            // When the paths into a statement disagree, the depth of the statement becomes unknown.
+            let merged = match (depths.get(&target).copied(), depth) {
+                (None, _) => depth,
+                (Some(known), _) if known == depth => continue,
+                (Some(None), _) => continue,
+                (Some(Some(known)), Some(depth)) => {
+                    warnings.push(
+                        Error::new(
+                            ErrorKind::UnbalancedStack((known - depth).abs()),
+                            values[target].pos,
+                        )
+                        .with_related(values[start].pos, "One Of The Paths Comes From Here."),
+                    );
+
+                    None
+                }
+                (Some(Some(_)), None) => None,
+            };
+            depths.insert(target, merged);
+            pending.push(target);
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the statements of the given label, mapping the index where each statement starts to the index after it.
+/// Nested labels are skipped, because they only run when they are called.
+///
+/// # Arguments
+/// `code` - The code, which has already been verified.
+/// `natives` - The native functions that the host registered.
+/// `label` - The label whose statements are found.
+pub(crate) fn get_statements(
+    code: &Code,
+    natives: &Natives,
+    label: &Label,
+) -> Result<BTreeMap<usize, usize>, Error> {
+    let mut statements = BTreeMap::new();
+    let mut idx = label.start_pos + 1;
+    while idx < label.end_pos {
+        let next = match &code.get_values()[idx].kind {
+            ValueKind::Label(name, _) => code
+                .get_labels()
+                .get(name)
+                .map_or(idx + 1, |nested| nested.end_pos + 1),
+            _ => verify_expression(code, natives, idx, 0)?,
+        };
+        statements.insert(idx, next);
+        idx = next;
+    }
+
+    Ok(statements)
+}
+
+/// Applies the effect of the statement between the given indices to the depth of the stack, and returns the new depth.
+/// If the depth is exact and an instruction would pop from an empty stack, an error is returned.
+///
+/// # Arguments
+/// `code` - The code being checked.
+/// `natives` - The native functions that the host registered.
+/// `start` - The index of the first value of the statement.
+/// `end` - The index after the statement.
+/// `depth` - The depth of the stack before the statement.
+/// `exact` - Whether the depth is relative to an empty stack.
+fn get_stack_effect(
+    code: &Code,
+    natives: &Natives,
+    start: usize,
+    end: usize,
+    mut depth: Option<i64>,
+    exact: bool,
+) -> Result<Option<i64>, Error> {
+    let values = code.get_values();
+
+    // The push instruction pushes its argument after the argument is evaluated, so the push is applied at the end of the argument.
+    // The arguments of nested push instructions end first, so the ends are kept as a stack.
+    let mut pushes = vec![];
+    for (idx, value) in values.iter().enumerate().take(end).skip(start) {
+        while pushes.last().is_some_and(|push_end| *push_end <= idx) {
+            pushes.pop();
+            depth = depth.map(|depth| depth + 1);
+        }
+
+        let opcode = match &value.kind {
+            ValueKind::Instruction(opcode) => *opcode,
+            _ => continue,
+        };
+
+        if opcode == Opcode::Push {
+            pushes.push(verify_expression(code, natives, idx + 1, 0)?);
+            continue;
+        }
+
+        // The conditional jumps and calls peek at the top of the stack without popping it.
+        let instruction = opcode.get_instruction();
+        let needed = match opcode {
+            Opcode::JumpIfTrue
+            | Opcode::JumpIfFalse
+            | Opcode::RelativeJumpIfTrue
+            | Opcode::RelativeJumpIfFalse
+            | Opcode::CallIfTrue
+            | Opcode::CallIfFalse => 1,
+            _ => instruction.pops as i64,
+        };
+
+        if let Some(current) = depth {
+            if exact && current < needed {
+                return Err(Error::new(ErrorKind::EmptyStack, value.pos));
+            }
+
+            depth = Some(current - instruction.pops as i64 + instruction.pushes as i64);
+        }
+
+        // The labels that are called share the stack, so the depth after a call is unknown.
+        if matches!(
+            opcode,
+            Opcode::Call
+                | Opcode::CallIfTrue
+                | Opcode::CallIfFalse
+                | Opcode::Recurse
+                | Opcode::Times
+                | Opcode::Match
+        ) {
+            depth = None;
+        }
+    }
+
+    Ok(depth.map(|depth| depth + pushes.len() as i64))
+}
+
+/// Finds the indices of the statements that may run after the statement between the given indices.
+/// None is returned if a jump can not be followed, because its target is computed at runtime, lies outside of the code, or the jump is nested inside of an expression.
+///
+/// # Arguments
+/// `code` - The code being checked.
+/// `start` - The index of the first value of the statement.
+/// `end` - The index after the statement.
+pub(crate) fn get_successors(code: &Code, start: usize, end: usize) -> Option<Vec<usize>> {
+    let values = code.get_values();
+    let is_jump = |kind: &ValueKind| {
+        matches!(
+            kind,
+            ValueKind::Instruction(Opcode::Jump)
+                | ValueKind::Instruction(Opcode::RelativeJump)
+                | ValueKind::Instruction(Opcode::JumpIfTrue)
+                | ValueKind::Instruction(Opcode::JumpIfFalse)
+                | ValueKind::Instruction(Opcode::RelativeJumpIfTrue)
+                | ValueKind::Instruction(Opcode::RelativeJumpIfFalse)
+        )
+    };
+
+    if (start + 1..end).any(|idx| is_jump(&values[idx].kind)) {
+        return None;
+    }
+
+    match &values[start].kind {
+        ValueKind::Instruction(Opcode::Jump) | ValueKind::Instruction(Opcode::RelativeJump) => {
+            Some(vec![code.get_jump_target(start)?])
+        }
+        ValueKind::Instruction(Opcode::JumpIfTrue)
+        | ValueKind::Instruction(Opcode::JumpIfFalse)
+        | ValueKind::Instruction(Opcode::RelativeJumpIfTrue)
+        | ValueKind::Instruction(Opcode::RelativeJumpIfFalse) => {
+            Some(vec![code.get_jump_target(start)?, end])
+        }
+        _ => Some(vec![end]),
+    }
+}
+
+/// Verifies the expression that starts at the given index, along with all of its arguments.
+/// This function returns the index after the expression.
+///
+/// # Arguments
+/// `code` - The code being verified.
+/// `natives` - The native functions that the host registered.
+/// `idx` - The index of the first value of the expression.
+/// `depth` - The number of expressions that this expression is nested in.
+fn verify_expression(
+    code: &Code,
+    natives: &Natives,
+    idx: usize,
+    depth: usize,
+) -> Result<usize, Error> {
+    let value = &code.get_values()[idx];
+    if depth > MAX_NESTING {
+        return Err(Error::new(
+            ErrorKind::NestingTooDeep(MAX_NESTING),
+            value.pos,
+        ));
+    }
+
+    match &value.kind {
+        // The first argument of set, the variable instructions, log, and the call instructions is a name, which is not evaluated.
+        ValueKind::Instruction(Opcode::Set)
+        | ValueKind::Instruction(Opcode::IncrementVariable)
+        | ValueKind::Instruction(Opcode::DecrementVariable) => {
+            expect_identifier(
+                code,
+                idx + 1,
+                2,
+                value.pos,
+                ValueKind::Identifier("".to_owned()),
+            )?;
+            verify_arguments(code, natives, idx + 2, 1, value.pos, depth)
+        }
+        ValueKind::Instruction(Opcode::Log) => {
+            let (level, level_pos) = expect_identifier(
+                code,
+                idx + 1,
+                2,
+                value.pos,
+                ValueKind::Identifier("".to_owned()),
+            )?;
+            if level.parse::<LogLevel>().is_err() {
+                return Err(Error::new(ErrorKind::UnknownLogLevel(level), level_pos));
+            }
+
+            verify_arguments(code, natives, idx + 2, 1, value.pos, depth)
+        }
+        ValueKind::Instruction(Opcode::Call)
+        | ValueKind::Instruction(Opcode::CallIfTrue)
+        | ValueKind::Instruction(Opcode::CallIfFalse) => {
+            let (name, name_pos) = expect_identifier(
+                code,
+                idx + 1,
+                1,
+                value.pos,
+                ValueKind::Label("".to_owned(), vec![]),
+            )?;
+            // A native function takes the place of a label with the same name.
+            let arity = match natives.get_arity(&name) {
+                Some(arity) => arity,
+                None => code.get_label_location(&name, name_pos)?.2.len(),
+            };
+            verify_arguments(code, natives, idx + 2, arity, value.pos, depth)
+        }
+        // The deferred label is called without arguments, so it may not define parameters.
+        ValueKind::Instruction(Opcode::Defer) => {
+            let (name, name_pos) = expect_identifier(
+                code,
+                idx + 1,
+                1,
+                value.pos,
+                ValueKind::Label("".to_owned(), vec![]),
+            )?;
+            let (_, _, parameters) = code.get_label_location(&name, name_pos)?;
+            if !parameters.is_empty() {
+                return Err(Error::new(ErrorKind::TooManyParameters(0), name_pos));
+            }
+
+            Ok(idx + 2)
+        }
+        // Both arguments of swapvar are names.
+        ValueKind::Instruction(Opcode::SwapVariables) => {
+            for (offset, remaining) in [(1, 2), (2, 1)] {
+                expect_identifier(
+                    code,
+                    idx + offset,
+                    remaining,
+                    value.pos,
+                    ValueKind::Identifier("".to_owned()),
+                )?;
+            }
+
+            Ok(idx + 3)
+        }
+        // The recurse instruction passes one argument for each parameter of the label that it is in.
+        ValueKind::Instruction(Opcode::Recurse) => {
+            let parameters = code
+                .get_labels()
+                .values()
+                .filter(|label| label.start_pos < idx && idx <= label.end_pos)
+                .max_by_key(|label| label.start_pos)
+                .map_or(0, |label| label.parameters.len());
+            verify_arguments(code, natives, idx + 1, parameters, value.pos, depth)
+        }
+        // The count of the times instruction is evaluated, but the label after it is a name.
+        ValueKind::Instruction(Opcode::Times) => {
+            // The label is still expected when the count is missing, so both arguments are reported.
+            if matches!(
+                code.get_values().get(idx + 1).map(|value| &value.kind),
+                Some(ValueKind::End) | Some(ValueKind::Label(_, _)) | None
+            ) {
+                return Err(Error::new(ErrorKind::ExpectedArgs(2), value.pos));
+            }
+
+            let idx = verify_arguments(code, natives, idx + 1, 1, value.pos, depth)?;
+            let (name, name_pos) = expect_identifier(
+                code,
+                idx,
+                1,
+                value.pos,
+                ValueKind::Label("".to_owned(), vec![]),
+            )?;
+            let (_, _, parameters) = code.get_label_location(&name, name_pos)?;
+            if parameters.len() > 1 {
+                return Err(Error::new(ErrorKind::TooManyParameters(1), name_pos));
+            }
+
+            Ok(idx + 1)
+        }
+        ValueKind::Instruction(Opcode::Match) => verify_match(code, idx, value.pos),
+        ValueKind::Instruction(Opcode::EndMatch) => {
+            Err(Error::new(ErrorKind::EndMatchWithoutMatch, value.pos))
+        }
+        kind => verify_arguments(code, natives, idx + 1, get_arity(kind), value.pos, depth),
+    }
+}
+
+/// Verifies the patterns of the match instruction at the given index, up to and including its endmatch instruction.
+/// Each pattern is a literal or the wildcard _, and it is followed by the name of a label that defines at most one parameter.
+/// This function returns the index after the endmatch instruction.
+///
+/// # Arguments
+/// `code` - The code being verified.
+/// `idx` - The index of the match instruction.
+/// `pos` - The position of the match instruction.
+fn verify_match(code: &Code, mut idx: usize, pos: usize) -> Result<usize, Error> {
+    idx += 1;
+    loop {
+        let pattern = match code.get_values().get(idx) {
+            Some(pattern) => pattern,
+            None => return Err(Error::new(ErrorKind::UnterminatedMatch, pos)),
+        };
+
+        match &pattern.kind {
+            ValueKind::Instruction(Opcode::EndMatch) => return Ok(idx + 1),
+            ValueKind::Null
+            | ValueKind::Int(_)
+            | ValueKind::Float(_)
+            | ValueKind::String(_)
+            | ValueKind::Char(_)
+            | ValueKind::Boolean(_) => {}
+            ValueKind::Identifier(name) if name == WILDCARD => {}
+            ValueKind::End | ValueKind::Label(_, _) => {
+                return Err(Error::new(ErrorKind::UnterminatedMatch, pos))
+            }
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        "Literal Or Wildcard".to_owned(),
+                        kind.get_value_name(),
+                    ),
+                    pattern.pos,
+                ))
+            }
+        }
+
+        let (name, name_pos) = expect_identifier(
+            code,
+            idx + 1,
+            1,
+            pos,
+            ValueKind::Label("".to_owned(), vec![]),
+        )?;
+        let (_, _, parameters) = code.get_label_location(&name, name_pos)?;
+        if parameters.len() > 1 {
+            return Err(Error::new(ErrorKind::TooManyParameters(1), name_pos));
+        }
+
+        idx += 2;
+    }
+}
+
+/// Finds the index after the given number of arguments, starting at the given index, without evaluating them.
+/// This is used by the instructions that skip their arguments, such as callt and callf when their condition does not hold.
+///
+/// # Arguments
+/// `code` - The code, which has already been verified.
+/// `natives` - The native functions that the host registered.
+/// `idx` - The index of the first argument.
+/// `arity` - The number of arguments to skip.
+/// `pos` - The position of the instruction.
+pub(crate) fn skip_arguments(
+    code: &Code,
+    natives: &Natives,
+    idx: usize,
+    arity: usize,
+    pos: usize,
+) -> Result<usize, Error> {
+    verify_arguments(code, natives, idx, arity, pos, 0)
+}
+
+/// Verifies the given number of arguments, starting at the given index.
+/// This function returns the index after the last argument.
+///
+/// # Arguments
+/// `code` - The code being verified.
+/// `natives` - The native functions that the host registered.
+/// `idx` - The index of the first argument.
+/// `arity` - The number of arguments expected.
+/// `pos` - The position of the instruction, which is used if an argument is missing.
+/// `depth` - The number of expressions that the instruction is nested in.
+fn verify_arguments(
+    code: &Code,
+    natives: &Natives,
+    mut idx: usize,
+    arity: usize,
+    pos: usize,
+    depth: usize,
+) -> Result<usize, Error> {
+    for remaining in (1..=arity).rev() {
+        match code.get_values().get(idx).map(|value| &value.kind) {
+            Some(ValueKind::End) | Some(ValueKind::Label(_, _)) | None => {
+                return Err(Error::new(ErrorKind::ExpectedArgs(remaining), pos))
+            }
+            Some(_) => idx = verify_expression(code, natives, idx, depth + 1)?,
+        }
+    }
+
+    Ok(idx)
+}
+
+/// Checks that the value at the given index is an identifier, and returns its name and position.
+///
+/// # Arguments
+/// `code` - The code being verified.
+/// `idx` - The index of the value.
+/// `remaining` - The number of arguments that the instruction still expects, which is used if the value is missing.
+/// `pos` - The position of the instruction.
+/// `expected` - The kind of value that is expected, which is used if the value is not an identifier.
+fn expect_identifier(
+    code: &Code,
+    idx: usize,
+    remaining: usize,
+    pos: usize,
+    expected: ValueKind,
+) -> Result<(String, usize), Error> {
+    match code.get_values().get(idx) {
+        Some(value) => match &value.kind {
+            ValueKind::Identifier(name) => Ok((name.to_owned(), value.pos)),
+            ValueKind::End | ValueKind::Label(_, _) => {
+                Err(Error::new(ErrorKind::ExpectedArgs(remaining), pos))
+            }
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(expected.get_value_name(), kind.get_value_name()),
+                value.pos,
+            )),
+        },
+        None => Err(Error::new(ErrorKind::ExpectedArgs(remaining), pos)),
+    }
+}
+
+/// This function gets the number of inline arguments that the value takes, from the registry of instructions.
+/// Values that are not instructions take no arguments.
+///
+/// # Arguments
+/// `kind` - The kind of the value.
+fn get_arity(kind: &ValueKind) -> usize {
+    match kind {
+        ValueKind::Instruction(opcode) => opcode.get_instruction().arity,
+        _ => 0,
+    }
+}
@@ -6,29 +6,126 @@
 //!
 //! # Example
 //! ```
+//! # use dark_vm::{errors::error::Error, lexer::Lexer, vm::VM};
 //! # fn run() -> Result<(), Error> {
-//! let contents = "push 1";
-//! let tokens = Lexer::new().lex(contents)?;
-//! let result = VM::new(tokens).run()?;
+//! let contents = "@main push 1 end";
+//! let tokens = Lexer::default().lex(contents)?;
+//! let result = VM::new(tokens)?.run().into_result()?;
 //! # Ok(())
 //! # }
 //! ```
 
+#[cfg(feature = "csv")]
+use crate::utils::csv;
+#[cfg(feature = "datetime")]
+use crate::utils::datetime;
+#[cfg(feature = "http")]
+use crate::utils::http;
 use crate::{
-    code::Code,
+    code::{Code, Cursor},
     errors::{error::Error, error_kind::ErrorKind},
+    instructions::{instructions, Opcode},
     tokens::token::Token,
-    utils::{frames::Frame, stack::Stack},
+    utils::{
+        calling_convention::CallingConvention,
+        capabilities::Capabilities,
+        case::{self, Case},
+        encoding,
+        float_equality::FloatEquality,
+        frames::{Frame, Repeat},
+        fs::file_error,
+        hashing::HashAlgorithm,
+        history::{History, HistoryEntry},
+        io::VmIo,
+        json::Json,
+        limits::Limits,
+        log_level::LogLevel,
+        metrics::Metrics,
+        natives::{NativeFunction, Natives},
+        number_format::NumberFormat,
+        outcome::{RunOutcome, RunStats},
+        overflow::Overflow,
+        regex::RegexOperation,
+        shadowing::Shadowing,
+        stack::Stack,
+        trace::Trace,
+        value_diff,
+        watch::{WatchEvent, Watchpoints},
+    },
     values::{value::Value, value_kinds::ValueKind},
+    verifier::{self, MAX_NESTING},
 };
 
-use std::{collections::VecDeque, rc::Rc};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    convert::TryFrom,
+    env, fmt,
+    fs::{self, OpenOptions},
+    io::{Read, Write},
+    path::PathBuf,
+    sync::Arc,
+    time::Instant,
+};
 
 #[derive(Debug)]
 pub struct VM {
-    code: Code,
-    pub operand_stack: Stack<Rc<Value>>,
+    code: Cursor,
+    pub operand_stack: Stack<Arc<Value>>,
     call_stack: Stack<Frame>,
+    pub(crate) limits: Limits,
+    pub(crate) io: VmIo,
+    pub(crate) log_level: LogLevel,
+    pub(crate) shadowing: Shadowing,
+    pub(crate) case: Case,
+    pub(crate) overflow: Overflow,
+    pub(crate) calling_convention: CallingConvention,
+    pub(crate) float_equality: FloatEquality,
+    pub(crate) history: History,
+    pub(crate) metrics: Metrics,
+    pub(crate) capabilities: Capabilities,
+    pub(crate) trace: Option<Trace>,
+
+    /// Whether the brk instruction and the writes to watched variables suspend the VM. This is set when a debugger or another host is attached.
+    pub(crate) suspend_on_break: bool,
+    pub(crate) assert_stack_empty: bool,
+
+    /// The variables that the host watches. Every write to them is reported.
+    pub(crate) watchpoints: Watchpoints,
+
+    /// The native functions that the host registered, which the call instructions look up before the labels.
+    pub(crate) natives: Natives,
+
+    /// Whether the VM should suspend once the current step finishes, because the brk instruction was run or a watched variable was written.
+    suspend_requested: bool,
+    instruction_count: u64,
+    start_time: Option<Instant>,
+    nesting: usize,
+
+    /// The warnings that were collected since the last run. These are returned in the outcome of the run.
+    warnings: Vec<Error>,
+
+    /// The main frame, once the program has finished and popped it from the call stack.
+    /// It is kept so that the globals survive when the code is swapped.
+    globals: Option<Frame>,
+
+    /// The spans of the labels that are currently called, in the same order as the call stack.
+    /// The span of the current label is entered whenever the VM steps, so that events are attributed to it.
+    #[cfg(feature = "tracing")]
+    call_spans: Vec<tracing::Span>,
+}
+
+/// The StepResult enum describes the state of the VM after a single step.
+#[derive(Debug)]
+pub enum StepResult {
+    /// The VM has more values to evaluate.
+    Running,
+
+    /// The VM has more values to evaluate, but the program asked the host to inspect it with the brk instruction.
+    /// This is only returned if the VM was built to suspend on breaks.
+    Suspended,
+
+    /// The VM has finished, optionally producing the value of the last expression.
+    Finished(Option<Arc<Value>>),
 }
 
 impl VM {
@@ -39,27 +136,81 @@ impl VM {
     /// # Arguments
     /// `tokens` - The tokens produced by the lexer.
     pub fn new(tokens: VecDeque<Token>) -> Result<VM, Error> {
-        let code = Code::new(tokens)?;
-        let main_frame = Frame::new(0, "main", None);
-        let mut call_stack = Stack::default();
-        call_stack.push(main_frame);
-        Ok(VM {
-            code,
-            operand_stack: Stack::default(),
-            call_stack,
-        })
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("load").entered();
+        VM::from_code(Arc::new(Code::new(tokens)?))
+    }
+
+    /// Constructs a new VM that runs the given code, which may be shared with other VMs.
+    /// Each VM has its own operand stack, call stack, and variables, so a server can run the same program for every request
+    /// without lexing it again or copying its values. The code is verified before the VM is constructed,
+    /// and the warnings about its stack effects and its identifiers that differ only by case are returned in the outcome of the first run.
+    ///
+    /// # Arguments
+    /// `code` - The code to run.
+    pub fn from_code(code: Arc<Code>) -> Result<VM, Error> {
+        VM::from_code_with_natives(code, Natives::default())
+    }
+
+    /// Constructs a new VM that runs the given code, where the call instructions may also call the given native functions.
+    /// The natives have to be known before the code is verified, because they decide how many arguments each call takes.
+    ///
+    /// # Arguments
+    /// `code` - The code to run.
+    /// `natives` - The native functions that the host registered.
+    pub(crate) fn from_code_with_natives(code: Arc<Code>, natives: Natives) -> Result<VM, Error> {
+        verifier::verify_with_natives(&code, &natives)?;
+        let mut warnings = verifier::check_stack_with_natives(&code, &natives)?;
+        warnings.extend(case::check_identifiers(&code));
+        let mut vm = VM::with_cursor(Cursor::new(code));
+        vm.warnings = warnings;
+        vm.natives = natives;
+        Ok(vm)
     }
 
     /// Creates a VM in REPL mode.
     pub fn repl() -> Result<VM, Error> {
-        let main_frame = Frame::new(0, "main", None);
+        let code = Code::repl(VecDeque::new())?;
+        Ok(VM::with_cursor(Cursor::new(Arc::new(code))))
+    }
+
+    /// Constructs a new VM with the given cursor and the default options.
+    ///
+    /// # Arguments
+    /// `code` - The cursor into the code to run.
+    fn with_cursor(code: Cursor) -> VM {
+        let main_frame = Frame::new(0, "main", None, Case::default());
         let mut call_stack = Stack::default();
         call_stack.push(main_frame);
-        Ok(VM {
-            code: Code::repl(VecDeque::new())?,
+        VM {
+            code,
             operand_stack: Stack::default(),
             call_stack,
-        })
+            limits: Limits::default(),
+            io: VmIo::default(),
+            log_level: LogLevel::default(),
+            shadowing: Shadowing::default(),
+            case: Case::default(),
+            overflow: Overflow::default(),
+            calling_convention: CallingConvention::default(),
+            float_equality: FloatEquality::default(),
+            history: History::default(),
+            metrics: Metrics::default(),
+            capabilities: Capabilities::default(),
+            trace: None,
+            suspend_on_break: false,
+            assert_stack_empty: false,
+            watchpoints: Watchpoints::default(),
+            natives: Natives::default(),
+            suspend_requested: false,
+            instruction_count: 0,
+            start_time: None,
+            nesting: 0,
+            warnings: vec![],
+            globals: None,
+            #[cfg(feature = "tracing")]
+            call_spans: vec![],
+        }
     }
 
     /// Loads the given tokens into the VM.
@@ -69,27 +220,549 @@ impl VM {
     /// # Arguments
     /// `tokens` - The tokens to load.
     pub fn load_tokens(&mut self, tokens: VecDeque<Token>) -> Result<(), Error> {
-        self.code = Code::repl(tokens)?;
+        let code = Code::repl(tokens)?;
+        verifier::verify_with_natives(&code, &self.natives)?;
+        self.code = Cursor::new(Arc::new(code));
         Ok(())
     }
 
     /// Runs the VM until the end of the code.
-    /// This function may return an optionally value, representing the value of the last expression.
-    /// It may also prematurely return an error. This may be updated to return a vector of errors.
-    pub fn run(&mut self) -> Result<Option<Rc<Value>>, Error> {
+    /// This function returns the outcome of the run, which contains either the value of the last expression or the error that stopped the program,
+    /// along with the exit code, the warnings that were collected, statistics about the run, and the output of the program if it was captured.
+    /// If a runtime error stops the program, the VM is unwound before the outcome is returned.
+    pub fn run(&mut self) -> RunOutcome {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("run").entered();
+        let start = Instant::now();
+        let instruction_count = self.instruction_count;
+        self.start_time = Some(start);
+        let result = loop {
+            match self.step() {
+                // There is no host to hand control to, so the run continues.
+                Ok(StepResult::Running) | Ok(StepResult::Suspended) => {}
+                Ok(StepResult::Finished(result)) => break Ok(result),
+                Err(error) => {
+                    self.unwind();
+                    break Err(error);
+                }
+            }
+        };
+
+        RunOutcome {
+            exit_code: if result.is_ok() { 0 } else { 1 },
+            result,
+            warnings: self.take_warnings(),
+            stats: RunStats {
+                instructions: self.instruction_count - instruction_count,
+                elapsed: start.elapsed(),
+            },
+            output: self.io.take_captured(),
+        }
+    }
+
+    /// Pops every frame but the main frame, from the innermost frame outwards, so that the VM is left in a consistent state after a runtime error.
+    /// The deferred labels of every frame run before it is popped, and then those of the main frame, which is kept for its globals.
+    /// The stacks that were set aside by labels with stacks of their own are restored, and the values that those labels left on their stacks are discarded.
+    /// The VM continues after the call that the main frame made, as if the label had ended.
+    ///
+    /// The run method unwinds on its own. A host that steps the VM can call this once it has inspected the frames of the error.
+    pub fn unwind(&mut self) {
+        self.nesting = 0;
+        self.unwind_to(1);
+        self.run_deferred();
+    }
+
+    /// Pops frames until the given number of frames is left, running the deferred labels of each one first.
+    /// The VM continues after the call that the outermost popped frame came from.
+    ///
+    /// A deferred label that fails leaves its frames on the call stack, and they are popped by the same loop, running their own deferred labels in turn.
+    /// The frames are unwound iteratively, so that deferred labels that keep failing can not overflow the stack of the host.
+    ///
+    /// # Arguments
+    /// `depth` - The number of frames to keep.
+    fn unwind_to(&mut self, depth: usize) {
+        let mut caller_position = None;
+        while self.call_stack.0.len() > depth {
+            let deferred = self
+                .call_stack
+                .peek_mut()
+                .and_then(|frame| frame.deferred.pop());
+            if let Some((label_name, label_pos)) = deferred {
+                self.run_deferred_label(&label_name, label_pos);
+                continue;
+            }
+
+            if let Some(frame) = self.call_stack.0.pop() {
+                caller_position = Some(frame.get_caller_position());
+                if let Some(caller_stack) = frame.caller_stack {
+                    self.operand_stack.0 = caller_stack;
+                }
+            }
+
+            #[cfg(feature = "tracing")]
+            self.call_spans.pop();
+        }
+
+        if let Some(caller_position) = caller_position {
+            self.code.jump(caller_position as i64, 0);
+        }
+    }
+
+    /// Runs the deferred labels of the current frame to completion, from the one that was deferred last, while the VM is being unwound.
+    /// An error in a deferred label does not stop the others. It is kept as a warning, and the frames of the deferred label are unwound in turn.
+    fn run_deferred(&mut self) {
+        let depth = self.call_stack.0.len();
+        while let Some((label_name, label_pos)) = self
+            .call_stack
+            .peek_mut()
+            .and_then(|frame| frame.deferred.pop())
+        {
+            self.run_deferred_label(&label_name, label_pos);
+            self.unwind_to(depth);
+        }
+    }
+
+    /// Calls the deferred label, and steps the VM until the label ends.
+    /// If the label fails, the error is kept as a warning, and the frames that the label left on the call stack are left for the caller to unwind.
+    ///
+    /// # Arguments
+    /// `label_name` - The name of the deferred label.
+    /// `label_pos` - The position where the label was deferred.
+    fn run_deferred_label(&mut self, label_name: &str, label_pos: usize) {
+        let depth = self.call_stack.0.len();
+        let mut result = self.enter_label(label_name, label_pos, vec![]);
+        while result.is_ok() && self.call_stack.0.len() > depth {
+            result = match self.step() {
+                Ok(StepResult::Finished(_)) => break,
+                Ok(_) => Ok(()),
+                Err(error) => Err(error),
+            };
+        }
+
+        if let Err(error) = result {
+            self.warnings.push(error);
+            self.nesting = 0;
+        }
+    }
+
+    /// Registers a native function, which programs call with the call instructions as if it were a label with the given number of parameters.
+    /// The code that the VM is running was verified without the function, so the function may not take the name of one of its labels,
+    /// and may only replace a native function that takes the same number of arguments. Otherwise, the calls that were verified would take the wrong arguments.
+    /// To call native functions from the main program, register them with the `native` method of the VMBuilder instead.
+    /// Native functions registered here can be called by the code that is loaded afterwards, such as the inputs of the REPL.
+    ///
+    /// # Arguments
+    /// `name` - The name that programs call the function by.
+    /// `arity` - The number of arguments that the function takes.
+    /// `function` - The function, which is given the VM and the values of its arguments, and returns the value of the call.
+    pub fn register_native<F>(&mut self, name: &str, arity: usize, function: F) -> Result<(), Error>
+    where
+        F: Fn(&mut VM, Vec<Arc<Value>>) -> Result<Option<Value>, Error> + 'static,
+    {
+        let is_label = self.code.get_code().get_labels().contains_key(name);
+        let changes_arity = self
+            .natives
+            .get_arity(name)
+            .is_some_and(|existing| existing != arity);
+        if is_label || changes_arity {
+            return Err(Error::message_only(ErrorKind::NativeConflict(
+                name.to_owned(),
+            )));
+        }
+
+        self.natives.register(name, arity, function);
+        Ok(())
+    }
+
+    /// This function takes the warnings that were collected since they were last taken, such as variables that were shadowed.
+    /// The run method takes them on its own, so this is only needed when the VM is stepped.
+    pub fn take_warnings(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Replaces the code of the VM, while keeping the operand stack and the variables of the main frame, which are the globals of the program.
+    /// This allows a program to be edited while it runs. The new code is verified first, and the VM is left untouched if it is not valid.
+    ///
+    /// The positions in the old code mean nothing in the new code, so every frame except the main frame is discarded, along with the history of the recent instructions,
+    /// and the VM continues from the beginning of the new main label. Labels are resolved by name in the new code,
+    /// so calls and jumps to labels that moved still find them.
+    ///
+    /// # Arguments
+    /// `code` - The new code.
+    pub fn swap_code(&mut self, code: Code) -> Result<(), Error> {
+        verifier::verify_with_natives(&code, &self.natives)?;
+        self.code = Cursor::new(Arc::new(code));
+        self.nesting = 0;
+
+        // With isolated stacks, the stack of the main frame was set aside by the first label that it called.
+        let main_stack = self
+            .call_stack
+            .0
+            .get_mut(1)
+            .and_then(|frame| frame.caller_stack.take());
+        if let Some(main_stack) = main_stack {
+            self.operand_stack.0 = main_stack;
+        }
+
+        self.call_stack.0.truncate(1);
+
+        // The positions of the instructions that were recorded refer to the old source, so they would point to the wrong lines.
+        self.history.clear();
+        #[cfg(feature = "tracing")]
+        self.call_spans.clear();
+
+        // The main frame is popped when the program finishes, so it is restored from the globals.
+        if self.call_stack.is_empty() {
+            let main_frame = self
+                .globals
+                .take()
+                .unwrap_or_else(|| Frame::new(0, "main", None, self.case));
+            self.call_stack.push(main_frame);
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates the next instruction, along with all of its arguments.
+    /// This allows the VM to be run one instruction at a time, which is used by the debugger.
+    pub fn step(&mut self) -> Result<StepResult, Error> {
+        // A seperate function must be called here.
+        // Otherwise, Rust's borrow checker will complain with the error that self.code is mutabley borrowed more than once.
+        if self.is_finished() {
+            return Ok(StepResult::Finished(None));
+        }
+
+        #[cfg(feature = "tracing")]
+        let span = self.call_spans.last().cloned();
+        #[cfg(feature = "tracing")]
+        let _entered = span.as_ref().map(tracing::Span::enter);
+
+        let next = match self.next() {
+            Some(next) => next,
+            None => return Ok(StepResult::Finished(None)),
+        };
+        self.watchpoints.clear_events();
+        let pos = next.pos;
+        let result = match self
+            .check_limits(pos)
+            .and_then(|_| self.evaluate_value(next))
+            .and_then(|result| self.check_stack_empty(pos).map(|_| result))
+        {
+            Ok(result) => result,
+            Err(error) => {
+                self.metrics.add_error();
+                let frames = self.call_stack.0.iter().rev().map(Frame::to_entry);
+                return Err(error
+                    .with_history(self.history.get_entries())
+                    .with_frames(frames.collect()));
+            }
+        };
+
+        if self.is_finished() {
+            Ok(StepResult::Finished(result))
+        } else if std::mem::take(&mut self.suspend_requested) {
+            Ok(StepResult::Suspended)
+        } else {
+            Ok(StepResult::Running)
+        }
+    }
+
+    /// Reads a line from the input stream, without its line ending. None is returned once the input has ended.
+    /// The output stream is flushed first, so that a prompt without a new line is shown before the program waits for its input.
+    /// The line is read through the trace, so that a recorded run can be replayed without its input.
+    ///
+    /// # Arguments
+    /// `instruction` - The name of the instruction that reads the line.
+    /// `pos` - The position where the instruction was called.
+    fn read_line(&self, instruction: &str, pos: usize) -> Result<Option<String>, Error> {
+        self.io
+            .out()
+            .flush()
+            .map_err(|error| Error::new(ErrorKind::FailedWrite(error.to_string()), pos))?;
+
+        let line = self.observe(instruction, pos, || {
+            Ok(Value::new(
+                pos,
+                self.read_input_line(pos)?
+                    .map_or(ValueKind::Null, ValueKind::String),
+            ))
+        })?;
+        match line.kind {
+            ValueKind::String(line) => Ok(Some(line)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Reads a line from the input stream itself, without its line ending. None is returned once the input has ended.
+    /// The line is read one byte at a time, so that nothing after it is consumed, and the readbytes instruction can still read the rest of the input.
+    ///
+    /// # Arguments
+    /// `pos` - The position where the instruction was called.
+    fn read_input_line(&self, pos: usize) -> Result<Option<String>, Error> {
+        let mut line = vec![];
+        let mut byte = [0];
+        let mut input = self.io.input();
         loop {
-            // A seperate function must be called here.
-            // Otherwise, Rust's borrow checker will complain with the error that self.code is mutabley borrowed more than once.
-            if self.is_finished() {
-                return Ok(None);
+            match input.read(&mut byte) {
+                Ok(0) if line.is_empty() => return Ok(None),
+                Ok(0) => break,
+                Ok(_) if byte[0] == b'\n' => break,
+                Ok(_) => line.push(byte[0]),
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(error) => {
+                    return Err(Error::new(ErrorKind::FailedRead(error.to_string()), pos))
+                }
+            }
+        }
+
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+
+        String::from_utf8(line)
+            .map(Some)
+            .map_err(|error| Error::new(ErrorKind::FailedRead(error.to_string()), pos))
+    }
+
+    /// Checks that the operand stack is empty once the program has finished, if the VM asserts that it is.
+    /// The value of the last expression is not on the stack, so it is still returned. Values that are left on the stack were usually pushed by mistake.
+    /// They are discarded along with the error, so that the next input of the REPL starts with an empty stack.
+    ///
+    /// # Arguments
+    /// `pos` - The position of the last value that was evaluated, such as the end of the main label.
+    fn check_stack_empty(&mut self, pos: usize) -> Result<(), Error> {
+        if self.assert_stack_empty && self.is_finished() && !self.operand_stack.is_empty() {
+            let len = self.operand_stack.0.len();
+            self.operand_stack.0.clear();
+            Err(Error::new(ErrorKind::StackNotEmpty(len), pos))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Evaluates the given tokens in the context of the current frame, without moving the instruction pointer.
+    /// This is used to evaluate expressions while the VM is paused, such as conditions of breakpoints.
+    /// The value of the last expression is returned.
+    ///
+    /// # Arguments
+    /// `tokens` - The tokens to evaluate.
+    pub fn evaluate(&mut self, tokens: VecDeque<Token>) -> Result<Option<Arc<Value>>, Error> {
+        let code = Code::repl(tokens)?;
+        verifier::verify_with_natives(&code, &self.natives)?;
+        let code = std::mem::replace(&mut self.code, Cursor::new(Arc::new(code)));
+        let mut result = Ok(None);
+        while let Some(next) = self.next() {
+            result = self.evaluate_value(next);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.code = code;
+        result
+    }
+
+    /// Calls the label with the given arguments, as if a call instruction had named it, and runs the VM until the label ends.
+    /// This lets a host use a program as a library of labels. The label may be called before the program is run,
+    /// or after it has finished, in which case the labels nested in the main label can still use the globals of the program.
+    /// If the label leaves more values on the operand stack than there were before the call, the top value is popped and returned.
+    /// If a runtime error stops the label, the frames of the call are unwound before the error is returned.
+    ///
+    /// # Arguments
+    /// `label_name` - The name of the label.
+    /// `arguments` - The values of the parameters of the label, from the first parameter to the last.
+    pub fn call_label(
+        &mut self,
+        label_name: &str,
+        arguments: Vec<Arc<Value>>,
+    ) -> Result<Option<Arc<Value>>, Error> {
+        // There is no instruction that names the label, so errors point to the label itself.
+        let code = Arc::clone(self.code.get_code());
+        let label = code
+            .get_labels()
+            .get(label_name)
+            .ok_or_else(|| Error::message_only(ErrorKind::UndefinedLabel))?;
+        let label_pos = code.get_value(label.start_pos).map_or(0, |value| value.pos);
+        let parameters = label
+            .parameters
+            .iter()
+            .map(|parameter| parameter.name.to_string())
+            .collect::<Vec<_>>();
+        if parameters.len() != arguments.len() {
+            return Err(Error::new(
+                ErrorKind::WrongArgumentCount(parameters.len(), arguments.len()),
+                label_pos,
+            ));
+        }
+
+        // Once the program has finished, the main frame is pushed again for the duration of the call, so that nested labels see the globals.
+        let restored = match self.globals.take() {
+            Some(frame) if self.call_stack.is_empty() => {
+                self.call_stack.push(frame);
+                true
+            }
+            globals => {
+                self.globals = globals;
+                false
+            }
+        };
+
+        // The result of the label is left on the stack, so the stack is not asserted to be empty when the label returns to a finished program.
+        let assert_stack_empty = std::mem::take(&mut self.assert_stack_empty);
+        let depth = self.call_stack.0.len();
+        let stack_len = self.operand_stack.0.len();
+        let parameter_values = parameters.iter().zip(arguments).collect();
+        let mut result = self
+            .check_depth(label_pos)
+            .and_then(|_| self.enter_label(label_name, label_pos, parameter_values));
+        while result.is_ok() && self.call_stack.0.len() > depth {
+            result = match self.step() {
+                Ok(StepResult::Finished(_)) => break,
+                Ok(_) => Ok(()),
+                Err(error) => Err(error),
+            };
+        }
+
+        if result.is_err() {
+            self.nesting = 0;
+            self.unwind_to(depth);
+        }
+
+        if restored {
+            self.globals = self.call_stack.0.pop();
+        }
+
+        self.assert_stack_empty = assert_stack_empty;
+
+        result?;
+        if self.operand_stack.0.len() > stack_len {
+            Ok(self.operand_stack.0.pop())
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// This function returns the code that the VM is running, which can be shared with other VMs.
+    pub fn get_code(&self) -> &Arc<Code> {
+        self.code.get_code()
+    }
+
+    /// This function returns the position of the next value that the VM will evaluate.
+    pub fn get_current_pos(&self) -> usize {
+        self.code.get_current_pos()
+    }
+
+    /// This function starts watching the variable with the given name, in every frame.
+    /// This function returns false if the variable was already watched.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    pub fn watch(&mut self, name: &str) -> bool {
+        self.watchpoints.add(&self.case.apply(name))
+    }
+
+    /// This function stops watching the variable with the given name.
+    /// This function returns false if the variable was not watched.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    pub fn unwatch(&mut self, name: &str) -> bool {
+        self.watchpoints.remove(&self.case.apply(name))
+    }
+
+    /// This function sets whether the names of variables are folded to lower case, including in the main frame, which exists before the VM is configured.
+    /// Folded names can not differ only by case, so the warnings about such identifiers are dropped.
+    ///
+    /// # Arguments
+    /// `case` - Whether the names are folded.
+    pub(crate) fn set_case(&mut self, case: Case) {
+        self.case = case;
+        for frame in &self.call_stack.0 {
+            frame.current_store.borrow_mut().set_case(case);
+        }
+
+        if case == Case::Fold {
+            self.warnings.retain(|warning| {
+                !matches!(warning.get_kind(), ErrorKind::IdentifierCaseMismatch(..))
+            });
+        }
+    }
+
+    /// This function returns the variables of the main frame, which are the globals of the program, sorted by name.
+    /// The globals are kept after the program finishes, so they can be read between the runs of the REPL.
+    pub fn get_globals(&self) -> Vec<(String, Arc<Value>)> {
+        self.call_stack
+            .0
+            .first()
+            .or(self.globals.as_ref())
+            .map(Frame::get_variables)
+            .unwrap_or_default()
+    }
+
+    /// This function defines a variable in the main frame, so that it is visible to the whole program, such as when a REPL session is restored.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    /// `value` - The value of the variable.
+    pub fn define_global(&mut self, name: &str, value: Arc<Value>) {
+        if let Some(frame) = self.call_stack.0.first_mut().or(self.globals.as_mut()) {
+            frame.define(name, value);
+        }
+    }
+
+    /// This function returns the names of the watched variables, sorted by name.
+    pub fn get_watched(&self) -> Vec<&str> {
+        self.watchpoints.get_names()
+    }
+
+    /// This function returns the writes to watched variables that made the VM suspend during the last step.
+    /// The events are only kept if the VM suspends on breaks.
+    pub fn take_watch_events(&mut self) -> Vec<WatchEvent> {
+        self.watchpoints.take_events()
+    }
+
+    /// This function returns the frames on the call stack. The last frame is the current frame.
+    pub fn get_frames(&self) -> &[Frame] {
+        &self.call_stack.0
+    }
+
+    /// This function returns the next value that the VM will evaluate, without evaluating it.
+    pub fn peek_next(&self) -> Option<Arc<Value>> {
+        self.code.peek()
+    }
+
+    /// This function returns a handle to the metrics of the VM.
+    /// The handle shares its counters with the VM, so it can be kept and read after the VM has run.
+    pub fn get_metrics(&self) -> Metrics {
+        self.metrics.clone()
+    }
+
+    /// This function returns the number of instructions that the VM has run so far.
+    pub fn get_instruction_count(&self) -> u64 {
+        self.instruction_count
+    }
+
+    /// Consumes one unit of fuel and checks that the VM has not run out of fuel or time.
+    ///
+    /// # Arguments
+    /// `pos` - The position of the instruction that is about to run.
+    fn check_limits(&mut self, pos: usize) -> Result<(), Error> {
+        self.instruction_count += 1;
+        self.metrics.add_instruction(self.limits.fuel.is_some());
+        if let Some(fuel) = self.limits.fuel {
+            if self.instruction_count > fuel {
+                return Err(Error::new(ErrorKind::OutOfFuel(fuel), pos));
             }
+        }
 
-            let next = self.next().unwrap();
-            let result = self.evaluate_value(next)?;
-            if self.is_finished() && result.is_some() {
-                return Ok(result);
+        if let Some(timeout) = self.limits.timeout {
+            let start = self.start_time.get_or_insert_with(Instant::now);
+            if start.elapsed() > timeout {
+                return Err(Error::new(ErrorKind::Timeout(timeout), pos));
             }
         }
+
+        Ok(())
     }
 
     /// Evaluates the next value.
@@ -97,21 +770,26 @@ impl VM {
     ///
     /// # Arguments
     /// `value` - The value to evaluate.
-    fn evaluate_value(&mut self, value: Rc<Value>) -> Result<Option<Rc<Value>>, Error> {
+    fn evaluate_value(&mut self, value: Arc<Value>) -> Result<Option<Arc<Value>>, Error> {
         match &value.kind {
             ValueKind::Void => Ok(None),
             ValueKind::Any => Ok(None),
 
-            ValueKind::Int(_)
+            ValueKind::Null
+            | ValueKind::Int(_)
             | ValueKind::Float(_)
             | ValueKind::Boolean(_)
-            | ValueKind::String(_) => Ok(Some(value)),
+            | ValueKind::String(_)
+            | ValueKind::Char(_)
+            | ValueKind::Bytes(_)
+            | ValueKind::Array(_)
+            | ValueKind::Map(_) => Ok(Some(value)),
 
             // Cloning here is cheap because val is reference counted, so only a counter is incremented.
             ValueKind::Identifier(name) => self
                 .call_stack
                 .peek()
-                .unwrap()
+                .ok_or_else(|| Error::new(ErrorKind::NoActiveFrame, value.pos))?
                 .find(name, value.pos)
                 .map(Some),
             ValueKind::Label(_, _) => {
@@ -130,44 +808,53 @@ impl VM {
                 }
             }
             ValueKind::End => {
-                let frame = self.call_stack.pop(value.pos)?;
-                if let Some(error) = self
+                if self.repeat_label(value.pos)? {
+                    return Ok(None);
+                }
+
+                // The deferred labels run before the frame is popped, so that they can still use its variables.
+                // Each one returns to this end, which is evaluated again until none are left.
+                let deferred = self
+                    .call_stack
+                    .peek_mut()
+                    .and_then(|frame| frame.deferred.pop());
+                if let Some((label_name, label_pos)) = deferred {
+                    self.code
+                        .jump(self.code.get_current_pos() as i64 - 1, value.pos);
+                    self.check_depth(value.pos)?;
+                    self.enter_label(&label_name, label_pos, vec![])?;
+                    return Ok(None);
+                }
+
+                let mut frame = self.call_stack.pop(value.pos)?;
+                #[cfg(feature = "tracing")]
+                self.call_spans.pop();
+                if let Some(caller_stack) = frame.caller_stack.take() {
+                    self.return_to_caller_stack(caller_stack, value.pos)?;
+                }
+
+                let result = self
                     .code
-                    .jump(frame.get_caller_position() as i64, value.pos)
-                {
+                    .jump(frame.get_caller_position() as i64, value.pos);
+                if self.call_stack.is_empty() {
+                    self.globals = Some(frame);
+                }
+
+                if let Some(error) = result {
                     Err(error)
                 } else {
                     Ok(None)
                 }
             }
 
-            ValueKind::Push => self.push(value.pos),
-            ValueKind::Pop => self.pop(value.pos).map(|(_, value)| value),
-            ValueKind::Peek => self.operand_stack.peek().map_or(
-                Ok(Some(Rc::new(Value::new(value.pos, ValueKind::Void)))),
-                |peeked_value| Ok(Some(peeked_value.clone())),
-            ),
-            ValueKind::Add => self.add(value.pos),
-            ValueKind::Sub => self.sub(value.pos),
-            ValueKind::Mul => self.mul(value.pos),
-            ValueKind::Div => self.div(value.pos),
-            ValueKind::Mod => self.modulus(value.pos),
-            ValueKind::LessThan => self.lt(value.pos),
-            ValueKind::LessThanEqual => self.lte(value.pos),
-            ValueKind::GreaterThan => self.gt(value.pos),
-            ValueKind::GreaterThanEqual => self.gte(value.pos),
-            ValueKind::Equal => self.eq(value.pos),
-            ValueKind::NotEqual => self.neq(value.pos),
-            ValueKind::Jump => self.jmp(value.pos),
-            ValueKind::RelativeJump => self.rjmp(value.pos),
-            ValueKind::JumpIfTrue => self.jmpt(value.pos),
-            ValueKind::JumpIfFalse => self.jmpf(value.pos),
-            ValueKind::RelativeJumpIfTrue => self.rjmpt(value.pos),
-            ValueKind::RelativeJumpIfFalse => self.rjmpf(value.pos),
-            ValueKind::Print => self.print(value.pos),
-            ValueKind::PrintNewLine => self.printn(value.pos),
-            ValueKind::Set => self.set(value.pos),
-            ValueKind::Call => self.call(value.pos),
+            ValueKind::Instruction(opcode) => {
+                self.history.record(HistoryEntry {
+                    pos: value.pos,
+                    opcode: *opcode,
+                    stack_depth: self.operand_stack.0.len(),
+                });
+                self.evaluate_instruction(*opcode, value.pos)
+            }
         }
     }
 
@@ -177,13 +864,13 @@ impl VM {
     ///
     /// # Arguments
     /// `pos` - The position where the instruction was called.
-    fn push(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
+    fn push(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
         // Get the next argument. The two parameters passed are useful in the case of errors.
         let (pos, arg) = self.get_arg(1, pos)?;
 
         // If the argument does not exist, return an error, otherwise push it on to the stack.
         match arg {
-            Some(value) => self.operand_stack.push(value),
+            Some(value) => self.push_value(value, pos)?,
             None => {
                 return Err(Error::new(
                     ErrorKind::ValueMismatch(
@@ -198,29 +885,65 @@ impl VM {
         Ok(None)
     }
 
+    /// Pushes the value on to the stack, unless the stack already holds as many values as the limits allow.
+    /// Every instruction that pushes values on to the stack should use this function, so that the limit cannot be bypassed.
+    ///
+    /// # Arguments
+    /// `value` - The value to push.
+    /// `pos` - The position where the value was pushed.
+    fn push_value(&mut self, value: Arc<Value>, pos: usize) -> Result<(), Error> {
+        if let Some(max_stack) = self.limits.max_stack {
+            if self.operand_stack.0.len() >= max_stack {
+                return Err(Error::new(ErrorKind::StackLimitExceeded(max_stack), pos));
+            }
+        }
+
+        self.operand_stack.push(value);
+        Ok(())
+    }
+
     /// Pops the top value from the stack.
     ///
     /// # Arguments
     /// `pos` - The position where the instruction was called.
-    fn pop(&mut self, pos: usize) -> Result<(usize, Option<Rc<Value>>), Error> {
+    fn pop(&mut self, pos: usize) -> Result<(usize, Option<Arc<Value>>), Error> {
         // Pop the value and if there are no errors, map it to an option with the value.
         // stack.pop takes the position where the instruction was used in the case that the stack was empty.
         self.operand_stack.pop(pos).map(|val| (val.pos, Some(val)))
     }
 
+    /// Pops the top value from the stack and returns it.
+    ///
+    /// # Arguments
+    /// `pos` - The position where the instruction was called.
+    fn pop_top(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.pop(pos).map(|(_, value)| value)
+    }
+
+    /// Returns the top value of the stack without popping it, or void if the stack is empty.
+    ///
+    /// # Arguments
+    /// `pos` - The position where the instruction was called.
+    fn peek(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.operand_stack.peek().map_or(
+            Ok(Some(Arc::new(Value::new(pos, ValueKind::Void)))),
+            |peeked_value| Ok(Some(peeked_value.clone())),
+        )
+    }
+
     /// Pops the top two values from the stack and adds them together.
     /// This internally calls both the pop instruction and the add method on the Value struct.
     ///
     /// # Arguments
     /// `pos` - The position where the instruction was called.
-    fn add(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
+    fn add(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
         let (arg_pos_1, arg1) = self.pop(pos)?;
         let (arg_pos_2, arg2) = self.pop(pos)?;
 
         match (arg1, arg2) {
             (Some(operand1), Some(operand2)) => operand1
-                .add(operand2.as_ref(), pos)
-                .map(|val| Some(Rc::new(val))),
+                .add(operand2.as_ref(), self.overflow, pos)
+                .map(|val| Some(Arc::new(val))),
             (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
                     ValueKind::Any.get_value_name(),
@@ -243,14 +966,14 @@ impl VM {
     ///
     /// # Arguments
     /// `pos` - The position where the instruction was called.
-    fn sub(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
+    fn sub(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
         let (arg_pos_1, arg1) = self.pop(pos)?;
         let (arg_pos_2, arg2) = self.pop(pos)?;
 
         match (arg1, arg2) {
             (Some(operand1), Some(operand2)) => operand1
-                .sub(operand2.as_ref(), pos)
-                .map(|val| Some(Rc::new(val))),
+                .sub(operand2.as_ref(), self.overflow, pos)
+                .map(|val| Some(Arc::new(val))),
             (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
                     ValueKind::Any.get_value_name(),
@@ -273,14 +996,25 @@ impl VM {
     ///
     /// # Arguments
     /// `pos` - The position where the instruction was called.
-    fn mul(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
+    fn mul(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
         let (arg_pos_1, arg1) = self.pop(pos)?;
         let (arg_pos_2, arg2) = self.pop(pos)?;
 
         match (arg1, arg2) {
-            (Some(operand1), Some(operand2)) => operand1
-                .mul(operand2.as_ref(), pos)
-                .map(|val| Some(Rc::new(val))),
+            (Some(operand1), Some(operand2)) => {
+                // Repeating a string is checked before it happens, so that a hostile program can not exhaust the memory of the host.
+                match (&operand1.kind, &operand2.kind) {
+                    (ValueKind::String(string), ValueKind::Int(count))
+                    | (ValueKind::Int(count), ValueKind::String(string)) => {
+                        self.limits.check_repeat(string.len(), *count, pos)?
+                    }
+                    _ => {}
+                }
+
+                operand1
+                    .mul(operand2.as_ref(), self.overflow, pos)
+                    .map(|val| Some(Arc::new(val)))
+            }
             (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
                     ValueKind::Any.get_value_name(),
@@ -303,14 +1037,14 @@ impl VM {
     ///
     /// # Arguments
     /// `pos` - The position where the instruction was called.
-    fn div(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
+    fn div(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
         let (arg_pos_1, arg1) = self.pop(pos)?;
         let (arg_pos_2, arg2) = self.pop(pos)?;
 
         match (arg1, arg2) {
             (Some(operand1), Some(operand2)) => operand1
                 .div(operand2.as_ref(), pos)
-                .map(|val| Some(Rc::new(val))),
+                .map(|val| Some(Arc::new(val))),
             (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
                     ValueKind::Any.get_value_name(),
@@ -328,19 +1062,19 @@ impl VM {
         }
     }
 
-    /// Pops the top two values from the stack and mods them.
-    /// This internally calls both the pop instruction and the modulus method on the Value struct.
+    /// Pops the top two ints from the stack and divides them with integer division.
+    /// This internally calls both the pop instruction and the int_div method on the Value struct.
     ///
     /// # Arguments
     /// `pos` - The position where the instruction was called.
-    fn modulus(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
+    fn idiv(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
         let (arg_pos_1, arg1) = self.pop(pos)?;
         let (arg_pos_2, arg2) = self.pop(pos)?;
 
         match (arg1, arg2) {
             (Some(operand1), Some(operand2)) => operand1
-                .modulus(operand2.as_ref(), pos)
-                .map(|val| Some(Rc::new(val))),
+                .int_div(operand2.as_ref(), self.overflow, pos)
+                .map(|val| Some(Arc::new(val))),
             (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
                     ValueKind::Any.get_value_name(),
@@ -358,18 +1092,23 @@ impl VM {
         }
     }
 
-    /// Compares the two arguments and returns if the first argument is less than the second argument.
+    /// Pops the top two ints from the stack and pushes the quotient of their integer division, followed by the remainder.
+    /// The remainder has the same sign as the dividend, so the quotient times the divisor plus the remainder is always the dividend.
     ///
     /// # Arguments
-    /// `pos` - The position where this instruction was called.
-    fn lt(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
-        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
-        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+    /// `pos` - The position where the instruction was called.
+    fn divmod(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.pop(pos)?;
+        let (arg_pos_2, arg2) = self.pop(pos)?;
 
         match (arg1, arg2) {
-            (Some(operand1), Some(operand2)) => operand1
-                .lt(operand2.as_ref(), pos)
-                .map(|val| Some(Rc::new(val))),
+            (Some(operand1), Some(operand2)) => {
+                let quotient = operand1.int_div(operand2.as_ref(), self.overflow, pos)?;
+                let remainder = operand1.modulus(operand2.as_ref(), self.overflow, pos)?;
+                self.push_value(Arc::new(quotient), pos)?;
+                self.push_value(Arc::new(remainder), pos)?;
+                Ok(None)
+            }
             (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
                     ValueKind::Any.get_value_name(),
@@ -387,18 +1126,19 @@ impl VM {
         }
     }
 
-    /// Compares the two arguments and returns if the first argument is less than the second argument.
+    /// Pops the top two values from the stack and mods them.
+    /// This internally calls both the pop instruction and the modulus method on the Value struct.
     ///
     /// # Arguments
-    /// `pos` - The position where this instruction was called.
-    fn lte(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
-        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
-        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+    /// `pos` - The position where the instruction was called.
+    fn modulus(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.pop(pos)?;
+        let (arg_pos_2, arg2) = self.pop(pos)?;
 
         match (arg1, arg2) {
             (Some(operand1), Some(operand2)) => operand1
-                .lte(operand2.as_ref(), pos)
-                .map(|val| Some(Rc::new(val))),
+                .modulus(operand2.as_ref(), self.overflow, pos)
+                .map(|val| Some(Arc::new(val))),
             (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
                     ValueKind::Any.get_value_name(),
@@ -416,19 +1156,107 @@ impl VM {
         }
     }
 
-    /// Compares the two arguments and returns if the first argument is less than the second argument.
+    /// Pops the top two ints from the stack and returns their bitwise and.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn gt(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
-        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
-        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+    fn band(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.compare_stack(pos, Value::bit_and)
+    }
 
-        match (arg1, arg2) {
-            (Some(operand1), Some(operand2)) => operand1
-                .gt(operand2.as_ref(), pos)
-                .map(|val| Some(Rc::new(val))),
-            (None, _) => Err(Error::new(
+    /// Pops the top two ints from the stack and returns their bitwise or.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn bor(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.compare_stack(pos, Value::bit_or)
+    }
+
+    /// Pops the top two ints from the stack and returns their bitwise exclusive or.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn bxor(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.compare_stack(pos, Value::bit_xor)
+    }
+
+    /// Pops the top int or float from the stack and returns it with its sign flipped.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn neg(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let overflow = self.overflow;
+        self.unary(pos, |operand| operand.neg(overflow, pos))
+    }
+
+    /// Pops the top int or float from the stack and returns its absolute value.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn abs(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let overflow = self.overflow;
+        self.unary(pos, |operand| operand.abs(overflow, pos))
+    }
+
+    /// Pops the top value from the stack and returns the result of the operation on it.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    /// `operation` - The operation to apply to the value.
+    fn unary<F>(&mut self, pos: usize, operation: F) -> Result<Option<Arc<Value>>, Error>
+    where
+        F: Fn(&Value) -> Result<Value, Error>,
+    {
+        let (arg_pos_1, arg1) = self.pop(pos)?;
+        match arg1 {
+            Some(operand) => operation(&operand).map(|val| Some(Arc::new(val))),
+            None => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+        }
+    }
+
+    /// Pops the top int from the stack and returns it with every bit flipped.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn bnot(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.unary(pos, |operand| operand.bit_not(pos))
+    }
+
+    /// Pops the top two ints from the stack and shifts the first int popped to the left by the second.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn shl(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.compare_stack(pos, Value::shift_left)
+    }
+
+    /// Pops the top two ints from the stack and shifts the first int popped to the right by the second, keeping its sign.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn shr(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.compare_stack(pos, Value::shift_right)
+    }
+
+    /// Compares the two arguments and returns if the first argument is less than the second argument.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn lt(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
+        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+
+        match (arg1, arg2) {
+            (Some(operand1), Some(operand2)) => operand1
+                .lt(operand2.as_ref(), pos)
+                .map(|val| Some(Arc::new(val))),
+            (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
                     ValueKind::Any.get_value_name(),
                     ValueKind::Void.get_value_name(),
@@ -445,18 +1273,18 @@ impl VM {
         }
     }
 
-    /// Compares the two arguments and returns if the first argument is less than the second argument.
+    /// Compares the two arguments and returns if the first argument is less than or equal to the second argument.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn gte(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
+    fn lte(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
         let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
         let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
 
         match (arg1, arg2) {
             (Some(operand1), Some(operand2)) => operand1
-                .gte(operand2.as_ref(), pos)
-                .map(|val| Some(Rc::new(val))),
+                .lte(operand2.as_ref(), pos)
+                .map(|val| Some(Arc::new(val))),
             (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
                     ValueKind::Any.get_value_name(),
@@ -474,18 +1302,18 @@ impl VM {
         }
     }
 
-    /// Compares the two arguments and returns if the first argument is less than the second argument.
+    /// Compares the two arguments and returns if the first argument is greater than the second argument.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn eq(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
+    fn gt(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
         let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
         let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
 
         match (arg1, arg2) {
-            (Some(operand1), Some(operand2)) => {
-                Ok(Some(Rc::new(operand1.equal(operand2.as_ref(), pos))))
-            }
+            (Some(operand1), Some(operand2)) => operand1
+                .gt(operand2.as_ref(), pos)
+                .map(|val| Some(Arc::new(val))),
             (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
                     ValueKind::Any.get_value_name(),
@@ -503,18 +1331,18 @@ impl VM {
         }
     }
 
-    /// Compares the two arguments and returns if the first argument is less than the second argument.
+    /// Compares the two arguments and returns if the first argument is greater than or equal to the second argument.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn neq(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
+    fn gte(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
         let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
         let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
 
         match (arg1, arg2) {
-            (Some(operand1), Some(operand2)) => {
-                Ok(Some(Rc::new(operand1.not_equal(operand2.as_ref(), pos))))
-            }
+            (Some(operand1), Some(operand2)) => operand1
+                .gte(operand2.as_ref(), pos)
+                .map(|val| Some(Arc::new(val))),
             (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
                     ValueKind::Any.get_value_name(),
@@ -532,276 +1360,2570 @@ impl VM {
         }
     }
 
-    /// Changes the instruction pointer in the Code struct to the argument passed in.
-    /// However, there are restrictions on the argument:
-    /// - First, the argument must be an int.
-    /// - Second, the argument must fit in the range 0 and values.len() inclusive.
-    /// If either of these constraints are broken, an error is returned.
+    /// Compares the two arguments and returns if they are equal. Floats are compared with the float equality mode of the VM.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn jmp(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
-        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
-        match arg1 {
-            Some(value) => {
-                if let ValueKind::Int(jump_location) = value.kind {
-                    if let Some(error) = self.code.jump(jump_location, pos) {
-                        Err(error)
-                    } else {
-                        Ok(None)
-                    }
-                } else {
-                    Err(Error::new(
-                        ErrorKind::ValueMismatch(
-                            ValueKind::Int(0).get_value_name(),
-                            value.kind.get_value_name(),
-                        ),
-                        arg_pos_1,
-                    ))
-                }
-            }
-            None => Err(Error::new(
+    fn eq(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
+        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+
+        match (arg1, arg2) {
+            (Some(operand1), Some(operand2)) => Ok(Some(Arc::new(operand1.equal(
+                operand2.as_ref(),
+                self.float_equality,
+                pos,
+            )))),
+            (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
-                    ValueKind::Int(0).get_value_name(),
+                    ValueKind::Any.get_value_name(),
                     ValueKind::Void.get_value_name(),
                 ),
                 arg_pos_1,
             )),
+            (_, None) => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_2,
+            )),
         }
     }
 
-    /// Changes the instruction pointer in the Code struct by the argument passed in.
-    /// This argument can be positive or negative. However, it must meet the same bound requirements
-    /// as the jmp instruction.
+    /// Compares the two arguments and returns if they are not equal. Floats are compared with the float equality mode of the VM.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn rjmp(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
-        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
-        match arg1 {
-            Some(value) => {
-                if let ValueKind::Int(jump_location) = value.kind {
-                    if let Some(error) = self.code.relative_jump(jump_location - 1, pos) {
-                        Err(error)
-                    } else {
-                        Ok(None)
-                    }
-                } else {
-                    Err(Error::new(
-                        ErrorKind::ValueMismatch(
-                            ValueKind::Int(0).get_value_name(),
-                            value.kind.get_value_name(),
-                        ),
-                        arg_pos_1,
-                    ))
-                }
-            }
-            None => Err(Error::new(
+    fn neq(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
+        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+
+        match (arg1, arg2) {
+            (Some(operand1), Some(operand2)) => Ok(Some(Arc::new(operand1.not_equal(
+                operand2.as_ref(),
+                self.float_equality,
+                pos,
+            )))),
+            (None, _) => Err(Error::new(
                 ErrorKind::ValueMismatch(
-                    ValueKind::Int(0).get_value_name(),
+                    ValueKind::Any.get_value_name(),
                     ValueKind::Void.get_value_name(),
                 ),
                 arg_pos_1,
             )),
+            (_, None) => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_2,
+            )),
         }
     }
 
-    /// Changes the instruction pointer in the Code struct to the argument passed in
-    /// if the top value on the stack is true.
-    /// However, there are restrictions on the argument:
-    /// - First, the argument must be an int.
-    /// - Second, the argument must fit in the range 0 and values.len() inclusive.
-    /// If either of these constraints are broken, an error is returned.
+    /// Compares the two string arguments and returns if the first argument is less than the second argument, ignoring case.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn jmpt(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
-        match self.operand_stack.peek() {
-            Some(value) if value.is_truthy() => self.jmp(pos),
-            None => Err(Error::new(ErrorKind::EmptyStack, pos)),
-            _ => Ok(None),
-        }
-    }
+    fn ltci(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
+        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
 
-    /// Changes the instruction pointer in the Code struct to the argument passed in
-    /// if the top value on the stack is false.
-    /// However, there are restrictions on the argument:
-    /// - First, the argument must be an int.
-    /// - Second, the argument must fit in the range 0 and values.len() inclusive.
-    /// If either of these constraints are broken, an error is returned.
-    ///
-    /// # Arguments
-    /// `pos` - The position where this instruction was called.
-    fn jmpf(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
-        match self.operand_stack.peek() {
-            Some(value) if !value.is_truthy() => self.jmp(pos),
-            None => Err(Error::new(ErrorKind::EmptyStack, pos)),
-            _ => Ok(None),
+        match (arg1, arg2) {
+            (Some(operand1), Some(operand2)) => operand1
+                .lt_ignore_case(operand2.as_ref(), pos)
+                .map(|val| Some(Arc::new(val))),
+            (None, _) => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+            (_, None) => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_2,
+            )),
         }
     }
 
-    /// Changes the instruction pointer in the Code struct by the argument passed in
-    /// if the top value on the stack is true. The constraints are the same are the rjmp instruction.
+    /// Compares the two string arguments and returns if they are equal, ignoring case.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn rjmpt(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
-        match self.operand_stack.peek() {
-            Some(value) if value.is_truthy() => self.rjmp(pos),
-            None => Err(Error::new(ErrorKind::EmptyStack, pos)),
-            _ => Ok(None),
+    fn eqci(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
+        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+
+        match (arg1, arg2) {
+            (Some(operand1), Some(operand2)) => operand1
+                .equal_ignore_case(operand2.as_ref(), pos)
+                .map(|val| Some(Arc::new(val))),
+            (None, _) => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+            (_, None) => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_2,
+            )),
         }
     }
 
-    //// Changes the instruction pointer in the Code struct by the argument passed in
-    /// if the top value on the stack is false. The constraints are the same are the rjmp instruction.
+    /// Returns the length of the argument passed in, which must be a string, an array, a map, or bytes.
+    /// The length of a string is the number of characters, rather than the number of bytes that encode it.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn rjmpf(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
-        match self.operand_stack.peek() {
-            Some(value) if !value.is_truthy() => self.rjmp(pos),
-            None => Err(Error::new(ErrorKind::EmptyStack, pos)),
-            _ => Ok(None),
-        }
+    fn len(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
+        let length = match arg1.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::String(text)) => text.chars().count(),
+            Some(ValueKind::Array(values)) => values.len(),
+            Some(ValueKind::Map(entries)) => entries.len(),
+            Some(ValueKind::Bytes(bytes)) => bytes.len(),
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        "String, Array, Map, Or Bytes".to_owned(),
+                        kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                    ),
+                    arg_pos_1,
+                ))
+            }
+        };
+
+        Ok(Some(Arc::new(Value::new(
+            pos,
+            ValueKind::Int(length as i64),
+        ))))
     }
 
-    /// Prints the argument passed in.
+    /// Returns the Unicode code point of the char passed in.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn print(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
+    fn ord(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
         let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
-        match arg1 {
-            Some(value) => {
-                print!("{:#?}", value);
-                Ok(None)
-            }
-            None => Err(Error::new(
+        match arg1.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::Char(value)) => Ok(Some(Arc::new(Value::new(
+                pos,
+                ValueKind::Int(*value as i64),
+            )))),
+            kind => Err(Error::new(
                 ErrorKind::ValueMismatch(
-                    ValueKind::Any.get_value_name(),
-                    ValueKind::Void.get_value_name(),
+                    ValueKind::Char(' ').get_value_name(),
+                    kind.unwrap_or(&ValueKind::Void).get_value_name(),
                 ),
                 arg_pos_1,
             )),
         }
     }
 
-    /// Prints the argument passed in with a new line after it.
+    /// Returns the char whose Unicode code point is the int passed in.
+    /// If the int is not a code point, or it is a surrogate, which can not be a char on its own, an error is returned.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn printn(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
+    fn chr(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
         let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
-        match arg1 {
-            Some(value) => {
-                println!("{:#?}", value);
-                Ok(None)
-            }
-            None => Err(Error::new(
+        match arg1.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::Int(code_point)) => u32::try_from(*code_point)
+                .ok()
+                .and_then(char::from_u32)
+                .map(|value| Some(Arc::new(Value::new(pos, ValueKind::Char(value)))))
+                .ok_or_else(|| Error::new(ErrorKind::InvalidCodePoint(*code_point), arg_pos_1)),
+            kind => Err(Error::new(
                 ErrorKind::ValueMismatch(
-                    ValueKind::Any.get_value_name(),
-                    ValueKind::Void.get_value_name(),
+                    ValueKind::Int(0).get_value_name(),
+                    kind.unwrap_or(&ValueKind::Void).get_value_name(),
                 ),
                 arg_pos_1,
             )),
         }
     }
 
-    /// Sets the identifier passed in to the value passed in.
+    /// Returns the char of the string passed in at the index passed in. Like len, the index counts characters rather than bytes.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn set(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
-        let (arg_pos_1, arg1) = self.get_arg_unevaluated(2, pos)?;
-        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+    fn charat(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (_, text) = self.get_string_arg(2, pos)?;
+        let index = self.get_index_arg(1, text.chars().count(), pos)?;
+        let value = text.chars().nth(index).unwrap_or_default();
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::Char(value)))))
+    }
 
-        match &arg1.kind {
-            ValueKind::Identifier(name) => {
+    /// Returns an array of the chars of the string passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn chars(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (_, text) = self.get_string_arg(1, pos)?;
+        let chars = text
+            .chars()
+            .map(|value| Arc::new(Value::new(pos, ValueKind::Char(value))))
+            .collect();
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::Array(chars)))))
+    }
+
+    /// Returns the number of characters in the string passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn slen(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (_, text) = self.get_string_arg(1, pos)?;
+        Ok(Some(Arc::new(Value::new(
+            pos,
+            ValueKind::Int(text.chars().count() as i64),
+        ))))
+    }
+
+    /// Returns the part of the string passed in that starts at the index passed in and has the length passed in, both counted in characters.
+    /// The index may be the length of the string, but the part may not go past the end of the string.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn substr(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (_, text) = self.get_string_arg(3, pos)?;
+        let len = text.chars().count();
+        let start = self.get_index_arg(2, len + 1, pos)?;
+        let count = self.get_index_arg(1, len - start + 1, pos)?;
+        let part = text.chars().skip(start).take(count).collect();
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::String(part)))))
+    }
+
+    /// Returns the first string passed in followed by the second string passed in.
+    /// Unlike the add instruction, both values must be strings.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn sconcat(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (_, mut first) = self.get_string_arg(2, pos)?;
+        let (_, second) = self.get_string_arg(1, pos)?;
+        first.push_str(&second);
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::String(first)))))
+    }
+
+    /// Returns the string passed in with every character in uppercase, following the Unicode rules, so that 'ß' becomes "SS".
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn supper(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (_, text) = self.get_string_arg(1, pos)?;
+        Ok(Some(Arc::new(Value::new(
+            pos,
+            ValueKind::String(text.to_uppercase()),
+        ))))
+    }
+
+    /// Returns the string passed in with every character in lowercase, following the Unicode rules.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn slower(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (_, text) = self.get_string_arg(1, pos)?;
+        Ok(Some(Arc::new(Value::new(
+            pos,
+            ValueKind::String(text.to_lowercase()),
+        ))))
+    }
+
+    /// Returns an empty array. Elements are added to it with the apush instruction.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn arr(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::Array(vec![])))))
+    }
+
+    /// Returns the element of the array passed in at the index passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn aget(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let elements = self.get_array_arg(2, pos)?;
+        let index = self.get_index_arg(1, elements.len(), pos)?;
+        Ok(Some(elements[index].clone()))
+    }
+
+    /// Returns a copy of the array passed in, with the element at the index passed in replaced by the value passed in.
+    /// The array itself is not changed, so the copy is usually stored back in the variable that held it.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn aset(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let mut elements = self.get_array_arg(3, pos)?;
+        let index = self.get_index_arg(2, elements.len(), pos)?;
+        let element = self.get_element_arg(1, pos)?;
+        self.limits.check_element(&element, pos)?;
+        elements[index] = element;
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::Array(elements)))))
+    }
+
+    /// Returns a copy of the array passed in, with the value passed in added to its end.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn apush(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let mut elements = self.get_array_arg(2, pos)?;
+        let element = self.get_element_arg(1, pos)?;
+        self.limits.check_element(&element, pos)?;
+        elements.push(element);
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::Array(elements)))))
+    }
+
+    /// Pushes a copy of the array passed in without its last element, followed by the last element.
+    /// This way, the last element can be popped first, and the rest of the array after it.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn apop(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let mut elements = self.get_array_arg(1, pos)?;
+        let last = elements
+            .pop()
+            .ok_or_else(|| Error::new(ErrorKind::EmptyArray, pos))?;
+        self.push_value(Arc::new(Value::new(pos, ValueKind::Array(elements))), pos)?;
+        self.push_value(last, pos)?;
+        Ok(None)
+    }
+
+    /// Returns the number of elements in the array passed in. Unlike len, the argument must be an array.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn alen(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let elements = self.get_array_arg(1, pos)?;
+        Ok(Some(Arc::new(Value::new(
+            pos,
+            ValueKind::Int(elements.len() as i64),
+        ))))
+    }
+
+    /// Returns an empty map. Keys are added to it with the mset instruction.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn mnew(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Ok(Some(Arc::new(Value::new(
+            pos,
+            ValueKind::Map(BTreeMap::new()),
+        ))))
+    }
+
+    /// Returns the value of the key passed in, in the map passed in. If the key is not in the map, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn mget(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let entries = self.get_map_arg(2, pos)?;
+        let (key_pos, key) = self.get_string_arg(1, pos)?;
+        match entries.get(&key) {
+            Some(value) => Ok(Some(value.clone())),
+            None => Err(Error::new(ErrorKind::MissingKey(key), key_pos)),
+        }
+    }
+
+    /// Returns a copy of the map passed in, with the key passed in set to the value passed in.
+    /// The map itself is not changed, so the copy is usually stored back in the variable that held it.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn mset(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let mut entries = self.get_map_arg(3, pos)?;
+        let (_, key) = self.get_string_arg(2, pos)?;
+        let element = self.get_element_arg(1, pos)?;
+        self.limits.check_element(&element, pos)?;
+        entries.insert(key, element);
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::Map(entries)))))
+    }
+
+    /// Returns a copy of the map passed in, without the key passed in. If the key is not in the map, the copy is the same as the map.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn mdel(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let mut entries = self.get_map_arg(2, pos)?;
+        let (_, key) = self.get_string_arg(1, pos)?;
+        entries.remove(&key);
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::Map(entries)))))
+    }
+
+    /// Returns whether the key passed in is in the map passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn mhas(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let entries = self.get_map_arg(2, pos)?;
+        let (_, key) = self.get_string_arg(1, pos)?;
+        Ok(Some(Arc::new(Value::new(
+            pos,
+            ValueKind::Boolean(entries.contains_key(&key)),
+        ))))
+    }
+
+    /// Returns an array of the keys of the map passed in. The keys are sorted, since a map keeps its keys in order.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn mkeys(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let keys = self
+            .get_map_arg(1, pos)?
+            .into_keys()
+            .map(|key| Arc::new(Value::new(pos, ValueKind::String(key))))
+            .collect();
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::Array(keys)))))
+    }
+
+    /// Evaluates both arguments and returns whether both of them are truthy.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn and(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.logical(pos, Value::logical_and)
+    }
+
+    /// Evaluates both arguments and returns whether either of them is truthy.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn or(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.logical(pos, Value::logical_or)
+    }
+
+    /// Returns whether the argument is not truthy.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn not(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
+        match arg1 {
+            Some(operand) => Ok(Some(Arc::new(operand.logical_not(pos)))),
+            None => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+        }
+    }
+
+    /// Evaluates the two arguments and combines them with the given logical operation.
+    /// Both arguments are evaluated, even if the first one already decides the result.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    /// `combine` - The operation to apply to the two values.
+    fn logical(
+        &mut self,
+        pos: usize,
+        combine: impl Fn(&Value, &Value, usize) -> Value,
+    ) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
+        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+
+        match (arg1, arg2) {
+            (Some(operand1), Some(operand2)) => Ok(Some(Arc::new(combine(
+                operand1.as_ref(),
+                operand2.as_ref(),
+                pos,
+            )))),
+            (None, _) => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+            (_, None) => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_2,
+            )),
+        }
+    }
+
+    /// Pops the top two values from the stack and returns whether the first value popped is less than the second.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn slt(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.compare_stack(pos, Value::lt)
+    }
+
+    /// Pops the top two values from the stack and returns whether the first value popped is less than or equal to the second.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn slte(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.compare_stack(pos, Value::lte)
+    }
+
+    /// Pops the top two values from the stack and returns whether the first value popped is greater than the second.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn sgt(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.compare_stack(pos, Value::gt)
+    }
+
+    /// Pops the top two values from the stack and returns whether the first value popped is greater than or equal to the second.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn sgte(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.compare_stack(pos, Value::gte)
+    }
+
+    /// Pops the top two values from the stack and returns whether they are equal.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn seq(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let float_equality = self.float_equality;
+        self.compare_stack(pos, |operand1, operand2, pos| {
+            Ok(operand1.equal(operand2, float_equality, pos))
+        })
+    }
+
+    /// Pops the top two values from the stack and returns whether they are not equal.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn sneq(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let float_equality = self.float_equality;
+        self.compare_stack(pos, |operand1, operand2, pos| {
+            Ok(operand1.not_equal(operand2, float_equality, pos))
+        })
+    }
+
+    /// Pops the top two values from the stack and compares them with the given comparison.
+    /// This is used by the stack forms of the comparison instructions, such as slt, and by the bitwise instructions, such as band.
+    /// Like the arithmetic instructions, the first value popped is the left operand.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    /// `compare` - The comparison to apply to the two values.
+    fn compare_stack(
+        &mut self,
+        pos: usize,
+        compare: impl Fn(&Value, &Value, usize) -> Result<Value, Error>,
+    ) -> Result<Option<Arc<Value>>, Error> {
+        let (_, arg1) = self.pop(pos)?;
+        let (_, arg2) = self.pop(pos)?;
+        match (arg1, arg2) {
+            (Some(operand1), Some(operand2)) => {
+                compare(operand1.as_ref(), operand2.as_ref(), pos).map(|val| Some(Arc::new(val)))
+            }
+            _ => Err(Error::new(ErrorKind::EmptyStack, pos)),
+        }
+    }
+
+    /// Changes the instruction pointer in the Code struct to the argument passed in.
+    /// However, there are restrictions on the argument:
+    /// - First, the argument must be an int.
+    /// - Second, the argument must fit in the range 0 and values.len() inclusive.
+    ///
+    /// If either of these constraints are broken, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn jmp(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
+        match arg1 {
+            Some(value) => {
+                if let ValueKind::Int(jump_location) = value.kind {
+                    if let Some(error) = self.code.jump(jump_location, pos) {
+                        Err(error)
+                    } else {
+                        Ok(None)
+                    }
+                } else {
+                    Err(Error::new(
+                        ErrorKind::ValueMismatch(
+                            ValueKind::Int(0).get_value_name(),
+                            value.kind.get_value_name(),
+                        ),
+                        arg_pos_1,
+                    ))
+                }
+            }
+            None => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Int(0).get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+        }
+    }
+
+    /// Changes the instruction pointer in the Code struct by the argument passed in.
+    /// This argument can be positive or negative. However, it must meet the same bound requirements
+    /// as the jmp instruction.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn rjmp(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
+        match arg1 {
+            Some(value) => {
+                if let ValueKind::Int(jump_location) = value.kind {
+                    if let Some(error) = self
+                        .code
+                        .relative_jump(jump_location.saturating_sub(1), pos)
+                    {
+                        Err(error)
+                    } else {
+                        Ok(None)
+                    }
+                } else {
+                    Err(Error::new(
+                        ErrorKind::ValueMismatch(
+                            ValueKind::Int(0).get_value_name(),
+                            value.kind.get_value_name(),
+                        ),
+                        arg_pos_1,
+                    ))
+                }
+            }
+            None => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Int(0).get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+        }
+    }
+
+    /// Changes the instruction pointer in the Code struct to the argument passed in
+    /// if the top value on the stack is true.
+    /// However, there are restrictions on the argument:
+    /// - First, the argument must be an int.
+    /// - Second, the argument must fit in the range 0 and values.len() inclusive.
+    ///
+    /// If either of these constraints are broken, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn jmpt(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        match self.operand_stack.peek() {
+            Some(value) if value.is_truthy() => self.jmp(pos),
+            None => Err(Error::new(ErrorKind::EmptyStack, pos)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Changes the instruction pointer in the Code struct to the argument passed in
+    /// if the top value on the stack is false.
+    /// However, there are restrictions on the argument:
+    /// - First, the argument must be an int.
+    /// - Second, the argument must fit in the range 0 and values.len() inclusive.
+    ///
+    /// If either of these constraints are broken, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn jmpf(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        match self.operand_stack.peek() {
+            Some(value) if !value.is_truthy() => self.jmp(pos),
+            None => Err(Error::new(ErrorKind::EmptyStack, pos)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Changes the instruction pointer in the Code struct by the argument passed in
+    /// if the top value on the stack is true. The constraints are the same are the rjmp instruction.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn rjmpt(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        match self.operand_stack.peek() {
+            Some(value) if value.is_truthy() => self.rjmp(pos),
+            None => Err(Error::new(ErrorKind::EmptyStack, pos)),
+            _ => Ok(None),
+        }
+    }
+
+    //// Changes the instruction pointer in the Code struct by the argument passed in
+    /// if the top value on the stack is false. The constraints are the same are the rjmp instruction.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn rjmpf(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        match self.operand_stack.peek() {
+            Some(value) if !value.is_truthy() => self.rjmp(pos),
+            None => Err(Error::new(ErrorKind::EmptyStack, pos)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Prints the argument passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn print(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.write_arg(pos, false, false)
+    }
+
+    /// Prints the argument passed in with a new line after it.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn printn(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.write_arg(pos, false, true)
+    }
+
+    /// Prints the argument passed in to the error stream.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn eprint(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.write_arg(pos, true, false)
+    }
+
+    /// Prints the argument passed in to the error stream with a new line after it.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn eprintn(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.write_arg(pos, true, true)
+    }
+
+    /// Reads a line from the input stream and returns it as a string, without its line ending.
+    /// Null is returned once the input has ended, so that an empty line can be told apart from the end of the input.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn read(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let kind = self
+            .read_line("read", pos)?
+            .map_or(ValueKind::Null, ValueKind::String);
+        Ok(Some(Arc::new(Value::new(pos, kind))))
+    }
+
+    /// Reads a line from the input stream and returns it as an int. Whitespace around the int is ignored.
+    /// Null is returned once the input has ended.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn readint(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let kind = match self.read_line("readint", pos)? {
+            Some(line) => ValueKind::Int(line.trim().parse().map_err(|_| {
+                Error::new(
+                    ErrorKind::InvalidInput(line.clone(), ValueKind::Int(0).get_value_name()),
+                    pos,
+                )
+            })?),
+            None => ValueKind::Null,
+        };
+
+        Ok(Some(Arc::new(Value::new(pos, kind))))
+    }
+
+    /// Reads a line from the input stream and returns it as a float. Whitespace around the float is ignored.
+    /// Null is returned once the input has ended.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn readfloat(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let kind = match self.read_line("readfloat", pos)? {
+            Some(line) => ValueKind::Float(line.trim().parse().map_err(|_| {
+                Error::new(
+                    ErrorKind::InvalidInput(line.clone(), ValueKind::Float(0.0).get_value_name()),
+                    pos,
+                )
+            })?),
+            None => ValueKind::Null,
+        };
+
+        Ok(Some(Arc::new(Value::new(pos, kind))))
+    }
+
+    /// Reads at most the number of bytes passed in from the input stream and returns them.
+    /// Fewer bytes are returned if the input ends first, so empty bytes mean that the input has ended.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn readbytes(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
+        let count = match arg1.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::Int(count)) => (*count).max(0) as u64,
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        ValueKind::Int(0).get_value_name(),
+                        kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                    ),
+                    arg_pos_1,
+                ))
+            }
+        };
+
+        // Traces are JSON, which has no bytes, so the bytes are recorded as Base64.
+        let encoded = self.observe("readbytes", pos, || {
+            let mut bytes = vec![];
+            let mut input = self.io.input();
+            (&mut *input)
+                .take(count)
+                .read_to_end(&mut bytes)
+                .map_err(|error| Error::new(ErrorKind::FailedRead(error.to_string()), pos))?;
+            Ok(Value::new(
+                pos,
+                ValueKind::String(encoding::base64_encode(&bytes)),
+            ))
+        })?;
+        let bytes = match &encoded.kind {
+            ValueKind::String(text) => encoding::base64_decode(text),
+            _ => None,
+        }
+        .ok_or_else(|| Error::new(ErrorKind::InvalidTrace, pos))?;
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::Bytes(bytes)))))
+    }
+
+    /// Writes the raw bytes of the argument passed in to the output stream.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn writebytes(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let bytes = self.get_bytes_arg(1, pos)?;
+        self.io
+            .out()
+            .write_all(&bytes)
+            .map(|_| None)
+            .map_err(|error| Error::new(ErrorKind::FailedWrite(error.to_string()), pos))
+    }
+
+    /// Reads the file at the path passed in and returns its contents as a string.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn readfile(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, path) = self.get_path_arg(1, pos, "readfile")?;
+        self.observe("readfile", pos, || {
+            fs::read_to_string(&path)
+                .map(|contents| Value::new(pos, ValueKind::String(contents)))
+                .map_err(|error| file_error(&path.to_string_lossy(), error, arg_pos_1))
+        })
+        .map(|contents| Some(Arc::new(contents)))
+    }
+
+    /// Writes the value passed in to the file at the path passed in, replacing its contents.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn writefile(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.write_file(pos, "writefile", false)
+    }
+
+    /// Writes the value passed in to the end of the file at the path passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn appendfile(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.write_file(pos, "appendfile", true)
+    }
+
+    /// Writes the second argument passed in to the file at the path given by the first argument.
+    /// The file is created if it does not exist.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    /// `instruction` - The name of the instruction, which is used if the file system capability was not granted.
+    /// `append` - Whether the value should be written to the end of the file, instead of replacing its contents.
+    fn write_file(
+        &mut self,
+        pos: usize,
+        instruction: &str,
+        append: bool,
+    ) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, path) = self.get_path_arg(2, pos, instruction)?;
+        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+        let contents = match arg2 {
+            Some(value) => value.to_string(),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        ValueKind::Any.get_value_name(),
+                        ValueKind::Void.get_value_name(),
+                    ),
+                    arg_pos_2,
+                ))
+            }
+        };
+
+        OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(&path)
+            .and_then(|mut file| file.write_all(contents.as_bytes()))
+            .map(|_| None)
+            .map_err(|error| file_error(&path.to_string_lossy(), error, arg_pos_1))
+    }
+
+    /// Runs the command passed in, and returns a map with its exit code and its captured output.
+    /// A string is split on whitespace, and an array holds the program and each of its arguments.
+    /// If the VM was not allowed to run commands, or the exec feature is not enabled, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn exec(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let policy = self.capabilities.get_exec("exec", pos)?.clone();
+
+        let (arg_pos, arg) = self.get_arg(1, pos)?;
+        let command: Vec<String> = match arg.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::String(command)) => {
+                command.split_whitespace().map(str::to_owned).collect()
+            }
+            Some(ValueKind::Array(elements)) => elements
+                .iter()
+                .map(|element| match &element.kind {
+                    ValueKind::String(part) => Ok(part.to_owned()),
+                    kind => Err(Error::new(
+                        ErrorKind::ValueMismatch(
+                            ValueKind::String("".to_owned()).get_value_name(),
+                            kind.get_value_name(),
+                        ),
+                        arg_pos,
+                    )),
+                })
+                .collect::<Result<_, _>>()?,
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        "String Or Array".to_owned(),
+                        kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                    ),
+                    arg_pos,
+                ))
+            }
+        };
+
+        #[cfg(feature = "exec")]
+        return self
+            .observe("exec", pos, || policy.run(&command, arg_pos))
+            .map(|result| Some(Arc::new(result)));
+
+        #[cfg(not(feature = "exec"))]
+        {
+            let _ = (policy, command);
+            Err(Error::new(
+                ErrorKind::FeatureDisabled("exec".to_owned(), "exec".to_owned()),
+                pos,
+            ))
+        }
+    }
+
+    /// Returns the value of the environment variable passed in, or void if it is not set or is not valid unicode.
+    /// If the VM was not allowed to read the environment, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn env(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.capabilities.check_env("env", pos)?;
+        let (_, name) = self.get_string_arg(1, pos)?;
+        let value = self.observe("env", pos, || {
+            Ok(Value::new(
+                pos,
+                env::var(&name).map_or(ValueKind::Void, ValueKind::String),
+            ))
+        })?;
+
+        match value.kind {
+            ValueKind::Void => Ok(None),
+            _ => Ok(Some(Arc::new(value))),
+        }
+    }
+
+    /// Sends a GET request to the url passed in, and returns a map with the status of the response and its body.
+    /// If the VM was not allowed to use the network, or the http feature is not enabled, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn fetch(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.capabilities.check_net("fetch", pos)?;
+
+        let (url_pos, url) = self.get_string_arg(1, pos)?;
+
+        #[cfg(feature = "http")]
+        return self
+            .observe("fetch", pos, || http::fetch(&url, url_pos))
+            .map(|response| Some(Arc::new(response)));
+
+        #[cfg(not(feature = "http"))]
+        {
+            let _ = (url_pos, url);
+            Err(Error::new(
+                ErrorKind::FeatureDisabled("fetch".to_owned(), "http".to_owned()),
+                pos,
+            ))
+        }
+    }
+
+    /// Parses the string passed in as JSON and returns the value.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn jsonparse(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
+        match arg1.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::String(text)) => Json::parse(text)
+                .map(|json| Some(Arc::new(json.to_value(pos))))
+                .map_err(|_| Error::new(ErrorKind::InvalidJson, arg_pos_1)),
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::String("".to_owned()).get_value_name(),
+                    kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+        }
+    }
+
+    /// Converts the value passed in into a JSON string.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn jsonstringify(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
+        match arg1 {
+            Some(value) => {
+                let json = Json::from_value(&value, arg_pos_1)?;
+                Ok(Some(Arc::new(Value::new(
+                    pos,
+                    ValueKind::String(json.to_string()),
+                ))))
+            }
+            None => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+        }
+    }
+
+    /// Parses the string passed in as CSV. If the boolean passed in is true, the first row is the header,
+    /// and every other row is a map. Otherwise, every row is an array.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn csvparse(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
+        let (_, arg2) = self.get_arg(1, pos)?;
+        let text = match arg1.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::String(text)) => text,
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        ValueKind::String("".to_owned()).get_value_name(),
+                        kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                    ),
+                    arg_pos_1,
+                ))
+            }
+        };
+
+        let has_header = arg2.is_some_and(|value| value.is_truthy());
+
+        #[cfg(feature = "csv")]
+        return csv::parse(text, has_header, arg_pos_1).map(|rows| Some(Arc::new(rows)));
+
+        #[cfg(not(feature = "csv"))]
+        {
+            let _ = (text, has_header);
+            Err(Error::new(
+                ErrorKind::FeatureDisabled("csvparse".to_owned(), "csv".to_owned()),
+                pos,
+            ))
+        }
+    }
+
+    /// Encodes the string or bytes passed in with base64.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn b64encode(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.encode(pos, encoding::base64_encode)
+    }
+
+    /// Decodes the base64 string passed in into bytes.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn b64decode(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.decode(pos, encoding::base64_decode, "Base64")
+    }
+
+    /// Encodes the string or bytes passed in as hexadecimal.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn hexencode(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.encode(pos, encoding::hex_encode)
+    }
+
+    /// Decodes the hexadecimal string passed in into bytes.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn hexdecode(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.decode(pos, encoding::hex_decode, "Hexadecimal")
+    }
+
+    /// Encodes the argument passed in, which must be a string or bytes, and returns the encoded string.
+    /// Strings are encoded as UTF-8.
+    ///
+    /// # Arguments
+    /// `pos` - The position where the instruction was called.
+    /// `encode` - The function that encodes the bytes.
+    fn encode(
+        &mut self,
+        pos: usize,
+        encode: fn(&[u8]) -> String,
+    ) -> Result<Option<Arc<Value>>, Error> {
+        let bytes = self.get_bytes_arg(1, pos)?;
+        Ok(Some(Arc::new(Value::new(
+            pos,
+            ValueKind::String(encode(&bytes)),
+        ))))
+    }
+
+    /// Returns the CRC-32 checksum of the string or bytes passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn crc32(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.hash(pos, HashAlgorithm::Crc32)
+    }
+
+    /// Returns the MD5 digest of the string or bytes passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn md5(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.hash(pos, HashAlgorithm::Md5)
+    }
+
+    /// Returns the SHA-256 digest of the string or bytes passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn sha256(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.hash(pos, HashAlgorithm::Sha256)
+    }
+
+    /// Hashes the argument passed in, which must be a string or bytes, and returns the digest as a hexadecimal string.
+    /// If the hashing feature is not enabled, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where the instruction was called.
+    /// `algorithm` - The algorithm to hash with.
+    fn hash(&mut self, pos: usize, algorithm: HashAlgorithm) -> Result<Option<Arc<Value>>, Error> {
+        let bytes = self.get_bytes_arg(1, pos)?;
+
+        #[cfg(feature = "hashing")]
+        return Ok(Some(Arc::new(Value::new(
+            pos,
+            ValueKind::String(algorithm.digest(&bytes)),
+        ))));
+
+        #[cfg(not(feature = "hashing"))]
+        {
+            let _ = bytes;
+            Err(Error::new(
+                ErrorKind::FeatureDisabled(algorithm.get_name().to_owned(), "hashing".to_owned()),
+                pos,
+            ))
+        }
+    }
+
+    /// Returns whether the regular expression passed in matches anywhere in the string passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn rmatch(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.regex(pos, RegexOperation::Match)
+    }
+
+    /// Returns an array of every match of the regular expression passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn rfind(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.regex(pos, RegexOperation::Find)
+    }
+
+    /// Replaces every match of the regular expression passed in, and returns the new string.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn rreplace(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.regex(pos, RegexOperation::Replace)
+    }
+
+    /// Applies the regular expression passed in to the string passed in.
+    /// The rreplace instruction also takes the replacement. If the regex feature is not enabled, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where the instruction was called.
+    /// `operation` - The operation to perform.
+    fn regex(
+        &mut self,
+        pos: usize,
+        operation: RegexOperation,
+    ) -> Result<Option<Arc<Value>>, Error> {
+        let arity = operation.get_arity();
+        let (pattern_pos, pattern) = self.get_string_arg(arity, pos)?;
+        let (_, text) = self.get_string_arg(arity - 1, pos)?;
+        let replacement = if operation == RegexOperation::Replace {
+            self.get_string_arg(1, pos)?.1
+        } else {
+            String::new()
+        };
+
+        #[cfg(feature = "regex")]
+        return operation
+            .apply(&pattern, pattern_pos, &text, &replacement, pos)
+            .map(|value| Some(Arc::new(value)));
+
+        #[cfg(not(feature = "regex"))]
+        {
+            let _ = (pattern_pos, pattern, text, replacement);
+            Err(Error::new(
+                ErrorKind::FeatureDisabled(operation.get_name().to_owned(), "regex".to_owned()),
+                pos,
+            ))
+        }
+    }
+
+    /// Returns the current time as a Unix timestamp.
+    /// If the datetime feature is not enabled, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn now(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.capabilities.check_clock("now", pos)?;
+
+        #[cfg(feature = "datetime")]
+        return self
+            .observe("now", pos, || {
+                Ok(Value::new(pos, ValueKind::Int(datetime::now())))
+            })
+            .map(|now| Some(Arc::new(now)));
+
+        #[cfg(not(feature = "datetime"))]
+        Err(Error::new(
+            ErrorKind::FeatureDisabled("now".to_owned(), "datetime".to_owned()),
+            pos,
+        ))
+    }
+
+    /// Formats the Unix timestamp passed in as a date, using the format passed in.
+    /// If the datetime feature is not enabled, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn dateformat(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos, arg) = self.get_arg(2, pos)?;
+        let timestamp = match arg.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::Int(timestamp)) => *timestamp,
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        ValueKind::Int(0).get_value_name(),
+                        kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                    ),
+                    arg_pos,
+                ))
+            }
+        };
+
+        let (format_pos, format) = self.get_string_arg(1, pos)?;
+
+        #[cfg(feature = "datetime")]
+        return datetime::format(timestamp, &format, format_pos)
+            .map(|date| Some(Arc::new(Value::new(pos, ValueKind::String(date)))));
+
+        #[cfg(not(feature = "datetime"))]
+        {
+            let _ = (timestamp, format_pos, format);
+            Err(Error::new(
+                ErrorKind::FeatureDisabled("dateformat".to_owned(), "datetime".to_owned()),
+                pos,
+            ))
+        }
+    }
+
+    /// Parses the string passed in as a date, using the format passed in, and returns its Unix timestamp.
+    /// If the datetime feature is not enabled, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn dateparse(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (text_pos, text) = self.get_string_arg(2, pos)?;
+        let (_, format) = self.get_string_arg(1, pos)?;
+
+        #[cfg(feature = "datetime")]
+        return datetime::parse(&text, &format, text_pos)
+            .map(|timestamp| Some(Arc::new(Value::new(pos, ValueKind::Int(timestamp)))));
+
+        #[cfg(not(feature = "datetime"))]
+        {
+            let _ = (text_pos, text, format);
+            Err(Error::new(
+                ErrorKind::FeatureDisabled("dateparse".to_owned(), "datetime".to_owned()),
+                pos,
+            ))
+        }
+    }
+
+    /// Gets the result of an instruction that observes the outside world, through the trace if the VM has one.
+    /// While the trace is replaying, the function is not called, and the recorded result is returned instead.
+    ///
+    /// # Arguments
+    /// `instruction` - The name of the instruction.
+    /// `pos` - The position where the instruction was called.
+    /// `observe` - The function that computes the result from the outside world.
+    fn observe<F: FnOnce() -> Result<Value, Error>>(
+        &self,
+        instruction: &str,
+        pos: usize,
+        observe: F,
+    ) -> Result<Value, Error> {
+        match &self.trace {
+            Some(trace) => trace.observe(instruction, pos, observe),
+            None => observe(),
+        }
+    }
+
+    /// Gets the next argument, which must be a string, and returns it along with its position.
+    ///
+    /// # Arguments
+    /// `expected_args` - The number of arguments still expected by the instruction.
+    /// `pos` - The position where the instruction was called.
+    fn get_string_arg(
+        &mut self,
+        expected_args: usize,
+        pos: usize,
+    ) -> Result<(usize, String), Error> {
+        let (arg_pos, arg) = self.get_arg(expected_args, pos)?;
+        match arg.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::String(text)) => Ok((arg_pos, text.to_owned())),
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::String("".to_owned()).get_value_name(),
+                    kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                ),
+                arg_pos,
+            )),
+        }
+    }
+
+    /// Gets the next argument, which must be an array, and returns its elements.
+    /// Only the references to the elements are copied, so this is cheap even for large elements.
+    ///
+    /// # Arguments
+    /// `expected_args` - The number of arguments still expected by the instruction.
+    /// `pos` - The position where the instruction was called.
+    fn get_array_arg(
+        &mut self,
+        expected_args: usize,
+        pos: usize,
+    ) -> Result<Vec<Arc<Value>>, Error> {
+        let (arg_pos, arg) = self.get_arg(expected_args, pos)?;
+        match arg.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::Array(elements)) => Ok(elements.to_owned()),
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Array(vec![]).get_value_name(),
+                    kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                ),
+                arg_pos,
+            )),
+        }
+    }
+
+    /// Gets the next argument, which must be a map, and returns its entries.
+    ///
+    /// # Arguments
+    /// `expected_args` - The number of arguments still expected by the instruction.
+    /// `pos` - The position where the instruction was called.
+    fn get_map_arg(
+        &mut self,
+        expected_args: usize,
+        pos: usize,
+    ) -> Result<BTreeMap<String, Arc<Value>>, Error> {
+        let (arg_pos, arg) = self.get_arg(expected_args, pos)?;
+        match arg.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::Map(entries)) => Ok(entries.to_owned()),
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Map(BTreeMap::new()).get_value_name(),
+                    kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                ),
+                arg_pos,
+            )),
+        }
+    }
+
+    /// Gets the next argument, which must be an int that is a valid index into a collection of the given length.
+    ///
+    /// # Arguments
+    /// `expected_args` - The number of arguments still expected by the instruction.
+    /// `len` - The length of the collection.
+    /// `pos` - The position where the instruction was called.
+    fn get_index_arg(
+        &mut self,
+        expected_args: usize,
+        len: usize,
+        pos: usize,
+    ) -> Result<usize, Error> {
+        let (arg_pos, arg) = self.get_arg(expected_args, pos)?;
+        match arg.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::Int(index)) if (0..len as i64).contains(index) => Ok(*index as usize),
+            Some(ValueKind::Int(_)) => Err(Error::new(ErrorKind::OutOfBounds(0, len), arg_pos)),
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Int(0).get_value_name(),
+                    kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                ),
+                arg_pos,
+            )),
+        }
+    }
+
+    /// Gets the next argument, which is stored in an array, so it must not be void.
+    ///
+    /// # Arguments
+    /// `expected_args` - The number of arguments still expected by the instruction.
+    /// `pos` - The position where the instruction was called.
+    fn get_element_arg(&mut self, expected_args: usize, pos: usize) -> Result<Arc<Value>, Error> {
+        let (arg_pos, arg) = self.get_arg(expected_args, pos)?;
+        arg.ok_or_else(|| {
+            Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos,
+            )
+        })
+    }
+
+    /// Gets the next argument, which must be a string or bytes, and returns its bytes.
+    /// Strings are converted to their UTF-8 bytes.
+    ///
+    /// # Arguments
+    /// `expected_args` - The number of arguments still expected by the instruction.
+    /// `pos` - The position where the instruction was called.
+    fn get_bytes_arg(&mut self, expected_args: usize, pos: usize) -> Result<Vec<u8>, Error> {
+        let (arg_pos, arg) = self.get_arg(expected_args, pos)?;
+        match arg.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::String(text)) => Ok(text.as_bytes().to_vec()),
+            Some(ValueKind::Bytes(bytes)) => Ok(bytes.to_owned()),
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    "String Or Bytes".to_owned(),
+                    kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                ),
+                arg_pos,
+            )),
+        }
+    }
+
+    /// Decodes the string passed in and returns the bytes.
+    ///
+    /// # Arguments
+    /// `pos` - The position where the instruction was called.
+    /// `decode` - The function that decodes the string, which returns None if the string is not valid.
+    /// `encoding` - The name of the encoding, which is used if the string is not valid.
+    fn decode(
+        &mut self,
+        pos: usize,
+        decode: fn(&str) -> Option<Vec<u8>>,
+        encoding: &str,
+    ) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
+        match arg1.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::String(text)) => decode(text)
+                .map(|bytes| Some(Arc::new(Value::new(pos, ValueKind::Bytes(bytes)))))
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidEncoding(encoding.to_owned()), arg_pos_1)
+                }),
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::String("".to_owned()).get_value_name(),
+                    kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+        }
+    }
+
+    /// Gets the next argument, which must be a string, and resolves it as a path.
+    /// An error is returned if the file system capability was not granted, or if the path is outside of the root directory.
+    ///
+    /// # Arguments
+    /// `expected_args` - The number of arguments still expected by the instruction.
+    /// `pos` - The position where the instruction was called.
+    /// `instruction` - The name of the instruction, which is used if the file system capability was not granted.
+    fn get_path_arg(
+        &mut self,
+        expected_args: usize,
+        pos: usize,
+        instruction: &str,
+    ) -> Result<(usize, PathBuf), Error> {
+        let fs = self.capabilities.get_fs(instruction, pos)?.clone();
+
+        let (arg_pos, arg) = self.get_arg(expected_args, pos)?;
+        match arg.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::String(path)) => Ok((arg_pos, fs.resolve(path, arg_pos)?)),
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::String("".to_owned()).get_value_name(),
+                    kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                ),
+                arg_pos,
+            )),
+        }
+    }
+
+    /// Asks the host to inspect the program, if the VM was built to suspend on breaks. Otherwise, this instruction does nothing.
+    /// The VM suspends once the current step finishes, so the next instruction has not been run yet.
+    ///
+    /// # Arguments
+    /// `_pos` - The position where this instruction was called.
+    fn brk(&mut self, _pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.suspend_requested = self.suspend_on_break;
+        Ok(None)
+    }
+
+    /// Writes the operand stack, from the top down, and the variables of the current frame to the error stream.
+    /// Values are written the way that the debugger writes them, so strings are quoted.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn dump(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let mut output = String::from("Stack, From The Top Down:\n");
+        if self.operand_stack.is_empty() {
+            output.push_str("    Empty\n");
+        }
+
+        for (depth, value) in self.operand_stack.0.iter().rev().enumerate() {
+            output.push_str(&format!("    {}: {:?}\n", depth, value));
+        }
+
+        if let Some(frame) = self.call_stack.0.last() {
+            let variables = frame.get_variables();
+            output.push_str(&format!("Variables Of @{}:\n", frame.name));
+            if variables.is_empty() {
+                output.push_str("    None\n");
+            }
+
+            for (name, value) in variables {
+                output.push_str(&format!("    {} = {:?}\n", name, value));
+            }
+        }
+
+        write!(self.io.err(), "{}", output)
+            .map(|_| None)
+            .map_err(|error| Error::new(ErrorKind::FailedWrite(error.to_string()), pos))
+    }
+
+    /// Formats the number passed in as a string, using the format specifier passed in.
+    /// The specifier is checked before the number is evaluated, so an invalid specifier is reported at its own position.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn format(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (spec_pos, spec) = self.get_string_arg(2, pos)?;
+        let format = spec
+            .parse::<NumberFormat>()
+            .map_err(|_| Error::new(ErrorKind::InvalidFormatSpecifier(spec), spec_pos))?;
+
+        let (arg_pos, arg) = self.get_arg(1, pos)?;
+        let kind = arg.as_ref().map_or(&ValueKind::Void, |value| &value.kind);
+        match format.apply(kind) {
+            Some(text) => Ok(Some(Arc::new(Value::new(pos, ValueKind::String(text))))),
+            None => Err(Error::new(
+                ErrorKind::ValueMismatch(format.get_expected(), kind.get_value_name()),
+                arg_pos,
+            )),
+        }
+    }
+
+    /// Writes the message passed in to the error stream, prefixed by its level.
+    /// If the level is below the level that the VM was configured with, the message is evaluated but not written.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn log(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg_unevaluated(2, pos)?;
+        let level = match &arg1.kind {
+            ValueKind::Identifier(name) => name
+                .parse::<LogLevel>()
+                .map_err(|_| Error::new(ErrorKind::UnknownLogLevel(name.to_owned()), arg_pos_1))?,
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        ValueKind::Identifier("".to_owned()).get_value_name(),
+                        kind.get_value_name(),
+                    ),
+                    arg_pos_1,
+                ))
+            }
+        };
+
+        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+        match arg2 {
+            Some(message) if level >= self.log_level => {
+                writeln!(self.io.err(), "[{}] {}", level, message)
+                    .map(|_| None)
+                    .map_err(|error| Error::new(ErrorKind::FailedWrite(error.to_string()), pos))
+            }
+            Some(_) => Ok(None),
+            None => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_2,
+            )),
+        }
+    }
+
+    /// Returns an error if the argument passed in is not truthy.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn assert(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
+        match arg1 {
+            Some(value) if value.is_truthy() => Ok(None),
+            Some(_) => Err(Error::new(ErrorKind::AssertionFailed, pos)),
+            None => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+        }
+    }
+
+    /// Returns an error if the two arguments passed in are not equal.
+    /// Rather than printing both values, the error lists where they differ, such as the index of an element or the key of an entry, so that large values can be compared.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn assert_eq(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
+        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+        match (arg1, arg2) {
+            (Some(expected), Some(actual)) => {
+                let differences = value_diff::diff(&expected, &actual, self.float_equality);
+                if differences.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(Error::new(ErrorKind::ValuesNotEqual(differences), pos))
+                }
+            }
+            (None, _) => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+            (_, None) => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_2,
+            )),
+        }
+    }
+
+    /// Writes the argument passed in to either the output stream or the error stream.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    /// `is_err` - Whether the argument should be written to the error stream.
+    /// `new_line` - Whether a new line should be written after the argument.
+    fn write_arg(
+        &mut self,
+        pos: usize,
+        is_err: bool,
+        new_line: bool,
+    ) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(1, pos)?;
+        match arg1 {
+            Some(value) => {
+                let binary = self.io.is_binary();
+                let mut stream = if is_err { self.io.err() } else { self.io.out() };
+                let result = if let (true, ValueKind::Bytes(bytes)) = (binary, &value.kind) {
+                    stream.write_all(bytes).and_then(|_| {
+                        if new_line {
+                            writeln!(stream)
+                        } else {
+                            Ok(())
+                        }
+                    })
+                } else if new_line {
+                    writeln!(stream, "{}", value)
+                } else {
+                    write!(stream, "{}", value)
+                };
+
+                result
+                    .map(|_| None)
+                    .map_err(|error| Error::new(ErrorKind::FailedWrite(error.to_string()), pos))
+            }
+            None => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+        }
+    }
+
+    /// Sets the identifier passed in to the value passed in.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn set(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg_unevaluated(2, pos)?;
+        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+
+        match &arg1.kind {
+            ValueKind::Identifier(name) => {
                 if let Some(value) = arg2 {
-                    self.call_stack.peek_mut().unwrap().define(name, value);
+                    let old = self.get_watched_value(name);
+                    let frame = self
+                        .call_stack
+                        .peek_mut()
+                        .ok_or_else(|| Error::new(ErrorKind::NoActiveFrame, pos))?;
+                    if self.shadowing != Shadowing::Allow {
+                        let shadowed = frame.current_store.borrow().get_shadowed(name);
+                        if let Some(shadowed) = shadowed {
+                            let error =
+                                Error::new(ErrorKind::ShadowedVariable(name.to_owned()), arg_pos_1)
+                                    .with_related(shadowed.pos, "Previously Defined Here.");
+                            if self.shadowing == Shadowing::Deny {
+                                return Err(error);
+                            }
+
+                            self.warnings.push(error);
+                        }
+                    }
+
+                    frame.define(name, value.clone());
+                    self.notify_write(name, old, value, pos);
                     Ok(None)
                 } else {
                     Err(Error::new(
                         ErrorKind::ValueMismatch(
-                            ValueKind::Any.get_value_name(),
-                            ValueKind::Void.get_value_name(),
+                            ValueKind::Any.get_value_name(),
+                            ValueKind::Void.get_value_name(),
+                        ),
+                        arg_pos_2,
+                    ))
+                }
+            }
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Identifier("".to_owned()).get_value_name(),
+                    kind.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+        }
+    }
+
+    /// Adds the value passed in to the variable passed in.
+    /// Unlike set, the variable must already be defined, and it is updated in the scope that defines it, even if that is an outer scope.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn incvar(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.update_variable(pos, Value::add)
+    }
+
+    /// Subtracts the value passed in from the variable passed in.
+    /// Unlike set, the variable must already be defined, and it is updated in the scope that defines it, even if that is an outer scope.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn decvar(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.update_variable(pos, Value::sub)
+    }
+
+    /// Exchanges the values of the two variables passed in. Each variable keeps the scope that defines it.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn swapvar(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (first_pos, first) = self.get_variable_name(2, pos)?;
+        let (second_pos, second) = self.get_variable_name(1, pos)?;
+        let frame = self
+            .call_stack
+            .peek()
+            .ok_or_else(|| Error::new(ErrorKind::NoActiveFrame, pos))?;
+        let first_value = frame.find(&first, first_pos)?;
+        let second_value = frame.find(&second, second_pos)?;
+
+        {
+            let mut store = frame.current_store.borrow_mut();
+            store.assign(&first, second_value.clone());
+            store.assign(&second, first_value.clone());
+        }
+
+        self.notify_write(&first, Some(first_value.clone()), second_value.clone(), pos);
+        self.notify_write(&second, Some(second_value), first_value, pos);
+        Ok(None)
+    }
+
+    /// Reads the variable passed in, combines it with the value passed in, and writes the result back to the scope that defines the variable.
+    ///
+    /// # Arguments
+    /// `pos` - The position where the instruction was called.
+    /// `operation` - The operation that combines the variable with the value.
+    fn update_variable(
+        &mut self,
+        pos: usize,
+        operation: fn(&Value, &Value, Overflow, usize) -> Result<Value, Error>,
+    ) -> Result<Option<Arc<Value>>, Error> {
+        let (name_pos, name) = self.get_variable_name(2, pos)?;
+        let (arg_pos_2, arg2) = self.get_arg(1, pos)?;
+        let amount = arg2.ok_or_else(|| {
+            Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Any.get_value_name(),
+                    ValueKind::Void.get_value_name(),
+                ),
+                arg_pos_2,
+            )
+        })?;
+
+        let frame = self
+            .call_stack
+            .peek()
+            .ok_or_else(|| Error::new(ErrorKind::NoActiveFrame, pos))?;
+        let old = frame.find(&name, name_pos)?;
+        let result = operation(&old, &amount, self.overflow, pos)?;
+        if self.limits.limits_values() {
+            self.limits.check_value(&result, pos)?;
+        }
+
+        let result = Arc::new(result);
+        frame
+            .current_store
+            .borrow_mut()
+            .assign(&name, result.clone());
+        self.notify_write(&name, Some(old), result, pos);
+        Ok(None)
+    }
+
+    /// Gets the current value of the variable passed in, if it is watched, so that it can be reported once the variable is written.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    fn get_watched_value(&self, name: &str) -> Option<Arc<Value>> {
+        if !self.watchpoints.is_watched(name) {
+            return None;
+        }
+
+        self.call_stack
+            .peek()
+            .and_then(|frame| frame.current_store.borrow().get(name, 0).ok())
+    }
+
+    /// Reports a write to the variable passed in, if it is watched. If the VM suspends on breaks, it suspends once the current step finishes.
+    ///
+    /// # Arguments
+    /// `name` - The name of the variable.
+    /// `old` - The value of the variable before the write, if it was defined.
+    /// `new` - The value of the variable after the write.
+    /// `pos` - The position of the instruction that wrote the variable.
+    fn notify_write(&mut self, name: &str, old: Option<Arc<Value>>, new: Arc<Value>, pos: usize) {
+        if !self.watchpoints.is_watched(name) {
+            return;
+        }
+
+        let event = WatchEvent {
+            name: name.to_owned(),
+            label: self
+                .call_stack
+                .peek()
+                .map_or_else(String::new, |frame| frame.name.clone()),
+            old,
+            new,
+            pos,
+        };
+        self.watchpoints.notify(event, self.suspend_on_break);
+        self.suspend_requested |= self.suspend_on_break;
+    }
+
+    /// Gets the next argument, which must be the name of a variable. The name is not evaluated.
+    ///
+    /// # Arguments
+    /// `expected_args` - The number of arguments remaining for the instruction.
+    /// `pos` - The position where the instruction was called.
+    fn get_variable_name(
+        &mut self,
+        expected_args: usize,
+        pos: usize,
+    ) -> Result<(usize, String), Error> {
+        let (arg_pos, arg) = self.get_arg_unevaluated(expected_args, pos)?;
+        match &arg.kind {
+            ValueKind::Identifier(name) => Ok((arg_pos, name.to_owned())),
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Identifier("".to_owned()).get_value_name(),
+                    kind.get_value_name(),
+                ),
+                arg_pos,
+            )),
+        }
+    }
+
+    /// Enters a new block in the current frame. The variables that are defined until the matching endblock instruction
+    /// are kept in a child store, so that they do not leak into the rest of the label.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn begin(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        self.call_stack
+            .peek_mut()
+            .ok_or_else(|| Error::new(ErrorKind::NoActiveFrame, pos))?
+            .begin_block();
+        Ok(None)
+    }
+
+    /// Leaves the innermost block of the current frame, discarding the variables defined in it.
+    /// If no block was entered in the current frame, an error is returned.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn endblock(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let frame = self
+            .call_stack
+            .peek_mut()
+            .ok_or_else(|| Error::new(ErrorKind::NoActiveFrame, pos))?;
+        if frame.end_block() {
+            Ok(None)
+        } else {
+            Err(Error::new(ErrorKind::EndBlockWithoutBegin, pos))
+        }
+    }
+
+    /// Calls the label passed in. In other words, it changes the instruction pointer.
+    /// In the future, this would be changed to include the number of parameters on the stack.
+    /// If the host registered a native function with the name, the native function is called instead.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn call(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg_unevaluated(1, pos)?;
+        match &arg1.kind {
+            ValueKind::Identifier(label_name) => {
+                if let Some((arity, function)) = self.natives.get(label_name) {
+                    return self.call_native(arity, function, pos);
+                }
+
+                self.check_depth(pos)?;
+                let (_, _, parameters) = self
+                    .code
+                    .get_code()
+                    .get_label_location(label_name, arg_pos_1)?;
+                let parameter_values = self.get_parameter_values(&parameters, arg_pos_1)?;
+                self.enter_label(label_name, arg_pos_1, parameter_values)?;
+                Ok(None)
+            }
+            kind => Err(Error::new(
+                ErrorKind::ValueMismatch(
+                    ValueKind::Label("".to_owned(), vec![]).get_value_name(),
+                    kind.get_value_name(),
+                ),
+                arg_pos_1,
+            )),
+        }
+    }
+
+    /// Evaluates the arguments of a native function, and calls it with them.
+    /// The native function runs in the frame of its caller, so no frame is pushed for it. The value that it returns is the value of the call instruction.
+    ///
+    /// # Arguments
+    /// `arity` - The number of arguments that the function takes.
+    /// `function` - The function.
+    /// `pos` - The position where the call instruction was called.
+    fn call_native(
+        &mut self,
+        arity: usize,
+        function: NativeFunction,
+        pos: usize,
+    ) -> Result<Option<Arc<Value>>, Error> {
+        let mut arguments = Vec::with_capacity(arity);
+        for remaining in (1..=arity).rev() {
+            let (arg_pos, arg) = self.get_arg(remaining, pos)?;
+            match arg {
+                Some(arg) => arguments.push(arg),
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::ValueMismatch(
+                            ValueKind::Any.get_value_name(),
+                            ValueKind::Void.get_value_name(),
+                        ),
+                        arg_pos,
+                    ))
+                }
+            }
+        }
+
+        function(self, arguments).map(|value| value.map(Arc::new))
+    }
+
+    /// Defers the label passed in until the current frame exits, whether it ends or is unwound by an error.
+    /// The label must exist and take no parameters, which the verifier checks before the program runs.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn defer(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos, arg) = self.get_arg_unevaluated(1, pos)?;
+        let label_name = match &arg.kind {
+            ValueKind::Identifier(label_name) => label_name,
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        ValueKind::Label("".to_owned(), vec![]).get_value_name(),
+                        kind.get_value_name(),
+                    ),
+                    arg_pos,
+                ))
+            }
+        };
+
+        self.code
+            .get_code()
+            .get_label_location(label_name, arg_pos)?;
+        match self.call_stack.peek_mut() {
+            Some(frame) => frame.deferred.push((label_name.to_owned(), arg_pos)),
+            None => return Err(Error::new(ErrorKind::NoActiveFrame, pos)),
+        }
+
+        Ok(None)
+    }
+
+    /// Reports an alias instruction that was not removed. The aliases are always defined and removed when the code is constructed,
+    /// so the handler of this instruction is never reached.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn alias(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Err(Error::new(ErrorKind::ExpectedAlias, pos))
+    }
+
+    /// Reports an if instruction that was not lowered. The if, else, and endif instructions are always lowered into jumps when the code is constructed,
+    /// so the handlers of these instructions are never reached.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn if_block(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Err(Error::new(ErrorKind::UnterminatedIf, pos))
+    }
+
+    /// Reports an else instruction that was not lowered.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn else_block(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Err(Error::new(ErrorKind::ElseWithoutIf, pos))
+    }
+
+    /// Reports an endif instruction that was not lowered.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn endif(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Err(Error::new(ErrorKind::EndIfWithoutIf, pos))
+    }
+
+    /// Reports a while instruction that was not lowered. Like the if instruction, the while, do, and endwhile instructions are always lowered into jumps.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn while_block(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Err(Error::new(ErrorKind::UnterminatedWhile, pos))
+    }
+
+    /// Reports a do instruction that was not lowered.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn do_block(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Err(Error::new(ErrorKind::DoWithoutWhile, pos))
+    }
+
+    /// Reports an endwhile instruction that was not lowered.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn endwhile(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Err(Error::new(ErrorKind::EndWhileWithoutWhile, pos))
+    }
+
+    /// Reports a break instruction that was not lowered. Like the while instruction, the break and continue instructions are always lowered into jumps.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn break_loop(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Err(Error::new(ErrorKind::BreakOutsideLoop, pos))
+    }
+
+    /// Reports a continue instruction that was not lowered.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn continue_loop(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Err(Error::new(ErrorKind::ContinueOutsideLoop, pos))
+    }
+
+    /// Pops the top value of the stack and compares it against each pattern of the match instruction, in order.
+    /// The label of the first pattern that is equal to the value, or of the wildcard, is called, and receives the value if it defines a parameter.
+    /// If no pattern matches, nothing is called. Either way, the program continues after the endmatch instruction.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn match_value(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let value = match self.pop(pos)? {
+            (_, Some(value)) => value,
+            (arg_pos, None) => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        ValueKind::Any.get_value_name(),
+                        ValueKind::Void.get_value_name(),
+                    ),
+                    arg_pos,
+                ))
+            }
+        };
+
+        // Every pattern is read, even after a match, so that the program continues after the endmatch instruction.
+        let mut matched = None;
+        loop {
+            let pattern = self
+                .next()
+                .ok_or_else(|| Error::new(ErrorKind::UnterminatedMatch, pos))?;
+            if let ValueKind::Instruction(Opcode::EndMatch) = pattern.kind {
+                break;
+            }
+
+            let (label_pos, label) = self.get_arg_unevaluated(1, pos)?;
+            let label_name = match &label.kind {
+                ValueKind::Identifier(label_name) => label_name,
+                kind => {
+                    return Err(Error::new(
+                        ErrorKind::ValueMismatch(
+                            ValueKind::Label("".to_owned(), vec![]).get_value_name(),
+                            kind.get_value_name(),
                         ),
-                        arg_pos_2,
+                        label_pos,
                     ))
                 }
+            };
+
+            let is_match = match &pattern.kind {
+                ValueKind::Identifier(name) => name == verifier::WILDCARD,
+                _ => value.equal(&pattern, self.float_equality, pos).is_truthy(),
+            };
+
+            if matched.is_none() && is_match {
+                matched = Some((label_name.to_owned(), label_pos));
             }
-            kind => Err(Error::new(
-                ErrorKind::ValueMismatch(
-                    ValueKind::Identifier("".to_owned()).get_value_name(),
-                    kind.get_value_name(),
-                ),
-                arg_pos_1,
-            )),
         }
+
+        if let Some((label_name, label_pos)) = matched {
+            self.check_depth(pos)?;
+            let (_, _, parameters) = self
+                .code
+                .get_code()
+                .get_label_location(&label_name, label_pos)?;
+            let parameter_values = parameters
+                .first()
+                .map(|parameter| (parameter, value))
+                .into_iter()
+                .collect();
+            self.enter_label(&label_name, label_pos, parameter_values)?;
+        }
+
+        Ok(None)
     }
 
-    /// Calls the label passed in. In other words, it changes the instruction pointer.
-    /// In the future, this would be changed to include the number of parameters on the stack.
+    /// Reports an endmatch instruction that was reached on its own. The match instruction always moves past its endmatch instruction,
+    /// so this is only reached if the instruction is not part of a match instruction.
     ///
     /// # Arguments
     /// `pos` - The position where this instruction was called.
-    fn call(&mut self, pos: usize) -> Result<Option<Rc<Value>>, Error> {
-        let (arg_pos_1, arg1) = self.get_arg_unevaluated(1, pos)?;
-        match &arg1.kind {
-            ValueKind::Identifier(label_name) => {
-                let caller_pos = self.code.get_current_pos();
-                let (start, end, parameters) =
-                    self.code.get_label_location(label_name, arg_pos_1)?;
-
-                let mut parameter_values = vec![];
-                for i in 0..parameters.len() {
-                    let (pos, parameter_value) =
-                        self.get_arg(parameters.len(), arg_pos_1)?;
-                    if let Some(parameter_value) = parameter_value {
-                        parameter_values.push((parameters.get(i as usize).unwrap(), parameter_value));
-                    } else {
-                        return Err(Error::new(
-                            ErrorKind::ValueMismatch(
-                                ValueKind::Any.get_value_name(),
-                                ValueKind::Void.get_value_name(),
-                            ),
-                            pos,
-                        ));
-                    }
-                }
+    fn endmatch(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        Err(Error::new(ErrorKind::EndMatchWithoutMatch, pos))
+    }
 
-                self.code.set_label_location(label_name, arg_pos_1)?;
-                let store = self
-                    .call_stack
-                    .peek()
-                    .filter(|frame| {
-                        if let Some((cur_start, cur_end)) =
-                            self.code.get_label_start_end(&frame.name)
-                        {
-                            cur_start < start && end < cur_end
-                        } else {
-                            false
-                        }
-                    })
-                    .map(|frame| &frame.current_store);
+    /// Returns the names of the parameters that the label of the current frame declares, as an array of strings.
+    /// This allows generic labels, such as wrappers, to find out what they were called with.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn params(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let label_name = self
+            .call_stack
+            .peek()
+            .map(|frame| frame.name.clone())
+            .ok_or_else(|| Error::new(ErrorKind::NoActiveFrame, pos))?;
+        let (_, _, parameters) = self.code.get_code().get_label_location(&label_name, pos)?;
+        let names = parameters
+            .into_iter()
+            .map(|name| Arc::new(Value::new(pos, ValueKind::String(name))))
+            .collect();
+        Ok(Some(Arc::new(Value::new(pos, ValueKind::Array(names)))))
+    }
+
+    /// Calls the label of the current frame again, passing one argument for each of its parameters.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn recurse(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let label_name = self
+            .call_stack
+            .peek()
+            .map(|frame| frame.name.clone())
+            .ok_or_else(|| Error::new(ErrorKind::NoActiveFrame, pos))?;
+        self.check_depth(pos)?;
+        let (_, _, parameters) = self.code.get_code().get_label_location(&label_name, pos)?;
+        let parameter_values = self.get_parameter_values(&parameters, pos)?;
+        self.enter_label(&label_name, pos, parameter_values)?;
+        Ok(None)
+    }
+
+    /// Evaluates one argument for each of the parameters of a label, and pairs each parameter with its value.
+    ///
+    /// # Arguments
+    /// `parameters` - The names of the parameters of the label.
+    /// `pos` - The position where the label was named.
+    fn get_parameter_values<'a>(
+        &mut self,
+        parameters: &'a [String],
+        pos: usize,
+    ) -> Result<Vec<(&'a String, Arc<Value>)>, Error> {
+        let mut parameter_values = vec![];
+        for (idx, parameter) in parameters.iter().enumerate() {
+            let (pos, parameter_value) = self.get_arg(parameters.len() - idx, pos)?;
+            if let Some(parameter_value) = parameter_value {
+                parameter_values.push((parameter, parameter_value));
+            } else {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        ValueKind::Any.get_value_name(),
+                        ValueKind::Void.get_value_name(),
+                    ),
+                    pos,
+                ));
+            }
+        }
+
+        Ok(parameter_values)
+    }
+
+    /// Calls the label passed in the given number of times, one iteration after another.
+    /// If the label defines a parameter, it is set to the number of the iteration, starting from 0.
+    /// The variables of the label are kept between iterations, so that they can be used to accumulate a result.
+    /// If the count is not positive, the label is not called at all.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn times(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg(2, pos)?;
+        let count = match arg1.as_ref().map(|value| &value.kind) {
+            Some(ValueKind::Int(count)) => (*count).max(0) as u64,
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        ValueKind::Int(0).get_value_name(),
+                        kind.unwrap_or(&ValueKind::Void).get_value_name(),
+                    ),
+                    arg_pos_1,
+                ))
+            }
+        };
+
+        let (arg_pos_2, arg2) = self.get_arg_unevaluated(1, pos)?;
+        let label_name = match &arg2.kind {
+            ValueKind::Identifier(label_name) => label_name,
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        ValueKind::Label("".to_owned(), vec![]).get_value_name(),
+                        kind.get_value_name(),
+                    ),
+                    arg_pos_2,
+                ))
+            }
+        };
+
+        if count == 0 {
+            return Ok(None);
+        }
+
+        self.check_depth(pos)?;
+        let (_, _, parameters) = self
+            .code
+            .get_code()
+            .get_label_location(label_name, arg_pos_2)?;
+        let parameter = parameters.into_iter().next();
+        let parameter_values = parameter
+            .iter()
+            .map(|name| (name, Arc::new(Value::new(pos, ValueKind::Int(0)))))
+            .collect();
+        self.enter_label(label_name, arg_pos_2, parameter_values)?;
+
+        // The number of the iteration is not an argument, so with isolated stacks, the label starts with an empty stack, which is kept between iterations.
+        if self.is_isolated(label_name) {
+            self.operand_stack.0.clear();
+        }
+
+        if let Some(frame) = self.call_stack.peek_mut() {
+            frame.repeat = Some(Repeat {
+                iteration: 0,
+                count,
+                parameter,
+            });
+        }
+
+        Ok(None)
+    }
+
+    /// Starts the next iteration of the current label, if it was called by the times instruction and has iterations left.
+    /// This function returns whether the label was started again.
+    ///
+    /// # Arguments
+    /// `pos` - The position of the end of the label.
+    fn repeat_label(&mut self, pos: usize) -> Result<bool, Error> {
+        let frame = match self.call_stack.peek_mut() {
+            Some(frame) => frame,
+            None => return Ok(false),
+        };
+
+        let repeat = match &mut frame.repeat {
+            Some(repeat) if repeat.iteration + 1 < repeat.count => repeat,
+            _ => return Ok(false),
+        };
+
+        repeat.iteration += 1;
+        let mut written = None;
+        if let Some(parameter) = &repeat.parameter {
+            let iteration = Arc::new(Value::new(pos, ValueKind::Int(repeat.iteration as i64)));
+            let old = frame.current_store.borrow().get(parameter, pos).ok();
+            frame
+                .current_store
+                .borrow_mut()
+                .define(parameter, iteration.clone());
+            written = Some((parameter.clone(), old, iteration));
+        }
+
+        let label_name = frame.name.clone();
+        if let Some((parameter, old, iteration)) = written {
+            self.notify_write(&parameter, old, iteration, pos);
+        }
+
+        self.code.set_label_location(&label_name, pos)?;
+        Ok(true)
+    }
+
+    /// Checks that another frame can be pushed without exceeding the maximum call depth.
+    ///
+    /// # Arguments
+    /// `pos` - The position of the instruction that calls the label.
+    fn check_depth(&self, pos: usize) -> Result<(), Error> {
+        match self.limits.max_depth {
+            Some(max_depth) if self.call_stack.0.len() >= max_depth => {
+                Err(Error::new(ErrorKind::CallDepthExceeded(max_depth), pos))
+            }
+            _ => Ok(()),
+        }
+    }
 
-                let new_frame = Frame::new(caller_pos, label_name, store);
-                for (name, value) in parameter_values {
-                    new_frame.current_store.borrow_mut().define(name, value);
+    /// Pushes a new frame for the label, and moves the instruction pointer to the start of the label.
+    /// The frame returns to the current position once the label ends, so any arguments must already have been evaluated.
+    ///
+    /// # Arguments
+    /// `label_name` - The name of the label.
+    /// `label_pos` - The position where the label was named.
+    /// `parameter_values` - The values of the parameters of the label.
+    fn enter_label(
+        &mut self,
+        label_name: &str,
+        label_pos: usize,
+        parameter_values: Vec<(&String, Arc<Value>)>,
+    ) -> Result<(), Error> {
+        // The caller position is taken after the arguments, so that they are not evaluated again when the label ends.
+        let caller_pos = self.code.get_current_pos();
+        let (start, end) = self.code.set_label_location(label_name, label_pos)?;
+        let store = self
+            .call_stack
+            .peek()
+            .filter(|frame| {
+                if let Some((cur_start, cur_end)) =
+                    self.code.get_code().get_label_start_end(&frame.name)
+                {
+                    cur_start < start && end < cur_end
+                } else {
+                    false
                 }
-                
-                self.call_stack.push(new_frame);
+            })
+            .map(|frame| &frame.current_store);
 
-                Ok(None)
+        let mut new_frame = Frame::new(caller_pos, label_name, store, self.case);
+        new_frame.call_pos = Some(label_pos);
+        if self.is_isolated(label_name) {
+            let arguments = parameter_values
+                .iter()
+                .map(|(_, value)| value.clone())
+                .collect();
+            new_frame.caller_stack = Some(std::mem::replace(&mut self.operand_stack.0, arguments));
+        }
+
+        for (name, value) in &parameter_values {
+            new_frame
+                .current_store
+                .borrow_mut()
+                .define(name, value.clone());
+        }
+
+        self.call_stack.push(new_frame);
+        for (name, value) in parameter_values {
+            self.notify_write(name, None, value, label_pos);
+        }
+
+        self.metrics.add_call();
+        #[cfg(feature = "tracing")]
+        self.call_spans
+            .push(tracing::info_span!("call", label = %label_name));
+
+        Ok(())
+    }
+
+    /// This function returns whether every call to the label has a stack of its own,
+    /// either because the calling convention of the VM isolates every call, or because the label is annotated as pure.
+    ///
+    /// # Arguments
+    /// `label_name` - The name of the label.
+    fn is_isolated(&self, label_name: &str) -> bool {
+        self.calling_convention == CallingConvention::Isolated
+            || self
+                .code
+                .get_code()
+                .get_labels()
+                .get(label_name)
+                .is_some_and(|label| label.pure)
+    }
+
+    /// Restores the stack of the caller of a label that had a stack of its own, and pushes the values left on the stack of the label on to it, in the same order.
+    ///
+    /// # Arguments
+    /// `caller_stack` - The stack of the caller, which was set aside when the label was entered.
+    /// `pos` - The position where the label ended.
+    fn return_to_caller_stack(
+        &mut self,
+        caller_stack: Vec<Arc<Value>>,
+        pos: usize,
+    ) -> Result<(), Error> {
+        let results = std::mem::replace(&mut self.operand_stack.0, caller_stack);
+        if let Some(max_stack) = self.limits.max_stack {
+            if self.operand_stack.0.len() + results.len() > max_stack {
+                return Err(Error::new(ErrorKind::StackLimitExceeded(max_stack), pos));
             }
-            kind => Err(Error::new(
-                ErrorKind::ValueMismatch(
-                    ValueKind::Label("".to_owned(), vec![]).get_value_name(),
-                    kind.get_value_name(),
-                ),
-                arg_pos_1,
-            )),
+        }
+
+        self.operand_stack.0.extend(results);
+        Ok(())
+    }
+
+    /// Calls the label passed in if the top value on the stack is true.
+    /// Otherwise, the label and its arguments are skipped without being evaluated.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn callt(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        match self.operand_stack.peek() {
+            Some(value) if value.is_truthy() => self.call(pos),
+            None => Err(Error::new(ErrorKind::EmptyStack, pos)),
+            _ => self.skip_call(pos),
+        }
+    }
+
+    /// Calls the label passed in if the top value on the stack is false.
+    /// Otherwise, the label and its arguments are skipped without being evaluated.
+    ///
+    /// # Arguments
+    /// `pos` - The position where this instruction was called.
+    fn callf(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        match self.operand_stack.peek() {
+            Some(value) if !value.is_truthy() => self.call(pos),
+            None => Err(Error::new(ErrorKind::EmptyStack, pos)),
+            _ => self.skip_call(pos),
+        }
+    }
+
+    /// Skips the label of a conditional call, along with one argument for each of its parameters.
+    /// If the name is a native function, one argument is skipped for each argument that it takes.
+    ///
+    /// # Arguments
+    /// `pos` - The position where the instruction was called.
+    fn skip_call(&mut self, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+        let (arg_pos_1, arg1) = self.get_arg_unevaluated(1, pos)?;
+        let label_name = match &arg1.kind {
+            ValueKind::Identifier(label_name) => label_name,
+            kind => {
+                return Err(Error::new(
+                    ErrorKind::ValueMismatch(
+                        ValueKind::Label("".to_owned(), vec![]).get_value_name(),
+                        kind.get_value_name(),
+                    ),
+                    arg_pos_1,
+                ))
+            }
+        };
+
+        let code = self.code.get_code();
+        let arity = match self.natives.get_arity(label_name) {
+            Some(arity) => arity,
+            None => code.get_label_location(label_name, arg_pos_1)?.2.len(),
+        };
+        let end =
+            verifier::skip_arguments(code, &self.natives, self.code.get_current_pos(), arity, pos)?;
+        match self.code.jump(end as i64, pos) {
+            Some(error) => Err(error),
+            None => Ok(None),
         }
     }
 
@@ -815,11 +3937,31 @@ impl VM {
         &mut self,
         expected_args: usize,
         pos: usize,
-    ) -> Result<(usize, Option<Rc<Value>>), Error> {
+    ) -> Result<(usize, Option<Arc<Value>>), Error> {
         let arg = self
             .next()
             .ok_or_else(|| Error::new(ErrorKind::ExpectedArgs(expected_args), pos))?;
-        Ok((arg.pos, self.evaluate_value(arg)?))
+
+        // Arguments are evaluated recursively, so the nesting is limited to protect the stack of the host.
+        let arg_pos = arg.pos;
+        if self.nesting >= MAX_NESTING {
+            return Err(Error::new(ErrorKind::NestingTooDeep(MAX_NESTING), arg_pos));
+        }
+
+        // Variables were already checked when they were defined, so only new values are checked against the limits.
+        let is_new = !matches!(arg.kind, ValueKind::Identifier(_));
+        self.nesting += 1;
+        let result = self.evaluate_value(arg);
+        self.nesting -= 1;
+        let result = result?;
+        if let Some(value) = result
+            .as_ref()
+            .filter(|_| is_new && self.limits.limits_values())
+        {
+            self.limits.check_value(value, arg_pos)?;
+        }
+
+        Ok((arg_pos, result))
     }
 
     /// Gets the next argument.
@@ -832,7 +3974,7 @@ impl VM {
         &mut self,
         expected_args: usize,
         pos: usize,
-    ) -> Result<(usize, Rc<Value>), Error> {
+    ) -> Result<(usize, Arc<Value>), Error> {
         let arg = self
             .next()
             .ok_or_else(|| Error::new(ErrorKind::ExpectedArgs(expected_args), pos))?;
@@ -841,13 +3983,152 @@ impl VM {
 
     /// Gets the next value.
     /// This method needs to be abstracted away because Rust will complain with the message that self.code was mutabley borrowed more than once.
-    fn next(&mut self) -> Option<Rc<Value>> {
+    fn next(&mut self) -> Option<Arc<Value>> {
         self.code.next()
     }
 
     /// Checks if there are any more values left.
     /// This method needs to be abstracted away because Rust will complain with the message that self.code was mutabley borrowed more than once.
-    fn is_finished(&self) -> bool {
+    pub fn is_finished(&self) -> bool {
         self.code.is_finished() || self.call_stack.is_empty()
     }
 }
+
+impl fmt::Display for VM {
+    /// Writes the state of the machine for people: the instruction pointer, the current label,
+    /// the operand stack from the top down, and each frame, from the innermost, with its variables.
+    /// Long lists and long values are truncated, so that the state of a large program stays readable.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.peek_next().filter(|_| !self.is_finished()) {
+            Some(next) => writeln!(
+                f,
+                "Instruction Pointer: {} (Next: {} At Position {})",
+                self.code.get_current_pos(),
+                next,
+                next.pos
+            )?,
+            None => writeln!(f, "Instruction Pointer: None (Finished)")?,
+        }
+
+        match self.call_stack.peek() {
+            Some(frame) => writeln!(f, "Current Label: @{}", frame.name)?,
+            None => writeln!(f, "Current Label: None")?,
+        }
+
+        let stack = &self.operand_stack.0;
+        writeln!(
+            f,
+            "Operand Stack ({}, Top First):",
+            count(stack.len(), "Value")
+        )?;
+        for (depth, value) in stack.iter().rev().take(MAX_SHOWN).enumerate() {
+            writeln!(f, "    {}: {}", depth, describe(value))?;
+        }
+
+        write_remaining(f, stack.len(), "    ")?;
+
+        let frames = &self.call_stack.0;
+        writeln!(
+            f,
+            "Frames ({}, Innermost First):",
+            count(frames.len(), "Frame")
+        )?;
+        for frame in frames.iter().rev().take(MAX_SHOWN) {
+            if frame.get_caller_position() == 0 {
+                writeln!(f, "    @{}", frame.name)?;
+            } else {
+                writeln!(
+                    f,
+                    "    @{} (Returns To {})",
+                    frame.name,
+                    frame.get_caller_position()
+                )?;
+            }
+
+            let variables = frame.get_variables();
+            for (name, value) in variables.iter().take(MAX_SHOWN) {
+                writeln!(f, "        {} = {}", name, describe(value))?;
+            }
+
+            write_remaining(f, variables.len(), "        ")?;
+        }
+
+        write_remaining(f, frames.len(), "    ")
+    }
+}
+
+/// The largest number of values, variables, or frames that are shown in each list of the machine state.
+const MAX_SHOWN: usize = 16;
+
+/// The largest number of characters that are shown for a single value of the machine state.
+const MAX_VALUE_WIDTH: usize = 60;
+
+/// This function describes a value of the machine state, with its type, truncating it if it is too long.
+///
+/// # Arguments
+/// `value` - The value to describe.
+fn describe(value: &Value) -> String {
+    let text = format!("{:?}", value);
+    let text = if text.chars().count() > MAX_VALUE_WIDTH {
+        format!(
+            "{}... ({} Characters)",
+            text.chars().take(MAX_VALUE_WIDTH).collect::<String>(),
+            text.chars().count()
+        )
+    } else {
+        text
+    };
+
+    format!("{} {}", value.kind.get_value_name(), text)
+}
+
+/// This function writes the number of things with the correct plural, such as "1 Value" or "2 Values".
+///
+/// # Arguments
+/// `amount` - The number of things.
+/// `name` - The singular name of the things.
+fn count(amount: usize, name: &str) -> String {
+    if amount == 1 {
+        format!("{} {}", amount, name)
+    } else {
+        format!("{} {}s", amount, name)
+    }
+}
+
+/// Writes how many entries of a list were not shown, if any.
+///
+/// # Arguments
+/// `f` - The formatter to write to.
+/// `len` - The length of the list.
+/// `indent` - The indentation of the entries of the list.
+fn write_remaining(f: &mut fmt::Formatter<'_>, len: usize, indent: &str) -> fmt::Result {
+    if len > MAX_SHOWN {
+        writeln!(f, "{}... And {} More", indent, len - MAX_SHOWN)?;
+    }
+
+    Ok(())
+}
+
+/// Generates the method of the VM that calls the handler of each instruction from the table of instructions.
+macro_rules! dispatch {
+    ($($opcode:ident {
+        name: $name:literal,
+        handler: $handler:ident,
+        $($rest:tt)*
+    },)*) => {
+        impl VM {
+            /// Evaluates the instruction with the given opcode, along with all of its arguments.
+            ///
+            /// # Arguments
+            /// `opcode` - The opcode of the instruction.
+            /// `pos` - The position where the instruction was called.
+            fn evaluate_instruction(&mut self, opcode: Opcode, pos: usize) -> Result<Option<Arc<Value>>, Error> {
+                match opcode {
+                    $(Opcode::$opcode => self.$handler(pos),)*
+                }
+            }
+        }
+    };
+}
+
+instructions!(dispatch);
@@ -0,0 +1,73 @@
+use dark_vm::{
+    builder::VMBuilder, errors::error_kind::ErrorKind, lexer::Lexer, utils::aliases::Aliases,
+};
+
+/// Runs the program with the given aliases, and returns everything that it printed, or the kind of the first error.
+fn output(contents: &str, aliases: Aliases) -> Result<String, ErrorKind> {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    let outcome = VMBuilder::default()
+        .aliases(aliases)
+        .capture_output()
+        .build(tokens)
+        .map_err(|error| error.get_kind().clone())?
+        .run();
+    let output = outcome.output.clone().unwrap_or_default();
+    outcome
+        .into_result()
+        .map(|_| output)
+        .map_err(|error| error.get_kind().clone())
+}
+
+#[test]
+fn aliases_stand_for_their_instructions_before_and_after_they_are_defined() {
+    assert_eq!(
+        output(
+            "@main say 'Hello' alias say printn alias shout say shout 'World' end",
+            Aliases::default()
+        ),
+        Ok("Hello\nWorld\n".to_owned())
+    );
+}
+
+#[test]
+fn aliases_may_come_from_a_file() {
+    let aliases = Aliases::parse("alias say printn\nalias shout say")
+        .ok()
+        .unwrap();
+    assert_eq!(
+        output("@main say 'Hello' shout 'World' end", aliases),
+        Ok("Hello\nWorld\n".to_owned())
+    );
+}
+
+#[test]
+fn invalid_aliases_are_rejected() {
+    assert_eq!(
+        output("@main alias printn print end", Aliases::default()),
+        Err(ErrorKind::AliasIsInstruction("printn".to_owned()))
+    );
+    assert_eq!(
+        output(
+            "@main alias say printn alias say print end",
+            Aliases::default()
+        ),
+        Err(ErrorKind::DuplicateAlias("say".to_owned()))
+    );
+
+    // An alias from the file can not be defined again by the program.
+    let aliases = Aliases::parse("alias say printn").ok().unwrap();
+    assert_eq!(
+        output("@main alias say print end", aliases),
+        Err(ErrorKind::DuplicateAlias("say".to_owned()))
+    );
+}
+
+#[test]
+fn an_aliases_file_holds_only_aliases() {
+    assert_eq!(
+        Aliases::parse("alias say printn\npush 1")
+            .err()
+            .map(|error| error.get_kind().clone()),
+        Some(ErrorKind::ExpectedAlias)
+    );
+}
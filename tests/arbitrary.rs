@@ -0,0 +1,85 @@
+#![cfg(feature = "arbitrary")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use dark_vm::{builder::VMBuilder, fuzzing::Program, lexer::Lexer, tokens::token::Token};
+use std::{collections::VecDeque, io, panic};
+
+/// The number of inputs generated by each test.
+const CASES: u64 = 2000;
+
+/// Generates deterministic bytes for the given case, so that failures are reproducible.
+fn bytes(case: u64) -> Vec<u8> {
+    let mut state = case.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..1024)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 56) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn programs_round_trip_through_the_lexer() {
+    for case in 0..CASES {
+        let bytes = bytes(case);
+        let program = Program::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        let tokens = Lexer::default()
+            .lex(program.get_source())
+            .unwrap_or_else(|error| panic!("{}", error.prettify(program.get_source())));
+        assert_eq!(
+            Vec::from(tokens),
+            program.get_tokens(),
+            "{}",
+            program.get_source()
+        );
+    }
+}
+
+#[test]
+fn programs_do_not_panic_and_always_end_under_fuel() {
+    let mut finished = 0;
+    for case in 0..CASES {
+        let bytes = bytes(case);
+        let program = Program::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        let result = panic::catch_unwind(|| {
+            let mut vm = VMBuilder::default()
+                .fuel(10_000)
+                .stdout(io::sink())
+                .stderr(io::sink())
+                .stdin(io::empty())
+                .build(program.to_tokens())?;
+            vm.run().into_result()
+        });
+
+        // Programs that loop forever are stopped by the fuel, so every program ends with a result.
+        match result {
+            Ok(Ok(_)) => finished += 1,
+            Ok(Err(_)) => {}
+            Err(_) => panic!("The program panicked:\n{}", program.get_source()),
+        }
+    }
+
+    // Some of the programs should get past the verifier and run to the end.
+    assert!(finished > 0);
+}
+
+#[test]
+fn arbitrary_tokens_do_not_panic() {
+    for case in 0..CASES {
+        let bytes = bytes(case);
+        let tokens = Vec::<Token>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        let tokens: VecDeque<Token> = tokens.into_iter().collect();
+        let result = panic::catch_unwind(|| {
+            let mut vm = VMBuilder::default()
+                .fuel(10_000)
+                .stdout(io::sink())
+                .stderr(io::sink())
+                .stdin(io::empty())
+                .build(tokens)?;
+            vm.run().into_result()
+        });
+        assert!(result.is_ok(), "The tokens panicked: {:?}", bytes);
+    }
+}
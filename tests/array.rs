@@ -0,0 +1,54 @@
+use dark_vm::{errors::error_kind::ErrorKind, values::value_kinds::ValueKind};
+
+mod common;
+
+use common::run;
+
+#[test]
+fn elements_can_be_added_read_and_replaced() {
+    assert_eq!(
+        run("@main set xs apush apush arr 10 20 set xs aset xs 0 5 push aget xs 0 push aget xs 1 push alen xs end"),
+        Ok(vec![ValueKind::Int(5), ValueKind::Int(20), ValueKind::Int(2)])
+    );
+}
+
+#[test]
+fn apop_pushes_the_rest_of_the_array_and_then_the_last_element() {
+    let stack =
+        run("@main set xs apush apush arr 1 2 apop xs set last pop set xs pop push last push alen xs end");
+    assert_eq!(stack, Ok(vec![ValueKind::Int(2), ValueKind::Int(1)]));
+}
+
+#[test]
+fn invalid_indexes_and_empty_arrays_are_errors() {
+    assert_eq!(
+        run("@main push aget apush arr 1 1 end"),
+        Err(ErrorKind::OutOfBounds(0, 1))
+    );
+    assert_eq!(
+        run("@main push aget apush arr 1 -1 end"),
+        Err(ErrorKind::OutOfBounds(0, 1))
+    );
+    assert_eq!(run("@main apop arr end"), Err(ErrorKind::EmptyArray));
+}
+
+#[test]
+fn arrays_and_maps_are_not_nested_too_deeply_without_a_limit() {
+    // Before the default cap, dropping the nested arrays overflowed the stack of the host.
+    assert_eq!(
+        run("@main set i 0 set xs arr while push lt i 200000 do set xs apush arr xs incvar i 1 endwhile end"),
+        Err(ErrorKind::ValueDepthExceeded(256))
+    );
+    assert_eq!(
+        run("@main set i 0 set xs apush arr 1 while push lt i 300 do set xs aset xs 0 xs incvar i 1 endwhile end"),
+        Err(ErrorKind::ValueDepthExceeded(256))
+    );
+    assert_eq!(
+        run("@main set i 0 set m mnew while push lt i 300 do set m mset mnew 'm' m incvar i 1 endwhile end"),
+        Err(ErrorKind::ValueDepthExceeded(256))
+    );
+    assert_eq!(
+        run("@main set i 0 set xs arr while push lt i 255 do set xs apush arr xs incvar i 1 endwhile push alen xs end"),
+        Ok(vec![ValueKind::Int(1)])
+    );
+}
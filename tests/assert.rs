@@ -0,0 +1,94 @@
+use dark_vm::ErrorKind;
+
+mod common;
+
+use common::run_status;
+
+/// Returns the differences that asserteq reports for the two values.
+fn differences(expected: &str, actual: &str) -> Vec<String> {
+    let contents = format!("@main asserteq {} {} end", expected, actual);
+    match run_status(&contents) {
+        Err(ErrorKind::ValuesNotEqual(differences)) => differences,
+        result => panic!("expected the values to differ, found {:?}", result),
+    }
+}
+
+#[test]
+fn passing_assertions_do_nothing() {
+    assert_eq!(
+        run_status(
+            "@main assert true assert 1 asserteq 1 1 asserteq 0.5 0.5 asserteq null null end"
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        run_status(r#"@main asserteq jsonparse '{"a": [1, 2]}' jsonparse '{"a": [1, 2]}' end"#),
+        Ok(())
+    );
+}
+
+#[test]
+fn falsy_values_fail_the_assertion() {
+    assert_eq!(
+        run_status("@main assert false end"),
+        Err(ErrorKind::AssertionFailed)
+    );
+    assert_eq!(
+        run_status("@main assert null end"),
+        Err(ErrorKind::AssertionFailed)
+    );
+}
+
+#[test]
+fn scalars_are_reported_with_their_types() {
+    assert_eq!(differences("1", "2"), vec!["value: expected 1, found 2"]);
+    assert_eq!(
+        differences("1", "'1'"),
+        vec![r#"value: expected Int 1, found String "1""#]
+    );
+}
+
+#[test]
+fn strings_are_reported_with_the_first_difference() {
+    assert_eq!(
+        differences("'hello'", "'help'"),
+        vec![r#"value: expected "hello", found "help", which first differ at character 3"#]
+    );
+}
+
+#[test]
+fn arrays_are_compared_element_by_element() {
+    assert_eq!(
+        differences("jsonparse '[1, 2, 3]'", "jsonparse '[1, 5]'"),
+        vec![
+            "value: expected 3 elements, found 2",
+            "[1]: expected 2, found 5",
+            "[2]: missing 3",
+        ]
+    );
+}
+
+#[test]
+fn maps_are_compared_key_by_key() {
+    assert_eq!(
+        differences(
+            r#"jsonparse '{"a": {"b": [true]}, "c": 1}'"#,
+            r#"jsonparse '{"a": {"b": [false]}, "d": 1}'"#
+        ),
+        vec![
+            r#"["a"]["b"][0]: expected true, found false"#,
+            r#"["c"]: missing 1"#,
+            r#"["d"]: unexpected 1"#,
+        ]
+    );
+}
+
+#[test]
+fn only_the_first_differences_are_listed() {
+    let differences = differences(
+        "jsonparse '[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]'",
+        "jsonparse '[1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]'",
+    );
+    assert_eq!(differences.len(), 17);
+    assert_eq!(differences[16], "... and 4 more differences");
+}
@@ -0,0 +1,40 @@
+use dark_vm::{ErrorKind, Lexer, VMBuilder, ValueKind};
+
+/// Runs the program with the stack asserted to be empty, and returns the kind of its result or of its error.
+fn run(contents: &str) -> Result<Option<ValueKind>, ErrorKind> {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    let mut vm = VMBuilder::default()
+        .assert_stack_empty()
+        .build(tokens)
+        .map_err(|error| error.get_kind().clone())?;
+    vm.run()
+        .into_result()
+        .map(|result| result.map(|value| value.kind.clone()))
+        .map_err(|error| error.get_kind().clone())
+}
+
+#[test]
+fn values_left_on_the_stack_are_an_error() {
+    assert_eq!(
+        run("@main push 1 push 2 pop end"),
+        Err(ErrorKind::StackNotEmpty(1))
+    );
+    assert_eq!(
+        run("@main call leak end @leak push 1 push 2 end"),
+        Err(ErrorKind::StackNotEmpty(2))
+    );
+}
+
+#[test]
+fn an_empty_stack_is_not_an_error() {
+    assert_eq!(run("@main push 1 pop end"), Ok(None));
+    assert_eq!(run("@main push 1 push 2 set sum add end"), Ok(None));
+}
+
+#[test]
+fn the_stack_is_not_checked_by_default() {
+    let tokens = Lexer::default().lex("@main push 1 end").ok().unwrap();
+    let mut vm = VMBuilder::default().build(tokens).ok().unwrap();
+    assert!(vm.run().is_success());
+    assert_eq!(vm.operand_stack.0.len(), 1);
+}
@@ -0,0 +1,84 @@
+use dark_vm::{errors::error_kind::ErrorKind, values::value_kinds::ValueKind};
+
+mod common;
+
+use common::{run, run_status};
+
+#[test]
+fn band_bor_and_bxor_combine_the_bits() {
+    assert_eq!(
+        run("@main push 10 push 12 push band end"),
+        Ok(vec![ValueKind::Int(8)])
+    );
+    assert_eq!(
+        run("@main push 10 push 12 push bor end"),
+        Ok(vec![ValueKind::Int(14)])
+    );
+    assert_eq!(
+        run("@main push 10 push 12 push bxor end"),
+        Ok(vec![ValueKind::Int(6)])
+    );
+}
+
+#[test]
+fn bnot_flips_every_bit() {
+    assert_eq!(
+        run("@main push 0 push bnot end"),
+        Ok(vec![ValueKind::Int(-1)])
+    );
+    assert_eq!(
+        run("@main push 5 push bnot end"),
+        Ok(vec![ValueKind::Int(-6)])
+    );
+}
+
+#[test]
+fn shifts_move_the_first_int_by_the_second() {
+    assert_eq!(
+        run("@main push 4 push 3 push shl end"),
+        Ok(vec![ValueKind::Int(48)])
+    );
+    assert_eq!(
+        run("@main push 1 push -8 push shr end"),
+        Ok(vec![ValueKind::Int(-4)])
+    );
+    assert_eq!(
+        run("@main push 63 push 1 push shl end"),
+        Ok(vec![ValueKind::Int(i64::MIN)])
+    );
+}
+
+#[test]
+fn shifting_by_an_invalid_amount_is_an_error() {
+    for amount in &[-1, 64] {
+        for instruction in &["shl", "shr"] {
+            let program = format!("@main push {} push 1 {} end", amount, instruction);
+            assert_eq!(
+                run_status(&program),
+                Err(ErrorKind::InvalidShift(*amount)),
+                "{}",
+                program
+            );
+        }
+    }
+}
+
+#[test]
+fn bitwise_instructions_only_accept_ints() {
+    for instruction in &["band", "bor", "bxor", "shl", "shr"] {
+        let program = format!("@main push 1.0 push 3 {} end", instruction);
+        assert!(
+            matches!(
+                run_status(&program),
+                Err(ErrorKind::UnsupportedOperation(..))
+            ),
+            "{}",
+            program
+        );
+    }
+
+    assert!(matches!(
+        run_status("@main push true bnot end"),
+        Err(ErrorKind::UnsupportedOperation(..))
+    ));
+}
@@ -0,0 +1,49 @@
+use dark_vm::ErrorKind;
+
+/// Runs the program, and returns everything that it printed, or the kind of the first error.
+fn output(source: &str) -> Result<String, ErrorKind> {
+    dark_vm::run_captured(source)
+        .map(|(_, output)| output)
+        .map_err(|error| error.get_kind().clone())
+}
+
+#[test]
+fn variables_of_a_block_are_discarded_when_it_is_left() {
+    assert_eq!(
+        output("@main set total 10 begin set temporary 5 printn total printn temporary endblock printn total end"),
+        Ok("10\n5\n10\n".to_owned())
+    );
+    assert!(matches!(
+        output("@main begin set temporary 5 endblock printn temporary end"),
+        Err(ErrorKind::UndefinedVariable(..))
+    ));
+}
+
+#[test]
+fn setting_an_outer_variable_defines_a_new_one_in_the_block() {
+    assert_eq!(
+        output("@main set x 1 begin set x 2 printn x endblock printn x end"),
+        Ok("2\n1\n".to_owned())
+    );
+}
+
+#[test]
+fn blocks_can_be_nested() {
+    assert_eq!(
+        output("@main begin set a 1 begin set b 2 printn a endblock printn a endblock printn 'done' end"),
+        Ok("1\n1\ndone\n".to_owned())
+    );
+}
+
+#[test]
+fn endblock_without_begin_is_an_error() {
+    assert_eq!(
+        output("@main endblock end"),
+        Err(ErrorKind::EndBlockWithoutBegin)
+    );
+    // Blocks belong to the frame they were entered in.
+    assert_eq!(
+        output("@main begin call inner endblock end @inner endblock end"),
+        Err(ErrorKind::EndBlockWithoutBegin)
+    );
+}
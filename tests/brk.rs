@@ -0,0 +1,43 @@
+use dark_vm::{builder::VMBuilder, lexer::Lexer, values::value_kinds::ValueKind, vm::StepResult};
+
+mod common;
+
+/// The program that these tests step through. It stops at the brk instruction with one value on the stack.
+const PROGRAM: &str = "@main push 1 brk push 2 end";
+
+/// Steps the program until it finishes, and returns the kinds of the values on the operand stack every time that it was suspended.
+fn suspensions(builder: VMBuilder) -> Vec<Vec<ValueKind>> {
+    let tokens = Lexer::default().lex(PROGRAM).ok().unwrap();
+    let mut vm = builder.build(tokens).ok().unwrap();
+    let mut suspensions = vec![];
+    loop {
+        match vm.step().ok().unwrap() {
+            StepResult::Running => {}
+            StepResult::Suspended => suspensions.push(common::stack(&vm)),
+            StepResult::Finished(_) => return suspensions,
+        }
+    }
+}
+
+#[test]
+fn brk_suspends_before_the_next_instruction() {
+    assert_eq!(
+        suspensions(VMBuilder::default().suspend_on_break()),
+        vec![vec![ValueKind::Int(1)]]
+    );
+}
+
+#[test]
+fn brk_does_nothing_without_a_host() {
+    assert!(suspensions(VMBuilder::default()).is_empty());
+
+    // Running the VM never hands control to the host, so the run continues past the brk instruction.
+    let tokens = Lexer::default().lex(PROGRAM).ok().unwrap();
+    let mut vm = VMBuilder::default()
+        .suspend_on_break()
+        .build(tokens)
+        .ok()
+        .unwrap();
+    assert!(vm.run().is_success());
+    assert_eq!(vm.operand_stack.0.len(), 2);
+}
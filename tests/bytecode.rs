@@ -0,0 +1,59 @@
+use dark_vm::{bytecode, Code, ErrorKind, Lexer, VMBuilder, ValueKind};
+use std::sync::Arc;
+
+const SOURCE: &str =
+    "@main set xs apush arr \"a\" call double 21 push xs end @double #x push x push 2 push mul end";
+
+/// Compiles the source into bytecode, with the source included.
+fn compile() -> (Code, Vec<u8>) {
+    let code = Code::new(Lexer::default().lex(SOURCE).ok().unwrap())
+        .ok()
+        .unwrap();
+    let bytes = bytecode::write(&code, Some(SOURCE));
+    (code, bytes)
+}
+
+#[test]
+fn compiled_programs_are_read_back_and_run() {
+    let (code, bytes) = compile();
+    let compiled = bytecode::read(&bytes).ok().unwrap();
+    assert_eq!(compiled.code.get_values(), code.get_values());
+    assert_eq!(compiled.source.as_deref(), Some(SOURCE));
+
+    let mut vm = VMBuilder::default()
+        .build_shared(Arc::new(compiled.code))
+        .ok()
+        .unwrap();
+    vm.run().into_result().ok().unwrap();
+    assert_eq!(vm.operand_stack.0.len(), 2);
+    assert_eq!(vm.operand_stack.0[0].kind, ValueKind::Int(42));
+}
+
+#[test]
+fn bytecode_of_another_version_is_rejected() {
+    let (_, mut bytes) = compile();
+    let version = bytecode::VERSION + 1;
+    bytes[bytecode::MAGIC.len()..bytecode::MAGIC.len() + 2].copy_from_slice(&version.to_le_bytes());
+    assert_eq!(
+        bytecode::read(&bytes)
+            .err()
+            .map(|error| error.get_kind().clone()),
+        Some(ErrorKind::UnsupportedBytecodeVersion(version))
+    );
+}
+
+#[test]
+fn truncated_bytecode_is_rejected() {
+    let (_, bytes) = compile();
+    for len in 0..bytes.len() {
+        match bytecode::read(&bytes[..len]) {
+            Err(error) => assert!(
+                matches!(error.get_kind(), ErrorKind::InvalidBytecode(_)),
+                "{} bytes: {:?}",
+                len,
+                error.get_kind()
+            ),
+            Ok(_) => panic!("{} bytes were read as a program", len),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use dark_vm::ErrorKind;
+
+/// Runs the program, and returns everything that it printed, or the kind of the first error.
+fn output(source: &str) -> Result<String, ErrorKind> {
+    dark_vm::run_captured(source)
+        .map(|(_, output)| output)
+        .map_err(|error| error.get_kind().clone())
+}
+
+#[test]
+fn callt_and_callf_call_the_label_by_the_top_of_the_stack() {
+    let greet = "@greet #name printn name end";
+    assert_eq!(
+        output(&format!(
+            "@main push true callt greet 'yes' callf greet 'no' end {}",
+            greet
+        )),
+        Ok("yes\n".to_owned())
+    );
+    assert_eq!(
+        output(&format!(
+            "@main push false callt greet 'yes' callf greet 'no' end {}",
+            greet
+        )),
+        Ok("no\n".to_owned())
+    );
+}
+
+#[test]
+fn the_condition_is_not_popped() {
+    assert_eq!(
+        output("@main push true callt noop printn pop end @noop end"),
+        Ok("true\n".to_owned())
+    );
+}
+
+#[test]
+fn the_arguments_of_a_skipped_call_are_not_evaluated() {
+    // Reading a file is denied, so evaluating the argument would stop the program.
+    assert_eq!(
+        output("@main push false callt greet readfile 'a.txt' printn 'done' end @greet #name printn name end"),
+        Ok("done\n".to_owned())
+    );
+}
+
+#[test]
+fn recurse_calls_the_label_that_is_running() {
+    assert_eq!(
+        output("@main call countdown 3 end @countdown #n printn n push gt n 0 if push 1 push n set m sub recurse m endif end"),
+        Ok("3\n2\n1\n0\n".to_owned())
+    );
+}
+
+#[test]
+fn params_returns_the_names_of_the_parameters() {
+    assert_eq!(
+        output("@main printn jsonstringify params call greet 1 2 end @greet #name #age printn jsonstringify params end"),
+        Ok("[]\n[\"name\",\"age\"]\n".to_owned())
+    );
+}
@@ -0,0 +1,58 @@
+use dark_vm::{
+    call_graph::{CallGraph, EdgeKind},
+    code::Code,
+    lexer::Lexer,
+};
+
+/// Builds the call graph of the program.
+fn graph(contents: &str) -> CallGraph {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    CallGraph::new(&Code::new(tokens).ok().unwrap())
+}
+
+#[test]
+fn calls_are_found_in_every_form() {
+    let graph = graph(
+        "@main call greet push true callt check 1 push 1 match 1 shout endmatch end @greet recurse end @check #n end @shout end",
+    );
+    assert_eq!(graph.get_labels(), ["main", "greet", "check", "shout"]);
+    assert_eq!(graph.get_callees("main"), vec!["check", "greet", "shout"]);
+    assert_eq!(graph.get_callees("greet"), vec!["greet"]);
+    assert_eq!(graph.get_callers("greet"), vec!["greet", "main"]);
+    assert!(graph
+        .get_edges()
+        .iter()
+        .all(|edge| edge.kind == EdgeKind::Call));
+}
+
+#[test]
+fn jumps_into_other_labels_are_found() {
+    let graph = graph("@main jmp 5 end @target push 1 end");
+    assert_eq!(graph.get_edges().len(), 1);
+    let edge = &graph.get_edges()[0];
+    assert_eq!(
+        (edge.caller.as_str(), edge.callee.as_str(), edge.kind),
+        ("main", "target", EdgeKind::Jump)
+    );
+}
+
+#[test]
+fn labels_that_main_never_reaches_are_unreachable() {
+    let graph = graph("@main call used end @used end @dead call used call other end @other end");
+    assert_eq!(graph.get_unreachable(), vec!["dead", "other"]);
+}
+
+#[test]
+fn the_graph_is_written_in_dot() {
+    let graph = graph("@main call greet call greet jmp 9 end @greet end");
+    assert_eq!(
+        graph.to_dot(),
+        "digraph calls {
+    \"main\";
+    \"greet\";
+    \"main\" -> \"greet\" [style=solid];
+    \"main\" -> \"greet\" [style=dashed];
+}
+"
+    );
+}
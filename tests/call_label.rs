@@ -0,0 +1,78 @@
+use dark_vm::{
+    errors::error_kind::ErrorKind,
+    lexer::Lexer,
+    values::{value::Value, value_kinds::ValueKind},
+    vm::VM,
+};
+use std::sync::Arc;
+
+/// Constructs a VM for the program. The program must be valid.
+fn load(contents: &str) -> VM {
+    VM::new(Lexer::default().lex(contents).ok().unwrap())
+        .ok()
+        .unwrap()
+}
+
+/// Calls the label with the given arguments, and returns the kind of its result or of its error.
+fn call(
+    vm: &mut VM,
+    label_name: &str,
+    arguments: Vec<ValueKind>,
+) -> Result<Option<ValueKind>, ErrorKind> {
+    let arguments = arguments
+        .into_iter()
+        .map(|kind| Arc::new(Value::new(0, kind)))
+        .collect();
+    vm.call_label(label_name, arguments)
+        .map(|result| result.map(|value| value.kind.clone()))
+        .map_err(|error| error.get_kind().clone())
+}
+
+#[test]
+fn labels_can_be_called_before_the_program_runs() {
+    let mut vm = load("@main printn \"unused\" end @double #x push x push x push add end");
+    assert_eq!(
+        call(&mut vm, "double", vec![ValueKind::Int(21)]),
+        Ok(Some(ValueKind::Int(42)))
+    );
+    assert_eq!(
+        call(&mut vm, "double", vec![ValueKind::Float(1.5)]),
+        Ok(Some(ValueKind::Float(3.0)))
+    );
+    assert!(vm.operand_stack.0.is_empty());
+}
+
+#[test]
+fn nested_labels_can_use_the_globals_after_the_program_finishes() {
+    let mut vm = load("@main set base 100 @offset #x push base push x push add end end");
+    vm.run().into_result().ok().unwrap();
+    assert_eq!(
+        call(&mut vm, "offset", vec![ValueKind::Int(5)]),
+        Ok(Some(ValueKind::Int(105)))
+    );
+    assert_eq!(
+        vm.get_globals()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        vec!["base".to_owned()]
+    );
+}
+
+#[test]
+fn calls_are_checked_and_unwound() {
+    let mut vm = load("@main end @fail #x push aget arr x end");
+    assert_eq!(
+        call(&mut vm, "missing", vec![]),
+        Err(ErrorKind::UndefinedLabel)
+    );
+    assert_eq!(
+        call(&mut vm, "fail", vec![]),
+        Err(ErrorKind::WrongArgumentCount(1, 0))
+    );
+    assert_eq!(
+        call(&mut vm, "fail", vec![ValueKind::Int(1)]),
+        Err(ErrorKind::OutOfBounds(0, 0))
+    );
+    assert_eq!(vm.get_frames().len(), 1);
+}
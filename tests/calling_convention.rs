@@ -0,0 +1,70 @@
+use dark_vm::{
+    builder::VMBuilder, errors::error_kind::ErrorKind,
+    utils::calling_convention::CallingConvention, values::value_kinds::ValueKind,
+};
+
+mod common;
+
+/// Runs the program with the given calling convention, and returns the kinds of the values left on the operand stack, from the bottom up, or the kind of the first error.
+fn run(contents: &str, calling_convention: CallingConvention) -> Result<Vec<ValueKind>, ErrorKind> {
+    common::run_with(
+        VMBuilder::default().calling_convention(calling_convention),
+        contents,
+    )
+}
+
+#[test]
+fn an_isolated_call_starts_with_its_arguments_and_returns_what_is_left() {
+    let program = "@main push 'kept' call double 21 end @double #x pop push x push x push add end";
+    assert_eq!(
+        run(program, CallingConvention::Isolated),
+        Ok(vec![
+            ValueKind::String("kept".to_owned()),
+            ValueKind::Int(42)
+        ])
+    );
+
+    // With a shared stack, the label pops the value that main pushed instead of its argument.
+    assert_eq!(
+        run(program, CallingConvention::Shared),
+        Ok(vec![ValueKind::Int(42)])
+    );
+}
+
+#[test]
+fn the_arguments_are_pushed_in_order() {
+    assert_eq!(
+        run(
+            "@main call pair 1 2 end @pair #a #b end",
+            CallingConvention::Isolated
+        ),
+        Ok(vec![ValueKind::Int(1), ValueKind::Int(2)])
+    );
+}
+
+#[test]
+fn an_isolated_call_can_not_pop_the_stack_of_its_caller() {
+    assert_eq!(
+        run(
+            "@main push 1 call take end @take pop end",
+            CallingConvention::Isolated
+        ),
+        Err(ErrorKind::EmptyStack)
+    );
+}
+
+#[test]
+fn the_labels_called_by_times_keep_their_stack_between_iterations() {
+    assert_eq!(
+        run(
+            "@main push 'kept' times 3 count end @count #i push i end",
+            CallingConvention::Isolated
+        ),
+        Ok(vec![
+            ValueKind::String("kept".to_owned()),
+            ValueKind::Int(0),
+            ValueKind::Int(1),
+            ValueKind::Int(2)
+        ])
+    );
+}
@@ -0,0 +1,89 @@
+use dark_vm::{Capabilities, ErrorKind, Lexer, VMBuilder};
+
+/// Runs the program with the VM built by the given builder, and returns the kind of the first error, if there is one.
+fn run(contents: &str, builder: VMBuilder) -> Result<(), ErrorKind> {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    let mut vm = builder
+        .build(tokens)
+        .map_err(|error| error.get_kind().clone())?;
+    vm.run()
+        .into_result()
+        .map(|_| ())
+        .map_err(|error| error.get_kind().clone())
+}
+
+/// The error of an instruction that requires a capability which was not granted.
+fn denied(instruction: &str, capability: &str) -> Result<(), ErrorKind> {
+    Err(ErrorKind::CapabilityDenied(
+        instruction.to_owned(),
+        capability.to_owned(),
+    ))
+}
+
+#[test]
+fn every_capability_is_denied_by_default() {
+    let cases = [
+        ("@main push readfile 'a.txt' end", "readfile", "File System"),
+        (
+            "@main writefile 'a.txt' 'a' end",
+            "writefile",
+            "File System",
+        ),
+        ("@main push env 'HOME' end", "env", "Environment"),
+        ("@main push exec 'true' end", "exec", "Process Execution"),
+        (
+            "@main push fetch 'http://localhost' end",
+            "fetch",
+            "Network",
+        ),
+        ("@main push now end", "now", "Clock"),
+    ];
+    for (contents, instruction, capability) in &cases {
+        assert_eq!(
+            run(contents, VMBuilder::default()),
+            denied(instruction, capability),
+            "{}",
+            contents
+        );
+    }
+}
+
+#[test]
+fn capabilities_are_granted_one_at_a_time() {
+    let builder = || {
+        VMBuilder::default().capabilities(Capabilities {
+            env: true,
+            ..Capabilities::default()
+        })
+    };
+    assert_eq!(run("@main push env 'HOME' end", builder()), Ok(()));
+    assert_eq!(
+        run("@main push readfile 'a.txt' end", builder()),
+        denied("readfile", "File System")
+    );
+    assert_eq!(
+        run(
+            "@main push env 'HOME' end",
+            VMBuilder::default().allow_env()
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn the_capabilities_replace_the_ones_granted_before() {
+    let builder = VMBuilder::default()
+        .allow_env()
+        .capabilities(Capabilities::default());
+    assert_eq!(
+        run("@main push env 'HOME' end", builder),
+        denied("env", "Environment")
+    );
+    assert_eq!(
+        run(
+            "@main push env 'HOME' end",
+            VMBuilder::default().capabilities(Capabilities::all())
+        ),
+        Ok(())
+    );
+}
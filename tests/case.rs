@@ -0,0 +1,71 @@
+use dark_vm::{
+    builder::VMBuilder, errors::error_kind::ErrorKind, lexer::Lexer, tokens::token_kind::TokenKind,
+    utils::case::Case, values::value_kinds::ValueKind,
+};
+
+mod common;
+
+/// Runs the program with the given case policy and returns the kinds of the values left on the operand stack, along with the kinds of the warnings.
+fn run(contents: &str, case: Case) -> (Vec<ValueKind>, Vec<ErrorKind>) {
+    let builder = VMBuilder::default().case(case);
+    let tokens = builder.lexer().lex(contents).ok().unwrap();
+    let mut vm = builder.build(tokens).ok().unwrap();
+    let outcome = vm.run();
+    outcome.result.ok().unwrap();
+    let stack = common::stack(&vm);
+    let warnings = outcome
+        .warnings
+        .iter()
+        .map(|warning| warning.get_kind().clone())
+        .collect();
+    (stack, warnings)
+}
+
+#[test]
+fn strict_identifiers_that_differ_by_case_are_different_variables() {
+    let (stack, warnings) = run(
+        "@main set count 1 set Count 2 push count push Count end",
+        Case::Strict,
+    );
+    assert_eq!(stack, vec![ValueKind::Int(1), ValueKind::Int(2)]);
+    assert_eq!(
+        warnings,
+        vec![ErrorKind::IdentifierCaseMismatch(
+            "Count".to_owned(),
+            "count".to_owned()
+        )]
+    );
+}
+
+#[test]
+fn folded_identifiers_name_the_same_variable() {
+    let (stack, warnings) = run(
+        "@main set count 1 set Count 2 push count push COUNT end",
+        Case::Fold,
+    );
+    assert_eq!(stack, vec![ValueKind::Int(2), ValueKind::Int(2)]);
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn folded_labels_and_parameters_are_lowered() {
+    let (stack, _) = run(
+        "@Double #X push x push X push add end @main call DOUBLE 2 end",
+        Case::Fold,
+    );
+    assert_eq!(stack, vec![ValueKind::Int(4)]);
+}
+
+#[test]
+fn folding_only_lexes_names_in_lower_case() {
+    let tokens = Lexer::with_case(Case::Fold)
+        .lex("PUSH Count 'Text'")
+        .ok()
+        .unwrap();
+    let kinds = tokens
+        .into_iter()
+        .map(|token| token.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(kinds[1], TokenKind::Identifier("count".to_owned()));
+    assert_eq!(kinds[2], TokenKind::StringLiteral("Text".to_owned()));
+}
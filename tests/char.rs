@@ -0,0 +1,40 @@
+use dark_vm::{errors::error_kind::ErrorKind, values::value_kinds::ValueKind};
+
+mod common;
+
+use common::run;
+
+#[test]
+fn chars_convert_to_and_from_code_points() {
+    assert_eq!(
+        run("@main push ord `a` push chr 233 push eq chr 97 `a` end"),
+        Ok(vec![
+            ValueKind::Int(97),
+            ValueKind::Char('é'),
+            ValueKind::Boolean(true)
+        ])
+    );
+    assert_eq!(
+        run("@main push chr -1 end"),
+        Err(ErrorKind::InvalidCodePoint(-1))
+    );
+}
+
+#[test]
+fn strings_are_indexed_by_characters() {
+    assert_eq!(
+        run(r#"@main push charat "héllo" 1 push len chars "héllo" end"#),
+        Ok(vec![ValueKind::Char('é'), ValueKind::Int(5)])
+    );
+    assert_eq!(
+        run(r#"@main push charat "hi" 2 end"#),
+        Err(ErrorKind::OutOfBounds(0, 2))
+    );
+}
+
+#[test]
+fn a_char_literal_holds_exactly_one_character() {
+    assert_eq!(run("@main push `ab` end"), Err(ErrorKind::InvalidChar));
+    assert_eq!(run("@main push `` end"), Err(ErrorKind::InvalidChar));
+    assert_eq!(run("@main push `a"), Err(ErrorKind::InvalidChar));
+}
@@ -0,0 +1,503 @@
+use std::{
+    env, fs,
+    process::{Command, Output, Stdio},
+};
+
+/// Saves the program to a file of its own and runs the command line interface on it with the given flags.
+///
+/// # Arguments
+/// `name` - The name of the file, which must be unique among the tests.
+/// `contents` - The program.
+/// `flags` - The flags passed after the path of the program.
+fn run(name: &str, contents: &str, flags: &[&str]) -> Output {
+    invoke(name, contents, &[], flags)
+}
+
+/// Saves the program to a file of its own and runs the given command of the command line interface on it.
+///
+/// # Arguments
+/// `name` - The name of the file, which must be unique among the tests.
+/// `command` - The command, such as xref, which is passed before the path of the program.
+/// `contents` - The program.
+fn command(name: &str, command: &str, contents: &str) -> Output {
+    invoke(name, contents, &[command], &[])
+}
+
+/// Saves the program to a file of its own and runs the command line interface on it.
+///
+/// # Arguments
+/// `name` - The name of the file, which must be unique among the tests.
+/// `contents` - The program.
+/// `before` - The arguments passed before the path of the program.
+/// `after` - The arguments passed after the path of the program.
+fn invoke(name: &str, contents: &str, before: &[&str], after: &[&str]) -> Output {
+    invoke_file(&format!("{}.dark", name), contents, before, after)
+}
+
+/// Saves the program to a file with the given name, which includes its extension, and runs the command line interface on it.
+///
+/// # Arguments
+/// `file_name` - The name of the file, which must be unique among the tests.
+/// `contents` - The program.
+/// `before` - The arguments passed before the path of the program.
+/// `after` - The arguments passed after the path of the program.
+fn invoke_file(file_name: &str, contents: &str, before: &[&str], after: &[&str]) -> Output {
+    let path = env::temp_dir().join(format!("dark-vm-cli-{}-{}", std::process::id(), file_name));
+    fs::write(&path, contents).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_dark-vm"))
+        .args(before)
+        .arg(&path)
+        .args(after)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    output
+}
+
+#[test]
+fn values_left_on_the_stack_fail_the_process() {
+    let output = run("assert", "@main push 1 end", &["--assert-stack-empty"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Left On The Stack"));
+
+    let output = run(
+        "assert-empty",
+        "@main push 1 pop end",
+        &["--assert-stack-empty"],
+    );
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn the_exit_code_of_the_program_is_the_exit_status_of_the_process() {
+    let output = run("success", "@main push 1 pop end", &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stderr.is_empty());
+
+    let output = run("failure", "@main push x end", &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Has Not Been Defined"));
+}
+
+#[test]
+fn diff_prints_what_every_instruction_changed() {
+    let output = run(
+        "diff",
+        "@main\n    set x 5\n    push x\n    call greet 2\n    pop\nend\n\n@greet #n\n    push n\nend\n",
+        &["--diff"],
+    );
+    assert!(output.status.success());
+    let diff = String::from_utf8_lossy(&output.stderr);
+    for expected in &[
+        "line 2: set\n    @main x = 5\n",
+        "line 3: push\n    + 5\n",
+        "line 4: call\n    -> @greet\n    @greet n = 2\n",
+        "line 10: end\n    <- @greet\n",
+        "line 5: pop\n    - 2\n",
+    ] {
+        assert!(diff.contains(expected), "{:?} in {}", expected, diff);
+    }
+}
+
+#[test]
+fn xref_lists_the_parameters_calls_and_jumps_of_every_label() {
+    let output = command(
+        "xref",
+        "xref",
+        "@main\n    call greet 'Ada'\n    jmp 8\nend\n\n@greet #name\n    printn name\nend\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "@main (line 1)
+  parameters: none
+  called: never
+  jumped into: never
+@greet (line 6)
+  parameters: name
+  called: line 2 in @main
+  jumped into: line 3 in @main
+"
+    );
+}
+
+#[test]
+fn dump_code_lists_the_labels_and_the_values() {
+    let output = run(
+        "dump-code",
+        "@main\n    call greet 1 2\nend\n\n@greet #a #b\n    push params\nend\n",
+        &["--dump-code"],
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(
+        "Labels (2):
+    @main (Values 0 To 5)
+    @greet #a #b (Values 6 To 9)
+Values (10):
+    0: Label 'main' At Position 1
+    1: <instruction call> At Position 11
+"
+    ));
+    assert!(
+        stdout.contains("    8: <instruction params> At Position 53\n    9: End At Position 60\n")
+    );
+}
+
+#[test]
+fn shadowing_warnings_are_printed_to_stderr() {
+    let output = run(
+        "shadowing",
+        "@main\n    set x 1\n    begin\n        set x 2\n    endblock\nend\n",
+        &["--shadowing", "warn"],
+    );
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("A Warning Occurred."));
+    assert!(stderr.contains("Previously Defined Here."));
+}
+
+#[test]
+fn dump_code_lists_the_documentation_of_the_labels() {
+    let output = run(
+        "dump-code-doc",
+        "@main\nend\n\n-- Greets someone.\n-- Prints a single line.\n@greet\nend\n",
+        &["--dump-code"],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with(
+        "Labels (2):
+    @main (Values 0 To 1)
+    @greet (Values 2 To 3)
+        -- Greets someone.
+        -- Prints a single line.
+Values (4):
+"
+    ));
+}
+
+/// The program that the tests of the doc command document.
+const DOCUMENTED: &str =
+    "@main\n    call greet 'Ada'\nend\n\n-- Greets someone by name.\n@greet #name\n    printn name\nend\n";
+
+#[test]
+fn doc_prints_the_documentation_as_text() {
+    let output = command("doc-text", "doc", DOCUMENTED);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "@main (line 1)\n    No documentation.\n@greet #name (line 6)\n    Greets someone by name.\n"
+    );
+}
+
+#[test]
+fn doc_renders_markdown_with_the_call_graph() {
+    let output = invoke(
+        "doc-markdown",
+        DOCUMENTED,
+        &["doc"],
+        &["--format", "markdown"],
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("# "));
+    assert!(stdout.ends_with(
+        "## Labels
+
+### `@main`
+
+- Defined on line 1.
+- Calls `@greet`.
+
+### `@greet #name`
+
+Greets someone by name.
+
+- Defined on line 6.
+- Called by `@main`.
+
+## Call Graph
+
+- `@main` calls `@greet`
+"
+    ));
+}
+
+#[test]
+fn doc_renders_a_standalone_html_page_with_links() {
+    let output = invoke(
+        "doc-html",
+        "@main\n    call greet\nend\n\n-- Prints <b> & more.\n@greet\nend\n",
+        &["doc"],
+        &["--format", "html"],
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("<!DOCTYPE html>\n<html>\n"));
+    assert!(stdout.contains("<section id=\"label-greet\">\n<h3><code>@greet</code></h3>\n<p>Prints &lt;b&gt; &amp; more.</p>\n"));
+    assert!(stdout.contains(
+        "<li><a href=\"#label-main\"><code>@main</code></a> calls <a href=\"#label-greet\"><code>@greet</code></a></li>\n"
+    ));
+    assert!(stdout.ends_with("</ul>\n</body>\n</html>\n"));
+}
+
+#[test]
+fn the_timeline_is_saved_even_if_the_program_fails() {
+    let path = env::temp_dir().join(format!("dark-vm-cli-{}-timeline.json", std::process::id()));
+    let output = run(
+        "timeline",
+        "@main push 1 push y end",
+        &["--timeline", path.to_str().unwrap()],
+    );
+    assert!(!output.status.success());
+    let timeline = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(timeline.starts_with(r#"{"source":"@main push 1 push y end","steps":[{"step":0,"#));
+    assert!(timeline.contains(r#""error":{"message":"#));
+}
+
+#[test]
+fn profile_prints_the_histogram_and_saves_the_folded_stacks() {
+    let path = env::temp_dir().join(format!("dark-vm-cli-{}-profile.folded", std::process::id()));
+    let output = invoke(
+        "profile",
+        "@main call greet end @greet push 1 pop end",
+        &["profile"],
+        &["--folded", path.to_str().unwrap()],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Instruction "));
+    let folded = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(folded.starts_with("main "));
+    assert!(folded.contains("\nmain;greet "));
+}
+
+#[test]
+fn the_language_is_chosen_by_the_extension_the_shebang_or_the_flag() {
+    let program = "@main printn 'ran' end";
+    let output = invoke_file("language-unknown.txt", program, &[], &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Could Not Be Determined"));
+
+    let output = invoke_file("language-flag.txt", program, &[], &["--lang", "dark"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ran\n");
+
+    for (name, shebang) in &[
+        ("language-env", "#!/usr/bin/env dark"),
+        ("language-path", "#!/usr/local/bin/dark-vm"),
+    ] {
+        let output = invoke_file(name, &format!("{}\n{}", shebang, program), &[], &[]);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "ran\n",
+            "{}",
+            shebang
+        );
+    }
+
+    let output = invoke_file(
+        "language-other",
+        &format!("#!/bin/sh\n{}", program),
+        &[],
+        &[],
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn the_debug_flag_starts_the_debugger() {
+    let output = run("debug-flag", "@main push 1 end", &["--debug"]);
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with(
+        "Debugging. Type 'help' for a list of commands.\n@main line 1: @main push 1 end\n"
+    ));
+
+    // The flag chooses the debug command, so it can not be combined with another command.
+    let output = invoke("debug-xref", "@main push 1 end", &["xref"], &["--debug"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("The Argument '--debug' Is Not A Valid Argument."));
+}
+
+#[test]
+fn disasm_prints_the_disassembly() {
+    let output = command("disasm", "disasm", "@main push 1 end");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Index  Kind         Position  Value
+    0  Label               1  @main  ; Values 0 To 3
+    1  Instruction         7    push
+    2  Int                12      1
+    3  End                14  end
+"
+    );
+}
+
+#[test]
+fn the_kernel_checks_how_its_messages_are_signed() {
+    let connection = |scheme, key| {
+        format!(
+            r#"{{"ip": "127.0.0.1", "transport": "tcp", "signature_scheme": "{}", "key": "{}"}}"#,
+            scheme, key
+        )
+    };
+
+    let output = invoke_file(
+        "kernel-md5.json",
+        &connection("hmac-md5", "secret"),
+        &["kernel"],
+        &[],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("The Signature Scheme 'hmac-md5' Is Not Supported."));
+
+    if !cfg!(feature = "jupyter") {
+        let output = invoke_file(
+            "kernel-key.json",
+            &connection("hmac-sha256", "secret"),
+            &["kernel"],
+            &[],
+        );
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr)
+            .contains("Signing Messages Requires The 'jupyter' Feature"));
+    }
+}
+
+#[test]
+fn the_kernel_needs_a_connection_file() {
+    let output = Command::new(env!("CARGO_BIN_EXE_dark-vm"))
+        .args(["kernel", "/nonexistent/connection.json"])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("The Connection File '/nonexistent/connection.json' Could Not Be Read"));
+}
+
+#[test]
+fn the_limit_flags_reject_missing_and_invalid_values() {
+    for flag in ["--fuel", "--timeout", "--max-depth", "--max-stack"] {
+        let output = run("limit-missing", "@main end", &[flag]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains(&format!(
+            "The Argument '{}' Expects A Value After It.",
+            flag
+        )));
+
+        let output = run("limit-invalid", "@main end", &[flag, "-1"]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains(&format!(
+            "The Value '-1' Is Not A Valid Value For The Argument '{}'.",
+            flag
+        )));
+    }
+}
+
+#[test]
+fn fuel_limits_the_number_of_instructions() {
+    // The end of the label uses fuel as well.
+    let program = "@main push 1 push 2 push 3 end";
+    assert!(run("fuel-enough", program, &["--fuel", "4"])
+        .status
+        .success());
+
+    let output = run("fuel", program, &["--fuel", "2"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("The Program Ran Out Of Fuel After 2 Instructions."));
+}
+
+#[test]
+fn timeout_stops_a_program_that_runs_forever() {
+    let output = run(
+        "timeout",
+        "@main while push true do endwhile end",
+        &["--timeout", "50"],
+    );
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("The Program Did Not Finish Within 50ms.")
+    );
+}
+
+#[test]
+fn max_depth_limits_the_call_stack() {
+    let program = "@main call f end @f call f end";
+    let output = run("max-depth", program, &["--max-depth", "5"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("The Maximum Call Depth Of 5 Was Exceeded."));
+}
+
+#[test]
+fn max_stack_limits_the_operand_stack() {
+    let program = "@main push 1 push 2 push 3 end";
+    assert!(run("max-stack-enough", program, &["--max-stack", "3"])
+        .status
+        .success());
+
+    let output = run("max-stack", program, &["--max-stack", "2"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("The Maximum Stack Size Of 2 Was Exceeded."));
+}
+
+#[test]
+fn the_machine_and_the_timings_are_shown_when_the_program_fails() {
+    let output = run(
+        "show-on-failure",
+        "@main push 1 push x end",
+        &["--show-machine", "--show-time"],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'x', Which Has Not Been Defined"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Operand Stack (1 Value, Top First):\n    0: Int 1\n"));
+    assert!(stdout.contains("Instructions Executed: 2\n"));
+}
+
+#[test]
+fn the_calling_convention_is_chosen_by_its_flag() {
+    let program = "@main push 1 call take end @take pop end";
+    assert!(run("convention-shared", program, &[]).status.success());
+
+    let output = run(
+        "convention-isolated",
+        program,
+        &["--calling-convention", "isolated"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Tried To Pop From An Empty Stack."));
+
+    let output = run(
+        "convention-invalid",
+        program,
+        &["--calling-convention", "fresh"],
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains(
+        "The Value 'fresh' Is Not A Valid Value For The Argument '--calling-convention'."
+    ));
+}
+
+#[test]
+fn the_aliases_file_applies_to_the_program() {
+    let path = env::temp_dir().join(format!("dark-vm-cli-{}-aliases.txt", std::process::id()));
+    fs::write(&path, "alias say printn").unwrap();
+    let output = run(
+        "aliases",
+        "@main say 'Hello' end",
+        &["--aliases", &path.to_string_lossy()],
+    );
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello\n");
+}
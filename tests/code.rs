@@ -0,0 +1,88 @@
+use dark_vm::{code::Code, lexer::Lexer, Opcode};
+use std::collections::BTreeMap;
+
+/// Loads the program.
+fn load(contents: &str) -> Code {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    Code::new(tokens).ok().unwrap()
+}
+
+#[test]
+fn instructions_are_listed_with_their_index_and_position() {
+    let program = "@main printn add 1 2 end";
+    assert_eq!(
+        load(program).get_instructions().collect::<Vec<_>>(),
+        vec![
+            (1, Opcode::from_name("printn").unwrap(), 7),
+            (2, Opcode::from_name("add").unwrap(), 14),
+        ]
+    );
+}
+
+#[test]
+fn labels_are_listed_in_the_order_that_they_appear() {
+    let code = load("@main call b call a end\n-- The second label.\n@b end @a #x end");
+    let labels = code
+        .get_labels_in_order()
+        .into_iter()
+        .map(|(name, label)| {
+            (
+                name,
+                label.start_pos,
+                label.end_pos,
+                label.parameters.len(),
+                label.doc.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![
+            ("main", 0, 5, 0, None),
+            ("b", 6, 7, 0, Some("The second label.".to_owned())),
+            ("a", 8, 9, 1, None),
+        ]
+    );
+}
+
+#[test]
+fn the_jump_table_maps_literal_jumps_to_their_targets() {
+    // The relative jump is measured from the value after it, so it lands on the push.
+    let code = load("@main jmp 5 push 1 rjmpt -4 set i 3 jmp i end");
+    assert_eq!(code.get_jump_table(), BTreeMap::from([(1, 5), (5, 2)]));
+    // The target of the last jump is computed at runtime, and values that are not jumps have no target.
+    assert_eq!(code.get_jump_target(10), None);
+    assert_eq!(code.get_jump_target(3), None);
+}
+
+#[test]
+fn the_jump_table_includes_lowered_instructions() {
+    // The if is lowered into a jump past the branch when the condition is false, and the branch jumps over the pop of the other path.
+    let code = load("@main push true if push 1 endif end");
+    assert_eq!(code.get_jump_table(), BTreeMap::from([(3, 10), (8, 11)]));
+}
+
+#[test]
+fn the_disassembly_lists_every_value_with_its_jumps_and_labels() {
+    let code = load("@main push true if call greet 'a' endif end @greet #n end");
+    assert_eq!(
+        code.disassemble(),
+        "Index  Kind         Position  Value
+    0  Label               1  @main  ; Values 0 To 12
+    1  Instruction         7    push
+    2  Boolean            12      true
+    3  Instruction        17    rjmpf  ; Jumps To 11
+    4  Int                17      7
+    5  Instruction        17    pop
+    6  Instruction        20    call
+    7  Identifier         25      greet
+    8  String             31      \"a\"
+    9  Instruction        35    rjmp  ; Jumps To 12
+   10  Int                35      2
+   11  Instruction        35    pop
+   12  End                41  end
+   13  Label              45  @greet #n  ; Values 13 To 14
+   14  End                55  end
+"
+    );
+}
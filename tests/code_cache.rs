@@ -0,0 +1,107 @@
+use dark_vm::{utils::aliases::Aliases, utils::case::Case, CodeCache, ErrorKind, VMBuilder};
+use std::{sync::Arc, thread};
+
+#[test]
+fn each_source_is_compiled_once() {
+    let cache = CodeCache::default();
+    let first = cache.get_or_compile("@main push 1 end").ok().unwrap();
+    let second = cache.get_or_compile("@main push 1 end").ok().unwrap();
+    let other = cache.get_or_compile("@main push 2 end").ok().unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+    assert!(!Arc::ptr_eq(&first, &other));
+    assert_eq!(cache.len(), 2);
+
+    assert!(cache.remove("@main push 1 end"));
+    assert!(!cache.remove("@main push 1 end"));
+    let third = cache.get_or_compile("@main push 1 end").ok().unwrap();
+    assert!(!Arc::ptr_eq(&first, &third));
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn errors_are_not_cached() {
+    let cache = CodeCache::default();
+    let error = cache.get_or_compile("@main push end").err().unwrap();
+    assert_eq!(error.get_kind(), &ErrorKind::ExpectedArgs(1));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn the_cache_can_be_shared_by_threads() {
+    let workers = (0..8).map(|_| {
+        thread::spawn(|| {
+            let code = CodeCache::global()
+                .get_or_compile("@main push 40 push 2 push add end")
+                .ok()
+                .unwrap();
+            let mut vm = VMBuilder::default()
+                .build_shared(Arc::clone(&code))
+                .ok()
+                .unwrap();
+            vm.run().into_result().ok().unwrap();
+            (
+                code,
+                vm.operand_stack.0.pop().map(|value| value.kind.clone()),
+            )
+        })
+    });
+
+    let results = workers
+        .map(|worker| worker.join().unwrap())
+        .collect::<Vec<_>>();
+    let code = CodeCache::global()
+        .get_or_compile("@main push 40 push 2 push add end")
+        .ok()
+        .unwrap();
+    for (worker_code, result) in results {
+        assert!(Arc::ptr_eq(&code, &worker_code));
+        assert_eq!(result, Some(dark_vm::ValueKind::Int(42)));
+    }
+}
+
+#[test]
+fn the_least_recently_used_program_is_evicted() {
+    let cache = CodeCache::new(2);
+    let first = cache.get_or_compile("@main push 1 end").ok().unwrap();
+    cache.get_or_compile("@main push 2 end").ok().unwrap();
+
+    // Using the first program again makes the second one the least recently used.
+    cache.get_or_compile("@main push 1 end").ok().unwrap();
+    cache.get_or_compile("@main push 3 end").ok().unwrap();
+    assert_eq!(cache.len(), 2);
+    assert!(Arc::ptr_eq(
+        &first,
+        &cache.get_or_compile("@main push 1 end").ok().unwrap()
+    ));
+    assert!(!cache.remove("@main push 2 end"));
+    assert!(cache.remove("@main push 3 end"));
+}
+
+#[test]
+fn a_cache_without_capacity_keeps_nothing() {
+    let cache = CodeCache::new(0);
+    let first = cache.get_or_compile("@main push 1 end").ok().unwrap();
+    let second = cache.get_or_compile("@main push 1 end").ok().unwrap();
+    assert!(!Arc::ptr_eq(&first, &second));
+    assert!(cache.is_empty());
+    assert_eq!(CodeCache::default().capacity(), 1024);
+}
+
+#[test]
+fn a_cache_for_a_builder_uses_its_case_and_aliases() {
+    let source = "@main say 'hi' call Count end @count end";
+    assert!(CodeCache::default().get_or_compile(source).is_err());
+
+    let builder = VMBuilder::default()
+        .case(Case::Fold)
+        .aliases(Aliases::parse("alias say printn").ok().unwrap())
+        .capture_output();
+    let code = CodeCache::for_builder(&builder, 8)
+        .get_or_compile(source)
+        .ok()
+        .unwrap();
+    let outcome = builder.build_shared(code).ok().unwrap().run();
+    assert_eq!(outcome.output.as_deref(), Some("hi\n"));
+}
@@ -0,0 +1,59 @@
+//! The helpers that are shared by the integration tests. Each test file only uses some of them.
+#![allow(dead_code)]
+
+use dark_vm::{
+    builder::VMBuilder, errors::error::Error, errors::error_kind::ErrorKind,
+    values::value_kinds::ValueKind, vm::VM,
+};
+
+/// Lexes the program with the lexer of the builder, builds a VM for it, and runs it.
+/// The VM is returned afterwards, so that its state can be inspected.
+///
+/// # Arguments
+/// `builder` - The builder with the options of the VM.
+/// `contents` - The source of the program.
+pub fn load_and_run(builder: VMBuilder, contents: &str) -> Result<VM, Error> {
+    let tokens = builder.lexer().lex(contents)?;
+    let mut vm = builder.build(tokens)?;
+    vm.run().into_result()?;
+    Ok(vm)
+}
+
+/// Returns the kinds of the values on the operand stack of the VM, from the bottom up.
+///
+/// # Arguments
+/// `vm` - The VM to inspect.
+pub fn stack(vm: &VM) -> Vec<ValueKind> {
+    vm.operand_stack
+        .0
+        .iter()
+        .map(|value| value.kind.clone())
+        .collect()
+}
+
+/// Runs the program with the options of the builder, and returns the kinds of the values left on the operand stack, from the bottom up, or the kind of the first error.
+///
+/// # Arguments
+/// `builder` - The builder with the options of the VM.
+/// `contents` - The source of the program.
+pub fn run_with(builder: VMBuilder, contents: &str) -> Result<Vec<ValueKind>, ErrorKind> {
+    load_and_run(builder, contents)
+        .map(|vm| stack(&vm))
+        .map_err(|error| error.get_kind().clone())
+}
+
+/// Runs the program, and returns the kinds of the values left on the operand stack, from the bottom up, or the kind of the first error.
+///
+/// # Arguments
+/// `contents` - The source of the program.
+pub fn run(contents: &str) -> Result<Vec<ValueKind>, ErrorKind> {
+    run_with(VMBuilder::default(), contents)
+}
+
+/// Runs the program, and returns the kind of the first error. The values left on the operand stack are ignored.
+///
+/// # Arguments
+/// `contents` - The source of the program.
+pub fn run_status(contents: &str) -> Result<(), ErrorKind> {
+    run(contents).map(|_| ())
+}
@@ -0,0 +1,45 @@
+use dark_vm::ErrorKind;
+
+/// Runs the program, and returns everything that it printed, or the kind of the first error.
+fn output(source: &str) -> Result<String, ErrorKind> {
+    dark_vm::run_captured(source)
+        .map(|(_, output)| output)
+        .map_err(|error| error.get_kind().clone())
+}
+
+#[test]
+fn stack_comparisons_use_the_first_value_popped_as_the_left_operand() {
+    assert_eq!(
+        output("@main push 5 push 1 printn slt push 5 push 1 printn sgt end"),
+        Ok("true\nfalse\n".to_owned())
+    );
+    assert_eq!(
+        output("@main push 2 push 2 printn slte push 2 push 2 printn sgte push 2 push 2 printn seq push 2 push 3 printn sneq end"),
+        Ok("true\ntrue\ntrue\ntrue\n".to_owned())
+    );
+}
+
+#[test]
+fn stack_and_inline_comparisons_agree() {
+    assert_eq!(
+        output("@main push 3 push 1 printn slt printn lt 1 3 end"),
+        Ok("true\ntrue\n".to_owned())
+    );
+}
+
+#[test]
+fn stack_comparisons_need_two_values() {
+    assert_eq!(
+        output("@main push 1 printn slt end"),
+        Err(ErrorKind::EmptyStack)
+    );
+}
+
+#[test]
+fn inline_comparisons_without_both_operands_are_rejected_when_loading() {
+    // Nothing is printed, because the program is rejected before it runs.
+    assert_eq!(
+        output("@main printn 'before' printn lt 1 end"),
+        Err(ErrorKind::ExpectedArgs(1))
+    );
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "csv")]
+
+use dark_vm::{ErrorKind, Lexer, VMBuilder, ValueKind};
+
+/// Parses the CSV text, and returns the rows written as JSON, or the kind of the first error.
+fn parse(text: &str, has_header: bool) -> Result<String, ErrorKind> {
+    let contents = format!(
+        "@main push jsonstringify csvparse '{}' {} end",
+        text, has_header
+    );
+    let tokens = Lexer::default().lex(&contents).ok().unwrap();
+    let mut vm = VMBuilder::default()
+        .build(tokens)
+        .map_err(|error| error.get_kind().clone())?;
+    vm.run()
+        .into_result()
+        .map_err(|error| error.get_kind().clone())?;
+    match &vm.operand_stack.0.pop().unwrap().kind {
+        ValueKind::String(json) => Ok(json.to_owned()),
+        kind => panic!("unexpected value {:?}", kind),
+    }
+}
+
+#[test]
+fn rows_without_a_header_are_arrays_of_strings() {
+    assert_eq!(
+        parse("a,1\nb,2,3\n", false),
+        Ok(r#"[["a","1"],["b","2","3"]]"#.to_owned())
+    );
+}
+
+#[test]
+fn rows_with_a_header_are_maps() {
+    assert_eq!(
+        parse("name,age\nAda,36\n\"Lovelace, A\",37\n", true),
+        Ok(r#"[{"age":"36","name":"Ada"},{"age":"37","name":"Lovelace, A"}]"#.to_owned())
+    );
+}
+
+#[test]
+fn rows_must_match_the_header() {
+    assert!(matches!(
+        parse("name,age\nAda\n", true),
+        Err(ErrorKind::InvalidCsv(_))
+    ));
+}
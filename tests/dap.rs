@@ -0,0 +1,163 @@
+use dark_vm::utils::json::Json;
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    process::{Child, Command, Stdio},
+};
+
+/// The Adapter struct maintains a debug adapter that runs in its own process, and the connection to it.
+struct Adapter {
+    child: Child,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    seq: i64,
+}
+
+impl Adapter {
+    /// Starts the debug adapter on a free port and connects to it.
+    fn start() -> Adapter {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut child = Command::new(env!("CARGO_BIN_EXE_dark-vm"))
+            .args(["dap", "--port", &port.to_string()])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        // The adapter prints a line once it is listening.
+        let mut line = String::new();
+        BufReader::new(child.stdout.as_mut().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        assert!(line.starts_with("Listening"), "{}", line);
+
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        Adapter {
+            child,
+            reader: BufReader::new(stream.try_clone().unwrap()),
+            writer: stream,
+            seq: 1,
+        }
+    }
+
+    /// Sends a request with the given command and arguments.
+    fn request(&mut self, command: &str, arguments: Json) {
+        let message = Json::object(vec![
+            ("seq", self.seq.into()),
+            ("type", "request".into()),
+            ("command", command.into()),
+            ("arguments", arguments),
+        ])
+        .to_string();
+        self.seq += 1;
+        write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            message.len(),
+            message
+        )
+        .unwrap();
+    }
+
+    /// Reads messages until one matches the given type and name, which is a command for responses and an event for events.
+    fn expect(&mut self, kind: &str, name: &str) -> Json {
+        loop {
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                self.reader.read_line(&mut header).unwrap();
+                match header.trim() {
+                    "" => break,
+                    header => {
+                        if let Some(value) = header.strip_prefix("Content-Length:") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+            }
+
+            let mut content = vec![0; length];
+            self.reader.read_exact(&mut content).unwrap();
+            let message = Json::parse(&String::from_utf8(content).unwrap())
+                .ok()
+                .unwrap();
+            let key = if kind == "event" { "event" } else { "command" };
+            if message.get("type").and_then(Json::as_str) == Some(kind)
+                && message.get(key).and_then(Json::as_str) == Some(name)
+            {
+                return message;
+            }
+        }
+    }
+}
+
+impl Drop for Adapter {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn a_program_stops_at_a_breakpoint_and_runs_to_the_end() {
+    let path = env::temp_dir().join(format!("dark-vm-dap-{}.dark", std::process::id()));
+    fs::write(&path, "@main\n  set x 1\n  push x\nend\n").unwrap();
+
+    let mut adapter = Adapter::start();
+    adapter.request("initialize", Json::object(vec![]));
+    let response = adapter.expect("response", "initialize");
+    assert_eq!(response.get("success").and_then(Json::as_bool), Some(true));
+    adapter.expect("event", "initialized");
+
+    let program = Json::String(path.to_string_lossy().into_owned());
+    adapter.request("launch", Json::object(vec![("program", program)]));
+    let response = adapter.expect("response", "launch");
+    assert_eq!(response.get("success").and_then(Json::as_bool), Some(true));
+
+    let breakpoint = Json::object(vec![("line", 3i64.into())]);
+    adapter.request(
+        "setBreakpoints",
+        Json::object(vec![("breakpoints", Json::Array(vec![breakpoint]))]),
+    );
+    adapter.expect("response", "setBreakpoints");
+    adapter.request("configurationDone", Json::object(vec![]));
+    let stopped = adapter.expect("event", "stopped");
+    assert_eq!(
+        stopped
+            .get("body")
+            .and_then(|body| body.get("reason"))
+            .and_then(Json::as_str),
+        Some("breakpoint")
+    );
+
+    adapter.request("stackTrace", Json::object(vec![("threadId", 1i64.into())]));
+    let response = adapter.expect("response", "stackTrace");
+    let line = response
+        .get("body")
+        .and_then(|body| body.get("stackFrames"))
+        .and_then(Json::as_array)
+        .and_then(|frames| frames.first())
+        .and_then(|frame| frame.get("line"))
+        .and_then(Json::as_int);
+    assert_eq!(line, Some(3));
+
+    adapter.request("continue", Json::object(vec![("threadId", 1i64.into())]));
+    let exited = adapter.expect("event", "exited");
+    assert_eq!(
+        exited
+            .get("body")
+            .and_then(|body| body.get("exitCode"))
+            .and_then(Json::as_int),
+        Some(0)
+    );
+    adapter.expect("event", "terminated");
+
+    adapter.request("disconnect", Json::object(vec![]));
+    adapter.expect("response", "disconnect");
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "datetime")]
+
+use dark_vm::{ErrorKind, Lexer, VMBuilder, ValueKind};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Runs a program that pushes the value of the expression with the VM built by the given builder,
+/// and returns the kind of that value, or the kind of the first error.
+fn evaluate_with(builder: VMBuilder, expression: &str) -> Result<ValueKind, ErrorKind> {
+    let contents = format!("@main push {} end", expression);
+    let tokens = Lexer::default().lex(&contents).ok().unwrap();
+    let mut vm = builder
+        .build(tokens)
+        .map_err(|error| error.get_kind().clone())?;
+    vm.run()
+        .into_result()
+        .map_err(|error| error.get_kind().clone())?;
+    Ok(vm.operand_stack.0.pop().unwrap().kind.clone())
+}
+
+/// Evaluates the expression like evaluate_with, with the default options.
+fn evaluate(expression: &str) -> Result<ValueKind, ErrorKind> {
+    evaluate_with(VMBuilder::default(), expression)
+}
+
+#[test]
+fn dates_are_formatted_in_utc() {
+    assert_eq!(
+        evaluate("dateformat 1700000000 '%Y-%m-%d %H:%M:%S'"),
+        Ok(ValueKind::String("2023-11-14 22:13:20".to_owned()))
+    );
+}
+
+#[test]
+fn dates_are_parsed_in_utc_at_midnight() {
+    assert_eq!(
+        evaluate("dateparse '2023-11-14' '%Y-%m-%d'"),
+        Ok(ValueKind::Int(1699920000))
+    );
+    assert_eq!(
+        evaluate("dateparse dateformat 1700000000 '%Y-%m-%d %H:%M:%S' '%Y-%m-%d %H:%M:%S'"),
+        Ok(ValueKind::Int(1700000000))
+    );
+    assert!(matches!(
+        evaluate("dateparse 'tomorrow' '%Y-%m-%d'"),
+        Err(ErrorKind::InvalidDate(_))
+    ));
+}
+
+#[test]
+fn now_requires_the_clock_capability() {
+    assert!(matches!(
+        evaluate("now"),
+        Err(ErrorKind::CapabilityDenied(..))
+    ));
+
+    let before = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    match evaluate_with(VMBuilder::default().allow_clock(), "now") {
+        Ok(ValueKind::Int(now)) => assert!(now >= before && now <= before + 60),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
@@ -0,0 +1,76 @@
+use dark_vm::{
+    builder::VMBuilder,
+    debugger::{Breakpoint, DebugEvent, Debugger},
+    values::value_kinds::ValueKind,
+};
+
+/// Starts debugging the program with the default builder.
+fn debug(contents: &str) -> Debugger {
+    Debugger::new(contents, VMBuilder::default()).ok().unwrap()
+}
+
+/// Evaluates the expression in the current frame of the debugger, and returns the kind of its value.
+fn evaluate(debugger: &mut Debugger, expression: &str) -> Option<ValueKind> {
+    debugger
+        .evaluate(expression)
+        .ok()
+        .unwrap()
+        .map(|value| value.kind.clone())
+}
+
+#[test]
+fn resume_pauses_at_label_and_line_breakpoints() {
+    let mut debugger =
+        debug("@main\n  call greet 'Ada'\n  set x 2\nend\n@greet #name\n  push name\nend");
+    let label = debugger.add_breakpoint(Breakpoint::Label("greet".to_owned()), None);
+    let line = debugger.add_breakpoint(Breakpoint::Line(3), None);
+
+    assert!(matches!(debugger.resume(), Ok(DebugEvent::Breakpoint(id)) if id == label));
+    assert_eq!(debugger.current_line(), Some(6));
+    assert_eq!(
+        evaluate(&mut debugger, "name"),
+        Some(ValueKind::String("Ada".to_owned()))
+    );
+
+    assert!(matches!(debugger.resume(), Ok(DebugEvent::Breakpoint(id)) if id == line));
+    assert_eq!(debugger.current_line(), Some(3));
+    assert!(matches!(debugger.resume(), Ok(DebugEvent::Finished(_))));
+}
+
+#[test]
+fn conditional_breakpoints_only_pause_when_the_condition_is_truthy() {
+    let mut debugger = debug("@main\n  times 5 count\nend\n@count #i\n  push i\nend");
+    debugger.add_breakpoint(
+        Breakpoint::Label("count".to_owned()),
+        Some("eq i 3".to_owned()),
+    );
+
+    assert!(matches!(debugger.resume(), Ok(DebugEvent::Breakpoint(_))));
+    assert_eq!(evaluate(&mut debugger, "i"), Some(ValueKind::Int(3)));
+    assert!(matches!(debugger.resume(), Ok(DebugEvent::Finished(_))));
+}
+
+#[test]
+fn step_enters_calls_and_step_over_runs_them() {
+    let source = "@main\n  call inc\n  push 2\nend\n@inc\n  push 1\nend";
+
+    let mut debugger = debug(source);
+    assert!(matches!(debugger.step(), Ok(DebugEvent::Paused)));
+    assert_eq!(debugger.current_line(), Some(6));
+    assert_eq!(debugger.get_frame_lines().len(), 2);
+    assert!(matches!(debugger.step_out(), Ok(DebugEvent::Paused)));
+    assert_eq!(debugger.current_line(), Some(3));
+
+    let mut debugger = debug(source);
+    assert!(matches!(debugger.step_over(), Ok(DebugEvent::Paused)));
+    assert_eq!(debugger.current_line(), Some(3));
+    assert_eq!(debugger.get_frame_lines().len(), 1);
+}
+
+#[test]
+fn finish_ignores_the_breakpoints() {
+    let mut debugger = debug("@main\n  push 1\n  push 2\nend");
+    debugger.add_breakpoint(Breakpoint::Line(3), None);
+    assert!(matches!(debugger.finish(), Ok(DebugEvent::Finished(_))));
+    assert_eq!(debugger.get_vm().operand_stack.0.len(), 2);
+}
@@ -0,0 +1,80 @@
+use dark_vm::{ErrorKind, Lexer, RunOutcome, VMBuilder};
+
+/// Runs the program, and returns its outcome along with everything that it printed.
+fn run(contents: &str) -> (RunOutcome, String) {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    let mut outcome = VMBuilder::default()
+        .capture_output()
+        .build(tokens)
+        .ok()
+        .unwrap()
+        .run();
+    let output = outcome.output.take().unwrap();
+    (outcome, output)
+}
+
+#[test]
+fn deferred_labels_run_when_the_label_returns() {
+    let (outcome, output) = run(
+        "@main call work printn \"after\" end @work defer close printn \"working\" end @close printn \"closed\" end",
+    );
+    assert!(outcome.result.is_ok());
+    assert_eq!(output, "working\nclosed\nafter\n");
+}
+
+#[test]
+fn deferred_labels_run_in_the_reverse_order_of_their_defers() {
+    let (outcome, output) = run(
+        "@main defer first defer second defer third end @first printn 1 end @second printn 2 end @third printn 3 end",
+    );
+    assert!(outcome.result.is_ok());
+    assert_eq!(output, "3\n2\n1\n");
+}
+
+#[test]
+fn deferred_labels_run_when_an_error_unwinds_the_label() {
+    let (outcome, output) = run(
+        "@main defer outer call work end @work defer inner push x end @inner printn \"inner\" end @outer printn \"outer\" end",
+    );
+    assert!(matches!(
+        outcome.result.err().map(|error| error.get_kind().clone()),
+        Some(ErrorKind::UndefinedVariable(name, _, _)) if name == "x"
+    ));
+    assert_eq!(output, "inner\nouter\n");
+}
+
+#[test]
+fn errors_in_deferred_labels_while_unwinding_are_warnings() {
+    let (outcome, output) =
+        run("@main defer first defer broken push y end @first printn 1 end @broken push x end");
+    assert!(matches!(
+        outcome.result.err().map(|error| error.get_kind().clone()),
+        Some(ErrorKind::UndefinedVariable(name, _, _)) if name == "y"
+    ));
+    assert_eq!(outcome.warnings.len(), 1);
+    assert_eq!(output, "1\n");
+}
+
+#[test]
+fn nested_deferred_labels_can_use_the_variables_of_the_frame() {
+    let (outcome, output) = run(
+        "@main call work end @work set file \"data.txt\" defer close printn \"working\" @close printn file end end",
+    );
+    assert!(outcome.result.is_ok());
+    assert_eq!(output, "working\ndata.txt\n");
+}
+
+#[test]
+fn deferred_labels_may_not_define_parameters() {
+    let tokens = Lexer::default()
+        .lex("@main defer close end @close #file end")
+        .ok()
+        .unwrap();
+    assert_eq!(
+        VMBuilder::default()
+            .build(tokens)
+            .err()
+            .map(|error| error.get_kind().clone()),
+        Some(ErrorKind::TooManyParameters(0))
+    );
+}
@@ -0,0 +1,30 @@
+use dark_vm::{Lexer, VMBuilder};
+
+/// Runs the program, and returns everything that it printed.
+fn output(contents: &str) -> String {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    VMBuilder::default()
+        .capture_output()
+        .build(tokens)
+        .ok()
+        .unwrap()
+        .run()
+        .output
+        .unwrap()
+}
+
+#[test]
+fn whole_floats_keep_their_decimal_point() {
+    assert_eq!(
+        output("@main printn 2.0 printn 2.5 printn 2 printn -0.0 end"),
+        "2.0\n2.5\n2\n-0.0\n"
+    );
+}
+
+#[test]
+fn floats_inside_of_arrays_keep_their_decimal_point() {
+    assert_eq!(
+        output("@main printn apush apush arr 1.0 2 end"),
+        "[1.0, 2]\n"
+    );
+}
@@ -0,0 +1,119 @@
+use dark_vm::{errors::error_kind::ErrorKind, values::value_kinds::ValueKind};
+
+mod common;
+
+use common::{run, run_status};
+
+#[test]
+fn div_of_two_ints_is_a_float() {
+    assert_eq!(
+        run("@main push 2 push 1 push div end"),
+        Ok(vec![ValueKind::Float(0.5)])
+    );
+    assert_eq!(
+        run("@main push 2 push 4 push div end"),
+        Ok(vec![ValueKind::Float(2.0)])
+    );
+}
+
+#[test]
+fn idiv_rounds_toward_zero() {
+    assert_eq!(
+        run("@main push 2 push 7 push idiv end"),
+        Ok(vec![ValueKind::Int(3)])
+    );
+    assert_eq!(
+        run("@main push 2 push -7 push idiv end"),
+        Ok(vec![ValueKind::Int(-3)])
+    );
+}
+
+#[test]
+fn idiv_only_accepts_ints() {
+    assert!(matches!(
+        run_status("@main push 2.0 push 7 idiv end"),
+        Err(ErrorKind::UnsupportedOperation(..))
+    ));
+}
+
+#[test]
+fn divmod_pushes_the_quotient_and_the_remainder() {
+    assert_eq!(
+        run("@main push 2 push 7 divmod end"),
+        Ok(vec![ValueKind::Int(3), ValueKind::Int(1)])
+    );
+    assert_eq!(
+        run("@main push 2 push -7 divmod end"),
+        Ok(vec![ValueKind::Int(-3), ValueKind::Int(-1)])
+    );
+}
+
+#[test]
+fn dividing_by_zero_is_an_error() {
+    for instruction in &["div", "idiv", "divmod", "mod"] {
+        let program = format!("@main push 0 push 7 {} end", instruction);
+        assert_eq!(
+            run_status(&program),
+            Err(ErrorKind::DivisionByZero),
+            "{}",
+            program
+        );
+    }
+
+    assert_eq!(
+        run_status("@main push -0.0 push 7 div end"),
+        Err(ErrorKind::DivisionByZero)
+    );
+}
+
+#[test]
+fn negative_divisors_are_not_zero() {
+    assert_eq!(
+        run("@main push -2.0 push 1 push div push -2 push 1.0 push div push -0.5 push 1.0 push div end"),
+        Ok(vec![
+            ValueKind::Float(-0.5),
+            ValueKind::Float(-0.5),
+            ValueKind::Float(-2.0)
+        ])
+    );
+}
+
+#[test]
+fn a_dividend_of_zero_is_not_an_error() {
+    assert_eq!(
+        run("@main push 2 push 0.0 push div end"),
+        Ok(vec![ValueKind::Float(0.0)])
+    );
+}
+
+#[test]
+fn mod_of_ints_and_floats_is_a_float() {
+    assert_eq!(
+        run("@main push 2.0 push 7 push mod push 2 push 7.5 push mod push 2.5 push 7.5 push mod end"),
+        Ok(vec![
+            ValueKind::Float(1.0),
+            ValueKind::Float(1.5),
+            ValueKind::Float(0.0)
+        ])
+    );
+}
+
+#[test]
+fn every_combination_of_a_zero_divisor_is_an_error() {
+    for divisor in &["0", "0.0", "-0.0"] {
+        for dividend in &["7", "7.5"] {
+            for instruction in &["div", "mod"] {
+                let program = format!(
+                    "@main push {} push {} {} end",
+                    divisor, dividend, instruction
+                );
+                assert_eq!(
+                    run_status(&program),
+                    Err(ErrorKind::DivisionByZero),
+                    "{}",
+                    program
+                );
+            }
+        }
+    }
+}
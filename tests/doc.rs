@@ -0,0 +1,36 @@
+use dark_vm::{code::Code, lexer::Lexer};
+
+/// Loads the program, and returns the documentation of the given label.
+fn doc(contents: &str, label: &str) -> Option<String> {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    let code = Code::new(tokens).ok().unwrap();
+    code.get_labels()[label].doc.clone()
+}
+
+#[test]
+fn comments_directly_above_a_label_are_its_documentation() {
+    let program = "@main\nend\n\n-- Greets someone by name.\n--   Prints a single line.\n@greet #name\n    printn name\nend\n";
+    assert_eq!(
+        doc(program, "greet").as_deref(),
+        Some("Greets someone by name.\n  Prints a single line.")
+    );
+}
+
+#[test]
+fn comments_that_are_separated_from_the_label_are_not_documentation() {
+    assert_eq!(doc("-- Far away.\n\n@main\nend\n", "main"), None);
+    assert_eq!(
+        doc(
+            "@main\n    -- Not the documentation.\n    push 1 @inner end\nend\n",
+            "inner"
+        ),
+        None
+    );
+    assert_eq!(
+        doc(
+            "@main\n    push 1 -- A note about the push.\n    @inner\n    end\nend\n",
+            "inner"
+        ),
+        None
+    );
+}
@@ -0,0 +1,55 @@
+use dark_vm::{ErrorKind, Lexer, VMBuilder, ValueKind};
+
+/// Runs a program that pushes the value of the expression, and returns the kind of that value, or the kind of the first error.
+fn evaluate(expression: &str) -> Result<ValueKind, ErrorKind> {
+    let contents = format!("@main push {} end", expression);
+    let tokens = Lexer::default().lex(&contents).ok().unwrap();
+    let mut vm = VMBuilder::default()
+        .build(tokens)
+        .map_err(|error| error.get_kind().clone())?;
+    vm.run()
+        .into_result()
+        .map_err(|error| error.get_kind().clone())?;
+    Ok(vm.operand_stack.0.pop().unwrap().kind.clone())
+}
+
+/// The result of an expression that evaluates to the given string.
+fn string(text: &str) -> Result<ValueKind, ErrorKind> {
+    Ok(ValueKind::String(text.to_owned()))
+}
+
+#[test]
+fn strings_and_bytes_are_encoded() {
+    assert_eq!(evaluate("b64encode 'hello'"), string("aGVsbG8="));
+    assert_eq!(evaluate("b64encode ''"), string(""));
+    assert_eq!(evaluate("hexencode 'hi'"), string("6869"));
+    assert_eq!(
+        evaluate("hexencode b64decode 'aGVsbG8='"),
+        string("68656c6c6f")
+    );
+    assert_eq!(evaluate("b64encode hexdecode 'ff00'"), string("/wA="));
+}
+
+#[test]
+fn decoding_returns_bytes() {
+    assert_eq!(
+        evaluate("b64decode 'aGk'"),
+        Ok(ValueKind::Bytes(b"hi".to_vec()))
+    );
+    assert_eq!(
+        evaluate("hexdecode 'FF0a'"),
+        Ok(ValueKind::Bytes(vec![0xff, 0x0a]))
+    );
+    assert_eq!(evaluate("len hexdecode 'ff0a'"), Ok(ValueKind::Int(2)));
+}
+
+#[test]
+fn invalid_encodings_are_errors() {
+    for expression in &["b64decode 'a!'", "hexdecode 'abc'", "hexdecode 'zz'"] {
+        assert!(
+            matches!(evaluate(expression), Err(ErrorKind::InvalidEncoding(_))),
+            "{}",
+            expression
+        );
+    }
+}
@@ -0,0 +1,61 @@
+#![cfg(all(feature = "exec", unix))]
+
+use dark_vm::{ErrorKind, Lexer, VMBuilder, ValueKind};
+
+/// Runs a program that pushes the result of the command, written as JSON, with the VM built by the given builder,
+/// and returns it, or the kind of the first error.
+fn exec(builder: VMBuilder, command: &str) -> Result<String, ErrorKind> {
+    let contents = format!("@main push jsonstringify exec {} end", command);
+    let tokens = Lexer::default().lex(&contents).ok().unwrap();
+    let mut vm = builder
+        .build(tokens)
+        .map_err(|error| error.get_kind().clone())?;
+    vm.run()
+        .into_result()
+        .map_err(|error| error.get_kind().clone())?;
+    match &vm.operand_stack.0.pop().unwrap().kind {
+        ValueKind::String(json) => Ok(json.to_owned()),
+        kind => panic!("unexpected value {:?}", kind),
+    }
+}
+
+#[test]
+fn exec_is_denied_by_default() {
+    assert!(matches!(
+        exec(VMBuilder::default(), "'echo hi'"),
+        Err(ErrorKind::CapabilityDenied(..))
+    ));
+}
+
+#[test]
+fn exec_captures_the_output_and_the_exit_code() {
+    assert_eq!(
+        exec(VMBuilder::default().allow_exec(), "'echo hello world'"),
+        Ok(r#"{"code":0,"stderr":"","stdout":"hello world\n"}"#.to_owned())
+    );
+    assert_eq!(
+        exec(VMBuilder::default().allow_exec(), "'false'"),
+        Ok(r#"{"code":1,"stderr":"","stdout":""}"#.to_owned())
+    );
+}
+
+#[test]
+fn exec_takes_the_arguments_as_an_array() {
+    assert_eq!(
+        exec(
+            VMBuilder::default().allow_exec(),
+            "apush apush arr 'echo' 'a  b'"
+        ),
+        Ok(r#"{"code":0,"stderr":"","stdout":"a  b\n"}"#.to_owned())
+    );
+}
+
+#[test]
+fn only_allowed_commands_run() {
+    let builder = || VMBuilder::default().allow_command("echo");
+    assert!(exec(builder(), "'echo hi'").is_ok());
+    assert_eq!(
+        exec(builder(), "'true'"),
+        Err(ErrorKind::CommandNotAllowed("true".to_owned()))
+    );
+}
@@ -0,0 +1,75 @@
+use dark_vm::{
+    builder::VMBuilder, errors::error_kind::ErrorKind, utils::float_equality::FloatEquality,
+    values::value_kinds::ValueKind,
+};
+
+mod common;
+
+/// Runs the program with the given mode and returns the kinds of the values left on the operand stack, from the bottom up.
+fn run(contents: &str, float_equality: FloatEquality) -> Result<Vec<ValueKind>, ErrorKind> {
+    common::run_with(
+        VMBuilder::default().float_equality(float_equality),
+        contents,
+    )
+}
+
+/// Returns whether the first float is equal to the second float in the given mode, according to the eq instruction.
+fn eq(first: &str, second: &str, float_equality: FloatEquality) -> bool {
+    let stack = run(
+        &format!("@main push eq {} {} end", first, second),
+        float_equality,
+    );
+    stack == Ok(vec![ValueKind::Boolean(true)])
+}
+
+#[test]
+fn rounding_errors_are_absorbed_by_default() {
+    let sum = "@main push 0.1 push 0.2 push add set sum pop push eq sum 0.3 push neq sum 0.3 end";
+    assert_eq!(
+        run(sum, FloatEquality::default()),
+        Ok(vec![ValueKind::Boolean(true), ValueKind::Boolean(false)])
+    );
+    assert_eq!(
+        run(sum, FloatEquality::Bitwise),
+        Ok(vec![ValueKind::Boolean(false), ValueKind::Boolean(true)])
+    );
+}
+
+#[test]
+fn ulps_scale_with_the_magnitude() {
+    // The floats are a single ULP apart, even though their difference is far larger than the epsilon.
+    let next = f64::from_bits(1e6_f64.to_bits() + 1).to_string();
+    assert!(eq("1000000.0", &next, FloatEquality::Ulps(1)));
+    assert!(!eq("1000000.0", &next, FloatEquality::Ulps(0)));
+    assert!(!eq("1.0", "1.001", FloatEquality::default()));
+}
+
+#[test]
+fn relative_mode_allows_a_fraction_of_the_larger_float() {
+    assert!(eq("100.0", "100.5", FloatEquality::Relative(0.01)));
+    assert!(!eq("100.0", "102.0", FloatEquality::Relative(0.01)));
+}
+
+#[test]
+fn floats_inside_of_arrays_use_the_same_mode() {
+    let program =
+        "@main push 0.1 push 0.2 push add set sum pop push eq apush arr sum apush arr 0.3 end";
+    assert_eq!(
+        run(program, FloatEquality::default()),
+        Ok(vec![ValueKind::Boolean(true)])
+    );
+    assert_eq!(
+        run(program, FloatEquality::Bitwise),
+        Ok(vec![ValueKind::Boolean(false)])
+    );
+}
+
+#[test]
+fn the_modes_are_parsed_from_text() {
+    assert_eq!("ulps:8".parse(), Ok(FloatEquality::Ulps(8)));
+    assert_eq!("relative:1e-9".parse(), Ok(FloatEquality::Relative(1e-9)));
+    assert_eq!("Bitwise".parse(), Ok(FloatEquality::Bitwise));
+    assert_eq!("relative:-1".parse::<FloatEquality>(), Err(()));
+    assert_eq!("ulps".parse::<FloatEquality>(), Err(()));
+    assert_eq!(FloatEquality::default().to_string(), "ulps:4");
+}
@@ -0,0 +1,52 @@
+use dark_vm::ErrorKind;
+
+/// Formats the number with the specifier, and returns the string or the kind of the error.
+fn format(spec: &str, number: &str) -> Result<String, ErrorKind> {
+    dark_vm::run_captured(&format!("@main print format '{}' {} end", spec, number))
+        .map(|(_, output)| output)
+        .map_err(|error| error.get_kind().clone())
+}
+
+#[test]
+fn floats_are_written_with_decimals_or_in_scientific_notation() {
+    assert_eq!(format("%.2f", "3.14159"), Ok("3.14".to_owned()));
+    assert_eq!(format("%08.3f", "-2.5"), Ok("-002.500".to_owned()));
+    assert_eq!(format("%f", "2"), Ok("2.000000".to_owned()));
+    assert_eq!(format("%e", "12345.678"), Ok("1.234568e4".to_owned()));
+    assert_eq!(format("%.1E", "0.5"), Ok("5.0E-1".to_owned()));
+}
+
+#[test]
+fn ints_are_written_in_every_base() {
+    assert_eq!(format("%5d", "42"), Ok("   42".to_owned()));
+    assert_eq!(format("%-5d", "42"), Ok("42   ".to_owned()));
+    // The leading % may be left out.
+    assert_eq!(format("05d", "-42"), Ok("-0042".to_owned()));
+    assert_eq!(format("%+d", "42"), Ok("+42".to_owned()));
+    assert_eq!(format("%x", "255"), Ok("ff".to_owned()));
+    assert_eq!(format("%X", "255"), Ok("FF".to_owned()));
+    assert_eq!(format("%o", "8"), Ok("10".to_owned()));
+    assert_eq!(format("%08b", "5"), Ok("00000101".to_owned()));
+    assert_eq!(format("%x", "-1"), Ok("ffffffffffffffff".to_owned()));
+}
+
+#[test]
+fn invalid_specifiers_and_numbers_are_errors() {
+    for spec in &["%q", "%.2d", "%2000d", ""] {
+        assert_eq!(
+            format(spec, "1"),
+            Err(ErrorKind::InvalidFormatSpecifier(spec.to_string())),
+            "{}",
+            spec
+        );
+    }
+
+    assert!(matches!(
+        format("%d", "1.5"),
+        Err(ErrorKind::ValueMismatch(..))
+    ));
+    assert!(matches!(
+        format("%f", "'a'"),
+        Err(ErrorKind::ValueMismatch(..))
+    ));
+}
@@ -0,0 +1,114 @@
+use dark_vm::{builder::VMBuilder, errors::error_kind::ErrorKind, values::value_kinds::ValueKind};
+use std::{env, fs, path::PathBuf};
+
+mod common;
+
+/// Creates an empty directory for the test with the given name, which is unique to this process.
+fn directory(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!("dark-vm-fs-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+/// Runs a program with the VM built by the given builder, and returns the kind of the value on top of the stack, or the kind of the first error.
+fn run(contents: &str, builder: VMBuilder) -> Result<Option<ValueKind>, ErrorKind> {
+    common::run_with(builder, contents).map(|mut stack| stack.pop())
+}
+
+#[test]
+fn file_instructions_are_denied_by_default() {
+    for contents in &[
+        "@main push readfile 'a.txt' end",
+        "@main writefile 'a.txt' 'a' end",
+        "@main appendfile 'a.txt' 'a' end",
+    ] {
+        assert!(
+            matches!(
+                run(contents, VMBuilder::default()),
+                Err(ErrorKind::CapabilityDenied(..))
+            ),
+            "{}",
+            contents
+        );
+    }
+}
+
+#[test]
+fn file_instructions_write_and_read_inside_of_the_root() {
+    let root = directory("root");
+    let result = run(
+        "@main writefile 'greeting.txt' 'Hello' appendfile 'greeting.txt' ', World!' push readfile 'greeting.txt' end",
+        VMBuilder::default().fs_root(&root),
+    );
+    assert_eq!(
+        result,
+        Ok(Some(ValueKind::String("Hello, World!".to_owned())))
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("greeting.txt")).unwrap(),
+        "Hello, World!"
+    );
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn file_instructions_reject_paths_outside_of_the_root() {
+    let root = directory("outside");
+    let inner = root.join("inner");
+    fs::create_dir_all(&inner).unwrap();
+    fs::write(root.join("secret.txt"), "secret").unwrap();
+    let outside = root.join("secret.txt").to_string_lossy().into_owned();
+    for path in &["../secret.txt", outside.as_str()] {
+        let contents = format!("@main push readfile '{}' end", path);
+        assert_eq!(
+            run(&contents, VMBuilder::default().fs_root(&inner)),
+            Err(ErrorKind::PathOutsideRoot((*path).to_owned())),
+            "{}",
+            path
+        );
+    }
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn missing_files_are_reported() {
+    let root = directory("missing");
+    assert!(matches!(
+        run(
+            "@main push readfile 'missing.txt' end",
+            VMBuilder::default().fs_root(&root)
+        ),
+        Err(ErrorKind::FailedFileOperation(..))
+    ));
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn symbolic_links_can_not_leave_the_root() {
+    let root = directory("links");
+    let inner = root.join("inner");
+    fs::create_dir(&inner).unwrap();
+    fs::write(root.join("secret.txt"), "secret").unwrap();
+    std::os::unix::fs::symlink(root.join("secret.txt"), inner.join("existing")).unwrap();
+    std::os::unix::fs::symlink(root.join("created.txt"), inner.join("dangling")).unwrap();
+
+    assert_eq!(
+        run(
+            "@main push readfile 'existing' end",
+            VMBuilder::default().fs_root(&inner)
+        ),
+        Err(ErrorKind::PathOutsideRoot("existing".to_owned()))
+    );
+    // The target of the dangling link does not exist, so writing through it would create a file outside of the root.
+    assert_eq!(
+        run(
+            "@main writefile 'dangling' 'escaped' end",
+            VMBuilder::default().fs_root(&inner)
+        ),
+        Err(ErrorKind::PathOutsideRoot("dangling".to_owned()))
+    );
+    assert!(!root.join("created.txt").exists());
+    fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "hashing")]
+
+use dark_vm::{Lexer, VMBuilder, ValueKind};
+
+/// Runs a program that pushes the value of the expression, and returns the string that it evaluates to.
+fn evaluate(expression: &str) -> String {
+    let contents = format!("@main push {} end", expression);
+    let tokens = Lexer::default().lex(&contents).ok().unwrap();
+    let mut vm = VMBuilder::default().build(tokens).ok().unwrap();
+    assert!(vm.run().is_success());
+    match &vm.operand_stack.0.pop().unwrap().kind {
+        ValueKind::String(digest) => digest.to_owned(),
+        kind => panic!("unexpected value {:?}", kind),
+    }
+}
+
+#[test]
+fn strings_are_hashed_as_utf8() {
+    assert_eq!(evaluate("crc32 'hello'"), "3610a686");
+    assert_eq!(evaluate("md5 'hello'"), "5d41402abc4b2a76b9719d911017c592");
+    assert_eq!(
+        evaluate("sha256 ''"),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}
+
+#[test]
+fn bytes_hash_like_the_same_string() {
+    for instruction in &["crc32", "md5", "sha256"] {
+        assert_eq!(
+            evaluate(&format!("{} b64decode 'aGVsbG8='", instruction)),
+            evaluate(&format!("{} 'hello'", instruction)),
+            "{}",
+            instruction
+        );
+    }
+}
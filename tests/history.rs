@@ -0,0 +1,60 @@
+use dark_vm::{utils::history::HistoryEntry, Error, ErrorKind, Lexer, Opcode, VMBuilder};
+
+/// The program that these tests run. It divides by zero after pushing three values.
+const PROGRAM: &str = "@main push 1 push 0 push 5 div end";
+
+/// Runs the program, keeping the given number of instructions, and returns the error that stopped it.
+fn run(size: usize) -> Error {
+    let tokens = Lexer::default().lex(PROGRAM).ok().unwrap();
+    VMBuilder::default()
+        .history(size)
+        .build(tokens)
+        .ok()
+        .unwrap()
+        .run()
+        .into_result()
+        .err()
+        .unwrap()
+}
+
+/// Creates the entry of an instruction that was executed at the position of its name in the program.
+fn entry(name: &str, nth: usize, stack_depth: usize) -> HistoryEntry {
+    HistoryEntry {
+        pos: PROGRAM.match_indices(name).nth(nth).unwrap().0 + 1,
+        opcode: Opcode::from_name(name).unwrap(),
+        stack_depth,
+    }
+}
+
+#[test]
+fn runtime_errors_carry_the_recent_instructions() {
+    let error = run(16);
+    assert_eq!(error.get_kind(), &ErrorKind::DivisionByZero);
+    assert_eq!(
+        error.get_history(),
+        [
+            entry("push", 0, 0),
+            entry("push", 1, 1),
+            entry("push", 2, 2),
+            entry("div", 0, 3)
+        ]
+    );
+}
+
+#[test]
+fn only_the_most_recent_instructions_are_kept() {
+    assert_eq!(
+        run(2).get_history(),
+        [entry("push", 2, 2), entry("div", 0, 3)]
+    );
+    assert!(run(0).get_history().is_empty());
+}
+
+#[test]
+fn the_history_is_listed_below_the_error() {
+    let pretty = run(2).prettify(PROGRAM);
+    assert!(pretty.ends_with(
+        "Recently Executed Instructions, Oldest First:\n    1:21 push (Stack Depth 2)\n    1:28 div (Stack Depth 3)\n"
+    ));
+    assert!(!run(0).prettify(PROGRAM).contains("Recently Executed"));
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "http")]
+
+use dark_vm::{ErrorKind, Lexer, VMBuilder, ValueKind};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread,
+};
+
+/// Serves a single request on a local port with the given status line and body, and returns the url of the server.
+fn serve(status: &'static str, body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/items", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+            line.clear();
+        }
+
+        let _ = write!(
+            reader.get_mut(),
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+    });
+    url
+}
+
+/// Runs a program that fetches the url with the VM built by the given builder, and returns the response written as JSON,
+/// or the kind of the first error.
+fn fetch(builder: VMBuilder, url: &str) -> Result<String, ErrorKind> {
+    let contents = format!("@main push jsonstringify fetch '{}' end", url);
+    let tokens = Lexer::default().lex(&contents).ok().unwrap();
+    let mut vm = builder
+        .build(tokens)
+        .map_err(|error| error.get_kind().clone())?;
+    vm.run()
+        .into_result()
+        .map_err(|error| error.get_kind().clone())?;
+    match &vm.operand_stack.0.pop().unwrap().kind {
+        ValueKind::String(json) => Ok(json.to_owned()),
+        kind => panic!("unexpected value {:?}", kind),
+    }
+}
+
+#[test]
+fn fetch_is_denied_by_default() {
+    assert!(matches!(
+        fetch(VMBuilder::default(), "http://127.0.0.1:1/"),
+        Err(ErrorKind::CapabilityDenied(..))
+    ));
+}
+
+#[test]
+fn fetch_returns_the_status_and_the_body() {
+    let url = serve("200 OK", "[1,2]");
+    assert_eq!(
+        fetch(VMBuilder::default().allow_net(), &url),
+        Ok(r#"{"body":"[1,2]","status":200}"#.to_owned())
+    );
+}
+
+#[test]
+fn fetch_returns_responses_with_any_status() {
+    let url = serve("404 Not Found", "missing");
+    assert_eq!(
+        fetch(VMBuilder::default().allow_net(), &url),
+        Ok(r#"{"body":"missing","status":404}"#.to_owned())
+    );
+}
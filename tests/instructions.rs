@@ -0,0 +1,109 @@
+use dark_vm::{
+    errors::error_kind::ErrorKind,
+    instructions::{Opcode, INSTRUCTIONS},
+    lexer::Lexer,
+    tokens::token_kind::TokenKind,
+    VMBuilder,
+};
+use std::{collections::HashSet, process::Command};
+
+#[test]
+fn every_instruction_is_found_by_its_name() {
+    let mut names = HashSet::new();
+    for instruction in INSTRUCTIONS {
+        assert!(names.insert(instruction.name), "{}", instruction.name);
+        assert_eq!(
+            Opcode::from_name(instruction.name),
+            Some(instruction.opcode)
+        );
+        assert_eq!(
+            Opcode::from_name(&instruction.name.to_ascii_uppercase()),
+            Some(instruction.opcode)
+        );
+        assert_eq!(instruction.opcode.get_name(), instruction.name);
+        assert!(
+            instruction.usage.starts_with(instruction.name),
+            "{}",
+            instruction.usage
+        );
+    }
+
+    assert_eq!(Opcode::from_name("greet"), None);
+}
+
+#[test]
+fn the_lexer_reads_the_registry() {
+    for instruction in INSTRUCTIONS {
+        let tokens = Lexer::default().lex(instruction.name).ok().unwrap();
+        assert_eq!(
+            tokens.front().map(|token| &token.kind),
+            Some(&TokenKind::Instruction(instruction.opcode)),
+            "{}",
+            instruction.name
+        );
+    }
+}
+
+#[test]
+fn stack_effects_are_described() {
+    let effect = |name| {
+        Opcode::from_name(name)
+            .unwrap()
+            .get_instruction()
+            .get_stack_effect()
+    };
+    assert_eq!(effect("push"), "+1");
+    assert_eq!(effect("pop"), "-1");
+    assert_eq!(effect("peek"), "none");
+    assert_eq!(effect("add"), "-2");
+}
+
+#[test]
+fn the_instructions_command_prints_the_registry() {
+    let output = Command::new(env!("CARGO_BIN_EXE_dark-vm"))
+        .arg("instructions")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    assert!(lines.next().unwrap().starts_with("USAGE"));
+    let rows = lines.collect::<Vec<_>>();
+    assert_eq!(rows.len(), INSTRUCTIONS.len());
+    for (row, instruction) in rows.iter().zip(INSTRUCTIONS) {
+        assert!(row.starts_with(instruction.usage), "{}", row);
+        assert!(row.ends_with(instruction.description), "{}", row);
+    }
+}
+
+#[test]
+fn the_table_lines_up_with_the_opcodes() {
+    for (index, instruction) in INSTRUCTIONS.iter().enumerate() {
+        assert_eq!(instruction.opcode as usize, index, "{}", instruction.name);
+        assert_eq!(instruction.opcode.get_instruction(), instruction);
+    }
+}
+
+#[test]
+fn the_verifier_checks_the_arity_and_the_stack_effect_from_the_table() {
+    for instruction in INSTRUCTIONS {
+        let contents = format!("@main {} end", instruction.name);
+        let tokens = Lexer::default().lex(&contents).ok().unwrap();
+        let error = VMBuilder::default()
+            .build(tokens)
+            .err()
+            .map(|error| error.get_kind().clone());
+        // The alias instruction is checked by the lexer, which expects the name of an instruction after it.
+        if instruction.arity > 0 && instruction.name != "alias" {
+            assert_eq!(
+                error,
+                Some(ErrorKind::ExpectedArgs(instruction.arity)),
+                "{}",
+                instruction.name
+            );
+        } else if instruction.pops > 0 && !["if", "do", "match"].contains(&instruction.name) {
+            // The if, do, and match keywords also pop a value, but a lone keyword is reported as a block that is not closed.
+            assert_eq!(error, Some(ErrorKind::EmptyStack), "{}", instruction.name);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use dark_vm::{ErrorKind, ValueKind};
+
+mod common;
+
+use common::run;
+
+/// Parses the JSON, serializes the value again, and returns the string.
+fn round_trip(json: &str) -> String {
+    let contents = format!("@main push jsonstringify jsonparse '{}' end", json);
+    match run(&contents).ok().unwrap().as_slice() {
+        [ValueKind::String(text)] => text.to_owned(),
+        stack => panic!("unexpected stack {:?}", stack),
+    }
+}
+
+#[test]
+fn json_survives_a_round_trip() {
+    for json in &[
+        "null",
+        "true",
+        "-42",
+        "2.5",
+        "2.0",
+        "\"a \\\"quoted\\\" line\\n\"",
+        "[1,[2,[]],{}]",
+        "{\"a\":[1,2],\"b\":{\"c\":null}}",
+    ] {
+        assert_eq!(&round_trip(json), json);
+    }
+}
+
+#[test]
+fn json_types_map_to_value_types() {
+    assert_eq!(
+        run(
+            r#"@main push jsonparse "null" push jsonparse "true" push jsonparse "1" push jsonparse "1.5" push jsonparse '"a"' end"#
+        ),
+        Ok(vec![
+            ValueKind::Null,
+            ValueKind::Boolean(true),
+            ValueKind::Int(1),
+            ValueKind::Float(1.5),
+            ValueKind::String("a".to_owned()),
+        ])
+    );
+    assert_eq!(
+        run(r#"@main push len jsonparse "[1, 2, 3]" push len jsonparse '{"a": 1}' end"#),
+        Ok(vec![ValueKind::Int(3), ValueKind::Int(1)])
+    );
+}
+
+#[test]
+fn values_map_to_json_types() {
+    assert_eq!(
+        run("@main push jsonstringify `c` push jsonstringify apush arr 1 end"),
+        Ok(vec![
+            ValueKind::String("\"c\"".to_owned()),
+            ValueKind::String("[1]".to_owned()),
+        ])
+    );
+}
+
+#[test]
+fn invalid_json_is_an_error() {
+    assert_eq!(
+        run("@main push jsonparse \"[1,\" end"),
+        Err(ErrorKind::InvalidJson)
+    );
+}
+
+#[test]
+fn objects_become_maps_with_sorted_keys() {
+    assert_eq!(
+        round_trip(r#"{"b":1,"c":[],"a":{"z":true,"y":false}}"#),
+        r#"{"a":{"y":false,"z":true},"b":1,"c":[]}"#
+    );
+    assert_eq!(
+        run(r#"@main push jsonstringify mkeys jsonparse '{"b": 1, "a": 2}' end"#),
+        Ok(vec![ValueKind::String(r#"["a","b"]"#.to_owned())])
+    );
+}
@@ -0,0 +1,340 @@
+use dark_vm::utils::json::Json;
+use std::{
+    env, fs,
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+/// The key that signs the messages. Without the `jupyter` feature, the kernel can only be started with an empty key.
+const KEY: &str = if cfg!(feature = "jupyter") {
+    "secret"
+} else {
+    ""
+};
+
+/// The frame that separates the identities of a message from its signature.
+const DELIMITER: &[u8] = b"<IDS|MSG>";
+
+/// The Kernel struct maintains a running `kernel` command, which is killed when the test ends.
+struct Kernel {
+    child: Child,
+    path: PathBuf,
+    shell_port: u16,
+}
+
+impl Kernel {
+    /// Writes a connection file with free ports, and starts the kernel with it.
+    ///
+    /// # Arguments
+    /// `name` - The name of the test, which keeps the connection files of the tests apart.
+    fn start(name: &str) -> Kernel {
+        let ports = (0..5)
+            .map(|_| TcpListener::bind("127.0.0.1:0").unwrap())
+            .collect::<Vec<_>>()
+            .iter()
+            .map(|listener| listener.local_addr().unwrap().port())
+            .collect::<Vec<_>>();
+        let names = [
+            "shell_port",
+            "control_port",
+            "iopub_port",
+            "hb_port",
+            "stdin_port",
+        ];
+        let mut entries = vec![
+            ("ip", "127.0.0.1".into()),
+            ("transport", "tcp".into()),
+            ("signature_scheme", "hmac-sha256".into()),
+            ("key", KEY.into()),
+        ];
+        entries.extend(
+            names
+                .iter()
+                .zip(&ports)
+                .map(|(name, port)| (*name, Json::Int(*port as i64))),
+        );
+
+        let path = env::temp_dir().join(format!(
+            "dark-vm-kernel-{}-{}.json",
+            std::process::id(),
+            name
+        ));
+        fs::write(&path, Json::object(entries).to_string()).unwrap();
+        let child = Command::new(env!("CARGO_BIN_EXE_dark-vm"))
+            .arg("kernel")
+            .arg(&path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        Kernel {
+            child,
+            path,
+            shell_port: ports[0],
+        }
+    }
+
+    /// Connects to the shell socket of the kernel, waiting until the kernel is listening.
+    fn shell(&self) -> Peer {
+        for _ in 0..100 {
+            if let Ok(stream) = TcpStream::connect(("127.0.0.1", self.shell_port)) {
+                return Peer::handshake(stream);
+            }
+
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        panic!("the kernel never started listening");
+    }
+}
+
+impl Drop for Kernel {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The Peer struct maintains a connection to a socket of the kernel, acting as a DEALER socket.
+struct Peer {
+    stream: TcpStream,
+    message_count: u64,
+}
+
+impl Peer {
+    /// Performs the ZMTP handshake: both peers send their greeting, and then a READY command with the type of their socket.
+    ///
+    /// # Arguments
+    /// `stream` - The new connection.
+    fn handshake(mut stream: TcpStream) -> Peer {
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let mut greeting = [0; 64];
+        greeting[0] = 0xff;
+        greeting[9] = 0x7f;
+        greeting[10] = 3;
+        greeting[12..16].copy_from_slice(b"NULL");
+        stream.write_all(&greeting).unwrap();
+
+        let mut kernel = [0; 64];
+        stream.read_exact(&mut kernel).unwrap();
+        assert_eq!(kernel[0], 0xff);
+        assert_eq!(kernel[10], 3);
+        assert_eq!(&kernel[12..17], b"NULL\0");
+
+        let mut peer = Peer {
+            stream,
+            message_count: 0,
+        };
+        let mut ready = b"\x05READY\x0bSocket-Type".to_vec();
+        ready.extend_from_slice(&6u32.to_be_bytes());
+        ready.extend_from_slice(b"DEALER");
+        peer.write_frame(0x04, &ready);
+
+        let (flags, body) = peer.read_frame();
+        assert_eq!(flags & 0x04, 0x04);
+        assert!(body.starts_with(b"\x05READY\x0bSocket-Type\x00\x00\x00\x06ROUTER"));
+        peer
+    }
+
+    /// Sends a request, and returns its header.
+    ///
+    /// # Arguments
+    /// `msg_type` - The type of the request.
+    /// `content` - The content of the request.
+    /// `signature` - The signature to send instead of the right one, if any.
+    fn send(&mut self, msg_type: &str, content: Json, signature: Option<&str>) -> Json {
+        self.message_count += 1;
+        let header = Json::object(vec![
+            ("msg_id", format!("test-{}", self.message_count).into()),
+            ("session", "test".into()),
+            ("username", "test".into()),
+            ("msg_type", msg_type.into()),
+            ("version", "5.3".into()),
+        ]);
+        let parts = [
+            header.clone(),
+            Json::Object(vec![]),
+            Json::Object(vec![]),
+            content,
+        ]
+        .map(|part| part.to_string().into_bytes());
+        let signature = signature.map_or_else(|| sign(&parts), str::to_owned);
+
+        let mut frames = vec![DELIMITER.to_vec(), signature.into_bytes()];
+        frames.extend(parts);
+        for (index, frame) in frames.iter().enumerate() {
+            let flags = if index + 1 < frames.len() { 0x01 } else { 0 };
+            self.write_frame(flags, frame);
+        }
+
+        header
+    }
+
+    /// Reads a reply, checks its signature, and returns its header, its parent header, and its content.
+    fn receive(&mut self) -> (Json, Json, Json) {
+        let mut frames = vec![];
+        loop {
+            let (flags, body) = self.read_frame();
+            frames.push(body);
+            if flags & 0x01 == 0 {
+                break;
+            }
+        }
+
+        assert_eq!(frames.len(), 6);
+        assert_eq!(frames[0], DELIMITER);
+        assert_eq!(frames[1], sign(&frames[2..]).into_bytes());
+        let parse = |frame: &[u8]| {
+            Json::parse(std::str::from_utf8(frame).unwrap())
+                .ok()
+                .unwrap()
+        };
+        (parse(&frames[2]), parse(&frames[3]), parse(&frames[5]))
+    }
+
+    /// Reads a frame, and returns its flags and its body.
+    fn read_frame(&mut self) -> (u8, Vec<u8>) {
+        let mut flags = [0];
+        self.stream.read_exact(&mut flags).unwrap();
+        let size = if flags[0] & 0x02 != 0 {
+            let mut size = [0; 8];
+            self.stream.read_exact(&mut size).unwrap();
+            u64::from_be_bytes(size) as usize
+        } else {
+            let mut size = [0];
+            self.stream.read_exact(&mut size).unwrap();
+            size[0] as usize
+        };
+
+        let mut body = vec![0; size];
+        self.stream.read_exact(&mut body).unwrap();
+        (flags[0], body)
+    }
+
+    /// Writes a frame with the given flags.
+    ///
+    /// # Arguments
+    /// `flags` - The flags of the frame. The flag for the size is added if it is needed.
+    /// `body` - The body of the frame.
+    fn write_frame(&mut self, flags: u8, body: &[u8]) {
+        let mut frame = vec![];
+        if body.len() > u8::MAX as usize {
+            frame.push(flags | 0x02);
+            frame.extend_from_slice(&(body.len() as u64).to_be_bytes());
+        } else {
+            frame.push(flags);
+            frame.push(body.len() as u8);
+        }
+
+        frame.extend_from_slice(body);
+        self.stream.write_all(&frame).unwrap();
+    }
+}
+
+/// Returns the signature of the parts of a message, as hex.
+///
+/// # Arguments
+/// `parts` - The header, the parent header, the metadata, and the content of the message.
+#[cfg(feature = "jupyter")]
+fn sign(parts: &[Vec<u8>]) -> String {
+    use hmac::{Hmac, Mac};
+
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(KEY.as_bytes()).unwrap();
+    for part in parts {
+        mac.update(part);
+    }
+
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Without the `jupyter` feature, the key is empty, so messages are not signed.
+#[cfg(not(feature = "jupyter"))]
+fn sign(_: &[Vec<u8>]) -> String {
+    String::new()
+}
+
+/// Returns the content of an execute_request for the code.
+///
+/// # Arguments
+/// `code` - The code of the cell.
+fn execute(code: &str) -> Json {
+    Json::object(vec![
+        ("code", code.into()),
+        ("silent", Json::Boolean(false)),
+    ])
+}
+
+#[test]
+fn the_kernel_answers_after_the_handshake() {
+    let kernel = Kernel::start("info");
+    let mut shell = kernel.shell();
+    let request = shell.send("kernel_info_request", Json::Object(vec![]), None);
+
+    let (header, parent, content) = shell.receive();
+    assert_eq!(
+        header.get("msg_type").and_then(Json::as_str),
+        Some("kernel_info_reply")
+    );
+    assert_eq!(parent.get("msg_id"), request.get("msg_id"));
+    assert_eq!(content.get("status").and_then(Json::as_str), Some("ok"));
+    assert_eq!(
+        content.get("implementation").and_then(Json::as_str),
+        Some("dark-vm")
+    );
+}
+
+#[test]
+fn cells_are_executed_by_the_same_vm() {
+    let kernel = Kernel::start("execute");
+    let mut shell = kernel.shell();
+
+    shell.send("execute_request", execute("set x 20 push x"), None);
+    let (header, _, content) = shell.receive();
+    assert_eq!(
+        header.get("msg_type").and_then(Json::as_str),
+        Some("execute_reply")
+    );
+    assert_eq!(content.get("status").and_then(Json::as_str), Some("ok"));
+    assert_eq!(content.get("execution_count"), Some(&Json::Int(1)));
+
+    // The globals are kept between cells, and errors point into the cell that caused them.
+    shell.send("execute_request", execute("push x\npop pop pop"), None);
+    let (_, _, content) = shell.receive();
+    assert_eq!(content.get("status").and_then(Json::as_str), Some("error"));
+    assert_eq!(content.get("execution_count"), Some(&Json::Int(2)));
+    assert_eq!(
+        content.get("ename").and_then(Json::as_str),
+        Some("EmptyStack")
+    );
+    let traceback = content.get("traceback").unwrap().to_string();
+    assert!(traceback.contains("2 | pop pop pop"), "{}", traceback);
+}
+
+#[test]
+fn requests_with_a_bad_signature_are_dropped() {
+    let kernel = Kernel::start("signature");
+    let mut shell = kernel.shell();
+
+    shell.send("execute_request", execute("push 1"), Some(&"0".repeat(64)));
+    shell.send("execute_request", execute("push 1"), Some("not hex"));
+    let request = shell.send("kernel_info_request", Json::Object(vec![]), None);
+
+    // The first reply belongs to the only request that was signed correctly.
+    let (header, parent, _) = shell.receive();
+    assert_eq!(
+        header.get("msg_type").and_then(Json::as_str),
+        Some("kernel_info_reply")
+    );
+    assert_eq!(parent.get("msg_id"), request.get("msg_id"));
+}
@@ -0,0 +1,21 @@
+use dark_vm::{ErrorKind, Lexer};
+
+#[test]
+fn a_shebang_line_at_the_start_is_skipped() {
+    let tokens = Lexer::default()
+        .lex("#!/usr/bin/env dark\n@main push 1 end")
+        .ok()
+        .unwrap();
+    assert_eq!(tokens.len(), 4);
+
+    // The characters of the shebang line still count, so the label starts after it.
+    assert_eq!(tokens[0].pos, 21);
+}
+
+#[test]
+fn errors_after_a_shebang_line_report_the_line_of_the_editor() {
+    let source = "#!/usr/bin/env dark\n@main\n    push x\nend\n";
+    let error = dark_vm::run_captured(source).err().unwrap();
+    assert!(matches!(error.get_kind(), ErrorKind::UndefinedVariable(..)));
+    assert!(error.prettify(source).starts_with("  |\n3 |     push x\n"));
+}
@@ -0,0 +1,69 @@
+use dark_vm::{ErrorKind, Lexer, VMBuilder};
+
+/// Runs the program with the VM built by the given builder, and returns the kind of the first error, if there is one.
+fn run(contents: &str, builder: VMBuilder) -> Result<(), ErrorKind> {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    let mut vm = builder
+        .build(tokens)
+        .map_err(|error| error.get_kind().clone())?;
+    vm.run()
+        .into_result()
+        .map(|_| ())
+        .map_err(|error| error.get_kind().clone())
+}
+
+#[test]
+fn strings_are_limited() {
+    let builder = || VMBuilder::default().max_string_len(4);
+    assert_eq!(run("@main push 'abcd' end", builder()), Ok(()));
+    assert_eq!(
+        run("@main push 'abcde' end", builder()),
+        Err(ErrorKind::StringLimitExceeded(4))
+    );
+    assert_eq!(
+        run("@main push 'ab' push 'cde' push add end", builder()),
+        Err(ErrorKind::StringLimitExceeded(4))
+    );
+    assert_eq!(
+        run("@main push b64decode 'aGVsbG8=' end", builder()),
+        Err(ErrorKind::StringLimitExceeded(4))
+    );
+}
+
+#[test]
+fn repeating_a_string_is_checked_before_it_is_built() {
+    assert_eq!(
+        run(
+            "@main push 'ab' push 9223372036854775807 push mul end",
+            VMBuilder::default().max_string_len(1024)
+        ),
+        Err(ErrorKind::StringLimitExceeded(1024))
+    );
+}
+
+#[test]
+fn arrays_and_maps_are_limited() {
+    let builder = || VMBuilder::default().max_array_len(2);
+    assert_eq!(run("@main push apush apush arr 1 2 end", builder()), Ok(()));
+    assert_eq!(
+        run("@main push apush apush apush arr 1 2 3 end", builder()),
+        Err(ErrorKind::ArrayLimitExceeded(2))
+    );
+    assert_eq!(
+        run(
+            r#"@main push jsonparse '{"a": 1, "b": 2, "c": 3}' end"#,
+            builder()
+        ),
+        Err(ErrorKind::ArrayLimitExceeded(2))
+    );
+}
+
+#[test]
+fn nesting_is_limited() {
+    let builder = || VMBuilder::default().max_value_depth(2);
+    assert_eq!(run("@main push jsonparse '[[1]]' end", builder()), Ok(()));
+    assert_eq!(
+        run(r#"@main push jsonparse '[{"a": [1]}]' end"#, builder()),
+        Err(ErrorKind::ValueDepthExceeded(2))
+    );
+}
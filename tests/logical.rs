@@ -0,0 +1,71 @@
+use dark_vm::{
+    builder::VMBuilder, errors::error_kind::ErrorKind, lexer::Lexer, values::value_kinds::ValueKind,
+};
+use std::io;
+
+/// Runs a program that pushes the value of the expression, and returns the kind of that value, or the kind of the first error.
+fn evaluate(expression: &str) -> Result<ValueKind, ErrorKind> {
+    let contents = format!("@main push {} end", expression);
+    let tokens = Lexer::default()
+        .lex(&contents)
+        .map_err(|error| error.get_kind().clone())?;
+    // The output is discarded, since some of the expressions print.
+    let mut vm = VMBuilder::default()
+        .stdout(io::sink())
+        .build(tokens)
+        .map_err(|error| error.get_kind().clone())?;
+    vm.run()
+        .into_result()
+        .map_err(|error| error.get_kind().clone())?;
+    Ok(vm.operand_stack.0.pop().unwrap().kind.clone())
+}
+
+#[test]
+fn and_and_or_use_truthiness() {
+    let cases = [
+        ("and true 1", true),
+        ("and true 0", false),
+        ("and 'a' 2.5", true),
+        ("or 0 ''", false),
+        ("or 0.0 'a'", true),
+        ("or false 2.5", true),
+    ];
+    for (expression, expected) in &cases {
+        assert_eq!(
+            evaluate(expression),
+            Ok(ValueKind::Boolean(*expected)),
+            "{}",
+            expression
+        );
+    }
+}
+
+#[test]
+fn not_negates_truthiness() {
+    assert_eq!(evaluate("not ''"), Ok(ValueKind::Boolean(true)));
+    assert_eq!(evaluate("not 3"), Ok(ValueKind::Boolean(false)));
+}
+
+#[test]
+fn logical_instructions_nest() {
+    assert_eq!(
+        evaluate("or lt 12 0 gt 12 10"),
+        Ok(ValueKind::Boolean(true))
+    );
+    assert_eq!(
+        evaluate("and not false eq 1 1"),
+        Ok(ValueKind::Boolean(true))
+    );
+}
+
+#[test]
+fn void_operands_are_an_error() {
+    assert!(matches!(
+        evaluate("and true printn 1"),
+        Err(ErrorKind::ValueMismatch(..))
+    ));
+    assert!(matches!(
+        evaluate("not printn 1"),
+        Err(ErrorKind::ValueMismatch(..))
+    ));
+}
@@ -0,0 +1,105 @@
+use dark_vm::{ErrorKind, ValueKind};
+
+mod common;
+
+use common::run;
+
+#[test]
+fn if_runs_one_of_its_branches() {
+    assert_eq!(
+        run("@main push true if push 1 else push 2 endif end"),
+        Ok(vec![ValueKind::Int(1)])
+    );
+    assert_eq!(
+        run("@main push false if push 1 else push 2 endif end"),
+        Ok(vec![ValueKind::Int(2)])
+    );
+    assert_eq!(run("@main push false if push 1 endif end"), Ok(vec![]));
+}
+
+#[test]
+fn while_repeats_until_its_condition_is_false() {
+    assert_eq!(
+        run("@main set i 0 while push lt i 3 do push i incvar i 1 endwhile end"),
+        Ok(vec![
+            ValueKind::Int(0),
+            ValueKind::Int(1),
+            ValueKind::Int(2)
+        ])
+    );
+}
+
+#[test]
+fn break_and_continue_leave_and_restart_the_loop() {
+    assert_eq!(
+        run("@main set i 0 while push true do incvar i 1 push eq i 2 if continue endif push gt i 4 if break endif push i endwhile end"),
+        Ok(vec![ValueKind::Int(1), ValueKind::Int(3), ValueKind::Int(4)])
+    );
+}
+
+#[test]
+fn absolute_jumps_after_an_if_land_on_the_same_instruction() {
+    // The jmp skips push 2 and lands on push 3, which is the token at index 11 as written.
+    assert_eq!(
+        run("@main push true if push 1 endif jmp 11 push 2 push 3 end"),
+        Ok(vec![ValueKind::Int(1), ValueKind::Int(3)])
+    );
+}
+
+#[test]
+fn absolute_jumps_before_an_if_land_on_the_same_instruction() {
+    // The jmp skips the first if and lands on the push true before the second one, which is the token at index 9 as written.
+    assert_eq!(
+        run("@main jmp 9 push true if push 1 endif push true if push 2 else push 3 endif end"),
+        Ok(vec![ValueKind::Int(2)])
+    );
+}
+
+#[test]
+fn relative_jumps_across_an_if_land_on_the_same_instruction() {
+    // The rjmp skips the whole if and lands on push 3.
+    assert_eq!(
+        run("@main rjmp 10 push true if push 1 else push 2 endif push 3 end"),
+        Ok(vec![ValueKind::Int(3)])
+    );
+}
+
+#[test]
+fn relative_jumps_across_a_while_land_on_the_same_instruction() {
+    // The first rjmp skips forward over the while to push 3, which jumps back over it to push 2, and the while is then jumped over to the end.
+    assert_eq!(
+        run("@main push 1 rjmp 10 push 2 while push false do endwhile rjmp 5 push 3 rjmp -12 end"),
+        Ok(vec![
+            ValueKind::Int(1),
+            ValueKind::Int(3),
+            ValueKind::Int(2)
+        ])
+    );
+}
+
+#[test]
+fn break_only_leaves_the_innermost_loop() {
+    assert_eq!(
+        run("@main set i 0 while push lt i 2 do incvar i 1 set j 0 while push true do incvar j 1 push gt j 2 if break endif push j endwhile endwhile end"),
+        Ok(vec![
+            ValueKind::Int(1),
+            ValueKind::Int(2),
+            ValueKind::Int(1),
+            ValueKind::Int(2)
+        ])
+    );
+}
+
+#[test]
+fn break_and_continue_outside_of_a_loop_are_errors() {
+    assert_eq!(run("@main break end"), Err(ErrorKind::BreakOutsideLoop));
+    assert_eq!(
+        run("@main push true if continue endif end"),
+        Err(ErrorKind::ContinueOutsideLoop)
+    );
+    // The loop must be in the same label as the break instruction.
+    assert_eq!(
+        run("@main while push true do call leave endwhile end @leave break end"),
+        Err(ErrorKind::BreakOutsideLoop)
+    );
+}
@@ -0,0 +1,166 @@
+use dark_vm::utils::json::Json;
+use std::{
+    env,
+    io::{BufRead, Read, Write},
+    process::{Command, Stdio},
+};
+
+/// Sends the messages to a language server in its own process, and returns every message that it sent back, once it exits.
+fn serve(messages: Vec<Json>) -> Vec<Json> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dark-vm"))
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    for message in messages {
+        let content = message.to_string();
+        write!(
+            stdin,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        )
+        .unwrap();
+    }
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let mut reader = output.stdout.as_slice();
+    let mut received = vec![];
+    loop {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).unwrap() == 0 {
+                return received;
+            }
+
+            match header.trim() {
+                "" => break,
+                header => {
+                    if let Some(value) = header.strip_prefix("Content-Length:") {
+                        length = value.trim().parse().ok();
+                    }
+                }
+            }
+        }
+
+        let mut content = vec![0; length.unwrap()];
+        reader.read_exact(&mut content).unwrap();
+        received.push(
+            Json::parse(&String::from_utf8(content).unwrap())
+                .ok()
+                .unwrap(),
+        );
+    }
+}
+
+/// Builds a request, or a notification if it has no id.
+fn message(id: Option<i64>, method: &str, params: Json) -> Json {
+    let mut entries = vec![
+        ("jsonrpc", "2.0".into()),
+        ("method", method.into()),
+        ("params", params),
+    ];
+    if let Some(id) = id {
+        entries.push(("id", id.into()));
+    }
+
+    Json::object(entries)
+}
+
+/// Builds the parameters of a request about the given position in the document.
+fn at(line: i64, character: i64) -> Json {
+    Json::object(vec![
+        (
+            "textDocument",
+            Json::object(vec![("uri", "file:///main.dark".into())]),
+        ),
+        (
+            "position",
+            Json::object(vec![("line", line.into()), ("character", character.into())]),
+        ),
+    ])
+}
+
+/// Finds the response to the request with the given id.
+fn response(messages: &[Json], id: i64) -> &Json {
+    messages
+        .iter()
+        .find(|message| message.get("id").and_then(Json::as_int) == Some(id))
+        .unwrap()
+}
+
+#[test]
+fn the_server_reports_errors_and_finds_labels() {
+    let text = "@main\n  call greet\n  push\nend\n\n@greet\n  printn \"hi\"\nend\n";
+    let document = Json::object(vec![
+        ("uri", "file:///main.dark".into()),
+        ("languageId", "dark".into()),
+        ("version", 1i64.into()),
+        ("text", text.into()),
+    ]);
+    let messages = serve(vec![
+        message(Some(1), "initialize", Json::object(vec![])),
+        message(None, "initialized", Json::object(vec![])),
+        message(
+            None,
+            "textDocument/didOpen",
+            Json::object(vec![("textDocument", document)]),
+        ),
+        message(Some(2), "textDocument/definition", at(1, 9)),
+        message(Some(3), "textDocument/hover", at(2, 3)),
+        message(Some(4), "shutdown", Json::Null),
+        message(None, "exit", Json::Null),
+    ]);
+
+    let capabilities = response(&messages, 1)
+        .get("result")
+        .and_then(|result| result.get("capabilities"))
+        .unwrap();
+    assert_eq!(
+        capabilities
+            .get("definitionProvider")
+            .and_then(Json::as_bool),
+        Some(true)
+    );
+
+    // The push on line 3 has no argument, so it is reported there.
+    let diagnostics = messages
+        .iter()
+        .find(|message| {
+            message.get("method").and_then(Json::as_str) == Some("textDocument/publishDiagnostics")
+        })
+        .and_then(|message| message.get("params"))
+        .and_then(|params| params.get("diagnostics"))
+        .and_then(Json::as_array)
+        .unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    let line = diagnostics[0]
+        .get("range")
+        .and_then(|range| range.get("start"))
+        .and_then(|start| start.get("line"))
+        .and_then(Json::as_int);
+    assert_eq!(line, Some(2));
+
+    // The definition of greet is the label on line 6.
+    let line = response(&messages, 2)
+        .get("result")
+        .and_then(|result| result.get("range"))
+        .and_then(|range| range.get("start"))
+        .and_then(|start| start.get("line"))
+        .and_then(Json::as_int);
+    assert_eq!(line, Some(5));
+
+    let hover = response(&messages, 3)
+        .get("result")
+        .and_then(|result| result.get("contents"))
+        .and_then(|contents| contents.get("value"))
+        .and_then(Json::as_str)
+        .unwrap();
+    assert!(hover.contains("push"), "{}", hover);
+}
@@ -0,0 +1,52 @@
+use dark_vm::{builder::VMBuilder, lexer::Lexer, vm::VM};
+
+/// Builds a VM for the program.
+fn build(contents: &str) -> VM {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    VMBuilder::default().build(tokens).ok().unwrap()
+}
+
+#[test]
+fn the_machine_shows_the_stack_and_the_frames() {
+    let mut vm = build("@main set x 'a' push 1 push 2.5 call greet 2 end @greet #n push n end");
+    // Stops before the end of greet, once it has pushed its parameter.
+    for _ in 0..5 {
+        vm.step().ok().unwrap();
+    }
+
+    assert_eq!(
+        vm.to_string(),
+        "Instruction Pointer: 15 (Next: end At Position 67)
+Current Label: @greet
+Operand Stack (3 Values, Top First):
+    0: Int 2
+    1: Float 2.5
+    2: Int 1
+Frames (2 Frames, Innermost First):
+    @greet (Returns To 11)
+        n = Int 2
+    @main
+        x = String \"a\"
+"
+    );
+}
+
+#[test]
+fn the_machine_truncates_long_lists_and_values() {
+    let contents = format!(
+        "@main {} push '{}' end",
+        "push 1 ".repeat(20),
+        "a".repeat(100)
+    );
+    let mut vm = build(&contents);
+    assert!(vm.run().is_success());
+    let machine = vm.to_string();
+    assert!(machine.starts_with("Instruction Pointer: None (Finished)\nCurrent Label: None\n"));
+    assert!(machine.contains("Operand Stack (21 Values, Top First):\n"));
+    assert!(machine.contains(&format!(
+        "    0: String \"{}... (102 Characters)\n",
+        "a".repeat(59)
+    )));
+    assert!(machine.contains("    15: Int 1\n    ... And 5 More\n"));
+    assert!(machine.ends_with("Frames (0 Frames, Innermost First):\n"));
+}
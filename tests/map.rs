@@ -0,0 +1,49 @@
+use dark_vm::{errors::error_kind::ErrorKind, values::value_kinds::ValueKind};
+
+mod common;
+
+use common::run;
+
+#[test]
+fn keys_can_be_set_read_and_deleted() {
+    assert_eq!(
+        run(r#"@main
+            set ages mset mset mnew "ada" 36 "alan" 41
+            push mget ages "alan"
+            push mhas ages "grace"
+            set ages mdel ages "ada"
+            push mhas ages "ada"
+            push len ages
+        end"#),
+        Ok(vec![
+            ValueKind::Int(41),
+            ValueKind::Boolean(false),
+            ValueKind::Boolean(false),
+            ValueKind::Int(1),
+        ])
+    );
+}
+
+#[test]
+fn mkeys_returns_the_keys_in_sorted_order() {
+    let stack = run(r#"@main push mkeys mset mset mnew "b" 1 "a" 2 end"#).unwrap();
+    let keys = match &stack[..] {
+        [ValueKind::Array(keys)] => keys.iter().map(|key| key.kind.clone()).collect::<Vec<_>>(),
+        _ => panic!("Expected A Single Array, Found {:?}.", stack),
+    };
+    assert_eq!(
+        keys,
+        vec![
+            ValueKind::String("a".to_owned()),
+            ValueKind::String("b".to_owned())
+        ]
+    );
+}
+
+#[test]
+fn getting_a_missing_key_is_an_error() {
+    assert_eq!(
+        run(r#"@main push mget mnew "missing" end"#),
+        Err(ErrorKind::MissingKey("missing".to_owned()))
+    );
+}
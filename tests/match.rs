@@ -0,0 +1,87 @@
+use dark_vm::{ErrorKind, Lexer, VMBuilder};
+
+/// Builds a VM for the program, and returns the kind of the error that the verifier found, if there is one.
+fn verify(contents: &str) -> Option<ErrorKind> {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    VMBuilder::default()
+        .build(tokens)
+        .err()
+        .map(|error| error.get_kind().clone())
+}
+
+/// Runs the program, and returns everything that it printed.
+fn output(source: &str) -> String {
+    dark_vm::run_captured(source).ok().unwrap().1
+}
+
+/// The labels that the programs of these tests match into.
+const LABELS: &str =
+    "@go printn 'go' end @stop printn 'stop' end @unknown #command print 'unknown ' printn command end";
+
+#[test]
+fn the_first_equal_pattern_calls_its_label() {
+    for (value, printed) in &[
+        ("'go'", "go\n"),
+        ("'stop'", "stop\n"),
+        ("'jump'", "unknown jump\n"),
+    ] {
+        let program = format!(
+            "@main push {} match 'go' go 'stop' stop _ unknown endmatch end {}",
+            value, LABELS
+        );
+        assert_eq!(output(&program), *printed);
+    }
+
+    assert_eq!(
+        output(&format!(
+            "@main push 2 match 1 go 2 stop 2 go endmatch end {}",
+            LABELS
+        )),
+        "stop\n"
+    );
+}
+
+#[test]
+fn no_label_is_called_without_a_match() {
+    assert_eq!(
+        output(&format!(
+            "@main push 3 match 1 go 2.5 stop true go endmatch printn 'after' end {}",
+            LABELS
+        )),
+        "after\n"
+    );
+}
+
+#[test]
+fn the_value_is_popped() {
+    assert_eq!(
+        output(&format!(
+            "@main push 'left' push 'go' match 'go' go endmatch printn pop end {}",
+            LABELS
+        )),
+        "go\nleft\n"
+    );
+}
+
+#[test]
+fn malformed_matches_are_reported_when_the_program_is_loaded() {
+    assert_eq!(
+        verify(&format!("@main push 1 match 1 go end {}", LABELS)),
+        Some(ErrorKind::UnterminatedMatch)
+    );
+    assert_eq!(
+        verify("@main endmatch end"),
+        Some(ErrorKind::EndMatchWithoutMatch)
+    );
+    assert_eq!(
+        verify(&format!("@main push 1 match x go endmatch end {}", LABELS)),
+        Some(ErrorKind::ValueMismatch(
+            "Literal Or Wildcard".to_owned(),
+            "Identifier".to_owned()
+        ))
+    );
+    assert_eq!(
+        verify("@main push 1 match 1 pair endmatch end @pair #a #b end"),
+        Some(ErrorKind::TooManyParameters(1))
+    );
+}
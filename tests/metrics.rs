@@ -0,0 +1,59 @@
+use dark_vm::{builder::VMBuilder, lexer::Lexer, Metrics};
+use std::io;
+
+/// Runs the program with the VM counting into the given metrics, and returns the number of instructions that the run reported.
+fn run(contents: &str, builder: VMBuilder, metrics: &Metrics) -> u64 {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    let mut vm = builder
+        .stdout(io::sink())
+        .stderr(io::sink())
+        .metrics(metrics.clone())
+        .build(tokens)
+        .ok()
+        .unwrap();
+    vm.run().stats.instructions
+}
+
+#[test]
+fn metrics_count_instructions_calls_and_errors() {
+    let metrics = Metrics::default();
+    let instructions = run(
+        "@main call greet call greet end @greet printn 'hi' end",
+        VMBuilder::default(),
+        &metrics,
+    );
+    assert_eq!(metrics.get_instructions(), instructions);
+    assert_eq!(metrics.get_calls(), 2);
+    assert_eq!(metrics.get_errors(), 0);
+    assert_eq!(metrics.get_fuel_consumed(), 0);
+}
+
+#[test]
+fn metrics_are_shared_between_vms() {
+    let metrics = Metrics::default();
+    let first = run("@main push 1 end", VMBuilder::default().fuel(100), &metrics);
+    let second = run("@main assert false end", VMBuilder::default(), &metrics);
+    assert_eq!(metrics.get_instructions(), first + second);
+    assert_eq!(metrics.get_fuel_consumed(), first);
+    assert_eq!(metrics.get_errors(), 1);
+}
+
+#[test]
+fn metrics_are_written_in_the_prometheus_format() {
+    let metrics = Metrics::default();
+    run(
+        "@main call greet end @greet end",
+        VMBuilder::default(),
+        &metrics,
+    );
+    let output = metrics.to_prometheus();
+    assert!(output.contains("# TYPE dark_vm_calls_total counter\ndark_vm_calls_total 1\n"));
+    assert!(output.contains("dark_vm_errors_total 0\n"));
+    assert_eq!(
+        output
+            .lines()
+            .filter(|line| line.starts_with("# HELP"))
+            .count(),
+        4
+    );
+}
@@ -0,0 +1,107 @@
+use dark_vm::{Error, ErrorKind, Lexer, VMBuilder, Value, ValueKind, VM};
+use std::sync::Arc;
+
+mod common;
+
+/// Builds a VM whose programs can call add2, which adds its two int arguments, and fail, which always returns an error.
+fn builder() -> VMBuilder {
+    VMBuilder::default()
+        .native("add2", 2, |_, args| match (&args[0].kind, &args[1].kind) {
+            (ValueKind::Int(a), ValueKind::Int(b)) => {
+                Ok(Some(Value::new(args[0].pos, ValueKind::Int(a + b))))
+            }
+            _ => Ok(None),
+        })
+        .native("fail", 0, |_, _| {
+            Err(Error::message_only(ErrorKind::AssertionFailed))
+        })
+}
+
+/// Runs the program, and returns the kinds of the values left on the operand stack, from the bottom up, or the kind of the first error.
+fn run(builder: VMBuilder, contents: &str) -> Result<Vec<ValueKind>, ErrorKind> {
+    common::run_with(builder, contents)
+}
+
+#[test]
+fn natives_return_the_value_of_the_call() {
+    assert_eq!(
+        run(builder(), "@main push call add2 20 call add2 1 1 end"),
+        Ok(vec![ValueKind::Int(22)])
+    );
+}
+
+#[test]
+fn natives_take_the_place_of_labels() {
+    assert_eq!(
+        run(
+            builder(),
+            "@main push 1 callt add2 2 3 push 2 end @add2 #a #b push 0 end"
+        ),
+        Ok(vec![ValueKind::Int(1), ValueKind::Int(2)])
+    );
+}
+
+#[test]
+fn skipped_calls_skip_the_arguments_of_the_native() {
+    assert_eq!(
+        run(builder(), "@main push false callt add2 1 2 push 3 end"),
+        Ok(vec![ValueKind::Boolean(false), ValueKind::Int(3)])
+    );
+}
+
+#[test]
+fn errors_of_natives_stop_the_program() {
+    assert_eq!(
+        run(builder(), "@main call fail push 1 end"),
+        Err(ErrorKind::AssertionFailed)
+    );
+}
+
+#[test]
+fn calls_to_natives_are_verified() {
+    assert_eq!(
+        run(builder(), "@main call add2 1 end"),
+        Err(ErrorKind::ExpectedArgs(1))
+    );
+    assert_eq!(
+        run(VMBuilder::default(), "@main call add2 1 2 end"),
+        Err(ErrorKind::UndefinedLabel)
+    );
+}
+
+#[test]
+fn natives_can_change_the_vm() {
+    let builder = VMBuilder::default().native("pushtwice", 1, |vm: &mut VM, args| {
+        vm.operand_stack.push(Arc::clone(&args[0]));
+        vm.operand_stack.push(Arc::clone(&args[0]));
+        Ok(None)
+    });
+    assert_eq!(
+        run(builder, "@main call pushtwice 'a' end"),
+        Ok(vec![
+            ValueKind::String("a".to_owned()),
+            ValueKind::String("a".to_owned())
+        ])
+    );
+}
+
+#[test]
+fn natives_can_be_registered_on_the_vm() {
+    let mut vm = VMBuilder::default().build_repl().ok().unwrap();
+    vm.register_native("answer", 0, |_, _| {
+        Ok(Some(Value::new(0, ValueKind::Int(42))))
+    })
+    .ok()
+    .unwrap();
+    vm.load_tokens(Lexer::default().lex("push call answer").ok().unwrap())
+        .ok()
+        .unwrap();
+    vm.run().into_result().ok().unwrap();
+    assert_eq!(vm.operand_stack.0[0].kind, ValueKind::Int(42));
+
+    let result = vm.register_native("answer", 1, |_, _| Ok(None));
+    assert_eq!(
+        result.map_err(|error| error.get_kind().clone()),
+        Err(ErrorKind::NativeConflict("answer".to_owned()))
+    );
+}
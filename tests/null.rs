@@ -0,0 +1,41 @@
+use dark_vm::values::value_kinds::ValueKind;
+
+mod common;
+
+use common::run;
+
+#[test]
+fn null_can_be_pushed_and_stored() {
+    assert_eq!(
+        run("@main set missing null push missing push null end"),
+        Ok(vec![ValueKind::Null, ValueKind::Null])
+    );
+    assert_eq!(
+        run("@main set xs apush arr null push alen xs push aget xs 0 end"),
+        Ok(vec![ValueKind::Int(1), ValueKind::Null])
+    );
+}
+
+#[test]
+fn null_is_only_equal_to_itself() {
+    assert_eq!(
+        run(
+            r#"@main push eq null null push eq null 0 push eq null "" push neq null null push not null end"#
+        ),
+        Ok(vec![
+            ValueKind::Boolean(true),
+            ValueKind::Boolean(false),
+            ValueKind::Boolean(false),
+            ValueKind::Boolean(false),
+            ValueKind::Boolean(true),
+        ])
+    );
+}
+
+#[test]
+fn json_null_becomes_null() {
+    assert_eq!(
+        run(r#"@main push mget jsonparse '{"a": null}' "a" push jsonstringify null end"#),
+        Ok(vec![ValueKind::Null, ValueKind::String("null".to_owned())])
+    );
+}
@@ -0,0 +1,68 @@
+use dark_vm::{ErrorKind, Lexer, Shadowing, VMBuilder};
+
+#[test]
+fn a_finished_run_has_an_exit_code_of_zero() {
+    let tokens = Lexer::default()
+        .lex("@main printn 'a' push 1 end")
+        .ok()
+        .unwrap();
+    let outcome = VMBuilder::default()
+        .capture_output()
+        .build(tokens)
+        .ok()
+        .unwrap()
+        .run();
+    assert!(outcome.is_success());
+    assert_eq!(outcome.exit_code, 0);
+    assert!(outcome.warnings.is_empty());
+    assert_eq!(outcome.stats.instructions, 3);
+    assert_eq!(outcome.output.as_deref(), Some("a\n"));
+}
+
+#[test]
+fn a_failed_run_keeps_its_output() {
+    let tokens = Lexer::default()
+        .lex("@main printn 'before' push x printn 'after' end")
+        .ok()
+        .unwrap();
+    let outcome = VMBuilder::default()
+        .capture_output()
+        .build(tokens)
+        .ok()
+        .unwrap()
+        .run();
+    assert!(!outcome.is_success());
+    assert_eq!(outcome.exit_code, 1);
+    assert_eq!(outcome.output.as_deref(), Some("before\n"));
+    assert!(matches!(
+        outcome
+            .into_result()
+            .err()
+            .map(|error| error.get_kind().clone()),
+        Some(ErrorKind::UndefinedVariable(..))
+    ));
+}
+
+#[test]
+fn warnings_are_collected_in_the_outcome() {
+    let tokens = Lexer::default()
+        .lex("@main set x 1 begin set x 2 endblock end")
+        .ok()
+        .unwrap();
+    let outcome = VMBuilder::default()
+        .shadowing(Shadowing::Warn)
+        .build(tokens)
+        .ok()
+        .unwrap()
+        .run();
+    assert!(outcome.is_success());
+    assert_eq!(
+        outcome
+            .warnings
+            .iter()
+            .map(|warning| warning.get_kind().clone())
+            .collect::<Vec<_>>(),
+        vec![ErrorKind::ShadowedVariable("x".to_owned())]
+    );
+    assert_eq!(outcome.output, None);
+}
@@ -0,0 +1,140 @@
+use dark_vm::{builder::VMBuilder, errors::error_kind::ErrorKind, lexer::Lexer, LogLevel};
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    rc::Rc,
+};
+
+/// A stream that keeps what is written to it, so that it can be read after the VM is done with it.
+#[derive(Clone, Default)]
+struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+impl Buffer {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs the program with both streams written to buffers, and returns what was written to the output stream and to the error stream.
+fn run(contents: &str) -> (String, String) {
+    run_with(VMBuilder::default(), contents)
+}
+
+/// Runs the program like run, with the VM built by the given builder.
+fn run_with(builder: VMBuilder, contents: &str) -> (String, String) {
+    let (out, err) = (Buffer::default(), Buffer::default());
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    let mut vm = builder
+        .stdout(out.clone())
+        .stderr(err.clone())
+        .build(tokens)
+        .ok()
+        .unwrap();
+    assert!(vm.run().is_success());
+    (out.contents(), err.contents())
+}
+
+#[test]
+fn eprint_writes_to_the_error_stream() {
+    let (out, err) = run("@main printn 'out' eprint 'err' eprintn 1 end");
+    assert_eq!(out, "out\n");
+    assert_eq!(err, "err1\n");
+}
+
+#[test]
+fn captured_output_leaves_the_error_stream_alone() {
+    let err = Buffer::default();
+    let tokens = Lexer::default()
+        .lex("@main print 'a' eprintn 'b' printn 'c' end")
+        .ok()
+        .unwrap();
+    let mut vm = VMBuilder::default()
+        .capture_output()
+        .stderr(err.clone())
+        .build(tokens)
+        .ok()
+        .unwrap();
+    assert_eq!(vm.run().output, Some("ac\n".to_owned()));
+    assert_eq!(err.contents(), "b\n");
+}
+
+#[test]
+fn log_writes_the_level_before_the_message() {
+    let (out, err) = run("@main log info 'started' log error 42 end");
+    assert_eq!(out, "");
+    assert_eq!(err, "[INFO] started\n[ERROR] 42\n");
+}
+
+#[test]
+fn log_skips_messages_below_the_log_level() {
+    let contents = "@main log debug 'a' log info 'b' log warn 'c' end";
+    let (_, err) = run(contents);
+    assert_eq!(err, "[INFO] b\n[WARN] c\n");
+
+    let (_, err) = run_with(VMBuilder::default().log_level(LogLevel::Warn), contents);
+    assert_eq!(err, "[WARN] c\n");
+
+    let (_, err) = run_with(VMBuilder::default().log_level(LogLevel::Debug), contents);
+    assert_eq!(err, "[DEBUG] a\n[INFO] b\n[WARN] c\n");
+}
+
+#[test]
+fn log_rejects_unknown_levels() {
+    let tokens = Lexer::default().lex("@main log loud 'a' end").ok().unwrap();
+    let result = VMBuilder::default()
+        .stderr(io::sink())
+        .build(tokens)
+        .and_then(|mut vm| vm.run().into_result());
+    assert_eq!(
+        result.err().map(|error| error.get_kind().clone()),
+        Some(ErrorKind::UnknownLogLevel("loud".to_owned()))
+    );
+}
+
+#[test]
+fn dump_writes_the_stack_and_the_variables_to_stderr() {
+    let (out, err) = run("@main push 1 push 'two' set name 'DarkVM' dump printn pop end");
+    assert_eq!(out, "two\n");
+    assert_eq!(
+        err,
+        "Stack, From The Top Down:\n    0: \"two\"\n    1: 1\nVariables Of @main:\n    name = \"DarkVM\"\n"
+    );
+}
+
+#[test]
+fn dump_writes_an_empty_stack_and_frame() {
+    let (_, err) = run("@main dump end");
+    assert_eq!(
+        err,
+        "Stack, From The Top Down:\n    Empty\nVariables Of @main:\n    None\n"
+    );
+}
+
+#[test]
+fn writebytes_copies_the_input_to_the_output() {
+    let program = "@main set chunk readbytes 2 while push gt len chunk 0 do writebytes chunk set chunk readbytes 2 endwhile writebytes 'é' end";
+    let builder = VMBuilder::default().stdin(io::Cursor::new("hello\nworld".to_owned()));
+    let (out, _) = run_with(builder, program);
+    assert_eq!(out, "hello\nworldé");
+}
+
+#[test]
+fn binary_io_prints_bytes_as_their_raw_bytes() {
+    let program = "@main printn hexdecode '6869' end";
+    assert_eq!(run(program).0, "b\"hi\"\n");
+    assert_eq!(
+        run_with(VMBuilder::default().binary_io(), program).0,
+        "hi\n"
+    );
+}
@@ -0,0 +1,64 @@
+use dark_vm::{
+    builder::VMBuilder, errors::error_kind::ErrorKind, utils::overflow::Overflow,
+    values::value_kinds::ValueKind,
+};
+
+mod common;
+
+/// Runs the program with the given overflow mode and returns the kinds of the values left on the operand stack, from the bottom up.
+fn run(contents: &str, overflow: Overflow) -> Result<Vec<ValueKind>, ErrorKind> {
+    common::run_with(VMBuilder::default().overflow(overflow), contents)
+}
+
+#[test]
+fn ints_wrap_around_by_default() {
+    let program = format!(
+        "@main push 1 push {max} push add push 1 push {min} push sub push 2 push {max} push mul end",
+        max = i64::MAX,
+        min = i64::MIN
+    );
+    assert_eq!(
+        run(&program, Overflow::default()),
+        Ok(vec![
+            ValueKind::Int(i64::MIN),
+            ValueKind::Int(i64::MAX),
+            ValueKind::Int(-2),
+        ])
+    );
+}
+
+#[test]
+fn checked_mode_stops_at_the_instruction_that_overflowed() {
+    let program = format!("@main push 1 push {} push add end", i64::MAX);
+    assert_eq!(
+        run(&program, Overflow::Check),
+        Err(ErrorKind::IntegerOverflow)
+    );
+
+    // Results that fit are not affected.
+    assert_eq!(
+        run("@main push 1 push 2 push add end", Overflow::Check),
+        Ok(vec![ValueKind::Int(3)])
+    );
+}
+
+#[test]
+fn incvar_follows_the_overflow_mode() {
+    let program = format!("@main set n {} incvar n 1 push n end", i64::MAX);
+    assert_eq!(
+        run(&program, Overflow::Wrap),
+        Ok(vec![ValueKind::Int(i64::MIN)])
+    );
+    assert_eq!(
+        run(&program, Overflow::Check),
+        Err(ErrorKind::IntegerOverflow)
+    );
+}
+
+#[test]
+fn the_modes_are_parsed_by_name() {
+    assert_eq!("wrap".parse(), Ok(Overflow::Wrap));
+    assert_eq!("CHECK".parse(), Ok(Overflow::Check));
+    assert_eq!("saturate".parse::<Overflow>(), Err(()));
+    assert_eq!(Overflow::Check.to_string(), "check");
+}
@@ -0,0 +1,189 @@
+use dark_vm::{
+    builder::VMBuilder,
+    errors::error_kind::ErrorKind,
+    instructions::INSTRUCTIONS,
+    lexer::Lexer,
+    utils::{json::Json, overflow::Overflow},
+    verifier::MAX_NESTING,
+};
+use std::{io, panic};
+
+mod common;
+
+/// Lexes, loads, and runs the program, returning the kind of the first error.
+/// The output of the program is discarded, and the fuel is limited so that loops always end.
+fn run(contents: &str) -> Result<(), ErrorKind> {
+    run_with(contents, VMBuilder::default())
+}
+
+/// Lexes, loads, and runs the program with the options of the given builder, returning the kind of the first error.
+fn run_with(contents: &str, builder: VMBuilder) -> Result<(), ErrorKind> {
+    let builder = builder.fuel(1000).stdout(io::sink()).stderr(io::sink());
+    common::load_and_run(builder, contents)
+        .map(|_| ())
+        .map_err(|error| {
+            // The error must always be printable with respect to the input that produced it.
+            let _ = error.diagnose(contents);
+            error.get_kind().clone()
+        })
+}
+
+#[test]
+fn parameter_without_a_name_is_an_error() {
+    assert_eq!(run("@main #"), Err(ErrorKind::InvalidParameterName));
+    assert_eq!(run("@main # x end"), Err(ErrorKind::InvalidParameterName));
+    assert_eq!(run("@main #1 end"), Err(ErrorKind::InvalidParameterName));
+}
+
+#[test]
+fn integer_overflow_is_an_error_in_checked_mode() {
+    let max = i64::MAX;
+    let min = i64::MIN;
+    let programs = [
+        format!("@main push 1 push {} add end", max),
+        format!("@main push 1 push {} sub end", min),
+        format!("@main push 2 push {} mul end", max),
+        format!("@main push -1 push {} idiv end", min),
+        format!("@main push -1 push {} divmod end", min),
+        format!("@main push -1 push {} mod end", min),
+    ];
+
+    for program in &programs {
+        let checked = VMBuilder::default().overflow(Overflow::Check);
+        assert_eq!(
+            run_with(program, checked),
+            Err(ErrorKind::IntegerOverflow),
+            "{}",
+            program
+        );
+
+        // By default, the result wraps around instead.
+        assert_eq!(run(program), Ok(()), "{}", program);
+    }
+}
+
+#[test]
+fn mod_by_zero_is_an_error() {
+    assert_eq!(
+        run("@main push 0 push 5 mod end"),
+        Err(ErrorKind::DivisionByZero)
+    );
+}
+
+#[test]
+fn repeating_a_string_too_many_times_is_an_error() {
+    let program = format!("@main push {} push 'abc' mul end", i64::MAX);
+    assert_eq!(run(&program), Err(ErrorKind::StringTooLong));
+}
+
+#[test]
+fn deeply_nested_arguments_are_an_error() {
+    let program = format!("@main {}1 end", "push ".repeat(MAX_NESTING * 4));
+    assert_eq!(run(&program), Err(ErrorKind::NestingTooDeep(MAX_NESTING)));
+
+    let program = format!("@main {}1 end", "push ".repeat(MAX_NESTING));
+    assert_ne!(run(&program), Err(ErrorKind::NestingTooDeep(MAX_NESTING)));
+}
+
+#[test]
+fn every_instruction_can_be_nested_to_the_limit_on_a_test_thread() {
+    // The arguments are evaluated recursively, so this fails by overflowing the stack if the limit is too high for debug builds.
+    for instruction in INSTRUCTIONS
+        .iter()
+        .filter(|instruction| instruction.arity > 0)
+    {
+        let call = format!(
+            "{} {}",
+            instruction.name,
+            "1 ".repeat(instruction.arity - 1)
+        );
+        let program = format!("@main {}1 end", call.repeat(MAX_NESTING - 1));
+        let builder = VMBuilder::default()
+            .max_string_len(1 << 16)
+            .stdin(io::empty());
+        let _ = run_with(&program, builder);
+    }
+}
+
+#[test]
+fn relative_jump_to_the_minimum_integer_is_an_error() {
+    let program = format!("@main rjmp {} end", i64::MIN);
+    assert!(matches!(run(&program), Err(ErrorKind::OutOfBounds(_, _))));
+}
+
+#[test]
+fn deeply_nested_json_is_an_error() {
+    let document = "[".repeat(100_000);
+    assert!(Json::parse(&document).is_err());
+}
+
+#[test]
+fn stepping_after_the_program_finishes_does_not_panic() {
+    let tokens = Lexer::default().lex("@main push 1 end").ok().unwrap();
+    let mut vm = VMBuilder::default().build(tokens).ok().unwrap();
+    assert!(vm.run().is_success());
+    assert!(vm.step().is_ok());
+    assert!(vm.step().is_ok());
+}
+
+/// A small linear congruential generator, so that the generated programs are the same on every run.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as usize
+    }
+}
+
+#[test]
+fn generated_programs_do_not_panic() {
+    let mut vocabulary: Vec<String> = dark_vm::instructions::INSTRUCTIONS
+        .iter()
+        .map(|instruction| instruction.name.to_string())
+        .collect();
+    vocabulary.extend(
+        [
+            "0", "1", "-1", "2", "0.0", "-0.5", "1.5", "true", "false", "void", "any", "'a'",
+            "\"\"", "x", "y", "@main", "@f", "@g #x", "#", "#x", "end", "@", "'", "--", "-!", "!-",
+            "?", "debug", "info",
+        ]
+        .iter()
+        .map(|word| word.to_string()),
+    );
+    vocabulary.push(i64::MAX.to_string());
+    vocabulary.push(i64::MIN.to_string());
+
+    let mut rng = Rng(0x5EED);
+    for _ in 0..5000 {
+        let len = rng.next(24);
+        let mut program = String::from("@main ");
+        for _ in 0..len {
+            program.push_str(&vocabulary[rng.next(vocabulary.len())]);
+            program.push(if rng.next(8) == 0 { '\n' } else { ' ' });
+        }
+
+        let result = panic::catch_unwind(|| run(&program));
+        assert!(result.is_ok(), "The program panicked:\n{}", program);
+    }
+}
+
+#[test]
+fn deferred_labels_that_keep_failing_do_not_overflow_the_stack() {
+    // Every call of main defers main again and then fails, so unwinding the call stack runs deferred labels that fail in turn until the fuel runs out.
+    let tokens = Lexer::default().lex("@main defer main x end").unwrap();
+    let outcome = VMBuilder::default()
+        .fuel(10_000)
+        .build(tokens)
+        .unwrap()
+        .run();
+    assert!(matches!(
+        outcome
+            .into_result()
+            .map_err(|error| error.get_kind().clone()),
+        Err(ErrorKind::UndefinedVariable(..))
+    ));
+}
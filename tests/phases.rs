@@ -0,0 +1,46 @@
+use dark_vm::ErrorKind;
+
+#[test]
+fn each_phase_runs_on_its_own() {
+    let tokens = dark_vm::lex("@main push 40 push 2 push add end")
+        .ok()
+        .unwrap();
+    assert_eq!(tokens.len(), 8);
+    let code = dark_vm::load(tokens).ok().unwrap();
+    // The code is only loaded once, and every execution starts from the beginning.
+    for _ in 0..2 {
+        let outcome = dark_vm::execute(code.clone()).ok().unwrap();
+        assert!(outcome.is_success());
+        assert_eq!(outcome.exit_code, 0);
+        assert_eq!(outcome.stats.instructions, 4);
+    }
+}
+
+#[test]
+fn each_phase_reports_its_own_errors() {
+    assert_eq!(
+        dark_vm::lex("@main push 'a end")
+            .err()
+            .map(|error| error.get_kind().clone()),
+        Some(ErrorKind::UnterminatedString)
+    );
+
+    // The tokens are fine, so only loading finds that an argument is missing.
+    let tokens = dark_vm::lex("@main push end").ok().unwrap();
+    assert_eq!(
+        dark_vm::load(tokens)
+            .err()
+            .map(|error| error.get_kind().clone()),
+        Some(ErrorKind::ExpectedArgs(1))
+    );
+
+    let code = dark_vm::load(dark_vm::lex("@main push x end").ok().unwrap())
+        .ok()
+        .unwrap();
+    let outcome = dark_vm::execute(code).ok().unwrap();
+    assert_eq!(outcome.exit_code, 1);
+    assert!(matches!(
+        outcome.result.err().map(|error| error.get_kind().clone()),
+        Some(ErrorKind::UndefinedVariable(..))
+    ));
+}
@@ -0,0 +1,67 @@
+use dark_vm::{lexer::Lexer, utils::profiler::Profiler, vm::VM};
+
+/// The program that these tests profile. The greet label is called three times.
+const PROGRAM: &str = "@main times 3 greet end @greet push 1 pop end";
+
+/// Runs the program under a profiler that times one in the given number of steps.
+fn profile(sample_every: u64) -> Profiler {
+    let mut vm = VM::new(Lexer::default().lex(PROGRAM).ok().unwrap())
+        .ok()
+        .unwrap();
+    let mut profiler = Profiler::new(sample_every);
+    profiler.run(&mut vm).ok().unwrap();
+    profiler
+}
+
+#[test]
+fn every_instruction_is_counted() {
+    let profiler = profile(1);
+    let mut counts = profiler
+        .get_instructions()
+        .into_iter()
+        .map(|(name, profile)| {
+            // Every step is timed when sampling one in one steps.
+            assert_eq!(profile.samples, profile.count, "{}", name);
+            (name, profile.count)
+        })
+        .collect::<Vec<_>>();
+    counts.sort();
+    assert_eq!(
+        counts,
+        vec![("End", 4), ("pop", 3), ("push", 3), ("times", 1)]
+    );
+}
+
+#[test]
+fn the_histogram_lists_every_instruction() {
+    let histogram = profile(1).to_histogram();
+    let lines = histogram.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines[0],
+        "Instruction      Count   Total (us)   Avg (ns)   Share"
+    );
+    assert_eq!(lines.len(), 5);
+    assert!(!histogram.contains("Estimates"));
+}
+
+#[test]
+fn sampled_runs_are_marked_as_estimates() {
+    let profiler = profile(4);
+    assert!(profiler
+        .get_instructions()
+        .iter()
+        .all(|(_, profile)| profile.samples <= profile.count));
+    assert!(profiler
+        .to_histogram()
+        .ends_with("Of 11 Steps Were Timed, So The Times Are Estimates.\n"));
+}
+
+#[test]
+fn the_folded_stacks_name_every_call_stack() {
+    let stacks = profile(1)
+        .to_folded()
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().0.to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(stacks, vec!["main", "main;greet"]);
+}
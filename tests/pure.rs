@@ -0,0 +1,73 @@
+use dark_vm::{
+    code::Code,
+    errors::error_kind::ErrorKind,
+    lexer::Lexer,
+    tokens::{token::Token, token_kind::TokenKind},
+    values::value_kinds::ValueKind,
+};
+use std::collections::VecDeque;
+
+mod common;
+
+use common::run;
+
+#[test]
+fn a_pure_label_has_a_stack_of_its_own() {
+    assert_eq!(
+        run("@main push 'kept' call double 21 end @[pure] double #x push x push add end"),
+        Ok(vec![
+            ValueKind::String("kept".to_owned()),
+            ValueKind::Int(42)
+        ])
+    );
+}
+
+#[test]
+fn a_pure_label_that_pops_the_stack_of_its_caller_is_rejected() {
+    // Without the annotation, the label pops the value that main pushed.
+    assert_eq!(run("@main push 1 call take end @take pop end"), Ok(vec![]));
+    assert_eq!(
+        run("@main push 1 call take end @[pure] take pop end"),
+        Err(ErrorKind::EmptyStack)
+    );
+}
+
+#[test]
+fn a_label_may_be_named_pure() {
+    assert_eq!(
+        run("@main call pure end @pure push 1 end"),
+        Ok(vec![ValueKind::Int(1)])
+    );
+}
+
+#[test]
+fn unknown_and_misplaced_annotations_are_rejected() {
+    assert_eq!(
+        run("@main end @[memo] f end"),
+        Err(ErrorKind::UnknownAnnotation("memo".to_owned()))
+    );
+
+    // The lexer always puts an annotation in front of its label, but tokens may also be built by hand.
+    let tokens = VecDeque::from(vec![
+        Token::new(TokenKind::Annotation("pure".to_owned()), 1),
+        Token::new(TokenKind::End, 9),
+    ]);
+    assert_eq!(
+        Code::new(tokens)
+            .err()
+            .map(|error| error.get_kind().clone()),
+        Some(ErrorKind::MisplacedAnnotation)
+    );
+}
+
+#[test]
+fn the_annotation_is_kept_in_the_code() {
+    let tokens = Lexer::default()
+        .lex("@main end @[pure] double #x push x end")
+        .ok()
+        .unwrap();
+    let code = Code::new(tokens).ok().unwrap();
+    assert!(code.get_labels()["double"].pure);
+    assert!(!code.get_labels()["main"].pure);
+    assert!(code.to_string().contains("    @[pure] double #x (Values"));
+}
@@ -0,0 +1,106 @@
+use dark_vm::{ErrorKind, Trace, VMBuilder, ValueKind};
+use std::io::Cursor;
+
+mod common;
+
+/// Runs the program with the given input, and returns the kinds of the values left on the operand stack, from the bottom up, or the kind of the first error.
+fn run(contents: &str, input: &str) -> Result<Vec<ValueKind>, ErrorKind> {
+    run_with_trace(contents, input, Trace::record())
+}
+
+/// Runs the program with the given input and trace, and returns the kinds of the values left on the operand stack, from the bottom up, or the kind of the first error.
+fn run_with_trace(contents: &str, input: &str, trace: Trace) -> Result<Vec<ValueKind>, ErrorKind> {
+    common::run_with(
+        VMBuilder::default()
+            .stdin(Cursor::new(input.to_owned()))
+            .capture_output()
+            .trace(trace),
+        contents,
+    )
+}
+
+#[test]
+fn lines_are_read_until_the_input_ends() {
+    assert_eq!(
+        run(
+            "@main push read push read push read push read end",
+            "ada\r\n\nlovelace"
+        ),
+        Ok(vec![
+            ValueKind::String("ada".to_owned()),
+            ValueKind::String("".to_owned()),
+            ValueKind::String("lovelace".to_owned()),
+            ValueKind::Null,
+        ])
+    );
+}
+
+#[test]
+fn numbers_are_parsed_from_lines() {
+    assert_eq!(
+        run(
+            "@main push readint push readfloat push readint end",
+            " 42 \n2.5\n"
+        ),
+        Ok(vec![
+            ValueKind::Int(42),
+            ValueKind::Float(2.5),
+            ValueKind::Null,
+        ])
+    );
+    assert_eq!(
+        run("@main push readint end", "forty two\n"),
+        Err(ErrorKind::InvalidInput(
+            "forty two".to_owned(),
+            "Int".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn the_rest_of_the_input_can_still_be_read_as_bytes() {
+    assert_eq!(
+        run("@main push read push readbytes 10 end", "first\nrest"),
+        Ok(vec![
+            ValueKind::String("first".to_owned()),
+            ValueKind::Bytes(b"rest".to_vec()),
+        ])
+    );
+}
+
+#[test]
+fn recorded_lines_are_replayed_without_the_input() {
+    let contents = "@main push read push readint push readfloat push read end";
+    let trace = Trace::record();
+    let recorded = run_with_trace(contents, "ada\n42\n2.5", trace.clone());
+    assert_eq!(
+        recorded,
+        Ok(vec![
+            ValueKind::String("ada".to_owned()),
+            ValueKind::Int(42),
+            ValueKind::Float(2.5),
+            ValueKind::Null,
+        ])
+    );
+    assert_eq!(trace.get_len(), 4);
+
+    let replay = Trace::replay(&trace.to_json()).ok().unwrap();
+    assert_eq!(run_with_trace(contents, "", replay), recorded);
+}
+
+#[test]
+fn recorded_bytes_are_replayed_without_the_input() {
+    let contents = "@main push readbytes 3 push readbytes 10 end";
+    let trace = Trace::record();
+    let recorded = run_with_trace(contents, "\u{0}\u{7f}\nrest", trace.clone());
+    assert_eq!(
+        recorded,
+        Ok(vec![
+            ValueKind::Bytes(vec![0, 127, b'\n']),
+            ValueKind::Bytes(b"rest".to_vec()),
+        ])
+    );
+
+    let replay = Trace::replay(&trace.to_json()).ok().unwrap();
+    assert_eq!(run_with_trace(contents, "", replay), recorded);
+}
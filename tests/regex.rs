@@ -0,0 +1,57 @@
+#![cfg(feature = "regex")]
+
+use dark_vm::{ErrorKind, Lexer, VMBuilder, ValueKind};
+
+/// Runs a program that pushes the value of the expression, written as JSON, and returns it, or the kind of the first error.
+fn evaluate(expression: &str) -> Result<String, ErrorKind> {
+    let contents = format!("@main push jsonstringify {} end", expression);
+    let tokens = Lexer::default().lex(&contents).ok().unwrap();
+    let mut vm = VMBuilder::default()
+        .build(tokens)
+        .map_err(|error| error.get_kind().clone())?;
+    vm.run()
+        .into_result()
+        .map_err(|error| error.get_kind().clone())?;
+    match &vm.operand_stack.0.pop().unwrap().kind {
+        ValueKind::String(json) => Ok(json.to_owned()),
+        kind => panic!("unexpected value {:?}", kind),
+    }
+}
+
+#[test]
+fn rmatch_checks_for_a_match_anywhere() {
+    assert_eq!(
+        evaluate(r#"rmatch '^[0-9]+$' '123'"#),
+        Ok("true".to_owned())
+    );
+    assert_eq!(
+        evaluate(r#"rmatch '^[0-9]+$' '12a'"#),
+        Ok("false".to_owned())
+    );
+    assert_eq!(evaluate(r#"rmatch 'b' 'abc'"#), Ok("true".to_owned()));
+}
+
+#[test]
+fn rfind_returns_every_match() {
+    assert_eq!(
+        evaluate(r#"rfind '[0-9]+' 'a1 b22 c333'"#),
+        Ok(r#"["1","22","333"]"#.to_owned())
+    );
+    assert_eq!(evaluate(r#"rfind '[0-9]+' 'abc'"#), Ok("[]".to_owned()));
+}
+
+#[test]
+fn rreplace_replaces_every_match_with_groups() {
+    assert_eq!(
+        evaluate(r#"rreplace '(\w+)@(\w+)' 'me@host you@there' '$2 at $1'"#),
+        Ok(r#""host at me there at you""#.to_owned())
+    );
+}
+
+#[test]
+fn invalid_patterns_are_errors() {
+    assert!(matches!(
+        evaluate("rmatch '(' 'a'"),
+        Err(ErrorKind::InvalidRegex(_))
+    ));
+}
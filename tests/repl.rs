@@ -0,0 +1,92 @@
+use std::{
+    env, fs,
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+/// Starts the REPL, enters the given lines, and returns what it wrote once the input ends.
+///
+/// # Arguments
+/// `lines` - The lines that are entered, each ending in a new line.
+fn repl(lines: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dark-vm"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(lines.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn the_stack_and_the_variables_are_kept_between_inputs() {
+    let output = repl("set x 41\npush 1\npush x\nadd\n:quit\n");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "DarkVM REPL. Type ':quit' to exit.\n> > > > 42\n> "
+    );
+}
+
+#[test]
+fn labels_are_kept_and_can_be_defined_again() {
+    let output = repl(
+        "@show #n\n  printn n\nend\ncall show 1\n@show #n\n  print 'new '\n  printn n\nend\ncall show 2\n",
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "DarkVM REPL. Type ':quit' to exit.\n> . . > 1\n> . . . > new 2\n> "
+    );
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn errors_do_not_end_the_session() {
+    let output = repl("push y\nset y 1\npush y\ny\n:bogus\n");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("> > > > 1\n> > "));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Tried To Use The Variable 'y'"));
+    assert!(stderr.contains("Unknown Command ':bogus'."));
+}
+
+#[test]
+fn sessions_are_saved_and_loaded() {
+    let path = env::temp_dir().join(format!("dark-vm-repl-{}-session.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    let output = repl(&format!(
+        "set name 'Ada'\nset raw hexdecode 'ff'\n@greet #who\n  printn who\nend\n:save {}\n",
+        path
+    ));
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("The Global 'raw' Has No JSON Form, So It Was Not Saved.\n"));
+
+    let output = repl(&format!(":load {}\ncall greet name\n:history\n", path));
+    fs::remove_file(path).unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "DarkVM REPL. Type ':quit' to exit.\n> > Ada\n> 1: set name 'Ada'\n2: set raw hexdecode 'ff'\n3: @greet #who\n  printn who\nend\n4: call greet name\n> "
+    );
+}
+
+#[test]
+fn loading_a_missing_session_is_an_error() {
+    let output = repl(":load /nonexistent/session.json\n1\n");
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("> > 1\n> "));
+    assert!(!output.stderr.is_empty());
+}
+
+#[test]
+fn errors_point_to_the_line_of_the_input() {
+    let output = repl("@one\n  push 1\nend\npop\n");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "  |\n1 | pop\n  | ^-- Tried To Pop From An Empty Stack.\nRecently Executed Instructions, Oldest First:\n    1:1 pop (Stack Depth 0)\n"
+    );
+}
@@ -0,0 +1,29 @@
+use dark_vm::ErrorKind;
+
+#[test]
+fn the_output_is_captured_instead_of_printed() {
+    let (_, output) = dark_vm::run_captured(
+        "@main print 'a' printn 1 call greet 'Ada' end @greet #name printn name end",
+    )
+    .ok()
+    .unwrap();
+    assert_eq!(output, "a1\nAda\n");
+}
+
+#[test]
+fn errors_are_returned_from_every_phase() {
+    let kind = |source| {
+        dark_vm::run_captured(source)
+            .err()
+            .map(|error| error.get_kind().clone())
+    };
+    assert_eq!(
+        kind("@main push 'a end"),
+        Some(ErrorKind::UnterminatedString)
+    );
+    assert_eq!(kind("@main push end"), Some(ErrorKind::ExpectedArgs(1)));
+    assert!(matches!(
+        kind("@main printn 'a' push x end"),
+        Some(ErrorKind::UndefinedVariable(..))
+    ));
+}
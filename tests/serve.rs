@@ -0,0 +1,125 @@
+use dark_vm::utils::json::Json;
+use std::{
+    env,
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    process::{Child, ChildStdout, Command, Stdio},
+};
+
+/// The Server struct maintains a running `serve` command, which is killed when the test ends.
+/// Its output is kept open, so that it can still print about the connections that end.
+struct Server {
+    child: Child,
+    _output: BufReader<ChildStdout>,
+    address: String,
+}
+
+impl Server {
+    /// Starts the server on a free port, and waits until it is listening.
+    fn start() -> Server {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_dark-vm"))
+            .args(["serve", "--listen", "127.0.0.1:0"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut output = BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        output.read_line(&mut line).unwrap();
+        let address = line
+            .trim_start_matches("Listening For Evaluation Requests On ")
+            .trim_end()
+            .trim_end_matches('.')
+            .to_owned();
+        Server {
+            child,
+            _output: output,
+            address,
+        }
+    }
+
+    /// Opens a connection to the server.
+    fn connect(&self) -> Connection {
+        let stream = TcpStream::connect(&self.address).unwrap();
+        Connection {
+            reader: BufReader::new(stream.try_clone().unwrap()),
+            writer: stream,
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// The Connection struct maintains a single connection to the server.
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    /// Sends the source as a request with the given id, and returns the response.
+    fn evaluate(&mut self, id: i64, source: &str) -> Json {
+        let request = Json::object(vec![("id", Json::Int(id)), ("source", source.into())]);
+        writeln!(self.writer, "{}", request).unwrap();
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        Json::parse(&line).ok().unwrap()
+    }
+}
+
+#[test]
+fn values_are_written_as_json() {
+    let server = Server::start();
+    let mut connection = server.connect();
+
+    let response = connection.evaluate(1, "set x 20 push x push 1 add");
+    assert_eq!(response.get("id"), Some(&Json::Int(1)));
+    assert_eq!(response.get("ok"), Some(&Json::Boolean(true)));
+    assert_eq!(response.get("value"), Some(&Json::Int(21)));
+
+    // The globals are kept between the requests of a connection.
+    let response = connection.evaluate(2, "push 'a' push x add");
+    assert_eq!(response.get("value"), Some(&Json::String("20a".to_owned())));
+    let response = connection.evaluate(3, "jsonparse '[1, {\"a\": null}]'");
+    assert_eq!(
+        response.get("value").map(ToString::to_string),
+        Some("[1,{\"a\":null}]".to_owned())
+    );
+
+    // Bytes have no JSON form, so they are written like print writes them.
+    let response = connection.evaluate(4, "hexdecode 'ff'");
+    assert_eq!(
+        response.get("value"),
+        Some(&Json::String("b\"\\xff\"".to_owned()))
+    );
+}
+
+#[test]
+fn errors_point_to_the_line_of_the_request() {
+    let server = Server::start();
+    let mut connection = server.connect();
+    connection.evaluate(1, "@one push 1 end");
+    let response = connection.evaluate(2, "pop");
+    assert_eq!(response.get("ok"), Some(&Json::Boolean(false)));
+    let error = response.get("error").and_then(Json::as_str).unwrap();
+    assert!(error.starts_with("  |\n1 | pop\n"), "{}", error);
+}
+
+#[test]
+fn an_idle_connection_does_not_block_the_others() {
+    let server = Server::start();
+    let _idle = server.connect();
+    let mut connection = server.connect();
+    assert_eq!(
+        connection.evaluate(1, "push 1").get("ok"),
+        Some(&Json::Boolean(true))
+    );
+    assert_eq!(
+        connection.evaluate(2, "1").get("value"),
+        Some(&Json::Int(1))
+    );
+}
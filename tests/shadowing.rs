@@ -0,0 +1,75 @@
+use dark_vm::{ErrorKind, Lexer, RunOutcome, Shadowing, VMBuilder};
+
+/// Runs the program with the given shadowing mode.
+fn run(contents: &str, shadowing: Shadowing) -> RunOutcome {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    VMBuilder::default()
+        .shadowing(shadowing)
+        .capture_output()
+        .build(tokens)
+        .ok()
+        .unwrap()
+        .run()
+}
+
+/// Returns the kinds of the warnings in the outcome.
+fn warnings(outcome: &RunOutcome) -> Vec<ErrorKind> {
+    outcome
+        .warnings
+        .iter()
+        .map(|warning| warning.get_kind().clone())
+        .collect()
+}
+
+const SHADOWED: &str = "@main set x 1 begin set x 2 printn x endblock printn x end";
+
+#[test]
+fn shadowing_is_allowed_by_default() {
+    let outcome = run(SHADOWED, Shadowing::default());
+    assert!(outcome.is_success());
+    assert!(outcome.warnings.is_empty());
+    assert_eq!(outcome.output.as_deref(), Some("2\n1\n"));
+}
+
+#[test]
+fn warn_collects_a_warning_that_points_at_the_earlier_definition() {
+    let outcome = run(SHADOWED, Shadowing::Warn);
+    assert!(outcome.is_success());
+    assert_eq!(outcome.output.as_deref(), Some("2\n1\n"));
+    assert_eq!(
+        warnings(&outcome),
+        vec![ErrorKind::ShadowedVariable("x".to_owned())]
+    );
+    // Positions start at one, and the earlier definition is where its value was written.
+    assert_eq!(
+        outcome.warnings[0].get_related_position(),
+        SHADOWED.find("1 begin").map(|idx| idx + 1)
+    );
+}
+
+#[test]
+fn deny_stops_the_program() {
+    let outcome = run(SHADOWED, Shadowing::Deny);
+    assert!(!outcome.is_success());
+    assert_eq!(outcome.output.as_deref(), Some(""));
+    assert_eq!(
+        outcome.result.err().map(|error| error.get_kind().clone()),
+        Some(ErrorKind::ShadowedVariable("x".to_owned()))
+    );
+}
+
+#[test]
+fn redefining_a_variable_in_the_same_scope_is_not_shadowing() {
+    let outcome = run("@main set x 1 set x 2 printn x end", Shadowing::Deny);
+    assert!(outcome.is_success());
+    assert_eq!(outcome.output.as_deref(), Some("2\n"));
+}
+
+#[test]
+fn the_modes_are_parsed_by_name() {
+    assert_eq!("allow".parse(), Ok(Shadowing::Allow));
+    assert_eq!("Warn".parse(), Ok(Shadowing::Warn));
+    assert_eq!("DENY".parse(), Ok(Shadowing::Deny));
+    assert_eq!("sometimes".parse::<Shadowing>(), Err(()));
+    assert_eq!(Shadowing::Warn.to_string(), "warn");
+}
@@ -0,0 +1,45 @@
+use dark_vm::{code::Code, lexer::Lexer, vm::VM, VMBuilder, ValueKind};
+use std::{io, sync::Arc};
+
+/// Checks at compile time that code can be sent to and shared between threads.
+fn assert_send_and_sync<T: Send + Sync>() {}
+
+#[test]
+fn code_is_send_and_sync() {
+    assert_send_and_sync::<Code>();
+}
+
+#[test]
+fn vms_that_share_code_keep_their_own_position_and_state() {
+    let tokens = Lexer::default()
+        .lex("@main set x 1 push x push 2 push add printn 'done' end")
+        .ok()
+        .unwrap();
+    let code = Arc::new(Code::new(tokens).ok().unwrap());
+    let mut first = VMBuilder::default()
+        .stdout(io::sink())
+        .build_shared(Arc::clone(&code))
+        .ok()
+        .unwrap();
+    let mut second = VM::from_code(Arc::clone(&code)).ok().unwrap();
+    assert_eq!(Arc::strong_count(&code), 3);
+
+    // The first VM runs ahead, which must not move the second one.
+    for _ in 0..3 {
+        first.step().ok().unwrap();
+    }
+    assert_eq!(first.operand_stack.0.len(), 2);
+    assert!(second.operand_stack.0.is_empty());
+
+    second.step().ok().unwrap();
+    assert!(second.operand_stack.0.is_empty());
+    assert!(first.run().is_success());
+    assert_eq!(
+        first.operand_stack.0.last().map(|value| value.kind.clone()),
+        Some(ValueKind::Int(3))
+    );
+
+    drop(first);
+    drop(second);
+    assert_eq!(Arc::strong_count(&code), 1);
+}
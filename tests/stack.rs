@@ -0,0 +1,176 @@
+use dark_vm::{
+    builder::VMBuilder,
+    errors::error_kind::ErrorKind,
+    lexer::Lexer,
+    utils::stack::Stack,
+    values::{value::Value, value_kinds::ValueKind},
+};
+use std::sync::Arc;
+
+mod common;
+
+use common::run;
+
+/// Runs the program with the given stack limit, and returns the kinds of the values left on the operand stack, from the bottom up, or the kind of the first error.
+fn run_with_max_stack(contents: &str, max_stack: usize) -> Result<Vec<ValueKind>, ErrorKind> {
+    common::run_with(VMBuilder::default().max_stack(max_stack), contents)
+}
+
+#[test]
+fn peek_returns_the_last_pushed_value() {
+    let mut stack = Stack::default();
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+    assert_eq!(stack.peek(), Some(&3));
+
+    *stack.peek_mut().unwrap() = 4;
+    assert_eq!(stack.0, vec![1, 2, 4]);
+}
+
+#[test]
+fn peek_on_an_empty_stack_returns_none() {
+    let mut stack: Stack<i32> = Stack::default();
+    assert_eq!(stack.peek(), None);
+    assert_eq!(stack.peek_mut(), None);
+}
+
+#[test]
+fn peek_instruction_uses_the_top_of_a_deep_stack() {
+    let stack = run("@main push 1 push 2 push 3 push peek end")
+        .ok()
+        .unwrap();
+    assert_eq!(
+        stack,
+        vec![
+            ValueKind::Int(1),
+            ValueKind::Int(2),
+            ValueKind::Int(3),
+            ValueKind::Int(3),
+        ]
+    );
+}
+
+#[test]
+fn jmpt_checks_the_top_of_a_deep_stack() {
+    let stack = run("@main push false push false push true rjmpt 3 push 10 push 20 end")
+        .ok()
+        .unwrap();
+    assert_eq!(
+        stack,
+        vec![
+            ValueKind::Boolean(false),
+            ValueKind::Boolean(false),
+            ValueKind::Boolean(true),
+            ValueKind::Int(20),
+        ]
+    );
+
+    let stack = run("@main push true push true push false rjmpt 3 push 10 push 20 end")
+        .ok()
+        .unwrap();
+    assert_eq!(
+        stack,
+        vec![
+            ValueKind::Boolean(true),
+            ValueKind::Boolean(true),
+            ValueKind::Boolean(false),
+            ValueKind::Int(10),
+            ValueKind::Int(20),
+        ]
+    );
+}
+
+#[test]
+fn jmpf_checks_the_top_of_a_deep_stack() {
+    let stack = run("@main push true push false rjmpf 3 push 10 push 20 end")
+        .ok()
+        .unwrap();
+    assert_eq!(
+        stack,
+        vec![
+            ValueKind::Boolean(true),
+            ValueKind::Boolean(false),
+            ValueKind::Int(20),
+        ]
+    );
+
+    let stack = run("@main push false push true rjmpf 3 push 10 push 20 end")
+        .ok()
+        .unwrap();
+    assert_eq!(
+        stack,
+        vec![
+            ValueKind::Boolean(false),
+            ValueKind::Boolean(true),
+            ValueKind::Int(10),
+            ValueKind::Int(20),
+        ]
+    );
+}
+
+#[test]
+fn relative_jumps_can_loop_backwards() {
+    // The offset is measured from the value after the rjmpt, so -8 lands on the incvar. The conditions are not popped.
+    let stack = run("@main set i 0 incvar i 1 push lt i 3 rjmpt -8 push i end")
+        .ok()
+        .unwrap();
+    assert_eq!(
+        stack,
+        vec![
+            ValueKind::Boolean(true),
+            ValueKind::Boolean(true),
+            ValueKind::Boolean(false),
+            ValueKind::Int(3),
+        ]
+    );
+}
+
+#[test]
+fn divmod_fits_in_a_full_stack() {
+    assert_eq!(
+        run_with_max_stack("@main push 0 push 2 push 7 divmod end", 3),
+        Ok(vec![
+            ValueKind::Int(0),
+            ValueKind::Int(3),
+            ValueKind::Int(1)
+        ])
+    );
+}
+
+#[test]
+fn divmod_checks_the_stack_limit() {
+    // divmod pops as many values as it pushes, so it can only exceed a limit that the stack is already over,
+    // such as when the embedder pushed values on to it directly. The values are popped in a label, because the verifier knows that main starts with an empty stack.
+    let tokens = Lexer::default()
+        .lex("@main call split end @split divmod end")
+        .ok()
+        .unwrap();
+    let mut vm = VMBuilder::default()
+        .max_stack(2)
+        .build(tokens)
+        .ok()
+        .unwrap();
+    for value in [0, 2, 7] {
+        vm.operand_stack
+            .push(Arc::new(Value::new(0, ValueKind::Int(value))));
+    }
+    assert_eq!(
+        vm.run()
+            .into_result()
+            .map_err(|error| error.get_kind().clone()),
+        Err(ErrorKind::StackLimitExceeded(2))
+    );
+}
+
+#[test]
+fn apop_checks_the_stack_limit() {
+    assert_eq!(
+        run_with_max_stack("@main push 0 push 0 apop apush arr 1 end", 3),
+        Err(ErrorKind::StackLimitExceeded(3))
+    );
+    assert_eq!(
+        run_with_max_stack("@main push 0 apop apush arr 1 end", 3).map(|stack| stack.len()),
+        Ok(3)
+    );
+}
@@ -0,0 +1,104 @@
+use dark_vm::{errors::error_kind::ErrorKind, values::value_kinds::ValueKind};
+
+mod common;
+
+use common::run;
+
+/// Creates the kind of a string.
+fn string(text: &str) -> ValueKind {
+    ValueKind::String(text.to_owned())
+}
+
+#[test]
+fn strings_are_measured_and_sliced_by_characters() {
+    assert_eq!(
+        run(
+            r#"@main push slen "héllo" push substr "héllo world" 1 4 push substr "abc" 3 0 push substr "abc" 0 3 end"#
+        ),
+        Ok(vec![
+            ValueKind::Int(5),
+            string("éllo"),
+            string(""),
+            string("abc")
+        ])
+    );
+    assert_eq!(
+        run(r#"@main push substr "abc" 4 0 end"#),
+        Err(ErrorKind::OutOfBounds(0, 4))
+    );
+    assert_eq!(
+        run(r#"@main push substr "abc" 1 3 end"#),
+        Err(ErrorKind::OutOfBounds(0, 3))
+    );
+}
+
+#[test]
+fn strings_are_joined_and_cased() {
+    assert_eq!(
+        run(r#"@main push sconcat "dark" "vm" push supper "straße" push slower "ÉCOLE" end"#),
+        Ok(vec![string("darkvm"), string("STRASSE"), string("école")])
+    );
+    assert_eq!(
+        run(r#"@main push sconcat "dark" 1 end"#),
+        Err(ErrorKind::ValueMismatch(
+            "String".to_owned(),
+            "Int".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn strings_are_ordered_by_code_points() {
+    assert_eq!(
+        run(
+            r#"@main push lt "Zebra" "apple" push lt "z" "é" push ltci "apple" "Banana" push ltci "Zebra" "apple" end"#
+        ),
+        Ok(vec![
+            ValueKind::Boolean(true),
+            ValueKind::Boolean(true),
+            ValueKind::Boolean(true),
+            ValueKind::Boolean(false)
+        ])
+    );
+}
+
+#[test]
+fn eqci_ignores_case_in_every_locale() {
+    assert_eq!(
+        run(
+            r#"@main push eqci "Hello" "hELLO" push eqci "STRASSE" "straße" push eqci "Hello" "World" end"#
+        ),
+        Ok(vec![
+            ValueKind::Boolean(true),
+            ValueKind::Boolean(true),
+            ValueKind::Boolean(false)
+        ])
+    );
+    assert!(matches!(
+        run(r#"@main push eqci "1" 1 end"#),
+        Err(ErrorKind::UnsupportedOperation(..))
+    ));
+}
+
+#[test]
+fn len_counts_characters_rather_than_bytes() {
+    assert_eq!(
+        run(
+            r#"@main push len "héllo" push len "" push len apush apush arr 1 2 push len mset mnew "a" 1 push len hexdecode "ff00aa" end"#
+        ),
+        Ok(vec![
+            ValueKind::Int(5),
+            ValueKind::Int(0),
+            ValueKind::Int(2),
+            ValueKind::Int(1),
+            ValueKind::Int(3)
+        ])
+    );
+    assert_eq!(
+        run("@main push len 5 end"),
+        Err(ErrorKind::ValueMismatch(
+            "String, Array, Map, Or Bytes".to_owned(),
+            "Int".to_owned()
+        ))
+    );
+}
@@ -0,0 +1,61 @@
+use dark_vm::{code::Code, lexer::Lexer, values::value_kinds::ValueKind, vm::VM};
+
+mod common;
+
+use common::stack;
+
+/// Lexes the program into code.
+fn code(contents: &str) -> Code {
+    Code::new(Lexer::default().lex(contents).ok().unwrap())
+        .ok()
+        .unwrap()
+}
+
+/// Builds a VM for the program.
+fn build(contents: &str) -> VM {
+    VM::new(Lexer::default().lex(contents).ok().unwrap())
+        .ok()
+        .unwrap()
+}
+
+#[test]
+fn swapping_code_keeps_the_globals_and_the_stack() {
+    let mut vm = build("@main set x 1 push 10 end");
+    assert!(vm.run().is_success());
+
+    vm.swap_code(code(
+        "@main push x call double x end @double #n push n push n push add end",
+    ))
+    .ok()
+    .unwrap();
+    assert!(vm.run().is_success());
+    assert_eq!(
+        stack(&vm),
+        vec![ValueKind::Int(10), ValueKind::Int(1), ValueKind::Int(2)]
+    );
+}
+
+#[test]
+fn swapping_code_abandons_the_running_labels() {
+    let mut vm = build("@main set x 1 call spin end @spin push 7 jmp 0 end");
+    // Steps into spin, which never returns.
+    for _ in 0..3 {
+        vm.step().ok().unwrap();
+    }
+
+    vm.swap_code(code("@main push x end")).ok().unwrap();
+    assert!(vm.run().is_success());
+    // The value that spin pushed stays, since the operand stack is kept.
+    assert_eq!(stack(&vm), vec![ValueKind::Int(7), ValueKind::Int(1)]);
+}
+
+#[test]
+fn invalid_code_leaves_the_vm_untouched() {
+    let mut vm = build("@main set x 1 push 10 end");
+    assert!(vm.run().is_success());
+
+    assert!(vm.swap_code(code("@main call missing end")).is_err());
+    vm.swap_code(code("@main push x end")).ok().unwrap();
+    assert!(vm.run().is_success());
+    assert_eq!(stack(&vm), vec![ValueKind::Int(10), ValueKind::Int(1)]);
+}
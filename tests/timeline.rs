@@ -0,0 +1,37 @@
+use dark_vm::{lexer::Lexer, utils::timeline::Timeline, vm::VM};
+
+/// Runs the program while recording its timeline, and returns whether it succeeded, along with the number of steps and the timeline as JSON.
+fn record(source: &str) -> (bool, usize, String) {
+    let mut vm = VM::new(Lexer::default().lex(source).ok().unwrap())
+        .ok()
+        .unwrap();
+    let mut timeline = Timeline::default();
+    let succeeded = timeline.run(&mut vm).is_ok();
+    (succeeded, timeline.get_len(), timeline.to_json(source))
+}
+
+#[test]
+fn every_step_records_the_stack_and_the_frames() {
+    let (succeeded, steps, json) = record("@main push 1\nset x 'a'\nend");
+    assert!(succeeded);
+    assert_eq!(steps, 3);
+    assert_eq!(
+        json,
+        concat!(
+            r#"{"source":"@main push 1\nset x 'a'\nend","steps":["#,
+            r#"{"step":0,"line":1,"pos":7,"instruction":"push","stack":[{"type":"Int","value":"1"}],"frames":[{"label":"main","variables":[]}]},"#,
+            r#"{"step":1,"line":2,"pos":14,"instruction":"set","stack":[{"type":"Int","value":"1"}],"frames":[{"label":"main","variables":[{"name":"x","value":{"type":"String","value":"a"}}]}]},"#,
+            r#"{"step":2,"line":3,"pos":24,"instruction":"end","stack":[{"type":"Int","value":"1"}],"frames":[]}]}"#
+        )
+    );
+}
+
+#[test]
+fn the_error_that_ended_the_run_is_recorded() {
+    let (succeeded, steps, json) = record("@main push 1\npush y end");
+    assert!(!succeeded);
+    assert_eq!(steps, 2);
+    assert!(json.ends_with(
+        r#"],"error":{"message":"Tried To Use The Variable 'y', Which Has Not Been Defined. Searched @main.","line":2,"pos":19}}"#
+    ));
+}
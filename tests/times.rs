@@ -0,0 +1,53 @@
+use dark_vm::{ErrorKind, Lexer, VMBuilder};
+
+/// Builds a VM for the program, and returns the kind of the error that the verifier found, if there is one.
+fn verify(contents: &str) -> Option<ErrorKind> {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    VMBuilder::default()
+        .build(tokens)
+        .err()
+        .map(|error| error.get_kind().clone())
+}
+
+/// Runs the program, and returns everything that it printed.
+fn output(source: &str) -> String {
+    dark_vm::run_captured(source).ok().unwrap().1
+}
+
+#[test]
+fn missing_arguments_are_counted() {
+    assert_eq!(verify("@main times end"), Some(ErrorKind::ExpectedArgs(2)));
+    assert_eq!(
+        verify("@main times 3 end @step end"),
+        Some(ErrorKind::ExpectedArgs(1))
+    );
+    assert_eq!(verify("@main times 3 step end @step end"), None);
+}
+
+#[test]
+fn the_label_receives_the_number_of_the_iteration() {
+    assert_eq!(
+        output("@main times 3 count end @count #i printn i end"),
+        "0\n1\n2\n"
+    );
+    assert_eq!(
+        output("@main times 2 hello end @hello printn 'hi' end"),
+        "hi\nhi\n"
+    );
+}
+
+#[test]
+fn the_label_is_not_called_for_counts_that_are_not_positive() {
+    assert_eq!(
+        output("@main times 0 count times -2 count printn 'done' end @count #i printn i end"),
+        "done\n"
+    );
+}
+
+#[test]
+fn the_count_may_be_a_variable() {
+    assert_eq!(
+        output("@main set n 2 times n step printn n end @step #i printn i end"),
+        "0\n1\n2\n"
+    );
+}
@@ -0,0 +1,85 @@
+use dark_vm::{ErrorKind, Trace, VMBuilder, ValueKind};
+use std::{env, fs};
+
+mod common;
+
+/// Runs the program with the environment and a root directory allowed, along with the given trace,
+/// and returns the kinds of the values left on the operand stack, from the bottom up, or the kind of the first error.
+fn run(contents: &str, trace: Trace) -> Result<Vec<ValueKind>, ErrorKind> {
+    common::run_with(
+        VMBuilder::default()
+            .allow_env()
+            .fs_root(env::temp_dir())
+            .trace(trace),
+        contents,
+    )
+}
+
+#[test]
+fn recorded_inputs_are_replayed_after_they_change() {
+    let variable = format!("DARK_VM_TRACE_{}", std::process::id());
+    let file = format!("dark-vm-trace-{}.txt", std::process::id());
+    let path = env::temp_dir().join(&file);
+    env::set_var(&variable, "recorded");
+    fs::write(&path, "first").unwrap();
+
+    let contents = format!("@main push env '{}' push readfile '{}' end", variable, file);
+    let trace = Trace::record();
+    let recorded = run(&contents, trace.clone());
+    assert_eq!(
+        recorded,
+        Ok(vec![
+            ValueKind::String("recorded".to_owned()),
+            ValueKind::String("first".to_owned()),
+        ])
+    );
+    assert_eq!(trace.get_len(), 2);
+
+    env::set_var(&variable, "changed");
+    fs::write(&path, "second").unwrap();
+    let replay = Trace::replay(&trace.to_json()).ok().unwrap();
+    assert!(replay.is_replaying());
+    assert_eq!(run(&contents, replay), recorded);
+
+    env::remove_var(&variable);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn replaying_a_different_instruction_is_a_mismatch() {
+    let trace = Trace::record();
+    run("@main push env 'PATH' end", trace.clone())
+        .ok()
+        .unwrap();
+    let replay = Trace::replay(&trace.to_json()).ok().unwrap();
+    assert_eq!(
+        run("@main push readfile 'a.txt' end", replay),
+        Err(ErrorKind::TraceMismatch(
+            "env".to_owned(),
+            "readfile".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn replaying_past_the_end_of_the_trace_is_an_error() {
+    let replay = Trace::replay(&Trace::record().to_json()).ok().unwrap();
+    assert_eq!(
+        run("@main push env 'PATH' end", replay),
+        Err(ErrorKind::TraceExhausted("env".to_owned()))
+    );
+}
+
+#[test]
+fn invalid_traces_are_rejected() {
+    for text in &["", "{}", "[{\"instruction\": 1}]"] {
+        assert_eq!(
+            Trace::replay(text)
+                .err()
+                .map(|error| error.get_kind().clone()),
+            Some(ErrorKind::InvalidTrace),
+            "{}",
+            text
+        );
+    }
+}
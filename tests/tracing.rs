@@ -0,0 +1,81 @@
+#![cfg(feature = "tracing")]
+
+use dark_vm::{builder::VMBuilder, lexer::Lexer};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Metadata, Subscriber,
+};
+
+/// A subscriber that records the name of every span that is created, along with the label of the call spans.
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<String>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+/// Collects the label field of a call span.
+struct Label(Option<String>);
+
+impl Visit for Label {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "label" {
+            self.0 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut label = Label(None);
+        span.record(&mut label);
+        let name = match label.0 {
+            Some(label) => format!("{} {}", span.metadata().name(), label),
+            None => span.metadata().name().to_owned(),
+        };
+        self.spans.lock().unwrap().push(name);
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn every_phase_and_call_has_a_span() {
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let tokens = Lexer::default()
+            .lex("@main call greet call greet end @greet push 1 end")
+            .ok()
+            .unwrap();
+        let mut vm = VMBuilder::default().build(tokens).ok().unwrap();
+        assert!(vm.run().is_success());
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    for phase in &["lex", "load", "run"] {
+        assert!(spans.iter().any(|span| span == phase), "{:?}", spans);
+    }
+    assert_eq!(
+        spans.iter().filter(|span| *span == "call greet").count(),
+        2,
+        "{:?}",
+        spans
+    );
+}
@@ -0,0 +1,65 @@
+use dark_vm::{type_checker, Code, ErrorKind, Lexer, VMBuilder};
+
+/// Checks the types of the program, and returns the kinds of the errors that would certainly occur.
+fn check(contents: &str) -> Vec<ErrorKind> {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    let code = Code::new(tokens).ok().unwrap();
+    type_checker::check_types(&code)
+        .ok()
+        .unwrap()
+        .iter()
+        .map(|error| error.get_kind().clone())
+        .collect()
+}
+
+#[test]
+fn well_typed_programs_have_no_errors() {
+    assert_eq!(check("@main push 1 push 2 add end"), vec![]);
+    assert_eq!(check("@main set x 1 push add x 2.5 end"), vec![]);
+    assert_eq!(check("@main push sconcat \"a\" \"b\" end"), vec![]);
+}
+
+#[test]
+fn operations_on_unsupported_types_are_reported() {
+    assert_eq!(
+        check("@main push true push 1 printn add end"),
+        vec![ErrorKind::UnsupportedOperation(
+            "Add".to_owned(),
+            "The Value 'Int' And The Value 'Boolean'.".to_owned()
+        )]
+    );
+}
+
+#[test]
+fn the_types_of_variables_are_followed() {
+    assert_eq!(
+        check("@main set x \"a\" push slen x set y 1 push slen y end"),
+        vec![ErrorKind::ValueMismatch(
+            "String".to_owned(),
+            "Int".to_owned()
+        )]
+    );
+}
+
+#[test]
+fn unknown_types_are_not_reported() {
+    assert_eq!(
+        check("@main push call f push 1 add end @f push true end"),
+        vec![]
+    );
+}
+
+#[test]
+fn the_builder_returns_the_first_error() {
+    let tokens = Lexer::default()
+        .lex("@main push true push 1 printn add end")
+        .ok()
+        .unwrap();
+    assert!(VMBuilder::default().type_check().build(tokens).is_err());
+
+    let tokens = Lexer::default()
+        .lex("@main push 1 push 2 add end")
+        .ok()
+        .unwrap();
+    assert!(VMBuilder::default().type_check().build(tokens).is_ok());
+}
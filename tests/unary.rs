@@ -0,0 +1,65 @@
+use dark_vm::{
+    builder::VMBuilder, errors::error_kind::ErrorKind, utils::overflow::Overflow,
+    values::value_kinds::ValueKind,
+};
+
+mod common;
+
+/// Runs the program with the given overflow mode and returns the kinds of the values left on the operand stack, from the bottom up.
+fn run(contents: &str, overflow: Overflow) -> Result<Vec<ValueKind>, ErrorKind> {
+    common::run_with(VMBuilder::default().overflow(overflow), contents)
+}
+
+#[test]
+fn neg_and_abs_handle_ints_and_floats() {
+    assert_eq!(
+        run(
+            "@main push 5 push neg push -5 push abs push 2.5 push neg push -2.5 push abs end",
+            Overflow::Wrap
+        ),
+        Ok(vec![
+            ValueKind::Int(-5),
+            ValueKind::Int(5),
+            ValueKind::Float(-2.5),
+            ValueKind::Float(2.5),
+        ])
+    );
+}
+
+#[test]
+fn neg_of_the_smallest_int_follows_the_overflow_mode() {
+    let program = format!("@main push {} push neg end", i64::MIN);
+    assert_eq!(
+        run(&program, Overflow::Wrap),
+        Ok(vec![ValueKind::Int(i64::MIN)])
+    );
+    assert_eq!(
+        run(&program, Overflow::Check),
+        Err(ErrorKind::IntegerOverflow)
+    );
+}
+
+#[test]
+fn neg_rejects_other_values() {
+    assert!(matches!(
+        run("@main push true push neg end", Overflow::Wrap),
+        Err(ErrorKind::UnsupportedOperation(..))
+    ));
+}
+
+#[test]
+fn abs_of_the_smallest_int_follows_the_overflow_mode() {
+    let program = format!("@main push {} push abs end", i64::MIN);
+    assert_eq!(
+        run(&program, Overflow::Wrap),
+        Ok(vec![ValueKind::Int(i64::MIN)])
+    );
+    assert_eq!(
+        run(&program, Overflow::Check),
+        Err(ErrorKind::IntegerOverflow)
+    );
+    assert!(matches!(
+        run("@main push 'a' push abs end", Overflow::Wrap),
+        Err(ErrorKind::UnsupportedOperation(..))
+    ));
+}
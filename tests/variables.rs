@@ -0,0 +1,66 @@
+use dark_vm::ErrorKind;
+
+/// Runs the program, and returns everything that it printed, or the kind of the first error.
+fn output(source: &str) -> Result<String, ErrorKind> {
+    dark_vm::run_captured(source)
+        .map(|(_, output)| output)
+        .map_err(|error| error.get_kind().clone())
+}
+
+#[test]
+fn undefined_variables_name_the_searched_scopes() {
+    assert_eq!(
+        output("@main begin push missing endblock end"),
+        Err(ErrorKind::UndefinedVariable(
+            "missing".to_owned(),
+            vec!["A Block In @main".to_owned(), "@main".to_owned()],
+            vec![]
+        ))
+    );
+}
+
+#[test]
+fn undefined_variables_suggest_similar_names() {
+    assert_eq!(
+        output("@main set total 1 set tool 2 set count 3 push totl end"),
+        Err(ErrorKind::UndefinedVariable(
+            "totl".to_owned(),
+            vec!["@main".to_owned()],
+            vec!["tool".to_owned(), "total".to_owned()]
+        ))
+    );
+    let message: String = ErrorKind::UndefinedVariable(
+        "conter".to_owned(),
+        vec!["@main".to_owned()],
+        vec!["counter".to_owned(), "count".to_owned()],
+    )
+    .into();
+    assert_eq!(
+        message,
+        "Tried To Use The Variable 'conter', Which Has Not Been Defined. Searched @main. Did You Mean 'counter' Or 'count'?"
+    );
+}
+
+#[test]
+fn incvar_and_decvar_update_the_variable_where_it_is_defined() {
+    assert_eq!(
+        output("@main set count 0 times 3 step printn count begin decvar count 1 endblock printn count @step incvar count 2 end end"),
+        Ok("6\n5\n".to_owned())
+    );
+}
+
+#[test]
+fn incvar_requires_a_defined_variable() {
+    assert!(matches!(
+        output("@main incvar count 1 end"),
+        Err(ErrorKind::UndefinedVariable(..))
+    ));
+}
+
+#[test]
+fn swapvar_exchanges_the_values() {
+    assert_eq!(
+        output("@main set a 1 set b 'two' begin swapvar a b endblock printn a printn b end"),
+        Ok("two\n1\n".to_owned())
+    );
+}
@@ -0,0 +1,66 @@
+use dark_vm::{verifier, Code, ErrorKind, Lexer, Natives};
+
+/// Checks the effect of every label on the stack, with a native function called double that takes one argument,
+/// and returns the kinds of the warnings, or the kind of the error.
+fn check(contents: &str) -> Result<Vec<ErrorKind>, ErrorKind> {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    let code = Code::new(tokens).map_err(|error| error.get_kind().clone())?;
+    let mut natives = Natives::default();
+    natives.register("double", 1, |_, _| Ok(None));
+    verifier::verify_with_natives(&code, &natives).map_err(|error| error.get_kind().clone())?;
+    verifier::check_stack_with_natives(&code, &natives)
+        .map(|warnings| {
+            warnings
+                .iter()
+                .map(|warning| warning.get_kind().clone())
+                .collect()
+        })
+        .map_err(|error| error.get_kind().clone())
+}
+
+#[test]
+fn balanced_programs_are_accepted() {
+    assert_eq!(check("@main push 1 push 2 add end"), Ok(vec![]));
+}
+
+#[test]
+fn popping_from_an_empty_stack_in_main_is_rejected() {
+    assert_eq!(
+        check("@main push 1 printn add end"),
+        Err(ErrorKind::EmptyStack)
+    );
+}
+
+#[test]
+fn branches_that_leave_different_depths_are_reported() {
+    assert_eq!(
+        check("@main push true if push 1 else push 2 endif end"),
+        Ok(vec![])
+    );
+    assert_eq!(
+        check("@main push true if push 1 endif end"),
+        Ok(vec![ErrorKind::UnbalancedStack(1)])
+    );
+}
+
+#[test]
+fn loops_that_grow_the_stack_are_reported() {
+    assert_eq!(
+        check("@main set i 0 while push lt i 3 do incvar i 1 endwhile end"),
+        Ok(vec![])
+    );
+    assert_eq!(
+        check("@main set i 0 while push lt i 3 do push i incvar i 1 endwhile end"),
+        Ok(vec![ErrorKind::UnbalancedStack(1)])
+    );
+}
+
+#[test]
+fn the_depth_is_unknown_after_a_native_function_is_called() {
+    assert_eq!(check("@main push call double 1 add end"), Ok(vec![]));
+    assert_eq!(check("@main call double 1 add end"), Ok(vec![]));
+    assert_eq!(
+        check("@main push 1 printn add call double 1 end"),
+        Err(ErrorKind::EmptyStack)
+    );
+}
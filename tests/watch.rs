@@ -0,0 +1,90 @@
+use dark_vm::{
+    builder::VMBuilder,
+    lexer::Lexer,
+    vm::{StepResult, VM},
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// Builds the VM for the program.
+fn build(contents: &str, builder: VMBuilder) -> VM {
+    let tokens = Lexer::default().lex(contents).ok().unwrap();
+    builder.build(tokens).ok().unwrap()
+}
+
+/// Runs the program while watching the given variable, and returns every event written as a sentence.
+fn events(contents: &str, name: &str) -> Vec<String> {
+    let events = Rc::new(RefCell::new(vec![]));
+    let recorded = Rc::clone(&events);
+    let outcome = build(
+        contents,
+        VMBuilder::default()
+            .watch(name)
+            .on_watch(move |event| recorded.borrow_mut().push(event.to_string())),
+    )
+    .run();
+    assert!(outcome.is_success());
+    let events = events.borrow().clone();
+    events
+}
+
+#[test]
+fn every_kind_of_write_is_reported() {
+    assert_eq!(
+        events(
+            "@main set total 1 incvar total 2 set other 5 swapvar total other decvar total 1 end",
+            "total"
+        ),
+        vec![
+            "The Variable 'total' In @main Was Defined As 1.",
+            "The Variable 'total' In @main Changed From 1 To 3.",
+            "The Variable 'total' In @main Changed From 3 To 5.",
+            "The Variable 'total' In @main Changed From 5 To 4.",
+        ]
+    );
+}
+
+#[test]
+fn parameters_are_writes_in_the_frame_of_the_label() {
+    assert_eq!(
+        events("@main call greet 'Ada' end @greet #name end", "name"),
+        vec!["The Variable 'name' In @greet Was Defined As \"Ada\"."]
+    );
+    assert!(events("@main set other 1 end", "name").is_empty());
+}
+
+#[test]
+fn writes_suspend_a_vm_that_suspends_on_breaks() {
+    let mut vm = build(
+        "@main set x 1 push 2 set x 3 end",
+        VMBuilder::default().watch("x").suspend_on_break(),
+    );
+    let mut suspended = vec![];
+    loop {
+        match vm.step().ok().unwrap() {
+            StepResult::Running => {}
+            StepResult::Suspended => {
+                suspended.extend(vm.take_watch_events().iter().map(|event| event.to_string()))
+            }
+            StepResult::Finished(_) => break,
+        }
+    }
+
+    assert_eq!(
+        suspended,
+        vec![
+            "The Variable 'x' In @main Was Defined As 1.",
+            "The Variable 'x' In @main Changed From 1 To 3.",
+        ]
+    );
+}
+
+#[test]
+fn variables_can_be_watched_and_unwatched_on_the_vm() {
+    let mut vm = build("@main end", VMBuilder::default().watch("b"));
+    assert!(vm.watch("a"));
+    assert!(!vm.watch("a"));
+    assert_eq!(vm.get_watched(), vec!["a", "b"]);
+    assert!(vm.unwatch("b"));
+    assert!(!vm.unwatch("b"));
+    assert_eq!(vm.get_watched(), vec!["a"]);
+}